@@ -0,0 +1,131 @@
+// pathfinder/benchmarks/benches/scene_builder.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! CPU-side benchmarks: tiling, stroking, text layout, and whole-scene builds.
+//!
+//! GPU rendering is measured by the `pathfinder-bench` binary instead, since criterion can't see
+//! GPU time.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use pathfinder_benchmarks::{self as benchmarks, BUNDLED_SVGS};
+use pathfinder_canvas::{Canvas, vec2f};
+use pathfinder_content::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeAlignment};
+use pathfinder_content::stroke::StrokeStyle;
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+use pathfinder_renderer::concurrent::rayon::RayonExecutor;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_renderer::options::{BuildOptions, TileSize};
+use pathfinder_renderer::scene::Scene;
+use pathfinder_text::{FontContext, FontRenderOptions, GlyphRun};
+
+fn tiling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tiling");
+    let mut scene = benchmarks::load_bundled_svg("Ghostscript_Tiger");
+    for &tile_size in &TileSize::ALL {
+        let id = BenchmarkId::new("tiger", format!("{}x{}", tile_size.width(), tile_size.height()));
+        group.bench_function(id, |b| {
+            b.iter(|| {
+                let options = BuildOptions { tile_size, ..BuildOptions::default() };
+                benchmarks::build_scene(&mut scene, options, &SequentialExecutor)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn stroking(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stroking");
+    let outline = benchmarks::zigzag_outline(1000, 512.0);
+    let joins = [("miter", LineJoin::Miter(10.0)),
+                 ("bevel", LineJoin::Bevel),
+                 ("round", LineJoin::Round)];
+    for &(join_name, line_join) in &joins {
+        let style = StrokeStyle {
+            line_width: 4.0,
+            line_cap: LineCap::Butt,
+            line_join,
+            alignment: StrokeAlignment::Center,
+        };
+        group.bench_function(BenchmarkId::new("zigzag", join_name), |b| {
+            b.iter(|| {
+                let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, style);
+                stroke_to_fill.offset();
+                stroke_to_fill.into_outline()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn text(c: &mut Criterion) {
+    let mut group = c.benchmark_group("text");
+    let font_context = benchmarks::text_font_context();
+
+    // Layout and glyph outline extraction.
+    group.bench_function("layout-50-lines", |b| {
+        b.iter(|| {
+            let canvas = Canvas::new(vec2f(1024.0, 1024.0));
+            let mut context = canvas.get_context_2d(font_context.clone());
+            benchmarks::draw_text_page(&mut context, 50);
+            context.into_canvas().into_scene()
+        })
+    });
+
+    // Transforming glyph outlines for a long document, one glyph run per line. The outlines are
+    // cached after the first iteration, as they would be when redrawing a document.
+    let font = benchmarks::text_font();
+    let lines = benchmarks::text_page_glyph_runs(&font, 1000);
+    let runs: Vec<_> = lines.iter().enumerate().map(|(line_index, &(ref glyph_ids, ref offsets))| {
+        let run = GlyphRun { font: &font, font_size: 16.0, glyph_ids, offsets };
+        let transform = Transform2F::from_translation(vec2f(16.0, 24.0 + line_index as f32 * 20.0));
+        (run, FontRenderOptions { transform, ..FontRenderOptions::default() })
+    }).collect();
+    let mut glyph_run_font_context = FontContext::new();
+    group.bench_function(BenchmarkId::new("glyph-runs-1000-lines", "sequential"), |b| {
+        b.iter(|| {
+            let mut scene = Scene::new();
+            glyph_run_font_context.push_glyph_runs(&mut scene, &runs, &SequentialExecutor).unwrap();
+            scene
+        })
+    });
+    group.bench_function(BenchmarkId::new("glyph-runs-1000-lines", "rayon"), |b| {
+        b.iter(|| {
+            let mut scene = Scene::new();
+            glyph_run_font_context.push_glyph_runs(&mut scene, &runs, &RayonExecutor).unwrap();
+            scene
+        })
+    });
+
+    // Tiling glyphs, which are small and numerous.
+    let mut scene = benchmarks::text_page_scene(font_context.clone(), 50);
+    group.bench_function("build-50-lines", |b| {
+        b.iter(|| benchmarks::build_scene(&mut scene, BuildOptions::default(), &SequentialExecutor))
+    });
+    group.finish();
+}
+
+fn full_scene(c: &mut Criterion) {
+    let mut group = c.benchmark_group("full-scene");
+    group.sample_size(20);
+    for &name in &BUNDLED_SVGS {
+        let mut scene = benchmarks::load_bundled_svg(name);
+        let options = BuildOptions::default();
+        group.bench_function(BenchmarkId::new("sequential", name), |b| {
+            b.iter(|| benchmarks::build_scene(&mut scene, options.clone(), &SequentialExecutor))
+        });
+        group.bench_function(BenchmarkId::new("rayon", name), |b| {
+            b.iter(|| benchmarks::build_scene(&mut scene, options.clone(), &RayonExecutor))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tiling, stroking, text, full_scene);
+criterion_main!(benches);
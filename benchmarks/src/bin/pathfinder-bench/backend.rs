@@ -0,0 +1,87 @@
+// pathfinder/benchmarks/src/bin/pathfinder-bench/backend.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Offscreen renderers for each backend.
+
+use pathfinder_testing::Backend as GLBackend;
+
+#[cfg(target_os = "macos")]
+use metal::{MTLPixelFormat, MTLStorageMode, MTLTextureType, MTLTextureUsage, TextureDescriptor};
+#[cfg(target_os = "macos")]
+use pathfinder_geometry::vector::Vector2I;
+#[cfg(target_os = "macos")]
+use pathfinder_metal::MetalDevice;
+#[cfg(target_os = "macos")]
+use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+#[cfg(target_os = "macos")]
+use pathfinder_renderer::gpu::renderer::Renderer;
+#[cfg(target_os = "macos")]
+use pathfinder_resources::embedded::EmbeddedResourceLoader;
+
+pub const BACKEND_NAMES: [&str; 3] = ["gl", "software", "metal"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    /// OpenGL on the system GPU.
+    GL,
+    /// OpenGL on a CPU rasterizer such as llvmpipe or SwiftShader, as `surfman` finds it.
+    Software,
+    /// Metal on the system GPU. Only available on macOS.
+    Metal,
+}
+
+impl Backend {
+    pub fn from_name(name: &str) -> Option<Backend> {
+        match name {
+            "gl" => Some(Backend::GL),
+            "software" => Some(Backend::Software),
+            "metal" => Some(Backend::Metal),
+            _ => None,
+        }
+    }
+
+    /// The OpenGL implementation that this backend renders with, if it's an OpenGL backend.
+    pub fn gl_backend(self) -> Option<GLBackend> {
+        match self {
+            Backend::GL => Some(GLBackend::Hardware),
+            Backend::Software => Some(GLBackend::Software),
+            Backend::Metal => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::GL => "gl",
+            Backend::Software => "software",
+            Backend::Metal => "metal",
+        }
+    }
+}
+
+/// Creates a Metal renderer drawing into an offscreen texture of `size` pixels.
+#[cfg(target_os = "macos")]
+pub fn create_metal_renderer(size: Vector2I) -> Result<Renderer<MetalDevice>, String> {
+    let metal_device = metal::Device::system_default().ok_or("no Metal device was found")?;
+    let descriptor = TextureDescriptor::new();
+    descriptor.set_texture_type(MTLTextureType::D2);
+    descriptor.set_pixel_format(MTLPixelFormat::BGRA8Unorm);
+    descriptor.set_width(size.x() as u64);
+    descriptor.set_height(size.y() as u64);
+    descriptor.set_storage_mode(MTLStorageMode::Private);
+    descriptor.set_usage(MTLTextureUsage::RenderTarget | MTLTextureUsage::ShaderRead);
+    let texture = metal_device.new_texture(&descriptor);
+    let device = unsafe { MetalDevice::new(metal_device, texture) };
+    Renderer::new(device,
+                  &EmbeddedResourceLoader::new(),
+                  DestFramebuffer::full_window(size),
+                  RendererOptions::default()).map_err(|error| {
+        format!("couldn't create the renderer: {}", error)
+    })
+}
@@ -0,0 +1,359 @@
+// pathfinder/benchmarks/src/bin/pathfinder-bench/main.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Replays scenes on a chosen backend and prints per-stage timings as JSON, so that performance
+//! numbers in bug reports can be compared with each other.
+//!
+//! Inputs can be scene archives written by `Scene::write()`, frame captures written by
+//! `FrameCapture::write()`, or SVG files. Scenes and SVGs are rebuilt every frame at each of the
+//! requested sizes. Captures are already built, so they are only replayed, at the size they were
+//! captured at. With no inputs, the bundled tiger, text page, and NBA scenes are used.
+
+use crate::backend::{BACKEND_NAMES, Backend};
+use crate::stats::{Counts, InputReport, Report, Samples, Stages};
+use clap::{App, Arg};
+use pathfinder_benchmarks::{self as benchmarks, BUNDLED_SVGS};
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_gpu::Device;
+use pathfinder_gpu::error::RenderError;
+use pathfinder_renderer::capture::FrameCapture;
+use pathfinder_renderer::concurrent::rayon::RayonExecutor;
+use pathfinder_renderer::gpu::options::{ClearMode, DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use pathfinder_renderer::scene::Scene;
+use pathfinder_testing::OffscreenRenderer;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+mod backend;
+mod stats;
+
+const DEFAULT_FRAME_COUNT: &str = "100";
+const DEFAULT_WARMUP_FRAME_COUNT: &str = "5";
+const DEFAULT_SIZE: &str = "1920x1080";
+
+const SCENE_MAGIC: &[u8] = b"PFSCENE\0";
+const CAPTURE_MAGIC: &[u8] = b"PFCAPTUR";
+
+// How long to wait for the last frames' GPU timer queries to resolve.
+const TIMER_QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+struct Input {
+    path: String,
+    kind: &'static str,
+    workload: Workload,
+}
+
+enum Workload {
+    Scene(Scene),
+    Capture(FrameCapture),
+}
+
+#[derive(Clone, Copy)]
+struct FrameCounts {
+    warmup: usize,
+    timed: usize,
+}
+
+fn main() {
+    let matches = App::new("pathfinder-bench")
+        .about("Renders scenes repeatedly and prints per-stage timings as JSON")
+        .arg(
+            Arg::with_name("backend")
+                .short("b")
+                .long("backend")
+                .takes_value(true)
+                .possible_values(&BACKEND_NAMES)
+                .default_value("gl")
+                .help("The renderer backend; `software` is OpenGL on a CPU rasterizer"),
+        )
+        .arg(
+            Arg::with_name("frames")
+                .short("n")
+                .long("frames")
+                .value_name("COUNT")
+                .takes_value(true)
+                .default_value(DEFAULT_FRAME_COUNT)
+                .help("The number of frames to time for each input and size"),
+        )
+        .arg(
+            Arg::with_name("warmup")
+                .short("w")
+                .long("warmup")
+                .value_name("COUNT")
+                .takes_value(true)
+                .default_value(DEFAULT_WARMUP_FRAME_COUNT)
+                .help("The number of untimed frames to render first"),
+        )
+        .arg(
+            Arg::with_name("size")
+                .short("s")
+                .long("size")
+                .value_name("WIDTHxHEIGHT")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .default_value(DEFAULT_SIZE)
+                .help("A framebuffer size to render scenes and SVGs at; may be repeated"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Writes the report to a file instead of standard output"),
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .multiple(true)
+                .help("Scene archives, frame captures, or SVG files; defaults to bundled SVGs"),
+        )
+        .get_matches();
+
+    let backend = Backend::from_name(matches.value_of("backend").unwrap()).unwrap();
+    let frame_counts = FrameCounts {
+        warmup: parse_count(matches.value_of("warmup").unwrap()),
+        timed: parse_count(matches.value_of("frames").unwrap()),
+    };
+    let sizes: Vec<Vector2I> = matches.values_of("size").unwrap().map(|size| {
+        benchmarks::parse_size(size).unwrap_or_else(|| {
+            fail(&format!("`{}` isn't a size of the form WIDTHxHEIGHT", size))
+        })
+    }).collect();
+    let mut inputs: Vec<Input> = match matches.values_of("INPUT") {
+        None => BUNDLED_SVGS.iter().map(|&name| {
+            Input {
+                path: name.to_owned(),
+                kind: "svg",
+                workload: Workload::Scene(benchmarks::load_bundled_svg(name)),
+            }
+        }).collect(),
+        Some(paths) => paths.map(|path| {
+            load_input(path).unwrap_or_else(|message| fail(&format!("{}: {}", path, message)))
+        }).collect(),
+    };
+
+    // Render into one surface big enough for everything, and draw into the corner of it.
+    let surface_size = inputs.iter().fold(Vector2I::default(), |size, input| {
+        match input.workload {
+            Workload::Capture(ref capture) => size.max(capture.framebuffer_size),
+            Workload::Scene(_) => sizes.iter().fold(size, |size, &other| size.max(other)),
+        }
+    });
+
+    let results = match backend.gl_backend() {
+        Some(gl_backend) => {
+            let mut offscreen =
+                OffscreenRenderer::new(gl_backend, surface_size, RendererOptions::default())
+                    .unwrap_or_else(|message| fail(&message));
+            bench(&mut offscreen.renderer, &mut inputs, &sizes, surface_size, frame_counts)
+        }
+        None => bench_metal(&mut inputs, &sizes, surface_size, frame_counts),
+    };
+
+    let report = Report {
+        backend: backend.name(),
+        warmup_frames: frame_counts.warmup,
+        frames: frame_counts.timed,
+        results,
+    };
+    let result = match matches.value_of("output") {
+        None => {
+            let stdout = io::stdout();
+            let mut writer = stdout.lock();
+            serde_json::to_writer_pretty(&mut writer, &report).map_err(io::Error::from)
+                                                              .and_then(|_| writeln!(writer))
+        }
+        Some(path) => {
+            File::create(path).and_then(|file| {
+                let mut writer = BufWriter::new(file);
+                serde_json::to_writer_pretty(&mut writer, &report).map_err(io::Error::from)?;
+                writeln!(writer)?;
+                writer.flush()
+            })
+        }
+    };
+    if let Err(error) = result {
+        fail(&format!("couldn't write the report: {}", error));
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn bench_metal(inputs: &mut [Input],
+               sizes: &[Vector2I],
+               surface_size: Vector2I,
+               frame_counts: FrameCounts)
+               -> Vec<InputReport> {
+    let mut renderer = backend::create_metal_renderer(surface_size)
+        .unwrap_or_else(|message| fail(&message));
+    bench(&mut renderer, inputs, sizes, surface_size, frame_counts)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn bench_metal(_: &mut [Input], _: &[Vector2I], _: Vector2I, _: FrameCounts)
+               -> Vec<InputReport> {
+    fail("the Metal backend is only available on macOS")
+}
+
+fn bench<D>(renderer: &mut Renderer<D>,
+            inputs: &mut [Input],
+            sizes: &[Vector2I],
+            surface_size: Vector2I,
+            frame_counts: FrameCounts)
+            -> Vec<InputReport>
+            where D: Device {
+    let mut results = vec![];
+    for input in inputs {
+        let input_sizes = match input.workload {
+            Workload::Capture(ref capture) => vec![capture.framebuffer_size],
+            Workload::Scene(_) => sizes.to_vec(),
+        };
+        for size in input_sizes {
+            eprintln!("{} at {}x{}...", input.path, size.x(), size.y());
+            renderer.replace_dest_framebuffer(DestFramebuffer::Default {
+                viewport: RectI::new(Vector2I::default(), size),
+                window_size: surface_size,
+            });
+
+            let mut samples = Samples::default();
+            let result = bench_workload(renderer, &mut input.workload, size, frame_counts,
+                                        &mut samples);
+            let (error, counts) = match result {
+                Ok(()) => (None, Some(Counts::from(&renderer.stats))),
+                Err(error) => {
+                    eprintln!("{}: {}", input.path, error);
+                    (Some(error.to_string()), None)
+                }
+            };
+            results.push(InputReport {
+                input: input.path.clone(),
+                kind: input.kind,
+                width: size.x(),
+                height: size.y(),
+                stages: if error.is_none() { samples.to_stages() } else { Stages::default() },
+                error,
+                counts,
+            });
+        }
+    }
+    results
+}
+
+fn bench_workload<D>(renderer: &mut Renderer<D>,
+                     workload: &mut Workload,
+                     size: Vector2I,
+                     frame_counts: FrameCounts,
+                     samples: &mut Samples)
+                     -> Result<(), RenderError>
+                     where D: Device {
+    let timer_queries = renderer.device.capabilities().timer_queries;
+    let frame_count = frame_counts.warmup + frame_counts.timed;
+    let mut timed_gpu_frame_count = 0;
+
+    // Don't count GPU times left over from the previous input as this one's.
+    while renderer.shift_rendering_time().is_some() {}
+
+    for frame_index in 0..frame_count {
+        let is_timed = frame_index >= frame_counts.warmup;
+
+        let built_capture;
+        let capture = match *workload {
+            Workload::Capture(ref capture) => capture,
+            Workload::Scene(ref mut scene) => {
+                let build_options = BuildOptions {
+                    transform: RenderTransform::Transform2D(benchmarks::fit_transform(scene,
+                                                                                      size)),
+                    ..BuildOptions::default()
+                };
+                let start_time = Instant::now();
+                built_capture = FrameCapture::build(scene,
+                                                    build_options,
+                                                    RendererOptions::default(),
+                                                    ClearMode::default(),
+                                                    size,
+                                                    &RayonExecutor);
+                if is_timed {
+                    samples.build.push(start_time.elapsed());
+                }
+                &built_capture
+            }
+        };
+
+        let start_time = Instant::now();
+        capture.replay(renderer)?;
+        if is_timed {
+            samples.render.push(start_time.elapsed());
+        }
+
+        if timer_queries {
+            shift_render_times(renderer, frame_counts, &mut timed_gpu_frame_count, samples);
+        }
+    }
+
+    // GPU timer queries resolve a few frames late; wait for the stragglers.
+    let deadline = Instant::now() + TIMER_QUERY_TIMEOUT;
+    while timer_queries && timed_gpu_frame_count < frame_count && Instant::now() < deadline {
+        if !shift_render_times(renderer, frame_counts, &mut timed_gpu_frame_count, samples) {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+    Ok(())
+}
+
+// Records the GPU times of every frame whose timer queries have resolved. Returns true if there
+// were any.
+fn shift_render_times<D>(renderer: &mut Renderer<D>,
+                         frame_counts: FrameCounts,
+                         gpu_frame_count: &mut usize,
+                         samples: &mut Samples)
+                         -> bool
+                         where D: Device {
+    let mut shifted_any = false;
+    while let Some(render_time) = renderer.shift_rendering_time() {
+        if *gpu_frame_count >= frame_counts.warmup {
+            samples.push_render_time(render_time);
+        }
+        *gpu_frame_count += 1;
+        shifted_any = true;
+    }
+    shifted_any
+}
+
+fn load_input(path: &str) -> Result<Input, String> {
+    let data = fs::read(path).map_err(|error| error.to_string())?;
+    let (kind, workload) = if data.starts_with(SCENE_MAGIC) {
+        let scene = Scene::read(&mut &data[..]).map_err(|error| error.to_string())?;
+        ("scene", Workload::Scene(scene))
+    } else if data.starts_with(CAPTURE_MAGIC) {
+        let capture = FrameCapture::read(&mut &data[..]).map_err(|error| error.to_string())?;
+        ("capture", Workload::Capture(capture))
+    } else if Path::new(path).extension().is_some_and(|extension| extension == "svg") {
+        ("svg", Workload::Scene(benchmarks::load_svg(&data)))
+    } else {
+        return Err("not a scene archive, frame capture, or SVG file".to_owned());
+    };
+    Ok(Input { path: path.to_owned(), kind, workload })
+}
+
+fn parse_count(string: &str) -> usize {
+    string.parse().unwrap_or_else(|_| fail(&format!("`{}` isn't a frame count", string)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("pathfinder-bench: {}", message);
+    process::exit(1)
+}
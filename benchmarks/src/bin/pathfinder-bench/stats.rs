@@ -0,0 +1,148 @@
+// pathfinder/benchmarks/src/bin/pathfinder-bench/stats.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The JSON report.
+
+use pathfinder_renderer::gpu::renderer::{RenderStats, RenderTime};
+use serde::Serialize;
+use std::time::Duration;
+
+#[derive(Serialize)]
+pub struct Report {
+    pub backend: &'static str,
+    pub warmup_frames: usize,
+    pub frames: usize,
+    pub results: Vec<InputReport>,
+}
+
+/// Timings for one input rendered at one size.
+#[derive(Serialize)]
+pub struct InputReport {
+    pub input: String,
+    /// `"scene"`, `"capture"`, or `"svg"`.
+    pub kind: &'static str,
+    pub width: i32,
+    pub height: i32,
+    /// Set if the renderer failed, in which case there are no timings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub stages: Stages,
+    /// Work counts from the last frame, which are the same for every frame.
+    pub counts: Option<Counts>,
+}
+
+/// Per-frame timings for each stage, or null for stages that weren't timed.
+#[derive(Default, Serialize)]
+pub struct Stages {
+    /// Building the scene into render commands on the CPU. Captures are already built.
+    pub build: Option<StageStats>,
+    /// Running the render commands on the CPU: uploading data and issuing draw calls.
+    pub render: Option<StageStats>,
+    /// Rasterizing edges into alpha tile masks and drawing clip masks on the GPU.
+    pub gpu_fill: Option<StageStats>,
+    /// Compositing tiles on the GPU.
+    pub gpu_tile: Option<StageStats>,
+    /// All GPU work.
+    pub gpu_total: Option<StageStats>,
+}
+
+#[derive(Serialize)]
+pub struct StageStats {
+    pub samples: usize,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub std_dev_ms: f64,
+}
+
+#[derive(Serialize)]
+pub struct Counts {
+    pub path_count: usize,
+    pub fill_count: usize,
+    pub alpha_tile_count: usize,
+    pub solid_tile_count: usize,
+    pub draw_call_count: usize,
+    pub bytes_uploaded: usize,
+    pub texture_memory_bytes: usize,
+}
+
+/// Raw per-frame timings, collected while rendering.
+#[derive(Default)]
+pub struct Samples {
+    pub build: Vec<Duration>,
+    pub render: Vec<Duration>,
+    pub gpu_fill: Vec<Duration>,
+    pub gpu_tile: Vec<Duration>,
+    pub gpu_total: Vec<Duration>,
+}
+
+impl Samples {
+    pub fn push_render_time(&mut self, render_time: RenderTime) {
+        self.gpu_fill.push(render_time.fill_time);
+        self.gpu_tile.push(render_time.tile_time);
+        self.gpu_total.push(render_time.gpu_time);
+    }
+
+    pub fn to_stages(&self) -> Stages {
+        Stages {
+            build: StageStats::new(&self.build),
+            render: StageStats::new(&self.render),
+            gpu_fill: StageStats::new(&self.gpu_fill),
+            gpu_tile: StageStats::new(&self.gpu_tile),
+            gpu_total: StageStats::new(&self.gpu_total),
+        }
+    }
+}
+
+impl StageStats {
+    /// Summarizes `samples`, or returns `None` if there aren't any.
+    pub fn new(samples: &[Duration]) -> Option<StageStats> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut times: Vec<f64> = samples.iter().map(|&time| duration_to_ms(time)).collect();
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let count = times.len();
+        let mean = times.iter().sum::<f64>() / count as f64;
+        // The two middle times, which are the same time if there's an odd number of them.
+        let median = (times[(count - 1) / 2] + times[count / 2]) * 0.5;
+        let variance = times.iter().map(|time| (time - mean) * (time - mean)).sum::<f64>() /
+            count as f64;
+
+        Some(StageStats {
+            samples: count,
+            mean_ms: mean,
+            median_ms: median,
+            min_ms: times[0],
+            max_ms: times[count - 1],
+            std_dev_ms: variance.sqrt(),
+        })
+    }
+}
+
+impl<'a> From<&'a RenderStats> for Counts {
+    fn from(stats: &'a RenderStats) -> Counts {
+        Counts {
+            path_count: stats.path_count,
+            fill_count: stats.fill_count,
+            alpha_tile_count: stats.alpha_tile_count,
+            solid_tile_count: stats.solid_tile_count,
+            draw_call_count: stats.draw_call_count,
+            bytes_uploaded: stats.bytes_uploaded,
+            texture_memory_bytes: stats.texture_memory_bytes,
+        }
+    }
+}
+
+fn duration_to_ms(time: Duration) -> f64 {
+    time.as_secs_f64() * 1000.0
+}
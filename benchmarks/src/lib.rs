@@ -0,0 +1,139 @@
+// pathfinder/benchmarks/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Workloads shared by the criterion benchmarks and the `pathfinder-bench` binary.
+
+use font_kit::handle::Handle;
+use font_kit::loader::Loader;
+use font_kit::loaders::default::Font;
+use pathfinder_canvas::{Canvas, CanvasFontContext, CanvasRenderingContext2D, ColorU, vec2f};
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2i};
+use pathfinder_renderer::concurrent::executor::Executor;
+use pathfinder_renderer::gpu_data::RenderCommand;
+use pathfinder_renderer::options::BuildOptions;
+use pathfinder_renderer::scene::{Scene, SceneStats};
+use pathfinder_resources::ResourceLoader;
+use pathfinder_resources::fs::FilesystemResourceLoader;
+use pathfinder_svg::BuiltSVG;
+use std::iter;
+use std::sync::Arc;
+use usvg::{Options as UsvgOptions, Tree};
+
+/// SVG files from `resources/svg` that make good benchmarks.
+///
+/// `julius-caesar` is a page of text converted to outlines.
+pub const BUNDLED_SVGS: [&str; 3] = ["Ghostscript_Tiger", "julius-caesar", "nba-notext"];
+
+const TEXT_FONT: &str = "fonts/Overpass-Regular.otf";
+
+const LOREM_IPSUM: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do \
+                           eiusmod tempor incididunt ut labore et dolore magna aliqua.";
+
+/// Parses an SVG file from `resources/svg`, e.g. `"Ghostscript_Tiger"`.
+pub fn load_bundled_svg(name: &str) -> Scene {
+    let data = FilesystemResourceLoader::locate().slurp(&format!("svg/{}.svg", name))
+                                                 .expect("No such bundled SVG!");
+    load_svg(&data)
+}
+
+pub fn load_svg(data: &[u8]) -> Scene {
+    let tree = Tree::from_data(data, &UsvgOptions::default()).expect("Failed to parse the SVG!");
+    BuiltSVG::from_tree(&tree).scene
+}
+
+/// Builds the scene and throws away the resulting render commands.
+pub fn build_scene<E>(scene: &mut Scene, options: BuildOptions, executor: &E) -> SceneStats
+                      where E: Executor {
+    scene.build(options, Box::new(|_: RenderCommand| {}), executor)
+}
+
+/// Scales the scene to fill the framebuffer, preserving its aspect ratio.
+pub fn fit_transform(scene: &Scene, framebuffer_size: Vector2I) -> Transform2F {
+    let view_box = scene.view_box();
+    if view_box.width() <= 0.0 || view_box.height() <= 0.0 {
+        return Transform2F::default();
+    }
+    let framebuffer_size = framebuffer_size.to_f32();
+    let scale = f32::min(framebuffer_size.x() / view_box.width(),
+                         framebuffer_size.y() / view_box.height());
+    Transform2F::from_translation(-view_box.origin()).scale(scale)
+}
+
+/// Parses a size of the form `WIDTHxHEIGHT`, e.g. `"1920x1080"`.
+pub fn parse_size(string: &str) -> Option<Vector2I> {
+    let mut dimensions = string.split('x').map(|dimension| dimension.parse::<i32>());
+    match (dimensions.next(), dimensions.next(), dimensions.next()) {
+        (Some(Ok(width)), Some(Ok(height)), None) if width > 0 && height > 0 => {
+            Some(vec2i(width, height))
+        }
+        _ => None,
+    }
+}
+
+/// A polyline that zigzags across a `size` × `size` square `count` times, for stroking
+/// benchmarks. Sharp angles make the joins expensive.
+pub fn zigzag_outline(count: usize, size: f32) -> Outline {
+    let mut contour = Contour::new();
+    for index in 0..=count {
+        let x = size * index as f32 / count as f32;
+        let y = if index % 2 == 0 { 0.0 } else { size };
+        contour.push_endpoint(vec2f(x, y));
+    }
+    let mut outline = Outline::new();
+    outline.push_contour(contour);
+    outline
+}
+
+/// The font that the text benchmarks use.
+pub fn text_font() -> Font {
+    let font_data = FilesystemResourceLoader::locate().slurp(TEXT_FONT).unwrap();
+    Font::from_bytes(Arc::new(font_data), 0).unwrap()
+}
+
+pub fn text_font_context() -> CanvasFontContext {
+    let font_data = FilesystemResourceLoader::locate().slurp(TEXT_FONT).unwrap();
+    let font = Handle::from_memory(Arc::new(font_data), 0);
+    CanvasFontContext::from_fonts(iter::once(font))
+}
+
+/// Lays out `line_count` lines of text at 16px, like a page of a document.
+pub fn draw_text_page(context: &mut CanvasRenderingContext2D, line_count: usize) {
+    context.set_font("Overpass-Regular");
+    context.set_font_size(16.0);
+    context.set_fill_style(ColorU::black());
+    for line_index in 0..line_count {
+        context.fill_text(LOREM_IPSUM, vec2f(16.0, 24.0 + line_index as f32 * 20.0));
+    }
+}
+
+/// The glyph IDs and offsets of `line_count` lines of text at 16px, one run per line, laid out
+/// by advancing along each line without kerning or shaping.
+pub fn text_page_glyph_runs(font: &Font, line_count: usize) -> Vec<(Vec<u32>, Vec<Vector2F>)> {
+    let scale = 16.0 / font.metrics().units_per_em as f32;
+    let (mut glyph_ids, mut offsets) = (vec![], vec![]);
+    let mut x = 0.0;
+    for character in LOREM_IPSUM.chars() {
+        let glyph_id = font.glyph_for_char(character).unwrap_or(0);
+        glyph_ids.push(glyph_id);
+        offsets.push(vec2f(x, 0.0));
+        x += font.advance(glyph_id).map(|advance| advance.x()).unwrap_or(0.0) * scale;
+    }
+    (0..line_count).map(|_| (glyph_ids.clone(), offsets.clone())).collect()
+}
+
+/// A scene containing a page of text; see `draw_text_page()`.
+pub fn text_page_scene(font_context: CanvasFontContext, line_count: usize) -> Scene {
+    let size = Vector2F::new(1024.0, 24.0 + line_count as f32 * 20.0);
+    let mut context = Canvas::new(size).get_context_2d(font_context);
+    draw_text_page(&mut context, line_count);
+    context.into_canvas().into_scene()
+}
@@ -14,7 +14,7 @@ use font_kit::handle::Handle;
 use foreign_types::ForeignTypeRef;
 use gl;
 use pathfinder_canvas::{Canvas, CanvasFontContext, CanvasRenderingContext2D, FillStyle, LineJoin};
-use pathfinder_canvas::{Path2D, TextAlign, TextMetrics};
+use pathfinder_canvas::{Path2D, StrokeAlignment, TextAlign, TextMetrics};
 use pathfinder_color::{ColorF, ColorU};
 use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::ArcDirection;
@@ -28,13 +28,14 @@ use pathfinder_resources::ResourceLoader;
 use pathfinder_resources::fs::FilesystemResourceLoader;
 use pathfinder_renderer::concurrent::rayon::RayonExecutor;
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::options::{ClearMode, DestFramebuffer, OutputAlpha, RendererOptions};
 use pathfinder_renderer::gpu::renderer::Renderer;
 use pathfinder_renderer::options::{BuildOptions, RenderTransform};
 use pathfinder_renderer::scene::Scene;
 use pathfinder_simd::default::F32x4;
 use std::ffi::CString;
 use std::os::raw::{c_char, c_void};
+use std::ptr;
 use std::slice;
 use std::str;
 
@@ -54,6 +55,12 @@ pub const PF_LINE_CAP_ROUND:    u8 = 2;
 pub const PF_LINE_JOIN_MITER:   u8 = 0;
 pub const PF_LINE_JOIN_BEVEL:   u8 = 1;
 pub const PF_LINE_JOIN_ROUND:   u8 = 2;
+pub const PF_LINE_JOIN_MITER_CLIP: u8 = 3;
+pub const PF_LINE_JOIN_ARCS:    u8 = 4;
+
+pub const PF_STROKE_ALIGNMENT_CENTER: u8 = 0;
+pub const PF_STROKE_ALIGNMENT_INNER:  u8 = 1;
+pub const PF_STROKE_ALIGNMENT_OUTER:  u8 = 2;
 
 pub const PF_TEXT_ALIGN_LEFT:   u8 = 0;
 pub const PF_TEXT_ALIGN_CENTER: u8 = 1;
@@ -72,6 +79,9 @@ pub const PF_GL_VERSION_GLES3:  u8 = 1;
 // `renderer`
 
 pub const PF_RENDERER_OPTIONS_FLAGS_HAS_BACKGROUND_COLOR: u8 = 0x1;
+pub const PF_RENDERER_OPTIONS_FLAGS_DITHER: u8 = 0x2;
+/// Flattens the output onto the background color, so that it's opaque.
+pub const PF_RENDERER_OPTIONS_FLAGS_OPAQUE: u8 = 0x4;
 
 // Types
 
@@ -85,6 +95,7 @@ pub type PFCanvasFontContextRef = *mut CanvasFontContext;
 pub type PFFillStyleRef = *mut FillStyle;
 pub type PFLineCap = u8;
 pub type PFLineJoin = u8;
+pub type PFStrokeAlignment = u8;
 pub type PFArcDirection = u8;
 pub type PFTextAlign = u8;
 #[repr(C)]
@@ -299,10 +310,22 @@ pub unsafe extern "C" fn PFCanvasSetLineJoin(canvas: PFCanvasRef, new_line_join:
     (*canvas).set_line_join(match new_line_join {
         PF_LINE_JOIN_BEVEL => LineJoin::Bevel,
         PF_LINE_JOIN_ROUND => LineJoin::Round,
+        PF_LINE_JOIN_MITER_CLIP => LineJoin::MiterClip,
+        PF_LINE_JOIN_ARCS  => LineJoin::Arcs,
         _                  => LineJoin::Miter,
     });
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetStrokeAlignment(canvas: PFCanvasRef,
+                                                    new_stroke_alignment: PFStrokeAlignment) {
+    (*canvas).set_stroke_alignment(match new_stroke_alignment {
+        PF_STROKE_ALIGNMENT_INNER => StrokeAlignment::Inner,
+        PF_STROKE_ALIGNMENT_OUTER => StrokeAlignment::Outer,
+        _                         => StrokeAlignment::Center,
+    });
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFCanvasSetMiterLimit(canvas: PFCanvasRef, new_miter_limit: f32) {
     (*canvas).set_miter_limit(new_miter_limit);
@@ -528,16 +551,25 @@ pub unsafe extern "C" fn PFGLDestFramebufferDestroy(dest_framebuffer: PFGLDestFr
 /// This function takes ownership of and automatically takes responsibility for destroying `device`
 /// and `dest_framebuffer`. However, it does not take ownership of `resources`; therefore, if you
 /// created the resource loader, you must destroy it yourself to avoid a memory leak.
+///
+/// Returns null if the renderer couldn't be created, for example because a shader failed to
+/// compile.
 #[no_mangle]
 pub unsafe extern "C" fn PFGLRendererCreate(device: PFGLDeviceRef,
                                             resources: PFResourceLoaderRef,
                                             dest_framebuffer: PFGLDestFramebufferRef,
                                             options: *const PFRendererOptions)
                                             -> PFGLRendererRef {
-    Box::into_raw(Box::new(Renderer::new(*Box::from_raw(device),
-                                         &*((*resources).0),
-                                         *Box::from_raw(dest_framebuffer),
-                                         (*options).to_rust())))
+    match Renderer::new(*Box::from_raw(device),
+                        &*((*resources).0),
+                        *Box::from_raw(dest_framebuffer),
+                        (*options).to_rust()) {
+        Ok(mut renderer) => {
+            renderer.set_clear_mode((*options).clear_mode());
+            Box::into_raw(Box::new(renderer))
+        }
+        Err(_) => ptr::null_mut(),
+    }
 }
 
 #[no_mangle]
@@ -567,6 +599,9 @@ pub unsafe extern "C" fn PFMetalDestFramebufferDestroy(dest_framebuffer:
 /// This function takes ownership of and automatically takes responsibility for destroying `device`
 /// and `dest_framebuffer`. However, it does not take ownership of `resources`; therefore, if you
 /// created the resource loader, you must destroy it yourself to avoid a memory leak.
+///
+/// Returns null if the renderer couldn't be created, for example because a shader failed to
+/// compile.
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
 #[no_mangle]
 pub unsafe extern "C" fn PFMetalRendererCreate(device: PFMetalDeviceRef,
@@ -574,10 +609,16 @@ pub unsafe extern "C" fn PFMetalRendererCreate(device: PFMetalDeviceRef,
                                                dest_framebuffer: PFMetalDestFramebufferRef,
                                                options: *const PFRendererOptions)
                                                -> PFMetalRendererRef {
-    Box::into_raw(Box::new(Renderer::new(*Box::from_raw(device),
-                                         &*((*resources).0),
-                                         *Box::from_raw(dest_framebuffer),
-                                         (*options).to_rust())))
+    match Renderer::new(*Box::from_raw(device),
+                        &*((*resources).0),
+                        *Box::from_raw(dest_framebuffer),
+                        (*options).to_rust()) {
+        Ok(mut renderer) => {
+            renderer.set_clear_mode((*options).clear_mode());
+            Box::into_raw(Box::new(renderer))
+        }
+        Err(_) => ptr::null_mut(),
+    }
 }
 
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
@@ -598,21 +639,27 @@ pub unsafe extern "C" fn PFMetalRendererGetDevice(renderer: PFMetalRendererRef)
 
 /// This function does not take ownership of `renderer` or `build_options`. Therefore, if you
 /// created the renderer and/or options, you must destroy them yourself to avoid a leak.
+///
+/// Returns false if the frame couldn't be rendered in full. The renderer can still be used.
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneProxyBuildAndRenderGL(scene_proxy: PFSceneProxyRef,
                                                       renderer: PFGLRendererRef,
-                                                      build_options: PFBuildOptionsRef) {
-    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone())
+                                                      build_options: PFBuildOptionsRef)
+                                                      -> bool {
+    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone()).is_ok()
 }
 
 /// This function does not take ownership of `renderer` or `build_options`. Therefore, if you
 /// created the renderer and/or options, you must destroy them yourself to avoid a leak.
+///
+/// Returns false if the frame couldn't be rendered in full. The renderer can still be used.
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneProxyBuildAndRenderMetal(scene_proxy: PFSceneProxyRef,
                                                          renderer: PFMetalRendererRef,
-                                                         build_options: PFBuildOptionsRef) {
-    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone())
+                                                         build_options: PFBuildOptionsRef)
+                                                         -> bool {
+    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone()).is_ok()
 }
 
 // `metal`
@@ -680,6 +727,12 @@ pub unsafe extern "C" fn PFBuildOptionsSetSubpixelAAEnabled(options: PFBuildOpti
     (*options).subpixel_aa_enabled = subpixel_aa_enabled
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFBuildOptionsSetExactAxisAlignedAAEnabled(options: PFBuildOptionsRef,
+                                                                    enabled: bool) {
+    (*options).exact_axis_aligned_aa = enabled
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneDestroy(scene: PFSceneRef) {
     drop(Box::from_raw(scene))
@@ -799,15 +852,23 @@ impl PFPerspective {
 
 impl PFRendererOptions {
     pub fn to_rust(&self) -> RendererOptions {
-        let has_background_color = self.flags & PF_RENDERER_OPTIONS_FLAGS_HAS_BACKGROUND_COLOR;
         RendererOptions {
-            background_color: if has_background_color != 0 {
-                Some(self.background_color.to_rust())
-            } else {
-                None
-            },
             // TODO(pcwalton): Expose this in the C API.
             no_compute: false,
+            dithering: (self.flags & PF_RENDERER_OPTIONS_FLAGS_DITHER) != 0,
+            output_alpha: if (self.flags & PF_RENDERER_OPTIONS_FLAGS_OPAQUE) != 0 {
+                OutputAlpha::Opaque(self.background_color.to_rust())
+            } else {
+                OutputAlpha::Premultiplied
+            },
+        }
+    }
+
+    pub fn clear_mode(&self) -> ClearMode {
+        if (self.flags & PF_RENDERER_OPTIONS_FLAGS_HAS_BACKGROUND_COLOR) != 0 {
+            ClearMode::Color(self.background_color.to_rust())
+        } else {
+            ClearMode::Preserve
         }
     }
 }
@@ -0,0 +1,823 @@
+// pathfinder/canvas/src/css.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The string-valued properties of the HTML canvas API.
+//!
+//! Code ported from JavaScript sets styles with CSS strings such as `"rgba(0, 0, 0, 0.5)"` and
+//! `"italic bold 14px serif"`. The `*_str` setters here accept those strings, so ports (and
+//! bindings to JavaScript) don't have to convert them. As in browsers, values that don't parse
+//! leave the state unchanged; the setters also report them as errors.
+
+use crate::{CanvasRenderingContext2D, CompositeOperation, FillStyle, ImageSmoothingQuality};
+use crate::{DEFAULT_FONT_SIZE, LineJoin};
+#[cfg(feature = "pf-text")]
+use crate::{TextAlign, TextBaseline};
+use pathfinder_color::{ColorF, ColorU};
+use pathfinder_content::gradient::ColorStop;
+use pathfinder_content::stroke::LineCap;
+use std::error::Error;
+use std::f32::consts::PI;
+use std::fmt::{self, Display, Formatter};
+
+/// A string that isn't a valid value for the property it was assigned to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidCssValue(pub String);
+
+/// A parsed CSS `font` shorthand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CssFont {
+    pub style: CssFontStyle,
+    pub small_caps: bool,
+    /// The weight, from 1 to 1000. Normal is 400 and bold is 700.
+    pub weight: f32,
+    /// The width, as a fraction of the normal width.
+    pub stretch: f32,
+    /// The size, in pixels.
+    pub size: f32,
+    /// The font families, in order of preference.
+    pub families: Vec<CssFontFamily>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CssFontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CssFontFamily {
+    Named(String),
+    Serif,
+    SansSerif,
+    Monospace,
+    Cursive,
+    Fantasy,
+}
+
+impl CanvasRenderingContext2D {
+    pub fn set_fill_style_str(&mut self, new_fill_style: &str) -> Result<(), InvalidCssValue> {
+        let color = parse_color(new_fill_style).ok_or_else(|| invalid(new_fill_style))?;
+        self.set_fill_style(FillStyle::Color(color));
+        Ok(())
+    }
+
+    pub fn set_stroke_style_str(&mut self, new_stroke_style: &str)
+                                -> Result<(), InvalidCssValue> {
+        let color = parse_color(new_stroke_style).ok_or_else(|| invalid(new_stroke_style))?;
+        self.set_stroke_style(FillStyle::Color(color));
+        Ok(())
+    }
+
+    pub fn set_shadow_color_str(&mut self, new_shadow_color: &str)
+                                -> Result<(), InvalidCssValue> {
+        let color = parse_color(new_shadow_color).ok_or_else(|| invalid(new_shadow_color))?;
+        self.set_shadow_color(color);
+        Ok(())
+    }
+
+    pub fn set_line_cap_str(&mut self, new_line_cap: &str) -> Result<(), InvalidCssValue> {
+        self.set_line_cap(match new_line_cap {
+            "butt" => LineCap::Butt,
+            "round" => LineCap::Round,
+            "square" => LineCap::Square,
+            _ => return Err(invalid(new_line_cap)),
+        });
+        Ok(())
+    }
+
+    pub fn set_line_join_str(&mut self, new_line_join: &str) -> Result<(), InvalidCssValue> {
+        self.set_line_join(match new_line_join {
+            "miter" => LineJoin::Miter,
+            "bevel" => LineJoin::Bevel,
+            "round" => LineJoin::Round,
+            "miter-clip" => LineJoin::MiterClip,
+            "arcs" => LineJoin::Arcs,
+            _ => return Err(invalid(new_line_join)),
+        });
+        Ok(())
+    }
+
+    /// Sets the text alignment. Text is assumed to be left-to-right, so `start` is `left` and
+    /// `end` is `right`.
+    #[cfg(feature = "pf-text")]
+    pub fn set_text_align_str(&mut self, new_text_align: &str) -> Result<(), InvalidCssValue> {
+        self.set_text_align(match new_text_align {
+            "left" | "start" => TextAlign::Left,
+            "right" | "end" => TextAlign::Right,
+            "center" => TextAlign::Center,
+            _ => return Err(invalid(new_text_align)),
+        });
+        Ok(())
+    }
+
+    #[cfg(feature = "pf-text")]
+    pub fn set_text_baseline_str(&mut self, new_text_baseline: &str)
+                                 -> Result<(), InvalidCssValue> {
+        self.set_text_baseline(match new_text_baseline {
+            "alphabetic" => TextBaseline::Alphabetic,
+            "top" => TextBaseline::Top,
+            "hanging" => TextBaseline::Hanging,
+            "middle" => TextBaseline::Middle,
+            "ideographic" => TextBaseline::Ideographic,
+            "bottom" => TextBaseline::Bottom,
+            _ => return Err(invalid(new_text_baseline)),
+        });
+        Ok(())
+    }
+
+    /// Sets the font and font size from a CSS `font` shorthand such as `"italic bold 14px
+    /// serif"`. The first family that the font context can find is used, falling back to the
+    /// default font if none of them is available.
+    #[cfg(feature = "pf-text")]
+    pub fn set_font_str(&mut self, new_font: &str) -> Result<(), InvalidCssValue> {
+        let font = parse_font(new_font).ok_or_else(|| invalid(new_font))?;
+        let font_collection = self.canvas_font_context.font_collection_for_css_font(&font);
+        self.set_font(font_collection);
+        self.set_font_size(font.size);
+        Ok(())
+    }
+
+    pub fn set_global_composite_operation_str(&mut self, new_composite_operation: &str)
+                                              -> Result<(), InvalidCssValue> {
+        self.set_global_composite_operation(match new_composite_operation {
+            "source-over" => CompositeOperation::SourceOver,
+            "source-in" => CompositeOperation::SourceIn,
+            "source-out" => CompositeOperation::SourceOut,
+            "source-atop" => CompositeOperation::SourceAtop,
+            "destination-over" => CompositeOperation::DestinationOver,
+            "destination-in" => CompositeOperation::DestinationIn,
+            "destination-out" => CompositeOperation::DestinationOut,
+            "destination-atop" => CompositeOperation::DestinationAtop,
+            "lighter" => CompositeOperation::Lighter,
+            "copy" => CompositeOperation::Copy,
+            "xor" => CompositeOperation::Xor,
+            "multiply" => CompositeOperation::Multiply,
+            "screen" => CompositeOperation::Screen,
+            "overlay" => CompositeOperation::Overlay,
+            "darken" => CompositeOperation::Darken,
+            "lighten" => CompositeOperation::Lighten,
+            "color-dodge" => CompositeOperation::ColorDodge,
+            "color-burn" => CompositeOperation::ColorBurn,
+            "hard-light" => CompositeOperation::HardLight,
+            "soft-light" => CompositeOperation::SoftLight,
+            "difference" => CompositeOperation::Difference,
+            "exclusion" => CompositeOperation::Exclusion,
+            "hue" => CompositeOperation::Hue,
+            "saturation" => CompositeOperation::Saturation,
+            "color" => CompositeOperation::Color,
+            "luminosity" => CompositeOperation::Luminosity,
+            _ => return Err(invalid(new_composite_operation)),
+        });
+        Ok(())
+    }
+
+    pub fn set_image_smoothing_quality_str(&mut self, new_quality: &str)
+                                           -> Result<(), InvalidCssValue> {
+        self.set_image_smoothing_quality(match new_quality {
+            "low" => ImageSmoothingQuality::Low,
+            "medium" => ImageSmoothingQuality::Medium,
+            "high" => ImageSmoothingQuality::High,
+            _ => return Err(invalid(new_quality)),
+        });
+        Ok(())
+    }
+}
+
+impl Display for InvalidCssValue {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "invalid CSS value: {:?}", self.0)
+    }
+}
+
+impl Error for InvalidCssValue {}
+
+fn invalid(value: &str) -> InvalidCssValue {
+    InvalidCssValue(value.to_owned())
+}
+
+// Colors
+
+/// Parses a CSS color: a named color, `transparent`, a hex color of 3, 4, 6, or 8 digits, or an
+/// `rgb()`, `rgba()`, `hsl()`, or `hsla()` function in either the comma-separated or the
+/// space-separated syntax.
+pub fn parse_color(value: &str) -> Option<ColorU> {
+    let value = value.trim().to_ascii_lowercase();
+    if let Some(digits) = value.strip_prefix('#') {
+        return parse_hex_color(digits);
+    }
+    if let Some(open_paren_index) = value.find('(') {
+        if !value.ends_with(')') {
+            return None;
+        }
+        let name = &value[0..open_paren_index];
+        let args = parse_color_function_args(&value[(open_paren_index + 1)..(value.len() - 1)])?;
+        return match name {
+            "rgb" | "rgba" => parse_rgb_args(&args),
+            "hsl" | "hsla" => parse_hsl_args(&args),
+            _ => None,
+        };
+    }
+    if value == "transparent" {
+        return Some(ColorU::transparent_black());
+    }
+    NAMED_COLORS.binary_search_by(|&(name, _)| name.cmp(&value))
+                .ok()
+                .map(|index| ColorU::from_u32((NAMED_COLORS[index].1 << 8) | 0xff))
+}
+
+fn parse_hex_color(digits: &str) -> Option<ColorU> {
+    if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |index: usize| u8::from_str_radix(&digits[index..(index + 1)], 16).unwrap();
+    let byte = |index: usize| u8::from_str_radix(&digits[index..(index + 2)], 16).unwrap();
+    match digits.len() {
+        3 => Some(ColorU::new(digit(0) * 17, digit(1) * 17, digit(2) * 17, 255)),
+        4 => Some(ColorU::new(digit(0) * 17, digit(1) * 17, digit(2) * 17, digit(3) * 17)),
+        6 => Some(ColorU::new(byte(0), byte(2), byte(4), 255)),
+        8 => Some(ColorU::new(byte(0), byte(2), byte(4), byte(6))),
+        _ => None,
+    }
+}
+
+// Splits the arguments of a color function into the three components and the optional alpha.
+fn parse_color_function_args(args: &str) -> Option<Vec<&str>> {
+    if args.contains(',') {
+        let args: Vec<&str> = args.split(',').map(|arg| arg.trim()).collect();
+        if args.len() < 3 || args.len() > 4 || args.iter().any(|arg| arg.is_empty()) {
+            return None;
+        }
+        return Some(args);
+    }
+
+    let mut parts = args.splitn(2, '/');
+    let mut args: Vec<&str> = parts.next()?.split_whitespace().collect();
+    if args.len() != 3 {
+        return None;
+    }
+    if let Some(alpha) = parts.next() {
+        let alpha = alpha.trim();
+        if alpha.is_empty() || alpha.contains(char::is_whitespace) {
+            return None;
+        }
+        args.push(alpha);
+    }
+    Some(args)
+}
+
+fn parse_rgb_args(args: &[&str]) -> Option<ColorU> {
+    let mut rgb = [0; 3];
+    for (channel, arg) in rgb.iter_mut().zip(args.iter()) {
+        let value = match parse_percentage(arg) {
+            Some(percentage) => percentage * 255.0,
+            None => arg.parse::<f32>().ok()?,
+        };
+        *channel = clamp_to_u8(value);
+    }
+    let alpha = parse_alpha(args.get(3))?;
+    Some(ColorU::new(rgb[0], rgb[1], rgb[2], clamp_to_u8(alpha * 255.0)))
+}
+
+fn parse_hsl_args(args: &[&str]) -> Option<ColorU> {
+    let hue = parse_angle(args[0])?;
+    let saturation = parse_percentage(args[1])?.clamp(0.0, 1.0);
+    let lightness = parse_percentage(args[2])?.clamp(0.0, 1.0);
+    let alpha = parse_alpha(args.get(3))?;
+    let color = ColorF::from_hsla(hue, saturation, lightness, 1.0).to_u8();
+    Some(ColorU { a: clamp_to_u8(alpha * 255.0), ..color })
+}
+
+fn parse_alpha(arg: Option<&&str>) -> Option<f32> {
+    let arg = match arg {
+        None => return Some(1.0),
+        Some(arg) => arg,
+    };
+    let alpha = match parse_percentage(arg) {
+        Some(percentage) => percentage,
+        None => arg.parse::<f32>().ok()?,
+    };
+    Some(alpha.clamp(0.0, 1.0))
+}
+
+// Returns the angle in radians. Unitless hues are in degrees.
+fn parse_angle(arg: &str) -> Option<f32> {
+    let (number, radians_per_unit) = if arg.ends_with("deg") {
+        (&arg[0..(arg.len() - 3)], PI / 180.0)
+    } else if arg.ends_with("grad") {
+        (&arg[0..(arg.len() - 4)], PI / 200.0)
+    } else if arg.ends_with("rad") {
+        (&arg[0..(arg.len() - 3)], 1.0)
+    } else if arg.ends_with("turn") {
+        (&arg[0..(arg.len() - 4)], 2.0 * PI)
+    } else {
+        (arg, PI / 180.0)
+    };
+    Some(number.parse::<f32>().ok()? * radians_per_unit)
+}
+
+// Returns the percentage as a fraction.
+fn parse_percentage(arg: &str) -> Option<f32> {
+    if !arg.ends_with('%') {
+        return None;
+    }
+    Some(arg[0..(arg.len() - 1)].parse::<f32>().ok()? / 100.0)
+}
+
+fn clamp_to_u8(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+// Gradients
+
+/// Parses the color stops of a CSS gradient function, such as `"red, 30%, blue 60% 80%, green"`,
+/// for adding to a `Gradient`.
+///
+/// Positions must be percentages, since lengths depend on the size of the box a gradient fills.
+/// Missing positions and positions out of order are fixed up as CSS specifies, and color
+/// interpolation hints (lone percentages between two colors) become the midpoints of stops.
+pub fn parse_color_stops(value: &str) -> Option<Vec<ColorStop>> {
+    // Each color stop, with its position if it has one, and the hint before it if any.
+    let mut stops: Vec<(ColorU, Option<f32>, Option<f32>)> = vec![];
+    let mut pending_hint = None;
+    for item in split_top_level_commas(value) {
+        let tokens = split_top_level_whitespace(item);
+        if tokens.len() == 1 {
+            if let Some(hint) = parse_percentage(tokens[0]) {
+                // Hints must come between two color stops.
+                if stops.is_empty() || pending_hint.is_some() {
+                    return None;
+                }
+                pending_hint = Some(hint);
+                continue;
+            }
+        }
+
+        let position_count = tokens.iter()
+                                   .rev()
+                                   .take(2)
+                                   .take_while(|token| parse_percentage(token).is_some())
+                                   .count();
+        let color_tokens = &tokens[0..(tokens.len() - position_count)];
+        if color_tokens.is_empty() {
+            return None;
+        }
+        let color = parse_color(&color_tokens.join(" "))?;
+        let positions: Vec<f32> = tokens[color_tokens.len()..].iter()
+                                                              .filter_map(|token| {
+                                                                  parse_percentage(token)
+                                                              })
+                                                              .collect();
+        match positions.len() {
+            0 => stops.push((color, None, pending_hint.take())),
+            1 => stops.push((color, Some(positions[0]), pending_hint.take())),
+            _ => {
+                stops.push((color, Some(positions[0]), pending_hint.take()));
+                stops.push((color, Some(positions[1]), None));
+            }
+        }
+    }
+    if stops.is_empty() || pending_hint.is_some() {
+        return None;
+    }
+
+    // The first and last stops default to the ends, and no stop may come before an earlier one.
+    if stops[0].1.is_none() {
+        stops[0].1 = Some(0.0);
+    }
+    let last_index = stops.len() - 1;
+    if stops[last_index].1.is_none() {
+        stops[last_index].1 = Some(1.0);
+    }
+    let mut max_position = f32::NEG_INFINITY;
+    for stop in &mut stops {
+        if let Some(ref mut position) = stop.1 {
+            *position = position.max(max_position);
+            max_position = *position;
+        }
+    }
+
+    // Stops without positions are spread evenly between the stops around them.
+    let mut index = 0;
+    while index < stops.len() {
+        if stops[index].1.is_some() {
+            index += 1;
+            continue;
+        }
+        let run_start = index;
+        while stops[index].1.is_none() {
+            index += 1;
+        }
+        let (start, end) = (stops[run_start - 1].1.unwrap(), stops[index].1.unwrap());
+        let step = (end - start) / (index - run_start + 1) as f32;
+        for (run_index, stop) in stops[run_start..index].iter_mut().enumerate() {
+            stop.1 = Some(start + step * (run_index + 1) as f32);
+        }
+    }
+
+    let mut color_stops: Vec<ColorStop> = vec![];
+    for (color, position, hint) in stops {
+        let offset = position.unwrap();
+        let midpoint = match (hint, color_stops.last()) {
+            (Some(hint), Some(prev_stop)) if offset > prev_stop.offset => {
+                ((hint - prev_stop.offset) / (offset - prev_stop.offset)).clamp(0.0, 1.0)
+            }
+            _ => 0.5,
+        };
+        color_stops.push(ColorStop::with_midpoint(color, offset, midpoint));
+    }
+    Some(color_stops)
+}
+
+fn split_top_level_commas(string: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let (mut depth, mut item_start) = (0, 0);
+    for (index, c) in string.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(string[item_start..index].trim());
+                item_start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(string[item_start..].trim());
+    items
+}
+
+fn split_top_level_whitespace(string: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let (mut depth, mut token_start) = (0, None);
+    for (index, c) in string.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if c.is_whitespace() && depth == 0 => {
+                if let Some(start) = token_start.take() {
+                    tokens.push(&string[start..index]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if token_start.is_none() {
+            token_start = Some(index);
+        }
+    }
+    if let Some(start) = token_start {
+        tokens.push(&string[start..]);
+    }
+    tokens
+}
+
+// Fonts
+
+/// Parses a CSS `font` shorthand, such as `"italic small-caps bold condensed 16px/2 cursive"`.
+///
+/// Line heights are accepted but ignored, as the canvas does. System fonts such as `caption`
+/// aren't supported.
+pub fn parse_font(value: &str) -> Option<CssFont> {
+    let mut font = CssFont {
+        style: CssFontStyle::Normal,
+        small_caps: false,
+        weight: 400.0,
+        stretch: 1.0,
+        size: DEFAULT_FONT_SIZE,
+        families: vec![],
+    };
+
+    // The style, variant, weight, and stretch come first, in any order. Each may appear at most
+    // once, but any of them may be `normal`.
+    let mut rest = value.trim();
+    let mut seen = (false, false, false, false);
+    let mut prefix_count = 0;
+    loop {
+        let (token, remainder) = split_font_token(rest);
+        let token = token.to_ascii_lowercase();
+        if let Some(size) = parse_font_size(&token) {
+            font.size = size;
+            rest = remainder;
+            break;
+        }
+
+        prefix_count += 1;
+        if prefix_count > 4 {
+            return None;
+        }
+        match &*token {
+            "normal" => {}
+            "italic" | "oblique" if !seen.0 => {
+                seen.0 = true;
+                font.style = if token == "italic" {
+                    CssFontStyle::Italic
+                } else {
+                    CssFontStyle::Oblique
+                };
+            }
+            "small-caps" if !seen.1 => {
+                seen.1 = true;
+                font.small_caps = true;
+            }
+            _ if !seen.2 && parse_font_weight(&token).is_some() => {
+                seen.2 = true;
+                font.weight = parse_font_weight(&token).unwrap();
+            }
+            _ if !seen.3 && parse_font_stretch(&token).is_some() => {
+                seen.3 = true;
+                font.stretch = parse_font_stretch(&token).unwrap();
+            }
+            _ => return None,
+        }
+        rest = remainder;
+    }
+
+    rest = rest.trim_start();
+    if rest.starts_with('/') {
+        let (line_height, remainder) = split_font_token(rest[1..].trim_start());
+        let line_height = line_height.to_ascii_lowercase();
+        if line_height != "normal" && line_height.parse::<f32>().is_err() &&
+                parse_font_size(&line_height).is_none() {
+            return None;
+        }
+        rest = remainder;
+    }
+
+    font.families = parse_font_families(rest)?;
+    Some(font)
+}
+
+// Splits off everything up to the next space or slash.
+fn split_font_token(string: &str) -> (&str, &str) {
+    let end = string.find(|c: char| c.is_whitespace() || c == '/').unwrap_or(string.len());
+    (&string[0..end], string[end..].trim_start())
+}
+
+// Returns the size in pixels.
+fn parse_font_size(token: &str) -> Option<f32> {
+    let keyword_size = match token {
+        "xx-small" => Some(9.0),
+        "x-small" => Some(10.0),
+        "small" => Some(13.0),
+        "medium" => Some(16.0),
+        "large" => Some(18.0),
+        "x-large" => Some(24.0),
+        "xx-large" => Some(32.0),
+        "xxx-large" => Some(48.0),
+        "smaller" => Some(DEFAULT_FONT_SIZE / 1.2),
+        "larger" => Some(DEFAULT_FONT_SIZE * 1.2),
+        _ => None,
+    };
+    if keyword_size.is_some() {
+        return keyword_size;
+    }
+
+    if let Some(percentage) = parse_percentage(token) {
+        return if percentage >= 0.0 { Some(percentage * DEFAULT_FONT_SIZE) } else { None };
+    }
+    let unit_start = token.find(|c: char| c.is_ascii_alphabetic())?;
+    let number: f32 = token[0..unit_start].parse().ok()?;
+    let pixels_per_unit = match &token[unit_start..] {
+        "px" => 1.0,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "q" => 96.0 / 101.6,
+        "em" | "rem" => DEFAULT_FONT_SIZE,
+        _ => return None,
+    };
+    if number < 0.0 {
+        return None;
+    }
+    Some(number * pixels_per_unit)
+}
+
+fn parse_font_weight(token: &str) -> Option<f32> {
+    match token {
+        "bold" | "bolder" => Some(700.0),
+        "lighter" => Some(100.0),
+        _ => {
+            let weight: f32 = token.parse().ok()?;
+            if (1.0..=1000.0).contains(&weight) { Some(weight) } else { None }
+        }
+    }
+}
+
+fn parse_font_stretch(token: &str) -> Option<f32> {
+    match token {
+        "ultra-condensed" => Some(0.5),
+        "extra-condensed" => Some(0.625),
+        "condensed" => Some(0.75),
+        "semi-condensed" => Some(0.875),
+        "semi-expanded" => Some(1.125),
+        "expanded" => Some(1.25),
+        "extra-expanded" => Some(1.5),
+        "ultra-expanded" => Some(2.0),
+        _ => None,
+    }
+}
+
+fn parse_font_families(string: &str) -> Option<Vec<CssFontFamily>> {
+    let mut families = vec![];
+    let mut rest = string.trim();
+    loop {
+        let quote = rest.chars().next()?;
+        if quote == '"' || quote == '\'' {
+            let end = rest[1..].find(quote)? + 1;
+            families.push(CssFontFamily::Named(rest[1..end].to_owned()));
+            rest = rest[(end + 1)..].trim_start();
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let words: Vec<&str> = rest[0..end].split_whitespace().collect();
+            if words.is_empty() || words.iter().any(|word| !is_css_identifier(word)) {
+                return None;
+            }
+            families.push(match &*words.join(" ").to_ascii_lowercase() {
+                "serif" => CssFontFamily::Serif,
+                "sans-serif" => CssFontFamily::SansSerif,
+                "monospace" => CssFontFamily::Monospace,
+                "cursive" => CssFontFamily::Cursive,
+                "fantasy" => CssFontFamily::Fantasy,
+                "inherit" | "initial" | "unset" | "default" => return None,
+                _ => CssFontFamily::Named(words.join(" ")),
+            });
+            rest = &rest[end..];
+        }
+
+        if rest.is_empty() {
+            return Some(families);
+        }
+        if !rest.starts_with(',') {
+            return None;
+        }
+        rest = rest[1..].trim_start();
+    }
+}
+
+fn is_css_identifier(word: &str) -> bool {
+    let first = word.chars().next().unwrap();
+    !first.is_ascii_digit() && !word.starts_with("--") &&
+        word.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+// The CSS named colors, sorted by name, as 0xRRGGBB.
+static NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
@@ -12,8 +12,9 @@
 
 pub use pathfinder_color::{ColorF, ColorU, rgbaf, rgbau, rgbf, rgbu};
 pub use pathfinder_color::{color_slice_to_u8_slice, u8_slice_to_color_slice, u8_vec_to_color_vec};
+pub use pathfinder_content::effects::BackdropFilter;
 pub use pathfinder_content::fill::FillRule;
-pub use pathfinder_content::stroke::LineCap;
+pub use pathfinder_content::stroke::{LineCap, StrokeAlignment};
 pub use pathfinder_content::outline::ArcDirection;
 pub use pathfinder_geometry::rect::{RectF, RectI};
 pub use pathfinder_geometry::transform2d::Transform2F;
@@ -22,20 +23,25 @@ pub use pathfinder_geometry::vector::{IntoVector2F, Vector2F, Vector2I, vec2f, v
 use pathfinder_content::dash::OutlineDash;
 use pathfinder_content::effects::{BlendMode, BlurDirection, PatternFilter};
 use pathfinder_content::gradient::Gradient;
+use pathfinder_content::mesh::GradientMesh;
 use pathfinder_content::outline::{Contour, Outline};
-use pathfinder_content::pattern::Pattern;
+use pathfinder_content::pattern::{Image, Pattern};
 use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_content::shadow::BlurredRoundedRect;
 use pathfinder_content::stroke::{LineJoin as StrokeLineJoin};
 use pathfinder_content::stroke::{OutlineStrokeToFill, StrokeStyle};
+use pathfinder_content::stroke_cache::{StrokeCache, StrokeCacheKey, StrokeSourceId};
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_renderer::paint::{Paint, PaintCompositeOp};
 use pathfinder_renderer::scene::{ClipPath, ClipPathId, DrawPath, RenderTarget, Scene};
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::default::Default;
 use std::f32::consts::PI;
 use std::f32;
 use std::fmt::{Debug, Error as FmtError, Formatter};
 use std::mem;
+use std::rc::Rc;
 use std::sync::Arc;
 
 pub use text::CanvasFontContext;
@@ -45,6 +51,8 @@ use skribo::FontCollection;
 #[cfg(not(feature = "pf-text"))]
 use crate::text::FontCollection;
 
+#[cfg(feature = "pf-text")]
+pub use pathfinder_text::{FontCache, GlyphRun};
 #[cfg(feature = "pf-text")]
 pub use text::TextMetrics;
 
@@ -69,11 +77,15 @@ mod text {
     pub struct FontCollection;
 }
 
+pub mod css;
+mod widgets;
+
 #[cfg(test)]
 mod tests;
 
 pub struct Canvas {
     scene: Scene,
+    device_pixel_ratio: f32,
 }
 
 impl Canvas {
@@ -86,7 +98,7 @@ impl Canvas {
 
     #[inline]
     pub fn from_scene(scene: Scene) -> Canvas {
-        Canvas { scene }
+        Canvas { scene, device_pixel_ratio: 1.0 }
     }
 
     #[inline]
@@ -101,18 +113,52 @@ impl Canvas {
             canvas_font_context.0.borrow().default_font_collection.clone();
         #[cfg(not(feature = "pf-text"))]
         let default_font_collection = Arc::new(FontCollection);
+        let mut current_state = State::default(default_font_collection);
+        current_state.transform = self.device_transform();
         CanvasRenderingContext2D {
             canvas: self,
-            current_state: State::default(default_font_collection),
+            current_state,
             saved_states: vec![],
             canvas_font_context,
+            stroke_cache: None,
         }
     }
 
+    /// Returns the size of the scene, in device pixels.
     #[inline]
     pub fn size(&self) -> Vector2I {
         self.scene.view_box().size().ceil().to_i32()
     }
+
+    /// Returns the size of the canvas in canvas units, which are device pixels divided by the
+    /// device pixel ratio, like CSS pixels.
+    #[inline]
+    pub fn css_size(&self) -> Vector2F {
+        self.scene.view_box().size() / self.device_pixel_ratio
+    }
+
+    #[inline]
+    pub fn device_pixel_ratio(&self) -> f32 {
+        self.device_pixel_ratio
+    }
+
+    /// Sets how many device pixels make up one canvas unit, like `window.devicePixelRatio` in a
+    /// browser. The ratio may be fractional, such as 1.25 or 1.5.
+    ///
+    /// The scene is resized to have one pixel per device pixel, while its size in canvas units
+    /// stays the same. Contexts made from this canvas afterward draw in canvas units, scaling
+    /// paths, line widths, text, and shadows up to device pixels, so call this before drawing.
+    pub fn set_device_pixel_ratio(&mut self, new_device_pixel_ratio: f32) {
+        let scale = new_device_pixel_ratio / self.device_pixel_ratio;
+        self.scene.set_view_box(self.scene.view_box() * scale);
+        self.device_pixel_ratio = new_device_pixel_ratio;
+    }
+
+    // Maps canvas units to device pixels.
+    #[inline]
+    fn device_transform(&self) -> Transform2F {
+        Transform2F::from_scale(self.device_pixel_ratio)
+    }
 }
 
 pub struct CanvasRenderingContext2D {
@@ -121,6 +167,7 @@ pub struct CanvasRenderingContext2D {
     saved_states: Vec<State>,
     #[allow(dead_code)]
     canvas_font_context: CanvasFontContext,
+    stroke_cache: Option<Rc<RefCell<StrokeCache>>>,
 }
 
 impl CanvasRenderingContext2D {
@@ -140,9 +187,18 @@ impl CanvasRenderingContext2D {
 
     #[inline]
     pub fn fill_rect(&mut self, rect: RectF) {
+        self.fill_rounded_rect_path(rect, 0.0);
+    }
+
+    // Like `fill_path()`, but because the shape is known, a blurred shadow can be drawn
+    // analytically instead of by blurring render targets.
+    pub(crate) fn fill_rounded_rect_path(&mut self, rect: RectF, corner_radius: f32) {
         let mut path = Path2D::new();
-        path.rect(rect);
-        self.fill_path(path, FillRule::Winding);
+        path.round_rect(rect, corner_radius);
+        self.push_path(path.into_outline(),
+                       PathOp::Fill,
+                       FillRule::Winding,
+                       Some((rect, corner_radius)));
     }
 
     #[inline]
@@ -190,6 +246,13 @@ impl CanvasRenderingContext2D {
         self.current_state.miter_limit = new_miter_limit
     }
 
+    /// Strokes closed paths inside or outside their outlines instead of centered on them. This
+    /// is an extension to the HTML canvas API.
+    #[inline]
+    pub fn set_stroke_alignment(&mut self, new_stroke_alignment: StrokeAlignment) {
+        self.current_state.stroke_alignment = new_stroke_alignment
+    }
+
     #[inline]
     pub fn set_line_dash(&mut self, mut new_line_dash: Vec<f32>) {
         // Duplicate and concatenate if an odd number of dashes are present.
@@ -223,12 +286,12 @@ impl CanvasRenderingContext2D {
 
     #[inline]
     pub fn shadow_blur(&self) -> f32 {
-        self.current_state.shadow_blur
+        self.current_state.shadow_blur / self.canvas.device_pixel_ratio
     }
 
     #[inline]
     pub fn set_shadow_blur(&mut self, new_shadow_blur: f32) {
-        self.current_state.shadow_blur = new_shadow_blur;
+        self.current_state.shadow_blur = new_shadow_blur * self.canvas.device_pixel_ratio;
     }
 
     #[inline]
@@ -243,23 +306,97 @@ impl CanvasRenderingContext2D {
 
     #[inline]
     pub fn shadow_offset(&self) -> Vector2F {
-        self.current_state.shadow_offset
+        self.current_state.shadow_offset / self.canvas.device_pixel_ratio
     }
 
     #[inline]
     pub fn set_shadow_offset(&mut self, new_shadow_offset: Vector2F) {
-        self.current_state.shadow_offset = new_shadow_offset;
+        self.current_state.shadow_offset = new_shadow_offset * self.canvas.device_pixel_ratio;
+    }
+
+    // Backdrop filters
+
+    #[inline]
+    pub fn backdrop_filter(&self) -> Option<BackdropFilter> {
+        self.current_state.backdrop_filter
+    }
+
+    /// Filters whatever has already been drawn beneath each path before drawing the path, like
+    /// the CSS `backdrop-filter` property. Filling with a translucent color over a blurred
+    /// backdrop looks like frosted glass. This is an extension to the HTML canvas API.
+    ///
+    /// As with shadows, blurs are measured in canvas pixels regardless of the transform.
+    #[inline]
+    pub fn set_backdrop_filter(&mut self, new_backdrop_filter: Option<BackdropFilter>) {
+        self.current_state.backdrop_filter = new_backdrop_filter;
     }
 
     // Drawing paths
 
     #[inline]
     pub fn fill_path(&mut self, path: Path2D, fill_rule: FillRule) {
-        self.push_path(path.into_outline(), PathOp::Fill, fill_rule);
+        self.push_path(path.into_outline(), PathOp::Fill, fill_rule, None);
     }
 
     #[inline]
     pub fn stroke_path(&mut self, path: Path2D) {
+        let path_id = path.id;
+        let outline = self.stroke_outline(path_id, &path.into_outline());
+        self.push_path(outline, PathOp::Stroke, FillRule::Winding, None);
+    }
+
+    /// Fills and strokes `path` with the current fill and stroke styles, in the given order.
+    ///
+    /// This is equivalent to calling `fill_path()` and `stroke_path()` in sequence, but the path
+    /// is only converted to an outline once.
+    pub fn fill_and_stroke_path(&mut self,
+                                path: Path2D,
+                                fill_rule: FillRule,
+                                paint_order: PaintOrder) {
+        let path_id = path.id;
+        let fill_outline = path.into_outline();
+        let stroke_outline = self.stroke_outline(path_id, &fill_outline);
+        match paint_order {
+            PaintOrder::FillThenStroke => {
+                self.push_path(fill_outline, PathOp::Fill, fill_rule, None);
+                self.push_path(stroke_outline, PathOp::Stroke, FillRule::Winding, None);
+            }
+            PaintOrder::StrokeThenFill => {
+                self.push_path(stroke_outline, PathOp::Stroke, FillRule::Winding, None);
+                self.push_path(fill_outline, PathOp::Fill, fill_rule, None);
+            }
+        }
+    }
+
+    /// Sets the cache that strokes of paths are looked up in and added to, so that paths stroked
+    /// again in later frames with the same line style aren't converted to fills again.
+    ///
+    /// Share one cache between the contexts of successive frames, since each frame usually makes
+    /// a new context. Paths are identified by their `Path2D` object: clones of a path share its
+    /// strokes, and modifying a path makes it a new one.
+    #[inline]
+    pub fn set_stroke_cache(&mut self, new_stroke_cache: Option<Rc<RefCell<StrokeCache>>>) {
+        self.stroke_cache = new_stroke_cache;
+    }
+
+    // Converts `outline`, which is the outline of the path `path_id`, to the outline of its
+    // stroke under the current line style, in untransformed coordinates.
+    fn stroke_outline(&self, path_id: StrokeSourceId, outline: &Outline) -> Outline {
+        let stroke_style = self.resolve_hairline_stroke_style();
+        let stroke_cache = match self.stroke_cache {
+            None => return self.stroke_outline_uncached(outline, stroke_style),
+            Some(ref stroke_cache) => stroke_cache,
+        };
+
+        let key = StrokeCacheKey::new(path_id, &stroke_style)
+                                 .with_dash(&self.current_state.line_dash,
+                                            self.current_state.line_dash_offset);
+        stroke_cache.borrow_mut().get_or_insert_with(key, || {
+            self.stroke_outline_uncached(outline, stroke_style)
+        })
+    }
+
+    fn resolve_hairline_stroke_style(&self) -> StrokeStyle {
         let mut stroke_style = self.current_state.resolve_stroke_style();
 
         // The smaller scale is relevant here, as we multiply by it and want to ensure it is always
@@ -271,21 +408,24 @@ impl CanvasRenderingContext2D {
         if stroke_style.line_width * transform_scale < HAIRLINE_STROKE_WIDTH {
             stroke_style.line_width = HAIRLINE_STROKE_WIDTH / transform_scale;
         }
+        stroke_style
+    }
 
-        let mut outline = path.into_outline();
+    fn stroke_outline_uncached(&self, outline: &Outline, stroke_style: StrokeStyle) -> Outline {
+        let dashed_outline;
+        let mut outline = outline;
         if !self.current_state.line_dash.is_empty() {
-            let mut dash = OutlineDash::new(&outline,
+            let mut dash = OutlineDash::new(outline,
                                             &self.current_state.line_dash,
                                             self.current_state.line_dash_offset);
             dash.dash();
-            outline = dash.into_outline();
+            dashed_outline = dash.into_outline();
+            outline = &dashed_outline;
         }
 
-        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
+        let mut stroke_to_fill = OutlineStrokeToFill::new(outline, stroke_style);
         stroke_to_fill.offset();
-        outline = stroke_to_fill.into_outline();
-
-        self.push_path(outline, PathOp::Stroke, FillRule::Winding);
+        stroke_to_fill.into_outline()
     }
 
     pub fn clip_path(&mut self, path: Path2D, fill_rule: FillRule) {
@@ -299,7 +439,64 @@ impl CanvasRenderingContext2D {
         self.current_state.clip_path = Some(clip_path_id);
     }
 
-    fn push_path(&mut self, mut outline: Outline, path_op: PathOp, fill_rule: FillRule) {
+    // Bounds queries
+
+    /// Returns the device pixels that `fill_path()` would change if it were called with `path`
+    /// now, taking the transform, shadow, clip, and canvas size into account. Nothing is drawn.
+    ///
+    /// Every pixel that the path, its shadow, or its backdrop filter affects is inside the
+    /// result, though for curved paths a few pixels inside the result may be left untouched.
+    /// The result is empty if nothing would be drawn.
+    pub fn fill_path_pixel_bounds(&self, path: &Path2D) -> RectI {
+        self.pixel_bounds_of_outline(path.clone().into_outline())
+    }
+
+    /// Returns the device pixels that `stroke_path()` would change if it were called with `path`
+    /// now, as `fill_path_pixel_bounds()` does for fills.
+    ///
+    /// The path is converted to its stroke under the current line style to compute this, using
+    /// and filling the stroke cache if there is one, so a `stroke_path()` that follows is cheap.
+    pub fn stroke_path_pixel_bounds(&self, path: &Path2D) -> RectI {
+        let outline = self.stroke_outline(path.id, &path.clone().into_outline());
+        self.pixel_bounds_of_outline(outline)
+    }
+
+    // Computes the pixel bounds of drawing `outline`, in untransformed coordinates, with the
+    // current state, as `push_path()` would draw it.
+    fn pixel_bounds_of_outline(&self, mut outline: Outline) -> RectI {
+        if outline.contours().is_empty() {
+            return RectI::default();
+        }
+        outline.transform(&self.current_state.transform);
+        let mut bounds = outline.bounds();
+
+        // Shadows are offset and blurred in device pixels, out to three standard deviations, as
+        // both the analytic and render target paths in `push_path()` draw them.
+        if !self.current_state.shadow_color.is_fully_transparent() {
+            let shadow_bounds = (outline.bounds() + self.current_state.shadow_offset)
+                .dilate(self.current_state.shadow_blur.max(0.0) * 0.5 * 3.0);
+            bounds = bounds.union_rect(shadow_bounds);
+        }
+
+        let mut bounds = bounds.round_out().to_i32();
+        if let Some(clip_path_id) = self.current_state.clip_path {
+            let clip_bounds = self.canvas.scene.get_clip_path(clip_path_id).outline().bounds();
+            bounds = match bounds.intersection(clip_bounds.round_out().to_i32()) {
+                Some(bounds) => bounds,
+                None => return RectI::default(),
+            };
+        }
+        let view_box = self.canvas.scene.view_box().round_out().to_i32();
+        bounds.intersection(view_box).unwrap_or_default()
+    }
+
+    // `shadow_rect`, if present, is a rounded rectangle, as a rectangle and corner radius, that
+    // has the same shape as `outline`.
+    fn push_path(&mut self,
+                 mut outline: Outline,
+                 path_op: PathOp,
+                 fill_rule: FillRule,
+                 shadow_rect: Option<(RectF, f32)>) {
         let paint = self.current_state.resolve_paint(match path_op {
             PathOp::Fill => &self.current_state.fill_paint,
             PathOp::Stroke => &self.current_state.stroke_paint,
@@ -312,7 +509,33 @@ impl CanvasRenderingContext2D {
 
         outline.transform(&transform);
 
-        if !self.current_state.shadow_color.is_fully_transparent() {
+        if let Some(backdrop_filter) = self.current_state.backdrop_filter {
+            self.canvas.scene.push_backdrop_filter(outline.clone(),
+                                                   fill_rule,
+                                                   backdrop_filter,
+                                                   clip_path);
+        }
+
+        let analytic_shadow = match shadow_rect {
+            Some(shadow_rect) if self.current_state.shadow_blur > 0.0 && paint.is_color() => {
+                blurred_rounded_rect_for_shadow(&self.current_state, shadow_rect)
+            }
+            _ => None,
+        };
+
+        if self.current_state.shadow_color.is_fully_transparent() {
+            // No shadow.
+        } else if let Some(shadow) = analytic_shadow {
+            let mut shadow_color = self.current_state.shadow_color.to_f32();
+            shadow_color.set_a(shadow_color.a() * paint.base_color().a as f32 / 255.0);
+            let shadow_paint = Paint::from_blurred_rounded_rect(shadow, shadow_color.to_u8());
+            let shadow_paint_id = self.canvas.scene.push_paint(&shadow_paint);
+
+            let mut path = DrawPath::new(Outline::from_rect(shadow.bounds()), shadow_paint_id);
+            path.set_clip_path(clip_path);
+            path.set_blend_mode(blend_mode);
+            self.canvas.scene.push_path(path);
+        } else {
             let mut outline = outline.clone();
             outline.transform(&Transform2F::from_translation(self.current_state.shadow_offset));
 
@@ -358,6 +581,28 @@ impl CanvasRenderingContext2D {
         path.set_blend_mode(blend_mode);
         self.canvas.scene.push_path(path);
 
+        // The analytic shadow only handles transforms that keep rectangles rectangular: rotation,
+        // uniform scale, and translation.
+        fn blurred_rounded_rect_for_shadow(current_state: &State,
+                                           (rect, corner_radius): (RectF, f32))
+                                           -> Option<BlurredRoundedRect> {
+            let transform = current_state.transform;
+            let (m11, m12, m21, m22) =
+                (transform.m11(), transform.m12(), transform.m21(), transform.m22());
+            let scale_squared = m11 * m22 - m12 * m21;
+            let epsilon = scale_squared.abs() * 1.0e-4;
+            if scale_squared <= 0.0 || (m11 - m22).abs() > epsilon || (m12 + m21).abs() > epsilon {
+                return None;
+            }
+
+            // The blur is specified in canvas pixels, so it's unaffected by the transform.
+            let sigma = current_state.shadow_blur * 0.5 / scale_squared.sqrt();
+            let mut shadow = BlurredRoundedRect::new(rect, corner_radius, sigma);
+            shadow.apply_transform(transform);
+            shadow.apply_transform(Transform2F::from_translation(current_state.shadow_offset));
+            Some(shadow)
+        }
+
         fn push_shadow_blur_render_targets_if_needed(scene: &mut Scene,
                                                      current_state: &State,
                                                      outline_bounds: RectF)
@@ -434,19 +679,32 @@ impl CanvasRenderingContext2D {
         self.current_state.transform *= Transform2F::from_translation(offset)
     }
 
+    /// Returns the current transform, from user space to canvas units. The scaling by the device
+    /// pixel ratio isn't included.
     #[inline]
     pub fn transform(&self) -> Transform2F {
-        self.current_state.transform
+        Transform2F::from_scale(1.0 / self.canvas.device_pixel_ratio) * self.current_state.transform
     }
 
     #[inline]
     pub fn set_transform(&mut self, new_transform: &Transform2F) {
-        self.current_state.transform = *new_transform;
+        self.current_state.transform = self.canvas.device_transform() * *new_transform;
     }
 
     #[inline]
     pub fn reset_transform(&mut self) {
-        self.current_state.transform = Transform2F::default();
+        self.current_state.transform = self.canvas.device_transform();
+    }
+
+    /// Moves `point`, in user space, to the nearest point that the current transform maps to a
+    /// whole number of device pixels.
+    ///
+    /// Snapping the corners of rectangles and the centers of odd-width lines this way keeps them
+    /// crisp when the device pixel ratio is fractional and canvas units don't line up with device
+    /// pixels.
+    pub fn snap_to_device_pixels(&self, point: Vector2F) -> Vector2F {
+        let transform = self.current_state.transform;
+        transform.inverse() * (transform * point).round()
     }
 
     // Compositing
@@ -537,12 +795,7 @@ impl CanvasRenderingContext2D {
                                       -> Pattern {
         let subscene_size = canvas.size();
         let subscene = canvas.into_scene();
-        let render_target = RenderTarget::new(subscene_size, String::new());
-        let render_target_id = self.canvas.scene.push_render_target(render_target);
-        self.canvas.scene.append_scene(subscene);
-        self.canvas.scene.pop_render_target();
-
-        let mut pattern = Pattern::from_render_target(render_target_id, subscene_size);
+        let mut pattern = self.canvas.scene.push_scene_as_pattern(subscene, subscene_size);
         pattern.apply_transform(transform);
         pattern
     }
@@ -550,6 +803,7 @@ impl CanvasRenderingContext2D {
 
 #[derive(Clone)]
 struct State {
+    // Maps user space to device pixels, so it includes the device pixel ratio.
     transform: Transform2F,
     font_collection: Arc<FontCollection>,
     font_size: f32,
@@ -557,13 +811,16 @@ struct State {
     line_cap: LineCap,
     line_join: LineJoin,
     miter_limit: f32,
+    stroke_alignment: StrokeAlignment,
     line_dash: Vec<f32>,
     line_dash_offset: f32,
     fill_paint: Paint,
     stroke_paint: Paint,
     shadow_color: ColorU,
+    // The shadow blur and offset are in device pixels too.
     shadow_blur: f32,
     shadow_offset: Vector2F,
+    backdrop_filter: Option<BackdropFilter>,
     text_align: TextAlign,
     text_baseline: TextBaseline,
     image_smoothing_enabled: bool,
@@ -583,6 +840,7 @@ impl State {
             line_cap: LineCap::Butt,
             line_join: LineJoin::Miter,
             miter_limit: 10.0,
+            stroke_alignment: StrokeAlignment::Center,
             line_dash: vec![],
             line_dash_offset: 0.0,
             fill_paint: Paint::black(),
@@ -590,6 +848,7 @@ impl State {
             shadow_color: ColorU::transparent_black(),
             shadow_blur: 0.0,
             shadow_offset: Vector2F::zero(),
+            backdrop_filter: None,
             text_align: TextAlign::Left,
             text_baseline: TextBaseline::Alphabetic,
             image_smoothing_enabled: true,
@@ -631,9 +890,12 @@ impl State {
             line_cap: self.line_cap,
             line_join: match self.line_join {
                 LineJoin::Miter => StrokeLineJoin::Miter(self.miter_limit),
+                LineJoin::MiterClip => StrokeLineJoin::MiterClip(self.miter_limit),
+                LineJoin::Arcs => StrokeLineJoin::Arcs(self.miter_limit),
                 LineJoin::Bevel => StrokeLineJoin::Bevel,
                 LineJoin::Round => StrokeLineJoin::Round,
             },
+            alignment: self.stroke_alignment,
         }
     }
 }
@@ -642,38 +904,49 @@ impl State {
 pub struct Path2D {
     outline: Outline,
     current_contour: Contour,
+    // Identifies the contents of this path in the stroke cache. Changes whenever the path does.
+    id: StrokeSourceId,
 }
 
 impl Path2D {
     #[inline]
     pub fn new() -> Path2D {
-        Path2D { outline: Outline::new(), current_contour: Contour::new() }
+        Path2D {
+            outline: Outline::new(),
+            current_contour: Contour::new(),
+            id: StrokeSourceId::new(),
+        }
     }
 
     #[inline]
     pub fn close_path(&mut self) {
+        self.id = StrokeSourceId::new();
         self.current_contour.close();
     }
 
     #[inline]
     pub fn move_to(&mut self, to: Vector2F) {
         // TODO(pcwalton): Cull degenerate contours.
+        self.id = StrokeSourceId::new();
         self.flush_current_contour();
         self.current_contour.push_endpoint(to);
     }
 
     #[inline]
     pub fn line_to(&mut self, to: Vector2F) {
+        self.id = StrokeSourceId::new();
         self.current_contour.push_endpoint(to);
     }
 
     #[inline]
     pub fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        self.id = StrokeSourceId::new();
         self.current_contour.push_quadratic(ctrl, to);
     }
 
     #[inline]
     pub fn bezier_curve_to(&mut self, ctrl0: Vector2F, ctrl1: Vector2F, to: Vector2F) {
+        self.id = StrokeSourceId::new();
         self.current_contour.push_cubic(ctrl0, ctrl1, to);
     }
 
@@ -684,6 +957,7 @@ impl Path2D {
                start_angle: f32,
                end_angle: f32,
                direction: ArcDirection) {
+        self.id = StrokeSourceId::new();
         let transform = Transform2F::from_scale(radius).translate(center);
         self.current_contour.push_arc(&transform, start_angle, end_angle, direction);
     }
@@ -691,6 +965,7 @@ impl Path2D {
     #[inline]
     pub fn arc_to(&mut self, ctrl: Vector2F, to: Vector2F, radius: f32) {
         // FIXME(pcwalton): What should we do if there's no initial point?
+        self.id = StrokeSourceId::new();
         let from = self.current_contour.last_position().unwrap_or_default();
         let (v0, v1) = (from - ctrl, to - ctrl);
         let (vu0, vu1) = (v0.normalize(), v1.normalize());
@@ -705,7 +980,25 @@ impl Path2D {
         self.current_contour.push_arc_from_unit_chord(&transform, chord, ArcDirection::CW);
     }
 
+    /// Adds a rectangle with corners rounded to `radius`, which is clamped to half the width and
+    /// height of the rectangle.
+    pub fn round_rect(&mut self, rect: RectF, radius: f32) {
+        let radius = radius.min(rect.width() * 0.5).min(rect.height() * 0.5);
+        if radius <= 0.0 {
+            self.rect(rect);
+            return;
+        }
+
+        self.move_to(vec2f(rect.min_x() + radius, rect.min_y()));
+        self.arc_to(rect.upper_right(), rect.lower_right(), radius);
+        self.arc_to(rect.lower_right(), rect.lower_left(), radius);
+        self.arc_to(rect.lower_left(), rect.origin(), radius);
+        self.arc_to(rect.origin(), rect.upper_right(), radius);
+        self.close_path();
+    }
+
     pub fn rect(&mut self, rect: RectF) {
+        self.id = StrokeSourceId::new();
         self.flush_current_contour();
         self.current_contour.push_endpoint(rect.origin());
         self.current_contour.push_endpoint(rect.upper_right());
@@ -721,6 +1014,7 @@ impl Path2D {
                       start_angle: f32,
                       end_angle: f32)
                       where A: IntoVector2F {
+        self.id = StrokeSourceId::new();
         self.flush_current_contour();
 
         let transform = Transform2F::from_scale(axes).rotate(rotation).translate(center);
@@ -733,6 +1027,7 @@ impl Path2D {
 
     // https://html.spec.whatwg.org/multipage/canvas.html#dom-path2d-addpath
     pub fn add_path(&mut self, mut path: Path2D, transform: &Transform2F) {
+        self.id = StrokeSourceId::new();
         self.flush_current_contour();
         path.flush_current_contour();
         path.outline.transform(transform);
@@ -760,6 +1055,7 @@ pub enum FillStyle {
     Color(ColorU),
     Gradient(Gradient),
     Pattern(Pattern),
+    Mesh(GradientMesh),
 }
 
 impl FillStyle {
@@ -768,6 +1064,7 @@ impl FillStyle {
             FillStyle::Color(color) => Paint::from_color(color),
             FillStyle::Gradient(gradient) => Paint::from_gradient(gradient),
             FillStyle::Pattern(pattern) => Paint::from_pattern(pattern),
+            FillStyle::Mesh(mesh) => Paint::from_mesh(mesh),
         }
     }
 }
@@ -789,6 +1086,22 @@ pub enum TextBaseline {
     Bottom,
 }
 
+/// Whether `fill_and_stroke_path()` draws the stroke on top of the fill, as SVG's
+/// `paint-order: normal` does, or underneath it, which keeps wide strokes from eating into the
+/// fill (useful for outlined text).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaintOrder {
+    FillThenStroke,
+    StrokeThenFill,
+}
+
+impl Default for PaintOrder {
+    #[inline]
+    fn default() -> PaintOrder {
+        PaintOrder::FillThenStroke
+    }
+}
+
 // We duplicate `pathfinder_content::stroke::LineJoin` here because the HTML canvas API treats the
 // miter limit as part of the canvas state, while the native Pathfinder API treats the miter limit
 // as part of the line join. Pathfinder's choice is more logical, because the miter limit is
@@ -797,8 +1110,12 @@ pub enum TextBaseline {
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum LineJoin {
     Miter,
+    /// SVG 2's `miter-clip`, which cuts miters off at the miter limit instead of beveling them.
+    MiterClip,
     Bevel,
     Round,
+    /// SVG 2's `arcs`, which extends the edges of the stroke along their curvature.
+    Arcs,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -889,6 +1206,15 @@ impl CanvasImageSource for Pattern {
     }
 }
 
+impl CanvasImageSource for Image {
+    #[inline]
+    fn to_pattern(self, _: &mut CanvasRenderingContext2D, transform: Transform2F) -> Pattern {
+        let mut pattern = Pattern::from_image(self);
+        pattern.apply_transform(transform);
+        pattern
+    }
+}
+
 impl CanvasImageSource for Canvas {
     #[inline]
     fn to_pattern(self, dest_context: &mut CanvasRenderingContext2D, transform: Transform2F)
@@ -946,6 +1272,13 @@ impl From<Pattern> for FillStyle {
     }
 }
 
+impl From<GradientMesh> for FillStyle {
+    #[inline]
+    fn from(mesh: GradientMesh) -> FillStyle {
+        FillStyle::Mesh(mesh)
+    }
+}
+
 struct ShadowBlurRenderTargetInfo {
     id_x: RenderTargetId,
     id_y: RenderTargetId,
@@ -3,8 +3,18 @@
 // For this file only, any copyright is dedicated to the Public Domain.
 // https://creativecommons.org/publicdomain/zero/1.0/
 
-use pathfinder_geometry::vector::{Vector2F, vec2f};
-use super::Path2D;
+use pathfinder_color::ColorU;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::ColorStop;
+use pathfinder_content::stroke_cache::StrokeCache;
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::vector::{Vector2F, vec2f, vec2i};
+use pathfinder_ui::widgets::{ProgressBar, Theme};
+use std::cell::RefCell;
+use std::rc::Rc;
+use super::css::{CssFontFamily, CssFontStyle, parse_color, parse_color_stops, parse_font};
+use super::{Canvas, CanvasFontContext, CanvasRenderingContext2D, CompositeOperation};
+use super::{ImageSmoothingQuality, PaintOrder, Path2D};
 
 #[test]
 pub fn test_path2d_formatting() {
@@ -17,3 +27,284 @@ pub fn test_path2d_formatting() {
     path.close_path();
     assert_eq!(format!("{:?}", path), "M 0 1 L 2 3 L 4 5 z");
 }
+
+#[test]
+pub fn test_progress_bar_draws_fill_only_when_nonempty() {
+    let theme = Theme::default();
+    let mut progress_bar = ProgressBar::new(RectF::new(Vector2F::zero(), vec2f(100.0, 8.0)),
+                                            &theme);
+
+    let mut context = new_context();
+    progress_bar.draw(&mut context);
+    assert_eq!(context.into_canvas().into_scene().path_count(), 1);
+
+    progress_bar.set_progress(1.5);
+    assert_eq!(progress_bar.progress(), 1.0);
+    let mut context = new_context();
+    progress_bar.draw(&mut context);
+    assert_eq!(context.into_canvas().into_scene().path_count(), 2);
+}
+
+#[test]
+pub fn test_fill_and_stroke_paint_order() {
+    for &paint_order in &[PaintOrder::FillThenStroke, PaintOrder::StrokeThenFill] {
+        let mut context = new_context();
+        context.set_fill_style(ColorU::new(255, 0, 0, 255));
+        context.set_stroke_style(ColorU::new(0, 0, 255, 255));
+        context.set_line_width(2.0);
+        let mut path = Path2D::new();
+        path.rect(RectF::new(vec2f(10.0, 2.0), vec2f(20.0, 4.0)));
+        context.fill_and_stroke_path(path, FillRule::Winding, paint_order);
+
+        let scene = context.into_canvas().into_scene();
+        assert_eq!(scene.path_count(), 2);
+        let first_color = scene.get_paint(scene.get_path(0).paint()).base_color();
+        let expected_first_color = match paint_order {
+            PaintOrder::FillThenStroke => ColorU::new(255, 0, 0, 255),
+            PaintOrder::StrokeThenFill => ColorU::new(0, 0, 255, 255),
+        };
+        assert_eq!(first_color, expected_first_color);
+
+        // The stroke extends past the fill by half the line width.
+        let (fill_index, stroke_index) = match paint_order {
+            PaintOrder::FillThenStroke => (0, 1),
+            PaintOrder::StrokeThenFill => (1, 0),
+        };
+        let fill_bounds = scene.get_path(fill_index).outline().bounds();
+        let stroke_bounds = scene.get_path(stroke_index).outline().bounds();
+        let expected_stroke_bounds = fill_bounds.dilate(1.0);
+        assert!((stroke_bounds.origin() - expected_stroke_bounds.origin()).length() < 0.001);
+        assert!((stroke_bounds.size() - expected_stroke_bounds.size()).length() < 0.001);
+    }
+}
+
+#[test]
+pub fn test_stroke_cache() {
+    let stroke_cache = Rc::new(RefCell::new(StrokeCache::new()));
+    let mut path = Path2D::new();
+    path.rect(RectF::new(vec2f(10.0, 2.0), vec2f(20.0, 4.0)));
+
+    // Each frame makes a new context, but strokes the same path, so it's only stroked once.
+    let mut stroke_bounds = vec![];
+    for &line_width in &[2.0, 2.0, 4.0] {
+        let mut context = new_context();
+        context.set_stroke_cache(Some(stroke_cache.clone()));
+        context.set_line_width(line_width);
+        context.stroke_path(path.clone());
+        stroke_bounds.push(context.into_canvas().into_scene().get_path(0).outline().bounds());
+    }
+    assert_eq!(stroke_bounds[0], stroke_bounds[1]);
+    assert_ne!(stroke_bounds[0], stroke_bounds[2]);
+    assert_eq!(stroke_cache.borrow().len(), 2);
+
+    // Changing the path makes it a different one.
+    path.line_to(vec2f(50.0, 6.0));
+    let mut context = new_context();
+    context.set_stroke_cache(Some(stroke_cache.clone()));
+    context.set_line_width(2.0);
+    context.stroke_path(path);
+    let new_stroke_bounds = context.into_canvas().into_scene().get_path(0).outline().bounds();
+    assert!(new_stroke_bounds.max_x() > 50.0);
+    assert_eq!(stroke_cache.borrow().len(), 3);
+}
+
+#[cfg(feature = "pf-text")]
+#[test]
+pub fn test_shared_font_cache() {
+    use super::FontCache;
+    use std::sync::Arc;
+
+    let cache = FontCache::with_text_run_capacity(8);
+    let (font_context_a, font_context_b) = (CanvasFontContext::from_system_source(),
+                                            CanvasFontContext::from_system_source());
+    assert!(font_context_a.0.borrow().text_run_cache.is_none());
+    font_context_a.set_cache(&cache);
+    font_context_b.set_cache(&cache);
+    for font_context in &[&font_context_a, &font_context_b] {
+        let font_context = font_context.0.borrow();
+        let text_run_cache = font_context.text_run_cache.as_ref().unwrap();
+        assert!(Arc::ptr_eq(text_run_cache, cache.text_run_cache()));
+        assert!(Arc::ptr_eq(font_context.font_context.glyph_outline_cache(),
+                            cache.glyph_outline_cache()));
+    }
+
+    // Settings made through one context apply to all of them.
+    font_context_a.enable_text_run_cache(4);
+    assert_eq!(cache.text_run_cache().capacity(), 4);
+
+    // Disabling the cache in one context leaves it in place for the others.
+    font_context_a.disable_text_run_cache();
+    assert!(font_context_a.0.borrow().text_run_cache.is_none());
+    assert!(font_context_b.0.borrow().text_run_cache.is_some());
+}
+
+#[test]
+pub fn test_parse_css_colors() {
+    assert_eq!(parse_color("#f80"), Some(ColorU::new(255, 136, 0, 255)));
+    assert_eq!(parse_color("#F808"), Some(ColorU::new(255, 136, 0, 136)));
+    assert_eq!(parse_color("#12345678"), Some(ColorU::new(0x12, 0x34, 0x56, 0x78)));
+    assert_eq!(parse_color(" RebeccaPurple "), Some(ColorU::new(102, 51, 153, 255)));
+    assert_eq!(parse_color("transparent"), Some(ColorU::transparent_black()));
+    assert_eq!(parse_color("rgb(255, 0, 128)"), Some(ColorU::new(255, 0, 128, 255)));
+    assert_eq!(parse_color("rgba(300, -5, 50%, 0.5)"), Some(ColorU::new(255, 0, 128, 128)));
+    assert_eq!(parse_color("rgb(10 20 30 / 25%)"), Some(ColorU::new(10, 20, 30, 64)));
+    assert_eq!(parse_color("hsl(120, 100%, 50%)"), Some(ColorU::new(0, 255, 0, 255)));
+    assert_eq!(parse_color("hsla(0.5turn 100% 25% / 1)"), Some(ColorU::new(0, 128, 128, 255)));
+
+    for invalid in &["", "#12345", "#ggg", "rgb(1, 2)", "rgb(1, 2, 3", "rgb(1 2, 3)", "blurple",
+                     "hsl(0, 100, 50%)", "currentcolor"] {
+        assert_eq!(parse_color(invalid), None, "{:?}", invalid);
+    }
+}
+
+#[test]
+pub fn test_parse_css_color_stops() {
+    let (red, blue, green) = (ColorU::new(255, 0, 0, 255),
+                              ColorU::new(0, 0, 255, 255),
+                              ColorU::new(0, 128, 0, 255));
+    assert_eq!(parse_color_stops("red, blue, green").unwrap(), vec![
+        ColorStop::new(red, 0.0),
+        ColorStop::new(blue, 0.5),
+        ColorStop::new(green, 1.0),
+    ]);
+
+    // Hints become midpoints, stops with two positions are doubled, and stops out of order are
+    // moved up to the stops before them.
+    let stops = parse_color_stops("red 20%, 30%, rgb(0 0 255) 40% 60%, green 50%").unwrap();
+    assert_eq!(stops.len(), 4);
+    assert_eq!(stops[0], ColorStop::new(red, 0.2));
+    assert_eq!(stops[1].color, blue);
+    assert_eq!(stops[1].offset, 0.4);
+    assert!((stops[1].midpoint - 0.5).abs() < 0.0001);
+    assert_eq!(stops[2], ColorStop::new(blue, 0.6));
+    assert_eq!(stops[3], ColorStop::new(green, 0.6));
+
+    let stops = parse_color_stops("red, 10%, blue").unwrap();
+    assert!((stops[1].midpoint - 0.1).abs() < 0.0001);
+
+    for invalid in &["", "10%, red, blue", "red, blue, 10%", "red, 10%, 20%, blue",
+                     "red 10px, blue", "red 1% 2% 3%, blue"] {
+        assert_eq!(parse_color_stops(invalid), None, "{:?}", invalid);
+    }
+}
+
+#[test]
+pub fn test_parse_css_fonts() {
+    let font = parse_font("italic bold 14px serif").unwrap();
+    assert_eq!(font.style, CssFontStyle::Italic);
+    assert_eq!(font.weight, 700.0);
+    assert_eq!(font.size, 14.0);
+    assert_eq!(font.families, vec![CssFontFamily::Serif]);
+
+    let font = parse_font("small-caps 300 condensed 12pt/1.5 \"Helvetica Neue\", Arial Black, \
+                           sans-serif").unwrap();
+    assert_eq!(font.style, CssFontStyle::Normal);
+    assert!(font.small_caps);
+    assert_eq!((font.weight, font.stretch, font.size), (300.0, 0.75, 16.0));
+    assert_eq!(font.families, vec![
+        CssFontFamily::Named("Helvetica Neue".to_owned()),
+        CssFontFamily::Named("Arial Black".to_owned()),
+        CssFontFamily::SansSerif,
+    ]);
+
+    let font = parse_font("normal normal 2em / normal 'Times'").unwrap();
+    assert_eq!((font.style, font.weight, font.size), (CssFontStyle::Normal, 400.0, 20.0));
+    assert_eq!(font.families, vec![CssFontFamily::Named("Times".to_owned())]);
+
+    for invalid in &["", "14px", "bold serif", "italic italic 14px serif", "14px serif,",
+                     "14px 'serif", "-2px serif", "14px 3d", "caption"] {
+        assert_eq!(parse_font(invalid), None, "{:?}", invalid);
+    }
+}
+
+#[test]
+pub fn test_css_string_setters() {
+    let mut context = new_context();
+    assert!(context.set_fill_style_str("rgba(0, 0, 255, 0.5)").is_ok());
+    assert!(context.set_fill_style_str("not a color").is_err());
+    assert!(context.set_line_cap_str("round").is_ok());
+    assert!(context.set_line_cap_str("Round").is_err());
+    assert!(context.set_global_composite_operation_str("multiply").is_ok());
+    assert!(context.set_global_composite_operation_str("plus-lighter").is_err());
+    assert_eq!(context.global_composite_operation(), CompositeOperation::Multiply);
+    assert!(context.set_image_smoothing_quality_str("high").is_ok());
+    assert_eq!(context.image_smoothing_quality(), ImageSmoothingQuality::High);
+
+    context.set_global_composite_operation_str("source-over").unwrap();
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 4.0)));
+    let scene = context.into_canvas().into_scene();
+    let color = scene.get_paint(scene.get_path(0).paint()).base_color();
+    assert_eq!(color, ColorU::new(0, 0, 255, 128));
+}
+
+#[test]
+pub fn test_path_pixel_bounds() {
+    let mut context = Canvas::new(vec2f(100.0, 100.0))
+        .get_context_2d(CanvasFontContext::from_system_source());
+    let mut path = Path2D::new();
+    path.rect(RectF::new(vec2f(10.5, 20.0), vec2f(20.0, 10.0)));
+
+    assert_eq!(context.fill_path_pixel_bounds(&path), RectI::new(vec2i(10, 20), vec2i(21, 10)));
+    context.set_line_width(4.0);
+    assert_eq!(context.stroke_path_pixel_bounds(&path), RectI::new(vec2i(8, 18), vec2i(25, 14)));
+
+    // Transparent shadows are never drawn.
+    context.set_shadow_offset(vec2f(5.0, 5.0));
+    assert_eq!(context.fill_path_pixel_bounds(&path), RectI::new(vec2i(10, 20), vec2i(21, 10)));
+    context.set_shadow_color(ColorU::black());
+    assert_eq!(context.fill_path_pixel_bounds(&path), RectI::new(vec2i(10, 20), vec2i(26, 15)));
+    context.set_shadow_blur(2.0);
+    assert_eq!(context.fill_path_pixel_bounds(&path), RectI::new(vec2i(10, 20), vec2i(29, 18)));
+
+    // Bounds are in device space and limited to the clip and the canvas.
+    context.set_shadow_color(ColorU::transparent_black());
+    context.scale(2.0);
+    assert_eq!(context.fill_path_pixel_bounds(&path), RectI::new(vec2i(21, 40), vec2i(40, 20)));
+    let mut clip = Path2D::new();
+    clip.rect(RectF::new(vec2f(0.0, 0.0), vec2f(15.0, 100.0)));
+    context.clip_path(clip, FillRule::Winding);
+    assert_eq!(context.fill_path_pixel_bounds(&path), RectI::new(vec2i(21, 40), vec2i(9, 20)));
+    context.translate(vec2f(40.0, 0.0));
+    assert_eq!(context.fill_path_pixel_bounds(&path), RectI::default());
+    assert_eq!(context.fill_path_pixel_bounds(&Path2D::new()), RectI::default());
+}
+
+#[test]
+pub fn test_device_pixel_ratio() {
+    let mut canvas = Canvas::new(vec2f(100.0, 50.0));
+    canvas.set_device_pixel_ratio(1.5);
+    assert_eq!(canvas.size(), vec2i(150, 75));
+    assert_eq!(canvas.css_size(), vec2f(100.0, 50.0));
+
+    // Drawing happens in canvas units, scaled up to device pixels.
+    let mut context = canvas.get_context_2d(CanvasFontContext::from_system_source());
+    assert!(context.transform().is_identity());
+    let mut path = Path2D::new();
+    path.rect(RectF::new(vec2f(10.0, 20.0), vec2f(20.0, 10.0)));
+    assert_eq!(context.fill_path_pixel_bounds(&path), RectI::new(vec2i(15, 30), vec2i(30, 15)));
+    context.set_line_width(2.0);
+    assert_eq!(context.stroke_path_pixel_bounds(&path), RectI::new(vec2i(13, 28), vec2i(34, 19)));
+
+    context.set_shadow_color(ColorU::black());
+    context.set_shadow_offset(vec2f(4.0, 0.0));
+    assert_eq!(context.shadow_offset(), vec2f(4.0, 0.0));
+    assert_eq!(context.fill_path_pixel_bounds(&path), RectI::new(vec2i(15, 30), vec2i(36, 15)));
+
+    context.translate(vec2f(1.0, 0.0));
+    assert_eq!(context.transform().translation(), vec2f(1.0, 0.0));
+    context.reset_transform();
+    assert!(context.transform().is_identity());
+
+    // With a fractional ratio, whole canvas units aren't whole device pixels.
+    let mut canvas = Canvas::new(vec2f(100.0, 50.0));
+    canvas.set_device_pixel_ratio(1.25);
+    let context = canvas.get_context_2d(CanvasFontContext::from_system_source());
+    let snapped = context.snap_to_device_pixels(vec2f(4.0, 4.0));
+    assert!((snapped - vec2f(4.0, 4.0)).length() < 0.0001);
+    let snapped = context.snap_to_device_pixels(vec2f(1.0, 3.0));
+    assert!((snapped - vec2f(0.8, 3.2)).length() < 0.0001);
+}
+
+fn new_context() -> CanvasRenderingContext2D {
+    Canvas::new(vec2f(100.0, 8.0)).get_context_2d(CanvasFontContext::from_system_source())
+}
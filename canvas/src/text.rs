@@ -9,21 +9,24 @@
 // except according to those terms.
 
 use crate::{CanvasRenderingContext2D, State, TextAlign, TextBaseline};
+use crate::css::{CssFont, CssFontFamily, CssFontStyle};
 use font_kit::canvas::RasterizationOptions;
 use font_kit::family_name::FamilyName;
 use font_kit::handle::Handle;
 use font_kit::hinting::HintingOptions;
 use font_kit::loaders::default::Font;
-use font_kit::properties::Properties;
+use font_kit::properties::{Properties, Stretch, Style, Weight};
 use font_kit::source::{Source, SystemSource};
 use font_kit::sources::mem::MemSource;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::util;
 use pathfinder_geometry::vector::{Vector2F, vec2f};
 use pathfinder_renderer::paint::PaintId;
-use pathfinder_text::{FontContext, FontRenderOptions, TextRenderMode};
+use pathfinder_text::{FontCache, FontContext, FontRenderOptions, GlyphRun, TextRenderMode};
+use pathfinder_text::TextRunCache;
 use skribo::{FontCollection, FontFamily, FontRef, Layout, TextStyle};
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -41,6 +44,24 @@ impl CanvasRenderingContext2D {
         self.fill_or_stroke_text(string, position, paint_id, render_mode);
     }
 
+    /// Fills a run of glyphs that was shaped outside Pathfinder, with its origin at `position`.
+    ///
+    /// Unlike `fill_text()`, this ignores the current font, font size, and text alignment
+    /// settings.
+    pub fn fill_glyph_run(&mut self, run: &GlyphRun<Font>, position: Vector2F) {
+        let paint = self.current_state.resolve_paint(&self.current_state.fill_paint);
+        let paint_id = self.canvas.scene.push_paint(&paint);
+        self.fill_or_stroke_glyph_run(run, position, paint_id, TextRenderMode::Fill);
+    }
+
+    /// Strokes a run of glyphs that was shaped outside Pathfinder, with its origin at `position`.
+    pub fn stroke_glyph_run(&mut self, run: &GlyphRun<Font>, position: Vector2F) {
+        let paint = self.current_state.resolve_paint(&self.current_state.stroke_paint);
+        let paint_id = self.canvas.scene.push_paint(&paint);
+        let render_mode = TextRenderMode::Stroke(self.current_state.resolve_stroke_style());
+        self.fill_or_stroke_glyph_run(run, position, paint_id, render_mode);
+    }
+
     pub fn measure_text(&self, string: &str) -> TextMetrics {
         let mut metrics = self.layout_text(string).metrics();
         metrics.make_origin_relative(&self.current_state);
@@ -76,11 +97,45 @@ impl CanvasRenderingContext2D {
                            mut position: Vector2F,
                            paint_id: PaintId,
                            render_mode: TextRenderMode) {
-        let layout = self.layout_text(string);
-
         let clip_path = self.current_state.clip_path;
         let blend_mode = self.current_state.global_composite_operation.to_blend_mode();
 
+        let mut font_context_data = self.canvas_font_context.0.borrow_mut();
+        let CanvasFontContextData {
+            ref mut font_context,
+            ref text_run_cache,
+            ..
+        } = *font_context_data;
+        if let Some(ref text_run_cache) = *text_run_cache {
+            // TODO(pcwalton): Report errors.
+            let run = match font_context.layout_text_run(text_run_cache,
+                                                         string,
+                                                         &TextStyle {
+                                                             size: self.current_state.font_size
+                                                         },
+                                                         &self.current_state.font_collection) {
+                Ok(run) => run,
+                Err(_) => return,
+            };
+
+            position += run.layout().metrics().text_origin(&self.current_state);
+            let transform = self.current_state.transform * Transform2F::from_translation(position);
+            font_context.push_text_run(&mut self.canvas.scene,
+                                       &run,
+                                       &FontRenderOptions {
+                                           transform,
+                                           render_mode,
+                                           hinting_options: HintingOptions::None,
+                                           clip_path,
+                                           blend_mode,
+                                           paint_id,
+                                       });
+            return;
+        }
+        drop(font_context_data);
+
+        let layout = self.layout_text(string);
+
         position += layout.metrics().text_origin(&self.current_state);
         let transform = self.current_state.transform * Transform2F::from_translation(position);
 
@@ -102,6 +157,32 @@ impl CanvasRenderingContext2D {
                               }));
     }
 
+    fn fill_or_stroke_glyph_run(&mut self,
+                                run: &GlyphRun<Font>,
+                                position: Vector2F,
+                                paint_id: PaintId,
+                                render_mode: TextRenderMode) {
+        let clip_path = self.current_state.clip_path;
+        let blend_mode = self.current_state.global_composite_operation.to_blend_mode();
+        let transform = self.current_state.transform * Transform2F::from_translation(position);
+
+        // TODO(pcwalton): Report errors.
+        drop(self.canvas_font_context
+                 .0
+                 .borrow_mut()
+                 .font_context
+                 .push_glyph_run(&mut self.canvas.scene,
+                                 run,
+                                 &FontRenderOptions {
+                                     transform,
+                                     render_mode,
+                                     hinting_options: HintingOptions::None,
+                                     clip_path,
+                                     blend_mode,
+                                     paint_id,
+                                 }));
+    }
+
     fn layout_text(&self, string: &str) -> Layout {
         skribo::layout(&TextStyle { size: self.current_state.font_size },
                        &self.current_state.font_collection,
@@ -200,14 +281,32 @@ pub struct CanvasFontContext(pub(crate) Rc<RefCell<CanvasFontContextData>>);
 
 pub(super) struct CanvasFontContextData {
     pub(super) font_context: FontContext<Font>,
-    #[allow(dead_code)]
     pub(super) font_source: Arc<dyn Source>,
-    #[allow(dead_code)]
     pub(super) default_font_collection: Arc<FontCollection>,
+    pub(super) text_run_cache: Option<Arc<TextRunCache>>,
 }
 
 impl CanvasFontContext {
     pub fn new(font_source: Arc<dyn Source>) -> CanvasFontContext {
+        CanvasFontContext::with_font_data(font_source, FontContext::new(), None)
+    }
+
+    /// Creates a font context that shapes text and loads glyph outlines through `cache`, which
+    /// other font contexts may share.
+    ///
+    /// Share one cache between all the windows or surfaces of an app, so that text drawn on more
+    /// than one of them is shaped and outlined only once. Text run caching is enabled, with the
+    /// capacity of the shared cache.
+    pub fn with_cache(font_source: Arc<dyn Source>, cache: &FontCache) -> CanvasFontContext {
+        CanvasFontContext::with_font_data(font_source,
+                                          FontContext::with_cache(cache),
+                                          Some(cache.text_run_cache().clone()))
+    }
+
+    fn with_font_data(font_source: Arc<dyn Source>,
+                      font_context: FontContext<Font>,
+                      text_run_cache: Option<Arc<TextRunCache>>)
+                      -> CanvasFontContext {
         let mut default_font_collection = FontCollection::new();
         if let Ok(default_font) = font_source.select_best_match(&[FamilyName::SansSerif],
                                                                 &Properties::new()) {
@@ -219,10 +318,45 @@ impl CanvasFontContext {
         CanvasFontContext(Rc::new(RefCell::new(CanvasFontContextData {
             font_source,
             default_font_collection: Arc::new(default_font_collection),
-            font_context: FontContext::new(),
+            font_context,
+            text_run_cache,
         })))
     }
 
+    /// Enables caching of shaped text, so that strings drawn repeatedly with the same font and
+    /// size skip shaping and outline extraction. At most `capacity` strings are retained.
+    ///
+    /// If the cache is shared with other font contexts, this changes its capacity for all of them.
+    pub fn enable_text_run_cache(&self, capacity: usize) {
+        let mut this = self.0.borrow_mut();
+        match this.text_run_cache {
+            Some(ref text_run_cache) => text_run_cache.set_capacity(capacity),
+            None => this.text_run_cache = Some(Arc::new(TextRunCache::with_capacity(capacity))),
+        }
+    }
+
+    /// Disables the text run cache for this font context. The cache is freed unless other font
+    /// contexts share it.
+    #[inline]
+    pub fn disable_text_run_cache(&self) {
+        self.0.borrow_mut().text_run_cache = None;
+    }
+
+    /// Discards all cached text runs. This must be called if the contents of any font change.
+    pub fn invalidate_text_run_cache(&self) {
+        if let Some(ref text_run_cache) = self.0.borrow().text_run_cache {
+            text_run_cache.clear();
+        }
+    }
+
+    /// Switches this font context over to a cache shared with other font contexts, enabling the
+    /// text run cache if it was disabled. Outlines and runs cached so far are left behind.
+    pub fn set_cache(&self, cache: &FontCache) {
+        let mut this = self.0.borrow_mut();
+        this.font_context.set_glyph_outline_cache(cache.glyph_outline_cache().clone());
+        this.text_run_cache = Some(cache.text_run_cache().clone());
+    }
+
     /// A convenience method to create a font context with the system source.
     /// This allows usage of fonts installed on the system.
     pub fn from_system_source() -> CanvasFontContext {
@@ -234,6 +368,20 @@ impl CanvasFontContext {
         CanvasFontContext::new(Arc::new(MemSource::from_fonts(fonts).unwrap()))
     }
 
+    /// A convenience method to create a font context with fonts read from files as they're
+    /// needed, rather than held in memory.
+    ///
+    /// Each file is read once here, to index its names, and again when its font is first used;
+    /// fonts that are never used don't stay resident. This suits large CJK fonts, of which only a
+    /// few may be needed at a time. For font collections, pass `Handle::from_path()` handles
+    /// with the right index to `from_fonts()` instead.
+    pub fn from_font_files<I, P>(paths: I) -> CanvasFontContext
+                                 where I: IntoIterator<Item = P>, P: Into<PathBuf> {
+        CanvasFontContext::from_fonts(paths.into_iter().map(|path| {
+            Handle::from_path(path.into(), 0)
+        }))
+    }
+
     fn get_font_by_postscript_name(&self, postscript_name: &str) -> Font {
         let this = self.0.borrow();
         if let Some(cached_font) = this.font_context.get_cached_font(postscript_name) {
@@ -245,6 +393,48 @@ impl CanvasFontContext {
             .load()
             .expect("Failed to load the font!")
     }
+
+    // Builds a collection of the families of a CSS font that the font source has, in order of
+    // preference, or returns the default collection if it has none of them.
+    pub(crate) fn font_collection_for_css_font(&self, font: &CssFont) -> Arc<FontCollection> {
+        let this = self.0.borrow();
+        let properties = Properties {
+            style: match font.style {
+                CssFontStyle::Normal => Style::Normal,
+                CssFontStyle::Italic => Style::Italic,
+                CssFontStyle::Oblique => Style::Oblique,
+            },
+            weight: Weight(font.weight),
+            stretch: Stretch(font.stretch),
+        };
+
+        let mut font_collection = FontCollection::new();
+        let mut found_family = false;
+        for family in &font.families {
+            let family_name = match *family {
+                CssFontFamily::Named(ref name) => FamilyName::Title(name.clone()),
+                CssFontFamily::Serif => FamilyName::Serif,
+                CssFontFamily::SansSerif => FamilyName::SansSerif,
+                CssFontFamily::Monospace => FamilyName::Monospace,
+                CssFontFamily::Cursive => FamilyName::Cursive,
+                CssFontFamily::Fantasy => FamilyName::Fantasy,
+            };
+            let handle = match this.font_source.select_best_match(&[family_name], &properties) {
+                Ok(handle) => handle,
+                Err(_) => continue,
+            };
+            if let Ok(loaded_font) = handle.load() {
+                font_collection.add_family(FontFamily::new_from_font(loaded_font));
+                found_family = true;
+            }
+        }
+
+        if found_family {
+            Arc::new(font_collection)
+        } else {
+            this.default_font_collection.clone()
+        }
+    }
 }
 
 // Text layout utilities
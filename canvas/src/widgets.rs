@@ -0,0 +1,56 @@
+// pathfinder/canvas/src/widgets.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Lets the widgets in `pathfinder_ui::widgets` draw to a canvas.
+
+use crate::{ArcDirection, CanvasRenderingContext2D, FillRule, Path2D};
+use pathfinder_color::ColorU;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::Vector2F;
+use pathfinder_ui::widgets::{Shadow, WidgetPainter};
+use std::f32::consts::PI;
+
+impl WidgetPainter for CanvasRenderingContext2D {
+    fn fill_rounded_rect(&mut self,
+                         rect: RectF,
+                         corner_radius: f32,
+                         color: ColorU,
+                         shadow: Option<Shadow>) {
+        self.save();
+        self.set_widget_style(color, shadow);
+        self.fill_rounded_rect_path(rect, corner_radius);
+        self.restore();
+    }
+
+    fn fill_circle(&mut self,
+                   center: Vector2F,
+                   radius: f32,
+                   color: ColorU,
+                   shadow: Option<Shadow>) {
+        let mut path = Path2D::new();
+        path.arc(center, radius, 0.0, PI * 2.0, ArcDirection::CW);
+        path.close_path();
+        self.save();
+        self.set_widget_style(color, shadow);
+        self.fill_path(path, FillRule::Winding);
+        self.restore();
+    }
+}
+
+impl CanvasRenderingContext2D {
+    fn set_widget_style(&mut self, color: ColorU, shadow: Option<Shadow>) {
+        self.set_fill_style(color);
+        if let Some(shadow) = shadow {
+            self.set_shadow_color(shadow.color);
+            self.set_shadow_blur(shadow.blur);
+            self.set_shadow_offset(shadow.offset);
+        }
+    }
+}
@@ -99,6 +99,7 @@ impl Debug for ColorU {
 }
 
 #[derive(Clone, Copy, PartialEq, Default)]
+#[repr(transparent)]
 pub struct ColorF(pub F32x4);
 
 impl ColorF {
@@ -165,7 +166,7 @@ impl ColorF {
 
     #[inline]
     pub fn lerp(&self, other: ColorF, t: f32) -> ColorF {
-        ColorF(self.0 + (other.0 - self.0) * F32x4::splat(t))
+        ColorF(self.0.lerp(other.0, F32x4::splat(t)))
     }
 
     #[inline]
@@ -222,6 +223,16 @@ impl Debug for ColorF {
     }
 }
 
+#[cfg(feature = "pod")]
+unsafe impl bytemuck::Zeroable for ColorU {}
+#[cfg(feature = "pod")]
+unsafe impl bytemuck::Pod for ColorU {}
+
+#[cfg(feature = "pod")]
+unsafe impl bytemuck::Zeroable for ColorF {}
+#[cfg(feature = "pod")]
+unsafe impl bytemuck::Pod for ColorF {}
+
 #[inline]
 pub fn color_slice_to_u8_slice(slice: &[ColorU]) -> &[u8] {
     unsafe {
@@ -243,6 +254,23 @@ pub fn u8_vec_to_color_vec(buffer: Vec<u8>) -> Vec<ColorU> {
     u8_slice_to_color_slice(&buffer).to_vec()
 }
 
+/// Converts RGBA8 pixels with premultiplied alpha, as read back from a framebuffer, to straight
+/// alpha in place.
+///
+/// Panics if the length of `pixels` isn't a multiple of 4.
+pub fn unpremultiply_u8_slice(pixels: &mut [u8]) {
+    assert_eq!(pixels.len() % 4, 0, "RGBA8 pixel data must be a multiple of 4 bytes long!");
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        if alpha == 0 || alpha == 255 {
+            continue;
+        }
+        for channel in &mut pixel[0..3] {
+            *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+        }
+    }
+}
+
 /// A convenience method to construct a `ColorU` from an RGB triple.
 ///
 /// Alpha is set to 255.
@@ -270,3 +298,43 @@ pub fn rgbf(r: f32, g: f32, b: f32) -> ColorF {
 pub fn rgbaf(r: f32, g: f32, b: f32, a: f32) -> ColorF {
     ColorF::new(r, g, b, a)
 }
+
+#[cfg(test)]
+mod test {
+    use super::unpremultiply_u8_slice;
+
+    fn premultiply(channel: u8, alpha: u8) -> u8 {
+        ((channel as u32 * alpha as u32 + 127) / 255) as u8
+    }
+
+    #[test]
+    fn test_unpremultiply_round_trip() {
+        // Premultiplying again must give back exactly what was read.
+        for alpha in 1..=255 {
+            for channel in 0..=255 {
+                let premultiplied = premultiply(channel, alpha);
+                let mut pixel = [premultiplied, premultiplied, premultiplied, alpha];
+                unpremultiply_u8_slice(&mut pixel);
+                assert_eq!(pixel[3], alpha);
+                assert_eq!(premultiply(pixel[0], alpha), premultiplied);
+            }
+        }
+
+        let mut pixels = [0, 64, 128, 128, 10, 20, 30, 255];
+        unpremultiply_u8_slice(&mut pixels);
+        assert_eq!(pixels, [0, 128, 255, 128, 10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn test_unpremultiply_transparent() {
+        let mut pixels = [0, 0, 0, 0, 12, 34, 56, 0];
+        unpremultiply_u8_slice(&mut pixels);
+        assert_eq!(pixels, [0, 0, 0, 0, 12, 34, 56, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "multiple of 4")]
+    fn test_unpremultiply_partial_pixel() {
+        unpremultiply_u8_slice(&mut [255, 255, 255, 255, 255]);
+    }
+}
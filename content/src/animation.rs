@@ -0,0 +1,208 @@
+// pathfinder/content/src/animation.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Animated images, for patterns that change over time.
+
+use crate::pattern::Image;
+use std::fmt::{self, Debug, Formatter};
+use std::ptr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// One frame of an animated image, composited onto the full canvas of the animation.
+#[derive(Clone, Debug)]
+pub struct AnimationFrame {
+    pub image: Image,
+    pub duration: Duration,
+}
+
+/// The frames of an animated image and their timing.
+///
+/// Frames can come from an iterator that produces them on demand, in which case they're pulled
+/// only as playback reaches them. Frames already produced are kept, so later loops don't have to
+/// produce them again. Animations loop forever.
+pub struct AnimatedImage {
+    frames: Mutex<Frames>,
+}
+
+struct Frames {
+    decoded: Vec<AnimationFrame>,
+    // The start time of each decoded frame, relative to the start of the animation.
+    start_times: Vec<Duration>,
+    pending: Option<Box<dyn Iterator<Item = AnimationFrame> + Send>>,
+}
+
+impl AnimatedImage {
+    /// Creates an animated image from frames that are produced as needed.
+    pub fn new<I>(frames: I) -> AnimatedImage
+                  where I: Iterator<Item = AnimationFrame> + Send + 'static {
+        AnimatedImage {
+            frames: Mutex::new(Frames {
+                decoded: vec![],
+                start_times: vec![],
+                pending: Some(Box::new(frames)),
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn from_frames(frames: Vec<AnimationFrame>) -> AnimatedImage {
+        AnimatedImage::new(frames.into_iter())
+    }
+
+    /// Returns the index and contents of the frame that is showing at `time` after the start of
+    /// the animation, or `None` if the animation has no frames.
+    pub fn frame_at(&self, time: Duration) -> Option<(usize, AnimationFrame)> {
+        let mut frames = self.frames.lock().unwrap();
+
+        // Produce frames until one covers `time` or there are no more.
+        while frames.pending.is_some() && frames.end_time() <= time {
+            if !frames.decode_next() {
+                break;
+            }
+        }
+
+        let mut time = time;
+        if frames.pending.is_none() {
+            let total_duration = frames.end_time();
+            if total_duration == Duration::from_secs(0) {
+                // Either there are no frames, or none of them has a duration; show the first.
+                return frames.decoded.first().map(|frame| (0, frame.clone()));
+            }
+            time = Duration::from_nanos((time.as_nanos() % total_duration.as_nanos()) as u64);
+        }
+
+        let index = frames.start_times.iter().rposition(|&start_time| start_time <= time)?;
+        Some((index, frames.decoded[index].clone()))
+    }
+
+    /// The number of frames in the animation, if all of them have been produced.
+    pub fn frame_count(&self) -> Option<usize> {
+        let frames = self.frames.lock().unwrap();
+        match frames.pending {
+            None => Some(frames.decoded.len()),
+            Some(_) => None,
+        }
+    }
+}
+
+impl Frames {
+    fn end_time(&self) -> Duration {
+        match (self.start_times.last(), self.decoded.last()) {
+            (Some(&start_time), Some(frame)) => start_time + frame.duration,
+            _ => Duration::from_secs(0),
+        }
+    }
+
+    // Returns false if there were no more frames.
+    fn decode_next(&mut self) -> bool {
+        let frame = match self.pending.as_mut().and_then(|pending| pending.next()) {
+            Some(frame) => frame,
+            None => {
+                self.pending = None;
+                return false;
+            }
+        };
+        let start_time = self.end_time();
+        self.start_times.push(start_time);
+        self.decoded.push(frame);
+        true
+    }
+}
+
+impl Debug for AnimatedImage {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let frames = self.frames.lock().unwrap();
+        write!(formatter, "(animated image, {} frames decoded)", frames.decoded.len())
+    }
+}
+
+// Animated images are compared by identity, since comparing their frames would mean decoding
+// all of them.
+impl PartialEq for AnimatedImage {
+    #[inline]
+    fn eq(&self, other: &AnimatedImage) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pattern::{Image, Pattern, PatternSource};
+    use pathfinder_color::ColorU;
+    use pathfinder_geometry::vector::vec2i;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use super::{AnimatedImage, AnimationFrame};
+
+    #[test]
+    fn test_frame_timing() {
+        let produced_count = Arc::new(AtomicUsize::new(0));
+        let frames = {
+            let produced_count = produced_count.clone();
+            (0..3).map(move |index| {
+                produced_count.fetch_add(1, Ordering::SeqCst);
+                let color = ColorU::new(index as u8, 0, 0, 255);
+                AnimationFrame {
+                    image: Image::new(vec2i(1, 1), Arc::new(vec![color])),
+                    duration: Duration::from_millis(100 * (index + 1)),
+                }
+            })
+        };
+        let animation = AnimatedImage::new(frames);
+
+        // Frames are produced only as playback reaches them.
+        assert_eq!(animation.frame_at(Duration::from_millis(50)).unwrap().0, 0);
+        assert_eq!(produced_count.load(Ordering::SeqCst), 1);
+        assert_eq!(animation.frame_at(Duration::from_millis(100)).unwrap().0, 1);
+        assert_eq!(animation.frame_count(), None);
+
+        // Frames last 100, 200, and 300 ms, then the animation loops.
+        assert_eq!(animation.frame_at(Duration::from_millis(599)).unwrap().0, 2);
+        assert_eq!(animation.frame_at(Duration::from_millis(650)).unwrap().0, 0);
+        assert_eq!(animation.frame_count(), Some(3));
+        assert_eq!(produced_count.load(Ordering::SeqCst), 3);
+
+        let empty = AnimatedImage::from_frames(vec![]);
+        assert!(empty.frame_at(Duration::from_secs(1)).is_none());
+    }
+
+    #[test]
+    fn test_pattern_advance() {
+        let frames = (0..2).map(|index| {
+            AnimationFrame {
+                image: Image::new(vec2i(1, 1), Arc::new(vec![ColorU::new(index, 0, 0, 255)])),
+                duration: Duration::from_millis(100),
+            }
+        }).collect();
+        let animation = Arc::new(AnimatedImage::from_frames(frames));
+        let mut pattern = Pattern::from_animated_image(animation.clone());
+        let red = |pattern: &Pattern| {
+            match *pattern.source() {
+                PatternSource::Image(ref image) => image.pixels()[0].r,
+                PatternSource::RenderTarget { .. } => unreachable!(),
+            }
+        };
+
+        assert_eq!(red(&pattern), 0);
+        assert!(!pattern.advance(Duration::from_millis(50)));
+        assert!(pattern.advance(Duration::from_millis(150)));
+        assert_eq!(red(&pattern), 1);
+        assert!(pattern.advance(Duration::from_millis(250)));
+        assert_eq!(red(&pattern), 0);
+
+        assert!(Arc::ptr_eq(pattern.animation().unwrap(), &animation));
+
+        // Patterns not made from animations never change.
+        let still_image = animation.frame_at(Duration::from_secs(0)).unwrap().1.image;
+        assert!(!Pattern::from_image(still_image).advance(Duration::from_secs(1)));
+    }
+}
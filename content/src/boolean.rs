@@ -0,0 +1,194 @@
+// pathfinder/content/src/boolean.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Boolean operations on outlines: union, intersection, difference, and exclusive or.
+//!
+//! Both operands are split at every point where they cross, and the result is traced along the
+//! pieces that separate its inside from its outside. Like `Outline::flatten_fill()`, the result
+//! has no overlaps or self-intersections, fills the same way under either fill rule, and has its
+//! curves flattened into lines.
+
+use crate::fill::FillRule;
+use crate::outline::Outline;
+use crate::planar::PlanarMap;
+
+/// How the areas of two outlines are combined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BooleanOp {
+    /// Everything inside either outline.
+    Union,
+    /// Everything inside both outlines.
+    Intersection,
+    /// Everything inside the first outline but not the second.
+    Difference,
+    /// Everything inside exactly one of the outlines.
+    Xor,
+}
+
+impl BooleanOp {
+    /// Returns true if a point inside the first operand or not, per `in_a`, and inside the second
+    /// or not, per `in_b`, is inside the result.
+    #[inline]
+    pub fn contains(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            BooleanOp::Union => in_a || in_b,
+            BooleanOp::Intersection => in_a && in_b,
+            BooleanOp::Difference => in_a && !in_b,
+            BooleanOp::Xor => in_a != in_b,
+        }
+    }
+}
+
+/// Combines the area that `a` covers when filled with `a_fill_rule` with the area that `b`
+/// covers when filled with `b_fill_rule`.
+///
+/// Open contours are implicitly closed, as they are when filled. To combine more than two
+/// outlines, combine the results in turn. Curves are flattened to within a tolerance relative to
+/// the size of each operand, so outlines in any units work alike. If either operand has a point
+/// that isn't finite, the result is empty.
+pub fn combine(a: &Outline,
+               a_fill_rule: FillRule,
+               b: &Outline,
+               b_fill_rule: FillRule,
+               op: BooleanOp)
+               -> Outline {
+    let mut planar_map = PlanarMap::new(2);
+    planar_map.push_outline(a, 0);
+    planar_map.push_outline(b, 1);
+    planar_map.extract(|windings| {
+        op.contains(a_fill_rule.is_filled(windings[0]), b_fill_rule.is_filled(windings[1]))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BooleanOp, combine};
+    use crate::fill::FillRule;
+    use crate::outline::{Contour, Outline};
+    use crate::test_util::{signed_area, square_outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::vec2f;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_overlapping_squares() {
+        let a = square_outline(vec2f(0.0, 0.0), 10.0);
+        let b = square_outline(vec2f(5.0, 5.0), 10.0);
+        let areas = [(BooleanOp::Union, 175.0, 1),
+                     (BooleanOp::Intersection, 25.0, 1),
+                     (BooleanOp::Difference, 75.0, 1),
+                     (BooleanOp::Xor, 150.0, 2)];
+        for &(op, area, contour_count) in &areas {
+            let result = combine(&a, FillRule::Winding, &b, FillRule::Winding, op);
+            assert_eq!(signed_area(&result), area, "{:?}", op);
+            assert_eq!(result.contours().len(), contour_count, "{:?}", op);
+        }
+
+        let intersection = combine(&a, FillRule::Winding, &b, FillRule::Winding,
+                                   BooleanOp::Intersection);
+        assert_eq!(intersection.bounds(), RectF::new(vec2f(5.0, 5.0), vec2f(5.0, 5.0)));
+    }
+
+    #[test]
+    fn test_disjoint_and_nested() {
+        let a = square_outline(vec2f(0.0, 0.0), 10.0);
+        let far = square_outline(vec2f(20.0, 0.0), 10.0);
+        let union = combine(&a, FillRule::Winding, &far, FillRule::Winding, BooleanOp::Union);
+        assert_eq!(union.contours().len(), 2);
+        assert_eq!(signed_area(&union), 200.0);
+        assert!(combine(&a, FillRule::Winding, &far, FillRule::Winding, BooleanOp::Intersection)
+            .contours()
+            .is_empty());
+
+        // Cutting a square out of the middle leaves a hole.
+        let inner = square_outline(vec2f(3.0, 3.0), 4.0);
+        let ring = combine(&a, FillRule::Winding, &inner, FillRule::Winding, BooleanOp::Difference);
+        assert_eq!(ring.contours().len(), 2);
+        assert_eq!(signed_area(&ring), 84.0);
+        assert!(combine(&inner, FillRule::Winding, &a, FillRule::Winding, BooleanOp::Difference)
+            .contours()
+            .is_empty());
+
+        // Empty operands.
+        let empty = Outline::new();
+        let union = combine(&a, FillRule::Winding, &empty, FillRule::Winding, BooleanOp::Union);
+        assert_eq!(signed_area(&union), 100.0);
+        assert!(combine(&empty, FillRule::Winding, &a, FillRule::Winding, BooleanOp::Difference)
+            .contours()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_fill_rules() {
+        // Two coincident squares wind twice, so even-odd leaves nothing of them.
+        let mut a = square_outline(vec2f(0.0, 0.0), 10.0);
+        a.push_contour(Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0))));
+        let b = square_outline(vec2f(5.0, 0.0), 10.0);
+        let union = combine(&a, FillRule::EvenOdd, &b, FillRule::Winding, BooleanOp::Union);
+        assert_eq!(signed_area(&union), 100.0);
+        let union = combine(&a, FillRule::Winding, &b, FillRule::Winding, BooleanOp::Union);
+        assert_eq!(signed_area(&union), 150.0);
+    }
+
+    #[test]
+    fn test_curves() {
+        // A circle with its right half cut away.
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::from_scale(10.0));
+        let mut a = Outline::new();
+        a.push_contour(circle);
+        let b = Outline::from_rect(RectF::new(vec2f(0.0, -20.0), vec2f(20.0, 40.0)));
+        let half = combine(&a, FillRule::Winding, &b, FillRule::Winding, BooleanOp::Difference);
+        assert!((signed_area(&half).abs() - 50.0 * PI).abs() < 1.0);
+        assert!(half.bounds().max_x() <= 0.0);
+    }
+
+    #[test]
+    fn test_scale() {
+        for &scale in &[1.0e-3, 1.0e5] {
+            let a = square_outline(vec2f(0.0, 0.0), 10.0 * scale);
+            let b = square_outline(vec2f(5.0, 5.0) * scale, 10.0 * scale);
+            let areas = [(BooleanOp::Union, 175.0, 1),
+                         (BooleanOp::Intersection, 25.0, 1),
+                         (BooleanOp::Difference, 75.0, 1),
+                         (BooleanOp::Xor, 150.0, 2)];
+            for &(op, area, contour_count) in &areas {
+                let result = combine(&a, FillRule::Winding, &b, FillRule::Winding, op);
+                let expected_area = area * scale * scale;
+                assert!((signed_area(&result) - expected_area).abs() <= expected_area * 1.0e-5,
+                        "{:?} at scale {}",
+                        op,
+                        scale);
+                assert_eq!(result.contours().len(), contour_count, "{:?} at scale {}", op, scale);
+            }
+        }
+    }
+
+    #[test]
+    fn test_non_finite_points() {
+        let a = square_outline(vec2f(0.0, 0.0), 10.0);
+        let mut b = Outline::new();
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(f32::INFINITY, 5.0));
+        contour.push_endpoint(vec2f(5.0, 10.0));
+        contour.close();
+        b.push_contour(contour);
+
+        for &op in &[BooleanOp::Union, BooleanOp::Intersection, BooleanOp::Difference,
+                     BooleanOp::Xor] {
+            let result = combine(&a, FillRule::Winding, &b, FillRule::Winding, op);
+            assert!(result.contours().is_empty(), "{:?}", op);
+            let result = combine(&b, FillRule::Winding, &a, FillRule::Winding, op);
+            assert!(result.contours().is_empty(), "{:?}", op);
+        }
+    }
+}
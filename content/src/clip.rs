@@ -13,8 +13,9 @@ use crate::segment::{CubicSegment, Segment};
 use arrayvec::ArrayVec;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform3d::{NEAR_W_EPSILON, Transform4F};
 use pathfinder_geometry::util::lerp;
-use pathfinder_geometry::vector::{Vector2F, Vector4F};
+use pathfinder_geometry::vector::{Vector2F, Vector4F, vec2f};
 use smallvec::SmallVec;
 use std::fmt::Debug;
 use std::mem;
@@ -301,6 +302,52 @@ impl ContourPolygonClipper {
     }
 }
 
+// Clipping against the near plane of a projective transform
+
+/// Clips a 2D contour to the part that a projective transform keeps in front of the camera.
+///
+/// For points in the z = 0 plane, the transformed w coordinate is an affine function of x and y,
+/// so the region where it exceeds `NEAR_W_EPSILON` is a half-plane in the contour's own space.
+pub(crate) struct ContourNearPlaneClipper {
+    edge: Option<Edge>,
+    contour: Contour,
+}
+
+impl ContourClipper for ContourNearPlaneClipper {
+    type Edge = Edge;
+
+    #[inline]
+    fn contour_mut(&mut self) -> &mut Contour {
+        &mut self.contour
+    }
+}
+
+impl ContourNearPlaneClipper {
+    pub(crate) fn new(transform: &Transform4F, contour: Contour) -> ContourNearPlaneClipper {
+        // w = normal · (x, y) + offset.
+        let normal = vec2f(transform.c0.w(), transform.c1.w());
+        let offset = transform.c3.w();
+        let square_length = normal.square_length();
+        if square_length == 0.0 {
+            // w is the same everywhere, so either everything is visible or nothing is.
+            let contour = if offset > NEAR_W_EPSILON { contour } else { Contour::new() };
+            return ContourNearPlaneClipper { edge: None, contour };
+        }
+
+        // Points on the left of the edge are inside, so run it perpendicular to the normal.
+        let from = normal * ((NEAR_W_EPSILON - offset) / square_length);
+        let to = from + vec2f(normal.y(), -normal.x());
+        ContourNearPlaneClipper { edge: Some(Edge(LineSegment2F::new(from, to))), contour }
+    }
+
+    pub(crate) fn clip(mut self) -> Contour {
+        if let Some(edge) = self.edge {
+            self.clip_against(edge);
+        }
+        self.contour
+    }
+}
+
 #[derive(PartialEq)]
 enum EdgeRelativeLocation {
     Intersecting,
@@ -485,3 +532,38 @@ bitflags! {
         const BOTTOM = 0x08;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{Contour, Outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform3d::{NEAR_W_EPSILON, Transform4F};
+    use pathfinder_geometry::vector::vec2f;
+
+    #[test]
+    fn test_clip_to_near_plane() {
+        // w = 1 - y, so everything below y = 1 - ε is behind the camera.
+        let transform = Transform4F::row_major(
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, -1.0, 0.0, 1.0,
+        );
+        let mut outline = Outline::from_rect(RectF::new(vec2f(-1.0, -1.0), vec2f(2.0, 4.0)));
+        outline.clip_to_near_plane(&transform);
+        let bounds = outline.bounds();
+        assert_eq!(bounds.origin(), vec2f(-1.0, -1.0));
+        assert!((bounds.max_y() - (1.0 - NEAR_W_EPSILON)).abs() < 0.00001);
+        assert!(bounds.max_x() == 1.0);
+
+        let mut hidden = Outline::from_rect(RectF::new(vec2f(0.0, 2.0), vec2f(1.0, 1.0)));
+        hidden.clip_to_near_plane(&transform);
+        assert!(hidden.contours().is_empty());
+
+        // Affine transforms keep everything.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(5.0, 100.0));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline.clip_to_near_plane(&Transform4F::from_uniform_scale(2.0));
+        assert_eq!(outline.contours()[0].points, [vec2f(0.0, 0.0), vec2f(5.0, 100.0)]);
+    }
+}
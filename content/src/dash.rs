@@ -28,6 +28,12 @@ impl<'a> OutlineDash<'a> {
     }
 
     pub fn dash(&mut self) {
+        // As in the HTML canvas, a pattern with no length at all draws a solid line.
+        if self.state.dashes.iter().sum::<f32>() <= 0.0 {
+            self.output = self.input.clone();
+            return;
+        }
+
         for contour in &self.input.contours {
             ContourDash::new(contour, &mut self.output, &mut self.state).dash()
         }
@@ -108,22 +114,32 @@ impl<'a> DashState<'a> {
     fn new(dashes: &'a [f32], mut offset: f32) -> DashState<'a> {
         let total: f32 = dashes.iter().cloned().sum();
         offset %= total;
+        if offset < 0.0 {
+            offset += total;
+        }
 
+        // Find the dash the offset lands in. An offset at the very start of a dash lands in it
+        // even if it's zero-length, so that dot patterns like `[0, 4]` begin with a dot.
         let mut current_dash_index = 0;
         while current_dash_index < dashes.len() {
             let dash = dashes[current_dash_index];
-            if offset < dash {
+            if offset < dash || offset == 0.0 {
                 break;
             }
             offset -= dash;
             current_dash_index += 1;
         }
+        if current_dash_index == dashes.len() {
+            // Rounding error carried the offset past the last dash.
+            current_dash_index = 0;
+            offset = 0.0;
+        }
 
         DashState {
             output: Contour::new(),
             dashes,
             current_dash_index,
-            distance_left: offset,
+            distance_left: dashes.get(current_dash_index).map_or(0.0, |&dash| dash - offset),
         }
     }
 
@@ -132,3 +148,46 @@ impl<'a> DashState<'a> {
         self.current_dash_index % 2 == 0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{Contour, Outline};
+    use pathfinder_geometry::vector::vec2f;
+    use super::OutlineDash;
+
+    fn dash(length: f32, dashes: &[f32], offset: f32) -> Outline {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(length, 0.0));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        let mut dash = OutlineDash::new(&outline, dashes, offset);
+        dash.dash();
+        dash.into_outline()
+    }
+
+    fn dash_extents(outline: &Outline) -> Vec<(f32, f32)> {
+        outline.contours().iter().map(|contour| {
+            (contour.position_of(0).x(), contour.position_of_last(1).x())
+        }).collect()
+    }
+
+    #[test]
+    fn test_dash_phase() {
+        assert_eq!(dash_extents(&dash(20.0, &[5.0, 5.0], 0.0)), vec![(0.0, 5.0), (10.0, 15.0)]);
+        assert_eq!(dash_extents(&dash(20.0, &[5.0, 5.0], 2.0)),
+                   vec![(0.0, 3.0), (8.0, 13.0), (18.0, 20.0)]);
+        assert_eq!(dash_extents(&dash(20.0, &[5.0, 5.0], -2.0)),
+                   vec![(2.0, 7.0), (12.0, 17.0)]);
+    }
+
+    #[test]
+    fn test_zero_length_dashes() {
+        // Zero-length dashes are kept, so that round and square caps can draw them as dots.
+        assert_eq!(dash_extents(&dash(10.0, &[0.0, 4.0], 0.0)),
+                   vec![(0.0, 0.0), (4.0, 4.0), (8.0, 8.0)]);
+
+        // A pattern with no length draws a solid line.
+        assert_eq!(dash_extents(&dash(10.0, &[0.0, 0.0], 0.0)), vec![(0.0, 10.0)]);
+    }
+}
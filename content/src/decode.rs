@@ -0,0 +1,298 @@
+// pathfinder/content/src/decode.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Decoding of encoded image files into images for patterns.
+//!
+//! Each format is decoded only if the Cargo feature of the same name is enabled (`png`, `jpeg`,
+//! `gif`, or `webp`), which turns on the matching decoder in the `image` crate. AVIF files and
+//! animated WebP files are recognized, but the version of `image` that Pathfinder uses can't
+//! decode them yet.
+
+use crate::animation::{AnimatedImage, AnimationFrame};
+use crate::pattern::Image;
+use image::{AnimationDecoder, Frame, Frames, ImageResult};
+use pathfinder_color::ColorU;
+use pathfinder_geometry::vector::vec2i;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::Cursor;
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// An encoded image format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    WebP,
+    Avif,
+}
+
+/// The color space of the decoded pixel values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceColorSpace {
+    /// Pixel values are sRGB-encoded. Nearly all image files are, and patterns expect it, so the
+    /// values are used as is.
+    Srgb,
+    /// Pixel values are linear light, as in some scientific and rendered imagery. They're
+    /// encoded to sRGB, at 16-bit precision for 16-bit images to avoid banding in the shadows.
+    LinearRgb,
+}
+
+/// How the decoded color values relate to alpha.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceAlpha {
+    /// Color values are independent of alpha, as the PNG, JPEG, and WebP formats specify.
+    Straight,
+    /// Color values have already been multiplied by alpha, as in files dumped from
+    /// premultiplied frame buffers. They're divided back out, since images are stored
+    /// non-premultiplied.
+    Premultiplied,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecodeOptions {
+    pub color_space: SourceColorSpace,
+    pub alpha: SourceAlpha,
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The data doesn't start with the signature of any known format.
+    UnknownFormat,
+    /// The format was recognized, but support for it isn't compiled in.
+    UnsupportedFormat(ImageFormat),
+    /// The decoder rejected the data.
+    Malformed(image::ImageError),
+}
+
+impl ImageFormat {
+    /// Identifies the format of encoded image data from its first few bytes.
+    pub fn from_signature(data: &[u8]) -> Option<ImageFormat> {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if data.starts_with(b"\xff\xd8\xff") {
+            Some(ImageFormat::Jpeg)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            Some(ImageFormat::WebP)
+        } else if data.len() >= 12 && &data[4..8] == b"ftyp" &&
+                (&data[8..12] == b"avif" || &data[8..12] == b"avis") {
+            Some(ImageFormat::Avif)
+        } else {
+            None
+        }
+    }
+
+    /// Whether this build of Pathfinder can decode images in this format.
+    pub fn is_supported(self) -> bool {
+        match self {
+            ImageFormat::Png => cfg!(feature = "png"),
+            ImageFormat::Jpeg => cfg!(feature = "jpeg"),
+            ImageFormat::Gif => cfg!(feature = "gif"),
+            ImageFormat::WebP => cfg!(feature = "webp"),
+            ImageFormat::Avif => false,
+        }
+    }
+
+    fn to_image_format(self) -> Option<image::ImageFormat> {
+        match self {
+            ImageFormat::Png => Some(image::ImageFormat::Png),
+            ImageFormat::Jpeg => Some(image::ImageFormat::Jpeg),
+            ImageFormat::Gif => Some(image::ImageFormat::Gif),
+            ImageFormat::WebP => Some(image::ImageFormat::WebP),
+            ImageFormat::Avif => None,
+        }
+    }
+}
+
+impl Default for DecodeOptions {
+    #[inline]
+    fn default() -> DecodeOptions {
+        DecodeOptions { color_space: SourceColorSpace::Srgb, alpha: SourceAlpha::Straight }
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::UnknownFormat => write!(formatter, "unknown image format"),
+            DecodeError::UnsupportedFormat(format) => {
+                write!(formatter, "support for {:?} images isn't enabled", format)
+            }
+            DecodeError::Malformed(ref error) => write!(formatter, "malformed image: {}", error),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Decodes a PNG, JPEG, GIF, or WebP image, detecting the format from the data. Only the first
+/// frame of animated images is decoded.
+#[inline]
+pub fn decode_image(data: &[u8]) -> Result<Image, DecodeError> {
+    decode_image_with_options(data, &DecodeOptions::default())
+}
+
+pub fn decode_image_with_options(data: &[u8], options: &DecodeOptions)
+                                 -> Result<Image, DecodeError> {
+    let format = ImageFormat::from_signature(data).ok_or(DecodeError::UnknownFormat)?;
+    let image_format = match format.to_image_format() {
+        Some(image_format) if format.is_supported() => image_format,
+        _ => return Err(DecodeError::UnsupportedFormat(format)),
+    };
+    let image = image::load_from_memory_with_format(data, image_format)
+                      .map_err(DecodeError::Malformed)?;
+
+    if *options == DecodeOptions::default() {
+        // Fast path: the pixels are already what patterns want.
+        return Ok(Image::from_image_buffer(image.to_rgba()));
+    }
+
+    let image = image.to_rgba16();
+    let size = vec2i(image.width() as i32, image.height() as i32);
+    let pixels = convert_pixels(&image.into_raw(), options);
+    Ok(Image::new(size, Arc::new(pixels)))
+}
+
+/// Decodes an animated GIF or PNG (APNG) image, detecting the format from the data. Other images
+/// become animations of a single frame.
+///
+/// Frames are decoded on a background thread, which only runs ahead of playback by one frame, so
+/// long animations don't have to be decoded before they start playing.
+pub fn decode_animated_image(data: Vec<u8>) -> Result<AnimatedImage, DecodeError> {
+    let format = ImageFormat::from_signature(&data).ok_or(DecodeError::UnknownFormat)?;
+
+    // Read the header here, so that errors are reported to the caller. The decoder itself can't
+    // be sent to the decoding thread, so that thread opens it again.
+    let is_animated = match format {
+        ImageFormat::Gif if format.is_supported() => {
+            image::gif::GifDecoder::new(Cursor::new(&data[..])).map_err(DecodeError::Malformed)?;
+            true
+        }
+        ImageFormat::Png if format.is_supported() => {
+            image::png::PngDecoder::new(Cursor::new(&data[..])).map_err(DecodeError::Malformed)?
+                                                               .is_apng()
+        }
+        _ => false,
+    };
+    if !is_animated {
+        let image = decode_image(&data)?;
+        let duration = Duration::from_secs(0);
+        return Ok(AnimatedImage::from_frames(vec![AnimationFrame { image, duration }]));
+    }
+
+    let (sender, receiver) = mpsc::sync_channel(0);
+    thread::spawn(move || {
+        let frames = match decode_frames(&data, format) {
+            Ok(frames) => frames,
+            Err(_) => return,
+        };
+        for frame in frames {
+            // Playback stops at the first bad frame, as browsers do. Sending fails once the
+            // animation has been dropped.
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(_) => return,
+            };
+            if sender.send(convert_frame(frame)).is_err() {
+                return;
+            }
+        }
+    });
+    Ok(AnimatedImage::new(receiver.into_iter()))
+}
+
+// `format` is GIF or APNG.
+fn decode_frames(data: &[u8], format: ImageFormat) -> ImageResult<Frames<'_>> {
+    let reader = Cursor::new(data);
+    match format {
+        ImageFormat::Gif => Ok(image::gif::GifDecoder::new(reader)?.into_frames()),
+        _ => Ok(image::png::PngDecoder::new(reader)?.apng().into_frames()),
+    }
+}
+
+fn convert_frame(frame: Frame) -> AnimationFrame {
+    let (numerator, denominator) = frame.delay().numer_denom_ms();
+    let duration = if denominator == 0 {
+        Duration::from_secs(0)
+    } else {
+        Duration::from_micros(numerator as u64 * 1000 / denominator as u64)
+    };
+    AnimationFrame { image: Image::from_image_buffer(frame.into_buffer()), duration }
+}
+
+// Converts 16-bit RGBA values, which may be linear and/or premultiplied, to straight sRGB.
+fn convert_pixels(values: &[u16], options: &DecodeOptions) -> Vec<ColorU> {
+    values.chunks(4).map(|pixel| {
+        let alpha = pixel[3] as f32 / 65535.0;
+        let mut color = [0; 3];
+        for (channel, &value) in color.iter_mut().zip(pixel[0..3].iter()) {
+            let mut value = value as f32 / 65535.0;
+            if options.alpha == SourceAlpha::Premultiplied {
+                value = if alpha == 0.0 { 0.0 } else { f32::min(value / alpha, 1.0) };
+            }
+            if options.color_space == SourceColorSpace::LinearRgb {
+                value = linear_to_srgb(value);
+            }
+            *channel = (value * 255.0).round() as u8;
+        }
+        ColorU::new(color[0], color[1], color[2], (alpha * 255.0).round() as u8)
+    }).collect()
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DecodeOptions, ImageFormat, SourceAlpha, SourceColorSpace, convert_pixels};
+    use pathfinder_color::ColorU;
+
+    #[test]
+    fn test_signatures() {
+        assert_eq!(ImageFormat::from_signature(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+                   Some(ImageFormat::Png));
+        assert_eq!(ImageFormat::from_signature(b"\xff\xd8\xff\xe0\0\x10JFIF"),
+                   Some(ImageFormat::Jpeg));
+        assert_eq!(ImageFormat::from_signature(b"RIFF\x24\0\0\0WEBPVP8 "),
+                   Some(ImageFormat::WebP));
+        assert_eq!(ImageFormat::from_signature(b"\0\0\0\x1cftypavif\0\0\0\0"),
+                   Some(ImageFormat::Avif));
+        assert_eq!(ImageFormat::from_signature(b"GIF89a\x01\0"), Some(ImageFormat::Gif));
+        assert_eq!(ImageFormat::from_signature(b"BM6\0\0\0"), None);
+        assert_eq!(ImageFormat::from_signature(b""), None);
+    }
+
+    #[test]
+    fn test_convert_pixels() {
+        // Half-transparent linear mid-gray, premultiplied.
+        let values = [16384, 16384, 16384, 32768, 0, 0, 0, 0];
+        let options = DecodeOptions {
+            color_space: SourceColorSpace::LinearRgb,
+            alpha: SourceAlpha::Premultiplied,
+        };
+        let pixels = convert_pixels(&values, &options);
+        assert_eq!(pixels, vec![ColorU::new(188, 188, 188, 128), ColorU::new(0, 0, 0, 0)]);
+
+        let options = DecodeOptions { alpha: SourceAlpha::Straight, ..options };
+        let pixels = convert_pixels(&values[0..4], &options);
+        assert_eq!(pixels, vec![ColorU::new(137, 137, 137, 128)]);
+    }
+}
@@ -10,10 +10,14 @@
 
 //! Special effects that can be applied to layers.
 
+use crate::util;
+use crate::yuv::{YuvRange, YuvStandard};
 use pathfinder_color::ColorF;
 use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::vector::Vector2F;
 use pathfinder_simd::default::F32x2;
+use std::hash::{Hash, Hasher};
 
 /// This intentionally does not precisely match what Core Graphics does (a
 /// Lanczos function), because we don't want any ringing artefacts.
@@ -47,6 +51,18 @@ pub enum Filter {
         uv_origin: Vector2F,
     },
 
+    /// Computes the coverage of a blurred rounded rectangle analytically.
+    BlurredRoundedRect {
+        /// The rectangle before blurring.
+        rect: RectF,
+        /// The radius of its corners.
+        corner_radius: f32,
+        /// The standard deviation of the blur.
+        sigma: f32,
+        /// The location of the shadow's color in the texture.
+        uv_origin: Vector2F,
+    },
+
     PatternFilter(PatternFilter),
 }
 
@@ -58,6 +74,10 @@ pub enum PatternFilter {
         /// The foreground color of the text.
         fg_color: ColorF,
         /// The background color of the text.
+        ///
+        /// If this is fully transparent and the blend mode is `SrcOver`, the text is composited
+        /// over whatever is already in the destination, using a separate alpha for each color
+        /// channel. Gamma correction isn't applied in that case, since the background is unknown.
         bg_color: ColorF,
         /// The kernel used for defringing, if subpixel AA is enabled.
         defringing_kernel: Option<DefringingKernel>,
@@ -75,10 +95,26 @@ pub enum PatternFilter {
         direction: BlurDirection,
         sigma: f32,
     },
+
+    /// Converts YUV values, stored in the red, green, and blue channels of the pattern, to RGB.
+    /// See `Pattern::from_yuv()`.
+    Yuv {
+        standard: YuvStandard,
+        range: YuvRange,
+    },
 }
 
-/// Blend modes that can be applied to individual paths.
+/// Filters applied to whatever has already been drawn behind a region, as in the CSS
+/// `backdrop-filter` property. Drawing translucent content over a blurred backdrop produces a
+/// "frosted glass" look.
 #[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BackdropFilter {
+    /// A Gaussian blur with the given standard deviation, in device pixels.
+    Blur { sigma: f32 },
+}
+
+/// Blend modes that can be applied to individual paths.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum BlendMode {
     // Porter-Duff, supported by GPU blender
     Clear,
@@ -115,7 +151,7 @@ pub enum BlendMode {
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct DefringingKernel(pub [f32; 4]);
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum BlurDirection {
     X,
     Y,
@@ -203,3 +239,32 @@ impl BlendMode {
         }
     }
 }
+
+impl Hash for PatternFilter {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        match *self {
+            PatternFilter::Text { fg_color, bg_color, defringing_kernel, gamma_correction } => {
+                0u8.hash(state);
+                util::hash_f32x4(fg_color.0, state);
+                util::hash_f32x4(bg_color.0, state);
+                defringing_kernel.is_some().hash(state);
+                if let Some(DefringingKernel(kernel)) = defringing_kernel {
+                    for &value in &kernel {
+                        util::hash_f32(value, state);
+                    }
+                }
+                gamma_correction.hash(state);
+            }
+            PatternFilter::Blur { direction, sigma } => {
+                1u8.hash(state);
+                direction.hash(state);
+                util::hash_f32(sigma, state);
+            }
+            PatternFilter::Yuv { standard, range } => {
+                2u8.hash(state);
+                standard.hash(state);
+                range.hash(state);
+            }
+        }
+    }
+}
@@ -10,8 +10,45 @@
 
 //! Fill rules.
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum FillRule {
     Winding,
     EvenOdd,
+    /// Fills points whose winding number is at least the given value in magnitude, so that, for
+    /// instance, only the areas where two or more contours overlap are drawn.
+    ///
+    /// `WindingAtLeast(1)` is the same as `Winding`. A threshold of 0 is treated as 1.
+    WindingAtLeast(u8),
+}
+
+impl FillRule {
+    /// Returns true if points with the given winding number are inside the filled area.
+    #[inline]
+    pub fn is_filled(self, winding: i32) -> bool {
+        match self {
+            FillRule::Winding => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::WindingAtLeast(threshold) => winding.abs() >= i32::max(threshold as i32, 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FillRule;
+
+    #[test]
+    fn test_is_filled() {
+        assert!(!FillRule::Winding.is_filled(0));
+        assert!(FillRule::Winding.is_filled(-2));
+        assert!(!FillRule::EvenOdd.is_filled(-2));
+        assert!(FillRule::EvenOdd.is_filled(-3));
+        assert!(!FillRule::WindingAtLeast(2).is_filled(1));
+        assert!(FillRule::WindingAtLeast(2).is_filled(-2));
+        assert!(FillRule::WindingAtLeast(2).is_filled(3));
+        for winding in -3..4 {
+            assert_eq!(FillRule::WindingAtLeast(0).is_filled(winding),
+                       FillRule::Winding.is_filled(winding));
+        }
+    }
 }
@@ -18,7 +18,6 @@ use pathfinder_simd::default::F32x2;
 use std::cmp::Ordering;
 use std::convert;
 use std::hash::{Hash, Hasher};
-use std::mem;
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct Gradient {
@@ -30,6 +29,10 @@ pub struct Gradient {
 pub struct ColorStop {
     pub offset: f32,
     pub color: ColorU,
+    /// Where the color halfway between the previous stop's color and this one's falls, as a
+    /// fraction of the distance from the previous stop to this one. This is a CSS color
+    /// interpolation hint. The default, 0.5, interpolates linearly.
+    pub midpoint: f32,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -79,11 +82,9 @@ impl Eq for ColorStop {}
 
 impl Hash for ColorStop {
     fn hash<H>(&self, state: &mut H) where H: Hasher {
-        unsafe {
-            self.color.hash(state);
-            let offset = mem::transmute::<f32, u32>(self.offset);
-            offset.hash(state);
-        }
+        self.color.hash(state);
+        self.offset.to_bits().hash(state);
+        self.midpoint.to_bits().hash(state);
     }
 }
 
@@ -152,10 +153,8 @@ impl Gradient {
             return lower_stop.color;
         }
 
-        lower_stop.color
-                  .to_f32()
-                  .lerp(upper_stop.color.to_f32(), (t - lower_stop.offset) / denom)
-                  .to_u8()
+        let t = apply_midpoint((t - lower_stop.offset) / denom, upper_stop.midpoint);
+        lower_stop.color.to_f32().lerp(upper_stop.color.to_f32(), t).to_u8()
     }
 
     #[inline]
@@ -185,7 +184,28 @@ impl Gradient {
 impl ColorStop {
     #[inline]
     pub fn new(color: ColorU, offset: f32) -> ColorStop {
-        ColorStop { color, offset }
+        ColorStop { color, offset, midpoint: 0.5 }
+    }
+
+    /// Creates a color stop whose transition from the previous stop is eased so that the colors
+    /// mix half-and-half at `midpoint` of the way there.
+    #[inline]
+    pub fn with_midpoint(color: ColorU, offset: f32, midpoint: f32) -> ColorStop {
+        ColorStop { color, offset, midpoint }
+    }
+}
+
+// Eases the fraction `t` of the way between two stops so that it's 0.5 at `midpoint`, as CSS
+// does for color interpolation hints.
+fn apply_midpoint(t: f32, midpoint: f32) -> f32 {
+    if midpoint == 0.5 || t <= 0.0 {
+        t
+    } else if t >= 1.0 || midpoint <= 0.0 {
+        1.0
+    } else if midpoint >= 1.0 {
+        0.0
+    } else {
+        t.powf(0.5f32.ln() / midpoint.ln())
     }
 }
 
@@ -209,7 +229,7 @@ impl RadialGradientLine for Vector2F {
 
 #[cfg(test)]
 mod test {
-    use crate::gradient::Gradient;
+    use crate::gradient::{ColorStop, Gradient};
     use pathfinder_color::ColorU;
     use pathfinder_geometry::vector::Vector2F;
 
@@ -239,4 +259,22 @@ mod test {
             assert!(sample.r == 0, "{} {}", i, sample.r);
         }
     }
+
+    #[test]
+    fn midpoints() {
+        let mut grad = Gradient::linear_from_points(Vector2F::default(), Vector2F::default());
+        grad.add_color_stop(ColorU::new(0, 0, 0, 255), 0.0);
+        grad.add(ColorStop::with_midpoint(ColorU::new(200, 0, 0, 255), 0.5, 0.25));
+        grad.add(ColorStop::with_midpoint(ColorU::new(0, 0, 0, 255), 1.0, 0.0));
+
+        // The colors mix evenly a quarter of the way from the first stop to the second.
+        assert_eq!(grad.sample(0.0).r, 0);
+        assert_eq!(grad.sample(0.125).r, 100);
+        assert!(grad.sample(0.0625).r > 50);
+        assert!(grad.sample(0.25).r < 150);
+        assert_eq!(grad.sample(0.5).r, 200);
+
+        // A midpoint at the previous stop switches colors immediately.
+        assert_eq!(grad.sample(0.51).r, 0);
+    }
 }
@@ -17,18 +17,32 @@ extern crate bitflags;
 #[macro_use]
 extern crate log;
 
+pub mod animation;
+pub mod boolean;
 pub mod clip;
 pub mod dash;
+#[cfg(feature = "pf-image")]
+pub mod decode;
 pub mod effects;
 pub mod fill;
 pub mod gradient;
+pub mod lru;
+pub mod measure;
+pub mod mesh;
 pub mod orientation;
 pub mod outline;
 pub mod pattern;
 pub mod render_target;
 pub mod segment;
+pub mod shadow;
 pub mod stroke;
+pub mod stroke_cache;
 pub mod transform;
+pub mod yuv;
 
 mod dilation;
+mod planar;
 mod util;
+
+#[cfg(test)]
+mod test_util;
@@ -0,0 +1,121 @@
+// pathfinder/content/src/lru.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A map that knows which of its entries was used least recently, for the caches of strokes,
+//! glyph outlines, and text runs.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A hash map that stamps each entry whenever it's inserted or looked up, so that the least
+/// recently used one can be evicted.
+///
+/// The map has no capacity of its own; caches decide when to evict, and by what measure.
+/// Eviction scans every entry, which is fine for the few thousand entries that caches hold.
+pub struct LruMap<K, V> {
+    entries: HashMap<K, LruEntry<V>>,
+    next_access_stamp: u64,
+}
+
+struct LruEntry<V> {
+    value: V,
+    last_access_stamp: u64,
+}
+
+impl<K, V> LruMap<K, V> where K: Clone + Eq + Hash {
+    #[inline]
+    pub fn new() -> LruMap<K, V> {
+        LruMap { entries: HashMap::new(), next_access_stamp: 0 }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the value for `key`, marking it as the most recently used.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let access_stamp = self.next_access_stamp;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_access_stamp = access_stamp;
+        self.next_access_stamp += 1;
+        Some(&entry.value)
+    }
+
+    /// Inserts a value as the most recently used, returning the value that it replaces, if any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let last_access_stamp = self.next_access_stamp;
+        self.next_access_stamp += 1;
+        self.entries.insert(key, LruEntry { value, last_access_stamp }).map(|entry| entry.value)
+    }
+
+    #[inline]
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.entries.remove(key).map(|entry| entry.value)
+    }
+
+    /// Keeps only the entries for which `keep` returns true. This doesn't count as using them.
+    pub fn retain<F>(&mut self, mut keep: F) where F: FnMut(&K, &V) -> bool {
+        self.entries.retain(|key, entry| keep(key, &entry.value));
+    }
+
+    /// Removes and returns the least recently used entry, if the map isn't empty.
+    pub fn pop_least_recently_used(&mut self) -> Option<(K, V)> {
+        let victim = self.entries
+                         .iter()
+                         .min_by_key(|(_, entry)| entry.last_access_stamp)
+                         .map(|(key, _)| (*key).clone())?;
+        let entry = self.entries.remove(&victim).unwrap();
+        Some((victim, entry.value))
+    }
+}
+
+impl<K, V> Default for LruMap<K, V> where K: Clone + Eq + Hash {
+    #[inline]
+    fn default() -> LruMap<K, V> {
+        LruMap::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LruMap;
+
+    #[test]
+    fn test_eviction_order() {
+        let mut map = LruMap::new();
+        for key in 0..4 {
+            assert!(map.insert(key, key * 10).is_none());
+        }
+
+        // Lookups and reinsertions count as uses; misses and retaining don't.
+        assert_eq!(map.get(&0), Some(&0));
+        assert_eq!(map.insert(1, 11), Some(10));
+        assert!(map.get(&4).is_none());
+        map.retain(|_, _| true);
+
+        assert_eq!(map.pop_least_recently_used(), Some((2, 20)));
+        assert_eq!(map.pop_least_recently_used(), Some((3, 30)));
+        assert_eq!(map.pop_least_recently_used(), Some((0, 0)));
+        assert_eq!(map.pop_least_recently_used(), Some((1, 11)));
+        assert!(map.pop_least_recently_used().is_none());
+        assert!(map.is_empty());
+    }
+}
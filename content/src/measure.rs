@@ -0,0 +1,336 @@
+// pathfinder/content/src/measure.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Measuring outlines by arc length, and extracting the parts of them between two distances.
+//!
+//! Trimming is what drives the common "line drawing" animation, in which a stroke appears to be
+//! drawn over time. Measure the outline once, then trim it each frame into the same output
+//! outline, whose storage is reused.
+
+use crate::outline::{Contour, ContourIterFlags, Outline};
+use crate::segment::Segment;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::Vector2F;
+use std::mem;
+
+// How many pieces curves are split into to measure them.
+const CURVE_SAMPLE_COUNT: usize = 16;
+
+// Pieces shorter than this are dropped.
+const EPSILON: f32 = 0.0001;
+
+/// The arc lengths of the segments of an outline.
+pub struct OutlineMeasure {
+    contours: Vec<MeasuredContour>,
+    segments: Vec<MeasuredSegment>,
+    // For each curve, the arc length from its start to each of the points where it was sampled.
+    curve_lengths: Vec<f32>,
+    source: Outline,
+    length: f32,
+}
+
+struct MeasuredContour {
+    // The range of `segments` that belongs to this contour.
+    first_segment: usize,
+    end_segment: usize,
+    distance: f32,
+    length: f32,
+}
+
+struct MeasuredSegment {
+    segment: Segment,
+    // The distance from the start of the outline to the start of this segment.
+    distance: f32,
+    length: f32,
+    // The index of this segment's samples in `curve_lengths`, if it's a curve.
+    curve_lengths: Option<usize>,
+}
+
+impl OutlineMeasure {
+    pub fn new(outline: &Outline) -> OutlineMeasure {
+        let mut measure = OutlineMeasure {
+            contours: vec![],
+            segments: vec![],
+            curve_lengths: vec![],
+            source: outline.clone(),
+            length: 0.0,
+        };
+
+        for contour in &outline.contours {
+            let (first_segment, distance) = (measure.segments.len(), measure.length);
+            for segment in contour.iter(ContourIterFlags::empty()) {
+                let (length, curve_lengths) = if segment.is_line() {
+                    (segment.baseline.vector().length(), None)
+                } else {
+                    let curve_lengths_index = measure.curve_lengths.len();
+                    let (mut length, mut last_point) = (0.0, segment.baseline.from());
+                    for sample_index in 1..(CURVE_SAMPLE_COUNT + 1) {
+                        let t = sample_index as f32 / CURVE_SAMPLE_COUNT as f32;
+                        let point = segment.sample(t);
+                        length += (point - last_point).length();
+                        measure.curve_lengths.push(length);
+                        last_point = point;
+                    }
+                    (length, Some(curve_lengths_index))
+                };
+                measure.segments.push(MeasuredSegment {
+                    segment,
+                    distance: measure.length,
+                    length,
+                    curve_lengths,
+                });
+                measure.length += length;
+            }
+            measure.contours.push(MeasuredContour {
+                first_segment,
+                end_segment: measure.segments.len(),
+                distance,
+                length: measure.length - distance,
+            });
+        }
+
+        measure
+    }
+
+    /// The total length of all contours of the outline.
+    #[inline]
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// Returns the point `distance` along the outline, clamped to its ends, or `None` if the
+    /// outline is empty.
+    pub fn position_at(&self, distance: f32) -> Option<Vector2F> {
+        let segment_index = match self.segments.binary_search_by(|segment| {
+            segment.distance.partial_cmp(&distance).unwrap_or(std::cmp::Ordering::Less)
+        }) {
+            Ok(segment_index) => segment_index,
+            Err(0) => 0,
+            Err(segment_index) => segment_index - 1,
+        };
+        let segment = self.segments.get(segment_index)?;
+        let local_distance = (distance - segment.distance).max(0.0).min(segment.length);
+        Some(segment.segment.sample(self.time_for_distance(segment, local_distance)))
+    }
+
+    /// Replaces the contents of `output` with the part of the outline between the fractions
+    /// `start` and `end` of its length, after moving both ahead by the fraction `offset`, as in
+    /// the "trim path" operation of animation tools.
+    ///
+    /// The contours are treated as one continuous path, in order. A range that runs past the end
+    /// wraps around to the start, so animating `offset` makes a fixed-length piece chase around
+    /// the outline. Contours that are entirely within the range are kept as they are, closed or
+    /// not; the other pieces are open.
+    ///
+    /// The contours already in `output` are cleared and refilled rather than freed, so trimming
+    /// into the same outline every frame doesn't allocate once their storage has grown large
+    /// enough.
+    pub fn trim_into(&self, start: f32, end: f32, offset: f32, output: &mut Outline) {
+        let (start, end) = (start.max(0.0).min(1.0), end.max(0.0).min(1.0));
+        let (start, end) = if start <= end { (start, end) } else { (end, start) };
+
+        let mut writer = TrimWriter {
+            contours: mem::replace(&mut output.contours, vec![]),
+            count: 0,
+        };
+        if end - start >= 1.0 {
+            for contour in &self.source.contours {
+                writer.push_contour(contour);
+            }
+        } else if end > start && self.length > 0.0 {
+            let from = (start + offset).rem_euclid(1.0) * self.length;
+            let to = from + (end - start) * self.length;
+            if to > self.length {
+                self.push_range(&mut writer, 0.0, to - self.length);
+                self.push_range(&mut writer, from, self.length);
+            } else {
+                self.push_range(&mut writer, from, to);
+            }
+        }
+
+        writer.contours.truncate(writer.count);
+        output.contours = writer.contours;
+
+        let mut new_bounds = None;
+        for contour in &mut output.contours {
+            contour.update_bounds(&mut new_bounds);
+        }
+        output.bounds = new_bounds.unwrap_or_else(|| RectF::default());
+    }
+
+    fn push_range(&self, writer: &mut TrimWriter, from: f32, to: f32) {
+        for (contour_index, contour) in self.contours.iter().enumerate() {
+            let (contour_from, contour_to) = (contour.distance, contour.distance + contour.length);
+            if contour_to < from || contour_from > to {
+                continue;
+            }
+            if from <= contour_from && to >= contour_to {
+                writer.push_contour(&self.source.contours[contour_index]);
+                continue;
+            }
+            if from.max(contour_from) + EPSILON >= to.min(contour_to) {
+                continue;
+            }
+
+            let output = writer.begin_contour();
+            for segment in &self.segments[contour.first_segment..contour.end_segment] {
+                let (segment_from, segment_to) = (segment.distance,
+                                                  segment.distance + segment.length);
+                if segment_to <= from || segment_from >= to {
+                    continue;
+                }
+
+                let mut piece = segment.segment;
+                if segment_to > to {
+                    let t = self.time_for_distance(segment, to - segment_from);
+                    piece = piece.split(t).0;
+                }
+                if segment_from < from {
+                    // Find where `from` falls within what's left after the split above.
+                    let t = self.time_for_distance(segment, from - segment_from) /
+                        self.time_for_distance(segment, segment.length.min(to - segment_from));
+                    piece = piece.split(t).1;
+                }
+                push_segment(output, &piece);
+            }
+            writer.end_contour();
+        }
+    }
+
+    fn time_for_distance(&self, segment: &MeasuredSegment, distance: f32) -> f32 {
+        if segment.length <= 0.0 {
+            return 0.0;
+        }
+        let curve_lengths_index = match segment.curve_lengths {
+            None => return (distance / segment.length).max(0.0).min(1.0),
+            Some(curve_lengths_index) => curve_lengths_index,
+        };
+
+        // Interpolate between the samples on either side.
+        let curve_lengths = &self.curve_lengths[curve_lengths_index..(curve_lengths_index +
+                                                                      CURVE_SAMPLE_COUNT)];
+        let mut prev_length = 0.0;
+        for (sample_index, &length) in curve_lengths.iter().enumerate() {
+            if distance <= length {
+                let fraction = if length > prev_length {
+                    (distance - prev_length) / (length - prev_length)
+                } else {
+                    0.0
+                };
+                return (sample_index as f32 + fraction.max(0.0)) / CURVE_SAMPLE_COUNT as f32;
+            }
+            prev_length = length;
+        }
+        1.0
+    }
+}
+
+// Fills in the contours of the output outline, reusing those that are already there.
+struct TrimWriter {
+    contours: Vec<Contour>,
+    count: usize,
+}
+
+impl TrimWriter {
+    fn begin_contour(&mut self) -> &mut Contour {
+        if self.count == self.contours.len() {
+            self.contours.push(Contour::new());
+        }
+        let contour = &mut self.contours[self.count];
+        contour.clear();
+        contour
+    }
+
+    fn end_contour(&mut self) {
+        if !self.contours[self.count].is_empty() {
+            self.count += 1;
+        }
+    }
+
+    fn push_contour(&mut self, source: &Contour) {
+        let contour = self.begin_contour();
+        contour.points.extend_from_slice(&source.points);
+        contour.flags.extend_from_slice(&source.flags);
+        contour.bounds = source.bounds;
+        contour.closed = source.closed;
+        self.end_contour();
+    }
+}
+
+// Appends `segment` to `contour`, which is assumed to end where `segment` starts.
+fn push_segment(contour: &mut Contour, segment: &Segment) {
+    if contour.is_empty() {
+        contour.push_endpoint(segment.baseline.from());
+    }
+    if segment.is_line() {
+        contour.push_endpoint(segment.baseline.to());
+    } else if segment.is_quadratic() {
+        contour.push_quadratic(segment.ctrl.from(), segment.baseline.to());
+    } else {
+        contour.push_cubic(segment.ctrl.from(), segment.ctrl.to(), segment.baseline.to());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::OutlineMeasure;
+    use crate::outline::{Contour, Outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+
+    #[test]
+    fn test_trim() {
+        let square = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 100.0)));
+        let measure = OutlineMeasure::new(&square);
+        assert_eq!(measure.length(), 400.0);
+        assert_eq!(measure.position_at(150.0), Some(vec2f(100.0, 50.0)));
+
+        // The first half runs along the top and right sides.
+        let mut output = Outline::new();
+        measure.trim_into(0.0, 0.5, 0.0, &mut output);
+        assert_eq!(output.contours().len(), 1);
+        assert!(!output.contours()[0].is_closed());
+        assert_eq!(output.bounds(), RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 100.0)));
+        assert_eq!(output.contours()[0].last_position(), Some(vec2f(100.0, 100.0)));
+
+        // A range that wraps around is split in two.
+        measure.trim_into(0.0, 0.25, 0.875, &mut output);
+        assert_eq!(output.contours().len(), 2);
+        assert_eq!(output.contours()[0].position_of(0), vec2f(0.0, 0.0));
+        assert_eq!(output.contours()[0].last_position(), Some(vec2f(50.0, 0.0)));
+        assert_eq!(output.contours()[1].position_of(0), vec2f(0.0, 50.0));
+
+        // The full range keeps the contour closed, and an empty one leaves nothing.
+        measure.trim_into(1.0, 0.0, 0.3, &mut output);
+        assert!(output.contours()[0].is_closed());
+        measure.trim_into(0.5, 0.5, 0.0, &mut output);
+        assert!(output.contours().is_empty());
+    }
+
+    #[test]
+    fn test_curve_length() {
+        // A quarter circle with a radius of 100.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(100.0, 0.0));
+        contour.push_cubic(vec2f(100.0, 55.228), vec2f(55.228, 100.0), vec2f(0.0, 100.0));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let measure = OutlineMeasure::new(&outline);
+        assert!((measure.length() - 157.08).abs() < 0.1);
+
+        let mut output = Outline::new();
+        measure.trim_into(0.25, 0.75, 0.0, &mut output);
+        let (from, to) = (output.contours()[0].position_of(0),
+                          output.contours()[0].last_position().unwrap());
+        assert!((from.length() - 100.0).abs() < 0.1 && (to.length() - 100.0).abs() < 0.1);
+        assert!((from.x() - 92.39).abs() < 0.2 && (to.x() - 38.27).abs() < 0.2);
+    }
+}
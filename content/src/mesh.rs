@@ -0,0 +1,275 @@
+// pathfinder/content/src/mesh.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Gradient meshes built from Coons patches, like PDF type 6 shadings and Illustrator gradient
+//! meshes.
+
+use crate::util;
+use pathfinder_color::{ColorF, ColorU};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_simd::default::F32x4;
+use std::hash::{Hash, Hasher};
+
+// The approximate size of one tessellated cell of a patch, in pixels.
+const CELL_SIZE: f32 = 4.0;
+// The most cells a patch is split into along each parameter.
+const MAX_CELLS_PER_SIDE: u32 = 64;
+
+// Tolerance for pixels exactly on a shared triangle edge, so that adjacent cells don't leave
+// cracks between them.
+const EDGE_EPSILON: f32 = 1.0e-4;
+
+/// A set of Coons patches, painted in order.
+///
+/// Wherever no patch covers a pixel, the mesh is transparent.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct GradientMesh {
+    patches: Vec<CoonsPatch>,
+}
+
+/// A patch bounded by four cubic Bézier curves, with a color at each corner.
+///
+/// The boundary runs through the twelve control points in order, in the same layout as PDF type
+/// 6 shadings: the edges are points 0-3, 3-6, 6-9, and 9-11 back to 0. Points 0, 3, 6, and 9
+/// are the corners.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CoonsPatch {
+    pub points: [Vector2F; 12],
+    /// The colors at points 0, 3, 6, and 9 respectively. Colors inside the patch are bilinearly
+    /// interpolated between these.
+    pub colors: [ColorU; 4],
+}
+
+impl GradientMesh {
+    #[inline]
+    pub fn new() -> GradientMesh {
+        GradientMesh::default()
+    }
+
+    /// Adds a patch, which will be drawn on top of all the patches already added.
+    #[inline]
+    pub fn push_patch(&mut self, patch: CoonsPatch) {
+        self.patches.push(patch);
+    }
+
+    #[inline]
+    pub fn patches(&self) -> &[CoonsPatch] {
+        &self.patches
+    }
+
+    /// The bounds of all the control points, which contain every patch.
+    pub fn bounds(&self) -> RectF {
+        let mut points = self.patches.iter().flat_map(|patch| patch.points.iter().cloned());
+        let first_point = match points.next() {
+            None => return RectF::default(),
+            Some(point) => point,
+        };
+        points.fold(RectF::new(first_point, Vector2F::zero()), |bounds, point| {
+            bounds.union_point(point)
+        })
+    }
+
+    /// A mesh never counts as opaque, since its patches need not cover the path it fills.
+    #[inline]
+    pub fn is_opaque(&self) -> bool {
+        false
+    }
+
+    pub fn is_fully_transparent(&self) -> bool {
+        self.patches.iter().all(|patch| patch.colors.iter().all(ColorU::is_fully_transparent))
+    }
+
+    pub fn apply_transform(&mut self, transform: Transform2F) {
+        for patch in &mut self.patches {
+            for point in &mut patch.points {
+                *point = transform * *point;
+            }
+        }
+    }
+
+    /// Renders the mesh through `transform` into a `size`-pixel image of non-premultiplied
+    /// RGBA, as an `Image` expects.
+    ///
+    /// Each patch is tessellated into a grid of cells fine enough to follow its curved edges, and
+    /// each cell is filled as two Gouraud-shaded triangles.
+    pub fn rasterize(&self, size: Vector2I, transform: &Transform2F) -> Vec<ColorU> {
+        let mut pixels = vec![ColorU::transparent_black(); size.x() as usize * size.y() as usize];
+        for patch in &self.patches {
+            let mut points = patch.points;
+            for point in &mut points {
+                *point = *transform * *point;
+            }
+            let patch = CoonsPatch { points, colors: patch.colors };
+            patch.rasterize(&mut pixels, size);
+        }
+        pixels
+    }
+}
+
+impl Eq for GradientMesh {}
+
+impl Hash for GradientMesh {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.patches.len().hash(state);
+        for patch in &self.patches {
+            for point in &patch.points {
+                util::hash_f32x2(point.0, state);
+            }
+            patch.colors.hash(state);
+        }
+    }
+}
+
+impl CoonsPatch {
+    /// Returns the point at parameters `(u, v)`, each between 0 and 1.
+    ///
+    /// `u` runs along the edge from point 0 to point 3, and `v` along the edge from point 0 to
+    /// point 9.
+    pub fn sample(&self, u: f32, v: f32) -> Vector2F {
+        let p = &self.points;
+        let top = cubic(p[0], p[1], p[2], p[3], u);
+        let bottom = cubic(p[9], p[8], p[7], p[6], u);
+        let left = cubic(p[0], p[11], p[10], p[9], v);
+        let right = cubic(p[3], p[4], p[5], p[6], v);
+        let corners = p[0] * ((1.0 - u) * (1.0 - v)) + p[3] * (u * (1.0 - v)) +
+            p[6] * (u * v) + p[9] * ((1.0 - u) * v);
+        top * (1.0 - v) + bottom * v + left * (1.0 - u) + right * u - corners
+    }
+
+    /// Returns the color at parameters `(u, v)`.
+    pub fn sample_color(&self, u: f32, v: f32) -> ColorF {
+        let top = self.colors[0].to_f32().lerp(self.colors[1].to_f32(), u);
+        let bottom = self.colors[3].to_f32().lerp(self.colors[2].to_f32(), u);
+        top.lerp(bottom, v)
+    }
+
+    fn rasterize(&self, pixels: &mut [ColorU], size: Vector2I) {
+        // Split finely enough that each cell is a few pixels across.
+        let edge_length = self.points.iter()
+                                     .zip(self.points.iter().cycle().skip(1))
+                                     .map(|(&from, &to)| (to - from).length())
+                                     .fold(0.0, f32::max);
+        let cells_per_side = ((edge_length * 3.0 / CELL_SIZE).ceil() as u32).max(1)
+                                                                          .min(MAX_CELLS_PER_SIDE);
+
+        // Later cells are drawn on top, so where a patch folds over itself the larger
+        // parameter values win, as PDF specifies.
+        let step = 1.0 / cells_per_side as f32;
+        let mut previous_row: Vec<(Vector2F, ColorF)> = (0..=cells_per_side).map(|x| {
+            let u = x as f32 * step;
+            (self.sample(u, 0.0), self.sample_color(u, 0.0))
+        }).collect();
+        for y in 1..=cells_per_side {
+            let v = y as f32 * step;
+            let row: Vec<(Vector2F, ColorF)> = (0..=cells_per_side).map(|x| {
+                let u = x as f32 * step;
+                (self.sample(u, v), self.sample_color(u, v))
+            }).collect();
+            for x in 0..(cells_per_side as usize) {
+                let (a, b) = (previous_row[x], previous_row[x + 1]);
+                let (c, d) = (row[x + 1], row[x]);
+                fill_triangle(pixels, size, [a, b, c]);
+                fill_triangle(pixels, size, [a, c, d]);
+            }
+            previous_row = row;
+        }
+    }
+}
+
+fn cubic(p0: Vector2F, p1: Vector2F, p2: Vector2F, p3: Vector2F, t: f32) -> Vector2F {
+    let s = 1.0 - t;
+    p0 * (s * s * s) + p1 * (3.0 * s * s * t) + p2 * (3.0 * s * t * t) + p3 * (t * t * t)
+}
+
+fn fill_triangle(pixels: &mut [ColorU], size: Vector2I, vertices: [(Vector2F, ColorF); 3]) {
+    let [(a, color_a), (b, color_b), (c, color_c)] = vertices;
+    let area = (b - a).det(c - a);
+    if area.abs() < EDGE_EPSILON {
+        return;
+    }
+
+    let min = a.min(b).min(c).floor().to_i32().max(Vector2I::zero());
+    let max = a.max(b).max(c).ceil().to_i32().min(size);
+    for y in min.y()..max.y() {
+        for x in min.x()..max.x() {
+            let point = Vector2I::new(x, y).to_f32() + Vector2F::splat(0.5);
+            let weight_a = (c - b).det(point - b) / area;
+            let weight_b = (a - c).det(point - c) / area;
+            let weight_c = 1.0 - weight_a - weight_b;
+            if weight_a < -EDGE_EPSILON || weight_b < -EDGE_EPSILON || weight_c < -EDGE_EPSILON {
+                continue;
+            }
+
+            let color = color_a.0 * weight_a + color_b.0 * weight_b + color_c.0 * weight_c;
+            let color = ColorF(color.clamp(F32x4::default(), F32x4::splat(1.0)));
+            pixels[(y * size.x() + x) as usize] = color.to_u8();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CoonsPatch, GradientMesh};
+    use pathfinder_color::ColorU;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, vec2f, vec2i};
+
+    // A patch whose edges are straight lines around the square from (0, 0) to (8, 8).
+    fn square_patch() -> CoonsPatch {
+        let corners = [vec2f(0.0, 0.0), vec2f(8.0, 0.0), vec2f(8.0, 8.0), vec2f(0.0, 8.0)];
+        let mut points = [Vector2F::zero(); 12];
+        for side in 0..4 {
+            let (from, to) = (corners[side], corners[(side + 1) % 4]);
+            for step in 0..3 {
+                points[side * 3 + step] = from.lerp(to, step as f32 / 3.0);
+            }
+        }
+        CoonsPatch {
+            points,
+            colors: [ColorU::black(), ColorU::new(255, 0, 0, 255), ColorU::white(),
+                     ColorU::new(0, 0, 255, 255)],
+        }
+    }
+
+    #[test]
+    fn test_sample() {
+        let patch = square_patch();
+        assert_eq!(patch.sample(0.0, 0.0), vec2f(0.0, 0.0));
+        assert_eq!(patch.sample(1.0, 0.0), vec2f(8.0, 0.0));
+        assert_eq!(patch.sample(1.0, 1.0), vec2f(8.0, 8.0));
+        assert!((patch.sample(0.5, 0.5) - vec2f(4.0, 4.0)).length() < 0.001);
+        assert_eq!(patch.sample_color(1.0, 0.0).to_u8(), ColorU::new(255, 0, 0, 255));
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let mut mesh = GradientMesh::new();
+        mesh.push_patch(square_patch());
+        assert_eq!(mesh.bounds().size(), vec2f(8.0, 8.0));
+
+        // Render into a 10x10 image with a one-pixel margin.
+        let pixels = mesh.rasterize(vec2i(10, 10), &Transform2F::from_translation(vec2f(1.0, 1.0)));
+        assert_eq!(pixels[0], ColorU::transparent_black());
+        assert_eq!(pixels[99], ColorU::transparent_black());
+        for y in 1..9 {
+            for x in 1..9 {
+                assert_eq!(pixels[y * 10 + x].a, 255);
+            }
+        }
+
+        // Red increases to the right, blue downwards.
+        let (top_left, top_right) = (pixels[1 * 10 + 1], pixels[1 * 10 + 8]);
+        let bottom_left = pixels[8 * 10 + 1];
+        assert!(top_right.r > top_left.r + 128);
+        assert!(bottom_left.b > top_left.b + 128);
+    }
+}
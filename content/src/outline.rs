@@ -10,18 +10,24 @@
 
 //! A compressed in-memory representation of paths.
 
-use crate::clip::{self, ContourPolygonClipper};
+use crate::clip::{self, ContourNearPlaneClipper, ContourPolygonClipper};
 use crate::dilation::ContourDilator;
+use crate::fill::FillRule;
+use crate::measure::OutlineMeasure;
 use crate::orientation::Orientation;
+use crate::planar::{self, PlanarMap};
 use crate::segment::{Segment, SegmentFlags, SegmentKind};
+use crate::util;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::transform3d::Perspective;
+use pathfinder_geometry::transform3d::{Perspective, Transform4F};
 use pathfinder_geometry::unit_vector::UnitVector;
 use pathfinder_geometry::vector::{Vector2F, vec2f};
+use pathfinder_simd::default::F32x4;
 use std::f32::consts::PI;
 use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
 use std::mem;
 
 #[derive(Clone)]
@@ -178,6 +184,21 @@ impl Outline {
         self.bounds = new_bounds.unwrap_or_else(|| RectF::default());
     }
 
+    /// Removes the parts of this outline that `transform` would put behind the camera, so that
+    /// applying the perspective afterward can't produce infinite or reflected points.
+    ///
+    /// Contours should be closed first if they're going to be filled.
+    pub fn clip_to_near_plane(&mut self, transform: &Transform4F) {
+        self.bounds = RectF::default();
+        for contour in mem::replace(&mut self.contours, vec![]) {
+            self.push_contour(ContourNearPlaneClipper::new(transform, contour).clip());
+        }
+    }
+
+    /// Projects every point of this outline.
+    ///
+    /// Points behind the camera project to meaningless positions, so call `clip_to_near_plane()`
+    /// first unless the outline is known to be in front of the camera.
     pub fn apply_perspective(&mut self, perspective: &Perspective) {
         let mut new_bounds = None;
         for contour in &mut self.contours {
@@ -218,6 +239,83 @@ impl Outline {
     pub fn close_all_contours(&mut self) {
         self.contours.iter_mut().for_each(|contour| contour.close());
     }
+
+    /// Returns an outline that covers the same area as this one filled with `fill_rule`, but
+    /// whose contours neither overlap nor intersect themselves or each other. This is the
+    /// "remove overlaps" or "expand" operation of design tools.
+    ///
+    /// Outer contours of the result wind clockwise and holes anticlockwise, so it fills the same
+    /// way under either fill rule. Curves are flattened into lines, to within a tolerance relative
+    /// to the size of the outline. If any point of the outline isn't finite, the result is empty.
+    pub fn flatten_fill(&self, fill_rule: FillRule) -> Outline {
+        let mut planar_map = PlanarMap::new(1);
+        planar_map.push_outline(self, 0);
+        planar_map.extract(|windings| fill_rule.is_filled(windings[0]))
+    }
+
+    /// Returns an outline that covers everything inside `bounds` that this outline, filled with
+    /// `fill_rule`, doesn't. This is the "inverse fill" of PDF and CAD formats.
+    ///
+    /// Like `flatten_fill()`, the result fills the same way under either fill rule, curves are
+    /// flattened into lines, and the result is empty if any point isn't finite.
+    pub fn inverse_fill(&self, fill_rule: FillRule, bounds: RectF) -> Outline {
+        let mut planar_map = PlanarMap::new(2);
+        planar_map.push_outline(self, 0);
+        planar_map.push_outline(&Outline::from_rect(bounds), 1);
+        planar_map.extract(|windings| windings[1] != 0 && !fill_rule.is_filled(windings[0]))
+    }
+
+    /// Returns the area that this outline covers when filled with `fill_rule`.
+    ///
+    /// If no contour crosses or touches itself or another, as in well-formed glyphs and most
+    /// imported shapes, curves are accounted for exactly. Otherwise the contours are untangled
+    /// as in `flatten_fill()` first, which flattens curves, so the result is only accurate to
+    /// within a small fraction of the size of the outline along the boundary. Outlines with
+    /// non-finite points have no area.
+    pub fn area(&self, fill_rule: FillRule) -> f32 {
+        let mut planar_map = PlanarMap::new(1);
+        planar_map.push_outline(self, 0);
+        if !planar_map.is_simple() {
+            let flattened = self.flatten_fill(fill_rule);
+            return flattened.contours.iter().map(Contour::signed_area).sum();
+        }
+
+        // Every contour now bounds a region nested inside some others and around some others.
+        // Its winding number steps by one across the contour, so each contour adds or removes
+        // its whole area depending on whether the fill rule counts the winding numbers on either
+        // side of it.
+        let tolerance = planar::flattening_tolerance(self.bounds);
+        let polygons: Vec<_> = self.contours.iter().map(|contour| {
+            planar::flatten_contour(contour, tolerance)
+        }).collect();
+        let mut area = 0.0;
+        for (contour_index, contour) in self.contours.iter().enumerate() {
+            let contour_area = contour.signed_area();
+            if contour_area == 0.0 {
+                continue;
+            }
+            let point = contour.position_of(0);
+            let outside: i32 = polygons.iter().enumerate().filter(|&(polygon_index, _)| {
+                polygon_index != contour_index
+            }).map(|(_, polygon)| planar::winding_number(polygon, point)).sum();
+            let inside = outside + if contour_area > 0.0 { 1 } else { -1 };
+            match (fill_rule.is_filled(inside), fill_rule.is_filled(outside)) {
+                (true, false) => area += contour_area.abs(),
+                (false, true) => area -= contour_area.abs(),
+                _ => {}
+            }
+        }
+        area
+    }
+
+    /// Returns the part of this outline between the fractions `start` and `end` of its length.
+    /// See `OutlineMeasure::trim_into()`, which should be used instead when trimming the same
+    /// outline repeatedly, as in an animation.
+    pub fn trim(&self, start: f32, end: f32, offset: f32) -> Outline {
+        let mut outline = Outline::new();
+        OutlineMeasure::new(self).trim_into(start, end, offset, &mut outline);
+        outline
+    }
 }
 
 impl Debug for Outline {
@@ -232,6 +330,13 @@ impl Debug for Outline {
     }
 }
 
+// The bounds are derived from the points, so they aren't hashed.
+impl Hash for Outline {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.contours.hash(state);
+    }
+}
+
 impl Contour {
     #[inline]
     pub fn new() -> Contour {
@@ -318,11 +423,54 @@ impl Contour {
         self.closed
     }
 
+    /// Returns the area enclosed by this contour, exactly, curves included. It's positive if the
+    /// contour winds clockwise with Y pointing down (anticlockwise with Y pointing up) and
+    /// negative otherwise. Open contours are treated as closed, as they are when filled.
+    ///
+    /// Where the contour crosses itself, loops winding opposite ways count with opposite signs.
+    pub fn signed_area(&self) -> f32 {
+        // Work relative to the first point, in double precision, so that contours far from the
+        // origin don't lose their area to rounding. The closing line then contributes nothing.
+        let origin = match self.points.first() {
+            None => return 0.0,
+            Some(&origin) => origin.to_f64(),
+        };
+        let mut twice_area = 0.0;
+        for segment in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            let from = segment.baseline.from().to_f64() - origin;
+            let to = segment.baseline.to().to_f64() - origin;
+            if segment.is_line() {
+                twice_area += from.det(to);
+                continue;
+            }
+
+            // Green's theorem over the cubic Bézier curve, which is exact for quadratics too.
+            let ctrl = segment.to_cubic().ctrl;
+            let (ctrl0, ctrl1) = (ctrl.from().to_f64() - origin, ctrl.to().to_f64() - origin);
+            twice_area += (6.0 * from.det(ctrl0) + 3.0 * from.det(ctrl1) + from.det(to) +
+                           3.0 * ctrl0.det(ctrl1) + 3.0 * ctrl0.det(to) +
+                           6.0 * ctrl1.det(to)) / 10.0;
+        }
+        (twice_area * 0.5) as f32
+    }
+
+    /// Returns true if this contour winds clockwise with Y pointing down, which is anticlockwise
+    /// with Y pointing up. See `signed_area()`.
+    #[inline]
+    pub fn is_clockwise(&self) -> bool {
+        self.signed_area() > 0.0
+    }
+
     #[inline]
     pub fn position_of(&self, index: u32) -> Vector2F {
         self.points[index as usize]
     }
 
+    #[inline]
+    pub fn flags_of(&self, index: u32) -> PointFlags {
+        self.flags[index as usize]
+    }
+
     #[inline]
     pub fn last_position(&self) -> Option<Vector2F> {
         self.points.last().cloned()
@@ -357,10 +505,7 @@ impl Contour {
     }
 
     #[inline]
-    pub(crate) fn push_point(&mut self,
-                             point: Vector2F,
-                             flags: PointFlags,
-                             update_bounds: bool) {
+    pub fn push_point(&mut self, point: Vector2F, flags: PointFlags, update_bounds: bool) {
         debug_assert!(!point.x().is_nan() && !point.y().is_nan());
 
         if update_bounds {
@@ -407,9 +552,10 @@ impl Contour {
         if end_angle - start_angle >= PI * 2.0 {
             self.push_ellipse(transform);
         } else {
-            let start = vec2f(start_angle.cos(), start_angle.sin());
-            let end   = vec2f(end_angle.cos(),   end_angle.sin());
-            self.push_arc_from_unit_chord(transform, LineSegment2F::new(start, end), direction);
+            let angles = F32x4::new(start_angle, end_angle, 0.0, 0.0);
+            let (sines, cosines) = angles.sin_cos();
+            let chord = LineSegment2F(cosines.concat_xy_xy(sines).xzyw());
+            self.push_arc_from_unit_chord(transform, chord, direction);
         }
     }
 
@@ -611,6 +757,16 @@ impl Contour {
     }
 }
 
+impl Hash for Contour {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.flags.hash(state);
+        for point in &self.points {
+            util::hash_f32x2(point.0, state);
+        }
+        self.closed.hash(state);
+    }
+}
+
 impl Debug for Contour {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         for (segment_index, segment) in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT)
@@ -762,3 +918,93 @@ pub(crate) fn union_rect(bounds: &mut RectF, new_point: Vector2F, first: bool) {
         *bounds = bounds.union_point(new_point)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::fill::FillRule;
+    use crate::outline::{Contour, Outline};
+    use crate::test_util::square;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::vec2f;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_signed_area() {
+        assert_eq!(square(vec2f(1.0, 2.0), 10.0, true).signed_area(), 100.0);
+        assert_eq!(square(vec2f(1.0, 2.0), 10.0, false).signed_area(), -100.0);
+        assert!(square(vec2f(1.0, 2.0), 10.0, true).is_clockwise());
+        assert!(!square(vec2f(1.0, 2.0), 10.0, false).is_clockwise());
+        assert_eq!(Contour::new().signed_area(), 0.0);
+
+        // Far from the origin.
+        assert_eq!(square(vec2f(1.0e6, -3.0e6), 4.0, true).signed_area(), 16.0);
+
+        // An open contour is closed implicitly.
+        let mut open = square(vec2f(0.0, 0.0), 10.0, true);
+        open.closed = false;
+        assert_eq!(open.signed_area(), 100.0);
+    }
+
+    #[test]
+    fn test_signed_area_of_curves() {
+        // A parabolic segment bulging downward from a chord of 10, with a peak 5 below it, has
+        // area ⅔ × 10 × 5 exactly.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_quadratic(vec2f(5.0, 10.0), vec2f(10.0, 0.0));
+        contour.close();
+        assert!((contour.signed_area().abs() - 100.0 / 3.0).abs() < 1.0e-4);
+
+        // The same curve as a cubic.
+        let mut cubic = Contour::new();
+        cubic.push_endpoint(vec2f(0.0, 0.0));
+        cubic.push_cubic(vec2f(10.0 / 3.0, 20.0 / 3.0), vec2f(20.0 / 3.0, 20.0 / 3.0),
+                         vec2f(10.0, 0.0));
+        cubic.close();
+        assert!((cubic.signed_area() - contour.signed_area()).abs() < 1.0e-4);
+
+        // A circle of radius 10, which is drawn with Bézier curves that are very nearly round.
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::from_scale(10.0));
+        assert!((circle.signed_area().abs() - 100.0 * PI).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_area_of_nested_contours() {
+        let mut outline = Outline::new();
+        outline.push_contour(square(vec2f(0.0, 0.0), 10.0, true));
+        outline.push_contour(square(vec2f(3.0, 3.0), 4.0, true));
+        assert_eq!(outline.area(FillRule::Winding), 100.0);
+        assert_eq!(outline.area(FillRule::EvenOdd), 84.0);
+        assert_eq!(outline.area(FillRule::WindingAtLeast(2)), 16.0);
+
+        // Holes winding the other way are holes under either rule.
+        let mut outline = Outline::new();
+        outline.push_contour(square(vec2f(0.0, 0.0), 10.0, false));
+        outline.push_contour(square(vec2f(3.0, 3.0), 4.0, true));
+        outline.push_contour(square(vec2f(20.0, 0.0), 1.0, true));
+        assert_eq!(outline.area(FillRule::Winding), 85.0);
+        assert_eq!(outline.area(FillRule::EvenOdd), 85.0);
+
+        // A circle with a square hole keeps its curves exact.
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::from_scale(10.0));
+        let circle_area = circle.signed_area().abs();
+        let mut outline = Outline::new();
+        outline.push_contour(circle);
+        outline.push_contour(Contour::from_rect(RectF::new(vec2f(-2.0, -2.0), vec2f(4.0, 4.0))));
+        assert!((outline.area(FillRule::EvenOdd) - (circle_area - 16.0)).abs() < 1.0e-3);
+        assert!((outline.area(FillRule::Winding) - circle_area).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_area_of_crossing_contours() {
+        let mut outline = Outline::new();
+        outline.push_contour(square(vec2f(0.0, 0.0), 10.0, true));
+        outline.push_contour(square(vec2f(5.0, 5.0), 10.0, true));
+        assert_eq!(outline.area(FillRule::Winding), 175.0);
+        assert_eq!(outline.area(FillRule::EvenOdd), 150.0);
+        assert_eq!(Outline::new().area(FillRule::Winding), 0.0);
+    }
+}
@@ -10,6 +10,7 @@
 
 //! Raster image patterns.
 
+use crate::animation::AnimatedImage;
 use crate::effects::PatternFilter;
 use crate::render_target::RenderTargetId;
 use crate::util;
@@ -20,6 +21,7 @@ use std::collections::hash_map::DefaultHasher;
 use std::fmt::{self, Debug, Formatter};
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[cfg(feature = "pf-image")]
 use image::RgbaImage;
@@ -31,6 +33,14 @@ pub struct Pattern {
     transform: Transform2F,
     filter: Option<PatternFilter>,
     flags: PatternFlags,
+    animation: Option<PatternAnimation>,
+}
+
+// The animation a pattern's image comes from, and which frame the image is.
+#[derive(Clone, PartialEq, Debug)]
+struct PatternAnimation {
+    image: Arc<AnimatedImage>,
+    frame_index: usize,
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
@@ -69,6 +79,7 @@ impl Pattern {
             transform: Transform2F::default(),
             filter: None,
             flags: PatternFlags::empty(),
+            animation: None,
         }
     }
 
@@ -77,6 +88,45 @@ impl Pattern {
         Pattern::from_source(PatternSource::Image(image))
     }
 
+    /// Creates a pattern that shows the first frame of `animation`. Call `advance()` to move
+    /// to later frames.
+    ///
+    /// An animation without frames gives an empty pattern.
+    pub fn from_animated_image(animation: Arc<AnimatedImage>) -> Pattern {
+        let image = match animation.frame_at(Duration::from_secs(0)) {
+            Some((_, frame)) => frame.image,
+            None => Image::new(Vector2I::zero(), Arc::new(vec![])),
+        };
+        let mut pattern = Pattern::from_image(image);
+        pattern.animation = Some(PatternAnimation { image: animation, frame_index: 0 });
+        pattern
+    }
+
+    /// Shows the frame of the animation that is current at `time` after its start. Frames are
+    /// decoded as needed.
+    ///
+    /// Returns true if the image changed, in which case paths using this pattern must be drawn
+    /// again with it to see the new frame. Patterns not made from animated images never change.
+    pub fn advance(&mut self, time: Duration) -> bool {
+        let animation = match self.animation {
+            None => return false,
+            Some(ref mut animation) => animation,
+        };
+        match animation.image.frame_at(time) {
+            Some((frame_index, frame)) if frame_index != animation.frame_index => {
+                animation.frame_index = frame_index;
+                self.source = PatternSource::Image(frame.image);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub fn animation(&self) -> Option<&Arc<AnimatedImage>> {
+        self.animation.as_ref().map(|animation| &animation.image)
+    }
+
     #[inline]
     pub fn from_render_target(id: RenderTargetId, size: Vector2I) -> Pattern {
         Pattern::from_source(PatternSource::RenderTarget { id, size })
@@ -221,6 +271,7 @@ impl Hash for Pattern {
     fn hash<H>(&self, state: &mut H) where H: Hasher {
         self.source.hash(state);
         util::hash_transform2f(self.transform, state);
+        self.filter.hash(state);
         self.flags.hash(state);
     }
 }
@@ -0,0 +1,750 @@
+// pathfinder/content/src/planar.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Planar maps: the arrangement of a set of outlines, split at every intersection, from which
+//! the boundary of any combination of their filled areas can be extracted.
+//!
+//! Curves are flattened to lines first, so extracted outlines are polygons. Tolerances scale with
+//! the input, so outlines in tiny or huge units are handled alike. Outlines with non-finite
+//! points have no meaningful arrangement; maps containing any extract as empty outlines.
+
+use crate::outline::{Contour, ContourIterFlags, Outline};
+use crate::segment::Segment;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::{Vector2F, vec2f};
+use std::collections::HashMap;
+use std::f32;
+
+// How far flattened curves may stray from the originals, as a fraction of the larger side of the
+// bounds of the outline they belong to.
+const FLATTENING_TOLERANCE: f32 = 1.0 / 16384.0;
+// Deeper than this, curves are split no further even if they aren't yet flat.
+const MAX_FLATTENING_DEPTH: u32 = 16;
+
+// Vertices are snapped to a grid whose spacing is the largest coordinate in the map divided by
+// about 2 to this power, which merges intersections that differ only by rounding error. `f32`s
+// have 24 bits of precision, which leaves some to absorb that error.
+const SNAP_PRECISION_BITS: i32 = 18;
+
+// Snapping the points where edges cross can push them across other edges, so the map is split
+// again until nothing crosses, but at most this many times.
+const MAX_SPLIT_PASSES: u32 = 8;
+
+// Intersections this close to the end of an edge, in parametric terms, are treated as touching
+// the endpoint.
+const PARAMETER_EPSILON: f64 = 1.0e-9;
+
+// Vertices where the boundary turns by an angle whose sine is less than this are dropped from
+// extracted contours. Being an angle, this doesn't depend on the scale of the input.
+const COLLINEAR_EPSILON: f32 = 1.0e-4;
+
+/// The arrangement of one or more layers of outlines.
+///
+/// Each layer is typically one operand of an operation; faces of the map remember their winding
+/// number with respect to each layer separately.
+pub(crate) struct PlanarMap {
+    layer_count: usize,
+    input_edges: Vec<InputEdge>,
+    has_non_finite_points: bool,
+}
+
+struct InputEdge {
+    from: Vector2F,
+    to: Vector2F,
+    layer: usize,
+}
+
+// An edge of the split arrangement, between two snapped vertices. `windings` holds, per layer,
+// how many input edges run from `from` to `to`, minus how many run the other way.
+struct PlanarEdge {
+    from: u32,
+    to: u32,
+    windings: Vec<i32>,
+}
+
+impl PlanarMap {
+    pub(crate) fn new(layer_count: usize) -> PlanarMap {
+        PlanarMap { layer_count, input_edges: vec![], has_non_finite_points: false }
+    }
+
+    /// Adds an outline to `layer`. Open contours are implicitly closed, as they are when filled.
+    pub(crate) fn push_outline(&mut self, outline: &Outline, layer: usize) {
+        debug_assert!(layer < self.layer_count);
+        let has_non_finite_points = outline.contours().iter().any(|contour| {
+            (0..contour.len()).any(|point_index| {
+                let point = contour.position_of(point_index);
+                !point.x().is_finite() || !point.y().is_finite()
+            })
+        });
+        if has_non_finite_points {
+            self.has_non_finite_points = true;
+            return;
+        }
+
+        let tolerance = flattening_tolerance(outline.bounds());
+        for contour in outline.contours() {
+            let points = flatten_contour(contour, tolerance);
+            for (index, &from) in points.iter().enumerate() {
+                let to = points[(index + 1) % points.len()];
+                if from != to {
+                    self.input_edges.push(InputEdge { from, to, layer });
+                }
+            }
+        }
+    }
+
+    /// Returns the boundary of the faces whose per-layer winding numbers satisfy `is_filled`.
+    ///
+    /// The result has no self-intersections or overlaps. Its outer contours wind clockwise and
+    /// its holes anticlockwise (with Y pointing down), so it fills the same area under either
+    /// fill rule.
+    pub(crate) fn extract<F>(&self, is_filled: F) -> Outline where F: Fn(&[i32]) -> bool {
+        if self.has_non_finite_points {
+            return Outline::new();
+        }
+
+        let (vertices, edges) = self.split();
+        let (face_indices, face_windings) = self.face_windings(&vertices, &edges);
+
+        // Keep every edge that separates a filled face from an unfilled one, oriented so that the
+        // filled face is on its left (which is the right, visually, with Y pointing down).
+        let mut boundary = vec![];
+        for (edge_index, edge) in edges.iter().enumerate() {
+            let left = is_filled(&face_windings[face_indices[edge_index * 2]]);
+            let right = is_filled(&face_windings[face_indices[edge_index * 2 + 1]]);
+            match (left, right) {
+                (true, false) => boundary.push((edge.from, edge.to)),
+                (false, true) => boundary.push((edge.to, edge.from)),
+                _ => {}
+            }
+        }
+
+        let mut outline = Outline::new();
+        for points in trace_contours(&vertices, &boundary) {
+            let points = remove_collinear_points(points);
+            if points.len() < 3 {
+                continue;
+            }
+            let mut contour = Contour::with_capacity(points.len());
+            points.into_iter().for_each(|point| contour.push_endpoint(point));
+            contour.close();
+            outline.push_contour(contour);
+        }
+        outline
+    }
+
+    /// Returns true if no edge crosses or touches another, except where consecutive edges of a
+    /// contour meet, and no edges overlap or cancel out.
+    pub(crate) fn is_simple(&self) -> bool {
+        if self.has_non_finite_points {
+            return false;
+        }
+
+        let (vertices, edges) = self.split();
+        if edges.len() != self.input_edges.len() {
+            return false;
+        }
+        let mut degrees = vec![0; vertices.len()];
+        for edge in &edges {
+            degrees[edge.from as usize] += 1;
+            degrees[edge.to as usize] += 1;
+        }
+        degrees.iter().all(|&degree| degree == 2)
+    }
+
+    // Splits the input edges at all their intersections and merges coincident pieces.
+    fn split(&self) -> (Vec<Vector2F>, Vec<PlanarEdge>) {
+        let magnitude = self.input_edges.iter().fold(0.0f32, |magnitude, edge| {
+            let (from, to) = (edge.from.abs(), edge.to.abs());
+            magnitude.max(from.x()).max(from.y()).max(to.x()).max(to.y())
+        });
+        let mut grid = SnapGrid::new(magnitude);
+
+        // Snap the input first, so that crossings are found between edges as they'll come out.
+        let mut edges = vec![];
+        let mut edge_indices = HashMap::new();
+        for input_edge in &self.input_edges {
+            let (from, to) = (grid.snap(input_edge.from), grid.snap(input_edge.to));
+            let mut windings = vec![0; self.layer_count];
+            windings[input_edge.layer] = 1;
+            merge_edge(&mut edges, &mut edge_indices, from, to, &windings);
+        }
+
+        for _ in 0..MAX_SPLIT_PASSES {
+            let split_parameters = find_crossings(&grid.vertices, &edges);
+
+            let mut changed = false;
+            let mut split_edges = vec![];
+            let mut split_edge_indices = HashMap::new();
+            for (edge, mut parameters) in edges.iter().zip(split_parameters) {
+                parameters.sort_by(f64::total_cmp);
+                let (from, to) = (grid.vertices[edge.from as usize],
+                                  grid.vertices[edge.to as usize]);
+                let mut prev_vertex = edge.from;
+                for t in parameters {
+                    let vertex = grid.snap(from.lerp(to, t as f32));
+                    if vertex != prev_vertex && vertex != edge.to {
+                        merge_edge(&mut split_edges,
+                                   &mut split_edge_indices,
+                                   prev_vertex,
+                                   vertex,
+                                   &edge.windings);
+                        prev_vertex = vertex;
+                        changed = true;
+                    }
+                }
+                merge_edge(&mut split_edges,
+                           &mut split_edge_indices,
+                           prev_vertex,
+                           edge.to,
+                           &edge.windings);
+            }
+
+            edges = split_edges;
+            if !changed {
+                break;
+            }
+        }
+
+        // Edges whose contributions cancel out separate nothing.
+        edges.retain(|edge| edge.windings.iter().any(|&winding| winding != 0));
+        (grid.vertices, edges)
+    }
+
+    // Finds the faces of the map and their winding numbers.
+    //
+    // Each edge is treated as two half-edges, `edge_index * 2` running from `from` to `to` and
+    // `edge_index * 2 + 1` running back, each with the face on its left. The first result maps
+    // half-edges to faces and the second holds the winding numbers of each face. Only one face
+    // per connected piece of the map needs a ray cast; the winding numbers of the rest follow by
+    // stepping across edges, each of which changes them by its own winding numbers.
+    fn face_windings(&self, vertices: &[Vector2F], edges: &[PlanarEdge])
+                     -> (Vec<usize>, Vec<Vec<i32>>) {
+        let half_edge_count = edges.len() * 2;
+        let half_edge_from = |half_edge: usize| {
+            let edge = &edges[half_edge / 2];
+            if half_edge & 1 == 0 { edge.from } else { edge.to }
+        };
+        let half_edge_vector = |half_edge: usize| {
+            vertices[half_edge_from(half_edge ^ 1) as usize] -
+                vertices[half_edge_from(half_edge) as usize]
+        };
+
+        // Sort the half-edges leaving each vertex by angle.
+        let mut outgoing = vec![vec![]; vertices.len()];
+        for half_edge in 0..half_edge_count {
+            outgoing[half_edge_from(half_edge) as usize].push(half_edge);
+        }
+        let mut slots = vec![0; half_edge_count];
+        for half_edges in &mut outgoing {
+            half_edges.sort_by(|&a, &b| {
+                let (a, b) = (half_edge_vector(a), half_edge_vector(b));
+                f32::atan2(a.y(), a.x()).total_cmp(&f32::atan2(b.y(), b.x()))
+            });
+            for (slot, &half_edge) in half_edges.iter().enumerate() {
+                slots[half_edge] = slot;
+            }
+        }
+
+        // Walk around each face, turning as sharply as possible to the left at each vertex.
+        let mut face_indices = vec![usize::MAX; half_edge_count];
+        let mut faces = vec![];
+        for start_half_edge in 0..half_edge_count {
+            if face_indices[start_half_edge] != usize::MAX {
+                continue;
+            }
+            let mut face = vec![];
+            let mut half_edge = start_half_edge;
+            while face_indices[half_edge] == usize::MAX {
+                face_indices[half_edge] = faces.len();
+                face.push(half_edge);
+                let twin = half_edge ^ 1;
+                let siblings = &outgoing[half_edge_from(twin) as usize];
+                half_edge = siblings[(slots[twin] + siblings.len() - 1) % siblings.len()];
+            }
+            faces.push(face);
+        }
+
+        let mut face_windings: Vec<Option<Vec<i32>>> = vec![None; faces.len()];
+        let mut stack = vec![];
+        for start_face_index in 0..faces.len() {
+            if face_windings[start_face_index].is_some() {
+                continue;
+            }
+
+            let half_edge = faces[start_face_index][0];
+            let from = vertices[half_edge_from(half_edge) as usize];
+            let vector = half_edge_vector(half_edge);
+            face_windings[start_face_index] = Some(self.winding_along_ray(vertices,
+                                                                          edges,
+                                                                          half_edge / 2,
+                                                                          from + vector * 0.5,
+                                                                          perpendicular(vector)));
+
+            stack.push(start_face_index);
+            while let Some(face_index) = stack.pop() {
+                for &half_edge in &faces[face_index] {
+                    let twin_face_index = face_indices[half_edge ^ 1];
+                    if face_windings[twin_face_index].is_some() {
+                        continue;
+                    }
+                    // The face left of an edge winds more than the face right of it.
+                    let sign = if half_edge & 1 == 0 { -1 } else { 1 };
+                    let windings = face_windings[face_index].as_ref().unwrap();
+                    let twin_windings = windings.iter()
+                                                .zip(edges[half_edge / 2].windings.iter())
+                                                .map(|(&winding, &edge_winding)| {
+                                                    winding + sign * edge_winding
+                                                })
+                                                .collect();
+                    face_windings[twin_face_index] = Some(twin_windings);
+                    stack.push(twin_face_index);
+                }
+            }
+        }
+
+        (face_indices, face_windings.into_iter().map(Option::unwrap).collect())
+    }
+
+    // Computes the winding numbers of the face just beside `origin`, which lies on the edge
+    // `skip_edge_index`, in the given direction, by casting a ray that way and counting the edges
+    // it crosses.
+    fn winding_along_ray(&self,
+                         vertices: &[Vector2F],
+                         edges: &[PlanarEdge],
+                         skip_edge_index: usize,
+                         origin: Vector2F,
+                         direction: Vector2F)
+                         -> Vec<i32> {
+        let mut windings = vec![0; self.layer_count];
+        for (edge_index, edge) in edges.iter().enumerate() {
+            if edge_index == skip_edge_index {
+                continue;
+            }
+
+            // Half-open classification makes rays through vertices count each crossing once.
+            let (from, to) = (vertices[edge.from as usize], vertices[edge.to as usize]);
+            let (side_from, side_to) = (direction.det(from - origin), direction.det(to - origin));
+            if (side_from >= 0.0) == (side_to >= 0.0) {
+                continue;
+            }
+            let crossing = from.lerp(to, side_from / (side_from - side_to));
+            if (crossing - origin).dot(direction) <= 0.0 {
+                continue;
+            }
+
+            let sign = if side_to >= 0.0 { 1 } else { -1 };
+            for (winding, &edge_winding) in windings.iter_mut().zip(edge.windings.iter()) {
+                *winding += sign * edge_winding;
+            }
+        }
+        windings
+    }
+}
+
+/// Returns the winding number of `polygon` around `point`, which is positive if the polygon winds
+/// clockwise with Y pointing down.
+pub(crate) fn winding_number(polygon: &[Vector2F], point: Vector2F) -> i32 {
+    let mut winding = 0;
+    for (index, &from) in polygon.iter().enumerate() {
+        let to = polygon[(index + 1) % polygon.len()];
+        let side = (to - from).det(point - from);
+        if from.y() <= point.y() {
+            if to.y() > point.y() && side > 0.0 {
+                winding += 1;
+            }
+        } else if to.y() <= point.y() && side < 0.0 {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+/// Returns how far curves in an outline with the given bounds may stray when flattened.
+pub(crate) fn flattening_tolerance(bounds: RectF) -> f32 {
+    bounds.width().max(bounds.height()) * FLATTENING_TOLERANCE
+}
+
+pub(crate) fn flatten_contour(contour: &Contour, tolerance: f32) -> Vec<Vector2F> {
+    let mut points = vec![];
+    for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+        if points.is_empty() {
+            points.push(segment.baseline.from());
+        }
+        flatten_segment(&segment, tolerance, &mut points, 0);
+    }
+    if points.is_empty() && !contour.is_empty() {
+        points.push(contour.position_of(0));
+    }
+    points
+}
+
+fn flatten_segment(segment: &Segment, tolerance: f32, points: &mut Vec<Vector2F>, depth: u32) {
+    if segment.is_line() || depth >= MAX_FLATTENING_DEPTH ||
+            segment.to_cubic().as_cubic_segment().is_flat(tolerance) {
+        points.push(segment.baseline.to());
+        return;
+    }
+    let (before, after) = segment.split(0.5);
+    flatten_segment(&before, tolerance, points, depth + 1);
+    flatten_segment(&after, tolerance, points, depth + 1);
+}
+
+// Adds `windings` to the edge from `from` to `to`, creating it if need be. Edges are stored
+// running from the lower vertex index to the higher, so the windings of edges running the other
+// way are negated.
+fn merge_edge(edges: &mut Vec<PlanarEdge>,
+              edge_indices: &mut HashMap<(u32, u32), usize>,
+              from: u32,
+              to: u32,
+              windings: &[i32]) {
+    if from == to {
+        return;
+    }
+    let (key, sign) = if from < to { ((from, to), 1) } else { ((to, from), -1) };
+    let edge_index = *edge_indices.entry(key).or_insert_with(|| {
+        edges.push(PlanarEdge { from: key.0, to: key.1, windings: vec![0; windings.len()] });
+        edges.len() - 1
+    });
+    for (edge_winding, &winding) in edges[edge_index].windings.iter_mut().zip(windings) {
+        *edge_winding += sign * winding;
+    }
+}
+
+// Returns, for each edge, the parameters along it of every point where another edge crosses or
+// touches it.
+fn find_crossings(vertices: &[Vector2F], edges: &[PlanarEdge]) -> Vec<Vec<f64>> {
+    let endpoints = |edge: &PlanarEdge| {
+        (vertices[edge.from as usize], vertices[edge.to as usize])
+    };
+
+    // Sweep from left to right, so that each edge is only tested against the edges whose
+    // horizontal extents overlap its own.
+    let mut sorted_indices: Vec<usize> = (0..edges.len()).collect();
+    sorted_indices.sort_by(|&a, &b| {
+        let (a, b) = (endpoints(&edges[a]), endpoints(&edges[b]));
+        a.0.x().min(a.1.x()).total_cmp(&b.0.x().min(b.1.x()))
+    });
+
+    let mut split_parameters = vec![vec![]; edges.len()];
+    for (sorted_index, &index_a) in sorted_indices.iter().enumerate() {
+        let edge_a = endpoints(&edges[index_a]);
+        let max_x = edge_a.0.x().max(edge_a.1.x());
+        for &index_b in &sorted_indices[(sorted_index + 1)..] {
+            let edge_b = endpoints(&edges[index_b]);
+            if edge_b.0.x().min(edge_b.1.x()) > max_x {
+                break;
+            }
+            if !bounds_overlap(edge_a, edge_b) {
+                continue;
+            }
+            intersect(edge_a, edge_b, |t_a, t_b| {
+                split_parameters[index_a].push(t_a);
+                split_parameters[index_b].push(t_b);
+            });
+        }
+    }
+    split_parameters
+}
+
+fn bounds_overlap(a: (Vector2F, Vector2F), b: (Vector2F, Vector2F)) -> bool {
+    let (a_min, a_max) = (a.0.min(a.1), a.0.max(a.1));
+    let (b_min, b_max) = (b.0.min(b.1), b.0.max(b.1));
+    a_min.x() <= b_max.x() && b_min.x() <= a_max.x() &&
+        a_min.y() <= b_max.y() && b_min.y() <= a_max.y()
+}
+
+// Calls `split` with the parameters along `a` and `b` of every point where they cross or touch
+// away from their endpoints. Collinear overlapping edges are split where each one's endpoints
+// lie on the other.
+fn intersect<F>(a: (Vector2F, Vector2F), b: (Vector2F, Vector2F), mut split: F)
+                where F: FnMut(f64, f64) {
+    let (a0, a1) = (to_f64(a.0), to_f64(a.1));
+    let (b0, b1) = (to_f64(b.0), to_f64(b.1));
+    let (da, db) = (sub(a1, a0), sub(b1, b0));
+    let denominator = det(da, db);
+    let offset = sub(b0, a0);
+
+    let scale = dot(da, da).max(dot(db, db));
+    if denominator.abs() > 1.0e-12 * scale {
+        let t_a = det(offset, db) / denominator;
+        let t_b = det(offset, da) / denominator;
+        let range = -PARAMETER_EPSILON..=(1.0 + PARAMETER_EPSILON);
+        if range.contains(&t_a) && range.contains(&t_b) {
+            split(t_a.clamp(0.0, 1.0), t_b.clamp(0.0, 1.0));
+        }
+        return;
+    }
+
+    // Parallel. Only collinear edges can touch.
+    if det(offset, da).abs() > 1.0e-12 * scale {
+        return;
+    }
+    let (length_a, length_b) = (dot(da, da), dot(db, db));
+    if length_a == 0.0 || length_b == 0.0 {
+        return;
+    }
+    for &(point, onto_start, onto_vector, onto_length, point_is_on_a) in
+            &[(b0, a0, da, length_a, false), (b1, a0, da, length_a, false),
+              (a0, b0, db, length_b, true), (a1, b0, db, length_b, true)] {
+        let t = dot(sub(point, onto_start), onto_vector) / onto_length;
+        if t > PARAMETER_EPSILON && t < 1.0 - PARAMETER_EPSILON {
+            // Only the edge being split needs the parameter; the other is at its endpoint.
+            if point_is_on_a {
+                split(0.0, t);
+            } else {
+                split(t, 0.0);
+            }
+        }
+    }
+}
+
+// The vertices of a map, each on a grid fine enough to tell apart the points of the input but
+// coarse enough to merge points that differ only by rounding error.
+struct SnapGrid {
+    scale: f64,
+    vertices: Vec<Vector2F>,
+    vertex_indices: HashMap<(i64, i64), u32>,
+}
+
+impl SnapGrid {
+    // Creates a grid for points no further than `magnitude` from the origin along either axis.
+    // The spacing is a power of two, so snapped vertices are exactly representable.
+    fn new(magnitude: f32) -> SnapGrid {
+        let exponent = if magnitude > 0.0 { (magnitude as f64).log2().ceil() as i32 } else { 0 };
+        SnapGrid {
+            scale: 2.0f64.powi(SNAP_PRECISION_BITS - exponent),
+            vertices: vec![],
+            vertex_indices: HashMap::new(),
+        }
+    }
+
+    // Returns the index of the vertex nearest `point`, adding it if need be.
+    fn snap(&mut self, point: Vector2F) -> u32 {
+        let scale = self.scale;
+        let key = ((point.x() as f64 * scale).round() as i64,
+                   (point.y() as f64 * scale).round() as i64);
+        let vertices = &mut self.vertices;
+        *self.vertex_indices.entry(key).or_insert_with(|| {
+            vertices.push(vec2f((key.0 as f64 / scale) as f32, (key.1 as f64 / scale) as f32));
+            vertices.len() as u32 - 1
+        })
+    }
+}
+
+// Links directed boundary edges into closed loops. Where several loops meet at a vertex, each
+// turns as sharply as possible toward its filled side, so that regions touching only at a corner
+// come out as separate contours.
+fn trace_contours(vertices: &[Vector2F], boundary: &[(u32, u32)]) -> Vec<Vec<Vector2F>> {
+    let mut outgoing: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (edge_index, &(from, _)) in boundary.iter().enumerate() {
+        outgoing.entry(from).or_default().push(edge_index);
+    }
+
+    let mut visited = vec![false; boundary.len()];
+    let mut contours = vec![];
+    for start_edge_index in 0..boundary.len() {
+        if visited[start_edge_index] {
+            continue;
+        }
+
+        let start_vertex = boundary[start_edge_index].0;
+        let mut points = vec![];
+        let mut edge_index = start_edge_index;
+        loop {
+            visited[edge_index] = true;
+            let (from, to) = boundary[edge_index];
+            points.push(vertices[from as usize]);
+            if to == start_vertex {
+                break;
+            }
+
+            let incoming = vertices[to as usize] - vertices[from as usize];
+            let candidates = match outgoing.get(&to) {
+                None => break,
+                Some(candidates) => candidates,
+            };
+            let next_edge_index = candidates.iter().cloned().filter(|&next_edge_index| {
+                !visited[next_edge_index]
+            }).max_by(|&a, &b| {
+                let turn_a = turn_angle(incoming, vertices, boundary[a]);
+                let turn_b = turn_angle(incoming, vertices, boundary[b]);
+                turn_a.total_cmp(&turn_b)
+            });
+            match next_edge_index {
+                Some(next_edge_index) => edge_index = next_edge_index,
+                None => break,
+            }
+        }
+        contours.push(points);
+    }
+    contours
+}
+
+fn turn_angle(incoming: Vector2F, vertices: &[Vector2F], edge: (u32, u32)) -> f32 {
+    let outgoing = vertices[edge.1 as usize] - vertices[edge.0 as usize];
+    f32::atan2(incoming.det(outgoing), incoming.dot(outgoing))
+}
+
+fn remove_collinear_points(mut points: Vec<Vector2F>) -> Vec<Vector2F> {
+    let mut index = 0;
+    while index < points.len() && points.len() >= 3 {
+        let prev = points[(index + points.len() - 1) % points.len()];
+        let next = points[(index + 1) % points.len()];
+        let (incoming, outgoing) = (points[index] - prev, next - points[index]);
+        let length = incoming.length() * outgoing.length();
+        if incoming.det(outgoing).abs() <= COLLINEAR_EPSILON * length &&
+                incoming.dot(outgoing) > 0.0 {
+            points.remove(index);
+            index = index.saturating_sub(1);
+        } else {
+            index += 1;
+        }
+    }
+    points
+}
+
+#[inline]
+fn perpendicular(vector: Vector2F) -> Vector2F {
+    vec2f(-vector.y(), vector.x())
+}
+
+#[inline]
+fn to_f64(point: Vector2F) -> (f64, f64) {
+    (point.x() as f64, point.y() as f64)
+}
+
+#[inline]
+fn sub(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+#[inline]
+fn dot(a: (f64, f64), b: (f64, f64)) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+#[inline]
+fn det(a: (f64, f64), b: (f64, f64)) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fill::FillRule;
+    use crate::outline::{Contour, Outline};
+    use crate::test_util::{signed_area, square};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+
+    #[test]
+    fn test_overlapping_squares() {
+        let mut outline = Outline::new();
+        outline.push_contour(square(vec2f(0.0, 0.0), 10.0, true));
+        outline.push_contour(square(vec2f(5.0, 5.0), 10.0, true));
+
+        let merged = outline.flatten_fill(FillRule::Winding);
+        assert_eq!(merged.contours().len(), 1);
+        assert_eq!(merged.contours()[0].len(), 8);
+        assert_eq!(signed_area(&merged), 175.0);
+        assert_eq!(merged.bounds(), RectF::new(vec2f(0.0, 0.0), vec2f(15.0, 15.0)));
+
+        // Under even-odd, the overlap becomes a hole.
+        let merged = outline.flatten_fill(FillRule::EvenOdd);
+        assert_eq!(merged.contours().len(), 2);
+        assert_eq!(signed_area(&merged), 150.0);
+    }
+
+    #[test]
+    fn test_holes_and_orientation() {
+        // An anticlockwise square with a clockwise hole comes out with the windings swapped.
+        let mut outline = Outline::new();
+        outline.push_contour(square(vec2f(0.0, 0.0), 10.0, false));
+        outline.push_contour(square(vec2f(3.0, 3.0), 4.0, true));
+        let merged = outline.flatten_fill(FillRule::Winding);
+        assert_eq!(merged.contours().len(), 2);
+        assert_eq!(signed_area(&merged), 84.0);
+
+        // Coincident contours winding opposite ways cancel out.
+        let mut outline = Outline::new();
+        outline.push_contour(square(vec2f(0.0, 0.0), 10.0, true));
+        outline.push_contour(square(vec2f(0.0, 0.0), 10.0, false));
+        assert!(outline.flatten_fill(FillRule::Winding).contours().is_empty());
+    }
+
+    #[test]
+    fn test_winding_threshold() {
+        let mut outline = Outline::new();
+        outline.push_contour(square(vec2f(0.0, 0.0), 10.0, true));
+        outline.push_contour(square(vec2f(5.0, 5.0), 10.0, true));
+
+        // Only the overlap winds twice.
+        let overlap = outline.flatten_fill(FillRule::WindingAtLeast(2));
+        assert_eq!(overlap.contours().len(), 1);
+        assert_eq!(signed_area(&overlap), 25.0);
+        assert!(outline.flatten_fill(FillRule::WindingAtLeast(3)).contours().is_empty());
+    }
+
+    #[test]
+    fn test_inverse_fill() {
+        let mut outline = Outline::new();
+        outline.push_contour(square(vec2f(5.0, 5.0), 10.0, true));
+        let bounds = RectF::new(vec2f(0.0, 0.0), vec2f(20.0, 20.0));
+
+        // A square hole in the bounds.
+        let inverse = outline.inverse_fill(FillRule::Winding, bounds);
+        assert_eq!(inverse.contours().len(), 2);
+        assert_eq!(signed_area(&inverse), 300.0);
+        assert_eq!(inverse.bounds(), bounds);
+
+        // Bounds that cut through the outline keep only the part outside it.
+        let bounds = RectF::new(vec2f(10.0, 0.0), vec2f(10.0, 20.0));
+        let inverse = outline.inverse_fill(FillRule::Winding, bounds);
+        assert_eq!(inverse.contours().len(), 1);
+        assert_eq!(signed_area(&inverse), 150.0);
+
+        // Nothing is left when the outline covers the bounds.
+        let bounds = RectF::new(vec2f(6.0, 6.0), vec2f(2.0, 2.0));
+        assert!(outline.inverse_fill(FillRule::Winding, bounds).contours().is_empty());
+    }
+
+    #[test]
+    fn test_scale_independence() {
+        // The same overlapping squares as above, in units far smaller and far larger than one.
+        for &scale in &[1.0e-3, 1.0e5] {
+            let mut outline = Outline::new();
+            outline.push_contour(square(vec2f(0.0, 0.0), 10.0 * scale, true));
+            outline.push_contour(square(vec2f(5.0, 5.0) * scale, 10.0 * scale, true));
+
+            let merged = outline.flatten_fill(FillRule::Winding);
+            assert_eq!(merged.contours().len(), 1);
+            assert_eq!(merged.contours()[0].len(), 8);
+            let expected_area = 175.0 * scale * scale;
+            assert!((signed_area(&merged) - expected_area).abs() <= expected_area * 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn test_non_finite_points() {
+        let mut outline = Outline::new();
+        outline.push_contour(square(vec2f(0.0, 0.0), 10.0, true));
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(f32::INFINITY, 5.0));
+        contour.push_endpoint(vec2f(5.0, f32::NEG_INFINITY));
+        contour.close();
+        outline.push_contour(contour);
+
+        assert!(outline.flatten_fill(FillRule::Winding).contours().is_empty());
+        let bounds = RectF::new(vec2f(0.0, 0.0), vec2f(20.0, 20.0));
+        assert!(outline.inverse_fill(FillRule::Winding, bounds).contours().is_empty());
+        assert_eq!(outline.area(FillRule::Winding), 0.0);
+    }
+}
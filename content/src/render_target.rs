@@ -10,8 +10,19 @@
 
 //! Render targets.
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+use std::hash::{Hash, Hasher};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct RenderTargetId {
     pub scene: u32,
     pub render_target: u32,
 }
+
+// The scene isn't hashed, so that patterns drawing render targets hash the same in separately-
+// built scenes with the same contents. See `Scene::content_hash()`.
+impl Hash for RenderTargetId {
+    #[inline]
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.render_target.hash(state);
+    }
+}
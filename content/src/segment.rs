@@ -292,16 +292,16 @@ impl<'s> CubicSegment<'s> {
             let p0p1 = p0p3.concat_xy_xy(p1p2);
 
             // p01 = lerp(p0, p1, t), p12 = lerp(p1, p2, t), p23 = lerp(p2, p3, t)
-            let p01p12 = p0p1 + tttt * (p1p2 - p0p1);
-            let pxxp23 = p1p2 + tttt * (p0p3 - p1p2);
+            let p01p12 = p0p1.lerp(p1p2, tttt);
+            let pxxp23 = p1p2.lerp(p0p3, tttt);
             let p12p23 = p01p12.concat_zw_zw(pxxp23);
 
             // p012 = lerp(p01, p12, t), p123 = lerp(p12, p23, t)
-            let p012p123 = p01p12 + tttt * (p12p23 - p01p12);
+            let p012p123 = p01p12.lerp(p12p23, tttt);
             let p123 = p012p123.zwzw();
 
             // p0123 = lerp(p012, p123, t)
-            let p0123 = p012p123 + tttt * (p123 - p012p123);
+            let p0123 = p012p123.lerp(p123, tttt);
 
             baseline0 = LineSegment2F(p0p3.concat_xy_xy(p0123));
             ctrl0 = LineSegment2F(p01p12.concat_xy_xy(p012p123));
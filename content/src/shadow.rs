@@ -0,0 +1,155 @@
+// pathfinder/content/src/shadow.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Analytically blurred rounded rectangles, for drop shadows.
+//!
+//! Blurring a layer with a Gaussian kernel takes two render targets and two passes over them.
+//! The blur of a rounded rectangle, which is the shape of most UI shadows, can instead be
+//! computed directly per pixel, using the error function along one axis and a few samples along
+//! the other. See Evan Wallace, "Fast Rounded Rectangle Shadows", 2015.
+
+use crate::util;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
+use std::f32::consts::PI;
+use std::hash::{Hash, Hasher};
+
+// The shadow is treated as zero beyond this many standard deviations from the rectangle.
+const SUPPORT_IN_SIGMAS: f32 = 3.0;
+
+// How many samples are taken across the blur kernel in the vertical direction. This must match
+// the tile shader.
+const VERTICAL_SAMPLE_COUNT: u32 = 4;
+
+// The smallest standard deviation, to avoid dividing by zero for sharp shadows.
+const MIN_SIGMA: f32 = 1.0e-3;
+
+/// A rounded rectangle blurred by a Gaussian, as in a CSS `box-shadow`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BlurredRoundedRect {
+    /// The rectangle before blurring.
+    pub rect: RectF,
+    /// The radius of the corners, which is clamped to half the size of the rectangle.
+    pub corner_radius: f32,
+    /// The standard deviation of the blur. CSS and the HTML canvas specify blurs as twice this.
+    pub sigma: f32,
+    /// Maps the space of `rect` to the space it's drawn in.
+    pub transform: Transform2F,
+}
+
+impl BlurredRoundedRect {
+    #[inline]
+    pub fn new(rect: RectF, corner_radius: f32, sigma: f32) -> BlurredRoundedRect {
+        BlurredRoundedRect { rect, corner_radius, sigma, transform: Transform2F::default() }
+    }
+
+    /// The area outside of which the shadow is negligible, in the space it's drawn in.
+    #[inline]
+    pub fn bounds(&self) -> RectF {
+        self.transform * self.local_bounds()
+    }
+
+    /// `bounds()` before `transform` is applied.
+    #[inline]
+    pub fn local_bounds(&self) -> RectF {
+        self.rect.dilate(self.sigma.max(0.0) * SUPPORT_IN_SIGMAS)
+    }
+
+    #[inline]
+    pub fn apply_transform(&mut self, transform: Transform2F) {
+        self.transform = transform * self.transform;
+    }
+
+    /// Returns the opacity of the shadow at `point`, in the space of `rect`.
+    ///
+    /// This computes the same approximation that the GPU does.
+    pub fn coverage(&self, point: Vector2F) -> f32 {
+        let sigma = self.sigma.max(MIN_SIGMA);
+        let half_size = self.rect.size() * 0.5;
+        let corner_radius = self.corner_radius.max(0.0).min(half_size.x()).min(half_size.y());
+        let point = point - self.rect.center();
+
+        // Integrate vertically over the part of the kernel that overlaps the rectangle.
+        let low = point.y() - half_size.y();
+        let high = point.y() + half_size.y();
+        let support = SUPPORT_IN_SIGMAS * sigma;
+        let start = (-support).max(low).min(high);
+        let end = support.max(low).min(high);
+        let step = (end - start) / VERTICAL_SAMPLE_COUNT as f32;
+
+        let mut y = start + step * 0.5;
+        let mut coverage = 0.0;
+        for _ in 0..VERTICAL_SAMPLE_COUNT {
+            coverage += horizontal_coverage(point.x(), point.y() - y, sigma, corner_radius,
+                                            half_size) * gaussian(y, sigma) * step;
+            y += step;
+        }
+        coverage.max(0.0).min(1.0)
+    }
+}
+
+impl Eq for BlurredRoundedRect {}
+
+impl Hash for BlurredRoundedRect {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        util::hash_f32x4(self.rect.0, state);
+        util::hash_f32(self.corner_radius, state);
+        util::hash_f32(self.sigma, state);
+        util::hash_transform2f(self.transform, state);
+    }
+}
+
+// The horizontal blur of the row of the rounded rectangle at `y`, which is exact.
+fn horizontal_coverage(x: f32, y: f32, sigma: f32, corner_radius: f32, half_size: Vector2F)
+                       -> f32 {
+    let delta = (half_size.y() - corner_radius - y.abs()).min(0.0);
+    let curved = half_size.x() - corner_radius +
+        (corner_radius * corner_radius - delta * delta).max(0.0).sqrt();
+    let scale = f32::sqrt(0.5) / sigma;
+    let (from, to) = (erf((x - curved) * scale), erf((x + curved) * scale));
+    0.5 * (to - from)
+}
+
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    f32::exp(-(x * x) / (2.0 * sigma * sigma)) / (f32::sqrt(2.0 * PI) * sigma)
+}
+
+// Abramowitz and Stegun 7.1.27, accurate to about 5 × 10⁻⁴.
+fn erf(x: f32) -> f32 {
+    let a = x.abs();
+    let mut y = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    y *= y;
+    (1.0 - 1.0 / (y * y)).copysign(x)
+}
+
+#[cfg(test)]
+mod test {
+    use super::BlurredRoundedRect;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+
+    #[test]
+    fn test_coverage() {
+        let shadow = BlurredRoundedRect::new(RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 50.0)),
+                                             10.0,
+                                             4.0);
+        assert_eq!(shadow.local_bounds(), RectF::new(vec2f(-12.0, -12.0), vec2f(124.0, 74.0)));
+
+        // Solid inside, clear outside, and half covered on the middle of an edge.
+        assert!(shadow.coverage(vec2f(50.0, 25.0)) > 0.99);
+        assert!(shadow.coverage(vec2f(-20.0, 25.0)) < 0.01);
+        assert!((shadow.coverage(vec2f(0.0, 25.0)) - 0.5).abs() < 0.01);
+
+        // The corner is rounded off, so it's fainter than a square corner would be.
+        let square = BlurredRoundedRect { corner_radius: 0.0, ..shadow };
+        assert!(shadow.coverage(vec2f(2.0, 2.0)) < square.coverage(vec2f(2.0, 2.0)));
+    }
+}
@@ -10,6 +10,8 @@
 
 //! Utilities for converting path strokes to fills.
 
+use crate::boolean::{self, BooleanOp};
+use crate::fill::FillRule;
 use crate::outline::{ArcDirection, Contour, ContourIterFlags, Outline, PushSegmentFlags};
 use crate::segment::Segment;
 use pathfinder_geometry::line_segment::LineSegment2F;
@@ -17,10 +19,14 @@ use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::util::EPSILON;
 use pathfinder_geometry::vector::{Vector2F, vec2f};
+use std::cmp::Ordering;
 use std::f32;
 
 const TOLERANCE: f32 = 0.01;
 
+// Below this, the edges at an `arcs` join are treated as straight.
+const CURVATURE_EPSILON: f32 = 1e-4;
+
 pub struct OutlineStrokeToFill<'a> {
     input: &'a Outline,
     output: Outline,
@@ -32,6 +38,7 @@ pub struct StrokeStyle {
     pub line_width: f32,
     pub line_cap: LineCap,
     pub line_join: LineJoin,
+    pub alignment: StrokeAlignment,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -43,9 +50,43 @@ pub enum LineCap {
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum LineJoin {
+    /// Extends the outer edges of the stroke until they meet, unless the miter would be longer
+    /// than the given limit times the line width, in which case a bevel is drawn instead.
+    ///
+    /// The length of a miter is measured from the inner corner of the join to its tip, as in SVG
+    /// and the HTML canvas.
     Miter(f32),
+    /// Like `Miter`, but miters that would be too long are cut off at the limit instead of
+    /// falling back to a bevel. This is SVG 2's `miter-clip`.
+    MiterClip(f32),
     Bevel,
     Round,
+    /// Extends the outer edges of the stroke along circles that match their curvature at the
+    /// join until they meet, and cuts the result off at the miter limit like `MiterClip`. This is
+    /// SVG 2's `arcs`.
+    ///
+    /// Where both edges are straight, this is the same as `MiterClip`. Where the circles don't
+    /// meet, this falls back to `MiterClip` as well.
+    Arcs(f32),
+}
+
+/// Where a stroke lies relative to the path it outlines.
+///
+/// Only closed contours have an inside and an outside; open contours are always stroked centered.
+/// The inside is wherever the closed contours are filled under the nonzero winding rule, whichever
+/// way each of them winds, so holes are stroked on the side away from the filled area, as they
+/// should be.
+///
+/// Inner and outer strokes are cut out of a centered stroke twice as wide with boolean
+/// operations, so they come out with their curves flattened.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StrokeAlignment {
+    /// Half the stroke lies on each side of the path, as in SVG and the HTML canvas.
+    Center,
+    /// The whole stroke lies inside the path, which it doesn't enlarge.
+    Inner,
+    /// The whole stroke lies outside the path, which it doesn't cover.
+    Outer,
 }
 
 impl<'a> OutlineStrokeToFill<'a> {
@@ -55,31 +96,39 @@ impl<'a> OutlineStrokeToFill<'a> {
     }
 
     pub fn offset(&mut self) {
+        let line_width = self.style.line_width;
+        let clip_op = match self.style.alignment {
+            StrokeAlignment::Center => None,
+            StrokeAlignment::Inner => Some(BooleanOp::Intersection),
+            StrokeAlignment::Outer => Some(BooleanOp::Difference),
+        };
+
         let mut new_contours = vec![];
+        let (mut aligned_stroke, mut inside) = (Outline::new(), Outline::new());
         for input in &self.input.contours {
-            let closed = input.closed;
-            let mut stroker = ContourStrokeToFill::new(input,
-                                                       Contour::new(),
-                                                       self.style.line_width * 0.5,
-                                                       self.style.line_join);
-
-            stroker.offset_forward();
-            if closed {
-                self.push_stroked_contour(&mut new_contours, stroker, true);
-                stroker = ContourStrokeToFill::new(input,
-                                                   Contour::new(),
-                                                   self.style.line_width * 0.5,
-                                                   self.style.line_join);
-            } else {
-                self.add_cap(&mut stroker.output);
+            // Zero-length subpaths have no direction to offset along, so they only get caps.
+            if input.is_zero_length() {
+                new_contours.extend(self.dot(input));
+                continue;
             }
 
-            stroker.offset_backward();
-            if !closed {
-                self.add_cap(&mut stroker.output);
+            if input.closed && clip_op.is_some() {
+                self.stroke_contour(input, line_width, &mut aligned_stroke.contours);
+                inside.push_contour(input.clone());
+            } else {
+                self.stroke_contour(input, line_width * 0.5, &mut new_contours);
             }
+        }
 
-            self.push_stroked_contour(&mut new_contours, stroker, closed);
+        if let Some(clip_op) = clip_op {
+            if !inside.contours.is_empty() {
+                let clipped = boolean::combine(&aligned_stroke,
+                                               FillRule::Winding,
+                                               &inside,
+                                               FillRule::Winding,
+                                               clip_op);
+                new_contours.extend(clipped.contours);
+            }
         }
 
         let mut new_bounds = None;
@@ -94,25 +143,78 @@ impl<'a> OutlineStrokeToFill<'a> {
         self.output
     }
 
-    fn push_stroked_contour(&mut self,
+    fn stroke_contour(&self, input: &Contour, radius: f32, new_contours: &mut Vec<Contour>) {
+        let closed = input.closed;
+        let mut stroker = ContourStrokeToFill::new(input,
+                                                   Contour::new(),
+                                                   radius,
+                                                   self.style.line_join);
+
+        stroker.offset_forward();
+        if closed {
+            self.push_stroked_contour(new_contours, stroker, true);
+            stroker = ContourStrokeToFill::new(input,
+                                               Contour::new(),
+                                               radius,
+                                               self.style.line_join);
+        } else {
+            self.add_cap(&mut stroker.output);
+        }
+
+        stroker.offset_backward();
+        if !closed {
+            self.add_cap(&mut stroker.output);
+        }
+
+        self.push_stroked_contour(new_contours, stroker, closed);
+    }
+
+    fn push_stroked_contour(&self,
                             new_contours: &mut Vec<Contour>,
                             mut stroker: ContourStrokeToFill,
                             closed: bool) {
         // Add join if necessary.
-        if closed && stroker.output.might_need_join(self.style.line_join) {
+        if closed && stroker.output.might_need_join() {
             let (p1, p0) = (stroker.output.position_of(1), stroker.output.position_of(0));
             let final_segment = LineSegment2F::new(p1, p0);
-            stroker.output.add_join(self.style.line_width * 0.5,
-                                    self.style.line_join,
+            let next_curvature = start_curvature(&stroker.output.segment_after(0));
+            stroker.output.add_join(stroker.radius,
+                                    stroker.join,
                                     stroker.input.position_of(0),
-                                    final_segment);
+                                    final_segment,
+                                    next_curvature);
         }
 
         stroker.output.closed = true;
         new_contours.push(stroker.output);
     }
 
-    fn add_cap(&mut self, contour: &mut Contour) {
+    // Per SVG and the HTML canvas, a zero-length subpath is drawn as a dot if it has round or
+    // square caps. There's no direction to orient a square dot along, so it's axis-aligned. A
+    // lone point that was moved to and never drawn from isn't a subpath, so it draws nothing.
+    fn dot(&self, input: &Contour) -> Option<Contour> {
+        if input.is_empty() || (input.len() < 2 && !input.closed) {
+            return None;
+        }
+
+        let center = input.position_of(0);
+        let radius = self.style.line_width * 0.5;
+        match self.style.line_cap {
+            LineCap::Butt => None,
+            LineCap::Square => {
+                let size = vec2f(self.style.line_width, self.style.line_width);
+                Some(Contour::from_rect(RectF::new(center - vec2f(radius, radius), size)))
+            }
+            LineCap::Round => {
+                let mut contour = Contour::new();
+                contour.push_ellipse(&Transform2F::from_scale(radius).translate(center));
+                contour.close();
+                Some(contour)
+            }
+        }
+    }
+
+    fn add_cap(&self, contour: &mut Contour) {
         if self.style.line_cap == LineCap::Butt || contour.len() < 2 {
             return
         }
@@ -239,7 +341,7 @@ impl Offset for Segment {
                       join_point: Vector2F,
                       contour: &mut Contour) {
         // Add join if necessary.
-        if contour.might_need_join(join) {
+        if contour.might_need_join() {
             let p3 = self.baseline.from();
             let p4 = if self.is_line() {
                 self.baseline.to()
@@ -249,7 +351,11 @@ impl Offset for Segment {
                 self.ctrl.from()
             };
 
-            contour.add_join(distance, join, join_point, LineSegment2F::new(p4, p3));
+            contour.add_join(distance,
+                             join,
+                             join_point,
+                             LineSegment2F::new(p4, p3),
+                             start_curvature(self));
         }
 
         // Push segment.
@@ -356,22 +462,27 @@ impl Offset for Segment {
 }
 
 impl Contour {
-    fn might_need_join(&self, join: LineJoin) -> bool {
-        if self.len() < 2 {
-            false
-        } else {
-            match join {
-                LineJoin::Miter(_) | LineJoin::Round => true,
-                LineJoin::Bevel => false,
-            }
+    fn is_zero_length(&self) -> bool {
+        if self.is_empty() {
+            return true;
         }
+        let first = self.position_of(0);
+        (1..self.len()).all(|point_index| {
+            (self.position_of(point_index) - first).square_length() <= EPSILON
+        })
+    }
+
+    // Every kind of join, bevels included, needs to handle the inside of corners.
+    fn might_need_join(&self) -> bool {
+        self.len() >= 2
     }
 
     fn add_join(&mut self,
                 distance: f32,
                 join: LineJoin,
                 join_point: Vector2F,
-                next_tangent: LineSegment2F) {
+                next_tangent: LineSegment2F,
+                next_curvature: f32) {
         let (p0, p1) = (self.position_of_last(2), self.position_of_last(1));
         let prev_tangent = LineSegment2F::new(p0, p1);
 
@@ -379,19 +490,41 @@ impl Contour {
             return;
         }
 
+        let corner = JoinCorner::new(join_point, prev_tangent, next_tangent);
+        if !corner.is_outer() {
+            self.add_inner_join(&corner, prev_tangent, next_tangent);
+            return;
+        }
+
         match join {
             LineJoin::Bevel => {}
             LineJoin::Miter(miter_limit) => {
-                if let Some(prev_tangent_t) = prev_tangent.intersection_t(next_tangent) {
-                    if prev_tangent_t < -EPSILON {
-                        return;
-                    }
-                    let miter_endpoint = prev_tangent.sample(prev_tangent_t);
-                    let threshold = miter_limit * distance;
-                    if (miter_endpoint - join_point).square_length() > threshold * threshold {
-                        return;
-                    }
-                    self.push_endpoint(miter_endpoint);
+                if corner.miter_is_within_limit(miter_limit) {
+                    self.add_miter(prev_tangent, next_tangent);
+                }
+            }
+            LineJoin::MiterClip(miter_limit) => {
+                self.add_miter_clip(distance, miter_limit, &corner, prev_tangent, next_tangent);
+            }
+            LineJoin::Arcs(miter_limit) => {
+                let prev_segment = self.segment_after(self.prev_endpoint_index_of(self.len() - 1));
+                let prev_edge = JoinEdge {
+                    point: corner.prev_point,
+                    direction: corner.prev_direction,
+                    curvature: end_curvature(&prev_segment),
+                };
+                let next_edge = JoinEdge {
+                    point: corner.next_point,
+                    direction: corner.next_direction,
+                    curvature: next_curvature,
+                };
+                if prev_edge.is_straight() && next_edge.is_straight() {
+                    self.add_miter_clip(distance, miter_limit, &corner, prev_tangent, next_tangent);
+                    return;
+                }
+                let clip_distance = miter_limit * distance.abs();
+                if !self.add_arcs(clip_distance, &corner, &prev_edge, &next_edge) {
+                    self.add_miter_clip(distance, miter_limit, &corner, prev_tangent, next_tangent);
                 }
             }
             LineJoin::Round => {
@@ -404,6 +537,297 @@ impl Contour {
             }
         }
     }
+
+    // On the inside of a corner, the offset segments overlap. Where they cross, the edge of the
+    // stroke can turn at the crossing. Where the stroke is too wide for them to, it has to go
+    // back through the join point instead: cutting straight across would leave the edge turned
+    // inside out, and the nonzero fill would then leave a hole in the stroke.
+    fn add_inner_join(&mut self,
+                      corner: &JoinCorner,
+                      prev_tangent: LineSegment2F,
+                      next_tangent: LineSegment2F) {
+        if (corner.prev_point - corner.next_point).square_length() < EPSILON {
+            return;
+        }
+        let crossing_t = prev_tangent.intersection_t(next_tangent)
+                                     .zip(next_tangent.intersection_t(prev_tangent));
+        match crossing_t {
+            Some((prev_t, next_t)) if (0.0..=1.0).contains(&prev_t) &&
+                    (0.0..=1.0).contains(&next_t) => {
+                self.push_endpoint(prev_tangent.sample(prev_t));
+            }
+            _ => self.push_endpoint(corner.join_point),
+        }
+    }
+
+    fn add_miter(&mut self, prev_tangent: LineSegment2F, next_tangent: LineSegment2F) {
+        if let Some(prev_tangent_t) = prev_tangent.intersection_t(next_tangent) {
+            if prev_tangent_t >= -EPSILON {
+                self.push_endpoint(prev_tangent.sample(prev_tangent_t));
+            }
+        }
+    }
+
+    // Per SVG 2, a miter that's too long is cut off by a line perpendicular to the bisector of
+    // the join, at the miter limit times half the line width from the join point.
+    fn add_miter_clip(&mut self,
+                      distance: f32,
+                      miter_limit: f32,
+                      corner: &JoinCorner,
+                      prev_tangent: LineSegment2F,
+                      next_tangent: LineSegment2F) {
+        if corner.miter_is_within_limit(miter_limit) {
+            self.add_miter(prev_tangent, next_tangent);
+            return;
+        }
+
+        let clip_distance = miter_limit * distance.abs();
+        let axis = corner.bisector();
+        let prev_offset = clip_distance - (corner.prev_point - corner.join_point).dot(axis);
+        let next_offset = clip_distance - (corner.next_point - corner.join_point).dot(axis);
+        if prev_offset <= 0.0 || next_offset <= 0.0 {
+            return;
+        }
+
+        let prev_direction = corner.prev_direction;
+        let next_direction = -corner.next_direction;
+        self.push_endpoint(corner.prev_point +
+                           prev_direction * (prev_offset / prev_direction.dot(axis)));
+        self.push_endpoint(corner.next_point +
+                           next_direction * (next_offset / next_direction.dot(axis)));
+    }
+
+    // Returns false if the extended edges don't meet, in which case nothing is added.
+    fn add_arcs(&mut self,
+                clip_distance: f32,
+                corner: &JoinCorner,
+                prev_edge: &JoinEdge,
+                next_edge: &JoinEdge)
+                -> bool {
+        // The edges must meet past the ends of both of them, on the outside of the corner.
+        let axis = corner.bisector();
+        let tips = prev_edge.intersections(next_edge);
+        let tips = tips.iter().filter_map(|&point| point).filter(|&point| {
+            (point - corner.prev_point).dot(corner.prev_direction) >= -EPSILON &&
+                (corner.next_point - point).dot(corner.next_direction) >= -EPSILON &&
+                (point - corner.join_point).dot(axis) > 0.0
+        });
+        let tip = match nearest_point(tips, corner.join_point) {
+            None => return false,
+            Some(tip) => tip,
+        };
+
+        if (tip - corner.join_point).square_length() <= clip_distance * clip_distance {
+            self.push_join_edge(prev_edge, corner.prev_point, tip);
+            self.push_join_edge(next_edge, tip, corner.next_point);
+            return true;
+        }
+
+        // Cut the join off perpendicular to the line from the join point to the tip.
+        let axis = (tip - corner.join_point).normalize();
+        let clip_point = corner.join_point + axis * clip_distance;
+        let clip_line = JoinEdge {
+            point: clip_point,
+            direction: perpendicular(axis),
+            curvature: 0.0,
+        };
+        let prev_clip_point = prev_edge.nearest_intersection(&clip_line, corner.prev_point);
+        let next_clip_point = next_edge.nearest_intersection(&clip_line, corner.next_point);
+        if let (Some(prev_clip_point), Some(next_clip_point)) = (prev_clip_point, next_clip_point) {
+            if (corner.prev_point - corner.join_point).dot(axis) < clip_distance &&
+                    (corner.next_point - corner.join_point).dot(axis) < clip_distance {
+                self.push_join_edge(prev_edge, corner.prev_point, prev_clip_point);
+                self.push_join_edge(next_edge, next_clip_point, corner.next_point);
+            }
+        }
+        true
+    }
+
+    fn push_join_edge(&mut self, edge: &JoinEdge, from: Vector2F, to: Vector2F) {
+        if edge.is_straight() {
+            self.push_endpoint(to);
+            return;
+        }
+
+        let (center, radius) = (edge.center(), edge.radius());
+        let chord = LineSegment2F::new((from - center).normalize(), (to - center).normalize());
+
+        // `push_arc_from_unit_chord()` collapses arcs this short to a point. They're usually on
+        // nearly straight edges with huge radii, where that point can be far from both ends.
+        if chord.from().dot(chord.to()) >= 1.0 - EPSILON {
+            let middle = (chord.from() + chord.to()).normalize();
+            let ctrl = center + middle * (radius / chord.from().dot(middle));
+            self.push_quadratic(ctrl, to);
+            return;
+        }
+
+        let transform = Transform2F::from_scale(radius).translate(center);
+        let direction = if edge.curvature > 0.0 { ArcDirection::CW } else { ArcDirection::CCW };
+        self.push_arc_from_unit_chord(&transform, chord, direction);
+    }
+}
+
+// The corner that a join fills in, between the end of the previous offset segment and the start
+// of the next.
+struct JoinCorner {
+    join_point: Vector2F,
+    prev_point: Vector2F,
+    prev_direction: Vector2F,
+    next_point: Vector2F,
+    next_direction: Vector2F,
+}
+
+impl JoinCorner {
+    fn new(join_point: Vector2F, prev_tangent: LineSegment2F, next_tangent: LineSegment2F)
+           -> JoinCorner {
+        // `next_tangent` points backward, toward the join.
+        JoinCorner {
+            join_point,
+            prev_point: prev_tangent.to(),
+            prev_direction: prev_tangent.vector().normalize(),
+            next_point: next_tangent.to(),
+            next_direction: -next_tangent.vector().normalize(),
+        }
+    }
+
+    // The ratio of the length of a miter to the line width is 1 / sin(θ / 2), where θ is the
+    // angle between the segments. Testing the angle instead of measuring the miter keeps the
+    // cutoff exact even when the miter is nearly infinitely long.
+    fn miter_is_within_limit(&self, miter_limit: f32) -> bool {
+        let cos_theta = -self.prev_direction.dot(self.next_direction);
+        miter_limit * miter_limit * (1.0 - cos_theta) >= 2.0
+    }
+
+    // Joins only grow on the outside of a corner. Where the path doubles back on itself, both
+    // sides are the outside.
+    fn is_outer(&self) -> bool {
+        let turn = self.prev_direction.det(self.next_direction);
+        let side = self.prev_direction.det(self.prev_point - self.join_point);
+        turn * side <= 0.0 || self.doubles_back()
+    }
+
+    fn doubles_back(&self) -> bool {
+        self.prev_direction.dot(self.next_direction) <= EPSILON - 1.0
+    }
+
+    // The direction from the join point toward the tip of the miter.
+    fn bisector(&self) -> Vector2F {
+        if self.doubles_back() {
+            self.prev_direction
+        } else {
+            (self.prev_direction - self.next_direction).normalize()
+        }
+    }
+}
+
+// An outer edge of the stroke at an `arcs` join, extended past the end of its segment along the
+// circle of its curvature there, or along its tangent if it's straight. Curvature is positive
+// where the edge turns toward increasing angles.
+struct JoinEdge {
+    point: Vector2F,
+    direction: Vector2F,
+    curvature: f32,
+}
+
+impl JoinEdge {
+    fn is_straight(&self) -> bool {
+        self.curvature.abs() < CURVATURE_EPSILON
+    }
+
+    fn center(&self) -> Vector2F {
+        self.point + perpendicular(self.direction) * (1.0 / self.curvature)
+    }
+
+    fn radius(&self) -> f32 {
+        1.0 / self.curvature.abs()
+    }
+
+    fn intersections(&self, other: &JoinEdge) -> [Option<Vector2F>; 2] {
+        match (self.is_straight(), other.is_straight()) {
+            (true, true) => {
+                let det = self.direction.det(other.direction);
+                if det.abs() < EPSILON {
+                    return [None, None];
+                }
+                let t = (other.point - self.point).det(other.direction) / det;
+                [Some(self.point + self.direction * t), None]
+            }
+            (true, false) => line_circle_intersections(self, other.center(), other.radius()),
+            (false, true) => line_circle_intersections(other, self.center(), self.radius()),
+            (false, false) => {
+                let (center_0, radius_0) = (self.center(), self.radius());
+                let (center_1, radius_1) = (other.center(), other.radius());
+                let vector = center_1 - center_0;
+                let distance = vector.length();
+                if distance < EPSILON {
+                    return [None, None];
+                }
+                let along = (distance * distance + radius_0 * radius_0 - radius_1 * radius_1) /
+                    (2.0 * distance);
+                let square_across = radius_0 * radius_0 - along * along;
+                if square_across < 0.0 {
+                    return [None, None];
+                }
+                let direction = vector * (1.0 / distance);
+                let midpoint = center_0 + direction * along;
+                let across = perpendicular(direction) * square_across.sqrt();
+                [Some(midpoint + across), Some(midpoint - across)]
+            }
+        }
+    }
+
+    fn nearest_intersection(&self, other: &JoinEdge, point: Vector2F) -> Option<Vector2F> {
+        nearest_point(self.intersections(other).iter().filter_map(|&intersection| intersection),
+                      point)
+    }
+}
+
+fn nearest_point<I>(points: I, to: Vector2F) -> Option<Vector2F>
+                    where I: Iterator<Item = Vector2F> {
+    points.min_by(|&a, &b| {
+        let (a_distance, b_distance) = ((a - to).square_length(), (b - to).square_length());
+        a_distance.partial_cmp(&b_distance).unwrap_or(Ordering::Equal)
+    })
+}
+
+// Rotates a vector a quarter turn toward increasing angles.
+#[inline]
+fn perpendicular(vector: Vector2F) -> Vector2F {
+    vector.yx() * vec2f(-1.0, 1.0)
+}
+
+fn line_circle_intersections(line: &JoinEdge, center: Vector2F, radius: f32)
+                             -> [Option<Vector2F>; 2] {
+    // Solve |point + direction * t - center|² = radius² for t.
+    let vector = line.point - center;
+    let b = line.direction.dot(vector);
+    let discriminant = b * b - vector.square_length() + radius * radius;
+    if discriminant < 0.0 {
+        return [None, None];
+    }
+    let root = discriminant.sqrt();
+    [Some(line.point + line.direction * (root - b)), Some(line.point - line.direction * (root + b))]
+}
+
+// The signed curvature at the start of a segment. Lines have none.
+fn start_curvature(segment: &Segment) -> f32 {
+    let (p0, p1, p2, scale) = if segment.is_quadratic() {
+        (segment.baseline.from(), segment.ctrl.from(), segment.baseline.to(), 0.5)
+    } else if segment.is_cubic() {
+        (segment.baseline.from(), segment.ctrl.from(), segment.ctrl.to(), 2.0 / 3.0)
+    } else {
+        return 0.0;
+    };
+    let (v0, v1) = (p1 - p0, p2 - p1);
+    let length = v0.length();
+    if length < EPSILON {
+        return 0.0;
+    }
+    scale * v0.det(v1) / (length * length * length)
+}
+
+fn end_curvature(segment: &Segment) -> f32 {
+    -start_curvature(&segment.reversed())
 }
 
 impl Default for StrokeStyle {
@@ -413,6 +837,7 @@ impl Default for StrokeStyle {
             line_width: 1.0,
             line_cap: LineCap::default(),
             line_join: LineJoin::default(),
+            alignment: StrokeAlignment::default(),
         }
     }
 }
@@ -426,3 +851,212 @@ impl Default for LineJoin {
     #[inline]
     fn default() -> LineJoin { LineJoin::Miter(10.0) }
 }
+
+impl Default for StrokeAlignment {
+    #[inline]
+    fn default() -> StrokeAlignment { StrokeAlignment::Center }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LineCap, LineJoin, OutlineStrokeToFill, StrokeAlignment, StrokeStyle};
+    use crate::fill::FillRule;
+    use crate::outline::{Contour, Outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+    use std::f32;
+
+    fn stroke_bounds(outline: &Outline, alignment: StrokeAlignment) -> RectF {
+        let style = StrokeStyle {
+            line_width: 2.0,
+            line_join: LineJoin::Miter(10.0),
+            alignment,
+            ..StrokeStyle::default()
+        };
+        let mut stroke_to_fill = OutlineStrokeToFill::new(outline, style);
+        stroke_to_fill.offset();
+        stroke_to_fill.into_outline().bounds()
+    }
+
+    #[test]
+    fn test_alignment() {
+        // Both windings, since which side is inside depends on it.
+        for &clockwise in &[true, false] {
+            let rect = RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0));
+            let mut contour = Contour::from_rect(rect);
+            if !clockwise {
+                let points: Vec<_> = (0..4).rev().map(|index| contour.position_of(index))
+                                               .collect();
+                contour = Contour::new();
+                points.into_iter().for_each(|point| contour.push_endpoint(point));
+                contour.close();
+            }
+            let mut outline = Outline::new();
+            outline.push_contour(contour);
+
+            assert_eq!(stroke_bounds(&outline, StrokeAlignment::Center),
+                       RectF::new(vec2f(-1.0, -1.0), vec2f(12.0, 12.0)));
+            assert_eq!(stroke_bounds(&outline, StrokeAlignment::Inner), rect);
+            assert_eq!(stroke_bounds(&outline, StrokeAlignment::Outer),
+                       RectF::new(vec2f(-2.0, -2.0), vec2f(14.0, 14.0)));
+        }
+    }
+
+    fn stroke_area(outline: &Outline, line_width: f32, alignment: StrokeAlignment)
+                   -> (RectF, f32) {
+        let style = StrokeStyle { line_width, alignment, ..StrokeStyle::default() };
+        let mut stroke_to_fill = OutlineStrokeToFill::new(outline, style);
+        stroke_to_fill.offset();
+        let stroke = stroke_to_fill.into_outline();
+        (stroke.bounds(), stroke.area(FillRule::Winding))
+    }
+
+    #[test]
+    fn test_strokes_wider_than_the_shape() {
+        let rect = RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0));
+        let outline = Outline::from_rect(rect);
+
+        // The inner edges of the stroke cross over, and mustn't leave a hole where they do.
+        assert_eq!(stroke_area(&outline, 40.0, StrokeAlignment::Center),
+                   (RectF::new(vec2f(-20.0, -20.0), vec2f(50.0, 50.0)), 2500.0));
+        assert_eq!(stroke_area(&outline, 20.0, StrokeAlignment::Inner), (rect, 100.0));
+        assert_eq!(stroke_area(&outline, 20.0, StrokeAlignment::Outer),
+                   (RectF::new(vec2f(-20.0, -20.0), vec2f(50.0, 50.0)), 2400.0));
+
+        // The same goes for bevels, which add nothing on the outside of the corners.
+        let style = StrokeStyle {
+            line_width: 40.0,
+            line_join: LineJoin::Bevel,
+            ..StrokeStyle::default()
+        };
+        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, style);
+        stroke_to_fill.offset();
+        assert_eq!(stroke_to_fill.into_outline().area(FillRule::Winding), 2500.0 - 4.0 * 200.0);
+    }
+
+    #[test]
+    fn test_alignment_of_reversed_contours() {
+        // Two squares winding opposite ways.
+        let mut outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+        let mut reversed = Contour::new();
+        for &point in &[vec2f(20.0, 0.0), vec2f(20.0, 10.0), vec2f(30.0, 10.0), vec2f(30.0, 0.0)] {
+            reversed.push_endpoint(point);
+        }
+        reversed.close();
+        outline.push_contour(reversed);
+
+        assert_eq!(stroke_area(&outline, 2.0, StrokeAlignment::Inner),
+                   (RectF::new(vec2f(0.0, 0.0), vec2f(30.0, 10.0)), 2.0 * 64.0));
+        assert_eq!(stroke_area(&outline, 2.0, StrokeAlignment::Outer),
+                   (RectF::new(vec2f(-2.0, -2.0), vec2f(34.0, 14.0)), 2.0 * 96.0));
+
+        // A ring whose hole winds the other way is stroked inside the ring on both edges.
+        let mut ring = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+        let mut hole = Contour::new();
+        for &point in &[vec2f(3.0, 3.0), vec2f(3.0, 7.0), vec2f(7.0, 7.0), vec2f(7.0, 3.0)] {
+            hole.push_endpoint(point);
+        }
+        hole.close();
+        ring.push_contour(hole);
+        assert_eq!(stroke_area(&ring, 1.0, StrokeAlignment::Inner),
+                   (RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)), 36.0 + 20.0));
+    }
+    #[test]
+    fn test_zero_length_subpaths() {
+        let stroke = |contour: &Contour, line_cap| {
+            let mut outline = Outline::new();
+            outline.push_contour(contour.clone());
+            let style = StrokeStyle { line_width: 2.0, line_cap, ..StrokeStyle::default() };
+            let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, style);
+            stroke_to_fill.offset();
+            stroke_to_fill.into_outline()
+        };
+
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(5.0, 5.0));
+        contour.push_endpoint(vec2f(5.0, 5.0));
+        let dot_bounds = RectF::new(vec2f(4.0, 4.0), vec2f(2.0, 2.0));
+        assert!(stroke(&contour, LineCap::Butt).contours().is_empty());
+        assert_eq!(stroke(&contour, LineCap::Square).bounds(), dot_bounds);
+        // The bounds of curves include their control points, so round dots come out a bit larger.
+        let round_dot_bounds = stroke(&contour, LineCap::Round).bounds();
+        assert!(round_dot_bounds.contains_rect(dot_bounds));
+        assert!(dot_bounds.dilate(0.25).contains_rect(round_dot_bounds));
+
+        // A point that's only moved to isn't drawn, unless it's closed.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(5.0, 5.0));
+        assert!(stroke(&contour, LineCap::Round).contours().is_empty());
+        contour.close();
+        assert_eq!(stroke(&contour, LineCap::Square).bounds(), dot_bounds);
+    }
+
+    fn stroke_join(contour: &Contour, line_join: LineJoin) -> RectF {
+        let mut outline = Outline::new();
+        outline.push_contour(contour.clone());
+        let style = StrokeStyle { line_width: 2.0, line_join, ..StrokeStyle::default() };
+        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, style);
+        stroke_to_fill.offset();
+        stroke_to_fill.into_outline().bounds()
+    }
+
+    #[test]
+    fn test_miter_limit() {
+        // A right angle, whose miter is exactly √2 times the line width long.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, -10.0));
+        contour.push_endpoint(vec2f(10.0, 0.0));
+        contour.push_endpoint(vec2f(0.0, 10.0));
+        let miter_x = 10.0 + f32::consts::SQRT_2;
+        let bevel_x = 10.0 + f32::consts::FRAC_1_SQRT_2;
+        let limit = f32::consts::SQRT_2;
+        let max_x = stroke_join(&contour, LineJoin::Miter(limit + 0.001)).max_x();
+        assert!((max_x - miter_x).abs() < 0.001);
+        let max_x = stroke_join(&contour, LineJoin::Miter(limit - 0.001)).max_x();
+        assert!((max_x - bevel_x).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_miter_clip_join() {
+        // A sharp V, whose miter is 10.05 times the line width long.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, -10.0));
+        contour.push_endpoint(vec2f(100.0, 0.0));
+        contour.push_endpoint(vec2f(0.0, 10.0));
+        let miter_x = 100.0 + f32::sqrt(101.0);
+        let bevel_x = 100.0 + f32::sqrt(1.0 / 101.0);
+
+        let max_x = stroke_join(&contour, LineJoin::Miter(4.0)).max_x();
+        assert!((max_x - bevel_x).abs() < 0.001);
+        let max_x = stroke_join(&contour, LineJoin::MiterClip(4.0)).max_x();
+        assert!((max_x - 104.0).abs() < 0.001);
+        for &line_join in &[LineJoin::Miter(20.0), LineJoin::MiterClip(20.0)] {
+            assert!((stroke_join(&contour, line_join).max_x() - miter_x).abs() < 0.001);
+        }
+
+        // Between straight edges, `arcs` joins are `miter-clip` joins.
+        for &miter_limit in &[4.0, 20.0] {
+            assert_eq!(stroke_join(&contour, LineJoin::Arcs(miter_limit)),
+                       stroke_join(&contour, LineJoin::MiterClip(miter_limit)));
+        }
+    }
+
+    #[test]
+    fn test_arcs_join() {
+        // A lens, whose sides curve toward each other at its tip, so the arcs meet short of where
+        // the miter would.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, -2.0));
+        contour.push_quadratic(vec2f(6.0, -2.0), vec2f(10.0, 0.0));
+        contour.push_quadratic(vec2f(6.0, 2.0), vec2f(0.0, 2.0));
+
+        let bevel_x = stroke_join(&contour, LineJoin::Bevel).max_x();
+        let miter_x = stroke_join(&contour, LineJoin::Miter(10.0)).max_x();
+        let arcs_x = stroke_join(&contour, LineJoin::Arcs(10.0)).max_x();
+        assert!(arcs_x > bevel_x + 1.0 && arcs_x < miter_x - 0.1);
+
+        // Cut off at the limit, like `miter-clip`.
+        let arcs_x = stroke_join(&contour, LineJoin::Arcs(1.5)).max_x();
+        assert!((arcs_x - 11.5).abs() < 0.001);
+    }
+}
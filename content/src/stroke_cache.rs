@@ -0,0 +1,322 @@
+// pathfinder/content/src/stroke_cache.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A cache of stroke-to-fill conversions, for scenes that stroke the same paths every frame.
+
+use crate::lru::LruMap;
+use crate::outline::{Contour, Outline};
+use crate::stroke::{LineCap, LineJoin, StrokeAlignment, StrokeStyle};
+use pathfinder_geometry::transform2d::Matrix2x2F;
+use pathfinder_geometry::vector::Vector2F;
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const DEFAULT_STROKE_CACHE_BYTE_CAPACITY: usize = 16 * 1024 * 1024;
+
+static NEXT_STROKE_SOURCE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Identifies the contents of a path whose stroke may be cached.
+///
+/// Every call to `StrokeSourceId::new()` returns a different ID. Owners of paths that can change
+/// must give them a new ID whenever they do, so that strokes of the old contents are never
+/// returned. This is unrelated to the IDs of paths in a scene.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct StrokeSourceId(u64);
+
+/// Everything that the stroke of a path depends on.
+///
+/// Strokes are computed to a fixed tolerance in the coordinate space of the path, so they don't
+/// depend on how the result will be transformed afterward. A key only needs a transform if the
+/// path is transformed *before* it's stroked, as glyph outlines are.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct StrokeCacheKey {
+    path: StrokeSourceId,
+    line_width: u32,
+    line_cap: u8,
+    line_join: (u8, u32),
+    alignment: u8,
+    transform: [u32; 4],
+    dash: Vec<u32>,
+    dash_offset: u32,
+}
+
+/// A least-recently-used cache of the outlines of strokes, bounded by the memory they use.
+pub struct StrokeCache {
+    entries: LruMap<StrokeCacheKey, StrokeCacheEntry>,
+    byte_capacity: usize,
+    byte_size: usize,
+}
+
+struct StrokeCacheEntry {
+    outline: Outline,
+    byte_size: usize,
+}
+
+impl StrokeSourceId {
+    #[inline]
+    pub fn new() -> StrokeSourceId {
+        StrokeSourceId(NEXT_STROKE_SOURCE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for StrokeSourceId {
+    #[inline]
+    fn default() -> StrokeSourceId {
+        StrokeSourceId::new()
+    }
+}
+
+impl StrokeCacheKey {
+    /// A key for the stroke of the given path with the given style, undashed.
+    pub fn new(path: StrokeSourceId, style: &StrokeStyle) -> StrokeCacheKey {
+        let line_join = match style.line_join {
+            LineJoin::Miter(limit) => (0, limit.to_bits()),
+            LineJoin::Bevel => (1, 0),
+            LineJoin::Round => (2, 0),
+            LineJoin::MiterClip(limit) => (3, limit.to_bits()),
+            LineJoin::Arcs(limit) => (4, limit.to_bits()),
+        };
+        StrokeCacheKey {
+            path,
+            line_width: style.line_width.to_bits(),
+            line_cap: match style.line_cap {
+                LineCap::Butt => 0,
+                LineCap::Square => 1,
+                LineCap::Round => 2,
+            },
+            line_join,
+            alignment: match style.alignment {
+                StrokeAlignment::Center => 0,
+                StrokeAlignment::Inner => 1,
+                StrokeAlignment::Outer => 2,
+            },
+            transform: bits(Matrix2x2F::default()),
+            dash: vec![],
+            dash_offset: 0,
+        }
+    }
+
+    /// Makes this the key for the stroke of the path after it's transformed by `matrix`.
+    #[inline]
+    pub fn with_transform(mut self, matrix: Matrix2x2F) -> StrokeCacheKey {
+        self.transform = bits(matrix);
+        self
+    }
+
+    /// Makes this the key for the stroke of the path after it's dashed.
+    #[inline]
+    pub fn with_dash(mut self, dash: &[f32], offset: f32) -> StrokeCacheKey {
+        self.dash = dash.iter().map(|length| length.to_bits()).collect();
+        self.dash_offset = offset.to_bits();
+        self
+    }
+
+    #[inline]
+    pub fn path(&self) -> StrokeSourceId {
+        self.path
+    }
+}
+
+impl StrokeCache {
+    #[inline]
+    pub fn new() -> StrokeCache {
+        StrokeCache::with_byte_capacity(DEFAULT_STROKE_CACHE_BYTE_CAPACITY)
+    }
+
+    /// Creates a cache whose outlines use at most about `byte_capacity` bytes.
+    #[inline]
+    pub fn with_byte_capacity(byte_capacity: usize) -> StrokeCache {
+        StrokeCache { entries: LruMap::new(), byte_capacity, byte_size: 0 }
+    }
+
+    #[inline]
+    pub fn byte_capacity(&self) -> usize {
+        self.byte_capacity
+    }
+
+    /// The approximate number of bytes that the cached outlines use.
+    #[inline]
+    pub fn byte_size(&self) -> usize {
+        self.byte_size
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Changes the memory budget, evicting the least recently used strokes if necessary.
+    pub fn set_byte_capacity(&mut self, new_byte_capacity: usize) {
+        self.byte_capacity = new_byte_capacity;
+        while self.byte_size > self.byte_capacity {
+            self.evict_least_recently_used();
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.byte_size = 0;
+    }
+
+    /// Removes all cached strokes of the given path.
+    pub fn invalidate_path(&mut self, path: StrokeSourceId) {
+        let mut freed_byte_size = 0;
+        self.entries.retain(|key, entry| {
+            if key.path != path {
+                return true;
+            }
+            freed_byte_size += entry.byte_size;
+            false
+        });
+        self.byte_size -= freed_byte_size;
+    }
+
+    pub fn get(&mut self, key: &StrokeCacheKey) -> Option<Outline> {
+        self.entries.get(key).map(|entry| entry.outline.clone())
+    }
+
+    /// Adds a stroke to the cache. Outlines too big to fit in the cache at all aren't added.
+    pub fn insert(&mut self, key: StrokeCacheKey, outline: Outline) {
+        let byte_size = outline_byte_size(&outline);
+        if byte_size > self.byte_capacity {
+            return;
+        }
+
+        if let Some(old_entry) = self.entries.remove(&key) {
+            self.byte_size -= old_entry.byte_size;
+        }
+        while self.byte_size + byte_size > self.byte_capacity {
+            self.evict_least_recently_used();
+        }
+
+        self.byte_size += byte_size;
+        self.entries.insert(key, StrokeCacheEntry { outline, byte_size });
+    }
+
+    /// Returns the cached stroke for `key`, calling `stroke` to compute and cache it if there is
+    /// none.
+    pub fn get_or_insert_with<F>(&mut self, key: StrokeCacheKey, stroke: F) -> Outline
+                                 where F: FnOnce() -> Outline {
+        if let Some(outline) = self.get(&key) {
+            return outline;
+        }
+        let outline = stroke();
+        self.insert(key, outline.clone());
+        outline
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        if let Some((_, entry)) = self.entries.pop_least_recently_used() {
+            self.byte_size -= entry.byte_size;
+        }
+    }
+}
+
+impl Default for StrokeCache {
+    #[inline]
+    fn default() -> StrokeCache {
+        StrokeCache::new()
+    }
+}
+
+fn bits(matrix: Matrix2x2F) -> [u32; 4] {
+    [matrix.0[0].to_bits(), matrix.0[1].to_bits(), matrix.0[2].to_bits(), matrix.0[3].to_bits()]
+}
+
+fn outline_byte_size(outline: &Outline) -> usize {
+    let point_size = mem::size_of::<Vector2F>() + 1;
+    mem::size_of::<Outline>() + outline.contours().iter().map(|contour| {
+        mem::size_of::<Contour>() + contour.len() as usize * point_size
+    }).sum::<usize>()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{Contour, Outline};
+    use crate::stroke::{LineCap, LineJoin, StrokeAlignment, StrokeStyle};
+    use pathfinder_geometry::transform2d::Matrix2x2F;
+    use pathfinder_geometry::vector::vec2f;
+    use super::{StrokeCache, StrokeCacheKey, StrokeSourceId, outline_byte_size};
+
+    fn line(length: f32) -> Outline {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(length, 0.0));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline
+    }
+
+    const STYLE: StrokeStyle = StrokeStyle {
+        line_width: 2.0,
+        line_cap: LineCap::Butt,
+        line_join: LineJoin::Miter(10.0),
+        alignment: StrokeAlignment::Center,
+    };
+
+    #[test]
+    fn test_keys() {
+        let path = StrokeSourceId::new();
+        assert_ne!(path, StrokeSourceId::new());
+
+        let key = StrokeCacheKey::new(path, &STYLE);
+        assert_eq!(key, StrokeCacheKey::new(path, &STYLE).with_transform(Matrix2x2F::default()));
+        assert_ne!(key, StrokeCacheKey::new(StrokeSourceId::new(), &STYLE));
+        assert_ne!(key, StrokeCacheKey::new(path, &StrokeStyle { line_width: 3.0, ..STYLE }));
+        assert_ne!(key, StrokeCacheKey::new(path, &STYLE).with_dash(&[1.0, 2.0], 0.0));
+        assert_ne!(key,
+                   StrokeCacheKey::new(path, &STYLE).with_transform(Matrix2x2F::from_scale(2.0)));
+    }
+
+    #[test]
+    fn test_eviction_and_invalidation() {
+        let entry_size = outline_byte_size(&line(1.0));
+        let mut cache = StrokeCache::with_byte_capacity(entry_size * 2);
+        let paths = [StrokeSourceId::new(), StrokeSourceId::new(), StrokeSourceId::new()];
+        let keys: Vec<_> = paths.iter().map(|&path| StrokeCacheKey::new(path, &STYLE)).collect();
+
+        let mut stroke_count = 0;
+        for key in &keys[0..2] {
+            cache.get_or_insert_with(key.clone(), || { stroke_count += 1; line(1.0) });
+        }
+        assert_eq!(stroke_count, 2);
+
+        // Touch the first entry, so that the second is evicted to make room for the third.
+        assert!(cache.get(&keys[0]).is_some());
+        cache.get_or_insert_with(keys[2].clone(), || { stroke_count += 1; line(1.0) });
+        assert_eq!(stroke_count, 3);
+        assert!(cache.get(&keys[1]).is_none());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.byte_size(), entry_size * 2);
+
+        cache.invalidate_path(paths[0]);
+        assert!(cache.get(&keys[0]).is_none());
+        assert_eq!(cache.byte_size(), entry_size);
+
+        // Outlines bigger than the whole cache aren't kept.
+        cache.insert(StrokeCacheKey::new(StrokeSourceId::new(), &STYLE), {
+            let mut outline = line(1.0);
+            outline.push_contour(line(2.0).into_contours().pop().unwrap());
+            outline.push_contour(line(3.0).into_contours().pop().unwrap());
+            outline
+        });
+        assert_eq!(cache.len(), 1);
+
+        cache.set_byte_capacity(0);
+        assert!(cache.is_empty());
+        assert_eq!(cache.byte_size(), 0);
+    }
+}
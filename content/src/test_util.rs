@@ -0,0 +1,42 @@
+// pathfinder/content/src/test_util.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Fixtures shared by the unit tests of this crate.
+
+use crate::outline::{Contour, Outline};
+use pathfinder_geometry::vector::{Vector2F, vec2f};
+
+/// Returns a closed square contour, clockwise with Y pointing down if `clockwise` is set.
+pub(crate) fn square(origin: Vector2F, size: f32, clockwise: bool) -> Contour {
+    let mut points = vec![origin,
+                          origin + vec2f(size, 0.0),
+                          origin + vec2f(size, size),
+                          origin + vec2f(0.0, size)];
+    if !clockwise {
+        points.reverse();
+    }
+    let mut contour = Contour::new();
+    points.into_iter().for_each(|point| contour.push_endpoint(point));
+    contour.close();
+    contour
+}
+
+/// Returns an outline made of a single clockwise square.
+pub(crate) fn square_outline(origin: Vector2F, size: f32) -> Outline {
+    let mut outline = Outline::new();
+    outline.push_contour(square(origin, size, true));
+    outline
+}
+
+/// Returns the total signed area of an outline's contours, positive for clockwise contours with Y
+/// pointing down.
+pub(crate) fn signed_area(outline: &Outline) -> f32 {
+    outline.contours().iter().map(Contour::signed_area).sum()
+}
@@ -14,7 +14,6 @@ use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_simd::default::{F32x2, F32x4};
 use std::hash::{Hash, Hasher};
-use std::mem;
 
 pub(crate) fn hash_line_segment<H>(line_segment: LineSegment2F, state: &mut H) where H: Hasher {
     hash_f32x4(line_segment.0, state);
@@ -26,22 +25,14 @@ pub(crate) fn hash_transform2f<H>(transform: Transform2F, state: &mut H) where H
 }
 
 pub(crate) fn hash_f32<H>(value: f32, state: &mut H) where H: Hasher {
-    unsafe {
-        let data: u32 = mem::transmute::<f32, u32>(value);
-        data.hash(state);
-    }
+    value.to_bits().hash(state);
 }
 
 pub(crate) fn hash_f32x2<H>(vector: F32x2, state: &mut H) where H: Hasher {
-    unsafe {
-        let data: [u32; 2] = mem::transmute::<F32x2, [u32; 2]>(vector);
-        data.hash(state);
-    }
+    [vector[0].to_bits(), vector[1].to_bits()].hash(state);
 }
 
 pub(crate) fn hash_f32x4<H>(vector: F32x4, state: &mut H) where H: Hasher {
-    unsafe {
-        let data: [u32; 4] = mem::transmute::<F32x4, [u32; 4]>(vector);
-        data.hash(state);
-    }
+    let bits = [vector[0].to_bits(), vector[1].to_bits(), vector[2].to_bits(), vector[3].to_bits()];
+    bits.hash(state);
 }
@@ -0,0 +1,207 @@
+// pathfinder/content/src/yuv.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! YUV video frames as patterns.
+//!
+//! The planes of a frame are interleaved into a single image, with Y, U, and V in the red, green,
+//! and blue channels, and the conversion to RGB happens in the tile shader when the pattern is
+//! drawn. Only 4:2:0 chroma subsampling is supported, which covers the output of nearly all video
+//! decoders.
+
+use crate::effects::PatternFilter;
+use crate::pattern::{Image, Pattern};
+use pathfinder_color::ColorU;
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_simd::default::F32x4;
+use std::sync::Arc;
+
+/// The standard that defines how YUV values relate to RGB.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum YuvStandard {
+    /// ITU-R BT.601, used by standard-definition video.
+    Bt601,
+    /// ITU-R BT.709, used by high-definition video.
+    Bt709,
+}
+
+/// The range of the encoded values.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum YuvRange {
+    /// Y ranges over 16–235 and U and V over 16–240, as in most video.
+    Limited,
+    /// All values range over 0–255, as in JPEG.
+    Full,
+}
+
+/// The planes of a YUV frame.
+#[derive(Clone, Copy, Debug)]
+pub enum YuvPlanes<'a> {
+    /// Separate Y, U, and V planes, as in I420.
+    Planar { y: &'a [u8], u: &'a [u8], v: &'a [u8] },
+    /// A Y plane and a plane of interleaved U and V samples, as in NV12.
+    Semiplanar { y: &'a [u8], uv: &'a [u8] },
+}
+
+/// A YUV 4:2:0 frame, as video decoders produce.
+#[derive(Clone, Copy, Debug)]
+pub struct YuvFrame<'a> {
+    /// The size of the frame, and of its Y plane, in pixels. The chroma planes are half as wide
+    /// and half as tall, rounded up.
+    pub size: Vector2I,
+    pub planes: YuvPlanes<'a>,
+    /// The number of bytes from one row of the Y plane to the next.
+    pub y_stride: usize,
+    /// The number of bytes from one row of a chroma plane to the next.
+    pub chroma_stride: usize,
+    pub standard: YuvStandard,
+    pub range: YuvRange,
+}
+
+impl Pattern {
+    /// Creates a pattern that shows a YUV frame, converting it to RGB as it's drawn.
+    ///
+    /// The conversion uses the pattern's filter, so no other filter can be applied.
+    pub fn from_yuv(frame: &YuvFrame) -> Pattern {
+        let (width, height) = (frame.size.x() as usize, frame.size.y() as usize);
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let y_row = y * frame.y_stride;
+            let chroma_row = (y / 2) * frame.chroma_stride;
+            for x in 0..width {
+                let (u, v) = match frame.planes {
+                    YuvPlanes::Planar { u, v, .. } => {
+                        (u[chroma_row + x / 2], v[chroma_row + x / 2])
+                    }
+                    YuvPlanes::Semiplanar { uv, .. } => {
+                        (uv[chroma_row + x / 2 * 2], uv[chroma_row + x / 2 * 2 + 1])
+                    }
+                };
+                let luma = match frame.planes {
+                    YuvPlanes::Planar { y, .. } | YuvPlanes::Semiplanar { y, .. } => y[y_row + x],
+                };
+                pixels.push(ColorU::new(luma, u, v, 255));
+            }
+        }
+
+        let mut pattern = Pattern::from_image(Image::new(frame.size, Arc::new(pixels)));
+        pattern.set_filter(Some(PatternFilter::Yuv {
+            standard: frame.standard,
+            range: frame.range,
+        }));
+        pattern
+    }
+}
+
+/// Returns the rows of the affine matrix that converts normalized (Y, U, V, 1) values to RGB.
+pub fn yuv_to_rgb_matrix(standard: YuvStandard, range: YuvRange) -> [F32x4; 3] {
+    let (kr, kb) = match standard {
+        YuvStandard::Bt601 => (0.299, 0.114),
+        YuvStandard::Bt709 => (0.2126, 0.0722),
+    };
+    let kg = 1.0 - kr - kb;
+    let (y_offset, y_scale, chroma_scale) = match range {
+        YuvRange::Limited => (16.0 / 255.0, 255.0 / 219.0, 255.0 / 224.0),
+        YuvRange::Full => (0.0, 1.0, 1.0),
+    };
+    let chroma_offset = 128.0 / 255.0;
+
+    // Coefficients of (Y', Cb, Cr), where Y' is in [0, 1] and Cb and Cr are in [-0.5, 0.5].
+    let rows = [
+        [1.0, 0.0, 2.0 * (1.0 - kr)],
+        [1.0, -2.0 * kb * (1.0 - kb) / kg, -2.0 * kr * (1.0 - kr) / kg],
+        [1.0, 2.0 * (1.0 - kb), 0.0],
+    ];
+    let mut matrix = [F32x4::default(); 3];
+    for (row, coefficients) in matrix.iter_mut().zip(rows.iter()) {
+        let (y, u, v) = (coefficients[0] * y_scale,
+                         coefficients[1] * chroma_scale,
+                         coefficients[2] * chroma_scale);
+        let offset = -(y * y_offset + (u + v) * chroma_offset);
+        *row = F32x4::new(y, u, v, offset);
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod test {
+    use crate::effects::PatternFilter;
+    use crate::pattern::{Pattern, PatternSource};
+    use pathfinder_color::ColorU;
+    use pathfinder_geometry::vector::vec2i;
+    use pathfinder_simd::default::F32x4;
+    use super::{YuvFrame, YuvPlanes, YuvRange, YuvStandard, yuv_to_rgb_matrix};
+
+    fn convert(standard: YuvStandard, range: YuvRange, yuv: [u8; 3]) -> [f32; 3] {
+        let matrix = yuv_to_rgb_matrix(standard, range);
+        let yuv = F32x4::new(yuv[0] as f32 / 255.0, yuv[1] as f32 / 255.0, yuv[2] as f32 / 255.0,
+                             1.0);
+        let mut rgb = [0.0; 3];
+        for (channel, row) in rgb.iter_mut().zip(matrix.iter()) {
+            *channel = row.dot(yuv);
+        }
+        rgb
+    }
+
+    fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+        for (actual, expected) in actual.iter().zip(expected.iter()) {
+            assert!((actual - expected).abs() < 0.005, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_conversion_matrices() {
+        for &standard in &[YuvStandard::Bt601, YuvStandard::Bt709] {
+            assert_close(convert(standard, YuvRange::Limited, [16, 128, 128]), [0.0; 3]);
+            assert_close(convert(standard, YuvRange::Limited, [235, 128, 128]), [1.0; 3]);
+            assert_close(convert(standard, YuvRange::Full, [0, 128, 128]), [0.0; 3]);
+            assert_close(convert(standard, YuvRange::Full, [255, 128, 128]), [1.0; 3]);
+        }
+
+        // Pure red in full-range BT.601, as JPEG encodes it.
+        assert_close(convert(YuvStandard::Bt601, YuvRange::Full, [76, 85, 255]), [1.0, 0.0, 0.0]);
+        // Pure blue in limited-range BT.709.
+        assert_close(convert(YuvStandard::Bt709, YuvRange::Limited, [32, 240, 118]),
+                     [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_planar_and_semiplanar_frames_match() {
+        // A 3×2 frame, with chroma planes 2×1 and rows padded to 4 bytes.
+        let y = [10, 20, 30, 0, 40, 50, 60, 0];
+        let (u, v) = ([100, 110, 0, 0], [200, 210, 0, 0]);
+        let uv = [100, 200, 110, 210];
+        let frame = |planes| {
+            YuvFrame {
+                size: vec2i(3, 2),
+                planes,
+                y_stride: 4,
+                chroma_stride: 4,
+                standard: YuvStandard::Bt709,
+                range: YuvRange::Limited,
+            }
+        };
+
+        let planar = Pattern::from_yuv(&frame(YuvPlanes::Planar { y: &y, u: &u, v: &v }));
+        let semiplanar = Pattern::from_yuv(&frame(YuvPlanes::Semiplanar { y: &y, uv: &uv }));
+        assert_eq!(planar, semiplanar);
+        assert_eq!(planar.filter(), Some(PatternFilter::Yuv {
+            standard: YuvStandard::Bt709,
+            range: YuvRange::Limited,
+        }));
+
+        match *planar.source() {
+            PatternSource::Image(ref image) => {
+                assert_eq!(image.pixels()[2], ColorU::new(30, 110, 210, 255));
+                assert_eq!(image.pixels()[3], ColorU::new(40, 100, 200, 255));
+            }
+            PatternSource::RenderTarget { .. } => unreachable!(),
+        }
+    }
+}
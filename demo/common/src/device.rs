@@ -11,6 +11,7 @@
 //! GPU rendering code specifically for the demo.
 
 use pathfinder_gpu::{BufferTarget, Device, VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
+use pathfinder_gpu::error::RenderError;
 use pathfinder_resources::ResourceLoader;
 
 pub struct GroundProgram<D>
@@ -28,19 +29,20 @@ impl<D> GroundProgram<D>
 where
     D: Device,
 {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> GroundProgram<D> {
-        let program = device.create_raster_program(resources, "demo_ground");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<GroundProgram<D>, RenderError> {
+        let program = device.create_raster_program(resources, "demo_ground")?;
         let transform_uniform = device.get_uniform(&program, "Transform");
         let gridline_count_uniform = device.get_uniform(&program, "GridlineCount");
         let ground_color_uniform = device.get_uniform(&program, "GroundColor");
         let gridline_color_uniform = device.get_uniform(&program, "GridlineColor");
-        GroundProgram {
+        Ok(GroundProgram {
             program,
             transform_uniform,
             gridline_count_uniform,
             ground_color_uniform,
             gridline_color_uniform,
-        }
+        })
     }
 }
 
@@ -0,0 +1,149 @@
+// pathfinder/demo/common/src/inspector.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A summary of the paths in the loaded scene, for the inspector panel.
+
+use pathfinder_content::gradient::GradientGeometry;
+use pathfinder_content::pattern::PatternSource;
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::vec2i;
+use pathfinder_renderer::options::TileSize;
+use pathfinder_renderer::paint::{Paint, PaintContents};
+use pathfinder_renderer::scene::{ClipPath, RenderTargetInfo, Scene};
+
+// Past this many tiles, the highlight outlines the whole tile-aligned bounds instead.
+const MAX_HIGHLIGHTED_TILES: i32 = 1024;
+
+#[derive(Default)]
+pub struct SceneInspector {
+    pub paths: Vec<InspectedPath>,
+    pub selected_path_index: Option<usize>,
+    pub first_row: usize,
+    pub render_targets: Vec<RenderTargetInfo>,
+    pub active_clip_paths: Vec<ClipPath>,
+}
+
+pub struct InspectedPath {
+    pub name: String,
+    pub paint: String,
+    pub bounds: RectF,
+    pub visible: bool,
+}
+
+impl SceneInspector {
+    pub fn new(scene: &Scene) -> SceneInspector {
+        let paths = (0..scene.path_count()).map(|path_index| {
+            let path = scene.get_path(path_index);
+            let name = if path.name().is_empty() {
+                format!("Path #{}", path_index)
+            } else {
+                path.name().to_owned()
+            };
+            InspectedPath {
+                name,
+                paint: describe_paint(scene.get_paint(path.paint())),
+                bounds: path.outline().bounds(),
+                visible: path.is_visible(),
+            }
+        }).collect();
+        let active_clip_paths = scene.active_clip_paths().into_iter().map(|clip_path_id| {
+            scene.get_clip_path(clip_path_id).clone()
+        }).collect();
+        SceneInspector {
+            paths,
+            selected_path_index: None,
+            first_row: 0,
+            render_targets: scene.render_target_hierarchy(),
+            active_clip_paths,
+        }
+    }
+
+    #[inline]
+    pub fn selected_path(&self) -> Option<&InspectedPath> {
+        self.selected_path_index.and_then(|path_index| self.paths.get(path_index))
+    }
+
+    /// Carries hidden paths over to a rebuilt copy of the same scene.
+    pub fn apply_visibility(&self, scene: &mut Scene) {
+        if scene.path_count() != self.paths.len() {
+            return;
+        }
+        for (path_index, path) in self.paths.iter().enumerate() {
+            if !path.visible {
+                scene.set_path_visible(path_index, false);
+            }
+        }
+    }
+}
+
+impl InspectedPath {
+    /// The tiles, in device pixels, that the path's bounds touch after `transform` is applied.
+    pub fn tile_rects(&self, transform: &Transform2F, tile_size: TileSize) -> Vec<RectI> {
+        let device_bounds = *transform * self.bounds;
+        let tile_size = tile_size.size();
+        let tile_size_f = tile_size.to_f32();
+        let tile_origin = (device_bounds.origin() / tile_size_f).floor().to_i32();
+        let tile_lower_right = (device_bounds.lower_right() / tile_size_f).ceil().to_i32();
+        let tile_rect = RectI::from_points(tile_origin, tile_lower_right);
+
+        if tile_rect.width() * tile_rect.height() > MAX_HIGHLIGHTED_TILES {
+            return vec![tile_rect.scale_xy(tile_size)];
+        }
+
+        let mut tile_rects = vec![];
+        for tile_y in tile_rect.min_y()..tile_rect.max_y() {
+            for tile_x in tile_rect.min_x()..tile_rect.max_x() {
+                tile_rects.push(RectI::new(vec2i(tile_x, tile_y) * tile_size, tile_size));
+            }
+        }
+        tile_rects
+    }
+}
+
+fn describe_paint(paint: &Paint) -> String {
+    let base_color = paint.base_color();
+    let color = format!("#{:02x}{:02x}{:02x}{:02x}",
+                        base_color.r,
+                        base_color.g,
+                        base_color.b,
+                        base_color.a);
+    match paint.overlay() {
+        None => color,
+        Some(overlay) => {
+            let contents = match *overlay.contents() {
+                PaintContents::Gradient(ref gradient) => {
+                    let kind = match gradient.geometry {
+                        GradientGeometry::Linear(_) => "Linear",
+                        GradientGeometry::Radial { .. } => "Radial",
+                    };
+                    format!("{} gradient, {} stops", kind, gradient.stops().len())
+                }
+                PaintContents::Pattern(ref pattern) => {
+                    let kind = match *pattern.source() {
+                        PatternSource::Image(_) => "Image",
+                        PatternSource::RenderTarget { .. } => "Render target",
+                    };
+                    let size = pattern.size();
+                    format!("{} pattern, {}x{}", kind, size.x(), size.y())
+                }
+                PaintContents::Mesh(ref mesh) => {
+                    format!("Gradient mesh, {} patches", mesh.patches().len())
+                }
+                PaintContents::BlurredRoundedRect(ref shadow) => {
+                    format!("Blurred rounded rect, radius {}, sigma {}",
+                            shadow.corner_radius,
+                            shadow.sigma)
+                }
+            };
+            format!("{} over {}", contents, color)
+        }
+    }
+}
@@ -15,13 +15,17 @@ extern crate log;
 
 // Mode is used in Options, so has to be public
 pub use crate::camera::Mode;
+pub use crate::recording::RecordMode;
 
 use crate::camera::Camera;
 use crate::concurrent::DemoExecutor;
 use crate::device::{GroundProgram, GroundVertexArray};
+use crate::inspector::SceneInspector;
+use crate::recording::{DemoFrameSink, RecordMode};
 use crate::ui::{DemoUIModel, DemoUIPresenter, ScreenshotInfo, ScreenshotType, UIAction};
 use crate::window::{Event, Keycode, SVGPath, Window, WindowSize};
 use clap::{App, Arg};
+use pathfinder_color::ColorU;
 use pathfinder_content::effects::DEFRINGING_KERNEL_CORE_GRAPHICS;
 use pathfinder_content::effects::PatternFilter;
 use pathfinder_content::effects::STEM_DARKENING_FACTORS;
@@ -35,7 +39,8 @@ use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_geometry::vector::{Vector2F, Vector2I, Vector4F, vec2f, vec2i};
 use pathfinder_gpu::Device;
 use pathfinder_renderer::concurrent::scene_proxy::{RenderCommandStream, SceneProxy};
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::options::{DestFramebuffer, OutputAlpha, RendererOptions};
+use pathfinder_renderer::gpu::recorder::FrameRecorder;
 use pathfinder_renderer::gpu::renderer::{RenderStats, RenderTime, Renderer};
 use pathfinder_renderer::options::{BuildOptions, RenderTransform};
 use pathfinder_renderer::paint::Paint;
@@ -72,11 +77,15 @@ const APPROX_FONT_SIZE: f32 = 16.0;
 
 const MESSAGE_TIMEOUT_SECS: u64 = 5;
 
+const INSPECTOR_HIGHLIGHT_COLOR: ColorU = ColorU { r: 255, g: 0, b: 255, a: 255 };
+
 pub mod window;
 
 mod camera;
 mod concurrent;
 mod device;
+mod inspector;
+mod recording;
 mod renderer;
 mod ui;
 
@@ -95,6 +104,7 @@ pub struct DemoApp<W> where W: Window {
     camera: Camera,
     frame_counter: u32,
     pending_screenshot_info: Option<ScreenshotInfo>,
+    frame_recorder: Option<FrameRecorder<DeviceImpl, DemoFrameSink>>,
     mouselook_enabled: bool,
     pub dirty: bool,
     expire_message_event_id: u32,
@@ -136,8 +146,9 @@ impl<W> DemoApp<W> where W: Window {
 
         let mut ui_model = DemoUIModel::new(&options);
         let render_options = RendererOptions {
-            background_color: None,
             no_compute: options.no_compute,
+            dithering: false,
+            output_alpha: OutputAlpha::Premultiplied,
         };
 
         let filter = build_filter(&ui_model);
@@ -155,15 +166,18 @@ impl<W> DemoApp<W> where W: Window {
             window_size: window_size.device_size(),
         };
 
-        let renderer = Renderer::new(device, resources, dest_framebuffer, render_options);
+        let renderer = Renderer::new(device, resources, dest_framebuffer, render_options)
+            .expect("Failed to create the renderer!");
 
         let scene_metadata = SceneMetadata::new_clipping_view_box(&mut built_svg.scene,
                                                                   viewport.size());
         let camera = Camera::new(options.mode, scene_metadata.view_box, viewport.size());
 
+        ui_model.inspector = SceneInspector::new(&built_svg.scene);
         let scene_proxy = SceneProxy::from_scene(built_svg.scene, executor);
 
-        let ground_program = GroundProgram::new(&renderer.device, resources);
+        let ground_program = GroundProgram::new(&renderer.device, resources)
+            .expect("Failed to create the ground program!");
         let ground_vertex_array = GroundVertexArray::new(&renderer.device,
                                                          &ground_program,
                                                          &renderer.quad_vertex_positions_buffer(),
@@ -177,7 +191,13 @@ impl<W> DemoApp<W> where W: Window {
             message,
         );
 
-        let ui_presenter = DemoUIPresenter::new(&renderer.device, resources);
+        let ui_presenter = DemoUIPresenter::new(&renderer.device, resources)
+            .expect("Failed to create the UI!");
+
+        let frame_recorder = options.record.as_ref().map(|record_mode| {
+            let sink = DemoFrameSink::new(record_mode).expect("Failed to set up recording!");
+            FrameRecorder::new(sink)
+        });
 
         DemoApp {
             window,
@@ -194,6 +214,7 @@ impl<W> DemoApp<W> where W: Window {
             camera,
             frame_counter: 0,
             pending_screenshot_info: None,
+            frame_recorder,
             mouselook_enabled: false,
             dirty: true,
             expire_message_event_id,
@@ -263,6 +284,7 @@ impl<W> DemoApp<W> where W: Window {
                 Vector2F::zero()
             },
             subpixel_aa_enabled: self.ui_model.subpixel_aa_effect_enabled,
+            ..BuildOptions::default()
         };
 
         self.render_command_stream = Some(self.scene_proxy.build_with_stream(build_options));
@@ -438,6 +460,7 @@ impl<W> DemoApp<W> where W: Window {
                                               self.scene_metadata.view_box,
                                               viewport_size);
 
+                    self.ui_model.inspector = SceneInspector::new(&built_svg.scene);
                     self.scene_proxy.replace_scene(built_svg.scene);
                     self.svg_tree = svg_tree;
 
@@ -469,6 +492,7 @@ impl<W> DemoApp<W> where W: Window {
 
     pub fn finish_drawing_frame(&mut self) {
         self.maybe_take_screenshot();
+        self.record_frame();
         self.update_stats();
         self.draw_debug_ui();
 
@@ -483,6 +507,7 @@ impl<W> DemoApp<W> where W: Window {
 
         let mut ui_action = UIAction::None;
         if self.options.ui == UIVisibility::All {
+            self.draw_inspector_highlight();
             self.ui_presenter.update(
                 &self.renderer.device,
                 &mut self.window,
@@ -492,6 +517,8 @@ impl<W> DemoApp<W> where W: Window {
             );
         }
 
+        self.renderer.debug_ui_presenter.ui_presenter.flush_debug_draw_list(&self.renderer.device);
+
         self.handle_ui_events(frame, &mut ui_action);
 
         self.renderer.device.end_commands();
@@ -500,6 +527,35 @@ impl<W> DemoApp<W> where W: Window {
         self.frame_counter += 1;
     }
 
+    // Lists the render targets, and outlines the clip paths in use and the tiles of the path
+    // selected in the inspector. Outlines are only supported in 2D.
+    fn draw_inspector_highlight(&mut self) {
+        if !self.ui_presenter.inspector_panel_visible() {
+            return;
+        }
+        let debug_ui_presenter = &mut self.renderer.debug_ui_presenter;
+        let inspector = &self.ui_model.inspector;
+        debug_ui_presenter.draw_render_target_window(&self.renderer.device,
+                                                     &inspector.render_targets);
+
+        let transform = match self.camera {
+            Camera::TwoD(transform) => transform,
+            Camera::ThreeD { .. } => return,
+        };
+        debug_ui_presenter.push_clip_path_outlines(&inspector.active_clip_paths, &transform);
+
+        let path = match inspector.selected_path() {
+            None => return,
+            Some(path) => path,
+        };
+
+        let debug_draw_list = &mut debug_ui_presenter.ui_presenter.debug_draw_list;
+        let tile_size = BuildOptions::default().tile_size;
+        for tile_rect in path.tile_rects(&transform, tile_size) {
+            debug_draw_list.rect(tile_rect.to_f32(), INSPECTOR_HIGHLIGHT_COLOR);
+        }
+    }
+
     fn update_stats(&mut self) {
         let frame = self.current_frame.as_mut().unwrap();
         if let Some(rendering_time) = self.renderer.shift_rendering_time() {
@@ -575,6 +631,7 @@ impl<W> DemoApp<W> where W: Window {
                 let mut built_svg = build_svg_tree(&self.svg_tree, viewport_size, filter);
                 self.scene_metadata =
                     SceneMetadata::new_clipping_view_box(&mut built_svg.scene, viewport_size);
+                self.ui_model.inspector.apply_visibility(&mut built_svg.scene);
                 self.scene_proxy.replace_scene(built_svg.scene);
                 self.dirty = true;
             }
@@ -613,6 +670,12 @@ impl<W> DemoApp<W> where W: Window {
                                           .translate(center);
                 }
             }
+            UIAction::SetPathVisible { path_index, visible } => {
+                let mut scene = self.scene_proxy.copy_scene();
+                scene.set_path_visible(*path_index, *visible);
+                self.scene_proxy.replace_scene(scene);
+                self.dirty = true;
+            }
         }
     }
 }
@@ -626,6 +689,7 @@ pub struct Options {
     pub background_color: BackgroundColor,
     pub high_performance_gpu: bool,
     pub no_compute: bool,
+    pub record: Option<RecordMode>,
     hidden_field_for_future_proofing: (),
 }
 
@@ -639,6 +703,7 @@ impl Default for Options {
             background_color: BackgroundColor::Light,
             high_performance_gpu: false,
             no_compute: false,
+            record: None,
             hidden_field_for_future_proofing: (),
         }
     }
@@ -697,6 +762,20 @@ impl Options {
                     .long("no-compute")
                     .help("Never use compute shaders")
             )
+            .arg(
+                Arg::with_name("record")
+                    .short("r")
+                    .long("record")
+                    .value_name("DIRECTORY")
+                    .takes_value(true)
+                    .help("Save every presented frame to numbered PNGs in DIRECTORY")
+                    .conflicts_with("record-raw"),
+            )
+            .arg(
+                Arg::with_name("record-raw")
+                    .long("record-raw")
+                    .help("Write every presented frame to stdout as raw RGBA, e.g. for ffmpeg")
+            )
             .arg(
                 Arg::with_name("INPUT")
                     .help("Path to the SVG file to render")
@@ -738,6 +817,12 @@ impl Options {
             self.no_compute = true;
         }
 
+        if let Some(directory) = matches.value_of("record") {
+            self.record = Some(RecordMode::PngSequence(PathBuf::from(directory)));
+        } else if matches.is_present("record-raw") {
+            self.record = Some(RecordMode::RawStdout);
+        }
+
         if let Some(path) = matches.value_of("INPUT") {
             self.input_path = SVGPath::Path(PathBuf::from(path));
         };
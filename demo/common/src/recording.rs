@@ -0,0 +1,61 @@
+// pathfinder/demo/common/src/recording.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Saves every presented frame, for making videos and rendering comparisons.
+
+use image::ColorType;
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_renderer::gpu::recorder::{FrameSink, RawFrameSink};
+use std::fs;
+use std::io::{self, Stdout};
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordMode {
+    /// Saves `frame-00000.png`, `frame-00001.png`, etc. to the given directory.
+    PngSequence(PathBuf),
+    /// Writes raw RGBA8 frames to standard output.
+    RawStdout,
+}
+
+pub enum DemoFrameSink {
+    PngSequence(PathBuf),
+    RawStdout(RawFrameSink<Stdout>),
+}
+
+impl DemoFrameSink {
+    pub fn new(mode: &RecordMode) -> io::Result<DemoFrameSink> {
+        match *mode {
+            RecordMode::PngSequence(ref directory) => {
+                fs::create_dir_all(directory)?;
+                Ok(DemoFrameSink::PngSequence(directory.clone()))
+            }
+            RecordMode::RawStdout => Ok(DemoFrameSink::RawStdout(RawFrameSink::new(io::stdout()))),
+        }
+    }
+}
+
+impl FrameSink for DemoFrameSink {
+    fn write_frame(&mut self, frame_index: u32, size: Vector2I, pixels: &[u8]) -> io::Result<()> {
+        match *self {
+            DemoFrameSink::PngSequence(ref directory) => {
+                let path = directory.join(format!("frame-{:05}.png", frame_index));
+                image::save_buffer(path,
+                                   pixels,
+                                   size.x() as u32,
+                                   size.y() as u32,
+                                   ColorType::Rgba8).map_err(|error| {
+                    io::Error::new(io::ErrorKind::Other, error)
+                })
+            }
+            DemoFrameSink::RawStdout(ref mut sink) => sink.write_frame(frame_index, size, pixels),
+        }
+    }
+}
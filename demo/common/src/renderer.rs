@@ -14,13 +14,13 @@ use crate::camera::{Camera, Mode};
 use crate::window::{View, Window};
 use crate::{BackgroundColor, DemoApp, UIVisibility};
 use image::ColorType;
-use pathfinder_color::{ColorF, ColorU};
+use pathfinder_color::{self as color, ColorU};
 use pathfinder_gpu::{ClearOps, DepthFunc, DepthState, Device, Primitive, RenderOptions};
 use pathfinder_gpu::{RenderState, RenderTarget, TextureData, TextureFormat, UniformData};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_geometry::vector::{Vector2I, Vector4F};
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::options::{ClearMode, DestFramebuffer, OutputAlpha, RendererOptions};
 use pathfinder_renderer::options::RenderTransform;
 use std::path::PathBuf;
 
@@ -84,14 +84,16 @@ impl<W> DemoApp<W> where W: Window {
         };
 
         // Clear to the appropriate color.
-        let clear_color = match mode {
-            Mode::TwoD => Some(self.ui_model.background_color().to_f32()),
-            Mode::ThreeD => None,
-            Mode::VR => Some(ColorF::transparent_black()),
+        let clear_mode = match mode {
+            Mode::TwoD => ClearMode::Color(self.ui_model.background_color().to_f32()),
+            Mode::ThreeD => ClearMode::Preserve,
+            Mode::VR => ClearMode::Transparent,
         };
+        self.renderer.set_clear_mode(clear_mode);
         self.renderer.set_options(RendererOptions {
-            background_color: clear_color,
             no_compute: self.options.no_compute,
+            dithering: false,
+            output_alpha: OutputAlpha::Premultiplied,
         });
 
         scene_count
@@ -242,6 +244,7 @@ impl<W> DemoApp<W> where W: Window {
                  UniformData::Vec4(GROUND_LINE_COLOR.to_f32().0)),
                 (&self.ground_program.gridline_count_uniform, UniformData::Int(GRIDLINE_COUNT)),
             ],
+            uniform_buffers: &[],
             viewport: self.renderer.draw_viewport(),
             options: RenderOptions {
                 depth: Some(DepthState { func: DepthFunc::Less, write: true }),
@@ -260,9 +263,12 @@ impl<W> DemoApp<W> where W: Window {
 
         self.renderer.begin_scene();
 
-        // Issue render commands!
+        // Issue render commands! If one fails, the rest of the frame is skipped, but the stream
+        // must still be drained so that the scene thread can move on to the next frame.
         for command in self.render_command_stream.as_mut().unwrap() {
-            self.renderer.render_command(&command);
+            if let Err(error) = self.renderer.render_command(&command) {
+                error!("Failed to render frame: {}", error);
+            }
         }
 
         self.current_frame
@@ -278,10 +284,12 @@ impl<W> DemoApp<W> where W: Window {
         let viewport = RectI::new(Vector2I::default(), drawable_size);
         let texture_data_receiver =
             self.renderer.device.read_pixels(&RenderTarget::Default, viewport);
-        let pixels = match self.renderer.device.recv_texture_data(&texture_data_receiver) {
+        let mut pixels = match self.renderer.device.recv_texture_data(&texture_data_receiver) {
             TextureData::U8(pixels) => pixels,
             _ => panic!("Unexpected pixel format for default framebuffer!"),
         };
+        // PNG expects straight alpha.
+        color::unpremultiply_u8_slice(&mut pixels);
         image::save_buffer(
             path,
             &pixels,
@@ -292,6 +300,28 @@ impl<W> DemoApp<W> where W: Window {
         .unwrap();
     }
 
+    // Captures the frame before the debug UI is drawn over it.
+    pub fn record_frame(&mut self) {
+        let frame_recorder = match self.frame_recorder {
+            None => return,
+            Some(ref mut frame_recorder) => frame_recorder,
+        };
+
+        let (device, should_exit) = (&self.renderer.device, self.should_exit);
+        let viewport = RectI::new(Vector2I::default(), self.window_size.device_size());
+        let result = frame_recorder.capture(device, &RenderTarget::Default, viewport).and_then(|_| {
+            if should_exit {
+                frame_recorder.finish(device)
+            } else {
+                frame_recorder.poll(device)
+            }
+        });
+        if let Err(error) = result {
+            error!("Failed to record frame: {}", error);
+            self.frame_recorder = None;
+        }
+    }
+
     pub fn draw_debug_ui(&mut self) {
         if self.options.ui == UIVisibility::None {
             return;
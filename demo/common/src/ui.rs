@@ -9,12 +9,14 @@
 // except according to those terms.
 
 use crate::camera::Mode;
+use crate::inspector::SceneInspector;
 use crate::window::Window;
 use crate::{BackgroundColor, Options};
 use pathfinder_color::ColorU;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::{Vector2I, vec2i};
 use pathfinder_gpu::{Device, TextureFormat};
+use pathfinder_gpu::error::RenderError;
 use pathfinder_renderer::gpu::debug::DebugUIPresenter;
 use pathfinder_resources::ResourceLoader;
 use pathfinder_ui::{BUTTON_HEIGHT, BUTTON_TEXT_OFFSET, BUTTON_WIDTH, FONT_ASCENT, PADDING};
@@ -40,6 +42,12 @@ const SCREENSHOT_PANEL_HEIGHT: i32 = BUTTON_HEIGHT * 2;
 const ROTATE_PANEL_WIDTH: i32 = SLIDER_WIDTH + PADDING * 2;
 const ROTATE_PANEL_HEIGHT: i32 = PADDING * 2 + SLIDER_HEIGHT;
 
+const INSPECTOR_PAGE_SIZE: usize = 6;
+const INSPECTOR_PANEL_WIDTH: i32 = 640;
+const INSPECTOR_PANEL_HEIGHT: i32 = BUTTON_HEIGHT * (INSPECTOR_PAGE_SIZE as i32 + 4);
+const INSPECTOR_NAME_CHARS: usize = 24;
+const INSPECTOR_PAGER_ITEM_WIDTH: i32 = 120;
+
 const LIGHT_BG_COLOR:       ColorU = ColorU { r: 248, g: 248, b: 248, a: 255, };
 const DARK_BG_COLOR:        ColorU = ColorU { r: 32,  g: 32,  b: 32,  a: 255, };
 const TRANSPARENT_BG_COLOR: ColorU = ColorU { r: 0,   g: 0,   b: 0,   a: 0,   };
@@ -52,6 +60,7 @@ static ZOOM_ACTUAL_SIZE_PNG_NAME: &'static str = "demo-zoom-actual-size";
 static ZOOM_OUT_PNG_NAME: &'static str = "demo-zoom-out";
 static BACKGROUND_PNG_NAME: &'static str = "demo-background";
 static SCREENSHOT_PNG_NAME: &'static str = "demo-screenshot";
+static INSPECT_PNG_NAME: &'static str = "demo-inspect";
 
 pub struct DemoUIModel {
     pub mode: Mode,
@@ -61,6 +70,7 @@ pub struct DemoUIModel {
     pub subpixel_aa_effect_enabled: bool,
     pub rotation: i32,
     pub message: String,
+    pub inspector: SceneInspector,
 }
 
 impl DemoUIModel {
@@ -73,6 +83,7 @@ impl DemoUIModel {
             subpixel_aa_effect_enabled: false,
             rotation: SLIDER_WIDTH / 2,
             message: String::new(),
+            inspector: SceneInspector::default(),
         }
     }
 
@@ -109,44 +120,50 @@ where
     zoom_out_texture: D::Texture,
     background_texture: D::Texture,
     screenshot_texture: D::Texture,
+    inspect_texture: D::Texture,
 
     effects_panel_visible: bool,
     background_panel_visible: bool,
     screenshot_panel_visible: bool,
     rotate_panel_visible: bool,
+    inspector_panel_visible: bool,
 }
 
 impl<D> DemoUIPresenter<D>
 where
     D: Device,
 {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> DemoUIPresenter<D> {
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<DemoUIPresenter<D>, RenderError> {
         let effects_texture = device.create_texture_from_png(resources,
                                                              EFFECTS_PNG_NAME,
-                                                             TextureFormat::R8);
+                                                             TextureFormat::R8)?;
         let open_texture = device.create_texture_from_png(resources,
                                                           OPEN_PNG_NAME,
-                                                          TextureFormat::R8);
+                                                          TextureFormat::R8)?;
         let rotate_texture = device.create_texture_from_png(resources,
                                                             ROTATE_PNG_NAME,
-                                                            TextureFormat::R8);
+                                                            TextureFormat::R8)?;
         let zoom_in_texture = device.create_texture_from_png(resources,
                                                              ZOOM_IN_PNG_NAME,
-                                                             TextureFormat::R8);
+                                                             TextureFormat::R8)?;
         let zoom_actual_size_texture = device.create_texture_from_png(resources,
                                                                       ZOOM_ACTUAL_SIZE_PNG_NAME,
-                                                                      TextureFormat::R8);
+                                                                      TextureFormat::R8)?;
         let zoom_out_texture = device.create_texture_from_png(resources,
                                                               ZOOM_OUT_PNG_NAME,
-                                                              TextureFormat::R8);
+                                                              TextureFormat::R8)?;
         let background_texture = device.create_texture_from_png(resources,
                                                                 BACKGROUND_PNG_NAME,
-                                                                TextureFormat::R8);
+                                                                TextureFormat::R8)?;
         let screenshot_texture = device.create_texture_from_png(resources,
                                                                 SCREENSHOT_PNG_NAME,
-                                                                TextureFormat::R8);
+                                                                TextureFormat::R8)?;
+        let inspect_texture = device.create_texture_from_png(resources,
+                                                             INSPECT_PNG_NAME,
+                                                             TextureFormat::R8)?;
 
-        DemoUIPresenter {
+        Ok(DemoUIPresenter {
             effects_texture,
             open_texture,
             rotate_texture,
@@ -155,12 +172,19 @@ where
             zoom_out_texture,
             background_texture,
             screenshot_texture,
+            inspect_texture,
 
             effects_panel_visible: false,
             background_panel_visible: false,
             screenshot_panel_visible: false,
             rotate_panel_visible: false,
-        }
+            inspector_panel_visible: false,
+        })
+    }
+
+    #[inline]
+    pub fn inspector_panel_visible(&self) -> bool {
+        self.inspector_panel_visible
     }
 
     pub fn update<W>(
@@ -268,6 +292,20 @@ where
         self.draw_background_panel(device, debug_ui_presenter, position.x(), action, model);
         position += vec2i(button_size.x() + PADDING, 0);
 
+        // Draw inspector button.
+        if debug_ui_presenter.ui_presenter.draw_button(device, position, &self.inspect_texture) {
+            self.inspector_panel_visible = !self.inspector_panel_visible;
+        }
+        if !self.inspector_panel_visible {
+            debug_ui_presenter.ui_presenter.draw_tooltip(device,
+                                                         "Inspect Scene",
+                                                         RectI::new(position, button_size));
+        }
+
+        // Draw inspector panel, if necessary.
+        self.draw_inspector_panel(device, debug_ui_presenter, position.x(), action, model);
+        position += vec2i(button_size.x() + PADDING, 0);
+
         // Draw effects panel, if necessary.
         self.draw_effects_panel(device, debug_ui_presenter, model, action);
 
@@ -521,6 +559,115 @@ where
         debug_ui_presenter.ui_presenter.draw_solid_rect(device, slider_knob_rect, TEXT_COLOR);
     }
 
+    fn draw_inspector_panel(
+        &mut self,
+        device: &D,
+        debug_ui_presenter: &mut DebugUIPresenter<D>,
+        panel_x: i32,
+        action: &mut UIAction,
+        model: &mut DemoUIModel,
+    ) {
+        if !self.inspector_panel_visible {
+            return;
+        }
+
+        let bottom = debug_ui_presenter.ui_presenter.framebuffer_size().y() - PADDING;
+        let panel_y = bottom - (BUTTON_HEIGHT + PADDING + INSPECTOR_PANEL_HEIGHT);
+        let panel_position = vec2i(panel_x, panel_y);
+        debug_ui_presenter.ui_presenter.draw_solid_rounded_rect(
+            device,
+            RectI::new(panel_position, vec2i(INSPECTOR_PANEL_WIDTH, INSPECTOR_PANEL_HEIGHT)),
+            WINDOW_COLOR,
+        );
+
+        let inspector = &mut model.inspector;
+        let row_size = vec2i(INSPECTOR_PANEL_WIDTH, BUTTON_HEIGHT);
+        let mut row_origin = panel_position;
+
+        // Draw one page of paths.
+        let page_end = (inspector.first_row + INSPECTOR_PAGE_SIZE).min(inspector.paths.len());
+        for path_index in inspector.first_row..page_end {
+            let path = &inspector.paths[path_index];
+            let mut text: String = path.name.chars().take(INSPECTOR_NAME_CHARS).collect();
+            if !path.visible {
+                text.push_str(" (hidden)");
+            }
+            let selected = inspector.selected_path_index == Some(path_index);
+            if self.draw_menu_item(device,
+                                   debug_ui_presenter,
+                                   &text,
+                                   RectI::new(row_origin, row_size),
+                                   selected) {
+                inspector.selected_path_index = if selected { None } else { Some(path_index) };
+                *action = UIAction::ModelChanged;
+            }
+            row_origin += vec2i(0, BUTTON_HEIGHT);
+        }
+        row_origin = panel_position + vec2i(0, BUTTON_HEIGHT * INSPECTOR_PAGE_SIZE as i32);
+
+        // Draw the pager.
+        let page_text = if inspector.paths.is_empty() {
+            "No paths".to_owned()
+        } else {
+            format!("{}-{} of {}", inspector.first_row + 1, page_end, inspector.paths.len())
+        };
+        let text_position = row_origin + vec2i(PADDING * 2, BUTTON_TEXT_OFFSET);
+        debug_ui_presenter.ui_presenter.draw_text(device, &page_text, text_position, false);
+
+        let pager_item_size = vec2i(INSPECTOR_PAGER_ITEM_WIDTH, BUTTON_HEIGHT);
+        let next_origin = row_origin + vec2i(INSPECTOR_PANEL_WIDTH - pager_item_size.x(), 0);
+        let prev_origin = next_origin - vec2i(pager_item_size.x(), 0);
+        if self.draw_menu_item(device,
+                               debug_ui_presenter,
+                               "Prev",
+                               RectI::new(prev_origin, pager_item_size),
+                               false) {
+            inspector.first_row = inspector.first_row.saturating_sub(INSPECTOR_PAGE_SIZE);
+        }
+        if self.draw_menu_item(device,
+                               debug_ui_presenter,
+                               "Next",
+                               RectI::new(next_origin, pager_item_size),
+                               false) && page_end < inspector.paths.len() {
+            inspector.first_row += INSPECTOR_PAGE_SIZE;
+        }
+        row_origin += vec2i(0, BUTTON_HEIGHT);
+
+        // Draw details of the selected path.
+        let selected_path_index = match inspector.selected_path_index {
+            Some(path_index) if path_index < inspector.paths.len() => path_index,
+            _ => return,
+        };
+        let path = &mut inspector.paths[selected_path_index];
+        let bounds = path.bounds;
+        let details = [
+            format!("Paint: {}", path.paint),
+            format!("Bounds: {:.0},{:.0} {:.0}x{:.0}",
+                    bounds.min_x(),
+                    bounds.min_y(),
+                    bounds.width(),
+                    bounds.height()),
+        ];
+        for detail in &details {
+            let text_position = row_origin + vec2i(PADDING * 2, BUTTON_TEXT_OFFSET);
+            debug_ui_presenter.ui_presenter.draw_text(device, detail, text_position, false);
+            row_origin += vec2i(0, BUTTON_HEIGHT);
+        }
+
+        let text_position = row_origin + vec2i(PADDING * 2, BUTTON_TEXT_OFFSET);
+        debug_ui_presenter.ui_presenter.draw_text(device, "Visible", text_position, false);
+        let switch_width = debug_ui_presenter.ui_presenter.measure_segmented_control(2);
+        let switch_position = row_origin + vec2i(INSPECTOR_PANEL_WIDTH - switch_width - PADDING, 0);
+        let visible =
+            debug_ui_presenter
+                .ui_presenter
+                .draw_text_switch(device, switch_position, &["Off", "On"], path.visible as u8) != 0;
+        if visible != path.visible {
+            path.visible = visible;
+            *action = UIAction::SetPathVisible { path_index: selected_path_index, visible };
+        }
+    }
+
     fn draw_screenshot_menu_item<W>(
         &mut self,
         device: &D,
@@ -640,6 +787,7 @@ pub enum UIAction {
     ZoomActualSize,
     ZoomOut,
     Rotate(f32),
+    SetPathVisible { path_index: usize, visible: bool },
 }
 
 #[derive(Clone, Debug, PartialEq)]
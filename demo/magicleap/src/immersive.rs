@@ -64,7 +64,8 @@ impl<D: Display> ImmersiveDemo<D> {
         let _ = scene_thread_proxy.sender.send(MainToSceneMsg::SetDrawableSize(display.size()));
         let device = GLDevice::new(display.gl_version());
 	let viewport = RectI32::new(Point2DI32::new(0, 0), display.size());
-        let renderer = Renderer::new(device, resources, viewport, display.size());
+        let renderer = Renderer::new(device, resources, viewport, display.size())
+            .expect("Failed to create the renderer!");
         Ok(ImmersiveDemo {
             display,
             renderer,
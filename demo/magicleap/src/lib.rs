@@ -20,7 +20,7 @@ use egl::EGLSurface;
 
 use gl::types::GLuint;
 
-use log::info;
+use log::{info, warn};
 
 use pathfinder_demo::DemoApp;
 use pathfinder_demo::Options;
@@ -206,6 +206,7 @@ pub unsafe extern "C" fn magicleap_pathfinder_render(pf: *mut c_void, options: *
             let device = GLDevice::new(GLVersion::GLES3, fbo as GLuint);
             let dest_framebuffer = DestFramebuffer::Default { viewport, window_size: size };
             Renderer::new(device, resources, dest_framebuffer)
+                .expect("Failed to create the renderer!")
         });
 
         renderer.set_main_framebuffer_size(size);
@@ -228,7 +229,9 @@ pub unsafe extern "C" fn magicleap_pathfinder_render(pf: *mut c_void, options: *
         };
 
         let scene_proxy = SceneProxy::from_scene(svg.scene.clone(), SequentialExecutor);
-        scene_proxy.build_and_render(renderer, render_options);
+        if let Err(error) = scene_proxy.build_and_render(renderer, render_options) {
+            warn!("Failed to render SVG: {}", error);
+        }
     }
 }
 
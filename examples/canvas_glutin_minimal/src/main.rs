@@ -24,7 +24,7 @@ use pathfinder_resources::embedded::EmbeddedResourceLoader;
 use pathfinder_renderer::concurrent::rayon::RayonExecutor;
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
 use pathfinder_renderer::gpu::renderer::Renderer;
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::options::{ClearMode, DestFramebuffer, RendererOptions};
 use pathfinder_renderer::options::BuildOptions;
 
 fn main() {
@@ -51,10 +51,8 @@ fn main() {
     let mut renderer = Renderer::new(GLDevice::new(GLVersion::GL3, 0),
                                      &EmbeddedResourceLoader,
                                      DestFramebuffer::full_window(window_size),
-                                     RendererOptions {
-                                         background_color: Some(ColorF::white()),
-                                         ..RendererOptions::default()
-                                     });
+                                     RendererOptions::default()).unwrap();
+    renderer.set_clear_mode(ClearMode::Color(ColorF::white()));
 
     // Make a canvas. We're going to draw a house.
     let font_context = CanvasFontContext::from_system_source();
@@ -79,7 +77,7 @@ fn main() {
 
     // Render the canvas to screen.
     let scene = SceneProxy::from_scene(canvas.into_canvas().into_scene(), RayonExecutor);
-    scene.build_and_render(&mut renderer, BuildOptions::default());
+    scene.build_and_render(&mut renderer, BuildOptions::default()).unwrap();
     gl_context.swap_buffers().unwrap();
 
     // Wait for a keypress.
@@ -16,7 +16,7 @@ use pathfinder_geometry::vector::{vec2f, vec2i};
 use pathfinder_gl::{GLDevice, GLVersion};
 use pathfinder_renderer::concurrent::rayon::RayonExecutor;
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::options::{ClearMode, DestFramebuffer, RendererOptions};
 use pathfinder_renderer::gpu::renderer::Renderer;
 use pathfinder_renderer::options::BuildOptions;
 use pathfinder_resources::embedded::EmbeddedResourceLoader;
@@ -75,10 +75,8 @@ fn main() {
     let mut renderer = Renderer::new(pathfinder_device,
                                      &EmbeddedResourceLoader::new(),
                                      DestFramebuffer::full_window(framebuffer_size),
-                                     RendererOptions {
-                                         background_color: Some(ColorF::white()),
-                                         ..RendererOptions::default()
-                                     });
+                                     RendererOptions::default()).unwrap();
+    renderer.set_clear_mode(ClearMode::Color(ColorF::white()));
 
     // Make a canvas. We're going to draw a house.
     let font_context = CanvasFontContext::from_system_source();
@@ -103,7 +101,7 @@ fn main() {
 
     // Render the canvas to screen.
     let scene = SceneProxy::from_scene(canvas.into_canvas().into_scene(), RayonExecutor);
-    scene.build_and_render(&mut renderer, BuildOptions::default());
+    scene.build_and_render(&mut renderer, BuildOptions::default()).unwrap();
 
     // Present the surface.
     let mut surface = device.unbind_surface_from_context(&mut context).unwrap().unwrap();
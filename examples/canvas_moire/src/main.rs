@@ -15,7 +15,7 @@ use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2f, vec2i};
 use pathfinder_gl::{GLDevice, GLVersion};
 use pathfinder_renderer::concurrent::rayon::RayonExecutor;
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::options::{ClearMode, DestFramebuffer, RendererOptions};
 use pathfinder_renderer::gpu::renderer::Renderer;
 use pathfinder_renderer::options::BuildOptions;
 use pathfinder_resources::embedded::EmbeddedResourceLoader;
@@ -85,13 +85,11 @@ fn main() {
     let pathfinder_device = GLDevice::new(GLVersion::GL3, default_framebuffer);
 
     // Create our renderers.
-    let renderer = Renderer::new(pathfinder_device,
-                                 &EmbeddedResourceLoader,
-                                 DestFramebuffer::full_window(framebuffer_size),
-                                 RendererOptions {
-                                     background_color: Some(ColorF::white()),
-                                     ..RendererOptions::default()
-                                 });
+    let mut renderer = Renderer::new(pathfinder_device,
+                                     &EmbeddedResourceLoader,
+                                     DestFramebuffer::full_window(framebuffer_size),
+                                     RendererOptions::default()).unwrap();
+    renderer.set_clear_mode(ClearMode::Color(ColorF::white()));
     let window_size = vec2i(window_size.width, window_size.height);
     let mut moire_renderer = MoireRenderer::new(renderer, window_size, framebuffer_size);
 
@@ -121,7 +119,6 @@ struct MoireRenderer {
     scene: SceneProxy,
     frame: i32,
     window_size: Vector2I,
-    drawable_size: Vector2I,
     device_pixel_ratio: f32,
     colors: ColorGradient,
 }
@@ -135,7 +132,6 @@ impl MoireRenderer {
             scene: SceneProxy::new(RayonExecutor),
             frame: 0,
             window_size,
-            drawable_size,
             device_pixel_ratio: drawable_size.x() as f32 / window_size.x() as f32,
             colors: ColorGradient::new(),
         }
@@ -155,15 +151,13 @@ impl MoireRenderer {
         let inner_center = window_center + vec2f(1.0, sin_time) * (cos_time * INNER_RADIUS);
 
         // Clear to background color.
-        self.renderer.set_options(RendererOptions {
-            background_color: Some(background_color),
-            ..RendererOptions::default()
-        });
+        self.renderer.set_clear_mode(ClearMode::Color(background_color));
 
         // Make a canvas.
-        let mut canvas =    
-            Canvas::new(self.drawable_size.to_f32()).get_context_2d(self.font_context.clone());
-        canvas.set_line_width(CIRCLE_THICKNESS * self.device_pixel_ratio);
+        let mut canvas = Canvas::new(self.window_size.to_f32());
+        canvas.set_device_pixel_ratio(self.device_pixel_ratio);
+        let mut canvas = canvas.get_context_2d(self.font_context.clone());
+        canvas.set_line_width(CIRCLE_THICKNESS);
         canvas.set_stroke_style(FillStyle::Color(foreground_color.to_u8()));
         canvas.set_global_alpha(0.75);
 
@@ -173,15 +167,14 @@ impl MoireRenderer {
 
         // Build and render scene.
         self.scene.replace_scene(canvas.into_canvas().into_scene());
-        self.scene.build_and_render(&mut self.renderer, BuildOptions::default());
+        self.scene.build_and_render(&mut self.renderer, BuildOptions::default()).unwrap();
 
         self.frame += 1;
     }
 
-    fn draw_circles(&self, canvas: &mut CanvasRenderingContext2D, mut center: Vector2F) {
-        center *= self.device_pixel_ratio;
+    fn draw_circles(&self, canvas: &mut CanvasRenderingContext2D, center: Vector2F) {
         for index in 0..CIRCLE_COUNT {
-            let radius = (index + 1) as f32 * CIRCLE_SPACING * self.device_pixel_ratio;
+            let radius = (index + 1) as f32 * CIRCLE_SPACING;
             let mut path = Path2D::new();
             path.ellipse(center, radius, 0.0, 0.0, PI * 2.0);
             canvas.stroke_path(path);
@@ -30,7 +30,7 @@ use pathfinder_geometry::vector::{Vector2F, vec2f, vec2i};
 use pathfinder_gl::{GLDevice, GLVersion};
 use pathfinder_renderer::concurrent::rayon::RayonExecutor;
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::options::{ClearMode, DestFramebuffer, RendererOptions};
 use pathfinder_renderer::gpu::renderer::Renderer;
 use pathfinder_renderer::options::BuildOptions;
 use pathfinder_resources::ResourceLoader;
@@ -1520,10 +1520,8 @@ fn main() {
     let mut renderer = Renderer::new(pathfinder_device,
                                      &resources,
                                      DestFramebuffer::full_window(framebuffer_size),
-                                     RendererOptions {
-                                         background_color: Some(rgbf(0.3, 0.3, 0.32)),
-                                         ..RendererOptions::default()
-                                     });
+                                     RendererOptions::default()).unwrap();
+    renderer.set_clear_mode(ClearMode::Color(rgbf(0.3, 0.3, 0.32)));
 
     // Initialize font state.
     let font_source = Arc::new(MemSource::from_fonts(font_data.into_iter()).unwrap());
@@ -1567,7 +1565,7 @@ fn main() {
         // Render the canvas to screen.
         let canvas = context.into_canvas();
         let scene = SceneProxy::from_scene(canvas.into_scene(), RayonExecutor);
-        scene.build_and_render(&mut renderer, BuildOptions::default());
+        scene.build_and_render(&mut renderer, BuildOptions::default()).unwrap();
 
         // Present the rendered canvas via `surfman`.
         let mut surface = device.unbind_surface_from_context(&mut gl_context).unwrap().unwrap();
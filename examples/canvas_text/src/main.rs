@@ -15,7 +15,7 @@ use pathfinder_geometry::vector::{vec2f, vec2i};
 use pathfinder_gl::{GLDevice, GLVersion};
 use pathfinder_renderer::concurrent::rayon::RayonExecutor;
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::options::{ClearMode, DestFramebuffer, RendererOptions};
 use pathfinder_renderer::gpu::renderer::Renderer;
 use pathfinder_renderer::options::BuildOptions;
 use pathfinder_resources::ResourceLoader;
@@ -53,10 +53,8 @@ fn main() {
     let mut renderer = Renderer::new(GLDevice::new(GLVersion::GL3, 0),
                                      &resource_loader,
                                      DestFramebuffer::full_window(window_size),
-                                     RendererOptions {
-                                         background_color: Some(ColorF::white()),
-                                         ..RendererOptions::default()
-                                     });
+                                     RendererOptions::default()).unwrap();
+    renderer.set_clear_mode(ClearMode::Color(ColorF::white()));
 
     // Load a font.
     let font_data = Arc::new(resource_loader.slurp("fonts/Overpass-Regular.otf").unwrap());
@@ -75,7 +73,7 @@ fn main() {
 
     // Render the canvas to screen.
     let scene = SceneProxy::from_scene(canvas.into_canvas().into_scene(), RayonExecutor);
-    scene.build_and_render(&mut renderer, BuildOptions::default());
+    scene.build_and_render(&mut renderer, BuildOptions::default()).unwrap();
     window.gl_swap_window();
 
     // Wait for a keypress.
@@ -0,0 +1,109 @@
+// pathfinder/examples/capture_replay/src/main.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Replays a frame capture written by `FrameCapture::write()`.
+//!
+//! Usage: `capture_replay <capture-file>`
+
+use pathfinder_geometry::vector::vec2i;
+use pathfinder_gl::{GLDevice, GLVersion};
+use pathfinder_renderer::capture::FrameCapture;
+use pathfinder_renderer::gpu::options::DestFramebuffer;
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_resources::embedded::EmbeddedResourceLoader;
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::process;
+use surfman::{Connection, ContextAttributeFlags, ContextAttributes, GLVersion as SurfmanGLVersion};
+use surfman::{SurfaceAccess, SurfaceType};
+use winit::dpi::PhysicalSize;
+use winit::{ControlFlow, Event, EventsLoop, WindowBuilder, WindowEvent};
+
+fn main() {
+    // Load the capture.
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: capture_replay <capture-file>");
+            process::exit(1);
+        }
+    };
+    let mut reader = BufReader::new(File::open(&path).expect("Failed to open the capture!"));
+    let capture = FrameCapture::read(&mut reader).expect("Failed to read the capture!");
+    println!("{}: {} commands, {}x{}",
+             path,
+             capture.commands.len(),
+             capture.framebuffer_size.x(),
+             capture.framebuffer_size.y());
+
+    // Open a window the same size, in device pixels, as the one the frame was captured from.
+    let mut event_loop = EventsLoop::new();
+    let hidpi_factor = event_loop.get_primary_monitor().get_hidpi_factor();
+    let physical_size = PhysicalSize::new(capture.framebuffer_size.x() as f64,
+                                          capture.framebuffer_size.y() as f64);
+    let logical_size = physical_size.to_logical(hidpi_factor);
+    let window = WindowBuilder::new().with_title("Capture replay")
+                                     .with_dimensions(logical_size)
+                                     .build(&event_loop)
+                                     .unwrap();
+    window.show();
+
+    // Create a `surfman` device and an OpenGL 3.x context.
+    let connection = Connection::from_winit_window(&window).unwrap();
+    let native_widget = connection.create_native_widget_from_winit_window(&window).unwrap();
+    let adapter = connection.create_low_power_adapter().unwrap();
+    let mut device = connection.create_device(&adapter).unwrap();
+    let context_attributes = ContextAttributes {
+        version: SurfmanGLVersion::new(3, 0),
+        flags: ContextAttributeFlags::ALPHA,
+    };
+    let context_descriptor = device.create_context_descriptor(&context_attributes).unwrap();
+    let surface_type = SurfaceType::Widget { native_widget };
+    let mut context = device.create_context(&context_descriptor).unwrap();
+    let surface = device.create_surface(&context, SurfaceAccess::GPUOnly, surface_type)
+                        .unwrap();
+    device.bind_surface_to_context(&mut context, surface).unwrap();
+    device.make_context_current(&context).unwrap();
+    gl::load_with(|symbol_name| device.get_proc_address(&context, symbol_name));
+
+    // Create a Pathfinder renderer. The capture supplies the renderer options.
+    let physical_size = logical_size.to_physical(hidpi_factor);
+    let framebuffer_size = vec2i(physical_size.width as i32, physical_size.height as i32);
+    let default_framebuffer = device.context_surface_info(&context)
+                                    .unwrap()
+                                    .unwrap()
+                                    .framebuffer_object;
+    let pathfinder_device = GLDevice::new(GLVersion::GL3, default_framebuffer);
+    let mut renderer = Renderer::new(pathfinder_device,
+                                     &EmbeddedResourceLoader::new(),
+                                     DestFramebuffer::full_window(framebuffer_size),
+                                     capture.renderer_options.clone()).unwrap();
+
+    // Replay the frame and present it.
+    capture.replay(&mut renderer).unwrap();
+    let mut surface = device.unbind_surface_from_context(&mut context).unwrap().unwrap();
+    device.present_surface(&mut context, &mut surface).unwrap();
+    device.bind_surface_to_context(&mut context, surface).unwrap();
+
+    // Wait for a keypress.
+    event_loop.run_forever(|event| {
+        match event {
+            Event::WindowEvent { event: WindowEvent::CloseRequested, .. } |
+            Event::WindowEvent { event: WindowEvent::KeyboardInput { .. }, .. } => {
+                ControlFlow::Break
+            }
+            _ => ControlFlow::Continue,
+        }
+    });
+
+    // Clean up.
+    drop(device.destroy_context(&mut context));
+}
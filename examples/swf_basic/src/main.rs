@@ -15,7 +15,7 @@ use pathfinder_gl::{GLDevice, GLVersion};
 use pathfinder_renderer::concurrent::rayon::RayonExecutor;
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
 use pathfinder_renderer::gpu::renderer::Renderer;
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::options::{ClearMode, DestFramebuffer, RendererOptions};
 use pathfinder_renderer::options::{RenderTransform, BuildOptions};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_resources::embedded::EmbeddedResourceLoader;
@@ -102,12 +102,10 @@ fn main() {
         GLDevice::new(GLVersion::GL3, 0),
         &resource_loader,
         DestFramebuffer::full_window(pixel_size),
-        RendererOptions {
-            background_color: Some(stage.background_color()),
-            ..RendererOptions::default()
-        }
-    );
+        RendererOptions::default()
+    ).unwrap();
     // Clear to swf stage background color.
+    renderer.set_clear_mode(ClearMode::Color(stage.background_color()));
     let mut scene = Scene::new();
     scene.set_view_box(RectF::new(Vector2F::zero(),
                                   vec2f(stage.width() as f32,
@@ -119,7 +117,7 @@ fn main() {
     let mut build_options = BuildOptions::default();
     let scale_transform = Transform2F::from_scale(device_pixel_ratio);
     build_options.transform = RenderTransform::Transform2D(scale_transform);
-    scene.build_and_render(&mut renderer, build_options);
+    scene.build_and_render(&mut renderer, build_options).unwrap();
 
     window.gl_swap_window();
     // Wait for a keypress.
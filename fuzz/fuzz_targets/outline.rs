@@ -0,0 +1,64 @@
+// pathfinder/fuzz/fuzz_targets/outline.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Runs arbitrary paths through stroking, transformation, clipping, and dilation.
+//!
+//! In addition to checking for panics, this checks that sane inputs (see
+//! `pathfinder_fuzz::is_sane()`) never produce NaN coordinates.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use pathfinder_fuzz::{FuzzPath, FuzzTransform, PathCommand};
+use pathfinder_geometry::vector::vec2f;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    path: FuzzPath,
+    transform: FuzzTransform,
+    clip_polygon: Vec<PathCommand>,
+    dilation: (f32, f32),
+}
+
+fuzz_target!(|input: Input| {
+    let check_nans = input.path.is_sane() && input.transform.is_sane();
+
+    let mut outline = input.path.to_outline();
+    if check_nans {
+        pathfinder_fuzz::assert_no_nans(&outline, "Stroking");
+    }
+
+    outline.transform(&input.transform.to_transform());
+    if check_nans {
+        pathfinder_fuzz::assert_no_nans(&outline, "Transformation");
+    }
+
+    // Use the endpoints of the clip commands as the clip polygon, as the canvas does for clip
+    // paths that have been flattened.
+    let clip_polygon: Vec<_> = input.clip_polygon.iter().filter_map(|command| {
+        match *command {
+            PathCommand::MoveTo(x, y) | PathCommand::LineTo(x, y) => Some(vec2f(x, y)),
+            _ => None,
+        }
+    }).collect();
+    if clip_polygon.len() >= 3 {
+        outline.clip_against_polygon(&clip_polygon);
+        if check_nans && input.clip_polygon.iter().all(PathCommand::is_sane) {
+            pathfinder_fuzz::assert_no_nans(&outline, "Clipping");
+        }
+    }
+
+    let (dilation_x, dilation_y) = input.dilation;
+    outline.dilate(vec2f(dilation_x, dilation_y));
+    if check_nans && pathfinder_fuzz::is_sane(&[dilation_x, dilation_y]) {
+        pathfinder_fuzz::assert_no_nans(&outline, "Dilation");
+    }
+});
@@ -0,0 +1,34 @@
+// pathfinder/fuzz/fuzz_targets/svg.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Feeds arbitrary bytes through the SVG parser, the SVG-to-scene conversion, and the tiler.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_renderer::options::TileSize;
+use pathfinder_svg::BuiltSVG;
+use usvg::{Options as UsvgOptions, Tree};
+
+fuzz_target!(|data: &[u8]| {
+    // Most inputs won't parse; that's fine, as long as parsing doesn't panic.
+    let tree = match Tree::from_data(data, &UsvgOptions::default()) {
+        Ok(tree) => tree,
+        Err(_) => return,
+    };
+
+    let mut built_svg = BuiltSVG::from_tree(&tree);
+    for (_, outline, _) in built_svg.scene.paths() {
+        pathfinder_fuzz::assert_no_nans(outline, "SVG conversion");
+    }
+
+    pathfinder_fuzz::build_scene(&mut built_svg.scene, Transform2F::default(), TileSize::default());
+});
@@ -0,0 +1,58 @@
+// pathfinder/fuzz/fuzz_targets/tiler.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Builds scenes from arbitrary paths, clip paths, and render transforms.
+//!
+//! This exercises the tiler, the clip tiler, and the Z-buffer with degenerate transforms and huge
+//! coordinates, which have historically caused panics and runaway allocations.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use pathfinder_color::ColorU;
+use pathfinder_content::fill::FillRule;
+use pathfinder_fuzz::{FuzzPath, FuzzTransform};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::{Vector2F, vec2f};
+use pathfinder_renderer::options::TileSize;
+use pathfinder_renderer::paint::Paint;
+use pathfinder_renderer::scene::{ClipPath, DrawPath, Scene};
+
+const MAX_PATHS: usize = 16;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    paths: Vec<(FuzzPath, bool, Option<FuzzPath>)>,
+    transform: FuzzTransform,
+    small_tiles: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let mut scene = Scene::new();
+    scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(1024.0, 768.0)));
+
+    let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+    for (path, even_odd, clip_path) in input.paths.iter().take(MAX_PATHS) {
+        let fill_rule = if *even_odd { FillRule::EvenOdd } else { FillRule::Winding };
+
+        let mut draw_path = DrawPath::new(path.to_outline(), paint);
+        draw_path.set_fill_rule(fill_rule);
+        if let Some(clip_path) = clip_path {
+            let mut clip_path = ClipPath::new(clip_path.to_outline());
+            clip_path.set_fill_rule(fill_rule);
+            draw_path.set_clip_path(Some(scene.push_clip_path(clip_path)));
+        }
+        scene.push_path(draw_path);
+    }
+
+    let tile_size = if input.small_tiles { TileSize::Tile8x8 } else { TileSize::Tile16x16 };
+    pathfinder_fuzz::build_scene(&mut scene, input.transform.to_transform(), tile_size);
+});
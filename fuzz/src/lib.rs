@@ -0,0 +1,234 @@
+// pathfinder/fuzz/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Input types and helpers shared by the fuzz targets.
+
+use arbitrary::Arbitrary;
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_content::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeAlignment};
+use pathfinder_content::stroke::StrokeStyle;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, vec2f};
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+use pathfinder_renderer::gpu_data::RenderCommand;
+use pathfinder_renderer::options::{BuildOptions, RenderTransform, TileSize};
+use pathfinder_renderer::scene::Scene;
+
+/// Coordinates with a larger magnitude than this are considered "huge".
+///
+/// Huge coordinates must not crash anything, but they may legitimately overflow to infinity during
+/// stroking or transformation, so the NaN checks only apply to inputs below this bound.
+pub const MAX_SANE_COORD: f32 = 1.0e6;
+
+/// The largest view box the fuzzers will build a scene for, in device pixels.
+///
+/// The tile maps are sized to the view box, so a huge one needs a lot of memory no matter what is
+/// drawn. Real applications size the view box to their window.
+pub const MAX_VIEW_BOX_SIZE: f32 = 4096.0;
+
+const MAX_COMMANDS: usize = 256;
+
+#[derive(Arbitrary, Clone, Copy, Debug)]
+pub enum PathCommand {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    QuadTo(f32, f32, f32, f32),
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    Ellipse(f32, f32, f32, f32, f32),
+    Close,
+}
+
+#[derive(Arbitrary, Clone, Copy, Debug)]
+pub struct FuzzTransform {
+    pub m11: f32,
+    pub m12: f32,
+    pub m13: f32,
+    pub m21: f32,
+    pub m22: f32,
+    pub m23: f32,
+}
+
+#[derive(Arbitrary, Clone, Copy, Debug)]
+pub struct FuzzStrokeStyle {
+    pub line_width: f32,
+    pub line_cap: u8,
+    pub line_join: u8,
+    pub miter_limit: f32,
+    pub alignment: u8,
+}
+
+#[derive(Arbitrary, Clone, Debug)]
+pub struct FuzzPath {
+    pub commands: Vec<PathCommand>,
+    pub stroke: Option<FuzzStrokeStyle>,
+}
+
+impl PathCommand {
+    /// Whether all of this command's coordinates are finite and of reasonable magnitude.
+    pub fn is_sane(&self) -> bool {
+        match *self {
+            PathCommand::MoveTo(x, y) | PathCommand::LineTo(x, y) => is_sane(&[x, y]),
+            PathCommand::QuadTo(a, b, c, d) => is_sane(&[a, b, c, d]),
+            PathCommand::CubicTo(a, b, c, d, e, f) => is_sane(&[a, b, c, d, e, f]),
+            PathCommand::Ellipse(a, b, c, d, e) => is_sane(&[a, b, c, d, e]),
+            PathCommand::Close => true,
+        }
+    }
+}
+
+impl FuzzTransform {
+    #[inline]
+    pub fn to_transform(&self) -> Transform2F {
+        Transform2F::row_major(self.m11, self.m12, self.m13, self.m21, self.m22, self.m23)
+    }
+
+    /// Whether the transform is finite and doesn't scale by an unreasonable amount.
+    ///
+    /// Degenerate (singular) transforms are considered sane; they're common in practice, e.g. when
+    /// animating a scale to zero.
+    pub fn is_sane(&self) -> bool {
+        let linear = [self.m11, self.m12, self.m21, self.m22];
+        is_sane(&[self.m13, self.m23]) && linear.iter().all(|m| m.is_finite() && m.abs() <= 1.0e3)
+    }
+}
+
+impl FuzzStrokeStyle {
+    pub fn to_stroke_style(&self) -> StrokeStyle {
+        let line_cap = match self.line_cap % 3 {
+            0 => LineCap::Butt,
+            1 => LineCap::Square,
+            _ => LineCap::Round,
+        };
+        let line_join = match self.line_join % 5 {
+            0 => LineJoin::Miter(self.miter_limit),
+            1 => LineJoin::Bevel,
+            2 => LineJoin::MiterClip(self.miter_limit),
+            3 => LineJoin::Arcs(self.miter_limit),
+            _ => LineJoin::Round,
+        };
+        let alignment = match self.alignment % 3 {
+            0 => StrokeAlignment::Center,
+            1 => StrokeAlignment::Inner,
+            _ => StrokeAlignment::Outer,
+        };
+        StrokeStyle { line_width: self.line_width, line_cap, line_join, alignment }
+    }
+
+    #[inline]
+    pub fn is_sane(&self) -> bool {
+        is_sane(&[self.line_width, self.miter_limit])
+    }
+}
+
+impl FuzzPath {
+    /// Builds the outline, stroking it if requested.
+    pub fn to_outline(&self) -> Outline {
+        let outline = build_outline(&self.commands);
+        match self.stroke {
+            None => outline,
+            Some(ref stroke) => {
+                let mut stroke_to_fill = OutlineStrokeToFill::new(&outline,
+                                                                  stroke.to_stroke_style());
+                stroke_to_fill.offset();
+                stroke_to_fill.into_outline()
+            }
+        }
+    }
+
+    pub fn is_sane(&self) -> bool {
+        self.commands.iter().all(PathCommand::is_sane) &&
+            self.stroke.map_or(true, |stroke| stroke.is_sane())
+    }
+}
+
+/// Builds an outline the way `Path2D` does, from at most `MAX_COMMANDS` commands.
+pub fn build_outline(commands: &[PathCommand]) -> Outline {
+    let mut outline = Outline::new();
+    let mut contour = Contour::new();
+    for command in commands.iter().take(MAX_COMMANDS) {
+        match *command {
+            PathCommand::MoveTo(x, y) => {
+                flush_contour(&mut outline, &mut contour);
+                contour.push_endpoint(vec2f(x, y));
+            }
+            PathCommand::LineTo(x, y) => contour.push_endpoint(vec2f(x, y)),
+            PathCommand::QuadTo(cx, cy, x, y) => {
+                contour.push_quadratic(vec2f(cx, cy), vec2f(x, y))
+            }
+            PathCommand::CubicTo(c0x, c0y, c1x, c1y, x, y) => {
+                contour.push_cubic(vec2f(c0x, c0y), vec2f(c1x, c1y), vec2f(x, y))
+            }
+            PathCommand::Ellipse(cx, cy, rx, ry, rotation) => {
+                flush_contour(&mut outline, &mut contour);
+                let transform = Transform2F::from_scale_rotation_translation(vec2f(rx, ry),
+                                                                             rotation,
+                                                                             vec2f(cx, cy));
+                contour.push_ellipse(&transform);
+                flush_contour(&mut outline, &mut contour);
+            }
+            PathCommand::Close => contour.close(),
+        }
+    }
+    flush_contour(&mut outline, &mut contour);
+    outline
+}
+
+fn flush_contour(outline: &mut Outline, contour: &mut Contour) {
+    if !contour.is_empty() {
+        outline.push_contour(std::mem::replace(contour, Contour::new()));
+    }
+}
+
+/// Returns true if every value is finite and no larger in magnitude than `MAX_SANE_COORD`.
+#[inline]
+pub fn is_sane(values: &[f32]) -> bool {
+    values.iter().all(|value| value.is_finite() && value.abs() <= MAX_SANE_COORD)
+}
+
+/// Panics if any point of the outline, or its bounds, is NaN.
+pub fn assert_no_nans(outline: &Outline, what: &str) {
+    let bounds = outline.bounds();
+    assert!(!has_nan(bounds.origin()) && !has_nan(bounds.lower_right()),
+            "{} produced NaN bounds: {:?}",
+            what,
+            bounds);
+    for contour in outline.contours() {
+        for point_index in 0..contour.len() {
+            let point = contour.position_of(point_index);
+            assert!(!has_nan(point), "{} produced a NaN point: {:?}", what, point);
+        }
+    }
+}
+
+#[inline]
+fn has_nan(point: Vector2F) -> bool {
+    point.x().is_nan() || point.y().is_nan()
+}
+
+/// Builds the scene on the current thread, discarding the render commands.
+///
+/// The view box is clamped to `MAX_VIEW_BOX_SIZE` so that memory use depends only on what's drawn.
+pub fn build_scene(scene: &mut Scene, transform: Transform2F, tile_size: TileSize) {
+    let max_view_box = RectF::new(Vector2F::zero(), Vector2F::splat(MAX_VIEW_BOX_SIZE));
+    let view_box = scene.view_box();
+    if !is_sane(&[view_box.min_x(), view_box.min_y(), view_box.max_x(), view_box.max_y()]) ||
+            view_box.width() > MAX_VIEW_BOX_SIZE || view_box.height() > MAX_VIEW_BOX_SIZE {
+        scene.set_view_box(max_view_box);
+    }
+
+    let options = BuildOptions {
+        transform: RenderTransform::Transform2D(transform),
+        tile_size,
+        ..BuildOptions::default()
+    };
+    let listener = Box::new(|_: RenderCommand| {});
+    scene.build(options, listener, &SequentialExecutor);
+}
@@ -9,9 +9,17 @@
 // except according to those terms.
 
 //! Basic geometry and linear algebra primitives, optimized with SIMD.
+//!
+//! Like `pathfinder_simd`, this crate can be used without the standard library by disabling the
+//! default `std` feature and enabling `libm`. The `pod` feature implements `bytemuck::Pod` for
+//! the vector, rectangle, and matrix types, so buffers of them can be uploaded to the GPU as is.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 pub mod angle;
 pub mod line_segment;
+#[cfg(feature = "pod")]
+mod pod;
 pub mod rect;
 pub mod transform2d;
 pub mod transform3d;
@@ -14,9 +14,11 @@ use crate::transform2d::Matrix2x2F;
 use crate::util;
 use crate::vector::{Vector2F, vec2f};
 use pathfinder_simd::default::F32x4;
-use std::ops::{Add, Mul, MulAssign, Sub};
+use pathfinder_simd::float;
+use core::ops::{Add, Mul, MulAssign, Sub};
 
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[repr(transparent)]
 pub struct LineSegment2F(pub F32x4);
 
 impl LineSegment2F {
@@ -92,7 +94,7 @@ impl LineSegment2F {
         debug_assert!(t >= 0.0 && t <= 1.0);
         let (from_from, to_to) = (self.0.xyxy(), self.0.zwzw());
         let d_d = to_to - from_from;
-        let mid_mid = from_from + d_d * F32x4::splat(t);
+        let mid_mid = from_from + d_d * t;
         (
             LineSegment2F(from_from.concat_xy_xy(mid_mid)),
             LineSegment2F(mid_mid.concat_xy_xy(to_to)),
@@ -207,7 +209,7 @@ impl LineSegment2F {
 
     #[inline]
     pub fn length(self) -> f32 {
-        self.square_length().sqrt()
+        float::sqrt(self.square_length())
     }
 
     #[inline]
@@ -219,7 +221,7 @@ impl LineSegment2F {
     pub fn intersection_t(self, other: LineSegment2F) -> Option<f32> {
         let p0p1 = self.vector();
         let matrix = Matrix2x2F(other.vector().0.concat_xy_xy((-p0p1).0));
-        if f32::abs(matrix.det()) < EPSILON {
+        if float::abs(matrix.det()) < EPSILON {
             return None;
         }
         return Some((matrix.inverse() * (self.from() - other.from())).y());
@@ -280,7 +282,7 @@ impl Mul<f32> for LineSegment2F {
     type Output = LineSegment2F;
     #[inline]
     fn mul(self, factor: f32) -> LineSegment2F {
-        LineSegment2F(self.0 * F32x4::splat(factor))
+        LineSegment2F(self.0 * factor)
     }
 }
 
@@ -0,0 +1,50 @@
+// pathfinder/geometry/src/pod.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! `bytemuck::Pod` impls for the geometry types that are plain data.
+//!
+//! The SIMD-backed types are `#[repr(transparent)]` wrappers around vectors that are `Pod`
+//! themselves. `Transform2F`, `Transform2D` and `Perspective` are left out, because they have
+//! padding after their last field, and so is `UnitVector`, whose zero value isn't a unit vector.
+
+use crate::line_segment::{LineSegment2F, LineSegmentU4, LineSegmentU8};
+use crate::rect::{RectF, RectI};
+use crate::transform2d::{Matrix2x2D, Matrix2x2F};
+use crate::transform3d::Transform4F;
+use crate::vector::{Vector2D, Vector2F, Vector2I, Vector3F, Vector4F};
+use bytemuck::{Pod, Zeroable};
+
+macro_rules! impl_pod {
+    ($($ty:ty),*) => {
+        $(
+            unsafe impl Zeroable for $ty {}
+            unsafe impl Pod for $ty {}
+        )*
+    }
+}
+
+impl_pod!(Vector2F, Vector2D, Vector2I, Vector3F, Vector4F);
+impl_pod!(RectF, RectI);
+impl_pod!(LineSegment2F, LineSegmentU4, LineSegmentU8);
+impl_pod!(Matrix2x2F, Matrix2x2D, Transform4F);
+
+#[cfg(test)]
+mod test {
+    use crate::rect::RectI;
+    use crate::vector::{Vector2F, Vector2I, vec2f};
+
+    #[test]
+    fn test_cast_slices() {
+        let points = [vec2f(1.0, 2.0), vec2f(3.0, 4.0)];
+        assert_eq!(bytemuck::cast_slice::<Vector2F, f32>(&points), &[1.0, 2.0, 3.0, 4.0]);
+        let rect = RectI::new(Vector2I::new(1, 2), Vector2I::new(3, 4));
+        assert_eq!(bytemuck::cast::<RectI, [i32; 4]>(rect), [1, 2, 4, 6]);
+    }
+}
@@ -12,9 +12,10 @@
 
 use crate::vector::{IntoVector2F, Vector2F, Vector2I};
 use pathfinder_simd::default::{F32x4, I32x4};
-use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
 
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[repr(transparent)]
 pub struct RectF(pub F32x4);
 
 impl RectF {
@@ -167,11 +168,37 @@ impl RectF {
         RectF(self.0.to_i32x4().to_f32x4())
     }
 
+    /// Rounds outward to the smallest integer-aligned rect containing this one.
     #[inline]
     pub fn round_out(self) -> RectF {
         RectF::from_points(self.origin().floor(), self.lower_right().ceil())
     }
 
+    /// Rounds inward to the largest integer-aligned rect inside this one. If there is none, the
+    /// result is empty.
+    #[inline]
+    pub fn round_in(self) -> RectF {
+        let origin = self.origin().ceil();
+        RectF::from_points(origin, self.lower_right().floor().max(origin))
+    }
+
+    /// Returns true if this rect and `other` overlap, or come within `tolerance` of overlapping.
+    ///
+    /// Rects that merely touch don't intersect, so this is useful for conservative tests where
+    /// floating-point error could otherwise make adjacent rects miss each other.
+    #[inline]
+    pub fn intersects_with_tolerance(self, other: RectF, tolerance: f32) -> bool {
+        self.dilate(tolerance).intersects(other)
+    }
+
+    /// Scales this rect by `factor` around its center, keeping the center in place.
+    #[inline]
+    pub fn uniform_scale_about_center(self, factor: f32) -> RectF {
+        let center = self.center();
+        let half_size = self.size() * (factor * 0.5);
+        RectF::from_points(center - half_size, center + half_size)
+    }
+
     #[inline]
     pub fn dilate<A>(self, amount: A) -> RectF where A: IntoVector2F {
         let amount = amount.into_vector_2f();
@@ -188,6 +215,18 @@ impl RectF {
     pub fn to_i32(&self) -> RectI {
         RectI(self.0.to_i32x4())
     }
+
+    /// Converts to the smallest integer rect containing this one.
+    #[inline]
+    pub fn to_i32_round_out(self) -> RectI {
+        self.round_out().to_i32()
+    }
+
+    /// Converts to the largest integer rect inside this one, which may be empty.
+    #[inline]
+    pub fn to_i32_round_in(self) -> RectI {
+        self.round_in().to_i32()
+    }
 }
 
 impl Add<Vector2F> for RectF {
@@ -239,7 +278,7 @@ impl Mul<f32> for RectF {
     type Output = RectF;
     #[inline]
     fn mul(self, factor: f32) -> RectF {
-        RectF(self.0 * F32x4::splat(factor))
+        RectF(self.0 * factor)
     }
 }
 
@@ -282,6 +321,7 @@ impl SubAssign<f32> for RectF {
 
 /// NB: The origin is inclusive, while the lower right point is exclusive.
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[repr(transparent)]
 pub struct RectI(pub I32x4);
 
 impl RectI {
@@ -427,3 +467,47 @@ impl Mul<i32> for RectI {
         RectI(self.0 * I32x4::splat(factor))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::rect::{RectF, RectI};
+    use crate::vector::{vec2f, vec2i};
+
+    #[test]
+    fn test_rounding() {
+        let rect = RectF::from_points(vec2f(0.5, -1.5), vec2f(3.25, 2.0));
+        assert_eq!(rect.round_out(), RectF::from_points(vec2f(0.0, -2.0), vec2f(4.0, 2.0)));
+        assert_eq!(rect.round_in(), RectF::from_points(vec2f(1.0, -1.0), vec2f(3.0, 2.0)));
+        assert_eq!(rect.to_i32_round_out(), RectI::from_points(vec2i(0, -2), vec2i(4, 2)));
+        assert_eq!(rect.to_i32_round_in(), RectI::from_points(vec2i(1, -1), vec2i(3, 2)));
+
+        // Rects too thin to contain a whole pixel round in to nothing.
+        let thin = RectF::from_points(vec2f(0.25, 0.0), vec2f(0.75, 10.0));
+        assert_eq!(thin.round_in().width(), 0.0);
+        assert_eq!(thin.to_i32_round_in().width(), 0);
+        assert_eq!(thin.to_i32_round_out(), RectI::from_points(vec2i(0, 0), vec2i(1, 10)));
+    }
+
+    #[test]
+    fn test_intersects_with_tolerance() {
+        let a = RectF::from_points(vec2f(0.0, 0.0), vec2f(10.0, 10.0));
+        let touching = RectF::from_points(vec2f(10.0, 0.0), vec2f(20.0, 10.0));
+        let near = RectF::from_points(vec2f(10.001, 0.0), vec2f(20.0, 10.0));
+        assert!(!a.intersects(touching));
+        assert!(a.intersects_with_tolerance(touching, 0.01));
+        assert!(a.intersects_with_tolerance(near, 0.01));
+        assert!(!a.intersects_with_tolerance(near, 0.0));
+        assert!(!a.intersects_with_tolerance(RectF::from_points(vec2f(11.0, 0.0),
+                                                                vec2f(20.0, 10.0)),
+                                             0.5));
+    }
+
+    #[test]
+    fn test_uniform_scale_about_center() {
+        let rect = RectF::from_points(vec2f(10.0, 20.0), vec2f(30.0, 60.0));
+        let scaled = rect.uniform_scale_about_center(1.5);
+        assert_eq!(scaled, RectF::from_points(vec2f(5.0, 10.0), vec2f(35.0, 70.0)));
+        assert_eq!(scaled.center(), rect.center());
+        assert_eq!(rect.uniform_scale_about_center(0.0).size(), vec2f(0.0, 0.0));
+    }
+}
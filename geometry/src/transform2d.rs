@@ -14,12 +14,14 @@ use crate::line_segment::LineSegment2F;
 use crate::rect::RectF;
 use crate::transform3d::Transform4F;
 use crate::unit_vector::UnitVector;
-use crate::vector::{IntoVector2F, Vector2F, vec2f};
-use pathfinder_simd::default::F32x4;
-use std::ops::{Mul, MulAssign, Sub};
+use crate::vector::{IntoVector2D, IntoVector2F, Vector2D, Vector2F, vec2d, vec2f};
+use pathfinder_simd::default::{F32x4, F64x2, F64x4};
+use pathfinder_simd::float;
+use core::ops::{Mul, MulAssign, Sub};
 
 /// A 2x2 matrix, optimized with SIMD, in column-major order.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(transparent)]
 pub struct Matrix2x2F(pub F32x4);
 
 impl Default for Matrix2x2F {
@@ -46,6 +48,22 @@ impl Matrix2x2F {
         Matrix2x2F((vector.0).0.to_f32x4().xyyx() * F32x4::new(1.0, 1.0, -1.0, 1.0))
     }
 
+    /// A shear that moves each point horizontally by `x` times its y coordinate and vertically
+    /// by `y` times its x coordinate.
+    #[inline]
+    pub fn from_shear(x: f32, y: f32) -> Matrix2x2F {
+        Matrix2x2F::row_major(1.0, x, y, 1.0)
+    }
+
+    /// A reflection across the line through the origin in the direction of `axis`.
+    #[inline]
+    pub fn from_reflection(axis: Vector2F) -> Matrix2x2F {
+        // Reflecting across a line at angle θ is rotating by 2θ and flipping the y axis.
+        let direction = UnitVector(axis.normalize());
+        let doubled = direction.rotate_by(direction).0;
+        Matrix2x2F::row_major(doubled.x(), doubled.y(), doubled.y(), -doubled.x())
+    }
+
     #[inline]
     pub fn row_major(m00: f32, m01: f32, m10: f32, m11: f32) -> Matrix2x2F {
         Matrix2x2F(F32x4::new(m00, m10, m01, m11))
@@ -73,7 +91,7 @@ impl Matrix2x2F {
 
     #[inline]
     pub fn scale(&self, factor: f32) -> Matrix2x2F {
-        Matrix2x2F(self.0 * F32x4::splat(factor))
+        Matrix2x2F(self.0 * factor)
     }
 
     /// Extracts the scale from this matrix.
@@ -183,6 +201,38 @@ impl Transform2F {
         Transform2F::from_scale(scale) * rotation * translation
     }
 
+    /// A shear that moves each point horizontally by `x` times its y coordinate and vertically
+    /// by `y` times its x coordinate.
+    ///
+    /// The SVG and canvas `skewX(θ)` transform is `from_shear(tan θ, 0.0)`.
+    #[inline]
+    pub fn from_shear(x: f32, y: f32) -> Transform2F {
+        Transform2F { matrix: Matrix2x2F::from_shear(x, y), vector: Vector2F::zero() }
+    }
+
+    /// A rotation by `theta` radians around `point` rather than the origin.
+    #[inline]
+    pub fn from_rotation_about(point: Vector2F, theta: f32) -> Transform2F {
+        Transform2F::from_translation(-point).rotate(theta).translate(point)
+    }
+
+    /// A reflection across the line through the origin in the direction of `axis`.
+    ///
+    /// To reflect across a line that doesn't pass through the origin, translate a point on it to
+    /// the origin first and back afterward.
+    #[inline]
+    pub fn from_reflection(axis: Vector2F) -> Transform2F {
+        Transform2F { matrix: Matrix2x2F::from_reflection(axis), vector: Vector2F::zero() }
+    }
+
+    /// The transform that maps `src` onto `dest`, scaling each axis independently.
+    #[inline]
+    pub fn from_rect_to_rect(src: RectF, dest: RectF) -> Transform2F {
+        let scale = dest.size() / src.size();
+        let vector = dest.origin() - src.origin() * scale;
+        Transform2F { matrix: Matrix2x2F::from_scale(scale), vector }
+    }
+
     #[inline]
     pub fn row_major(m11: f32, m12: f32, m13: f32, m21: f32, m22: f32, m23: f32) -> Transform2F {
         Transform2F {
@@ -279,7 +329,7 @@ impl Transform2F {
     /// This decomposition assumes that scale, rotation, and translation are applied in that order.
     #[inline]
     pub fn rotation(&self) -> f32 {
-        f32::atan2(self.m21(), self.m11())
+        float::atan2(self.m21(), self.m11())
     }
 
     /// Returns the scale factor of this matrix.
@@ -296,6 +346,14 @@ impl Transform2F {
         let vector_inv = -(matrix_inv * self.vector);
         Transform2F { matrix: matrix_inv, vector: vector_inv }
     }
+
+    #[inline]
+    pub fn to_f64(&self) -> Transform2D {
+        Transform2D {
+            matrix: Matrix2x2D(F64x4::from_f32x4(self.matrix.0)),
+            vector: self.vector.to_f64(),
+        }
+    }
 }
 
 impl Mul<Transform2F> for Transform2F {
@@ -343,3 +401,314 @@ impl MulAssign for Transform2F {
         *self = *self * other
     }
 }
+
+/// A 2x2 matrix of 64-bit floats, in column-major order, like `Matrix2x2F`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(transparent)]
+pub struct Matrix2x2D(pub F64x4);
+
+impl Default for Matrix2x2D {
+    #[inline]
+    fn default() -> Matrix2x2D {
+        Self::from_scale(1.0)
+    }
+}
+
+impl Matrix2x2D {
+    #[inline]
+    pub fn from_scale<S>(scale: S) -> Matrix2x2D where S: IntoVector2D {
+        let scale = scale.into_vector_2d();
+        Matrix2x2D(F64x4::new(scale.x(), 0.0, 0.0, scale.y()))
+    }
+
+    #[inline]
+    pub fn from_rotation(theta: f64) -> Matrix2x2D {
+        let (sin, cos) = (float::sin_f64(theta), float::cos_f64(theta));
+        Matrix2x2D(F64x4::new(cos, sin, -sin, cos))
+    }
+
+    #[inline]
+    pub fn row_major(m00: f64, m01: f64, m10: f64, m11: f64) -> Matrix2x2D {
+        Matrix2x2D(F64x4::new(m00, m10, m01, m11))
+    }
+
+    #[inline]
+    pub fn adjugate(&self) -> Matrix2x2D {
+        Matrix2x2D(self.0.shuffle::<3, 1, 2, 0>() * F64x4::new(1.0, -1.0, -1.0, 1.0))
+    }
+
+    #[inline]
+    pub fn det(&self) -> f64 {
+        self.0[0] * self.0[3] - self.0[2] * self.0[1]
+    }
+
+    #[inline]
+    pub fn inverse(&self) -> Matrix2x2D {
+        Matrix2x2D(F64x4::splat(1.0 / self.det()) * self.adjugate().0)
+    }
+
+    #[inline]
+    pub fn scale(&self, factor: f64) -> Matrix2x2D {
+        Matrix2x2D(self.0 * factor)
+    }
+
+    #[inline]
+    pub fn m11(&self) -> f64 {
+        self.0[0]
+    }
+
+    #[inline]
+    pub fn m21(&self) -> f64 {
+        self.0[1]
+    }
+
+    #[inline]
+    pub fn m12(&self) -> f64 {
+        self.0[2]
+    }
+
+    #[inline]
+    pub fn m22(&self) -> f64 {
+        self.0[3]
+    }
+
+    /// Rounds each entry to the nearest `f32`.
+    #[inline]
+    pub fn to_f32(&self) -> Matrix2x2F {
+        Matrix2x2F(self.0.to_f32x4())
+    }
+}
+
+impl Sub<Matrix2x2D> for Matrix2x2D {
+    type Output = Matrix2x2D;
+    #[inline]
+    fn sub(self, other: Matrix2x2D) -> Matrix2x2D {
+        Matrix2x2D(self.0 - other.0)
+    }
+}
+
+impl Mul<Matrix2x2D> for Matrix2x2D {
+    type Output = Matrix2x2D;
+    #[inline]
+    fn mul(self, other: Matrix2x2D) -> Matrix2x2D {
+        Matrix2x2D(self.0.shuffle::<0, 1, 0, 1>() * other.0.shuffle::<0, 0, 2, 2>() +
+                   self.0.shuffle::<2, 3, 2, 3>() * other.0.shuffle::<1, 1, 3, 3>())
+    }
+}
+
+impl Mul<Vector2D> for Matrix2x2D {
+    type Output = Vector2D;
+    #[inline]
+    fn mul(self, vector: Vector2D) -> Vector2D {
+        let xxyy = F64x4::from_halves(F64x2::splat(vector.x()), F64x2::splat(vector.y()));
+        let halves = self.0 * xxyy;
+        Vector2D(halves.lo() + halves.hi())
+    }
+}
+
+/// An affine transform with 64-bit floating point entries.
+///
+/// This is for transforms that need to keep their precision far from the origin, such as the
+/// transform from map to screen coordinates for a map whose coordinates are in the millions.
+/// Compose such transforms here, and convert the result to a `Transform2F` with `to_f32()` once
+/// it maps the geometry near the origin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform2D {
+    pub matrix: Matrix2x2D,
+    pub vector: Vector2D,
+}
+
+impl Default for Transform2D {
+    #[inline]
+    fn default() -> Transform2D {
+        Self::from_scale(vec2d(1.0, 1.0))
+    }
+}
+
+impl Transform2D {
+    #[inline]
+    pub fn from_scale<S>(scale: S) -> Transform2D where S: IntoVector2D {
+        Transform2D { matrix: Matrix2x2D::from_scale(scale), vector: Vector2D::zero() }
+    }
+
+    #[inline]
+    pub fn from_rotation(theta: f64) -> Transform2D {
+        Transform2D { matrix: Matrix2x2D::from_rotation(theta), vector: Vector2D::zero() }
+    }
+
+    #[inline]
+    pub fn from_translation(vector: Vector2D) -> Transform2D {
+        Transform2D { matrix: Matrix2x2D::default(), vector }
+    }
+
+    #[inline]
+    pub fn row_major(m11: f64, m12: f64, m13: f64, m21: f64, m22: f64, m23: f64) -> Transform2D {
+        Transform2D {
+            matrix: Matrix2x2D::row_major(m11, m12, m21, m22),
+            vector: Vector2D::new(m13, m23),
+        }
+    }
+
+    #[inline]
+    pub fn is_identity(&self) -> bool {
+        *self == Transform2D::default()
+    }
+
+    #[inline]
+    pub fn m11(&self) -> f64 {
+        self.matrix.m11()
+    }
+    #[inline]
+    pub fn m21(&self) -> f64 {
+        self.matrix.m21()
+    }
+    #[inline]
+    pub fn m12(&self) -> f64 {
+        self.matrix.m12()
+    }
+    #[inline]
+    pub fn m22(&self) -> f64 {
+        self.matrix.m22()
+    }
+    #[inline]
+    pub fn m13(&self) -> f64 {
+        self.vector.x()
+    }
+    #[inline]
+    pub fn m23(&self) -> f64 {
+        self.vector.y()
+    }
+
+    #[inline]
+    pub fn translate(&self, vector: Vector2D) -> Transform2D {
+        Transform2D::from_translation(vector) * *self
+    }
+
+    #[inline]
+    pub fn rotate(&self, theta: f64) -> Transform2D {
+        Transform2D::from_rotation(theta) * *self
+    }
+
+    #[inline]
+    pub fn scale<S>(&self, scale: S) -> Transform2D where S: IntoVector2D {
+        Transform2D::from_scale(scale) * *self
+    }
+
+    #[inline]
+    pub fn translation(&self) -> Vector2D {
+        self.vector
+    }
+
+    #[inline]
+    pub fn inverse(&self) -> Transform2D {
+        let matrix_inv = self.matrix.inverse();
+        let vector_inv = -(matrix_inv * self.vector);
+        Transform2D { matrix: matrix_inv, vector: vector_inv }
+    }
+
+    /// Rounds each entry to the nearest `f32`.
+    #[inline]
+    pub fn to_f32(&self) -> Transform2F {
+        Transform2F { matrix: self.matrix.to_f32(), vector: self.vector.to_f32() }
+    }
+}
+
+impl Mul<Transform2D> for Transform2D {
+    type Output = Transform2D;
+    #[inline]
+    fn mul(self, other: Transform2D) -> Transform2D {
+        Transform2D {
+            matrix: self.matrix * other.matrix,
+            vector: self * other.vector,
+        }
+    }
+}
+
+impl Mul<Vector2D> for Transform2D {
+    type Output = Vector2D;
+    #[inline]
+    fn mul(self, vector: Vector2D) -> Vector2D {
+        self.matrix * vector + self.vector
+    }
+}
+
+impl MulAssign for Transform2D {
+    #[inline]
+    fn mul_assign(&mut self, other: Transform2D) {
+        *self = *self * other
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rect::RectF;
+    use crate::transform2d::{Transform2D, Transform2F};
+    use crate::vector::{Vector2F, vec2d, vec2f};
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_close(actual: Vector2F, expected: Vector2F) {
+        assert!((actual - expected).length() < 0.0001, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn test_shear() {
+        let shear = Transform2F::from_shear(2.0, 0.5);
+        assert_eq!(shear * vec2f(0.0, 0.0), vec2f(0.0, 0.0));
+        assert_eq!(shear * vec2f(1.0, 0.0), vec2f(1.0, 0.5));
+        assert_eq!(shear * vec2f(0.0, 1.0), vec2f(2.0, 1.0));
+        assert_eq!(shear * vec2f(3.0, 4.0), vec2f(11.0, 5.5));
+    }
+
+    #[test]
+    fn test_rotation_about() {
+        let rotation = Transform2F::from_rotation_about(vec2f(10.0, 20.0), FRAC_PI_2);
+        assert_close(rotation * vec2f(10.0, 20.0), vec2f(10.0, 20.0));
+        assert_close(rotation * vec2f(11.0, 20.0), vec2f(10.0, 21.0));
+        assert_close(rotation * vec2f(10.0, 22.0), vec2f(8.0, 20.0));
+        assert_close(rotation.inverse() * vec2f(10.0, 21.0), vec2f(11.0, 20.0));
+    }
+
+    #[test]
+    fn test_reflection() {
+        let flip_y = Transform2F::from_reflection(vec2f(3.0, 0.0));
+        assert_close(flip_y * vec2f(2.0, 5.0), vec2f(2.0, -5.0));
+
+        let diagonal = Transform2F::from_reflection(vec2f(1.0, 1.0));
+        assert_close(diagonal * vec2f(2.0, 5.0), vec2f(5.0, 2.0));
+        assert_close(diagonal * vec2f(-1.0, -1.0), vec2f(-1.0, -1.0));
+        assert_close((diagonal * diagonal) * vec2f(2.0, 5.0), vec2f(2.0, 5.0));
+        assert!(diagonal.matrix.det() < 0.0);
+    }
+
+    #[test]
+    fn test_rect_to_rect() {
+        let src = RectF::new(vec2f(10.0, 10.0), vec2f(20.0, 40.0));
+        let dest = RectF::new(vec2f(-5.0, 100.0), vec2f(10.0, 10.0));
+        let transform = Transform2F::from_rect_to_rect(src, dest);
+        assert_eq!(transform * src.origin(), dest.origin());
+        assert_eq!(transform * src.lower_right(), dest.lower_right());
+        assert_eq!(transform * src, dest);
+        assert_eq!(transform * src.center(), dest.center());
+    }
+
+    #[test]
+    fn test_double_precision() {
+        // A point on a map in projected coordinates, a few meters from the map's viewport origin.
+        let point = vec2d(4_512_345.125, 5_678_901.75);
+        let origin = vec2d(4_512_340.0, 5_678_900.0);
+        let transform = Transform2D::from_translation(-origin).scale(2.0).rotate(0.5);
+        let expected = Transform2D::from_rotation(0.5) * vec2d(10.25, 3.5);
+        assert!((transform * point - expected).length() < 1.0e-6);
+        assert!((transform.inverse() * (transform * point) - point).length() < 1.0e-6);
+
+        // The same transform in single precision is off by more than half a unit.
+        let transform_f32 = Transform2F::from_translation(-origin.to_f32()).scale(2.0);
+        assert!((transform_f32 * point.to_f32() - vec2f(10.25, 3.5)).length() > 0.5);
+
+        // Converting once the point is near the origin keeps the precision.
+        let transform = Transform2D::from_translation(-origin).scale(2.0);
+        assert_eq!(transform.to_f32() * vec2f(0.0, 0.0), (transform * vec2d(0.0, 0.0)).to_f32());
+        assert_eq!((transform * point).to_f32(), vec2f(10.25, 3.5));
+        assert_eq!(Transform2F::from_scale(3.0).to_f64().to_f32(), Transform2F::from_scale(3.0));
+    }
+}
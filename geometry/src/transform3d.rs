@@ -10,11 +10,20 @@
 
 //! 3D transforms that can be applied to paths.
 
+use crate::line_segment::LineSegment2F;
 use crate::rect::RectF;
 use crate::transform2d::Matrix2x2F;
 use crate::vector::{Vector2F, Vector2I, Vector3F, Vector4F};
 use pathfinder_simd::default::F32x4;
-use std::ops::{Add, Mul, MulAssign, Neg};
+use pathfinder_simd::float;
+use core::ops::{Add, Mul, MulAssign, Neg};
+
+/// Points whose w coordinate is no greater than this after a projective transform are treated as
+/// being behind the camera.
+///
+/// Dividing by a w near zero produces enormous or infinite coordinates, and dividing by a negative
+/// w reflects the point through the eye, so such points must be clipped away before projection.
+pub const NEAR_W_EPSILON: f32 = 0.0001;
 
 /// An transform, optimized with SIMD.
 ///
@@ -91,9 +100,9 @@ impl Transform4F {
 
     // TODO(pcwalton): Optimize.
     pub fn from_rotation(yaw: f32, pitch: f32, roll: f32) -> Transform4F {
-        let (cos_b, sin_b) = (yaw.cos(), yaw.sin());
-        let (cos_c, sin_c) = (pitch.cos(), pitch.sin());
-        let (cos_a, sin_a) = (roll.cos(), roll.sin());
+        let (cos_b, sin_b) = (float::cos(yaw), float::sin(yaw));
+        let (cos_c, sin_c) = (float::cos(pitch), float::sin(pitch));
+        let (cos_a, sin_a) = (float::cos(roll), float::sin(roll));
         let m00 = cos_a * cos_b;
         let m01 = cos_a * sin_b * sin_c - sin_a * cos_c;
         let m02 = cos_a * sin_b * cos_c + sin_a * sin_c;
@@ -179,17 +188,17 @@ impl Transform4F {
 
     /// Linearly interpolate between transforms
     pub fn lerp(&self, weight: f32, other: &Transform4F) -> Transform4F {
-        let c0 = self.c0 * F32x4::splat(weight) + other.c0 * F32x4::splat(1.0 - weight);
-        let c1 = self.c1 * F32x4::splat(weight) + other.c1 * F32x4::splat(1.0 - weight);
-        let c2 = self.c2 * F32x4::splat(weight) + other.c2 * F32x4::splat(1.0 - weight);
-        let c3 = self.c3 * F32x4::splat(weight) + other.c3 * F32x4::splat(1.0 - weight);
+        let c0 = self.c0 * weight + other.c0 * (1.0 - weight);
+        let c1 = self.c1 * weight + other.c1 * (1.0 - weight);
+        let c2 = self.c2 * weight + other.c2 * (1.0 - weight);
+        let c3 = self.c3 * weight + other.c3 * (1.0 - weight);
         Transform4F { c0, c1, c2, c3 }
     }
 
     /// Just like `gluPerspective()`.
     #[inline]
     pub fn from_perspective(fov_y: f32, aspect: f32, z_near: f32, z_far: f32) -> Transform4F {
-        let f = 1.0 / (fov_y * 0.5).tan();
+        let f = 1.0 / float::tan(fov_y * 0.5);
         let z_denom = 1.0 / (z_near - z_far);
         let m00 = f / aspect;
         let m11 = f;
@@ -309,6 +318,32 @@ impl Transform4F {
             && self.c3.approx_eq(other.c3, epsilon)
     }
 
+    /// Transforms `point` and performs the perspective division, returning normalized device
+    /// coordinates, or `None` if the point is behind the camera.
+    #[inline]
+    pub fn project(&self, point: Vector4F) -> Option<Vector2F> {
+        let point = *self * point;
+        if point.w() > NEAR_W_EPSILON {
+            Some(point.to_2d())
+        } else {
+            None
+        }
+    }
+
+    /// Transforms the given 2D line segment, clips away the part behind the camera, and performs
+    /// the perspective division on the rest. Returns `None` if the segment is entirely behind the
+    /// camera.
+    pub fn project_line_segment(&self, segment: LineSegment2F) -> Option<LineSegment2F> {
+        let (mut from, mut to) = (*self * segment.from().to_4d(), *self * segment.to().to_4d());
+        match (from.w() > NEAR_W_EPSILON, to.w() > NEAR_W_EPSILON) {
+            (false, false) => return None,
+            (true, true) => {}
+            (true, false) => to = from.lerp(to, (from.w() - NEAR_W_EPSILON) / (from.w() - to.w())),
+            (false, true) => from = to.lerp(from, (to.w() - NEAR_W_EPSILON) / (to.w() - from.w())),
+        }
+        Some(LineSegment2F::new(from.to_2d(), to.to_2d()))
+    }
+
     #[inline]
     pub fn as_ptr(&self) -> *const f32 {
         (&self.c0) as *const F32x4 as *const f32
@@ -345,10 +380,10 @@ impl Mul<Vector4F> for Transform4F {
 
     #[inline]
     fn mul(self, vector: Vector4F) -> Vector4F {
-        let term0 = self.c0 * F32x4::splat(vector.x());
-        let term1 = self.c1 * F32x4::splat(vector.y());
-        let term2 = self.c2 * F32x4::splat(vector.z());
-        let term3 = self.c3 * F32x4::splat(vector.w());
+        let term0 = self.c0 * vector.x();
+        let term1 = self.c1 * vector.y();
+        let term2 = self.c2 * vector.z();
+        let term3 = self.c3 * vector.w();
         Vector4F(term0 + term1 + term2 + term3)
     }
 }
@@ -389,6 +424,25 @@ impl Perspective {
             window_size,
         }
     }
+
+    /// Projects `point` into window coordinates, or returns `None` if it's behind the camera.
+    #[inline]
+    pub fn project(&self, point: Vector2F) -> Option<Vector2F> {
+        self.transform.project(point.to_4d()).map(|point| self.ndc_to_window(point))
+    }
+
+    /// Projects a line segment into window coordinates, clipping away the part behind the
+    /// camera. Returns `None` if the segment is entirely behind the camera.
+    pub fn project_line_segment(&self, segment: LineSegment2F) -> Option<LineSegment2F> {
+        let segment = self.transform.project_line_segment(segment)?;
+        let (from, to) = (self.ndc_to_window(segment.from()), self.ndc_to_window(segment.to()));
+        Some(LineSegment2F::new(from, to))
+    }
+
+    #[inline]
+    fn ndc_to_window(&self, point: Vector2F) -> Vector2F {
+        (point * Vector2F::new(1.0, -1.0) + 1.0) * self.window_size.to_f32() * 0.5
+    }
 }
 
 impl Mul<Transform4F> for Perspective {
@@ -406,8 +460,7 @@ impl Mul<Vector2F> for Perspective {
     type Output = Vector2F;
     #[inline]
     fn mul(self, vector: Vector2F) -> Vector2F {
-        let point = (self.transform * vector.to_4d()).to_2d() * Vector2F::new(1.0, -1.0);
-        (point + 1.0) * self.window_size.to_f32() * 0.5
+        self.ndc_to_window((self.transform * vector.to_4d()).to_2d())
     }
 }
 
@@ -425,8 +478,9 @@ impl Mul<RectF> for Perspective {
 
 #[cfg(test)]
 mod test {
-    use crate::vector::Vector4F;
-    use crate::transform3d::Transform4F;
+    use crate::line_segment::LineSegment2F;
+    use crate::vector::{Vector2I, Vector4F, vec2f};
+    use crate::transform3d::{NEAR_W_EPSILON, Perspective, Transform4F};
 
     #[test]
     fn test_post_mul() {
@@ -501,4 +555,44 @@ mod test {
         let p2 = m_inv * p1;
         assert!(p0.approx_eq(p2, 0.0001));
     }
+
+    // A perspective transform under which w = 1 - y, so points with y >= 1 are behind the camera.
+    fn tilted_perspective() -> Transform4F {
+        Transform4F::row_major(
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, -1.0, 0.0, 1.0,
+        )
+    }
+
+    #[test]
+    fn test_project() {
+        let transform = tilted_perspective();
+        assert_eq!(transform.project(Vector4F::new(1.0, 0.5, 0.0, 1.0)), Some(vec2f(2.0, 1.0)));
+        assert_eq!(transform.project(Vector4F::new(1.0, 1.0, 0.0, 1.0)), None);
+        assert_eq!(transform.project(Vector4F::new(1.0, 3.0, 0.0, 1.0)), None);
+
+        let perspective = Perspective::new(&transform, Vector2I::new(100, 100));
+        assert_eq!(perspective.project(vec2f(0.0, 0.0)), Some(vec2f(50.0, 50.0)));
+        assert_eq!(perspective.project(vec2f(0.0, 2.0)), None);
+    }
+
+    #[test]
+    fn test_project_line_segment() {
+        let transform = tilted_perspective();
+        let visible = LineSegment2F::new(vec2f(0.0, 0.0), vec2f(1.0, 0.5));
+        assert_eq!(transform.project_line_segment(visible),
+                   Some(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(2.0, 1.0))));
+        let hidden = LineSegment2F::new(vec2f(0.0, 2.0), vec2f(1.0, 3.0));
+        assert_eq!(transform.project_line_segment(hidden), None);
+
+        // Segments crossing the near plane are cut off where w reaches the epsilon, so their
+        // projections are large but finite.
+        for &segment in &[LineSegment2F::new(vec2f(0.0, 0.0), vec2f(0.0, 2.0)),
+                          LineSegment2F::new(vec2f(0.0, 2.0), vec2f(0.0, 0.0))] {
+            let projected = transform.project_line_segment(segment).unwrap();
+            let far_end = if segment.from().y() > 0.0 { projected.from() } else { projected.to() };
+            assert!(far_end.y().is_finite());
+            let expected = (1.0 - NEAR_W_EPSILON) / NEAR_W_EPSILON;
+            assert!((far_end.y() - expected).abs() < expected * 0.01);
+        }
+    }
 }
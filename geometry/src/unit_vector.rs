@@ -12,14 +12,16 @@
 
 use crate::vector::Vector2F;
 use pathfinder_simd::default::F32x2;
+use pathfinder_simd::float;
 
 #[derive(Clone, Copy, Debug)]
+#[repr(transparent)]
 pub struct UnitVector(pub Vector2F);
 
 impl UnitVector {
     #[inline]
     pub fn from_angle(theta: f32) -> UnitVector {
-        UnitVector(Vector2F::new(theta.cos(), theta.sin()))
+        UnitVector(Vector2F::new(float::cos(theta), float::sin(theta)))
     }
 
     /// Angle addition formula.
@@ -10,14 +10,15 @@
 
 //! Various utilities.
 
-use std::f32;
+use core::f32;
+use pathfinder_simd::float;
 
 pub const EPSILON: f32 = 0.001;
 
 /// Approximate equality.
 #[inline]
 pub fn approx_eq(a: f32, b: f32) -> bool {
-    f32::abs(a - b) <= EPSILON
+    float::abs(a - b) <= EPSILON
 }
 
 /// Linear interpolation.
@@ -10,12 +10,14 @@
 
 //! A SIMD-optimized point type.
 
-use pathfinder_simd::default::{F32x2, F32x4, I32x2};
-use std::hash::{Hash, Hasher};
-use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+use pathfinder_simd::default::{F32x2, F32x4, F64x2, I32x2};
+use pathfinder_simd::float;
+use core::hash::{Hash, Hasher};
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
 /// 2D points with 32-bit floating point coordinates.
 #[derive(Clone, Copy, Debug, Default)]
+#[repr(transparent)]
 pub struct Vector2F(pub F32x2);
 
 impl Vector2F {
@@ -86,8 +88,7 @@ impl Vector2F {
 
     #[inline]
     pub fn dot(self, other: Vector2F) -> f32 {
-        let xy = self.0 * other.0;
-        xy.x() + xy.y()
+        self.0.dot(other.0)
     }
 
     #[inline]
@@ -109,14 +110,13 @@ impl Vector2F {
     /// Treats this point as a vector and calculates its squared length.
     #[inline]
     pub fn square_length(self) -> f32 {
-        let squared = self.0 * self.0;
-        squared[0] + squared[1]
+        self.0.dot(self.0)
     }
 
     /// Treats this point as a vector and calculates its length.
     #[inline]
     pub fn length(self) -> f32 {
-        f32::sqrt(self.square_length())
+        float::sqrt(self.square_length())
     }
 
     /// Treats this point as a vector and normalizes it.
@@ -160,6 +160,11 @@ impl Vector2F {
     pub fn to_i32(self) -> Vector2I {
         Vector2I(self.0.to_i32x2())
     }
+
+    #[inline]
+    pub fn to_f64(self) -> Vector2D {
+        Vector2D(F64x2::from_f32x2(self.0))
+    }
 }
 
 /// A convenience alias for `Vector2F::new()`.
@@ -299,8 +304,267 @@ impl IntoVector2F for f32 {
     }
 }
 
+/// 2D points with 64-bit floating point coordinates.
+///
+/// These are for geometry that needs more precision than `Vector2F` has, such as map coordinates
+/// in the millions, which lose their fractional parts in 32 bits. Convert them with `to_f32()`
+/// once they're near the origin.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(transparent)]
+pub struct Vector2D(pub F64x2);
+
+impl Vector2D {
+    #[inline]
+    pub fn new(x: f64, y: f64) -> Vector2D {
+        Vector2D(F64x2::new(x, y))
+    }
+
+    #[inline]
+    pub fn splat(value: f64) -> Vector2D {
+        Vector2D(F64x2::splat(value))
+    }
+
+    #[inline]
+    pub fn zero() -> Vector2D {
+        Vector2D::default()
+    }
+
+    #[inline]
+    pub fn x(self) -> f64 {
+        self.0[0]
+    }
+
+    #[inline]
+    pub fn y(self) -> f64 {
+        self.0[1]
+    }
+
+    #[inline]
+    pub fn set_x(&mut self, x: f64) {
+        self.0[0] = x;
+    }
+
+    #[inline]
+    pub fn set_y(&mut self, y: f64) {
+        self.0[1] = y;
+    }
+
+    #[inline]
+    pub fn min(self, other: Vector2D) -> Vector2D {
+        Vector2D(self.0.min(other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: Vector2D) -> Vector2D {
+        Vector2D(self.0.max(other.0))
+    }
+
+    #[inline]
+    pub fn clamp(self, min_val: Vector2D, max_val: Vector2D) -> Vector2D {
+        self.max(min_val).min(max_val)
+    }
+
+    #[inline]
+    pub fn det(self, other: Vector2D) -> f64 {
+        self.x() * other.y() - self.y() * other.x()
+    }
+
+    #[inline]
+    pub fn dot(self, other: Vector2D) -> f64 {
+        self.0.dot(other.0)
+    }
+
+    #[inline]
+    pub fn floor(self) -> Vector2D {
+        Vector2D(self.0.floor())
+    }
+
+    #[inline]
+    pub fn ceil(self) -> Vector2D {
+        Vector2D(self.0.ceil())
+    }
+
+    /// Rounds both coordinates to the nearest integer, with halfway cases rounded to even.
+    #[inline]
+    pub fn round(self) -> Vector2D {
+        Vector2D(self.0.round())
+    }
+
+    /// Treats this point as a vector and calculates its squared length.
+    #[inline]
+    pub fn square_length(self) -> f64 {
+        self.0.dot(self.0)
+    }
+
+    /// Treats this point as a vector and calculates its length.
+    #[inline]
+    pub fn length(self) -> f64 {
+        float::sqrt_f64(self.square_length())
+    }
+
+    /// Treats this point as a vector and normalizes it.
+    #[inline]
+    pub fn normalize(self) -> Vector2D {
+        self * (1.0 / self.length())
+    }
+
+    /// Swaps y and x.
+    #[inline]
+    pub fn yx(self) -> Vector2D {
+        Vector2D(self.0.yx())
+    }
+
+    /// Returns the vector (|x|, |y|).
+    #[inline]
+    pub fn abs(self) -> Vector2D {
+        Vector2D(self.0.abs())
+    }
+
+    #[inline]
+    pub fn is_zero(self) -> bool {
+        self == Vector2D::zero()
+    }
+
+    #[inline]
+    pub fn lerp(self, other: Vector2D, t: f64) -> Vector2D {
+        self + (other - self) * t
+    }
+
+    /// Rounds both coordinates to the nearest `f32`.
+    #[inline]
+    pub fn to_f32(self) -> Vector2F {
+        Vector2F(self.0.to_f32x2())
+    }
+}
+
+/// A convenience alias for `Vector2D::new()`.
+#[inline]
+pub fn vec2d(x: f64, y: f64) -> Vector2D {
+    Vector2D::new(x, y)
+}
+
+impl Add<Vector2D> for Vector2D {
+    type Output = Vector2D;
+    #[inline]
+    fn add(self, other: Vector2D) -> Vector2D {
+        Vector2D(self.0 + other.0)
+    }
+}
+
+impl Add<f64> for Vector2D {
+    type Output = Vector2D;
+    #[inline]
+    fn add(self, other: f64) -> Vector2D {
+        self + Vector2D::splat(other)
+    }
+}
+
+impl AddAssign<Vector2D> for Vector2D {
+    #[inline]
+    fn add_assign(&mut self, other: Vector2D) {
+        *self = *self + other
+    }
+}
+
+impl Sub<Vector2D> for Vector2D {
+    type Output = Vector2D;
+    #[inline]
+    fn sub(self, other: Vector2D) -> Vector2D {
+        Vector2D(self.0 - other.0)
+    }
+}
+
+impl Sub<f64> for Vector2D {
+    type Output = Vector2D;
+    #[inline]
+    fn sub(self, other: f64) -> Vector2D {
+        self - Vector2D::splat(other)
+    }
+}
+
+impl SubAssign<Vector2D> for Vector2D {
+    #[inline]
+    fn sub_assign(&mut self, other: Vector2D) {
+        *self = *self - other
+    }
+}
+
+impl Mul<Vector2D> for Vector2D {
+    type Output = Vector2D;
+    #[inline]
+    fn mul(self, other: Vector2D) -> Vector2D {
+        Vector2D(self.0 * other.0)
+    }
+}
+
+impl Mul<f64> for Vector2D {
+    type Output = Vector2D;
+    #[inline]
+    fn mul(self, other: f64) -> Vector2D {
+        self * Vector2D::splat(other)
+    }
+}
+
+impl MulAssign<Vector2D> for Vector2D {
+    #[inline]
+    fn mul_assign(&mut self, other: Vector2D) {
+        *self = *self * other
+    }
+}
+
+impl MulAssign<f64> for Vector2D {
+    #[inline]
+    fn mul_assign(&mut self, other: f64) {
+        *self = *self * other
+    }
+}
+
+impl Div<Vector2D> for Vector2D {
+    type Output = Vector2D;
+    #[inline]
+    fn div(self, other: Vector2D) -> Vector2D {
+        Vector2D(self.0 / other.0)
+    }
+}
+
+impl Div<f64> for Vector2D {
+    type Output = Vector2D;
+    #[inline]
+    fn div(self, other: f64) -> Vector2D {
+        self / Vector2D::splat(other)
+    }
+}
+
+impl Neg for Vector2D {
+    type Output = Vector2D;
+    #[inline]
+    fn neg(self) -> Vector2D {
+        Vector2D(-self.0)
+    }
+}
+
+/// Either a scalar or a `Vector2D`, which works like `IntoVector2F`.
+pub trait IntoVector2D {
+    fn into_vector_2d(self) -> Vector2D;
+}
+
+impl IntoVector2D for Vector2D {
+    #[inline]
+    fn into_vector_2d(self) -> Vector2D {
+        self
+    }
+}
+
+impl IntoVector2D for f64 {
+    #[inline]
+    fn into_vector_2d(self) -> Vector2D {
+        Vector2D::splat(self)
+    }
+}
+
 /// 2D points with 32-bit signed integer coordinates.
 #[derive(Clone, Copy, Debug, Default)]
+#[repr(transparent)]
 pub struct Vector2I(pub I32x2);
 
 impl Vector2I {
@@ -445,6 +709,7 @@ impl Hash for Vector2I {
 ///
 /// The w value in the SIMD vector is always 0.0.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(transparent)]
 pub struct Vector3F(pub F32x4);
 
 impl Vector3F {
@@ -476,7 +741,7 @@ impl Vector3F {
 
     #[inline]
     pub fn cross(self, other: Vector3F) -> Vector3F {
-        Vector3F(self.0.yzxw() * other.0.zxyw() - self.0.zxyw() * other.0.yzxw())
+        Vector3F(self.0.cross(other.0))
     }
 
     #[inline]
@@ -487,7 +752,7 @@ impl Vector3F {
 
     #[inline]
     pub fn length(self) -> f32 {
-        f32::sqrt(self.square_length())
+        float::sqrt(self.square_length())
     }
 
     #[inline]
@@ -512,7 +777,7 @@ impl Vector3F {
 
     #[inline]
     pub fn scale(self, factor: f32) -> Vector3F {
-        Vector3F(self.0 * F32x4::splat(factor))
+        Vector3F(self.0 * factor)
     }
 }
 
@@ -549,6 +814,7 @@ impl Sub<Vector3F> for Vector3F {
 
 /// 3D homogeneous points.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(transparent)]
 pub struct Vector4F(pub F32x4);
 
 impl Vector4F {
@@ -638,7 +904,7 @@ impl Vector4F {
 
     #[inline]
     pub fn lerp(self, other: Vector4F, t: f32) -> Vector4F {
-        Vector4F(self.0 + (other.0 - self.0) * F32x4::splat(t))
+        Vector4F(self.0.lerp(other.0, F32x4::splat(t)))
     }
 }
 
@@ -18,19 +18,24 @@ use gl::types::{GLuint, GLvoid};
 use half::f16;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
-use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode, ClearOps};
-use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, FeatureLevel};
+use pathfinder_gpu::{BlendFactor, BlendOp, BlendState, BufferData, BufferTarget, BufferUploadMode};
+use pathfinder_gpu::{ClearOps, ComputeDimensions, ComputeState, DepthFunc, DepthState, Device};
+use pathfinder_gpu::{DeviceCapabilities, FeatureLevel};
 use pathfinder_gpu::{ImageAccess, ImageBinding, Primitive, ProgramKind, RenderOptions};
-use pathfinder_gpu::{RenderState, RenderTarget, ShaderKind, StencilFunc, TextureBinding, TextureData};
+use pathfinder_gpu::{RenderState, RenderTarget, ShaderKind, StencilFunc, StencilState};
+use pathfinder_gpu::{TextureBinding, TextureData};
 use pathfinder_gpu::{TextureDataRef, TextureFormat, TextureSamplingFlags, UniformData};
 use pathfinder_gpu::{VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
+use pathfinder_gpu::error::RenderError;
+use pathfinder_gpu::program_cache::{ProgramCache, ProgramCacheKey};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::F32x4;
 use std::cell::RefCell;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::mem;
 use std::ptr;
 use std::str;
+use std::sync::Arc;
 use std::time::Duration;
 
 const DUMMY_TEXTURE_LENGTH: i32 = 16;
@@ -39,6 +44,13 @@ pub struct GLDevice {
     version: GLVersion,
     default_framebuffer: GLuint,
     dummy_texture: GLTexture,
+    capabilities: DeviceCapabilities,
+    direct_state_access: bool,
+    state: RefCell<GLStateCache>,
+    program_cache: Option<Arc<ProgramCache>>,
+    // The vendor, renderer, and version strings, which are part of program cache keys, since
+    // binaries only work with the driver that produced them.
+    driver_id: Vec<u8>,
 }
 
 impl GLDevice {
@@ -50,7 +62,17 @@ impl GLDevice {
             format: TextureFormat::RGBA8,
         };
 
-        let mut device = GLDevice { version, default_framebuffer, dummy_texture };
+        let (capabilities, direct_state_access) = query_capabilities(version);
+        let mut device = GLDevice {
+            version,
+            default_framebuffer,
+            dummy_texture,
+            capabilities,
+            direct_state_access,
+            state: RefCell::new(GLStateCache::default()),
+            program_cache: None,
+            driver_id: vec![],
+        };
         let dummy_texture_data =
             [0; DUMMY_TEXTURE_LENGTH as usize * DUMMY_TEXTURE_LENGTH as usize * 4];
         device.dummy_texture =
@@ -64,13 +86,18 @@ impl GLDevice {
         self.default_framebuffer = framebuffer;
     }
 
+    /// Forgets what this device knows about the current GL state.
+    ///
+    /// The device remembers the bindings, blend state, and so forth that it last set, and skips
+    /// calls that wouldn't change them. Call this after making GL calls of your own on the same
+    /// context, before using the device again.
+    pub fn invalidate_state_cache(&self) {
+        *self.state.borrow_mut() = GLStateCache::default();
+    }
+
     fn set_render_state(&self, render_state: &RenderState<GLDevice>) {
         self.bind_render_target(render_state.target);
-
-        unsafe {
-            let (origin, size) = (render_state.viewport.origin(), render_state.viewport.size());
-            gl::Viewport(origin.x(), origin.y(), size.x(), size.y());
-        }
+        self.set_viewport(render_state.viewport);
 
         if render_state.options.clear_ops.has_ops() {
             self.clear(&render_state.options.clear_ops);
@@ -84,6 +111,9 @@ impl GLDevice {
                                       &render_state.images);
 
         render_state.uniforms.iter().for_each(|(uniform, data)| self.set_uniform(uniform, data));
+        for &(uniform_buffer, buffer) in render_state.uniform_buffers {
+            self.set_uniform_buffer(uniform_buffer, buffer);
+        }
 
         self.set_render_options(&render_state.options);
     }
@@ -96,6 +126,9 @@ impl GLDevice {
                                       &compute_state.images);
 
         compute_state.uniforms.iter().for_each(|(uniform, data)| self.set_uniform(uniform, data));
+        for &(uniform_buffer, buffer) in compute_state.uniform_buffers {
+            self.set_uniform_buffer(uniform_buffer, buffer);
+        }
 
         for &(storage_buffer, buffer) in compute_state.storage_buffers {
             self.set_storage_buffer(storage_buffer, buffer);
@@ -116,13 +149,17 @@ impl GLDevice {
             images_bound |= 1 << image_binding.0.image_unit as u64;
         }
 
+        // Units are assigned to uniforms the first time the program is used after they're looked
+        // up. The assignments never change afterward, so they don't need to be set again.
         unsafe {
-            let parameters = program.parameters.borrow();
+            let mut parameters = program.parameters.borrow_mut();
             for (texture_unit, uniform) in parameters.textures.iter().enumerate() {
                 if (textures_bound & (1 << texture_unit as u64)) == 0 {
                     self.bind_texture(&self.dummy_texture, texture_unit as GLuint);
                 }
-                gl::Uniform1i(uniform.location, texture_unit as GLint); ck();
+                if texture_unit >= parameters.assigned_texture_units {
+                    gl::Uniform1i(uniform.location, texture_unit as GLint); ck();
+                }
             }
             for (image_unit, uniform) in parameters.images.iter().enumerate() {
                 if (images_bound & (1 << image_unit as u64)) == 0 {
@@ -134,63 +171,80 @@ impl GLDevice {
                                          gl::READ_ONLY,
                                          gl::RGBA8 as GLenum); ck();
                 }
-                gl::Uniform1i(uniform.location, image_unit as GLint); ck();
+                if image_unit >= parameters.assigned_image_units {
+                    gl::Uniform1i(uniform.location, image_unit as GLint); ck();
+                }
             }
+            parameters.assigned_texture_units = parameters.textures.len();
+            parameters.assigned_image_units = parameters.images.len();
         }
     }
 
     fn set_render_options(&self, render_options: &RenderOptions) {
+        let mut state = self.state.borrow_mut();
         unsafe {
             // Set blend.
-            match render_options.blend {
-                None => {
-                    gl::Disable(gl::BLEND); ck();
-                }
-                Some(ref blend) => {
-                    gl::BlendFuncSeparate(blend.src_rgb_factor.to_gl_blend_factor(),
-                                          blend.dest_rgb_factor.to_gl_blend_factor(),
-                                          blend.src_alpha_factor.to_gl_blend_factor(),
-                                          blend.dest_alpha_factor.to_gl_blend_factor()); ck();
-                    gl::BlendEquation(blend.op.to_gl_blend_op()); ck();
-                    gl::Enable(gl::BLEND); ck();
+            if state.blend != Some(render_options.blend) {
+                match render_options.blend {
+                    None => {
+                        gl::Disable(gl::BLEND); ck();
+                    }
+                    Some(ref blend) => {
+                        gl::BlendFuncSeparate(blend.src_rgb_factor.to_gl_blend_factor(),
+                                              blend.dest_rgb_factor.to_gl_blend_factor(),
+                                              blend.src_alpha_factor.to_gl_blend_factor(),
+                                              blend.dest_alpha_factor.to_gl_blend_factor()); ck();
+                        gl::BlendEquation(blend.op.to_gl_blend_op()); ck();
+                        gl::Enable(gl::BLEND); ck();
+                    }
                 }
+                state.blend = Some(render_options.blend);
             }
 
             // Set depth.
-            match render_options.depth {
-                None => {
-                    gl::Disable(gl::DEPTH_TEST); ck();
-                }
-                Some(ref state) => {
-                    gl::DepthFunc(state.func.to_gl_depth_func()); ck();
-                    gl::DepthMask(state.write as GLboolean); ck();
-                    gl::Enable(gl::DEPTH_TEST); ck();
+            if state.depth != Some(render_options.depth) {
+                match render_options.depth {
+                    None => {
+                        gl::Disable(gl::DEPTH_TEST); ck();
+                    }
+                    Some(ref depth) => {
+                        gl::DepthFunc(depth.func.to_gl_depth_func()); ck();
+                        gl::DepthMask(depth.write as GLboolean); ck();
+                        gl::Enable(gl::DEPTH_TEST); ck();
+                    }
                 }
+                state.depth = Some(render_options.depth);
             }
 
             // Set stencil.
-            match render_options.stencil {
-                None => {
-                    gl::Disable(gl::STENCIL_TEST); ck();
-                }
-                Some(ref state) => {
-                    gl::StencilFunc(state.func.to_gl_stencil_func(),
-                                    state.reference as GLint,
-                                    state.mask); ck();
-                    let (pass_action, write_mask) = if state.write {
-                        (gl::REPLACE, state.mask)
-                    } else {
-                        (gl::KEEP, 0)
-                    };
-                    gl::StencilOp(gl::KEEP, gl::KEEP, pass_action); ck();
-                    gl::StencilMask(write_mask);
-                    gl::Enable(gl::STENCIL_TEST); ck();
+            if state.stencil != Some(render_options.stencil) {
+                match render_options.stencil {
+                    None => {
+                        gl::Disable(gl::STENCIL_TEST); ck();
+                    }
+                    Some(ref stencil) => {
+                        gl::StencilFunc(stencil.func.to_gl_stencil_func(),
+                                        stencil.reference as GLint,
+                                        stencil.mask); ck();
+                        let (pass_action, write_mask) = if stencil.write {
+                            (gl::REPLACE, stencil.mask)
+                        } else {
+                            (gl::KEEP, 0)
+                        };
+                        gl::StencilOp(gl::KEEP, gl::KEEP, pass_action); ck();
+                        gl::StencilMask(write_mask);
+                        gl::Enable(gl::STENCIL_TEST); ck();
+                    }
                 }
+                state.stencil = Some(render_options.stencil);
             }
 
             // Set color mask.
-            let color_mask = render_options.color_mask as GLboolean;
-            gl::ColorMask(color_mask, color_mask, color_mask, color_mask); ck();
+            if state.color_mask != Some(render_options.color_mask) {
+                let color_mask = render_options.color_mask as GLboolean;
+                gl::ColorMask(color_mask, color_mask, color_mask, color_mask); ck();
+                state.color_mask = Some(render_options.color_mask);
+            }
         }
     }
 
@@ -237,6 +291,12 @@ impl GLDevice {
         }
     }
 
+    fn set_uniform_buffer(&self, uniform_buffer: &GLUniformBuffer, buffer: &GLBuffer) {
+        unsafe {
+            gl::BindBufferBase(gl::UNIFORM_BUFFER, uniform_buffer.binding, buffer.gl_buffer); ck();
+        }
+    }
+
     fn set_storage_buffer(&self, storage_buffer: &GLStorageBuffer, buffer: &GLBuffer) {
         unsafe {
             gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER,
@@ -251,59 +311,21 @@ impl GLDevice {
         }
     }
 
+    // Program, vertex array, texture, and render option state is left as it is after each draw,
+    // since the next one will most likely set much of it to the same values again. Image and
+    // storage buffer bindings, which are rare, are still undone.
     fn reset_render_state(&self, render_state: &RenderState<GLDevice>) {
-        self.reset_render_options(&render_state.options);
-
-        unsafe {
-            for image_binding in render_state.images {
-                self.unbind_image(image_binding.0.image_unit);
-                gl::Uniform1i(image_binding.0.uniform.location, 0); ck();
-            }
-            for texture_binding in render_state.textures {
-                self.unbind_texture(texture_binding.0.texture_unit);
-                gl::Uniform1i(texture_binding.0.uniform.location, 0); ck();
-            }
+        for image_binding in render_state.images {
+            self.unbind_image(image_binding.0.image_unit);
         }
-
-        self.unuse_program();
-        self.unbind_vertex_array();
     }
 
     fn reset_compute_state(&self, compute_state: &ComputeState<GLDevice>) {
         for &(storage_buffer, _) in compute_state.storage_buffers {
             self.unset_storage_buffer(storage_buffer);
         }
-
-        unsafe {
-            for image_binding in compute_state.images {
-                self.unbind_image(image_binding.0.image_unit);
-                gl::Uniform1i(image_binding.0.uniform.location, 0); ck();
-            }
-            for texture_binding in compute_state.textures {
-                self.unbind_texture(texture_binding.0.texture_unit);
-                gl::Uniform1i(texture_binding.0.uniform.location, 0); ck();
-            }
-        }
-
-        self.unuse_program();
-    }
-
-    fn reset_render_options(&self, render_options: &RenderOptions) {
-        unsafe {
-            if render_options.blend.is_some() {
-                gl::Disable(gl::BLEND); ck();
-            }
-
-            if render_options.depth.is_some() {
-                gl::Disable(gl::DEPTH_TEST); ck();
-            }
-
-            if render_options.stencil.is_some() {
-                gl::StencilMask(!0); ck();
-                gl::Disable(gl::STENCIL_TEST); ck();
-            }
-
-            gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE); ck();
+        for image_binding in compute_state.images {
+            self.unbind_image(image_binding.0.image_unit);
         }
     }
 }
@@ -311,6 +333,7 @@ impl GLDevice {
 impl Device for GLDevice {
     type Buffer = GLBuffer;
     type Fence = GLFence;
+    type QueueFence = ();
     type Framebuffer = GLFramebuffer;
     type ImageParameter = GLImageParameter;
     type Program = GLProgram;
@@ -321,6 +344,7 @@ impl Device for GLDevice {
     type TextureParameter = GLTextureParameter;
     type TimerQuery = GLTimerQuery;
     type Uniform = GLUniform;
+    type UniformBuffer = GLUniformBuffer;
     type VertexArray = GLVertexArray;
     type VertexAttr = GLVertexAttr;
 
@@ -331,10 +355,16 @@ impl Device for GLDevice {
         }
     }
 
+    #[inline]
+    fn capabilities(&self) -> DeviceCapabilities {
+        self.capabilities
+    }
+
     fn create_texture(&self, format: TextureFormat, size: Vector2I) -> GLTexture {
         let mut texture = GLTexture { gl_texture: 0, size, format };
         unsafe {
             gl::GenTextures(1, &mut texture.gl_texture); ck();
+            self.state.borrow_mut().forget_texture(texture.gl_texture);
             self.bind_texture(&texture, 0);
             gl::TexImage2D(gl::TEXTURE_2D,
                            0,
@@ -357,6 +387,7 @@ impl Device for GLDevice {
         let mut texture = GLTexture { gl_texture: 0, size, format: TextureFormat::R8 };
         unsafe {
             gl::GenTextures(1, &mut texture.gl_texture); ck();
+            self.state.borrow_mut().forget_texture(texture.gl_texture);
             self.bind_texture(&texture, 0);
             gl::TexImage2D(gl::TEXTURE_2D,
                            0,
@@ -373,7 +404,8 @@ impl Device for GLDevice {
         texture
     }
 
-    fn create_shader_from_source(&self, name: &str, source: &[u8], kind: ShaderKind) -> GLShader {
+    fn create_shader_from_source(&self, name: &str, source: &[u8], kind: ShaderKind)
+                                 -> Result<GLShader, RenderError> {
         // FIXME(pcwalton): Do this once and cache it.
         let glsl_version_spec = self.version.to_glsl_version_spec();
 
@@ -388,7 +420,9 @@ impl Device for GLDevice {
         };
 
         unsafe {
-            let gl_shader = gl::CreateShader(gl_shader_kind); ck();
+            // Wrap the shader right away, so that it's deleted if compilation fails.
+            let shader = GLShader { gl_shader: gl::CreateShader(gl_shader_kind) }; ck();
+            let gl_shader = shader.gl_shader;
             gl::ShaderSource(gl_shader,
                              1,
                              [source.as_ptr() as *const GLchar].as_ptr(),
@@ -405,11 +439,11 @@ impl Device for GLDevice {
                                      info_log.len() as GLint,
                                      ptr::null_mut(),
                                      info_log.as_mut_ptr() as *mut GLchar); ck();
-                error!("Shader info log:\n{}", String::from_utf8_lossy(&info_log));
-                panic!("{:?} shader '{}' compilation failed", kind, name);
+                let log = String::from_utf8_lossy(&info_log).into_owned();
+                return Err(RenderError::ShaderCompilation { name: name.to_owned(), kind, log });
             }
 
-            GLShader { gl_shader }
+            Ok(shader)
         }
     }
 
@@ -417,10 +451,16 @@ impl Device for GLDevice {
                                    _resources: &dyn ResourceLoader,
                                    name: &str,
                                    shaders: ProgramKind<GLShader>)
-                                   -> GLProgram {
+                                   -> Result<GLProgram, RenderError> {
         let gl_program;
         unsafe {
             gl_program = gl::CreateProgram(); ck();
+            self.state.borrow_mut().forget_program(gl_program);
+            if self.program_cache.is_some() {
+                gl::ProgramParameteri(gl_program,
+                                      gl::PROGRAM_BINARY_RETRIEVABLE_HINT,
+                                      gl::TRUE as GLint); ck();
+            }
             match shaders {
                 ProgramKind::Raster {
                     vertex: ref vertex_shader,
@@ -433,6 +473,22 @@ impl Device for GLDevice {
                     gl::AttachShader(gl_program, compute_shader.gl_shader); ck();
                 }
             }
+            // A fragment output named `oFragBlend` is the second source for blending into the
+            // first color attachment.
+            if self.capabilities.dual_source_blending {
+                if let ProgramKind::Raster { .. } = shaders {
+                    gl::BindFragDataLocationIndexed(gl_program,
+                                                    0,
+                                                    0,
+                                                    b"oFragColor\0".as_ptr() as *const GLchar);
+                    ck();
+                    gl::BindFragDataLocationIndexed(gl_program,
+                                                    0,
+                                                    1,
+                                                    b"oFragBlend\0".as_ptr() as *const GLchar);
+                    ck();
+                }
+            }
             gl::LinkProgram(gl_program); ck();
 
             let mut link_status = 0;
@@ -445,14 +501,115 @@ impl Device for GLDevice {
                                       info_log.len() as GLint,
                                       ptr::null_mut(),
                                       info_log.as_mut_ptr() as *mut GLchar); ck();
-                eprintln!("Program info log:\n{}", String::from_utf8_lossy(&info_log));
-                panic!("Program '{}' linking failed", name);
+                gl::DeleteProgram(gl_program); ck();
+                let log = String::from_utf8_lossy(&info_log).into_owned();
+                return Err(RenderError::ProgramLinking { name: name.to_owned(), log });
+            }
+        }
+
+        Ok(GLProgram {
+            gl_program,
+            shaders: Some(shaders),
+            parameters: RefCell::new(GLProgramParameters::default()),
+        })
+    }
+
+    fn set_program_cache(&mut self, cache: Arc<ProgramCache>) {
+        // Program binaries are core in OpenGL 4.1 and OpenGL ES 3.0, but drivers may support no
+        // binary formats at all.
+        unsafe {
+            if !gl::GetProgramBinary::is_loaded() || !gl::ProgramBinary::is_loaded() {
+                return;
+            }
+            let mut format_count = 0;
+            gl::GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, &mut format_count); ck();
+            if format_count == 0 {
+                return;
+            }
+
+            self.driver_id.clear();
+            for &name in &[gl::VENDOR, gl::RENDERER, gl::VERSION] {
+                let string = gl::GetString(name); ck();
+                if !string.is_null() {
+                    let string = CStr::from_ptr(string as *const GLchar);
+                    self.driver_id.extend_from_slice(string.to_bytes());
+                }
+                self.driver_id.push(0);
             }
         }
+        self.program_cache = Some(cache);
+    }
 
-        let parameters = GLProgramParameters { textures: vec![], images: vec![] };
+    fn create_program_from_shader_names(&self,
+                                        resources: &dyn ResourceLoader,
+                                        program_name: &str,
+                                        shader_names: ProgramKind<&str>)
+                                        -> Result<GLProgram, RenderError> {
+        let sources = match shader_names {
+            ProgramKind::Raster { vertex, fragment } => {
+                let vertex_source =
+                    self.load_shader_source(resources, vertex, ShaderKind::Vertex)?;
+                let fragment_source =
+                    self.load_shader_source(resources, fragment, ShaderKind::Fragment)?;
+                ProgramKind::Raster {
+                    vertex: (vertex, vertex_source),
+                    fragment: (fragment, fragment_source),
+                }
+            }
+            ProgramKind::Compute(compute) => {
+                ProgramKind::Compute((compute,
+                                      self.load_shader_source(resources,
+                                                              compute,
+                                                              ShaderKind::Compute)?))
+            }
+        };
 
-        GLProgram { gl_program, shaders, parameters: RefCell::new(parameters) }
+        let cache_key = self.program_cache.as_ref().map(|_| {
+            let version = self.version.to_glsl_version_spec().as_bytes();
+            match sources {
+                ProgramKind::Raster { ref vertex, ref fragment } => {
+                    ProgramCache::key(&[&self.driver_id, version, &vertex.1, &fragment.1])
+                }
+                ProgramKind::Compute(ref compute) => {
+                    ProgramCache::key(&[&self.driver_id, version, &compute.1])
+                }
+            }
+        });
+        if let (Some(cache), Some(cache_key)) = (self.program_cache.as_ref(), cache_key) {
+            if let Some(binary) = cache.get(cache_key) {
+                match self.create_program_from_binary(&binary) {
+                    Some(program) => return Ok(program),
+                    None => {
+                        warn!("Cached binary for program '{}' was rejected", program_name);
+                        cache.remove(cache_key);
+                    }
+                }
+            }
+        }
+
+        let shaders = match sources {
+            ProgramKind::Raster { vertex, fragment } => {
+                ProgramKind::Raster {
+                    vertex: self.create_shader_from_source(vertex.0,
+                                                           &vertex.1,
+                                                           ShaderKind::Vertex)?,
+                    fragment: self.create_shader_from_source(fragment.0,
+                                                             &fragment.1,
+                                                             ShaderKind::Fragment)?,
+                }
+            }
+            ProgramKind::Compute(compute) => {
+                ProgramKind::Compute(self.create_shader_from_source(compute.0,
+                                                                    &compute.1,
+                                                                    ShaderKind::Compute)?)
+            }
+        };
+        let program = self.create_program_from_shaders(resources, program_name, shaders)?;
+
+        if let (Some(cache), Some(cache_key)) = (self.program_cache.as_ref(), cache_key) {
+            self.store_program_binary(&program, cache, cache_key);
+        }
+        Ok(program)
     }
 
     #[inline]
@@ -465,6 +622,7 @@ impl Device for GLDevice {
         unsafe {
             let mut array = GLVertexArray { gl_vertex_array: 0 };
             gl::GenVertexArrays(1, &mut array.gl_vertex_array); ck();
+            self.state.borrow_mut().forget_vertex_array(array.gl_vertex_array);
             array
         }
     }
@@ -521,6 +679,20 @@ impl Device for GLDevice {
         GLStorageBuffer { location: binding as GLint }
     }
 
+    fn get_uniform_buffer(&self, program: &GLProgram, name: &str, binding: u32)
+                          -> GLUniformBuffer {
+        // OpenGL 3.3 and OpenGL ES 3.0 can't set the binding in the shader, so set it here.
+        let name = CString::new(format!("u{}", name)).unwrap();
+        unsafe {
+            let index = gl::GetUniformBlockIndex(program.gl_program,
+                                                 name.as_ptr() as *const GLchar); ck();
+            if index != gl::INVALID_INDEX {
+                gl::UniformBlockBinding(program.gl_program, index, binding); ck();
+            }
+        }
+        GLUniformBuffer { binding }
+    }
+
     fn configure_vertex_attr(&self,
                              vertex_array: &GLVertexArray,
                              attr: &GLVertexAttr,
@@ -565,8 +737,8 @@ impl Device for GLDevice {
         let mut gl_framebuffer = 0;
         unsafe {
             gl::GenFramebuffers(1, &mut gl_framebuffer); ck();
-            gl::BindFramebuffer(gl::FRAMEBUFFER, gl_framebuffer); ck();
-            self.bind_texture(&texture, 0);
+            self.state.borrow_mut().forget_framebuffer(gl_framebuffer);
+            self.set_framebuffer(gl_framebuffer);
             gl::FramebufferTexture2D(gl::FRAMEBUFFER,
                                      gl::COLOR_ATTACHMENT0,
                                      gl::TEXTURE_2D,
@@ -580,8 +752,13 @@ impl Device for GLDevice {
 
     fn create_buffer(&self, mode: BufferUploadMode) -> GLBuffer {
         unsafe {
+            // Direct state access needs the buffer object to exist before it's first bound.
             let mut gl_buffer = 0;
-            gl::GenBuffers(1, &mut gl_buffer); ck();
+            if self.direct_state_access {
+                gl::CreateBuffers(1, &mut gl_buffer); ck();
+            } else {
+                gl::GenBuffers(1, &mut gl_buffer); ck();
+            }
             GLBuffer { gl_buffer, mode }
         }
     }
@@ -590,7 +767,6 @@ impl Device for GLDevice {
                           buffer: &GLBuffer,
                           data: BufferData<T>,
                           target: BufferTarget) {
-        let target = target.to_gl_target();
         let (ptr, len) = match data {
             BufferData::Uninitialized(len) => (ptr::null(), len),
             BufferData::Memory(buffer) => (buffer.as_ptr() as *const GLvoid, buffer.len()),
@@ -598,8 +774,12 @@ impl Device for GLDevice {
         let len = (len * mem::size_of::<T>()) as GLsizeiptr;
         let usage = buffer.mode.to_gl_usage();
         unsafe {
-            gl::BindBuffer(target, buffer.gl_buffer); ck();
-            gl::BufferData(target, len, ptr, usage); ck();
+            if self.direct_state_access {
+                gl::NamedBufferData(buffer.gl_buffer, len, ptr, usage); ck();
+            } else {
+                let target = self.bind_buffer_for_upload(buffer, target);
+                gl::BufferData(target, len, ptr, usage); ck();
+            }
         }
     }
 
@@ -608,14 +788,20 @@ impl Device for GLDevice {
                            position: usize,
                            data: &[T],
                            target: BufferTarget) {
-        let target = target.to_gl_target();
         let len = (data.len() * mem::size_of::<T>()) as GLsizeiptr;
         unsafe {
-            gl::BindBuffer(target, buffer.gl_buffer); ck();
-            gl::BufferSubData(target,
-                              position as GLintptr,
-                              len,
-                              data.as_ptr() as *const GLvoid); ck();
+            if self.direct_state_access {
+                gl::NamedBufferSubData(buffer.gl_buffer,
+                                       position as GLintptr,
+                                       len,
+                                       data.as_ptr() as *const GLvoid); ck();
+            } else {
+                let target = self.bind_buffer_for_upload(buffer, target);
+                gl::BufferSubData(target,
+                                  position as GLintptr,
+                                  len,
+                                  data.as_ptr() as *const GLvoid); ck();
+            }
         }
     }
 
@@ -649,36 +835,40 @@ impl Device for GLDevice {
     }
 
     fn set_texture_sampling_mode(&self, texture: &Self::Texture, flags: TextureSamplingFlags) {
-        self.bind_texture(texture, 0);
+        let parameters = [
+            (gl::TEXTURE_MIN_FILTER, if flags.contains(TextureSamplingFlags::NEAREST_MIN) {
+                gl::NEAREST
+            } else {
+                gl::LINEAR
+            }),
+            (gl::TEXTURE_MAG_FILTER, if flags.contains(TextureSamplingFlags::NEAREST_MAG) {
+                gl::NEAREST
+            } else {
+                gl::LINEAR
+            }),
+            (gl::TEXTURE_WRAP_S, if flags.contains(TextureSamplingFlags::REPEAT_U) {
+                gl::REPEAT
+            } else {
+                gl::CLAMP_TO_EDGE
+            }),
+            (gl::TEXTURE_WRAP_T, if flags.contains(TextureSamplingFlags::REPEAT_V) {
+                gl::REPEAT
+            } else {
+                gl::CLAMP_TO_EDGE
+            }),
+        ];
+
         unsafe {
-            gl::TexParameteri(gl::TEXTURE_2D,
-                              gl::TEXTURE_MIN_FILTER,
-                              if flags.contains(TextureSamplingFlags::NEAREST_MIN) {
-                                  gl::NEAREST as GLint
-                              } else {
-                                  gl::LINEAR as GLint
-                              }); ck();
-            gl::TexParameteri(gl::TEXTURE_2D,
-                              gl::TEXTURE_MAG_FILTER,
-                              if flags.contains(TextureSamplingFlags::NEAREST_MAG) {
-                                  gl::NEAREST as GLint
-                              } else {
-                                  gl::LINEAR as GLint
-                              }); ck();
-            gl::TexParameteri(gl::TEXTURE_2D,
-                              gl::TEXTURE_WRAP_S,
-                              if flags.contains(TextureSamplingFlags::REPEAT_U) {
-                                  gl::REPEAT as GLint
-                              } else {
-                                  gl::CLAMP_TO_EDGE as GLint
-                              }); ck();
-            gl::TexParameteri(gl::TEXTURE_2D,
-                              gl::TEXTURE_WRAP_T,
-                              if flags.contains(TextureSamplingFlags::REPEAT_V) {
-                                  gl::REPEAT as GLint
-                              } else {
-                                  gl::CLAMP_TO_EDGE as GLint
-                              }); ck();
+            if self.direct_state_access {
+                for &(name, value) in &parameters {
+                    gl::TextureParameteri(texture.gl_texture, name, value as GLint); ck();
+                }
+            } else {
+                self.bind_texture(texture, 0);
+                for &(name, value) in &parameters {
+                    gl::TexParameteri(gl::TEXTURE_2D, name, value as GLint); ck();
+                }
+            }
         }
     }
 
@@ -691,8 +881,20 @@ impl Device for GLDevice {
         assert!(rect.max_y() <= texture.size.y());
 
         unsafe {
-            self.bind_texture(texture, 0);
-            if rect.origin() == Vector2I::default() && rect.size() == texture.size {
+            if self.direct_state_access {
+                // The texture's storage was allocated when it was created, so even a full upload
+                // can go through `TextureSubImage2D`.
+                gl::TextureSubImage2D(texture.gl_texture,
+                                      0,
+                                      rect.origin().x(),
+                                      rect.origin().y(),
+                                      rect.size().x() as GLsizei,
+                                      rect.size().y() as GLsizei,
+                                      texture.format.gl_format(),
+                                      texture.format.gl_type(),
+                                      data_ptr); ck();
+            } else if rect.origin() == Vector2I::default() && rect.size() == texture.size {
+                self.bind_texture(texture, 0);
                 gl::TexImage2D(gl::TEXTURE_2D,
                                0,
                                texture.format.gl_internal_format(),
@@ -703,6 +905,7 @@ impl Device for GLDevice {
                                texture.format.gl_type(),
                                data_ptr); ck();
             } else {
+                self.bind_texture(texture, 0);
                 gl::TexSubImage2D(gl::TEXTURE_2D,
                                   0,
                                   rect.origin().x(),
@@ -878,10 +1081,50 @@ impl Device for GLDevice {
 
     #[inline]
     fn create_shader(&self, resources: &dyn ResourceLoader, name: &str, kind: ShaderKind)
-                     -> Self::Shader {
+                     -> Result<Self::Shader, RenderError> {
+        let source = self.load_shader_source(resources, name, kind)?;
+        self.create_shader_from_source(name, &source, kind)
+    }
+
+    fn add_fence(&self) -> Self::Fence {
+        unsafe {
+            let gl_sync = gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0); ck();
+            GLFence { gl_sync }
+        }
+    }
+
+    fn wait_for_fence(&self, fence: &Self::Fence) {
+        unsafe {
+            gl::ClientWaitSync(fence.gl_sync, gl::SYNC_FLUSH_COMMANDS_BIT, 0); ck();
+        }
+    }
+
+    // OpenGL exposes only a single queue, so all of these are no-ops.
+
+    #[inline]
+    fn supports_async_compute(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn begin_async_compute_commands(&self) {}
+
+    #[inline]
+    fn end_async_compute_commands(&self) {}
+
+    #[inline]
+    fn signal_queue_fence(&self) -> Self::QueueFence {}
+
+    #[inline]
+    fn wait_for_queue_fence(&self, _: &Self::QueueFence) {}
+}
+
+impl GLDevice {
+    fn load_shader_source(&self, resources: &dyn ResourceLoader, name: &str, kind: ShaderKind)
+                          -> Result<Vec<u8>, RenderError> {
         match (self.version, kind) {
             (GLVersion::GL3, ShaderKind::Compute) | (GLVersion::GLES3, ShaderKind::Compute) => {
-                panic!("Compute shaders are not supported on OpenGL versions prior to 4!")
+                return Err(RenderError::UnsupportedFeature("compute shaders before OpenGL 4"));
             }
             (GLVersion::GL3, ShaderKind::Vertex) |
             (GLVersion::GL3, ShaderKind::Fragment) |
@@ -899,24 +1142,67 @@ impl Device for GLDevice {
             ShaderKind::Compute => 'c',
         };
         let path = format!("shaders/{}/{}.{}s.glsl", directory, name, suffix);
-        self.create_shader_from_source(name, &resources.slurp(&path).unwrap(), kind)
+        resources.slurp(&path).map_err(|error| {
+            RenderError::MissingResource { path, reason: error.to_string() }
+        })
     }
 
-    fn add_fence(&self) -> Self::Fence {
+    fn create_program_from_binary(&self, binary: &[u8]) -> Option<GLProgram> {
+        // The binary format is stored in the first four bytes.
+        if binary.len() < 4 {
+            return None;
+        }
+        let format = u32::from_le_bytes([binary[0], binary[1], binary[2], binary[3]]);
+
         unsafe {
-            let gl_sync = gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0); ck();
-            GLFence { gl_sync }
+            let gl_program = gl::CreateProgram(); ck();
+            self.state.borrow_mut().forget_program(gl_program);
+            gl::ProgramBinary(gl_program,
+                              format as GLenum,
+                              binary[4..].as_ptr() as *const GLvoid,
+                              (binary.len() - 4) as GLsizei);
+            // A format that the driver no longer supports is an error rather than a failed link,
+            // so check for it here instead of letting `ck()` panic.
+            let error = gl::GetError();
+            let mut link_status = 0;
+            gl::GetProgramiv(gl_program, gl::LINK_STATUS, &mut link_status); ck();
+            if error != gl::NO_ERROR || link_status != gl::TRUE as GLint {
+                gl::DeleteProgram(gl_program); ck();
+                return None;
+            }
+
+            Some(GLProgram {
+                gl_program,
+                shaders: None,
+                parameters: RefCell::new(GLProgramParameters::default()),
+            })
         }
     }
 
-    fn wait_for_fence(&self, fence: &Self::Fence) {
+    fn store_program_binary(&self,
+                            program: &GLProgram,
+                            cache: &ProgramCache,
+                            cache_key: ProgramCacheKey) {
         unsafe {
-            gl::ClientWaitSync(fence.gl_sync, gl::SYNC_FLUSH_COMMANDS_BIT, 0); ck();
+            let mut length = 0;
+            gl::GetProgramiv(program.gl_program, gl::PROGRAM_BINARY_LENGTH, &mut length); ck();
+            if length <= 0 {
+                return;
+            }
+
+            let mut binary = vec![0; 4 + length as usize];
+            let (mut format, mut written_length) = (0, 0);
+            gl::GetProgramBinary(program.gl_program,
+                                 length,
+                                 &mut written_length,
+                                 &mut format,
+                                 binary[4..].as_mut_ptr() as *mut GLvoid); ck();
+            binary[0..4].copy_from_slice(&(format as u32).to_le_bytes());
+            binary.truncate(4 + written_length as usize);
+            cache.insert(cache_key, binary);
         }
     }
-}
 
-impl GLDevice {
     fn bind_render_target(&self, attachment: &RenderTarget<GLDevice>) {
         match *attachment {
             RenderTarget::Default => self.bind_default_framebuffer(),
@@ -925,29 +1211,60 @@ impl GLDevice {
     }
 
     fn bind_vertex_array(&self, vertex_array: &GLVertexArray) {
-        unsafe {
-            gl::BindVertexArray(vertex_array.gl_vertex_array); ck();
-        }
+        self.set_vertex_array(vertex_array.gl_vertex_array);
     }
 
     fn unbind_vertex_array(&self) {
-        unsafe {
-            gl::BindVertexArray(0); ck();
+        self.set_vertex_array(0);
+    }
+
+    fn set_vertex_array(&self, gl_vertex_array: GLuint) {
+        let mut state = self.state.borrow_mut();
+        if state.vertex_array != Some(gl_vertex_array) {
+            unsafe {
+                gl::BindVertexArray(gl_vertex_array); ck();
+            }
+            state.vertex_array = Some(gl_vertex_array);
         }
     }
 
     fn bind_texture(&self, texture: &GLTexture, unit: u32) {
+        self.set_texture(texture.gl_texture, unit);
+    }
+
+    fn set_texture(&self, gl_texture: GLuint, unit: u32) {
+        let mut state = self.state.borrow_mut();
+        let unit_index = unit as usize;
+        if state.textures.len() <= unit_index {
+            state.textures.resize(unit_index + 1, None);
+        }
+        if state.textures[unit_index] == Some(gl_texture) {
+            return;
+        }
+
         unsafe {
-            gl::ActiveTexture(gl::TEXTURE0 + unit); ck();
-            gl::BindTexture(gl::TEXTURE_2D, texture.gl_texture); ck();
+            if state.active_texture_unit != Some(unit) {
+                gl::ActiveTexture(gl::TEXTURE0 + unit); ck();
+                state.active_texture_unit = Some(unit);
+            }
+            gl::BindTexture(gl::TEXTURE_2D, gl_texture); ck();
         }
+        state.textures[unit_index] = Some(gl_texture);
     }
 
-    fn unbind_texture(&self, unit: u32) {
+    // Binds a buffer so that it can be uploaded to without direct state access, and returns the
+    // target it was bound to.
+    fn bind_buffer_for_upload(&self, buffer: &GLBuffer, target: BufferTarget) -> GLenum {
+        // The index buffer binding belongs to the vertex array, so don't disturb whichever one
+        // the last draw left bound.
+        if let BufferTarget::Index = target {
+            self.unbind_vertex_array();
+        }
+        let target = target.to_gl_target();
         unsafe {
-            gl::ActiveTexture(gl::TEXTURE0 + unit); ck();
-            gl::BindTexture(gl::TEXTURE_2D, 0); ck();
+            gl::BindBuffer(target, buffer.gl_buffer); ck();
         }
+        target
     }
 
     fn bind_image(&self, binding: &ImageBinding<GLImageParameter, GLTexture>) {
@@ -969,26 +1286,41 @@ impl GLDevice {
     }
 
     fn use_program(&self, program: &GLProgram) {
-        unsafe {
-            gl::UseProgram(program.gl_program); ck();
+        let mut state = self.state.borrow_mut();
+        if state.program != Some(program.gl_program) {
+            unsafe {
+                gl::UseProgram(program.gl_program); ck();
+            }
+            state.program = Some(program.gl_program);
         }
     }
 
-    fn unuse_program(&self) {
-        unsafe {
-            gl::UseProgram(0); ck();
-        }
+    fn bind_default_framebuffer(&self) {
+        self.set_framebuffer(self.default_framebuffer);
     }
 
-    fn bind_default_framebuffer(&self) {
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.default_framebuffer); ck();
+    fn bind_framebuffer(&self, framebuffer: &GLFramebuffer) {
+        self.set_framebuffer(framebuffer.gl_framebuffer);
+    }
+
+    fn set_framebuffer(&self, gl_framebuffer: GLuint) {
+        let mut state = self.state.borrow_mut();
+        if state.framebuffer != Some(gl_framebuffer) {
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, gl_framebuffer); ck();
+            }
+            state.framebuffer = Some(gl_framebuffer);
         }
     }
 
-    fn bind_framebuffer(&self, framebuffer: &GLFramebuffer) {
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer.gl_framebuffer); ck();
+    fn set_viewport(&self, viewport: RectI) {
+        let mut state = self.state.borrow_mut();
+        if state.viewport != Some(viewport) {
+            unsafe {
+                let (origin, size) = (viewport.origin(), viewport.size());
+                gl::Viewport(origin.x(), origin.y(), size.x(), size.y()); ck();
+            }
+            state.viewport = Some(viewport);
         }
     }
 
@@ -1036,6 +1368,18 @@ impl GLDevice {
                 gl::Clear(flags); ck();
             }
         }
+
+        // Clearing changed the write masks.
+        let mut state = self.state.borrow_mut();
+        if ops.color.is_some() {
+            state.color_mask = Some(true);
+        }
+        if ops.depth.is_some() {
+            state.depth = None;
+        }
+        if ops.stencil.is_some() {
+            state.stencil = None;
+        }
     }
 
     fn render_target_format(&self, render_target: &RenderTarget<GLDevice>) -> TextureFormat {
@@ -1204,6 +1548,11 @@ pub struct GLImageParameter {
     image_unit: GLuint,
 }
 
+#[derive(Debug)]
+pub struct GLUniformBuffer {
+    binding: GLuint,
+}
+
 #[derive(Debug)]
 pub struct GLStorageBuffer {
     location: GLint,
@@ -1211,8 +1560,9 @@ pub struct GLStorageBuffer {
 
 pub struct GLProgram {
     pub gl_program: GLuint,
+    // Programs loaded from binaries have no shaders.
     #[allow(dead_code)]
-    shaders: ProgramKind<GLShader>,
+    shaders: Option<ProgramKind<GLShader>>,
     parameters: RefCell<GLProgramParameters>,
 }
 
@@ -1224,11 +1574,62 @@ impl Drop for GLProgram {
     }
 }
 
+#[derive(Default)]
 pub struct GLProgramParameters {
     // Mapping from texture unit number to uniform location.
     textures: Vec<GLUniform>,
     // Mapping from image unit number to uniform location.
     images: Vec<GLUniform>,
+    // How many of the uniforms above have been set to their unit numbers.
+    assigned_texture_units: usize,
+    assigned_image_units: usize,
+}
+
+// The GL state that the device last set, so that calls that wouldn't change it can be skipped.
+// `None` means that the state is unknown, and must be set before it's relied on.
+#[derive(Default)]
+struct GLStateCache {
+    framebuffer: Option<GLuint>,
+    viewport: Option<RectI>,
+    program: Option<GLuint>,
+    vertex_array: Option<GLuint>,
+    active_texture_unit: Option<GLuint>,
+    // Indexed by texture unit.
+    textures: Vec<Option<GLuint>>,
+    blend: Option<Option<BlendState>>,
+    depth: Option<Option<DepthState>>,
+    stencil: Option<Option<StencilState>>,
+    color_mask: Option<bool>,
+}
+
+// Deleting an object that's bound resets the binding to zero behind our back, and the name may
+// then be handed out again. So newly-created objects must not be assumed to be bound already.
+impl GLStateCache {
+    fn forget_texture(&mut self, gl_texture: GLuint) {
+        for texture in &mut self.textures {
+            if *texture == Some(gl_texture) {
+                *texture = None;
+            }
+        }
+    }
+
+    fn forget_program(&mut self, gl_program: GLuint) {
+        if self.program == Some(gl_program) {
+            self.program = None;
+        }
+    }
+
+    fn forget_vertex_array(&mut self, gl_vertex_array: GLuint) {
+        if self.vertex_array == Some(gl_vertex_array) {
+            self.vertex_array = None;
+        }
+    }
+
+    fn forget_framebuffer(&mut self, gl_framebuffer: GLuint) {
+        if self.framebuffer == Some(gl_framebuffer) {
+            self.framebuffer = None;
+        }
+    }
 }
 
 pub struct GLShader {
@@ -1285,6 +1686,9 @@ impl BlendFactorExt for BlendFactor {
             BlendFactor::DestAlpha => gl::DST_ALPHA,
             BlendFactor::OneMinusDestAlpha => gl::ONE_MINUS_DST_ALPHA,
             BlendFactor::DestColor => gl::DST_COLOR,
+            BlendFactor::OneMinusSrcColor => gl::ONE_MINUS_SRC_COLOR,
+            BlendFactor::Src1Color => gl::SRC1_COLOR,
+            BlendFactor::OneMinusSrc1Color => gl::ONE_MINUS_SRC1_COLOR,
         }
     }
 }
@@ -1316,6 +1720,7 @@ impl BufferTargetExt for BufferTarget {
             BufferTarget::Vertex => gl::ARRAY_BUFFER,
             BufferTarget::Index => gl::ELEMENT_ARRAY_BUFFER,
             BufferTarget::Storage => gl::SHADER_STORAGE_BUFFER,
+            BufferTarget::Uniform => gl::UNIFORM_BUFFER,
         }
     }
 }
@@ -1473,6 +1878,64 @@ impl GLVersion {
     }
 }
 
+// Also returns whether direct state access is available.
+fn query_capabilities(version: GLVersion) -> (DeviceCapabilities, bool) {
+    unsafe {
+        let mut max_texture_size = 0;
+        gl::GetIntegerv(gl::MAX_TEXTURE_SIZE, &mut max_texture_size); ck();
+
+        let mut extension_count = 0;
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut extension_count); ck();
+        let (mut has_color_buffer_float, mut has_direct_state_access) = (false, false);
+        for extension_index in 0..(extension_count as GLuint) {
+            let extension = gl::GetStringi(gl::EXTENSIONS, extension_index); ck();
+            let extension = CStr::from_ptr(extension as *const GLchar);
+            match extension.to_bytes() {
+                b"GL_EXT_color_buffer_float" | b"GL_EXT_color_buffer_half_float" => {
+                    has_color_buffer_float = true;
+                }
+                b"GL_ARB_direct_state_access" => has_direct_state_access = true,
+                _ => {}
+            }
+        }
+
+        // Dual-source blending and timer queries are core in OpenGL 3.3, but OpenGL ES only has
+        // them as extensions, and its timer query extension uses different enums.
+        let desktop = match version {
+            GLVersion::GL3 | GLVersion::GL4 => true,
+            GLVersion::GLES3 => false,
+        };
+        // Direct state access is core in OpenGL 4.5.
+        if let GLVersion::GL4 = version {
+            let (mut major_version, mut minor_version) = (0, 0);
+            gl::GetIntegerv(gl::MAJOR_VERSION, &mut major_version); ck();
+            gl::GetIntegerv(gl::MINOR_VERSION, &mut minor_version); ck();
+            if (major_version, minor_version) >= (4, 5) {
+                has_direct_state_access = true;
+            }
+        }
+        let direct_state_access = desktop && has_direct_state_access &&
+            gl::CreateBuffers::is_loaded() &&
+            gl::NamedBufferData::is_loaded() &&
+            gl::NamedBufferSubData::is_loaded() &&
+            gl::TextureParameteri::is_loaded() &&
+            gl::TextureSubImage2D::is_loaded();
+
+        let capabilities = DeviceCapabilities {
+            max_texture_size,
+            compute: match version {
+                GLVersion::GL4 => true,
+                GLVersion::GL3 | GLVersion::GLES3 => false,
+            },
+            dual_source_blending: desktop,
+            f16_render_targets: desktop || has_color_buffer_float,
+            timer_queries: desktop,
+            aliasable_transient_textures: false,
+        };
+        (capabilities, direct_state_access)
+    }
+}
+
 // Error checking
 
 #[cfg(debug_assertions)]
@@ -0,0 +1,113 @@
+// pathfinder/gpu/src/error.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Errors that devices and renderers report instead of panicking.
+//!
+//! These are all failures that depend on the GPU, driver, or content rather than on bugs in the
+//! caller, so a long-running application can log them and carry on: skip a frame, fall back to
+//! another renderer, or rebuild with simpler content.
+
+use crate::ShaderKind;
+use pathfinder_geometry::vector::Vector2I;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RenderError {
+    /// A shader, texture, or other resource couldn't be loaded.
+    MissingResource { path: String, reason: String },
+    /// The driver rejected a shader. `log` is its explanation, which may be empty.
+    ShaderCompilation { name: String, kind: ShaderKind, log: String },
+    /// Shaders compiled, but the driver couldn't link them into a program.
+    ProgramLinking { name: String, log: String },
+    /// Something needed a feature that this device doesn't have.
+    UnsupportedFeature(&'static str),
+    /// A texture was needed that is wider or taller than the device allows.
+    TextureTooLarge { size: Vector2I, max_size: i32 },
+    /// More entries were needed in a fixed-size GPU buffer or texture than it can hold.
+    BufferOverflow { buffer: &'static str, capacity: usize, requested: usize },
+    /// A paint couldn't be updated in place, for the given reason, so the scene must be rebuilt.
+    IncompatiblePaint(&'static str),
+    /// A path couldn't be moved without retiling it, for the given reason, so the scene must be
+    /// rebuilt.
+    NeedsRetiling(&'static str),
+}
+
+impl Display for RenderError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            RenderError::MissingResource { ref path, ref reason } => {
+                write!(formatter, "couldn't load `{}`: {}", path, reason)
+            }
+            RenderError::ShaderCompilation { ref name, kind, ref log } => {
+                write!(formatter, "{:?} shader '{}' failed to compile", kind, name)?;
+                write_log(formatter, log)
+            }
+            RenderError::ProgramLinking { ref name, ref log } => {
+                write!(formatter, "program '{}' failed to link", name)?;
+                write_log(formatter, log)
+            }
+            RenderError::UnsupportedFeature(feature) => {
+                write!(formatter, "the device doesn't support {}", feature)
+            }
+            RenderError::TextureTooLarge { size, max_size } => {
+                write!(formatter,
+                       "a {}×{} texture is larger than the device's limit of {} pixels",
+                       size.x(),
+                       size.y(),
+                       max_size)
+            }
+            RenderError::BufferOverflow { buffer, capacity, requested } => {
+                write!(formatter,
+                       "{} entries don't fit in the {}, which holds {}",
+                       requested,
+                       buffer,
+                       capacity)
+            }
+            RenderError::IncompatiblePaint(reason) => {
+                write!(formatter, "the paint can't be updated without a rebuild: {}", reason)
+            }
+            RenderError::NeedsRetiling(reason) => {
+                write!(formatter, "the path can't be moved without a rebuild: {}", reason)
+            }
+        }
+    }
+}
+
+impl Error for RenderError {}
+
+fn write_log(formatter: &mut Formatter, log: &str) -> fmt::Result {
+    let log = log.trim_end_matches(|c: char| c == '\0' || c.is_whitespace());
+    if log.is_empty() {
+        Ok(())
+    } else {
+        write!(formatter, ":\n{}", log)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ShaderKind;
+    use super::RenderError;
+
+    #[test]
+    fn test_logs_are_trimmed() {
+        let error = RenderError::ShaderCompilation {
+            name: "fill".to_owned(),
+            kind: ShaderKind::Fragment,
+            log: "0:1: syntax error\n\0\0".to_owned(),
+        };
+        assert_eq!(error.to_string(),
+                   "Fragment shader 'fill' failed to compile:\n0:1: syntax error");
+
+        let error = RenderError::ProgramLinking { name: "tile".to_owned(), log: "\0".to_owned() };
+        assert_eq!(error.to_string(), "program 'tile' failed to link");
+    }
+}
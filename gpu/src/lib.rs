@@ -13,6 +13,8 @@
 #[macro_use]
 extern crate bitflags;
 
+use crate::error::RenderError;
+use crate::program_cache::ProgramCache;
 use half::f16;
 use image::ImageFormat;
 use pathfinder_color::ColorF;
@@ -22,11 +24,16 @@ use pathfinder_geometry::vector::{Vector2I, vec2i};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::{F32x2, F32x4, I32x2};
 use std::os::raw::c_void;
+use std::sync::Arc;
 use std::time::Duration;
 
+pub mod error;
+pub mod program_cache;
+
 pub trait Device: Sized {
     type Buffer;
     type Fence;
+    type QueueFence;
     type Framebuffer;
     type ImageParameter;
     type Program;
@@ -37,23 +44,36 @@ pub trait Device: Sized {
     type TextureDataReceiver;
     type TimerQuery;
     type Uniform;
+    type UniformBuffer;
     type VertexArray;
     type VertexAttr;
 
     fn feature_level(&self) -> FeatureLevel;
+    fn capabilities(&self) -> DeviceCapabilities;
     fn create_texture(&self, format: TextureFormat, size: Vector2I) -> Self::Texture;
+    /// Creates a texture that is only rendered to and sampled on the GPU, never uploaded to or
+    /// read back, and whose contents are only needed until `discard_transient_texture()` is
+    /// called. See `DeviceCapabilities::aliasable_transient_textures`.
+    fn create_transient_texture(&self, format: TextureFormat, size: Vector2I) -> Self::Texture {
+        self.create_texture(format, size)
+    }
+    /// Destroys a texture created with `create_transient_texture()` once the commands already
+    /// submitted are done with it, letting transient textures created later reuse its memory.
+    fn discard_transient_texture(&self, texture: Self::Texture) {
+        drop(texture)
+    }
     fn create_texture_from_data(&self, format: TextureFormat, size: Vector2I, data: TextureDataRef)
                                 -> Self::Texture;
     fn create_shader(&self, resources: &dyn ResourceLoader, name: &str, kind: ShaderKind)
-                     -> Self::Shader;
+                     -> Result<Self::Shader, RenderError>;
     fn create_shader_from_source(&self, name: &str, source: &[u8], kind: ShaderKind)
-                                 -> Self::Shader;
+                                 -> Result<Self::Shader, RenderError>;
     fn create_vertex_array(&self) -> Self::VertexArray;
     fn create_program_from_shaders(&self,
                                    resources: &dyn ResourceLoader,
                                    name: &str,
                                    shaders: ProgramKind<Self::Shader>)
-                                   -> Self::Program;
+                                   -> Result<Self::Program, RenderError>;
     fn set_compute_program_local_size(&self,
                                       program: &mut Self::Program,
                                       local_size: ComputeDimensions);
@@ -63,6 +83,11 @@ pub trait Device: Sized {
     fn get_image_parameter(&self, program: &Self::Program, name: &str) -> Self::ImageParameter;
     fn get_storage_buffer(&self, program: &Self::Program, name: &str, binding: u32)
                           -> Self::StorageBuffer;
+    /// Looks up a `std140` uniform block, to be bound to a buffer in `RenderState` or
+    /// `ComputeState`. The block is given the binding point `binding` on backends that need one;
+    /// programs using more than one block must give each a different binding.
+    fn get_uniform_buffer(&self, program: &Self::Program, name: &str, binding: u32)
+                          -> Self::UniformBuffer;
     fn bind_buffer(&self,
                    vertex_array: &Self::VertexArray,
                    buffer: &Self::Buffer,
@@ -101,6 +126,11 @@ pub trait Device: Sized {
     fn dispatch_compute(&self, dimensions: ComputeDimensions, state: &ComputeState<Self>);
     fn add_fence(&self) -> Self::Fence;
     fn wait_for_fence(&self, fence: &Self::Fence);
+    fn supports_async_compute(&self) -> bool;
+    fn begin_async_compute_commands(&self);
+    fn end_async_compute_commands(&self);
+    fn signal_queue_fence(&self) -> Self::QueueFence;
+    fn wait_for_queue_fence(&self, fence: &Self::QueueFence);
     fn create_timer_query(&self) -> Self::TimerQuery;
     fn begin_timer_query(&self, query: &Self::TimerQuery);
     fn end_timer_query(&self, query: &Self::TimerQuery);
@@ -113,50 +143,60 @@ pub trait Device: Sized {
                                resources: &dyn ResourceLoader,
                                name: &str,
                                format: TextureFormat)
-                               -> Self::Texture {
-        let data = resources.slurp(&format!("textures/{}.png", name)).unwrap();
-        let image = image::load_from_memory_with_format(&data, ImageFormat::Png).unwrap();
+                               -> Result<Self::Texture, RenderError> {
+        let path = format!("textures/{}.png", name);
+        let missing = |reason: String| RenderError::MissingResource { path: path.clone(), reason };
+        let data = resources.slurp(&path).map_err(|error| missing(error.to_string()))?;
+        let image = image::load_from_memory_with_format(&data, ImageFormat::Png)
+            .map_err(|error| missing(error.to_string()))?;
         match format {
             TextureFormat::R8 => {
                 let image = image.to_luma();
                 let size = vec2i(image.width() as i32, image.height() as i32);
-                self.create_texture_from_data(format, size, TextureDataRef::U8(&image))
+                Ok(self.create_texture_from_data(format, size, TextureDataRef::U8(&image)))
             }
             TextureFormat::RGBA8 => {
                 let image = image.to_rgba();
                 let size = vec2i(image.width() as i32, image.height() as i32);
-                self.create_texture_from_data(format, size, TextureDataRef::U8(&image))
+                Ok(self.create_texture_from_data(format, size, TextureDataRef::U8(&image)))
             }
-            _ => unimplemented!(),
+            _ => Err(RenderError::UnsupportedFeature("loading PNGs into floating-point textures")),
         }
     }
 
+    /// Makes programs created from now on save their compiled binaries in `cache`, and load them
+    /// from it instead of compiling them again when they're already there. Devices that can't
+    /// save compiled programs ignore this.
+    fn set_program_cache(&mut self, _: Arc<ProgramCache>) {}
+
     fn create_program_from_shader_names(
         &self,
         resources: &dyn ResourceLoader,
         program_name: &str,
         shader_names: ProgramKind<&str>,
-    ) -> Self::Program {
+    ) -> Result<Self::Program, RenderError> {
         let shaders = match shader_names {
             ProgramKind::Raster { vertex, fragment } => {
                 ProgramKind::Raster {
-                    vertex: self.create_shader(resources, vertex, ShaderKind::Vertex),
-                    fragment: self.create_shader(resources, fragment, ShaderKind::Fragment),
+                    vertex: self.create_shader(resources, vertex, ShaderKind::Vertex)?,
+                    fragment: self.create_shader(resources, fragment, ShaderKind::Fragment)?,
                 }
             }
             ProgramKind::Compute(compute) => {
-                ProgramKind::Compute(self.create_shader(resources, compute, ShaderKind::Compute))
+                ProgramKind::Compute(self.create_shader(resources, compute, ShaderKind::Compute)?)
             }
         };
         self.create_program_from_shaders(resources, program_name, shaders)
     }
 
-    fn create_raster_program(&self, resources: &dyn ResourceLoader, name: &str) -> Self::Program {
+    fn create_raster_program(&self, resources: &dyn ResourceLoader, name: &str)
+                             -> Result<Self::Program, RenderError> {
         let shaders = ProgramKind::Raster { vertex: name, fragment: name };
         self.create_program_from_shader_names(resources, name, shaders)
     }
 
-    fn create_compute_program(&self, resources: &dyn ResourceLoader, name: &str) -> Self::Program {
+    fn create_compute_program(&self, resources: &dyn ResourceLoader, name: &str)
+                              -> Result<Self::Program, RenderError> {
         let shaders = ProgramKind::Compute(name);
         self.create_program_from_shader_names(resources, name, shaders)
     }
@@ -170,6 +210,26 @@ pub enum FeatureLevel {
     D3D11,
 }
 
+/// The optional features that a device supports, so that callers can pick a way of rendering that
+/// works instead of assuming a fixed set per backend.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeviceCapabilities {
+    /// The largest width or height of a texture, in pixels.
+    pub max_texture_size: i32,
+    /// Whether compute programs, storage buffers, and images can be used.
+    pub compute: bool,
+    /// Whether blend factors can refer to a second color output of the fragment shader.
+    pub dual_source_blending: bool,
+    /// Whether `TextureFormat::RGBA16F` textures can be rendered to and blended.
+    pub f16_render_targets: bool,
+    /// Whether timer queries measure anything. If not, they shouldn't be used, as they may never
+    /// finish.
+    pub timer_queries: bool,
+    /// Whether transient textures share memory, so that discarding them at the end of every
+    /// frame and creating them again is cheaper than keeping them around.
+    pub aliasable_transient_textures: bool,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum TextureFormat {
     R8,
@@ -199,6 +259,7 @@ pub enum BufferTarget {
     Vertex,
     Index,
     Storage,
+    Uniform,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -256,6 +317,7 @@ pub struct RenderState<'a, D> where D: Device {
     pub vertex_array: &'a D::VertexArray,
     pub primitive: Primitive,
     pub uniforms: &'a [UniformBinding<'a, D::Uniform>],
+    pub uniform_buffers: &'a [UniformBufferBinding<'a, D::UniformBuffer, D::Buffer>],
     pub textures: &'a [TextureBinding<'a, D::TextureParameter, D::Texture>],
     pub images: &'a [ImageBinding<'a, D::ImageParameter, D::Texture>],
     pub viewport: RectI,
@@ -266,6 +328,7 @@ pub struct RenderState<'a, D> where D: Device {
 pub struct ComputeState<'a, D> where D: Device {
     pub program: &'a D::Program,
     pub uniforms: &'a [UniformBinding<'a, D::Uniform>],
+    pub uniform_buffers: &'a [UniformBufferBinding<'a, D::UniformBuffer, D::Buffer>],
     pub textures: &'a [TextureBinding<'a, D::TextureParameter, D::Texture>],
     pub images: &'a [ImageBinding<'a, D::ImageParameter, D::Texture>],
     pub storage_buffers: &'a [(&'a D::StorageBuffer, &'a D::Buffer)],
@@ -273,6 +336,8 @@ pub struct ComputeState<'a, D> where D: Device {
 
 pub type UniformBinding<'a, U> = (&'a U, UniformData);
 
+pub type UniformBufferBinding<'a, UB, B> = (&'a UB, &'a B);
+
 pub type TextureBinding<'a, TP, T> = (&'a TP, &'a T);
 
 pub type ImageBinding<'a, IP, T> = (&'a IP, &'a T, ImageAccess);
@@ -317,6 +382,12 @@ pub enum BlendFactor {
     DestAlpha,
     OneMinusDestAlpha,
     DestColor,
+    OneMinusSrcColor,
+    /// The second color output of the fragment shader. Requires `dual_source_blending`.
+    Src1Color,
+    /// One minus the second color output of the fragment shader. Requires
+    /// `dual_source_blending`.
+    OneMinusSrc1Color,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -328,19 +399,19 @@ pub enum BlendOp {
     Max,
 }
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
 pub struct DepthState {
     pub func: DepthFunc,
     pub write: bool,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DepthFunc {
     Less,
     Always,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct StencilState {
     pub func: StencilFunc,
     pub reference: u32,
@@ -348,7 +419,7 @@ pub struct StencilState {
     pub write: bool,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum StencilFunc {
     Always,
     Equal,
@@ -420,6 +491,87 @@ impl UniformData {
     pub fn from_transform_3d(transform: &Transform4F) -> UniformData {
         UniformData::Mat4([transform.c0, transform.c1, transform.c2, transform.c3])
     }
+
+    /// The alignment of this value in a uniform block, in bytes, per the `std140` layout rules.
+    #[inline]
+    pub fn std140_alignment(&self) -> usize {
+        match *self {
+            UniformData::Float(_) | UniformData::Int(_) => 4,
+            UniformData::IVec2(_) | UniformData::Vec2(_) => 8,
+            UniformData::IVec3(_) |
+            UniformData::Vec3(_) |
+            UniformData::Vec4(_) |
+            UniformData::Mat2(_) |
+            UniformData::Mat4(_) => 16,
+        }
+    }
+}
+
+/// Packs uniform values into the `std140` layout, for uploading to a buffer bound to a uniform
+/// block.
+///
+/// Values must be written in the order that the block declares its members.
+#[derive(Clone, Debug, Default)]
+pub struct Std140Writer {
+    data: Vec<u8>,
+}
+
+impl Std140Writer {
+    #[inline]
+    pub fn new() -> Std140Writer {
+        Std140Writer::default()
+    }
+
+    /// Appends a value, padding before it as needed, and returns its offset in bytes.
+    pub fn write(&mut self, value: &UniformData) -> usize {
+        let offset = align_up(self.data.len(), value.std140_alignment());
+        self.data.resize(offset, 0);
+        match *value {
+            UniformData::Float(value) => self.write_f32s(&[value]),
+            UniformData::Int(value) => self.write_i32s(&[value]),
+            UniformData::IVec2(value) => self.write_i32s(&[value.x(), value.y()]),
+            UniformData::IVec3(values) => self.write_i32s(&values),
+            UniformData::Vec2(value) => self.write_f32s(&[value.x(), value.y()]),
+            UniformData::Vec3(values) => self.write_f32s(&values),
+            UniformData::Vec4(value) => {
+                self.write_f32s(&[value.x(), value.y(), value.z(), value.w()])
+            }
+            // Matrix columns are each padded out to a `vec4`.
+            UniformData::Mat2(value) => {
+                self.write_f32s(&[value.x(), value.y(), 0.0, 0.0, value.z(), value.w(), 0.0, 0.0])
+            }
+            UniformData::Mat4(columns) => {
+                for column in &columns {
+                    self.write_f32s(&[column.x(), column.y(), column.z(), column.w()]);
+                }
+            }
+        }
+        offset
+    }
+
+    /// Returns the packed data, padded out to a multiple of 16 bytes as the layout requires.
+    pub fn into_data(mut self) -> Vec<u8> {
+        let len = align_up(self.data.len(), 16);
+        self.data.resize(len, 0);
+        self.data
+    }
+
+    fn write_f32s(&mut self, values: &[f32]) {
+        for value in values {
+            self.data.extend_from_slice(&value.to_ne_bytes());
+        }
+    }
+
+    fn write_i32s(&mut self, values: &[i32]) {
+        for value in values {
+            self.data.extend_from_slice(&value.to_ne_bytes());
+        }
+    }
+}
+
+#[inline]
+fn align_up(offset: usize, alignment: usize) -> usize {
+    offset.div_ceil(alignment) * alignment
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -527,3 +679,25 @@ impl<'a> TextureDataRef<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Std140Writer, UniformData};
+    use pathfinder_simd::default::{F32x2, F32x4};
+
+    #[test]
+    fn test_std140_packing() {
+        let mut writer = Std140Writer::new();
+        assert_eq!(writer.write(&UniformData::Float(1.0)), 0);
+        assert_eq!(writer.write(&UniformData::Vec2(F32x2::new(2.0, 3.0))), 8);
+        assert_eq!(writer.write(&UniformData::Vec3([4.0, 5.0, 6.0])), 16);
+        assert_eq!(writer.write(&UniformData::Int(7)), 28);
+        assert_eq!(writer.write(&UniformData::Mat2(F32x4::new(1.0, 2.0, 3.0, 4.0))), 32);
+        assert_eq!(writer.write(&UniformData::Float(8.0)), 64);
+
+        let data = writer.into_data();
+        assert_eq!(data.len(), 80);
+        assert_eq!(data[28..32], 7i32.to_ne_bytes());
+        assert_eq!(data[48..52], 3.0f32.to_ne_bytes());
+    }
+}
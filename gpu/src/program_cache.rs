@@ -0,0 +1,149 @@
+// pathfinder/gpu/src/program_cache.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A cache of compiled program binaries, so that programs don't have to be compiled from source
+//! every time a device is created.
+//!
+//! A single cache can be shared among many devices. Binaries are kept in memory for the life of
+//! the cache and, if it was given a directory, on disk, where later runs of the application will
+//! find them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Identifies a compiled program by everything that went into compiling it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProgramCacheKey(pub u64);
+
+pub struct ProgramCache {
+    binaries: Mutex<HashMap<ProgramCacheKey, Arc<Vec<u8>>>>,
+    directory: Option<PathBuf>,
+}
+
+impl ProgramCache {
+    /// Creates a cache that keeps binaries in memory only.
+    #[inline]
+    pub fn new() -> ProgramCache {
+        ProgramCache { binaries: Mutex::new(HashMap::new()), directory: None }
+    }
+
+    /// Creates a cache that also stores binaries as files in `directory`, which is created if
+    /// it doesn't exist.
+    #[inline]
+    pub fn with_directory<P>(directory: P) -> ProgramCache where P: Into<PathBuf> {
+        ProgramCache { binaries: Mutex::new(HashMap::new()), directory: Some(directory.into()) }
+    }
+
+    /// Computes a key from the shader sources of a program and anything else that affects the
+    /// binary, such as the identity of the driver.
+    ///
+    /// The hash is stable across runs and builds, unlike that of `std::hash`, so that keys of
+    /// binaries on disk stay valid.
+    pub fn key(parts: &[&[u8]]) -> ProgramCacheKey {
+        let mut hash = FNV_OFFSET_BASIS;
+        for part in parts {
+            // Hash the length too, so that moving bytes from one part to the next changes the
+            // key.
+            for &byte in (part.len() as u64).to_le_bytes().iter().chain(part.iter()) {
+                hash = (hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+            }
+        }
+        ProgramCacheKey(hash)
+    }
+
+    /// Returns the binary stored under `key`, looking on disk if it isn't in memory yet.
+    pub fn get(&self, key: ProgramCacheKey) -> Option<Arc<Vec<u8>>> {
+        let mut binaries = self.binaries.lock().unwrap();
+        if let Some(binary) = binaries.get(&key) {
+            return Some((*binary).clone());
+        }
+
+        let binary = Arc::new(fs::read(self.path_for(key)?).ok()?);
+        binaries.insert(key, binary.clone());
+        Some(binary)
+    }
+
+    /// Stores a binary under `key`.
+    ///
+    /// Failing to write the file is not an error, since the binary can always be compiled again.
+    pub fn insert(&self, key: ProgramCacheKey, binary: Vec<u8>) {
+        if let Some(path) = self.path_for(key) {
+            // Write to a temporary file and rename it, so that another process reading the cache
+            // never sees half a binary.
+            let temp_path = path.with_extension(format!("tmp{}", std::process::id()));
+            let written = path.parent().map_or(Ok(()), fs::create_dir_all)
+                                       .and_then(|_| fs::write(&temp_path, &binary))
+                                       .and_then(|_| fs::rename(&temp_path, &path));
+            if written.is_err() {
+                drop(fs::remove_file(&temp_path));
+            }
+        }
+        self.binaries.lock().unwrap().insert(key, Arc::new(binary));
+    }
+
+    /// Forgets the binary stored under `key`, for example because the driver rejected it after
+    /// an update.
+    pub fn remove(&self, key: ProgramCacheKey) {
+        self.binaries.lock().unwrap().remove(&key);
+        if let Some(path) = self.path_for(key) {
+            drop(fs::remove_file(path));
+        }
+    }
+
+    fn path_for(&self, key: ProgramCacheKey) -> Option<PathBuf> {
+        self.directory.as_ref().map(|directory| directory.join(format!("{:016x}.bin", key.0)))
+    }
+}
+
+impl Default for ProgramCache {
+    #[inline]
+    fn default() -> ProgramCache {
+        ProgramCache::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ProgramCache;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_keys() {
+        let key = ProgramCache::key(&[b"vertex", b"fragment"]);
+        assert_eq!(key, ProgramCache::key(&[b"vertex", b"fragment"]));
+        assert_ne!(key, ProgramCache::key(&[b"vertexf", b"ragment"]));
+        assert_ne!(key, ProgramCache::key(&[b"fragment", b"vertex"]));
+    }
+
+    #[test]
+    fn test_disk_cache() {
+        let directory = env::temp_dir().join(format!("pathfinder-program-cache-test-{}",
+                                                     std::process::id()));
+        let key = ProgramCache::key(&[b"program"]);
+
+        let cache = ProgramCache::with_directory(&directory);
+        assert!(cache.get(key).is_none());
+        cache.insert(key, vec![1, 2, 3]);
+
+        // A new cache finds the binary on disk.
+        let cache = ProgramCache::with_directory(&directory);
+        assert_eq!(*cache.get(key).unwrap(), vec![1, 2, 3]);
+        cache.remove(key);
+        assert!(ProgramCache::with_directory(&directory).get(key).is_none());
+
+        drop(fs::remove_dir_all(&directory));
+    }
+}
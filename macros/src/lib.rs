@@ -0,0 +1,74 @@
+// pathfinder/macros/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Procedural macros that build Pathfinder scenes at compile time.
+//!
+//! Static assets such as icons don't change between runs, so there's no need to parse their XML
+//! and construct their geometry every time the program starts. `include_svg!` does that work
+//! during compilation and embeds the result in the binary as an archived scene.
+
+extern crate proc_macro;
+
+use pathfinder_svg::BuiltSVG;
+use proc_macro::TokenStream;
+use quote::quote;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use syn::{Error, LitByteStr, LitStr};
+use usvg::{Options as UsvgOptions, Tree};
+
+/// Parses an SVG file at compile time and evaluates to a `pathfinder_renderer::scene::Scene`
+/// containing it.
+///
+/// The path is relative to the directory containing the calling crate's `Cargo.toml`. The crate
+/// is rebuilt whenever the file changes.
+///
+/// ```ignore
+/// let scene = pathfinder_macros::include_svg!("resources/svg/logo.svg");
+/// ```
+#[proc_macro]
+pub fn include_svg(input: TokenStream) -> TokenStream {
+    let path_literal = syn::parse_macro_input!(input as LitStr);
+    match archive_svg(&path_literal) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn archive_svg(path_literal: &LitStr) -> Result<proc_macro2::TokenStream, Error> {
+    let mut path = match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(manifest_dir) => PathBuf::from(manifest_dir),
+        None => PathBuf::new(),
+    };
+    path.push(path_literal.value());
+
+    let error = |message: String| Error::new(path_literal.span(), message);
+    let data = fs::read(&path).map_err(|err| error(format!("{}: {}", path.display(), err)))?;
+    let tree = Tree::from_data(&data, &UsvgOptions::default())
+        .map_err(|err| error(format!("{}: {}", path.display(), err)))?;
+    let scene = BuiltSVG::from_tree(&tree).scene;
+
+    let mut archive = vec![];
+    scene.write(&mut archive)
+         .map_err(|err| error(format!("{}: can't embed this SVG: {}", path.display(), err)))?;
+
+    // `include_bytes!` of the source file makes Cargo track it, so that editing the SVG
+    // rebuilds the crate.
+    let path_string = LitStr::new(&path.to_string_lossy(), path_literal.span());
+    let archive = LitByteStr::new(&archive, path_literal.span());
+    Ok(quote! {
+        {
+            const _: &[u8] = include_bytes!(#path_string);
+            ::pathfinder_renderer::scene::Scene::read(&mut &#archive[..])
+                .expect("Embedded scene archive was corrupt!")
+        }
+    })
+}
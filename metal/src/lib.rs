@@ -41,17 +41,20 @@ use metal::{RenderCommandEncoder, RenderCommandEncoderRef, RenderPassDescriptor}
 use metal::{RenderPassDescriptorRef, RenderPipelineColorAttachmentDescriptorRef};
 use metal::{RenderPipelineDescriptor, RenderPipelineReflection, RenderPipelineReflectionRef};
 use metal::{RenderPipelineState, SamplerDescriptor, SamplerState, StencilDescriptor};
-use metal::{StructMemberRef, StructType, StructTypeRef, TextureDescriptor, Texture, TextureRef};
+use metal::{MTLTexture, StructMemberRef, StructType, StructTypeRef, TextureDescriptor, Texture};
+use metal::TextureRef;
 use metal::{VertexAttribute, VertexAttributeRef, VertexDescriptor, VertexDescriptorRef};
-use objc::runtime::{Class, Object};
+use objc::runtime::{BOOL, Class, Object, YES};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::{Vector2I, vec2i};
 use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode};
-use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, FeatureLevel};
+use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, DeviceCapabilities};
+use pathfinder_gpu::FeatureLevel;
 use pathfinder_gpu::{ImageAccess, Primitive, ProgramKind, RenderState, RenderTarget, ShaderKind};
 use pathfinder_gpu::{StencilFunc, TextureData, TextureDataRef, TextureFormat};
 use pathfinder_gpu::{TextureSamplingFlags, UniformData, VertexAttrClass};
 use pathfinder_gpu::{VertexAttrDescriptor, VertexAttrType};
+use pathfinder_gpu::error::RenderError;
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::{F32x2, F32x4, I32x2};
 use std::cell::{Cell, RefCell};
@@ -65,16 +68,28 @@ use std::time::{Duration, Instant};
 
 const FIRST_VERTEX_BUFFER_INDEX: u64 = 16;
 
+// Transient textures are suballocated from heaps at least this large.
+const TRANSIENT_HEAP_SIZE: u64 = 16 * 1024 * 1024;
+
+// `MTLHazardTrackingModeTracked`.
+const MTL_HAZARD_TRACKING_MODE_TRACKED: NSUInteger = 2;
+
 pub struct MetalDevice {
     device: metal::Device,
     main_color_texture: Texture,
     main_depth_stencil_texture: Texture,
     command_queue: CommandQueue,
     command_buffers: RefCell<Vec<CommandBuffer>>,
+    async_compute_command_queue: CommandQueue,
+    async_compute_command_buffer: RefCell<Option<CommandBuffer>>,
     samplers: Vec<SamplerState>,
     shared_event: SharedEvent,
     shared_event_listener: SharedEventListener,
     next_timer_query_event_value: Cell<u64>,
+    queue_event: SharedEvent,
+    next_queue_fence_value: Cell<u64>,
+    transient_heaps: RefCell<Vec<TransientHeap>>,
+    supports_tracked_heaps: bool,
 }
 
 pub enum MetalProgram {
@@ -102,6 +117,7 @@ impl MetalDevice {
     #[inline]
     pub unsafe fn new<T>(device: metal::Device, texture: T) -> MetalDevice where T: IntoTexture {
         let command_queue = device.new_command_queue();
+        let async_compute_command_queue = device.new_command_queue();
 
         let samplers = (0..16).map(|sampling_flags_value| {
             let sampling_flags = TextureSamplingFlags::from_bits(sampling_flags_value).unwrap();
@@ -140,6 +156,18 @@ impl MetalDevice {
         let main_depth_stencil_texture = device.create_depth_stencil_texture(framebuffer_size);
 
         let shared_event = device.new_shared_event();
+        let queue_event = device.new_shared_event();
+
+        // Resources in heaps that don't track hazards need manual synchronization, which the
+        // device abstraction has no way to express, so only use heaps where they can track them
+        // (macOS 10.15 and iOS 13).
+        let supports_tracked_heaps: bool = {
+            let descriptor: *mut Object = msg_send![class!(MTLHeapDescriptor), new];
+            let responds: BOOL = msg_send![descriptor,
+                                           respondsToSelector:sel!(setHazardTrackingMode:)];
+            let () = msg_send![descriptor, release];
+            responds == YES
+        };
 
         MetalDevice {
             device,
@@ -147,10 +175,16 @@ impl MetalDevice {
             main_depth_stencil_texture,
             command_queue,
             command_buffers: RefCell::new(vec![]),
+            async_compute_command_queue,
+            async_compute_command_buffer: RefCell::new(None),
             samplers,
             shared_event,
             shared_event_listener: SharedEventListener::new(),
             next_timer_query_event_value: Cell::new(1),
+            queue_event,
+            next_queue_fence_value: Cell::new(1),
+            transient_heaps: RefCell::new(vec![]),
+            supports_tracked_heaps,
         }
     }
 
@@ -269,6 +303,8 @@ pub struct MetalStorageBufferIndex(u64);
 #[derive(Clone)]
 pub struct MetalFence(Arc<MetalFenceInfo>);
 
+pub struct MetalQueueFence(u64);
+
 struct MetalFenceInfo {
     mutex: Mutex<MetalFenceStatus>,
     cond: Condvar,
@@ -289,6 +325,7 @@ pub struct MetalVertexArray {
 impl Device for MetalDevice {
     type Buffer = MetalBuffer;
     type Fence = MetalFence;
+    type QueueFence = MetalQueueFence;
     type Framebuffer = MetalFramebuffer;
     type ImageParameter = MetalImageParameter;
     type Program = MetalProgram;
@@ -299,6 +336,8 @@ impl Device for MetalDevice {
     type TextureParameter = MetalTextureParameter;
     type TimerQuery = MetalTimerQuery;
     type Uniform = MetalUniform;
+    // Uniform blocks and storage buffers are both just buffer arguments in Metal.
+    type UniformBuffer = MetalStorageBuffer;
     type VertexArray = MetalVertexArray;
     type VertexAttr = VertexAttribute;
 
@@ -307,25 +346,62 @@ impl Device for MetalDevice {
         FeatureLevel::D3D11
     }
 
-    // TODO: Add texture usage hint.
-    fn create_texture(&self, format: TextureFormat, size: Vector2I) -> MetalTexture {
-        let descriptor = TextureDescriptor::new();
-        descriptor.set_texture_type(MTLTextureType::D2);
-        match format {
-            TextureFormat::R8 => descriptor.set_pixel_format(MTLPixelFormat::R8Unorm),
-            TextureFormat::R16F => descriptor.set_pixel_format(MTLPixelFormat::R16Float),
-            TextureFormat::RGBA8 => descriptor.set_pixel_format(MTLPixelFormat::RGBA8Unorm),
-            TextureFormat::RGBA16F => descriptor.set_pixel_format(MTLPixelFormat::RGBA16Float),
-            TextureFormat::RGBA32F => descriptor.set_pixel_format(MTLPixelFormat::RGBA32Float),
+    fn capabilities(&self) -> DeviceCapabilities {
+        // Metal doesn't report the texture size limit; these are the limits of the oldest GPU
+        // families that have the shared events that fences and timer queries use.
+        let max_texture_size = if cfg!(target_os = "macos") { 16384 } else { 8192 };
+        DeviceCapabilities {
+            max_texture_size,
+            compute: true,
+            dual_source_blending: true,
+            f16_render_targets: true,
+            timer_queries: true,
+            aliasable_transient_textures: self.supports_tracked_heaps,
         }
-        descriptor.set_width(size.x() as u64);
-        descriptor.set_height(size.y() as u64);
+    }
+
+    fn create_texture(&self, format: TextureFormat, size: Vector2I) -> MetalTexture {
+        let descriptor = create_texture_descriptor(format, size);
         descriptor.set_storage_mode(MTLStorageMode::Managed);
         descriptor.set_usage(MTLTextureUsage::Unknown);
-        MetalTexture {
-            texture: self.device.new_texture(&descriptor),
-            sampling_flags: Cell::new(TextureSamplingFlags::empty()),
-            dirty: Cell::new(false),
+        MetalTexture::new(self.device.new_texture(&descriptor))
+    }
+
+    fn create_transient_texture(&self, format: TextureFormat, size: Vector2I) -> MetalTexture {
+        if !self.supports_tracked_heaps {
+            return self.create_texture(format, size);
+        }
+
+        let descriptor = create_texture_descriptor(format, size);
+        descriptor.set_storage_mode(MTLStorageMode::Private);
+        descriptor.set_usage(MTLTextureUsage::RenderTarget | MTLTextureUsage::ShaderRead |
+                             MTLTextureUsage::ShaderWrite);
+        let size_and_align = self.device.heap_texture_size_and_align(&descriptor);
+
+        let mut transient_heaps = self.transient_heaps.borrow_mut();
+        for heap in transient_heaps.iter() {
+            if heap.max_available_size(size_and_align.align) >= size_and_align.size {
+                if let Some(texture) = heap.new_texture(&descriptor) {
+                    return MetalTexture::new(texture);
+                }
+            }
+        }
+
+        let heap_size = TRANSIENT_HEAP_SIZE.max(size_and_align.size + size_and_align.align);
+        let heap = TransientHeap::new(&self.device, heap_size);
+        let texture = heap.new_texture(&descriptor).expect("Failed to allocate transient texture!");
+        transient_heaps.push(heap);
+        MetalTexture::new(texture)
+    }
+
+    fn discard_transient_texture(&self, texture: MetalTexture) {
+        // Heap resources keep their memory until they're released, unless they're marked
+        // aliasable. The heap tracks hazards, so commands that still use this texture finish
+        // before any texture that reuses its memory is written.
+        if self.supports_tracked_heaps {
+            unsafe {
+                let () = msg_send![texture.texture.as_ptr(), makeAliasable];
+            }
         }
     }
 
@@ -336,19 +412,28 @@ impl Device for MetalDevice {
         texture
     }
 
-    fn create_shader_from_source(&self, name: &str, source: &[u8], _: ShaderKind) -> MetalShader {
-        let source = String::from_utf8(source.to_vec()).expect("Source wasn't valid UTF-8!");
+    fn create_shader_from_source(&self, name: &str, source: &[u8], kind: ShaderKind)
+                                 -> Result<MetalShader, RenderError> {
+        let compilation_error = |log: String| {
+            RenderError::ShaderCompilation { name: name.to_owned(), kind, log }
+        };
+
+        let source = String::from_utf8(source.to_vec()).map_err(|error| {
+            compilation_error(error.to_string())
+        })?;
 
         let compile_options = CompileOptions::new();
-        let library = self.device.new_library_with_source(&source, &compile_options).unwrap();
-        let function = library.get_function("main0", None).unwrap();
+        let library = self.device
+                          .new_library_with_source(&source, &compile_options)
+                          .map_err(compilation_error)?;
+        let function = library.get_function("main0", None).map_err(compilation_error)?;
 
-        MetalShader {
+        Ok(MetalShader {
             library,
             function,
             name: name.to_owned(),
             arguments: RefCell::new(None),
-        }
+        })
     }
 
     fn create_vertex_array(&self) -> MetalVertexArray {
@@ -378,8 +463,9 @@ impl Device for MetalDevice {
                                    _: &dyn ResourceLoader,
                                    _: &str,
                                    shaders: ProgramKind<MetalShader>)
-                                   -> MetalProgram {
-        match shaders {
+                                   -> Result<MetalProgram, RenderError> {
+        // Metal has no separate link step, so anything that could fail already has.
+        Ok(match shaders {
             ProgramKind::Raster { vertex: vertex_shader, fragment: fragment_shader } => {
                 MetalProgram::Raster(MetalRasterProgram { vertex_shader, fragment_shader })
             }
@@ -387,7 +473,7 @@ impl Device for MetalDevice {
                 let local_size = MTLSize { width: 0, height: 0, depth: 0 };
                 MetalProgram::Compute(MetalComputeProgram { shader, local_size })
             }
-        }
+        })
     }
 
     // FIXME(pcwalton): Is there a way to introspect the shader to find `gl_WorkGroupSize`? That
@@ -438,6 +524,10 @@ impl Device for MetalDevice {
         MetalStorageBuffer { indices: RefCell::new(None), name: name.to_owned() }
     }
 
+    fn get_uniform_buffer(&self, _: &Self::Program, name: &str, _: u32) -> MetalStorageBuffer {
+        MetalStorageBuffer { indices: RefCell::new(None), name: format!("u{}", name) }
+    }
+
     fn configure_vertex_attr(&self,
                              vertex_array: &MetalVertexArray,
                              attr: &VertexAttribute,
@@ -709,8 +799,12 @@ impl Device for MetalDevice {
     fn dispatch_compute(&self,
                         size: ComputeDimensions,
                         compute_state: &ComputeState<MetalDevice>) {
+        let async_compute_command_buffer = self.async_compute_command_buffer.borrow();
         let command_buffers = self.command_buffers.borrow();
-        let command_buffer = command_buffers.last().unwrap();
+        let command_buffer = match *async_compute_command_buffer {
+            Some(ref async_compute_command_buffer) => async_compute_command_buffer,
+            None => command_buffers.last().unwrap(),
+        };
 
         let encoder = command_buffer.new_compute_command_encoder();
 
@@ -793,19 +887,15 @@ impl Device for MetalDevice {
     }
 
     fn begin_timer_query(&self, query: &MetalTimerQuery) {
-        self.command_buffers
-            .borrow_mut()
-            .last()
-            .unwrap()
-            .encode_signal_event(&self.shared_event, query.0.event_value);
+        self.with_current_command_buffer(|command_buffer| {
+            command_buffer.encode_signal_event(&self.shared_event, query.0.event_value)
+        });
     }
 
     fn end_timer_query(&self, query: &MetalTimerQuery) {
-        self.command_buffers
-            .borrow_mut()
-            .last()
-            .unwrap()
-            .encode_signal_event(&self.shared_event, query.0.event_value + 1);
+        self.with_current_command_buffer(|command_buffer| {
+            command_buffer.encode_signal_event(&self.shared_event, query.0.event_value + 1)
+        });
     }
 
     fn try_recv_timer_query(&self, query: &MetalTimerQuery) -> Option<Duration> {
@@ -844,14 +934,17 @@ impl Device for MetalDevice {
         resources: &dyn ResourceLoader,
         name: &str,
         kind: ShaderKind,
-    ) -> Self::Shader {
+    ) -> Result<Self::Shader, RenderError> {
         let suffix = match kind {
             ShaderKind::Vertex => 'v',
             ShaderKind::Fragment => 'f',
             ShaderKind::Compute => 'c',
         };
         let path = format!("shaders/metal/{}.{}s.metal", name, suffix);
-        self.create_shader_from_source(name, &resources.slurp(&path).unwrap(), kind)
+        let source = resources.slurp(&path).map_err(|error| {
+            RenderError::MissingResource { path, reason: error.to_string() }
+        })?;
+        self.create_shader_from_source(name, &source, kind)
     }
 
     fn add_fence(&self) -> MetalFence {
@@ -876,9 +969,54 @@ impl Device for MetalDevice {
             guard = fence.0.cond.wait(guard).unwrap();
         }
     }
+
+    #[inline]
+    fn supports_async_compute(&self) -> bool {
+        true
+    }
+
+    fn begin_async_compute_commands(&self) {
+        let mut async_compute_command_buffer = self.async_compute_command_buffer.borrow_mut();
+        debug_assert!(async_compute_command_buffer.is_none());
+        *async_compute_command_buffer =
+            Some(self.async_compute_command_queue.new_command_buffer().retain());
+    }
+
+    fn end_async_compute_commands(&self) {
+        let async_compute_command_buffer = self.async_compute_command_buffer.borrow_mut().take();
+        async_compute_command_buffer.expect("Not recording async compute commands!").commit();
+    }
+
+    // Queue fences are GPU-side only: they signal and wait on a shared event with monotonically
+    // increasing values and never block the CPU.
+    fn signal_queue_fence(&self) -> MetalQueueFence {
+        let value = self.next_queue_fence_value.get();
+        self.next_queue_fence_value.set(value + 1);
+        self.with_current_command_buffer(|command_buffer| {
+            command_buffer.encode_signal_event(&self.queue_event, value)
+        });
+        MetalQueueFence(value)
+    }
+
+    fn wait_for_queue_fence(&self, fence: &MetalQueueFence) {
+        self.with_current_command_buffer(|command_buffer| {
+            command_buffer.encode_wait_for_event(&self.queue_event, fence.0)
+        });
+    }
 }
 
 impl MetalDevice {
+    // Calls the given function with the async compute command buffer if one is being recorded, or
+    // the main command buffer otherwise.
+    fn with_current_command_buffer<F, R>(&self, f: F) -> R where F: FnOnce(&CommandBuffer) -> R {
+        let async_compute_command_buffer = self.async_compute_command_buffer.borrow();
+        if let Some(ref async_compute_command_buffer) = *async_compute_command_buffer {
+            return f(async_compute_command_buffer);
+        }
+        drop(async_compute_command_buffer);
+        f(self.command_buffers.borrow().last().unwrap())
+    }
+
     fn get_uniform_index(&self, shader: &MetalShader, name: &str) -> Option<MetalUniformIndex> {
         let uniforms = shader.arguments.borrow();
         let arguments = match *uniforms {
@@ -955,8 +1093,10 @@ impl MetalDevice {
                 MTLDataType::Struct => {}
                 _ => continue,
             }
+            // Storage buffers are found by their array member, and uniform blocks, which are
+            // looked up with a `u` prefix, by their own name.
             let struct_type = argument.buffer_struct_type();
-            if struct_type.member_from_name(&main_name).is_some() {
+            if argument.name() == name || struct_type.member_from_name(&main_name).is_some() {
                 main_argument = Some(argument.index());
             }
         }
@@ -1264,6 +1404,28 @@ impl MetalDevice {
                                                             Some(&image.texture));
             }
         }
+
+        // Set uniform buffers.
+        for &(uniform_buffer_id, uniform_buffer_binding) in render_state.uniform_buffers {
+            self.populate_storage_buffer_indices_if_necessary(uniform_buffer_id,
+                                                              &render_state.program);
+
+            let indices = uniform_buffer_id.indices.borrow_mut();
+            let indices = indices.as_ref().unwrap();
+            let (vertex_indices, fragment_indices) = match indices.0 {
+                ProgramKind::Raster { ref vertex, ref fragment } => (vertex, fragment),
+                _ => unreachable!(),
+            };
+
+            if let Some(ref buffer) = *uniform_buffer_binding.buffer.borrow() {
+                if let Some(vertex_index) = *vertex_indices {
+                    render_command_encoder.set_vertex_buffer(vertex_index.0, Some(buffer), 0);
+                }
+                if let Some(fragment_index) = *fragment_indices {
+                    render_command_encoder.set_fragment_buffer(fragment_index.0, Some(buffer), 0);
+                }
+            }
+        }
     }
 
     fn set_compute_uniforms(&self,
@@ -1322,8 +1484,9 @@ impl MetalDevice {
             }
         }
 
-        // Set storage buffers.
-        for &(storage_buffer_id, storage_buffer_binding) in compute_state.storage_buffers {
+        // Set uniform and storage buffers.
+        for &(storage_buffer_id, storage_buffer_binding) in
+                compute_state.uniform_buffers.iter().chain(compute_state.storage_buffers.iter()) {
             self.populate_storage_buffer_indices_if_necessary(storage_buffer_id,
                                                               &compute_state.program);
 
@@ -1634,10 +1797,17 @@ impl MetalDevice {
 }
 
 trait DeviceExtra {
+    fn heap_texture_size_and_align(&self, descriptor: &TextureDescriptor) -> SizeAndAlign;
     fn create_depth_stencil_texture(&self, size: Vector2I) -> Texture;
 }
 
 impl DeviceExtra for metal::Device {
+    fn heap_texture_size_and_align(&self, descriptor: &TextureDescriptor) -> SizeAndAlign {
+        unsafe {
+            msg_send![self.as_ptr(), heapTextureSizeAndAlignWithDescriptor:descriptor.as_ptr()]
+        }
+    }
+
     fn create_depth_stencil_texture(&self, size: Vector2I) -> Texture {
         let descriptor = TextureDescriptor::new();
         descriptor.set_texture_type(MTLTextureType::D2);
@@ -1650,6 +1820,21 @@ impl DeviceExtra for metal::Device {
     }
 }
 
+fn create_texture_descriptor(format: TextureFormat, size: Vector2I) -> TextureDescriptor {
+    let descriptor = TextureDescriptor::new();
+    descriptor.set_texture_type(MTLTextureType::D2);
+    match format {
+        TextureFormat::R8 => descriptor.set_pixel_format(MTLPixelFormat::R8Unorm),
+        TextureFormat::R16F => descriptor.set_pixel_format(MTLPixelFormat::R16Float),
+        TextureFormat::RGBA8 => descriptor.set_pixel_format(MTLPixelFormat::RGBA8Unorm),
+        TextureFormat::RGBA16F => descriptor.set_pixel_format(MTLPixelFormat::RGBA16Float),
+        TextureFormat::RGBA32F => descriptor.set_pixel_format(MTLPixelFormat::RGBA32Float),
+    }
+    descriptor.set_width(size.x() as u64);
+    descriptor.set_height(size.y() as u64);
+    descriptor
+}
+
 // Helper types
 
 struct UniformBuffer {
@@ -1657,6 +1842,23 @@ struct UniformBuffer {
     ranges: Vec<Range<usize>>,
 }
 
+// `MTLSizeAndAlign`.
+#[repr(C)]
+struct SizeAndAlign {
+    size: NSUInteger,
+    align: NSUInteger,
+}
+
+impl MetalTexture {
+    fn new(texture: Texture) -> MetalTexture {
+        MetalTexture {
+            texture,
+            sampling_flags: Cell::new(TextureSamplingFlags::empty()),
+            dirty: Cell::new(false),
+        }
+    }
+}
+
 // Miscellaneous extra public methods
 
 impl MetalTexture {
@@ -1719,6 +1921,9 @@ impl BlendFactorExt for BlendFactor {
             BlendFactor::DestAlpha => MTLBlendFactor::DestinationAlpha,
             BlendFactor::OneMinusDestAlpha => MTLBlendFactor::OneMinusDestinationAlpha,
             BlendFactor::DestColor => MTLBlendFactor::DestinationColor,
+            BlendFactor::OneMinusSrcColor => MTLBlendFactor::OneMinusSourceColor,
+            BlendFactor::Src1Color => MTLBlendFactor::Source1Color,
+            BlendFactor::OneMinusSrc1Color => MTLBlendFactor::OneMinusSource1Color,
         }
     }
 }
@@ -2083,8 +2288,49 @@ impl CoreAnimationLayerExt for CoreAnimationLayer {
     }
 }
 
+// An `MTLHeap` that transient textures are suballocated from.
+struct TransientHeap(*mut Object);
+
+impl Drop for TransientHeap {
+    fn drop(&mut self) {
+        unsafe { msg_send![self.0, release] }
+    }
+}
+
+impl TransientHeap {
+    fn new(device: &metal::Device, size: u64) -> TransientHeap {
+        unsafe {
+            let descriptor: *mut Object = msg_send![class!(MTLHeapDescriptor), new];
+            let () = msg_send![descriptor, setSize:size];
+            let () = msg_send![descriptor, setStorageMode:MTLStorageMode::Private];
+            let () = msg_send![descriptor, setHazardTrackingMode:MTL_HAZARD_TRACKING_MODE_TRACKED];
+            let heap: *mut Object = msg_send![device.as_ptr(), newHeapWithDescriptor:descriptor];
+            let () = msg_send![descriptor, release];
+            assert!(!heap.is_null(), "Failed to create Metal heap!");
+            TransientHeap(heap)
+        }
+    }
+
+    fn max_available_size(&self, alignment: NSUInteger) -> NSUInteger {
+        unsafe { msg_send![self.0, maxAvailableSizeWithAlignment:alignment] }
+    }
+
+    fn new_texture(&self, descriptor: &TextureDescriptor) -> Option<Texture> {
+        unsafe {
+            let texture: *mut MTLTexture = msg_send![self.0,
+                                                     newTextureWithDescriptor:descriptor.as_ptr()];
+            if texture.is_null() {
+                None
+            } else {
+                Some(Texture::from_ptr(texture))
+            }
+        }
+    }
+}
+
 trait CommandBufferExt {
     fn encode_signal_event(&self, event: &SharedEvent, value: u64);
+    fn encode_wait_for_event(&self, event: &SharedEvent, value: u64);
     fn add_completed_handler(&self, block: RcBlock<(*mut Object,), ()>);
 }
 
@@ -2095,6 +2341,12 @@ impl CommandBufferExt for CommandBuffer {
         }
     }
 
+    fn encode_wait_for_event(&self, event: &SharedEvent, value: u64) {
+        unsafe {
+            msg_send![self.as_ptr(), encodeWaitForEvent:event.0 value:value]
+        }
+    }
+
     fn add_completed_handler(&self, block: RcBlock<(*mut Object,), ()>) {
         unsafe {
             msg_send![self.as_ptr(), addCompletedHandler:&*block]
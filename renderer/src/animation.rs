@@ -0,0 +1,121 @@
+// pathfinder/renderer/src/animation.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Paths that move after their scene is built. See `DrawPath::set_animated()`.
+//!
+//! Each animated path gets a transform slot when the scene is built, which its tiles carry to the
+//! GPU. The tile shader moves those tiles by the slot's transform, so the path can be moved every
+//! frame with `Renderer::set_path_transform()` without being tiled again. The masks are only
+//! accurate to within a pixel or so of the geometry they were rasterized for, though, so moves
+//! are limited to translations and small scales; anything else has to be retiled.
+
+use crate::scene::PathId;
+use pathfinder_geometry::transform2d::Transform2F;
+
+/// The most paths that can be animated in one scene. Any past this many are drawn as ordinary
+/// paths.
+pub const MAX_ANIMATED_PATHS: usize = 255;
+
+/// How far an animated path can be scaled, up or down, from the size it was tiled at before it
+/// has to be retiled.
+pub const MAX_ANIMATED_PATH_SCALE_CHANGE: f32 = 1.25;
+
+// How far the device transform of an animated path may stray from being axis-aligned.
+const AXIS_ALIGNMENT_EPSILON: f32 = 1.0e-4;
+
+/// The animated paths of a built scene, as the renderer needs them to move the paths.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AnimatedPathLayout {
+    /// A hash of the outlines of the animated paths, so that the renderer can tell a scene
+    /// retiled with paths in new places apart from the same one rendered again.
+    pub geometry_hash: u64,
+    /// The transform from scene to device coordinates that the paths were tiled with.
+    pub render_transform: Transform2F,
+    /// The animated paths, in slot order. Slot 0 means "not animated", so the path at index `i`
+    /// is in slot `i + 1`.
+    pub paths: Vec<PathId>,
+}
+
+impl AnimatedPathLayout {
+    /// Returns the transform slot of the given path, if it's animated.
+    pub fn slot(&self, path_id: PathId) -> Option<u8> {
+        self.paths.iter().position(|&other| other == path_id).map(|index| (index + 1) as u8)
+    }
+
+    /// Converts a transform of a path in scene coordinates, relative to the geometry it was tiled
+    /// with, to the device-space transform that the tile shader applies, if it's within the
+    /// limits that tiles can be moved by.
+    pub(crate) fn device_transform(&self, transform: &Transform2F)
+                                   -> Result<Transform2F, &'static str> {
+        let device_transform =
+            self.render_transform * *transform * self.render_transform.inverse();
+        let matrix = device_transform.matrix;
+        let scale = matrix.m11().abs().max(matrix.m22().abs());
+        if matrix.m12().abs() > scale * AXIS_ALIGNMENT_EPSILON ||
+                matrix.m21().abs() > scale * AXIS_ALIGNMENT_EPSILON {
+            return Err("the transform rotates or skews the path");
+        }
+        let scale_range = (1.0 / MAX_ANIMATED_PATH_SCALE_CHANGE)..=MAX_ANIMATED_PATH_SCALE_CHANGE;
+        if !scale_range.contains(&matrix.m11()) || !scale_range.contains(&matrix.m22()) {
+            return Err("the transform scales the path too much");
+        }
+        if !device_transform.vector.x().is_finite() || !device_transform.vector.y().is_finite() {
+            return Err("the transform isn't finite");
+        }
+        Ok(device_transform)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AnimatedPathLayout;
+    use crate::scene::PathId;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::vec2f;
+
+    fn layout(render_transform: Transform2F) -> AnimatedPathLayout {
+        AnimatedPathLayout {
+            geometry_hash: 0,
+            render_transform,
+            paths: vec![PathId(3), PathId(7)],
+        }
+    }
+
+    #[test]
+    fn test_slots() {
+        let layout = layout(Transform2F::default());
+        assert_eq!(layout.slot(PathId(3)), Some(1));
+        assert_eq!(layout.slot(PathId(7)), Some(2));
+        assert_eq!(layout.slot(PathId(4)), None);
+    }
+
+    #[test]
+    fn test_device_transform() {
+        // Scene coordinates are doubled on the way to device coordinates, so translations are
+        // too, but scales aren't. Scales stay centered on the scene origin.
+        let layout = layout(Transform2F::from_scale(2.0).translate(vec2f(10.0, 0.0)));
+        let transform = Transform2F::from_translation(vec2f(5.0, -3.0));
+        let device_transform = layout.device_transform(&transform).unwrap();
+        assert_eq!(device_transform.vector, vec2f(10.0, -6.0));
+        assert_eq!(device_transform.matrix, Transform2F::default().matrix);
+
+        let transform = Transform2F::from_scale(vec2f(1.2, 0.9));
+        let device_transform = layout.device_transform(&transform).unwrap();
+        assert!((device_transform.matrix.m11() - 1.2).abs() < 1.0e-5);
+        assert!((device_transform.matrix.m22() - 0.9).abs() < 1.0e-5);
+        let origin = layout.render_transform * vec2f(0.0, 0.0);
+        assert!((device_transform * origin - origin).length() < 1.0e-3);
+
+        assert!(layout.device_transform(&Transform2F::from_scale(1.5)).is_err());
+        assert!(layout.device_transform(&Transform2F::from_scale(0.5)).is_err());
+        assert!(layout.device_transform(&Transform2F::from_scale(vec2f(-1.0, 1.0))).is_err());
+        assert!(layout.device_transform(&Transform2F::from_rotation(0.1)).is_err());
+    }
+}
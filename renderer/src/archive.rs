@@ -0,0 +1,661 @@
+// pathfinder/renderer/src/archive.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A compact binary form of scenes, for assets that are prepared ahead of time.
+//!
+//! Reading an archived scene skips parsing and geometry construction entirely: the outlines,
+//! paints, and clip paths are stored exactly as the scene holds them. The `pathfinder_macros`
+//! crate uses this to embed SVG files in a binary at compile time.
+
+use crate::capture::{self, BLEND_MODES, invalid_data};
+use crate::paint::{Paint, PaintCompositeOp, PaintContents, PaintId};
+use crate::scene::{ClipPath, ClipPathId, ContourPaint, DisplayItem, DrawPath, Scene};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use pathfinder_color::ColorU;
+use pathfinder_content::effects::Filter;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::{ColorStop, Gradient, GradientGeometry};
+use pathfinder_content::mesh::{CoonsPatch, GradientMesh};
+use pathfinder_content::outline::{Contour, Outline, PointFlags};
+use pathfinder_content::pattern::{Image, Pattern, PatternSource};
+use pathfinder_content::shadow::BlurredRoundedRect;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::Vector2F;
+use pathfinder_resources::ResourceLoader;
+use pathfinder_simd::default::F32x2;
+use std::io::{self, Error, ErrorKind, Read, Write};
+use std::sync::Arc;
+
+const MAGIC: &[u8; 8] = b"PFSCENE\0";
+const VERSION: u32 = 5;
+
+impl Scene {
+    /// Writes this scene in the archive format.
+    ///
+    /// Scenes that draw into render targets can't be archived, since their paints refer to
+    /// textures that only exist at render time.
+    pub fn write<W>(&self, writer: &mut W) -> io::Result<()> where W: Write {
+        let has_render_targets = self.display_list.iter().any(|item| {
+            match *item {
+                DisplayItem::DrawPaths { .. } => false,
+                DisplayItem::PushRenderTarget(_) |
+                DisplayItem::PopRenderTarget |
+                DisplayItem::ResolveBackdrop { .. } => true,
+            }
+        });
+        if has_render_targets {
+            return Err(Error::new(ErrorKind::InvalidInput,
+                                  "scenes with render targets can't be archived"));
+        }
+
+        writer.write_all(MAGIC)?;
+        writer.write_u32::<LittleEndian>(VERSION)?;
+        write_rect(writer, self.view_box())?;
+        write_rect(writer, self.bounds())?;
+
+        writer.write_u32::<LittleEndian>(self.paint_count() as u32)?;
+        for paint_index in 0..self.paint_count() {
+            write_paint(writer, self.get_paint(PaintId(paint_index as u16)))?;
+        }
+
+        writer.write_u32::<LittleEndian>(self.clip_paths.len() as u32)?;
+        for clip_path in &self.clip_paths {
+            write_outline(writer, clip_path.outline())?;
+            write_fill_rule(writer, clip_path.fill_rule())?;
+            writer.write_u8(clip_path.is_inverted() as u8)?;
+            write_string(writer, clip_path.name())?;
+        }
+
+        writer.write_u32::<LittleEndian>(self.paths.len() as u32)?;
+        for path in &self.paths {
+            write_outline(writer, path.outline())?;
+            writer.write_u16::<LittleEndian>(path.paint().0)?;
+            writer.write_u32::<LittleEndian>(path.contour_paints().len() as u32)?;
+            for contour_paint in path.contour_paints() {
+                writer.write_u32::<LittleEndian>(contour_paint.contours.start)?;
+                writer.write_u32::<LittleEndian>(contour_paint.contours.end)?;
+                writer.write_u16::<LittleEndian>(contour_paint.paint.0)?;
+            }
+            match path.clip_path() {
+                None => writer.write_u8(0)?,
+                Some(ClipPathId(clip_path_index)) => {
+                    writer.write_u8(1)?;
+                    writer.write_u32::<LittleEndian>(clip_path_index)?;
+                }
+            }
+            write_fill_rule(writer, path.fill_rule())?;
+            let blend_mode_index = BLEND_MODES.iter()
+                                              .position(|&mode| mode == path.blend_mode())
+                                              .unwrap();
+            writer.write_u8(blend_mode_index as u8)?;
+            write_string(writer, path.name())?;
+            writer.write_u8(path.is_visible() as u8)?;
+            writer.write_u8(path.is_animated() as u8)?;
+            match path.hit_test_id() {
+                None => writer.write_u8(0)?,
+                Some(hit_test_id) => {
+                    writer.write_u8(1)?;
+                    writer.write_u32::<LittleEndian>(hit_test_id)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a scene written with `write()`.
+    pub fn read<R>(reader: &mut R) -> io::Result<Scene> where R: Read {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a Pathfinder scene archive"));
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        // Version 1 archives are the same, minus contour paints, version 2 archives are the same,
+        // minus the midpoints of gradient stops, version 3 archives are the same, minus inverted
+        // clip paths, and version 4 archives are the same, minus animated paths.
+        if version == 0 || version > VERSION {
+            return Err(invalid_data("unsupported scene archive version"));
+        }
+
+        let mut scene = Scene::new();
+        let view_box = read_rect(reader)?;
+        let bounds = read_rect(reader)?;
+
+        // Identical paints are merged on the way in, so map the archived IDs to the new ones.
+        let paint_count = reader.read_u32::<LittleEndian>()?;
+        let mut paint_mapping = vec![];
+        for _ in 0..paint_count {
+            paint_mapping.push(scene.push_paint(&read_paint(reader, version)?));
+        }
+
+        let clip_path_count = reader.read_u32::<LittleEndian>()?;
+        for _ in 0..clip_path_count {
+            let mut clip_path = ClipPath::new(read_outline(reader)?);
+            clip_path.set_fill_rule(read_fill_rule(reader)?);
+            if version >= 4 {
+                clip_path.set_inverted(reader.read_u8()? != 0);
+            }
+            clip_path.set_name(read_string(reader)?);
+            scene.push_clip_path(clip_path);
+        }
+
+        let path_count = reader.read_u32::<LittleEndian>()?;
+        for _ in 0..path_count {
+            let outline = read_outline(reader)?;
+            let paint = read_paint_id(reader, &paint_mapping)?;
+            let mut path = DrawPath::new(outline, paint);
+            if version >= 2 {
+                let contour_paint_count = reader.read_u32::<LittleEndian>()?;
+                let mut contour_paints = vec![];
+                for _ in 0..contour_paint_count {
+                    let start = reader.read_u32::<LittleEndian>()?;
+                    let end = reader.read_u32::<LittleEndian>()?;
+                    let paint = read_paint_id(reader, &paint_mapping)?;
+                    contour_paints.push(ContourPaint { contours: start..end, paint });
+                }
+                path.set_contour_paints(contour_paints);
+            }
+            match reader.read_u8()? {
+                0 => {}
+                1 => {
+                    let clip_path_index = reader.read_u32::<LittleEndian>()?;
+                    if clip_path_index >= clip_path_count {
+                        return Err(invalid_data("invalid clip path ID"));
+                    }
+                    path.set_clip_path(Some(ClipPathId(clip_path_index)));
+                }
+                _ => return Err(invalid_data("invalid clip path")),
+            }
+            path.set_fill_rule(read_fill_rule(reader)?);
+            match BLEND_MODES.get(reader.read_u8()? as usize) {
+                Some(&blend_mode) => path.set_blend_mode(blend_mode),
+                None => return Err(invalid_data("invalid blend mode")),
+            }
+            path.set_name(read_string(reader)?);
+            path.set_visible(reader.read_u8()? != 0);
+            if version >= 5 {
+                path.set_animated(reader.read_u8()? != 0);
+            }
+            match reader.read_u8()? {
+                0 => {}
+                1 => path.set_hit_test_id(Some(reader.read_u32::<LittleEndian>()?)),
+                _ => return Err(invalid_data("invalid hit test ID")),
+            }
+            scene.push_path(path);
+        }
+
+        scene.set_view_box(view_box);
+        scene.set_bounds(bounds);
+        Ok(scene)
+    }
+
+    /// Reads a scene archived at `path` in `resources`.
+    ///
+    /// The archive is memory-mapped if the loader supports that, so large archives are never
+    /// copied onto the heap whole; only the scene built from them is.
+    pub fn load(resources: &dyn ResourceLoader, path: &str) -> io::Result<Scene> {
+        let archive = resources.map(path)?;
+        Scene::read(&mut &archive[..])
+    }
+}
+
+// Paints
+
+fn write_paint<W>(writer: &mut W, paint: &Paint) -> io::Result<()> where W: Write {
+    capture::write_color_u(writer, paint.base_color())?;
+    let overlay = match *paint.overlay() {
+        None => return writer.write_u8(0),
+        Some(ref overlay) => overlay,
+    };
+
+    writer.write_u8(1)?;
+    writer.write_u8(match overlay.composite_op() {
+        PaintCompositeOp::SrcIn => 0,
+        PaintCompositeOp::DestIn => 1,
+    })?;
+    match *overlay.contents() {
+        PaintContents::Gradient(ref gradient) => {
+            writer.write_u8(0)?;
+            write_gradient(writer, gradient)
+        }
+        PaintContents::Pattern(ref pattern) => {
+            writer.write_u8(1)?;
+            write_pattern(writer, pattern)
+        }
+        PaintContents::Mesh(ref mesh) => {
+            writer.write_u8(2)?;
+            write_mesh(writer, mesh)
+        }
+        PaintContents::BlurredRoundedRect(ref shadow) => {
+            writer.write_u8(3)?;
+            write_blurred_rounded_rect(writer, shadow)
+        }
+    }
+}
+
+fn read_paint<R>(reader: &mut R, version: u32) -> io::Result<Paint> where R: Read {
+    let base_color = capture::read_color_u(reader)?;
+    if reader.read_u8()? == 0 {
+        return Ok(Paint::from_color(base_color));
+    }
+
+    let composite_op = match reader.read_u8()? {
+        0 => PaintCompositeOp::SrcIn,
+        1 => PaintCompositeOp::DestIn,
+        _ => return Err(invalid_data("invalid paint composite op")),
+    };
+    let mut paint = match reader.read_u8()? {
+        0 => Paint::from_gradient(read_gradient(reader, version)?),
+        1 => Paint::from_pattern(read_pattern(reader)?),
+        2 => Paint::from_mesh(read_mesh(reader)?),
+        3 => Paint::from_blurred_rounded_rect(read_blurred_rounded_rect(reader)?, base_color),
+        _ => return Err(invalid_data("invalid paint overlay")),
+    };
+    paint.set_base_color(base_color);
+    if let Some(ref mut overlay) = *paint.overlay_mut() {
+        overlay.set_composite_op(composite_op);
+    }
+    Ok(paint)
+}
+
+// Paints are merged on the way in, so archived paint IDs go through `paint_mapping`.
+fn read_paint_id<R>(reader: &mut R, paint_mapping: &[PaintId]) -> io::Result<PaintId>
+                    where R: Read {
+    match paint_mapping.get(reader.read_u16::<LittleEndian>()? as usize) {
+        Some(&paint) => Ok(paint),
+        None => Err(invalid_data("invalid paint ID")),
+    }
+}
+
+fn write_gradient<W>(writer: &mut W, gradient: &Gradient) -> io::Result<()> where W: Write {
+    match gradient.geometry {
+        GradientGeometry::Linear(line) => {
+            writer.write_u8(0)?;
+            write_line_segment(writer, line)?;
+        }
+        GradientGeometry::Radial { line, radii, ref transform } => {
+            writer.write_u8(1)?;
+            write_line_segment(writer, line)?;
+            writer.write_f32::<LittleEndian>(radii[0])?;
+            writer.write_f32::<LittleEndian>(radii[1])?;
+            capture::write_transform_2f(writer, transform)?;
+        }
+    }
+
+    writer.write_u32::<LittleEndian>(gradient.stops().len() as u32)?;
+    for stop in gradient.stops() {
+        writer.write_f32::<LittleEndian>(stop.offset)?;
+        capture::write_color_u(writer, stop.color)?;
+        writer.write_f32::<LittleEndian>(stop.midpoint)?;
+    }
+    Ok(())
+}
+
+fn read_gradient<R>(reader: &mut R, version: u32) -> io::Result<Gradient> where R: Read {
+    let geometry = match reader.read_u8()? {
+        0 => GradientGeometry::Linear(read_line_segment(reader)?),
+        1 => {
+            let line = read_line_segment(reader)?;
+            let radius_0 = reader.read_f32::<LittleEndian>()?;
+            let radius_1 = reader.read_f32::<LittleEndian>()?;
+            let transform = capture::read_transform_2f(reader)?;
+            GradientGeometry::Radial { line, radii: F32x2::new(radius_0, radius_1), transform }
+        }
+        _ => return Err(invalid_data("invalid gradient geometry")),
+    };
+
+    let mut gradient = Gradient::linear(LineSegment2F::default());
+    gradient.geometry = geometry;
+    let stop_count = reader.read_u32::<LittleEndian>()?;
+    for _ in 0..stop_count {
+        let offset = reader.read_f32::<LittleEndian>()?;
+        let color = capture::read_color_u(reader)?;
+        let midpoint = if version >= 3 { reader.read_f32::<LittleEndian>()? } else { 0.5 };
+        gradient.add(ColorStop::with_midpoint(color, offset, midpoint));
+    }
+    Ok(gradient)
+}
+
+fn write_pattern<W>(writer: &mut W, pattern: &Pattern) -> io::Result<()> where W: Write {
+    let image = match *pattern.source() {
+        PatternSource::Image(ref image) => image,
+        PatternSource::RenderTarget { .. } => {
+            return Err(Error::new(ErrorKind::InvalidInput,
+                                  "render target patterns can't be archived"));
+        }
+    };
+
+    capture::write_vector_2i(writer, image.size())?;
+    for &pixel in image.pixels().iter() {
+        capture::write_color_u(writer, pixel)?;
+    }
+    capture::write_transform_2f(writer, &pattern.transform())?;
+    match pattern.filter() {
+        None => writer.write_u8(0)?,
+        Some(filter) => {
+            writer.write_u8(1)?;
+            capture::write_filter(writer, &Filter::PatternFilter(filter))?;
+        }
+    }
+    writer.write_u8(pattern.repeat_x() as u8 |
+                    (pattern.repeat_y() as u8) << 1 |
+                    (pattern.smoothing_enabled() as u8) << 2)
+}
+
+fn read_pattern<R>(reader: &mut R) -> io::Result<Pattern> where R: Read {
+    let size = capture::read_vector_2i(reader)?;
+    if size.x() < 0 || size.y() < 0 {
+        return Err(invalid_data("invalid image size"));
+    }
+    let pixel_count = size.x() as usize * size.y() as usize;
+    let mut pixels = Vec::with_capacity(pixel_count.min(capture::MAX_PREALLOCATED_ENTRIES));
+    for _ in 0..pixel_count {
+        pixels.push(capture::read_color_u(reader)?);
+    }
+
+    let mut pattern = Pattern::from_image(Image::new(size, Arc::new(pixels)));
+    pattern.apply_transform(capture::read_transform_2f(reader)?);
+    match reader.read_u8()? {
+        0 => {}
+        1 => {
+            match capture::read_filter(reader)? {
+                Filter::PatternFilter(filter) => pattern.set_filter(Some(filter)),
+                _ => return Err(invalid_data("invalid pattern filter")),
+            }
+        }
+        _ => return Err(invalid_data("invalid pattern filter")),
+    }
+    let flags = reader.read_u8()?;
+    pattern.set_repeat_x(flags & 0x01 != 0);
+    pattern.set_repeat_y(flags & 0x02 != 0);
+    pattern.set_smoothing_enabled(flags & 0x04 != 0);
+    Ok(pattern)
+}
+
+fn write_blurred_rounded_rect<W>(writer: &mut W, shadow: &BlurredRoundedRect) -> io::Result<()>
+                                 where W: Write {
+    capture::write_vector_2f(writer, shadow.rect.origin())?;
+    capture::write_vector_2f(writer, shadow.rect.lower_right())?;
+    writer.write_f32::<LittleEndian>(shadow.corner_radius)?;
+    writer.write_f32::<LittleEndian>(shadow.sigma)?;
+    capture::write_transform_2f(writer, &shadow.transform)
+}
+
+fn read_blurred_rounded_rect<R>(reader: &mut R) -> io::Result<BlurredRoundedRect> where R: Read {
+    let origin = capture::read_vector_2f(reader)?;
+    let lower_right = capture::read_vector_2f(reader)?;
+    let corner_radius = reader.read_f32::<LittleEndian>()?;
+    let sigma = reader.read_f32::<LittleEndian>()?;
+    let mut shadow = BlurredRoundedRect::new(RectF::from_points(origin, lower_right),
+                                             corner_radius,
+                                             sigma);
+    shadow.transform = capture::read_transform_2f(reader)?;
+    Ok(shadow)
+}
+
+fn write_mesh<W>(writer: &mut W, mesh: &GradientMesh) -> io::Result<()> where W: Write {
+    writer.write_u32::<LittleEndian>(mesh.patches().len() as u32)?;
+    for patch in mesh.patches() {
+        for &point in &patch.points {
+            capture::write_vector_2f(writer, point)?;
+        }
+        for &color in &patch.colors {
+            capture::write_color_u(writer, color)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_mesh<R>(reader: &mut R) -> io::Result<GradientMesh> where R: Read {
+    let mut mesh = GradientMesh::new();
+    let patch_count = reader.read_u32::<LittleEndian>()?;
+    for _ in 0..patch_count {
+        let mut points = [Vector2F::zero(); 12];
+        for point in &mut points {
+            *point = capture::read_vector_2f(reader)?;
+        }
+        let mut colors = [ColorU::transparent_black(); 4];
+        for color in &mut colors {
+            *color = capture::read_color_u(reader)?;
+        }
+        mesh.push_patch(CoonsPatch { points, colors });
+    }
+    Ok(mesh)
+}
+
+// Geometry
+
+fn write_outline<W>(writer: &mut W, outline: &Outline) -> io::Result<()> where W: Write {
+    writer.write_u32::<LittleEndian>(outline.contours().len() as u32)?;
+    for contour in outline.contours() {
+        writer.write_u32::<LittleEndian>(contour.len())?;
+        writer.write_u8(contour.is_closed() as u8)?;
+        for point_index in 0..contour.len() {
+            capture::write_vector_2f(writer, contour.position_of(point_index))?;
+            writer.write_u8(contour.flags_of(point_index).bits())?;
+        }
+    }
+    Ok(())
+}
+
+fn read_outline<R>(reader: &mut R) -> io::Result<Outline> where R: Read {
+    let mut outline = Outline::new();
+    let contour_count = reader.read_u32::<LittleEndian>()?;
+    for _ in 0..contour_count {
+        let point_count = reader.read_u32::<LittleEndian>()?;
+        let closed = reader.read_u8()? != 0;
+        let mut contour = Contour::new();
+        for _ in 0..point_count {
+            let position = capture::read_vector_2f(reader)?;
+            if position.x().is_nan() || position.y().is_nan() {
+                return Err(invalid_data("invalid point"));
+            }
+            let flags = PointFlags::from_bits_truncate(reader.read_u8()?);
+            contour.push_point(position, flags, true);
+        }
+        if closed {
+            contour.close();
+        }
+        outline.push_contour(contour);
+    }
+    Ok(outline)
+}
+
+fn write_line_segment<W>(writer: &mut W, line: LineSegment2F) -> io::Result<()> where W: Write {
+    capture::write_vector_2f(writer, line.from())?;
+    capture::write_vector_2f(writer, line.to())
+}
+
+fn read_line_segment<R>(reader: &mut R) -> io::Result<LineSegment2F> where R: Read {
+    let from = capture::read_vector_2f(reader)?;
+    let to = capture::read_vector_2f(reader)?;
+    Ok(LineSegment2F::new(from, to))
+}
+
+fn write_rect<W>(writer: &mut W, rect: RectF) -> io::Result<()> where W: Write {
+    capture::write_vector_2f(writer, rect.origin())?;
+    capture::write_vector_2f(writer, rect.size())
+}
+
+fn read_rect<R>(reader: &mut R) -> io::Result<RectF> where R: Read {
+    let origin = capture::read_vector_2f(reader)?;
+    let size = capture::read_vector_2f(reader)?;
+    Ok(RectF::new(origin, size))
+}
+
+// Primitives
+
+fn write_fill_rule<W>(writer: &mut W, fill_rule: FillRule) -> io::Result<()> where W: Write {
+    match fill_rule {
+        FillRule::Winding => writer.write_u8(0),
+        FillRule::EvenOdd => writer.write_u8(1),
+        FillRule::WindingAtLeast(threshold) => {
+            writer.write_u8(2)?;
+            writer.write_u8(threshold)
+        }
+    }
+}
+
+fn read_fill_rule<R>(reader: &mut R) -> io::Result<FillRule> where R: Read {
+    match reader.read_u8()? {
+        0 => Ok(FillRule::Winding),
+        1 => Ok(FillRule::EvenOdd),
+        2 => Ok(FillRule::WindingAtLeast(reader.read_u8()?)),
+        _ => Err(invalid_data("invalid fill rule")),
+    }
+}
+
+fn write_string<W>(writer: &mut W, string: &str) -> io::Result<()> where W: Write {
+    writer.write_u32::<LittleEndian>(string.len() as u32)?;
+    writer.write_all(string.as_bytes())
+}
+
+fn read_string<R>(reader: &mut R) -> io::Result<String> where R: Read {
+    let length = reader.read_u32::<LittleEndian>()?;
+    let mut bytes = vec![];
+    reader.take(length as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != length as usize {
+        return Err(Error::new(ErrorKind::UnexpectedEof, "truncated string"));
+    }
+    String::from_utf8(bytes).map_err(|_| invalid_data("invalid UTF-8 in name"))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::paint::Paint;
+    use crate::scene::{ClipPath, ContourPaint, DrawPath, Scene};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::effects::BlendMode;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::gradient::{ColorStop, Gradient};
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_content::pattern::{Image, Pattern};
+    use pathfinder_content::shadow::BlurredRoundedRect;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{vec2f, vec2i};
+    use pathfinder_resources::fs::{FilesystemResourceLoader, MappingResourceLoader};
+    use pathfinder_simd::default::F32x2;
+    use std::env;
+    use std::fs;
+    use std::process;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_round_trip() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 100.0)));
+
+        let mut gradient = Gradient::radial(vec2f(50.0, 50.0), F32x2::new(0.0, 40.0));
+        gradient.add_color_stop(ColorU::white(), 0.0);
+        gradient.add(ColorStop::with_midpoint(ColorU::new(255, 0, 0, 128), 1.0, 0.3));
+        let gradient_paint = scene.push_paint(&Paint::from_gradient(gradient));
+
+        let pixels = vec![ColorU::black(), ColorU::white(), ColorU::white(), ColorU::black()];
+        let mut pattern = Pattern::from_image(Image::new(vec2i(2, 2), Arc::new(pixels)));
+        pattern.apply_transform(Transform2F::from_scale(8.0));
+        pattern.set_repeat_x(true);
+        let pattern_paint = scene.push_paint(&Paint::from_pattern(pattern));
+
+        let mut clip_path = ClipPath::new(Outline::from_rect(RectF::new(vec2f(10.0, 10.0),
+                                                                        vec2f(80.0, 80.0))));
+        clip_path.set_fill_rule(FillRule::EvenOdd);
+        clip_path.set_inverted(true);
+        let clip_path = scene.push_clip_path(clip_path);
+
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_quadratic(vec2f(50.0, -20.0), vec2f(100.0, 0.0));
+        contour.push_cubic(vec2f(120.0, 30.0), vec2f(80.0, 70.0), vec2f(100.0, 100.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        let mut path = DrawPath::new(outline, gradient_paint);
+        path.set_clip_path(Some(clip_path));
+        path.set_blend_mode(BlendMode::Multiply);
+        path.set_name("curves".to_owned());
+        path.set_hit_test_id(Some(42));
+        scene.push_path(path);
+
+        let mut outline = Outline::from_rect(RectF::new(vec2f(20.0, 20.0), vec2f(10.0, 10.0)));
+        let inner_outline = Outline::from_rect(RectF::new(vec2f(24.0, 24.0), vec2f(2.0, 2.0)));
+        outline.push_contour(inner_outline.contours()[0].clone());
+        let mut path = DrawPath::new(outline, pattern_paint);
+        path.set_contour_paints(vec![
+            ContourPaint { contours: 0..1, paint: pattern_paint },
+            ContourPaint { contours: 1..2, paint: gradient_paint },
+        ]);
+        path.set_fill_rule(FillRule::WindingAtLeast(2));
+        path.set_visible(false);
+        path.set_animated(true);
+        scene.push_path(path);
+
+        let mut shadow = BlurredRoundedRect::new(RectF::new(vec2f(30.0, 30.0),
+                                                            vec2f(40.0, 20.0)),
+                                                 6.0,
+                                                 3.0);
+        shadow.apply_transform(Transform2F::from_rotation(0.25));
+        let shadow_paint = Paint::from_blurred_rounded_rect(shadow, ColorU::new(0, 0, 0, 96));
+        let shadow_paint = scene.push_paint(&shadow_paint);
+        scene.push_path(DrawPath::new(Outline::from_rect(shadow.bounds()), shadow_paint));
+
+        let mut bytes = vec![];
+        scene.write(&mut bytes).unwrap();
+        let read_scene = Scene::read(&mut &bytes[..]).unwrap();
+
+        assert_eq!(read_scene.view_box(), scene.view_box());
+        assert_eq!(read_scene.bounds(), scene.bounds());
+        assert_eq!(read_scene.paint_count(), scene.paint_count());
+        assert_eq!(read_scene.path_count(), scene.path_count());
+        for path_index in 0..scene.path_count() {
+            let (path, read_path) = (scene.get_path(path_index), read_scene.get_path(path_index));
+            assert_eq!(format!("{:?}", read_path), format!("{:?}", path));
+            assert_eq!(read_scene.get_paint(read_path.paint()), scene.get_paint(path.paint()));
+        }
+        assert_eq!(read_scene.clip_paths[0].name(), scene.clip_paths[0].name());
+        assert_eq!(read_scene.clip_paths[0].fill_rule(), scene.clip_paths[0].fill_rule());
+        assert!(read_scene.clip_paths[0].is_inverted());
+
+        // Re-archiving gives back the same bytes.
+        let mut rewritten_bytes = vec![];
+        read_scene.write(&mut rewritten_bytes).unwrap();
+        assert_eq!(rewritten_bytes, bytes);
+    }
+
+    #[test]
+    fn test_load_maps_archive() {
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let outline = Outline::from_rect(RectF::new(vec2f(1.0, 2.0), vec2f(3.0, 4.0)));
+        scene.push_path(DrawPath::new(outline, paint));
+
+        let directory = env::temp_dir().join(format!("pathfinder-archive-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let mut bytes = vec![];
+        scene.write(&mut bytes).unwrap();
+        fs::write(directory.join("scene.pfscene"), &bytes).unwrap();
+
+        // Nothing else touches this directory while the test runs.
+        let resources = unsafe {
+            MappingResourceLoader::new(FilesystemResourceLoader { directory: directory.clone() })
+        };
+        let loaded_scene = Scene::load(&resources, "scene.pfscene").unwrap();
+        assert_eq!(format!("{:?}", loaded_scene.get_path(0)), format!("{:?}", scene.get_path(0)));
+        assert!(Scene::load(&resources, "missing.pfscene").is_err());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_garbage() {
+        assert!(Scene::read(&mut &b"PFCAPTUR\x01\0\0\0"[..]).is_err());
+        assert!(Scene::read(&mut &b"PFSCENE\0\x01\0\0\0"[..]).is_err());
+    }
+}
@@ -10,29 +10,36 @@
 
 //! Packs data onto the GPU.
 
+use crate::animation::{AnimatedPathLayout, MAX_ANIMATED_PATHS};
 use crate::concurrent::executor::Executor;
 use crate::gpu::renderer::{BlendModeExt, MASK_TILES_ACROSS, MASK_TILES_DOWN};
 use crate::gpu_data::{AlphaTileId, Clip, ClipBatch, ClipBatchKey, ClipBatchKind, Fill};
 use crate::gpu_data::{FillBatchEntry, RenderCommand, TILE_CTRL_MASK_0_SHIFT};
 use crate::gpu_data::{TILE_CTRL_MASK_EVEN_ODD, TILE_CTRL_MASK_WINDING, Tile, TileBatch};
+use crate::gpu_data::{TILE_CTRL_MASK_THRESHOLD_0_SHIFT, TILE_CTRL_MASK_WINDING_THRESHOLD};
 use crate::gpu_data::{TileBatchTexture, TileObjectPrimitive};
 use crate::options::{PreparedBuildOptions, PreparedRenderTransform, RenderCommandListener};
-use crate::paint::{PaintInfo, PaintMetadata};
-use crate::scene::{DisplayItem, Scene};
-use crate::tile_map::DenseTileMap;
+use crate::paint::{PaintId, PaintInfo, PaintMetadata};
+use crate::scene::{DisplayItem, DrawPath, PathId, Scene, SceneStats};
+use crate::tile_data::{ClipPathTileData, PathTileData};
+use crate::tile_map::{DenseTileMap, TileMap};
 use crate::tiler::Tiler;
-use crate::tiles::{self, DrawTilingPathInfo, PackedTile, TILE_HEIGHT, TILE_WIDTH, TilingPathInfo};
+use crate::tiles::{self, DrawTilingPathInfo, PackedTile, TileSize, TilingPathInfo};
 use crate::z_buffer::{DepthMetadata, ZBuffer};
-use pathfinder_content::effects::{BlendMode, Filter};
+use pathfinder_content::effects::{BlendMode, Filter, PatternFilter};
 use pathfinder_content::fill::FillRule;
+use pathfinder_content::outline::Outline;
 use pathfinder_content::render_target::RenderTargetId;
 use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU4, LineSegmentU8};
-use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{Vector2I, vec2i};
 use pathfinder_gpu::TextureSamplingFlags;
 use pathfinder_simd::default::{F32x4, I32x4};
-use std::sync::atomic::AtomicUsize;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use instant::Instant;
 use std::u32;
 
@@ -41,15 +48,18 @@ pub(crate) const ALPHA_TILES_PER_LEVEL: usize = 1 << (32 - ALPHA_TILE_LEVEL_COUN
 
 pub(crate) struct SceneBuilder<'a, 'b> {
     scene: &'a mut Scene,
-    built_options: &'b PreparedBuildOptions,
+    pub(crate) built_options: &'b PreparedBuildOptions,
     next_alpha_tile_indices: [AtomicUsize; ALPHA_TILE_LEVEL_COUNT],
     pub(crate) listener: Box<dyn RenderCommandListener + 'a>,
+    fill_count: AtomicUsize,
+    stats: SceneStats,
 }
 
 #[derive(Debug)]
 pub(crate) struct ObjectBuilder {
     pub built_path: BuiltPath,
     /// During tiling, this stores the sum of backdrops for tile columns above the viewport.
+    /// Sparse tile maps store these sums themselves, so this is empty for them.
     pub current_backdrops: Vec<i8>,
     pub fills: Vec<FillBatchEntry>,
     pub bounds: RectF,
@@ -58,11 +68,13 @@ pub(crate) struct ObjectBuilder {
 #[derive(Debug)]
 struct BuiltDrawPath {
     path: BuiltPath,
+    paint_id: PaintId,
     blend_mode: BlendMode,
     filter: Filter,
     color_texture: Option<TileBatchTexture>,
     sampling_flags_1: TextureSamplingFlags,
     mask_0_fill_rule: FillRule,
+    transform_slot: u8,
 }
 
 #[derive(Debug)]
@@ -71,8 +83,10 @@ pub(crate) struct BuiltPath {
     pub empty_tiles: Vec<BuiltTile>,
     pub single_mask_tiles: Vec<BuiltTile>,
     pub clip_tiles: Vec<BuiltClip>,
-    pub tiles: DenseTileMap<TileObjectPrimitive>,
+    pub tiles: TileMap<TileObjectPrimitive>,
     pub fill_rule: FillRule,
+    /// For clip paths, whether the paths they clip are kept outside of them instead of inside.
+    pub clip_inverted: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -110,10 +124,12 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
             built_options,
             next_alpha_tile_indices: [AtomicUsize::new(0), AtomicUsize::new(0)],
             listener,
+            fill_count: AtomicUsize::new(0),
+            stats: SceneStats::default(),
         }
     }
 
-    pub fn build<E>(&mut self, executor: &E) where E: Executor {
+    pub fn build<E>(&mut self, executor: &E) -> SceneStats where E: Executor {
         let start_time = Instant::now();
 
         // Send the start rendering command.
@@ -129,6 +145,7 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
             bounding_quad,
             path_count: total_path_count,
             needs_readable_framebuffer,
+            tile_size: self.built_options.tile_size,
         });
 
         let render_transform = match self.built_options.transform {
@@ -146,40 +163,100 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
             self.listener.send(render_command);
         }
 
+        let (transform_slots, animated_path_layout) = self.assign_transform_slots();
+        let mut clips_animated_path = vec![false; clip_path_count];
+        for (path, &transform_slot) in self.scene.paths.iter().zip(transform_slots.iter()) {
+            if let Some(clip_path_id) = path.clip_path() {
+                clips_animated_path[clip_path_id.0 as usize] |= transform_slot != 0;
+            }
+        }
+        self.listener.send(RenderCommand::DeclareAnimatedPaths(Arc::new(animated_path_layout)));
+
+        if let Some(ref build_handle) = self.built_options.build_handle {
+            build_handle.begin(total_path_count);
+        }
+        if let Some(ref recorder) = self.built_options.tile_data_recorder {
+            recorder.begin(self.built_options.tile_size, clip_path_count, draw_path_count);
+        }
+
         let effective_view_box = self.scene.effective_view_box(self.built_options);
 
+        // If the build is cancelled partway through, the paths that didn't get built come back as
+        // `None`. Since cancellation is permanent, once we've checked that the build hasn't been
+        // cancelled, all of the paths are guaranteed to be present.
         let built_clip_paths = executor.build_vector(clip_path_count, |path_index| {
-            self.build_clip_path(PathBuildParams {
+            if self.built_options.is_cancelled() {
+                return None;
+            }
+            Some(self.build_clip_path(PathBuildParams {
                 path_index,
                 view_box: effective_view_box,
+                animated: clips_animated_path[path_index],
                 built_options: &self.built_options,
                 scene: &self.scene,
-            })
+            }))
         });
+        let built_clip_paths: Vec<BuiltPath> = match built_clip_paths.into_iter().collect() {
+            Some(built_clip_paths) if !self.built_options.is_cancelled() => built_clip_paths,
+            _ => return self.finish_cancelled_build(start_time),
+        };
 
         let built_draw_paths = executor.build_vector(draw_path_count, |path_index| {
-            self.build_draw_path(DrawPathBuildParams {
+            if self.built_options.is_cancelled() {
+                return None;
+            }
+            Some(self.build_draw_path(DrawPathBuildParams {
                 path_build_params: PathBuildParams {
                     path_index,
                     view_box: effective_view_box,
+                    animated: transform_slots[path_index] != 0,
                     built_options: &self.built_options,
                     scene: &self.scene,
                 },
                 paint_metadata: &paint_metadata,
                 built_clip_paths: &built_clip_paths,
-            })
+                transform_slot: transform_slots[path_index],
+            }))
         });
+        let built_draw_paths: Vec<Vec<BuiltDrawPath>> =
+                match built_draw_paths.into_iter().collect() {
+            Some(built_draw_paths) if !self.built_options.is_cancelled() => built_draw_paths,
+            _ => return self.finish_cancelled_build(start_time),
+        };
+
+        if let Some(ref recorder) = self.built_options.tile_data_recorder {
+            recorder.finish();
+        }
 
         self.finish_building(&paint_metadata, built_draw_paths);
 
         let cpu_build_time = Instant::now() - start_time;
         self.listener.send(RenderCommand::Finish { cpu_build_time });
+
+        self.stats.draw_path_count = draw_path_count;
+        self.stats.clip_path_count = clip_path_count;
+        self.stats.fill_count = self.fill_count.load(Ordering::Relaxed);
+        self.stats.cpu_build_time = cpu_build_time;
+        self.stats
+    }
+
+    // Terminates the command stream without drawing anything. Fills that were already sent still
+    // need to be flushed so that they don't leak into the next frame.
+    fn finish_cancelled_build(&mut self, start_time: Instant) -> SceneStats {
+        debug!("scene build cancelled");
+        self.listener.send(RenderCommand::FlushFills);
+        let cpu_build_time = Instant::now() - start_time;
+        self.listener.send(RenderCommand::Finish { cpu_build_time });
+        self.stats.fill_count = self.fill_count.load(Ordering::Relaxed);
+        self.stats.cpu_build_time = cpu_build_time;
+        self.stats
     }
 
     fn build_clip_path(&self, params: PathBuildParams) -> BuiltPath {
-        let PathBuildParams { path_index, view_box, built_options, scene } = params;
+        let PathBuildParams { path_index, view_box, animated, built_options, scene } = params;
         let path_object = &scene.clip_paths[path_index];
         let outline = scene.apply_render_options(path_object.outline(), built_options);
+        let view_box = widen_view_box_if_animated(view_box, &outline, animated);
 
         let mut tiler = Tiler::new(self,
                                    &outline,
@@ -188,28 +265,101 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
                                    TilingPathInfo::Clip);
 
         tiler.generate_tiles();
+        if let Some(ref recorder) = self.built_options.tile_data_recorder {
+            let fills = tiler.object_builder.fills.clone();
+            let tile_data = ClipPathTileData::new(&tiler.object_builder.built_path, fills);
+            recorder.record_clip_path(path_index, tile_data);
+        }
         self.send_fills(tiler.object_builder.fills);
-        tiler.object_builder.built_path
+        self.path_built();
+
+        let mut built_path = tiler.object_builder.built_path;
+        built_path.clip_inverted = path_object.is_inverted();
+        built_path
     }
 
-    fn build_draw_path(&self, params: DrawPathBuildParams) -> BuiltDrawPath {
+    // Returns one built path per paint: just one, unless the path has contour paints.
+    fn build_draw_path(&self, params: DrawPathBuildParams) -> Vec<BuiltDrawPath> {
         let DrawPathBuildParams {
-            path_build_params: PathBuildParams { path_index, view_box, built_options, scene },
+            path_build_params: PathBuildParams {
+                path_index,
+                view_box,
+                animated,
+                built_options,
+                scene,
+            },
             paint_metadata,
             built_clip_paths,
+            transform_slot,
         } = params;
 
         let path_object = &scene.paths[path_index];
-        let outline = scene.apply_render_options(path_object.outline(), built_options);
+        let outline = if path_object.is_visible() {
+            scene.apply_render_options(path_object.outline(), built_options)
+        } else {
+            Outline::new()
+        };
+        let view_box = widen_view_box_if_animated(view_box, &outline, animated);
 
-        let paint_id = path_object.paint();
-        let paint_metadata = &paint_metadata[paint_id.0 as usize];
         let built_clip_path = path_object.clip_path().map(|clip_path_id| {
             &built_clip_paths[clip_path_id.0 as usize]
         });
 
+        let layer_params = DrawPathLayerParams {
+            path_object,
+            view_box,
+            paint_metadata,
+            built_clip_path,
+            transform_slot,
+        };
+        let mut tile_data = self.built_options.tile_data_recorder.as_ref().map(|_| vec![]);
+        let contour_paints = path_object.contour_paints();
+        let built_draw_paths = if contour_paints.is_empty() {
+            vec![self.build_draw_path_layer(&outline,
+                                            path_object.paint(),
+                                            &layer_params,
+                                            tile_data.as_mut())]
+        } else {
+            contour_paints.iter().map(|contour_paint| {
+                let contours = outline.contours();
+                let end = usize::min(contour_paint.contours.end as usize, contours.len());
+                let start = usize::min(contour_paint.contours.start as usize, end);
+                let mut layer_outline = Outline::new();
+                for contour in &contours[start..end] {
+                    layer_outline.push_contour(contour.clone());
+                }
+                self.build_draw_path_layer(&layer_outline,
+                                           contour_paint.paint,
+                                           &layer_params,
+                                           tile_data.as_mut())
+            }).collect()
+        };
+
+        if let (Some(recorder), Some(tile_data)) =
+                (self.built_options.tile_data_recorder.as_ref(), tile_data) {
+            recorder.record_draw_path(path_index, tile_data);
+        }
+        self.path_built();
+        built_draw_paths
+    }
+
+    fn build_draw_path_layer(&self,
+                             outline: &Outline,
+                             paint_id: PaintId,
+                             params: &DrawPathLayerParams,
+                             tile_data: Option<&mut Vec<PathTileData>>)
+                             -> BuiltDrawPath {
+        let DrawPathLayerParams {
+            path_object,
+            view_box,
+            paint_metadata,
+            built_clip_path,
+            transform_slot,
+        } = *params;
+
+        let paint_metadata = &paint_metadata[paint_id.0 as usize];
         let mut tiler = Tiler::new(self,
-                                   &outline,
+                                   outline,
                                    path_object.fill_rule(),
                                    view_box,
                                    TilingPathInfo::Draw(DrawTilingPathInfo {
@@ -218,35 +368,116 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
             blend_mode: path_object.blend_mode(),
             built_clip_path,
             fill_rule: path_object.fill_rule(),
+            transform_slot,
         }));
 
         tiler.generate_tiles();
+        if let Some(tile_data) = tile_data {
+            let fills = tiler.object_builder.fills.clone();
+            tile_data.push(PathTileData::new(&tiler.object_builder.built_path, fills));
+        }
         self.send_fills(tiler.object_builder.fills);
+
+        let filter = match paint_metadata.filter() {
+            Filter::PatternFilter(PatternFilter::Blur { .. })
+                    if !self.built_options.quality.blur_enabled => Filter::None,
+            filter => filter,
+        };
+
         BuiltDrawPath {
             path: tiler.object_builder.built_path,
+            paint_id,
             blend_mode: path_object.blend_mode(),
-            filter: paint_metadata.filter(),
+            filter,
             color_texture: paint_metadata.tile_batch_texture(),
             sampling_flags_1: TextureSamplingFlags::empty(),
             mask_0_fill_rule: path_object.fill_rule(),
+            transform_slot,
+        }
+    }
+
+    // Gives each animated path that can be moved on the GPU a transform slot. Returns the slot of
+    // every draw path, which is 0 for those that stay put, and the layout to declare.
+    fn assign_transform_slots(&self) -> (Vec<u8>, AnimatedPathLayout) {
+        let mut transform_slots = vec![0; self.scene.paths.len()];
+
+        // Perspective and subpixel AA distort device space, so tiles can't be moved within it.
+        let render_transform = match self.built_options.transform {
+            _ if self.built_options.subpixel_aa_enabled => None,
+            PreparedRenderTransform::Transform2D(transform) => Some(transform),
+            PreparedRenderTransform::None => Some(Transform2F::default()),
+            PreparedRenderTransform::Perspective { .. } => None,
+        };
+        let mut layout = AnimatedPathLayout {
+            geometry_hash: 0,
+            render_transform: render_transform.unwrap_or_default(),
+            paths: vec![],
+        };
+        if render_transform.is_none() {
+            return (transform_slots, layout);
+        }
+
+        let mut hasher = DefaultHasher::new();
+        let mut framebuffer_nesting = 0;
+        for display_item in &self.scene.display_list {
+            match *display_item {
+                DisplayItem::PushRenderTarget(_) => framebuffer_nesting += 1,
+                DisplayItem::PopRenderTarget => framebuffer_nesting -= 1,
+                DisplayItem::ResolveBackdrop { .. } => {}
+                DisplayItem::DrawPaths { start_index, end_index } => {
+                    // Paths in render targets only show up through patterns, which can't
+                    // follow them.
+                    if framebuffer_nesting > 0 {
+                        continue;
+                    }
+                    for path_index in start_index..end_index {
+                        let path = &self.scene.paths[path_index as usize];
+                        let blend_mode = path.blend_mode();
+                        if !path.is_animated() || !path.is_visible() ||
+                                blend_mode.is_destructive() ||
+                                blend_mode.needs_readable_framebuffer() ||
+                                layout.paths.len() == MAX_ANIMATED_PATHS {
+                            continue;
+                        }
+                        layout.paths.push(PathId(path_index));
+                        transform_slots[path_index as usize] = layout.paths.len() as u8;
+                        path.outline().hash(&mut hasher);
+                        if let Some(clip_path_id) = path.clip_path() {
+                            self.scene.clip_paths[clip_path_id.0 as usize].outline()
+                                                                          .hash(&mut hasher);
+                        }
+                    }
+                }
+            }
+        }
+
+        layout.geometry_hash = hasher.finish();
+        (transform_slots, layout)
+    }
+
+    fn path_built(&self) {
+        if let Some(ref build_handle) = self.built_options.build_handle {
+            build_handle.path_built();
         }
     }
 
     fn send_fills(&self, fills: Vec<FillBatchEntry>) {
         if !fills.is_empty() {
+            self.fill_count.fetch_add(fills.len(), Ordering::Relaxed);
             self.listener.send(RenderCommand::AddFills(fills));
         }
     }
 
-    fn build_clips(&self, built_draw_paths: &[BuiltDrawPath]) {
+    fn build_clips(&mut self, built_draw_paths: &[Vec<BuiltDrawPath>]) {
         let mut built_clip_tiles = vec![];
-        for built_draw_path in built_draw_paths {
+        for built_draw_path in built_draw_paths.iter().flatten() {
             for built_clip_tile in &built_draw_path.path.clip_tiles {
                 built_clip_tiles.push(*built_clip_tile);
             }
         }
 
         built_clip_tiles.sort_by_key(|built_clip_tile| built_clip_tile.key);
+        self.stats.clip_tile_count = built_clip_tiles.len();
 
         let mut batches: Vec<ClipBatch> = vec![];
         for built_clip_tile in built_clip_tiles {
@@ -261,7 +492,9 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
         }
     }
 
-    fn cull_tiles(&self, paint_metadata: &[PaintMetadata], built_draw_paths: Vec<BuiltDrawPath>)
+    fn cull_tiles(&self,
+                  paint_metadata: &[PaintMetadata],
+                  built_draw_paths: Vec<Vec<BuiltDrawPath>>)
                   -> CulledTiles {
         let mut culled_tiles = CulledTiles { display_list: vec![] };
 
@@ -297,13 +530,32 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
                     layer_z_buffers_stack.pop();
                 }
 
+                DisplayItem::ResolveBackdrop { render_target, source_rect } => {
+                    culled_tiles.display_list.push(CulledDisplayItem::ResolveBackdrop {
+                        render_target,
+                        source_rect,
+                    });
+
+                    let z_buffer = remaining_layer_z_buffers.pop().unwrap();
+                    let solid_tiles = z_buffer.build_solid_tiles(paint_metadata);
+                    for batch in solid_tiles.batches {
+                        culled_tiles.display_list.push(CulledDisplayItem::DrawTiles(batch));
+                    }
+                    *layer_z_buffers_stack.last_mut().unwrap() = z_buffer;
+                }
+
                 DisplayItem::DrawPaths {
                     start_index: start_draw_path_index,
                     end_index: end_draw_path_index,
                 } => {
-                    for draw_path_index in start_draw_path_index..end_draw_path_index {
-                        let built_draw_path = &built_draw_paths[draw_path_index as usize];
-                        let layer_z_buffer = layer_z_buffers_stack.last().unwrap();
+                    let built_draw_paths = &built_draw_paths[start_draw_path_index as usize..
+                                                             end_draw_path_index as usize];
+                    for built_draw_path in built_draw_paths.iter().flatten() {
+                        // Animated paths can be moved out from behind the paths that cover them,
+                        // so none of their tiles can be culled.
+                        let layer_z_buffer = layer_z_buffers_stack.last().filter(|_| {
+                            built_draw_path.transform_slot == 0
+                        });
                         let color_texture = built_draw_path.color_texture;
 
                         debug_assert!(built_draw_path.path.empty_tiles.is_empty() ||
@@ -346,9 +598,10 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
         culled_tiles
     }
 
-    fn build_solid_tiles(&self, built_draw_paths: &[BuiltDrawPath]) -> Vec<ZBuffer> {
+    fn build_solid_tiles(&self, built_draw_paths: &[Vec<BuiltDrawPath>]) -> Vec<ZBuffer> {
         let effective_view_box = self.scene.effective_view_box(self.built_options);
-        let mut z_buffers = vec![ZBuffer::new(effective_view_box)];
+        let tile_size = self.built_options.tile_size;
+        let mut z_buffers = vec![ZBuffer::new(effective_view_box, tile_size)];
         let mut z_buffer_index_stack = vec![0];
         let mut current_depth = 1;
 
@@ -357,19 +610,24 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
             match *display_item {
                 DisplayItem::PushRenderTarget { .. } => {
                     z_buffer_index_stack.push(z_buffers.len());
-                    z_buffers.push(ZBuffer::new(effective_view_box));
+                    z_buffers.push(ZBuffer::new(effective_view_box, tile_size));
                 }
                 DisplayItem::PopRenderTarget => {
                     z_buffer_index_stack.pop();
                 }
+                DisplayItem::ResolveBackdrop { .. } => {
+                    // Solid tiles are drawn before everything else in their Z-buffer, so paths
+                    // drawn after the backdrop is copied need a Z-buffer of their own, or they'd
+                    // show up in the copy.
+                    *z_buffer_index_stack.last_mut().unwrap() = z_buffers.len();
+                    z_buffers.push(ZBuffer::new(effective_view_box, tile_size));
+                }
                 DisplayItem::DrawPaths { start_index, end_index } => {
                     let (start_index, end_index) = (start_index as usize, end_index as usize);
                     let z_buffer = &mut z_buffers[*z_buffer_index_stack.last().unwrap()];
-                    for (path_subindex, built_draw_path) in
-                            built_draw_paths[start_index..end_index].iter().enumerate() {
-                        let path_index = (path_subindex + start_index) as u32;
-                        let path = &self.scene.paths[path_index as usize];
-                        let metadata = DepthMetadata { paint_id: path.paint() };
+                    for built_draw_path in built_draw_paths[start_index..end_index].iter()
+                                                                                   .flatten() {
+                        let metadata = DepthMetadata { paint_id: built_draw_path.paint_id };
                         match built_draw_path.path.solid_tiles {
                             SolidTiles::Regular(_) => {
                                 z_buffer.update(&[], current_depth, metadata);
@@ -390,7 +648,7 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
 
     fn add_alpha_tiles(&self,
                        culled_tiles: &mut CulledTiles,
-                       layer_z_buffer: &ZBuffer,
+                       layer_z_buffer: Option<&ZBuffer>,
                        built_alpha_tiles: &[BuiltTile],
                        current_depth: u32,
                        color_texture: Option<TileBatchTexture>,
@@ -400,8 +658,10 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
         for built_alpha_tile in built_alpha_tiles {
             // Early cull if possible.
             let alpha_tile_coords = built_alpha_tile.tile.tile_position();
-            if !layer_z_buffer.test(alpha_tile_coords, current_depth) {
-                continue;
+            if let Some(layer_z_buffer) = layer_z_buffer {
+                if !layer_z_buffer.test(alpha_tile_coords, current_depth) {
+                    continue;
+                }
             }
 
             // Find an appropriate batch if we can.
@@ -473,6 +733,13 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
         for display_item in culled_tiles.display_list {
             match display_item {
                 CulledDisplayItem::DrawTiles(batch) => {
+                    let solid_tile_count = batch.tiles
+                                                .iter()
+                                                .filter(|tile| tile.is_solid())
+                                                .count();
+                    self.stats.solid_tile_count += solid_tile_count;
+                    self.stats.alpha_tile_count += batch.tiles.len() - solid_tile_count;
+                    self.stats.tile_batch_count += 1;
                     self.listener.send(RenderCommand::DrawTiles(batch))
                 }
                 CulledDisplayItem::PushRenderTarget(render_target_id) => {
@@ -481,13 +748,19 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
                 CulledDisplayItem::PopRenderTarget => {
                     self.listener.send(RenderCommand::PopRenderTarget)
                 }
+                CulledDisplayItem::ResolveBackdrop { render_target, source_rect } => {
+                    self.listener.send(RenderCommand::ResolveBackdrop {
+                        render_target,
+                        source_rect,
+                    })
+                }
             }
         }
     }
 
     fn finish_building(&mut self,
                        paint_metadata: &[PaintMetadata],
-                       built_draw_paths: Vec<BuiltDrawPath>) {
+                       built_draw_paths: Vec<Vec<BuiltDrawPath>>) {
         self.listener.send(RenderCommand::FlushFills);
         self.build_clips(&built_draw_paths);
         let culled_tiles = self.cull_tiles(paint_metadata, built_draw_paths);
@@ -500,6 +773,11 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
             match *display_item {
                 DisplayItem::PushRenderTarget(_) => framebuffer_nesting += 1,
                 DisplayItem::PopRenderTarget => framebuffer_nesting -= 1,
+                DisplayItem::ResolveBackdrop { .. } => {
+                    if framebuffer_nesting == 0 {
+                        return true;
+                    }
+                }
                 DisplayItem::DrawPaths { start_index, end_index } => {
                     if framebuffer_nesting > 0 {
                         continue;
@@ -520,6 +798,8 @@ impl<'a, 'b> SceneBuilder<'a, 'b> {
 struct PathBuildParams<'a> {
     path_index: usize,
     view_box: RectF,
+    /// True for animated paths and the clip paths of animated paths.
+    animated: bool,
     built_options: &'a PreparedBuildOptions,
     scene: &'a Scene,
 }
@@ -528,18 +808,32 @@ struct DrawPathBuildParams<'a> {
     path_build_params: PathBuildParams<'a>,
     paint_metadata: &'a [PaintMetadata],
     built_clip_paths: &'a [BuiltPath],
+    transform_slot: u8,
+}
+
+// What each layer of a draw path has in common. Paths with contour paints have one layer per
+// paint.
+struct DrawPathLayerParams<'a> {
+    path_object: &'a DrawPath,
+    view_box: RectF,
+    paint_metadata: &'a [PaintMetadata],
+    built_clip_path: Option<&'a BuiltPath>,
+    transform_slot: u8,
 }
 
 impl BuiltPath {
     fn new(path_bounds: RectF,
            view_box_bounds: RectF,
            fill_rule: FillRule,
+           tile_size: TileSize,
+           edge_tile_estimate: usize,
            tiling_path_info: &TilingPathInfo)
            -> BuiltPath {
         let occludes = match *tiling_path_info {
             TilingPathInfo::Draw(ref draw_tiling_path_info) => {
                 draw_tiling_path_info.paint_metadata.is_opaque &&
-                    draw_tiling_path_info.blend_mode.occludes_backdrop()
+                    draw_tiling_path_info.blend_mode.occludes_backdrop() &&
+                    draw_tiling_path_info.transform_slot == 0
             }
             TilingPathInfo::Clip => true,
         };
@@ -550,6 +844,18 @@ impl BuiltPath {
             path_bounds
         };
 
+        // Clip paths are looked up tile by tile, and destructive blend modes touch every tile, so
+        // their tile maps are always dense.
+        let tile_rect = tiles::round_rect_out_to_tile_bounds(tile_map_bounds, tile_size);
+        let tiles = match *tiling_path_info {
+            TilingPathInfo::Draw(_) if !tiling_path_info.has_destructive_blend_mode() => {
+                TileMap::new(tile_rect, edge_tile_estimate)
+            }
+            TilingPathInfo::Draw(_) | TilingPathInfo::Clip => {
+                TileMap::Dense(DenseTileMap::new(tile_rect))
+            }
+        };
+
         BuiltPath {
             empty_tiles: vec![],
             single_mask_tiles: vec![],
@@ -559,8 +865,9 @@ impl BuiltPath {
             } else {
                 SolidTiles::Regular(vec![])
             },
-            tiles: DenseTileMap::new(tiles::round_rect_out_to_tile_bounds(tile_map_bounds)),
+            tiles,
             fill_rule,
+            clip_inverted: false,
         }
     }
 }
@@ -580,6 +887,7 @@ enum CulledDisplayItem {
     DrawTiles(TileBatch),
     PushRenderTarget(RenderTargetId),
     PopRenderTarget,
+    ResolveBackdrop { render_target: RenderTargetId, source_rect: RectI },
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -591,13 +899,25 @@ pub struct TileStats {
 // Utilities for built objects
 
 impl ObjectBuilder {
+    /// Creates a builder for a path whose edges are expected to pass through about
+    /// `edge_tile_estimate` tiles, which decides how its tiles are stored.
     pub(crate) fn new(path_bounds: RectF,
                       view_box_bounds: RectF,
                       fill_rule: FillRule,
+                      tile_size: TileSize,
+                      edge_tile_estimate: usize,
                       tiling_path_info: &TilingPathInfo)
                       -> ObjectBuilder {
-        let built_path = BuiltPath::new(path_bounds, view_box_bounds, fill_rule, tiling_path_info);
-        let current_backdrops = vec![0; built_path.tiles.rect.width() as usize];
+        let built_path = BuiltPath::new(path_bounds,
+                                        view_box_bounds,
+                                        fill_rule,
+                                        tile_size,
+                                        edge_tile_estimate,
+                                        tiling_path_info);
+        let current_backdrops = match built_path.tiles {
+            TileMap::Dense(ref tiles) => vec![0; tiles.rect.width() as usize],
+            TileMap::Sparse(_) => vec![],
+        };
         ObjectBuilder { built_path, bounds: path_bounds, current_backdrops, fills: vec![] }
     }
 
@@ -608,19 +928,17 @@ impl ObjectBuilder {
         debug!("add_fill({:?} ({:?}))", segment, tile_coords);
 
         // Ensure this fill is in bounds. If not, cull it.
-        if self.tile_coords_to_local_index(tile_coords).is_none() {
+        if !self.built_path.tiles.rect().contains_point(tile_coords) {
             return;
         }
 
-        debug_assert_eq!(TILE_WIDTH, TILE_HEIGHT);
-
         // Compute the upper left corner of the tile.
-        let tile_size = F32x4::splat(TILE_WIDTH as f32);
+        let tile_size = scene_builder.built_options.tile_size.size().to_f32().0.to_f32x4().xyxy();
         let tile_upper_left = tile_coords.to_f32().0.to_f32x4().xyxy() * tile_size;
 
         // Convert to 4.8 fixed point.
-        let segment = (segment.0 - tile_upper_left) * F32x4::splat(256.0);
-        let (min, max) = (F32x4::default(), F32x4::splat((TILE_WIDTH * 256 - 1) as f32));
+        let segment = (segment.0 - tile_upper_left) * 256.0;
+        let (min, max) = (F32x4::default(), tile_size * 256.0 - F32x4::splat(1.0));
         let segment = segment.clamp(min, max).to_i32x4();
         let (from_x, from_y, to_x, to_y) = (segment[0], segment[1], segment[2], segment[3]);
 
@@ -659,42 +977,38 @@ impl ObjectBuilder {
         scene_builder: &SceneBuilder,
         tile_coords: Vector2I,
     ) -> AlphaTileId {
-        let local_tile_index = self.built_path.tiles.coords_to_index_unchecked(tile_coords);
-        let alpha_tile_id = self.built_path.tiles.data[local_tile_index].alpha_tile_id;
-        if alpha_tile_id.is_valid() {
-            return alpha_tile_id;
+        let tile = self.built_path.tiles.get_mut(tile_coords).unwrap();
+        if tile.alpha_tile_id.is_valid() {
+            return tile.alpha_tile_id;
         }
 
         let alpha_tile_id = AlphaTileId::new(&scene_builder.next_alpha_tile_indices, 0);
-        self.built_path.tiles.data[local_tile_index].alpha_tile_id = alpha_tile_id;
+        tile.alpha_tile_id = alpha_tile_id;
         alpha_tile_id
     }
 
-    #[inline]
-    pub(crate) fn tile_coords_to_local_index(&self, coords: Vector2I) -> Option<u32> {
-        self.built_path.tiles.coords_to_index(coords).map(|index| index as u32)
-    }
-
-    #[inline]
-    pub(crate) fn local_tile_index_to_coords(&self, tile_index: u32) -> Vector2I {
-        self.built_path.tiles.index_to_coords(tile_index as usize)
-    }
-
     #[inline]
     pub(crate) fn adjust_alpha_tile_backdrop(&mut self, tile_coords: Vector2I, delta: i8) {
-        let tile_offset = tile_coords - self.built_path.tiles.rect.origin();
-        if tile_offset.x() < 0 || tile_offset.x() >= self.built_path.tiles.rect.width() ||
-                tile_offset.y() >= self.built_path.tiles.rect.height() {
+        let tile_rect = self.built_path.tiles.rect();
+        let tile_offset = tile_coords - tile_rect.origin();
+        if tile_offset.x() < 0 || tile_offset.x() >= tile_rect.width() ||
+                tile_offset.y() >= tile_rect.height() {
             return;
         }
 
         if tile_offset.y() < 0 {
-            self.current_backdrops[tile_offset.x() as usize] += delta;
+            match self.built_path.tiles {
+                TileMap::Dense(_) => self.current_backdrops[tile_offset.x() as usize] += delta,
+                TileMap::Sparse(ref mut tiles) => {
+                    // Sparse maps keep the backdrops of their columns in the row just above them.
+                    let coords = vec2i(tile_coords.x(), tile_rect.min_y() - 1);
+                    tiles.get_or_insert(coords).backdrop += delta;
+                }
+            }
             return;
         }
 
-        let local_tile_index = self.built_path.tiles.coords_to_index_unchecked(tile_coords);
-        self.built_path.tiles.data[local_tile_index].backdrop += delta;
+        self.built_path.tiles.get_mut(tile_coords).unwrap().backdrop += delta;
     }
 }
 
@@ -727,10 +1041,26 @@ impl<'a> PackedTile<'a> {
                 let dest_tile_page = dest_tile_id.page() as u16;
                 let dest_tile_index = dest_tile_id.tile() as u16;
 
+                // Solid draw tiles have no mask of their own to copy. They only get here when
+                // the clip is inverted, so fill the destination by copying the clip mask with a
+                // backdrop large enough to saturate every pixel.
+                let (src_page, src_index, src_backdrop) = if self.draw_tile.is_solid() {
+                    (clip_tile_page, clip_tile_index, i8::MAX)
+                } else {
+                    (draw_tile_page, draw_tile_index, draw_tile_backdrop)
+                };
+                let clip_inverted = draw_tiling_path_info.built_clip_path
+                                                         .is_some_and(|path| path.clip_inverted);
+                let clip_kind = if clip_inverted {
+                    ClipBatchKind::InvertedClip
+                } else {
+                    ClipBatchKind::Clip
+                };
+
                 clips.push(BuiltClip {
-                    clip: Clip::new(dest_tile_index, draw_tile_index, draw_tile_backdrop),
+                    clip: Clip::new(dest_tile_index, src_index, src_backdrop),
                     key: ClipBatchKey {
-                        src_page: draw_tile_page,
+                        src_page,
                         dest_page: dest_tile_page,
                         kind: ClipBatchKind::Draw,
                     },
@@ -740,7 +1070,7 @@ impl<'a> PackedTile<'a> {
                     key: ClipBatchKey {
                         src_page: clip_tile_page,
                         dest_page: dest_tile_page,
-                        kind: ClipBatchKind::Clip,
+                        kind: clip_kind,
                     },
                 });
                 tiles.push(BuiltTile {
@@ -767,7 +1097,13 @@ impl Tile {
         let mut ctrl = 0;
         match draw_tiling_path_info.fill_rule {
             FillRule::EvenOdd => ctrl |= TILE_CTRL_MASK_EVEN_ODD << TILE_CTRL_MASK_0_SHIFT,
-            FillRule::Winding => ctrl |= TILE_CTRL_MASK_WINDING << TILE_CTRL_MASK_0_SHIFT,
+            FillRule::Winding | FillRule::WindingAtLeast(0) | FillRule::WindingAtLeast(1) => {
+                ctrl |= TILE_CTRL_MASK_WINDING << TILE_CTRL_MASK_0_SHIFT
+            }
+            FillRule::WindingAtLeast(threshold) => {
+                ctrl |= TILE_CTRL_MASK_WINDING_THRESHOLD << TILE_CTRL_MASK_0_SHIFT;
+                ctrl |= (threshold as i32) << TILE_CTRL_MASK_THRESHOLD_0_SHIFT;
+            }
         }
 
         Tile {
@@ -777,7 +1113,7 @@ impl Tile {
             mask_0_v: mask_0_uv.y() as u8,
             mask_0_backdrop: draw_tile_backdrop,
             ctrl: ctrl as u16,
-            pad: 0,
+            transform_slot: draw_tiling_path_info.transform_slot,
             color: draw_tiling_path_info.paint_id.0,
         }
     }
@@ -805,6 +1141,16 @@ impl Clip {
     }
 }
 
+// Animated paths can be moved into view later, so they're tiled in full, not just the parts
+// that are in the view box.
+fn widen_view_box_if_animated(view_box: RectF, outline: &Outline, animated: bool) -> RectF {
+    if animated && !outline.contours().is_empty() {
+        view_box.union_rect(outline.bounds())
+    } else {
+        view_box
+    }
+}
+
 fn calculate_mask_uv(tile_index: u16) -> Vector2I {
     debug_assert_eq!(MASK_TILES_ACROSS, MASK_TILES_DOWN);
     let mask_u = tile_index as i32 % MASK_TILES_ACROSS as i32;
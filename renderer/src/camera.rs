@@ -0,0 +1,226 @@
+// pathfinder/renderer/src/camera.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A simple 2D camera that handles panning and zooming.
+
+use crate::options::RenderTransform;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
+
+/// The smallest zoom level a camera allows. Zooming out further, or to zero or a negative zoom,
+/// stops here, so that the world-to-screen transform stays invertible.
+pub const MIN_ZOOM: f32 = 1.0e-6;
+
+/// A 2D viewport onto a scene.
+///
+/// "World" coordinates are those of the scene. "Screen" coordinates are logical window
+/// coordinates, as mouse and touch events report them. "Device" coordinates are physical pixels,
+/// which is what the renderer draws in; they differ from screen coordinates by the device pixel
+/// ratio.
+#[derive(Clone, Copy, Debug)]
+pub struct Camera2D {
+    viewport_size: Vector2F,
+    device_pixel_ratio: f32,
+    zoom: f32,
+    translation: Vector2F,
+}
+
+impl Camera2D {
+    /// Creates a camera with an identity world-to-screen transform.
+    ///
+    /// `viewport_size` is in screen (logical) coordinates.
+    #[inline]
+    pub fn new(viewport_size: Vector2F, device_pixel_ratio: f32) -> Camera2D {
+        Camera2D { viewport_size, device_pixel_ratio, zoom: 1.0, translation: Vector2F::default() }
+    }
+
+    #[inline]
+    pub fn viewport_size(&self) -> Vector2F {
+        self.viewport_size
+    }
+
+    /// Changes the viewport size, keeping the world point at the center of the viewport fixed.
+    pub fn set_viewport_size(&mut self, new_viewport_size: Vector2F) {
+        let center = self.screen_point_to_world(self.viewport_size * 0.5);
+        self.viewport_size = new_viewport_size;
+        self.translation = new_viewport_size * 0.5 - center * self.zoom;
+    }
+
+    #[inline]
+    pub fn device_pixel_ratio(&self) -> f32 {
+        self.device_pixel_ratio
+    }
+
+    /// Changes the device pixel ratio. The on-screen appearance of the scene is unaffected.
+    #[inline]
+    pub fn set_device_pixel_ratio(&mut self, device_pixel_ratio: f32) {
+        self.device_pixel_ratio = device_pixel_ratio;
+    }
+
+    /// The number of screen units per world unit.
+    #[inline]
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Sets the zoom level, keeping the world point at the center of the viewport fixed.
+    ///
+    /// The zoom level is clamped to at least `MIN_ZOOM`.
+    #[inline]
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom_about(zoom / self.zoom, self.viewport_size * 0.5)
+    }
+
+    /// Moves the scene by the given amount in screen coordinates.
+    #[inline]
+    pub fn pan(&mut self, screen_delta: Vector2F) {
+        self.translation += screen_delta;
+    }
+
+    /// Scales the scene by `factor`, keeping the world point under `screen_point` fixed.
+    ///
+    /// This is what scroll-wheel and pinch gestures usually want. The resulting zoom level is
+    /// clamped to at least `MIN_ZOOM`.
+    pub fn zoom_about(&mut self, factor: f32, screen_point: Vector2F) {
+        let new_zoom = (self.zoom * factor).max(MIN_ZOOM);
+        let factor = new_zoom / self.zoom;
+        self.translation = screen_point + (self.translation - screen_point) * factor;
+        self.zoom = new_zoom;
+    }
+
+    /// Centers `world_bounds` in the viewport and zooms so that it fits entirely within it,
+    /// preserving its aspect ratio.
+    pub fn fit_to_bounds(&mut self, world_bounds: RectF) {
+        if world_bounds.width() <= 0.0 || world_bounds.height() <= 0.0 {
+            return;
+        }
+        self.zoom = f32::min(self.viewport_size.x() / world_bounds.width(),
+                             self.viewport_size.y() / world_bounds.height());
+        self.translation = self.viewport_size * 0.5 - world_bounds.center() * self.zoom;
+    }
+
+    /// The transform from world coordinates to screen (logical) coordinates.
+    #[inline]
+    pub fn world_to_screen(&self) -> Transform2F {
+        Transform2F::from_scale(self.zoom).translate(self.translation)
+    }
+
+    /// The transform from screen (logical) coordinates to world coordinates.
+    #[inline]
+    pub fn screen_to_world(&self) -> Transform2F {
+        self.world_to_screen().inverse()
+    }
+
+    /// The transform from world coordinates to device pixels.
+    #[inline]
+    pub fn world_to_device(&self) -> Transform2F {
+        self.world_to_screen().scale(self.device_pixel_ratio)
+    }
+
+    #[inline]
+    pub fn world_point_to_screen(&self, world_point: Vector2F) -> Vector2F {
+        self.world_to_screen() * world_point
+    }
+
+    #[inline]
+    pub fn screen_point_to_world(&self, screen_point: Vector2F) -> Vector2F {
+        self.screen_to_world() * screen_point
+    }
+
+    /// The region of the world currently visible in the viewport.
+    #[inline]
+    pub fn visible_world_bounds(&self) -> RectF {
+        self.screen_to_world() * RectF::new(Vector2F::default(), self.viewport_size)
+    }
+
+    /// The transform to hand to `BuildOptions` (and so to `SceneProxy::build()`).
+    ///
+    /// This maps world coordinates to device pixels, so the scene is rendered at full resolution
+    /// on high-DPI displays.
+    #[inline]
+    pub fn render_transform(&self) -> RenderTransform {
+        RenderTransform::Transform2D(self.world_to_device())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Camera2D, MIN_ZOOM};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::{Vector2F, vec2f};
+
+    fn assert_close(actual: Vector2F, expected: Vector2F) {
+        assert!((actual - expected).length() < 0.001, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn test_world_screen_round_trip() {
+        let mut camera = Camera2D::new(vec2f(800.0, 600.0), 2.0);
+        camera.pan(vec2f(30.0, -20.0));
+        camera.zoom_about(3.0, vec2f(100.0, 50.0));
+
+        let world_point = vec2f(12.5, -40.0);
+        let screen_point = camera.world_point_to_screen(world_point);
+        assert_close(screen_point, vec2f(100.0, 50.0) + (vec2f(-70.0, -70.0) + world_point) * 3.0);
+        assert_close(camera.screen_point_to_world(screen_point), world_point);
+        assert_close(camera.world_to_device() * world_point, screen_point * 2.0);
+
+        let visible = camera.visible_world_bounds();
+        assert_close(camera.world_point_to_screen(visible.origin()), Vector2F::zero());
+        assert_close(camera.world_point_to_screen(visible.lower_right()), vec2f(800.0, 600.0));
+    }
+
+    #[test]
+    fn test_zoom_about_keeps_point_fixed() {
+        let mut camera = Camera2D::new(vec2f(800.0, 600.0), 1.0);
+        camera.pan(vec2f(-15.0, 40.0));
+        let screen_point = vec2f(210.0, 330.0);
+        let world_point = camera.screen_point_to_world(screen_point);
+        for &factor in &[2.0, 0.25, 1.5] {
+            camera.zoom_about(factor, screen_point);
+            assert_close(camera.world_point_to_screen(world_point), screen_point);
+        }
+        assert_eq!(camera.zoom(), 0.75);
+
+        let center_world_point = camera.screen_point_to_world(vec2f(400.0, 300.0));
+        camera.set_zoom(4.0);
+        assert_eq!(camera.zoom(), 4.0);
+        assert_close(camera.world_point_to_screen(center_world_point), vec2f(400.0, 300.0));
+    }
+
+    #[test]
+    fn test_non_positive_zoom_is_clamped() {
+        let mut camera = Camera2D::new(vec2f(800.0, 600.0), 1.0);
+        camera.set_zoom(0.0);
+        assert_eq!(camera.zoom(), MIN_ZOOM);
+        camera.set_zoom(2.0);
+        camera.zoom_about(-1.0, vec2f(10.0, 10.0));
+        assert_eq!(camera.zoom(), MIN_ZOOM);
+        assert!(camera.screen_to_world().m11().is_finite());
+    }
+
+    #[test]
+    fn test_fit_to_bounds() {
+        let mut camera = Camera2D::new(vec2f(800.0, 600.0), 1.0);
+        let world_bounds = RectF::new(vec2f(100.0, 100.0), vec2f(200.0, 100.0));
+        camera.fit_to_bounds(world_bounds);
+
+        // The bounds fill the width of the viewport and are centered vertically.
+        assert_eq!(camera.zoom(), 4.0);
+        assert_close(camera.world_point_to_screen(world_bounds.origin()), vec2f(0.0, 100.0));
+        assert_close(camera.world_point_to_screen(world_bounds.lower_right()),
+                     vec2f(800.0, 500.0));
+
+        // Empty bounds leave the camera alone.
+        camera.fit_to_bounds(RectF::new(vec2f(5.0, 5.0), vec2f(0.0, 10.0)));
+        assert_eq!(camera.zoom(), 4.0);
+    }
+}
@@ -0,0 +1,1037 @@
+// pathfinder/renderer/src/capture.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Frame capture and replay, for attaching reproducible test cases to bug reports.
+//!
+//! A capture contains the complete render command stream for a single built scene, along with
+//! the renderer options and framebuffer size in effect. Replaying it exercises exactly the same
+//! GPU work as the original frame, without needing the application that produced it.
+
+use crate::animation::{AnimatedPathLayout, MAX_ANIMATED_PATHS};
+use crate::concurrent::executor::Executor;
+use crate::gpu::options::{ClearMode, OutputAlpha, RendererOptions};
+use crate::gpu::renderer::Renderer;
+use crate::gpu_data::{Clip, ClipBatch, ClipBatchKey, ClipBatchKind, Fill};
+use crate::gpu_data::{FillBatchEntry, RenderCommand, TextureLocation, TextureMetadataEntry};
+use crate::gpu_data::{TexturePageDescriptor, TexturePageId, Tile, TileBatch, TileBatchTexture};
+use crate::options::{BoundingQuad, BuildOptions};
+use crate::paint::{PaintCompositeOp, PaintLayout, PaintSlot, PaintSlotTexture};
+use crate::scene::{PathId, Scene};
+use crate::tiles::TileSize;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use pathfinder_color::{ColorF, ColorU};
+use pathfinder_content::effects::{BlendMode, BlurDirection, DefringingKernel, Filter};
+use pathfinder_content::effects::PatternFilter;
+use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_content::yuv::{YuvRange, YuvStandard};
+use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU4, LineSegmentU8};
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I, Vector4F};
+use pathfinder_gpu::{Device, TextureSamplingFlags};
+use pathfinder_gpu::error::RenderError;
+use pathfinder_simd::default::F32x2;
+use std::io::{self, Error, ErrorKind, Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+const MAGIC: &[u8; 8] = b"PFCAPTUR";
+const VERSION: u32 = 4;
+
+pub(crate) const MAX_PREALLOCATED_ENTRIES: usize = 4096;
+
+pub(crate) const BLEND_MODES: [BlendMode; 27] = [
+    BlendMode::Clear,
+    BlendMode::Copy,
+    BlendMode::SrcIn,
+    BlendMode::SrcOut,
+    BlendMode::SrcOver,
+    BlendMode::SrcAtop,
+    BlendMode::DestIn,
+    BlendMode::DestOut,
+    BlendMode::DestOver,
+    BlendMode::DestAtop,
+    BlendMode::Xor,
+    BlendMode::Lighter,
+    BlendMode::Darken,
+    BlendMode::Lighten,
+    BlendMode::Multiply,
+    BlendMode::Screen,
+    BlendMode::HardLight,
+    BlendMode::Overlay,
+    BlendMode::ColorDodge,
+    BlendMode::ColorBurn,
+    BlendMode::SoftLight,
+    BlendMode::Difference,
+    BlendMode::Exclusion,
+    BlendMode::Hue,
+    BlendMode::Saturation,
+    BlendMode::Color,
+    BlendMode::Luminosity,
+];
+
+/// A single built frame, ready to be written to disk or replayed.
+pub struct FrameCapture {
+    /// The size of the framebuffer the frame was rendered to, in device pixels.
+    pub framebuffer_size: Vector2I,
+    pub renderer_options: RendererOptions,
+    pub clear_mode: ClearMode,
+    pub commands: Vec<RenderCommand>,
+}
+
+impl FrameCapture {
+    /// Builds `scene` and records the resulting render commands.
+    pub fn build<E>(scene: &mut Scene,
+                    build_options: BuildOptions,
+                    renderer_options: RendererOptions,
+                    clear_mode: ClearMode,
+                    framebuffer_size: Vector2I,
+                    executor: &E)
+                    -> FrameCapture
+                    where E: Executor {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let listener = Box::new(move |command| drop(sender.send(command)));
+        scene.build(build_options, listener, executor);
+        let commands = receiver.try_iter().collect();
+        FrameCapture { framebuffer_size, renderer_options, clear_mode, commands }
+    }
+
+    /// Renders the captured frame with the given renderer, using the captured renderer options
+    /// and clear mode.
+    ///
+    /// The renderer's destination framebuffer is left alone; it should generally be
+    /// `framebuffer_size` in size for the output to match the original.
+    pub fn replay<D>(&self, renderer: &mut Renderer<D>) -> Result<(), RenderError>
+                     where D: Device {
+        renderer.set_options(self.renderer_options.clone());
+        renderer.set_clear_mode(self.clear_mode);
+        renderer.begin_scene();
+        let result = self.commands.iter().try_for_each(|command| renderer.render_command(command));
+        renderer.end_scene();
+        result
+    }
+
+    pub fn write<W>(&self, writer: &mut W) -> io::Result<()> where W: Write {
+        writer.write_all(MAGIC)?;
+        writer.write_u32::<LittleEndian>(VERSION)?;
+        write_vector_2i(writer, self.framebuffer_size)?;
+        write_renderer_options(writer, &self.renderer_options, self.clear_mode)?;
+        writer.write_u32::<LittleEndian>(self.commands.len() as u32)?;
+        for command in &self.commands {
+            write_command(writer, command)?;
+        }
+        Ok(())
+    }
+
+    pub fn read<R>(reader: &mut R) -> io::Result<FrameCapture> where R: Read {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a Pathfinder frame capture"));
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        // Version 1 captures are the same, minus the output alpha mode. Version 2 captures just
+        // never declare their paints, and version 3 captures never declare animated paths, and
+        // their tiles have no transform slots.
+        if version == 0 || version > VERSION {
+            return Err(invalid_data("unsupported frame capture version"));
+        }
+        let framebuffer_size = read_vector_2i(reader)?;
+        let (renderer_options, clear_mode) = read_renderer_options(reader, version)?;
+        let command_count = reader.read_u32::<LittleEndian>()?;
+        let mut commands = vec![];
+        for _ in 0..command_count {
+            commands.push(read_command(reader, version)?);
+        }
+        Ok(FrameCapture { framebuffer_size, renderer_options, clear_mode, commands })
+    }
+}
+
+pub(crate) fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+// Counts are read from the file, so a truncated or corrupt one can claim billions of entries.
+// Room for only so many is reserved up front, and vectors grow as entries actually arrive.
+pub(crate) fn vec_with_capacity_for<T>(count: u32) -> Vec<T> {
+    Vec::with_capacity((count as usize).min(MAX_PREALLOCATED_ENTRIES))
+}
+
+// Renderer options, and the clear mode, which is stored with them
+
+fn write_renderer_options<W>(writer: &mut W, options: &RendererOptions, clear_mode: ClearMode)
+                             -> io::Result<()>
+                             where W: Write {
+    match clear_mode {
+        ClearMode::Color(color) => {
+            writer.write_u8(0)?;
+            write_color_f(writer, color)?;
+        }
+        ClearMode::Transparent => writer.write_u8(1)?,
+        ClearMode::Preserve => writer.write_u8(2)?,
+    }
+    writer.write_u8(options.no_compute as u8)?;
+    writer.write_u8(options.dithering as u8)?;
+    match options.output_alpha {
+        OutputAlpha::Premultiplied => writer.write_u8(0),
+        OutputAlpha::Opaque(color) => {
+            writer.write_u8(1)?;
+            write_color_f(writer, color)
+        }
+    }
+}
+
+fn read_renderer_options<R>(reader: &mut R, version: u32)
+                            -> io::Result<(RendererOptions, ClearMode)>
+                            where R: Read {
+    let clear_mode = match reader.read_u8()? {
+        0 => ClearMode::Color(read_color_f(reader)?),
+        1 => ClearMode::Transparent,
+        2 => ClearMode::Preserve,
+        _ => return Err(invalid_data("invalid clear mode")),
+    };
+    let no_compute = reader.read_u8()? != 0;
+    let dithering = reader.read_u8()? != 0;
+    let output_alpha = if version < 2 {
+        OutputAlpha::Premultiplied
+    } else {
+        match reader.read_u8()? {
+            0 => OutputAlpha::Premultiplied,
+            1 => OutputAlpha::Opaque(read_color_f(reader)?),
+            _ => return Err(invalid_data("invalid output alpha mode")),
+        }
+    };
+    Ok((RendererOptions { no_compute, dithering, output_alpha }, clear_mode))
+}
+
+// Render commands
+
+fn write_command<W>(writer: &mut W, command: &RenderCommand) -> io::Result<()> where W: Write {
+    match *command {
+        RenderCommand::Start {
+            path_count,
+            ref bounding_quad,
+            needs_readable_framebuffer,
+            tile_size,
+        } => {
+            writer.write_u8(0)?;
+            writer.write_u32::<LittleEndian>(path_count as u32)?;
+            for &point in bounding_quad.iter() {
+                write_vector_4f(writer, point)?;
+            }
+            writer.write_u8(needs_readable_framebuffer as u8)?;
+            writer.write_u8(match tile_size {
+                TileSize::Tile8x8 => 0,
+                TileSize::Tile16x16 => 1,
+            })
+        }
+        RenderCommand::AllocateTexturePage { page_id, descriptor } => {
+            writer.write_u8(1)?;
+            writer.write_u32::<LittleEndian>(page_id.0)?;
+            write_vector_2i(writer, descriptor.size)
+        }
+        RenderCommand::UploadTexelData { ref texels, location } => {
+            writer.write_u8(2)?;
+            write_texture_location(writer, location)?;
+            writer.write_u32::<LittleEndian>(texels.len() as u32)?;
+            for &texel in texels.iter() {
+                write_color_u(writer, texel)?;
+            }
+            Ok(())
+        }
+        RenderCommand::DeclareRenderTarget { id, location } => {
+            writer.write_u8(3)?;
+            write_render_target_id(writer, id)?;
+            write_texture_location(writer, location)
+        }
+        RenderCommand::UploadTextureMetadata(ref metadata) => {
+            writer.write_u8(4)?;
+            writer.write_u32::<LittleEndian>(metadata.len() as u32)?;
+            for entry in metadata {
+                write_transform_2f(writer, &entry.color_0_transform)?;
+                write_color_u(writer, entry.base_color)?;
+            }
+            Ok(())
+        }
+        RenderCommand::AddFills(ref fills) => {
+            writer.write_u8(5)?;
+            writer.write_u32::<LittleEndian>(fills.len() as u32)?;
+            for entry in fills {
+                write_line_segment_u8(writer, entry.fill.subpx)?;
+                write_line_segment_u4(writer, entry.fill.px)?;
+                writer.write_u16::<LittleEndian>(entry.fill.alpha_tile_index)?;
+                writer.write_u16::<LittleEndian>(entry.page)?;
+            }
+            Ok(())
+        }
+        RenderCommand::FlushFills => writer.write_u8(6),
+        RenderCommand::ClipTiles(ref batches) => {
+            writer.write_u8(7)?;
+            writer.write_u32::<LittleEndian>(batches.len() as u32)?;
+            for batch in batches {
+                writer.write_u16::<LittleEndian>(batch.key.dest_page)?;
+                writer.write_u16::<LittleEndian>(batch.key.src_page)?;
+                writer.write_u8(match batch.key.kind {
+                    ClipBatchKind::Draw => 0,
+                    ClipBatchKind::Clip => 1,
+                    ClipBatchKind::InvertedClip => 2,
+                })?;
+                writer.write_u32::<LittleEndian>(batch.clips.len() as u32)?;
+                for clip in &batch.clips {
+                    writer.write_u8(clip.dest_u)?;
+                    writer.write_u8(clip.dest_v)?;
+                    writer.write_u8(clip.src_u)?;
+                    writer.write_u8(clip.src_v)?;
+                    writer.write_i8(clip.backdrop)?;
+                }
+            }
+            Ok(())
+        }
+        RenderCommand::PushRenderTarget(id) => {
+            writer.write_u8(8)?;
+            write_render_target_id(writer, id)
+        }
+        RenderCommand::PopRenderTarget => writer.write_u8(9),
+        RenderCommand::BeginTileDrawing => writer.write_u8(10),
+        RenderCommand::DrawTiles(ref batch) => {
+            writer.write_u8(11)?;
+            write_tile_batch(writer, batch)
+        }
+        RenderCommand::Finish { cpu_build_time } => {
+            writer.write_u8(12)?;
+            writer.write_u64::<LittleEndian>(cpu_build_time.as_nanos() as u64)
+        }
+        RenderCommand::ResolveBackdrop { render_target, source_rect } => {
+            writer.write_u8(13)?;
+            write_render_target_id(writer, render_target)?;
+            write_vector_2i(writer, source_rect.origin())?;
+            write_vector_2i(writer, source_rect.size())
+        }
+        RenderCommand::DeclarePaints(ref layout) => {
+            writer.write_u8(14)?;
+            write_paint_layout(writer, layout)
+        }
+        RenderCommand::DeclareAnimatedPaths(ref layout) => {
+            writer.write_u8(15)?;
+            write_animated_path_layout(writer, layout)
+        }
+    }
+}
+
+fn read_command<R>(reader: &mut R, version: u32) -> io::Result<RenderCommand> where R: Read {
+    match reader.read_u8()? {
+        0 => {
+            let path_count = reader.read_u32::<LittleEndian>()? as usize;
+            let mut bounding_quad: BoundingQuad = [Vector4F::default(); 4];
+            for point in &mut bounding_quad {
+                *point = read_vector_4f(reader)?;
+            }
+            let needs_readable_framebuffer = reader.read_u8()? != 0;
+            let tile_size = match reader.read_u8()? {
+                0 => TileSize::Tile8x8,
+                1 => TileSize::Tile16x16,
+                _ => return Err(invalid_data("invalid tile size")),
+            };
+            Ok(RenderCommand::Start {
+                path_count,
+                bounding_quad,
+                needs_readable_framebuffer,
+                tile_size,
+            })
+        }
+        1 => {
+            let page_id = TexturePageId(reader.read_u32::<LittleEndian>()?);
+            let descriptor = TexturePageDescriptor { size: read_vector_2i(reader)? };
+            Ok(RenderCommand::AllocateTexturePage { page_id, descriptor })
+        }
+        2 => {
+            let location = read_texture_location(reader)?;
+            let texel_count = reader.read_u32::<LittleEndian>()?;
+            let mut texels = vec_with_capacity_for(texel_count);
+            for _ in 0..texel_count {
+                texels.push(read_color_u(reader)?);
+            }
+            Ok(RenderCommand::UploadTexelData { texels: Arc::new(texels), location })
+        }
+        3 => {
+            let id = read_render_target_id(reader)?;
+            let location = read_texture_location(reader)?;
+            Ok(RenderCommand::DeclareRenderTarget { id, location })
+        }
+        4 => {
+            let entry_count = reader.read_u32::<LittleEndian>()?;
+            let mut metadata = vec_with_capacity_for(entry_count);
+            for _ in 0..entry_count {
+                let color_0_transform = read_transform_2f(reader)?;
+                let base_color = read_color_u(reader)?;
+                metadata.push(TextureMetadataEntry { color_0_transform, base_color });
+            }
+            Ok(RenderCommand::UploadTextureMetadata(metadata))
+        }
+        5 => {
+            let fill_count = reader.read_u32::<LittleEndian>()?;
+            let mut fills = vec_with_capacity_for(fill_count);
+            for _ in 0..fill_count {
+                let subpx = read_line_segment_u8(reader)?;
+                let px = read_line_segment_u4(reader)?;
+                let alpha_tile_index = reader.read_u16::<LittleEndian>()?;
+                let page = reader.read_u16::<LittleEndian>()?;
+                fills.push(FillBatchEntry { fill: Fill { subpx, px, alpha_tile_index }, page });
+            }
+            Ok(RenderCommand::AddFills(fills))
+        }
+        6 => Ok(RenderCommand::FlushFills),
+        7 => {
+            let batch_count = reader.read_u32::<LittleEndian>()?;
+            let mut batches = vec_with_capacity_for(batch_count);
+            for _ in 0..batch_count {
+                let dest_page = reader.read_u16::<LittleEndian>()?;
+                let src_page = reader.read_u16::<LittleEndian>()?;
+                let kind = match reader.read_u8()? {
+                    0 => ClipBatchKind::Draw,
+                    1 => ClipBatchKind::Clip,
+                    2 => ClipBatchKind::InvertedClip,
+                    _ => return Err(invalid_data("invalid clip batch kind")),
+                };
+                let clip_count = reader.read_u32::<LittleEndian>()?;
+                let mut clips = vec_with_capacity_for(clip_count);
+                for _ in 0..clip_count {
+                    clips.push(Clip {
+                        dest_u: reader.read_u8()?,
+                        dest_v: reader.read_u8()?,
+                        src_u: reader.read_u8()?,
+                        src_v: reader.read_u8()?,
+                        backdrop: reader.read_i8()?,
+                        ..Clip::default()
+                    });
+                }
+                batches.push(ClipBatch { clips, key: ClipBatchKey { dest_page, src_page, kind } });
+            }
+            Ok(RenderCommand::ClipTiles(batches))
+        }
+        8 => Ok(RenderCommand::PushRenderTarget(read_render_target_id(reader)?)),
+        9 => Ok(RenderCommand::PopRenderTarget),
+        10 => Ok(RenderCommand::BeginTileDrawing),
+        11 => Ok(RenderCommand::DrawTiles(read_tile_batch(reader, version)?)),
+        12 => {
+            let cpu_build_time = Duration::from_nanos(reader.read_u64::<LittleEndian>()?);
+            Ok(RenderCommand::Finish { cpu_build_time })
+        }
+        13 => {
+            let render_target = read_render_target_id(reader)?;
+            let origin = read_vector_2i(reader)?;
+            let source_rect = RectI::new(origin, read_vector_2i(reader)?);
+            Ok(RenderCommand::ResolveBackdrop { render_target, source_rect })
+        }
+        14 => Ok(RenderCommand::DeclarePaints(Arc::new(read_paint_layout(reader)?))),
+        15 => {
+            let layout = read_animated_path_layout(reader)?;
+            Ok(RenderCommand::DeclareAnimatedPaths(Arc::new(layout)))
+        }
+        _ => Err(invalid_data("invalid render command")),
+    }
+}
+
+// Tile batches
+
+fn write_tile_batch<W>(writer: &mut W, batch: &TileBatch) -> io::Result<()> where W: Write {
+    writer.write_u32::<LittleEndian>(batch.tiles.len() as u32)?;
+    for tile in &batch.tiles {
+        writer.write_i16::<LittleEndian>(tile.tile_x)?;
+        writer.write_i16::<LittleEndian>(tile.tile_y)?;
+        writer.write_u8(tile.mask_0_u)?;
+        writer.write_u8(tile.mask_0_v)?;
+        writer.write_i8(tile.mask_0_backdrop)?;
+        writer.write_u8(tile.transform_slot)?;
+        writer.write_u16::<LittleEndian>(tile.color)?;
+        writer.write_u16::<LittleEndian>(tile.ctrl)?;
+    }
+
+    match batch.color_texture {
+        None => writer.write_u8(0)?,
+        Some(ref color_texture) => {
+            writer.write_u8(1)?;
+            writer.write_u32::<LittleEndian>(color_texture.page.0)?;
+            writer.write_u8(color_texture.sampling_flags.bits())?;
+            write_composite_op(writer, color_texture.composite_op)?;
+        }
+    }
+
+    write_filter(writer, &batch.filter)?;
+    let blend_mode_index = BLEND_MODES.iter()
+                                      .position(|&blend_mode| blend_mode == batch.blend_mode)
+                                      .unwrap();
+    writer.write_u8(blend_mode_index as u8)?;
+    writer.write_u16::<LittleEndian>(batch.tile_page)
+}
+
+fn read_tile_batch<R>(reader: &mut R, version: u32) -> io::Result<TileBatch> where R: Read {
+    let tile_count = reader.read_u32::<LittleEndian>()?;
+    let mut tiles = vec_with_capacity_for(tile_count);
+    for _ in 0..tile_count {
+        tiles.push(Tile {
+            tile_x: reader.read_i16::<LittleEndian>()?,
+            tile_y: reader.read_i16::<LittleEndian>()?,
+            mask_0_u: reader.read_u8()?,
+            mask_0_v: reader.read_u8()?,
+            mask_0_backdrop: reader.read_i8()?,
+            transform_slot: if version >= 4 { reader.read_u8()? } else { 0 },
+            color: reader.read_u16::<LittleEndian>()?,
+            ctrl: reader.read_u16::<LittleEndian>()?,
+        });
+    }
+
+    let color_texture = match reader.read_u8()? {
+        0 => None,
+        1 => {
+            let page = TexturePageId(reader.read_u32::<LittleEndian>()?);
+            let sampling_flags = TextureSamplingFlags::from_bits_truncate(reader.read_u8()?);
+            let composite_op = read_composite_op(reader)?;
+            Some(TileBatchTexture { page, sampling_flags, composite_op })
+        }
+        _ => return Err(invalid_data("invalid tile batch texture")),
+    };
+
+    let filter = read_filter(reader)?;
+    let blend_mode = match BLEND_MODES.get(reader.read_u8()? as usize) {
+        Some(&blend_mode) => blend_mode,
+        None => return Err(invalid_data("invalid blend mode")),
+    };
+    let tile_page = reader.read_u16::<LittleEndian>()?;
+    Ok(TileBatch { tiles, color_texture, filter, blend_mode, tile_page })
+}
+
+// Paints
+
+fn write_paint_layout<W>(writer: &mut W, layout: &PaintLayout) -> io::Result<()>
+                         where W: Write {
+    writer.write_u64::<LittleEndian>(layout.palette_version)?;
+    write_transform_2f(writer, &layout.render_transform)?;
+    writer.write_u32::<LittleEndian>(layout.slots.len() as u32)?;
+    for slot in &layout.slots {
+        match slot.color_texture {
+            None => writer.write_u8(0)?,
+            Some(ref color_texture) => {
+                writer.write_u8(1)?;
+                write_texture_location(writer, color_texture.location)?;
+                write_vector_2f(writer, color_texture.page_scale)?;
+                writer.write_u8(color_texture.sampling_flags.bits())?;
+                write_composite_op(writer, color_texture.composite_op)?;
+            }
+        }
+        write_filter(writer, &slot.filter)?;
+        writer.write_u8(slot.is_opaque as u8)?;
+    }
+    Ok(())
+}
+
+fn read_paint_layout<R>(reader: &mut R) -> io::Result<PaintLayout> where R: Read {
+    let palette_version = reader.read_u64::<LittleEndian>()?;
+    let render_transform = read_transform_2f(reader)?;
+    let slot_count = reader.read_u32::<LittleEndian>()?;
+    let mut slots = vec_with_capacity_for(slot_count);
+    for _ in 0..slot_count {
+        let color_texture = match reader.read_u8()? {
+            0 => None,
+            1 => {
+                let location = read_texture_location(reader)?;
+                let page_scale = read_vector_2f(reader)?;
+                let sampling_flags = TextureSamplingFlags::from_bits_truncate(reader.read_u8()?);
+                let composite_op = read_composite_op(reader)?;
+                Some(PaintSlotTexture { location, page_scale, sampling_flags, composite_op })
+            }
+            _ => return Err(invalid_data("invalid paint slot texture")),
+        };
+        let filter = read_filter(reader)?;
+        let is_opaque = reader.read_u8()? != 0;
+        slots.push(PaintSlot { color_texture, filter, is_opaque });
+    }
+    Ok(PaintLayout { palette_version, render_transform, slots })
+}
+
+// Animated paths
+
+fn write_animated_path_layout<W>(writer: &mut W, layout: &AnimatedPathLayout) -> io::Result<()>
+                                 where W: Write {
+    writer.write_u64::<LittleEndian>(layout.geometry_hash)?;
+    write_transform_2f(writer, &layout.render_transform)?;
+    writer.write_u32::<LittleEndian>(layout.paths.len() as u32)?;
+    for path_id in &layout.paths {
+        writer.write_u32::<LittleEndian>(path_id.0)?;
+    }
+    Ok(())
+}
+
+fn read_animated_path_layout<R>(reader: &mut R) -> io::Result<AnimatedPathLayout>
+                                where R: Read {
+    let geometry_hash = reader.read_u64::<LittleEndian>()?;
+    let render_transform = read_transform_2f(reader)?;
+    let path_count = reader.read_u32::<LittleEndian>()?;
+    if path_count as usize > MAX_ANIMATED_PATHS {
+        return Err(invalid_data("too many animated paths"));
+    }
+    let mut paths = vec_with_capacity_for(path_count);
+    for _ in 0..path_count {
+        paths.push(PathId(reader.read_u32::<LittleEndian>()?));
+    }
+    Ok(AnimatedPathLayout { geometry_hash, render_transform, paths })
+}
+
+fn write_composite_op<W>(writer: &mut W, composite_op: PaintCompositeOp) -> io::Result<()>
+                         where W: Write {
+    writer.write_u8(match composite_op {
+        PaintCompositeOp::SrcIn => 0,
+        PaintCompositeOp::DestIn => 1,
+    })
+}
+
+fn read_composite_op<R>(reader: &mut R) -> io::Result<PaintCompositeOp> where R: Read {
+    match reader.read_u8()? {
+        0 => Ok(PaintCompositeOp::SrcIn),
+        1 => Ok(PaintCompositeOp::DestIn),
+        _ => Err(invalid_data("invalid paint composite op")),
+    }
+}
+
+// Filters
+
+pub(crate) fn write_filter<W>(writer: &mut W, filter: &Filter) -> io::Result<()> where W: Write {
+    match *filter {
+        Filter::None => writer.write_u8(0),
+        Filter::RadialGradient { line, radii, uv_origin } => {
+            writer.write_u8(1)?;
+            write_vector_2f(writer, line.from())?;
+            write_vector_2f(writer, line.to())?;
+            writer.write_f32::<LittleEndian>(radii[0])?;
+            writer.write_f32::<LittleEndian>(radii[1])?;
+            write_vector_2f(writer, uv_origin)
+        }
+        Filter::PatternFilter(PatternFilter::Text {
+            fg_color,
+            bg_color,
+            defringing_kernel,
+            gamma_correction,
+        }) => {
+            writer.write_u8(2)?;
+            write_color_f(writer, fg_color)?;
+            write_color_f(writer, bg_color)?;
+            match defringing_kernel {
+                None => writer.write_u8(0)?,
+                Some(DefringingKernel(kernel)) => {
+                    writer.write_u8(1)?;
+                    for &value in kernel.iter() {
+                        writer.write_f32::<LittleEndian>(value)?;
+                    }
+                }
+            }
+            writer.write_u8(gamma_correction as u8)
+        }
+        Filter::PatternFilter(PatternFilter::Blur { direction, sigma }) => {
+            writer.write_u8(3)?;
+            writer.write_u8(match direction {
+                BlurDirection::X => 0,
+                BlurDirection::Y => 1,
+            })?;
+            writer.write_f32::<LittleEndian>(sigma)
+        }
+        Filter::BlurredRoundedRect { rect, corner_radius, sigma, uv_origin } => {
+            writer.write_u8(4)?;
+            write_vector_2f(writer, rect.origin())?;
+            write_vector_2f(writer, rect.lower_right())?;
+            writer.write_f32::<LittleEndian>(corner_radius)?;
+            writer.write_f32::<LittleEndian>(sigma)?;
+            write_vector_2f(writer, uv_origin)
+        }
+        Filter::PatternFilter(PatternFilter::Yuv { standard, range }) => {
+            writer.write_u8(5)?;
+            writer.write_u8(match standard {
+                YuvStandard::Bt601 => 0,
+                YuvStandard::Bt709 => 1,
+            })?;
+            writer.write_u8(match range {
+                YuvRange::Limited => 0,
+                YuvRange::Full => 1,
+            })
+        }
+    }
+}
+
+pub(crate) fn read_filter<R>(reader: &mut R) -> io::Result<Filter> where R: Read {
+    match reader.read_u8()? {
+        0 => Ok(Filter::None),
+        1 => {
+            let from = read_vector_2f(reader)?;
+            let to = read_vector_2f(reader)?;
+            let radius_0 = reader.read_f32::<LittleEndian>()?;
+            let radius_1 = reader.read_f32::<LittleEndian>()?;
+            let uv_origin = read_vector_2f(reader)?;
+            Ok(Filter::RadialGradient {
+                line: LineSegment2F::new(from, to),
+                radii: F32x2::new(radius_0, radius_1),
+                uv_origin,
+            })
+        }
+        2 => {
+            let fg_color = read_color_f(reader)?;
+            let bg_color = read_color_f(reader)?;
+            let defringing_kernel = match reader.read_u8()? {
+                0 => None,
+                1 => {
+                    let mut kernel = [0.0; 4];
+                    for value in &mut kernel {
+                        *value = reader.read_f32::<LittleEndian>()?;
+                    }
+                    Some(DefringingKernel(kernel))
+                }
+                _ => return Err(invalid_data("invalid defringing kernel")),
+            };
+            let gamma_correction = reader.read_u8()? != 0;
+            Ok(Filter::PatternFilter(PatternFilter::Text {
+                fg_color,
+                bg_color,
+                defringing_kernel,
+                gamma_correction,
+            }))
+        }
+        3 => {
+            let direction = match reader.read_u8()? {
+                0 => BlurDirection::X,
+                1 => BlurDirection::Y,
+                _ => return Err(invalid_data("invalid blur direction")),
+            };
+            let sigma = reader.read_f32::<LittleEndian>()?;
+            Ok(Filter::PatternFilter(PatternFilter::Blur { direction, sigma }))
+        }
+        4 => {
+            let origin = read_vector_2f(reader)?;
+            let lower_right = read_vector_2f(reader)?;
+            let corner_radius = reader.read_f32::<LittleEndian>()?;
+            let sigma = reader.read_f32::<LittleEndian>()?;
+            let uv_origin = read_vector_2f(reader)?;
+            Ok(Filter::BlurredRoundedRect {
+                rect: RectF::from_points(origin, lower_right),
+                corner_radius,
+                sigma,
+                uv_origin,
+            })
+        }
+        5 => {
+            let standard = match reader.read_u8()? {
+                0 => YuvStandard::Bt601,
+                1 => YuvStandard::Bt709,
+                _ => return Err(invalid_data("invalid YUV standard")),
+            };
+            let range = match reader.read_u8()? {
+                0 => YuvRange::Limited,
+                1 => YuvRange::Full,
+                _ => return Err(invalid_data("invalid YUV range")),
+            };
+            Ok(Filter::PatternFilter(PatternFilter::Yuv { standard, range }))
+        }
+        _ => Err(invalid_data("invalid filter")),
+    }
+}
+
+// Primitives
+
+pub(crate) fn write_vector_2i<W>(writer: &mut W, vector: Vector2I) -> io::Result<()>
+                                 where W: Write {
+    writer.write_i32::<LittleEndian>(vector.x())?;
+    writer.write_i32::<LittleEndian>(vector.y())
+}
+
+pub(crate) fn read_vector_2i<R>(reader: &mut R) -> io::Result<Vector2I> where R: Read {
+    let x = reader.read_i32::<LittleEndian>()?;
+    let y = reader.read_i32::<LittleEndian>()?;
+    Ok(Vector2I::new(x, y))
+}
+
+pub(crate) fn write_vector_2f<W>(writer: &mut W, vector: Vector2F) -> io::Result<()>
+                                 where W: Write {
+    writer.write_f32::<LittleEndian>(vector.x())?;
+    writer.write_f32::<LittleEndian>(vector.y())
+}
+
+pub(crate) fn read_vector_2f<R>(reader: &mut R) -> io::Result<Vector2F> where R: Read {
+    let x = reader.read_f32::<LittleEndian>()?;
+    let y = reader.read_f32::<LittleEndian>()?;
+    Ok(Vector2F::new(x, y))
+}
+
+fn write_vector_4f<W>(writer: &mut W, vector: Vector4F) -> io::Result<()> where W: Write {
+    writer.write_f32::<LittleEndian>(vector.x())?;
+    writer.write_f32::<LittleEndian>(vector.y())?;
+    writer.write_f32::<LittleEndian>(vector.z())?;
+    writer.write_f32::<LittleEndian>(vector.w())
+}
+
+fn read_vector_4f<R>(reader: &mut R) -> io::Result<Vector4F> where R: Read {
+    let x = reader.read_f32::<LittleEndian>()?;
+    let y = reader.read_f32::<LittleEndian>()?;
+    let z = reader.read_f32::<LittleEndian>()?;
+    let w = reader.read_f32::<LittleEndian>()?;
+    Ok(Vector4F::new(x, y, z, w))
+}
+
+pub(crate) fn write_transform_2f<W>(writer: &mut W, transform: &Transform2F) -> io::Result<()>
+                                    where W: Write {
+    for &value in &[transform.m11(), transform.m12(), transform.m13(),
+                    transform.m21(), transform.m22(), transform.m23()] {
+        writer.write_f32::<LittleEndian>(value)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_transform_2f<R>(reader: &mut R) -> io::Result<Transform2F> where R: Read {
+    let mut values = [0.0; 6];
+    for value in &mut values {
+        *value = reader.read_f32::<LittleEndian>()?;
+    }
+    Ok(Transform2F::row_major(values[0], values[1], values[2], values[3], values[4], values[5]))
+}
+
+pub(crate) fn write_color_u<W>(writer: &mut W, color: ColorU) -> io::Result<()> where W: Write {
+    writer.write_all(&[color.r, color.g, color.b, color.a])
+}
+
+pub(crate) fn read_color_u<R>(reader: &mut R) -> io::Result<ColorU> where R: Read {
+    let mut rgba = [0; 4];
+    reader.read_exact(&mut rgba)?;
+    Ok(ColorU::new(rgba[0], rgba[1], rgba[2], rgba[3]))
+}
+
+fn write_color_f<W>(writer: &mut W, color: ColorF) -> io::Result<()> where W: Write {
+    writer.write_f32::<LittleEndian>(color.r())?;
+    writer.write_f32::<LittleEndian>(color.g())?;
+    writer.write_f32::<LittleEndian>(color.b())?;
+    writer.write_f32::<LittleEndian>(color.a())
+}
+
+fn read_color_f<R>(reader: &mut R) -> io::Result<ColorF> where R: Read {
+    let r = reader.read_f32::<LittleEndian>()?;
+    let g = reader.read_f32::<LittleEndian>()?;
+    let b = reader.read_f32::<LittleEndian>()?;
+    let a = reader.read_f32::<LittleEndian>()?;
+    Ok(ColorF::new(r, g, b, a))
+}
+
+fn write_texture_location<W>(writer: &mut W, location: TextureLocation) -> io::Result<()>
+                             where W: Write {
+    writer.write_u32::<LittleEndian>(location.page.0)?;
+    write_vector_2i(writer, location.rect.origin())?;
+    write_vector_2i(writer, location.rect.size())
+}
+
+fn read_texture_location<R>(reader: &mut R) -> io::Result<TextureLocation> where R: Read {
+    let page = TexturePageId(reader.read_u32::<LittleEndian>()?);
+    let origin = read_vector_2i(reader)?;
+    let size = read_vector_2i(reader)?;
+    Ok(TextureLocation { page, rect: RectI::new(origin, size) })
+}
+
+fn write_render_target_id<W>(writer: &mut W, id: RenderTargetId) -> io::Result<()>
+                             where W: Write {
+    writer.write_u32::<LittleEndian>(id.scene)?;
+    writer.write_u32::<LittleEndian>(id.render_target)
+}
+
+fn read_render_target_id<R>(reader: &mut R) -> io::Result<RenderTargetId> where R: Read {
+    let scene = reader.read_u32::<LittleEndian>()?;
+    let render_target = reader.read_u32::<LittleEndian>()?;
+    Ok(RenderTargetId { scene, render_target })
+}
+
+fn write_line_segment_u4<W>(writer: &mut W, segment: LineSegmentU4) -> io::Result<()>
+                            where W: Write {
+    writer.write_all(&[segment.from, segment.to])
+}
+
+fn read_line_segment_u4<R>(reader: &mut R) -> io::Result<LineSegmentU4> where R: Read {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(LineSegmentU4 { from: bytes[0], to: bytes[1] })
+}
+
+fn write_line_segment_u8<W>(writer: &mut W, segment: LineSegmentU8) -> io::Result<()>
+                            where W: Write {
+    writer.write_all(&[segment.from_x, segment.from_y, segment.to_x, segment.to_y])
+}
+
+fn read_line_segment_u8<R>(reader: &mut R) -> io::Result<LineSegmentU8> where R: Read {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(LineSegmentU8 { from_x: bytes[0], from_y: bytes[1], to_x: bytes[2], to_y: bytes[3] })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::animation::AnimatedPathLayout;
+    use crate::gpu::options::{ClearMode, OutputAlpha, RendererOptions};
+    use crate::gpu_data::{Fill, FillBatchEntry, RenderCommand, TextureLocation, TexturePageId};
+    use crate::gpu_data::{Tile, TileBatch};
+    use crate::paint::{PaintCompositeOp, PaintLayout, PaintSlot, PaintSlotTexture};
+    use crate::scene::PathId;
+    use crate::tiles::TileSize;
+    use pathfinder_color::ColorF;
+    use pathfinder_content::effects::{BlendMode, Filter};
+    use pathfinder_content::render_target::RenderTargetId;
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::rect::RectI;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I, Vector4F};
+    use pathfinder_gpu::TextureSamplingFlags;
+    use pathfinder_simd::default::F32x2;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::FrameCapture;
+
+    #[test]
+    fn test_round_trip() {
+        let capture = FrameCapture {
+            framebuffer_size: Vector2I::new(640, 480),
+            renderer_options: RendererOptions {
+                output_alpha: OutputAlpha::Opaque(ColorF::black()),
+                ..RendererOptions::default()
+            },
+            clear_mode: ClearMode::Color(ColorF::white()),
+            commands: vec![
+                RenderCommand::Start {
+                    path_count: 1,
+                    bounding_quad: [Vector4F::new(1.0, 2.0, 3.0, 4.0); 4],
+                    needs_readable_framebuffer: false,
+                    tile_size: TileSize::Tile8x8,
+                },
+                RenderCommand::DeclarePaints(Arc::new(PaintLayout {
+                    palette_version: 7,
+                    render_transform: Transform2F::from_scale(Vector2F::splat(0.5)),
+                    slots: vec![
+                        PaintSlot { color_texture: None, filter: Filter::None, is_opaque: true },
+                        PaintSlot {
+                            color_texture: Some(PaintSlotTexture {
+                                location: TextureLocation {
+                                    page: TexturePageId(1),
+                                    rect: RectI::new(Vector2I::new(0, 3), Vector2I::new(256, 1)),
+                                },
+                                page_scale: Vector2F::splat(1.0 / 256.0),
+                                sampling_flags: TextureSamplingFlags::REPEAT_U,
+                                composite_op: PaintCompositeOp::DestIn,
+                            }),
+                            filter: Filter::None,
+                            is_opaque: false,
+                        },
+                    ],
+                })),
+                RenderCommand::DeclareAnimatedPaths(Arc::new(AnimatedPathLayout {
+                    geometry_hash: 0x0123_4567_89ab_cdef,
+                    render_transform: Transform2F::from_translation(Vector2F::new(8.0, -2.0)),
+                    paths: vec![PathId(4), PathId(1)],
+                })),
+                RenderCommand::AddFills(vec![FillBatchEntry {
+                    fill: Fill { alpha_tile_index: 7, ..Fill::default() },
+                    page: 1,
+                }]),
+                RenderCommand::FlushFills,
+                RenderCommand::BeginTileDrawing,
+                RenderCommand::ResolveBackdrop {
+                    render_target: RenderTargetId { scene: 0, render_target: 2 },
+                    source_rect: RectI::new(Vector2I::new(16, 8), Vector2I::new(64, 32)),
+                },
+                RenderCommand::DrawTiles(TileBatch {
+                    tiles: vec![
+                        Tile { tile_x: -3, tile_y: 5, ..Tile::default() },
+                        Tile { tile_x: 2, tile_y: 1, transform_slot: 200, ..Tile::default() },
+                    ],
+                    color_texture: None,
+                    filter: Filter::RadialGradient {
+                        line: LineSegment2F::new(Vector2F::new(0.0, 0.0),
+                                                 Vector2F::new(10.0, 0.0)),
+                        radii: F32x2::new(1.0, 5.0),
+                        uv_origin: Vector2F::new(0.5, 0.5),
+                    },
+                    blend_mode: BlendMode::Multiply,
+                    tile_page: 0,
+                }),
+                RenderCommand::Finish { cpu_build_time: Duration::from_millis(3) },
+            ],
+        };
+
+        let mut bytes = vec![];
+        capture.write(&mut bytes).unwrap();
+        let read_capture = FrameCapture::read(&mut &bytes[..]).unwrap();
+        assert_eq!(read_capture.framebuffer_size, capture.framebuffer_size);
+        assert_eq!(read_capture.clear_mode, capture.clear_mode);
+        assert_eq!(read_capture.renderer_options.output_alpha,
+                   capture.renderer_options.output_alpha);
+        assert_eq!(read_capture.commands.len(), capture.commands.len());
+        match (&read_capture.commands[1], &capture.commands[1]) {
+            (RenderCommand::DeclarePaints(read_layout), RenderCommand::DeclarePaints(layout)) => {
+                assert_eq!(read_layout, layout)
+            }
+            _ => panic!("Expected paint declarations!"),
+        }
+        match (&read_capture.commands[2], &capture.commands[2]) {
+            (RenderCommand::DeclareAnimatedPaths(read_layout),
+             RenderCommand::DeclareAnimatedPaths(layout)) => assert_eq!(read_layout, layout),
+            _ => panic!("Expected animated path declarations!"),
+        }
+        match read_capture.commands[7] {
+            RenderCommand::DrawTiles(ref batch) => assert_eq!(batch.tiles[1].transform_slot, 200),
+            _ => panic!("Expected tiles!"),
+        }
+
+        let mut round_tripped_bytes = vec![];
+        read_capture.write(&mut round_tripped_bytes).unwrap();
+        assert_eq!(bytes, round_tripped_bytes);
+    }
+
+    #[test]
+    fn test_reject_bad_magic() {
+        assert!(FrameCapture::read(&mut &b"NOTACAPTURE!"[..]).is_err());
+    }
+
+    #[test]
+    fn test_reject_truncated_capture() {
+        let capture = FrameCapture {
+            framebuffer_size: Vector2I::new(64, 64),
+            renderer_options: RendererOptions::default(),
+            clear_mode: ClearMode::default(),
+            commands: vec![
+                RenderCommand::AddFills(vec![FillBatchEntry { fill: Fill::default(), page: 0 }]),
+                RenderCommand::DrawTiles(TileBatch {
+                    tiles: vec![Tile::default(); 3],
+                    color_texture: None,
+                    filter: Filter::None,
+                    blend_mode: BlendMode::SrcOver,
+                    tile_page: 0,
+                }),
+            ],
+        };
+        let mut bytes = vec![];
+        capture.write(&mut bytes).unwrap();
+        for length in 0..bytes.len() {
+            assert!(FrameCapture::read(&mut &bytes[..length]).is_err());
+        }
+
+        // A count far larger than the rest of the file must fail without reserving room for it.
+        let commands_start = bytes.len() - capture_commands_size(&capture);
+        let mut huge_fills = bytes[..commands_start].to_vec();
+        huge_fills.extend_from_slice(&[5, 0xff, 0xff, 0xff, 0xff, 0, 0, 0]);
+        assert!(FrameCapture::read(&mut &huge_fills[..]).is_err());
+    }
+
+    fn capture_commands_size(capture: &FrameCapture) -> usize {
+        let mut bytes = vec![];
+        for command in &capture.commands {
+            super::write_command(&mut bytes, command).unwrap();
+        }
+        bytes.len()
+    }
+}
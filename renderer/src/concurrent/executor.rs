@@ -9,6 +9,10 @@
 // except according to those terms.
 
 //! An abstraction over threading and parallelism systems such as Rayon.
+//!
+//! Applications with their own job systems (as most game engines have) can run scene building on
+//! them instead of on Rayon's thread pool by implementing `JobSystem` and wrapping it in a
+//! `JobSystemExecutor`.
 
 /// An abstraction over threading and parallelism systems such as Rayon.
 pub trait Executor {
@@ -21,6 +25,12 @@ pub trait Executor {
                           where T: Send, F: Fn(usize) -> T + Send + Sync;
 }
 
+/// Builds scenes on the calling thread without any parallelism.
+///
+/// Besides staying out of the way of the application's own threads, this makes scene building
+/// deterministic: the same scene and options always produce the same render commands in the same
+/// order. (With parallel executors, the order in which paths finish tiling, and therefore the
+/// layout of the mask texture, varies from run to run.)
 pub struct SequentialExecutor;
 
 impl Executor for SequentialExecutor {
@@ -29,3 +39,111 @@ impl Executor for SequentialExecutor {
         (0..length).into_iter().map(builder).collect()
     }
 }
+
+/// A job that a `JobSystem` runs. Jobs may borrow from the scene being built, so they're only
+/// guaranteed to live until `JobSystem::spawn_and_join()` returns.
+pub type Job<'a> = Box<dyn FnOnce() + Send + 'a>;
+
+/// A system that runs batches of jobs, such as an engine's task pool or a fiber scheduler.
+pub trait JobSystem {
+    /// Spawns all of the jobs, in any order and on any threads, and returns once every one of
+    /// them has finished.
+    ///
+    /// This has the same semantics as spawning the jobs within a Rayon `scope()`.
+    fn spawn_and_join<'a>(&self, jobs: Vec<Job<'a>>);
+}
+
+impl JobSystem for SequentialExecutor {
+    fn spawn_and_join<'a>(&self, jobs: Vec<Job<'a>>) {
+        for job in jobs {
+            job();
+        }
+    }
+}
+
+/// Builds scenes by spawning jobs on a `JobSystem`.
+///
+/// Each job builds a batch of consecutive items, to keep the overhead of spawning jobs small
+/// relative to the work they do.
+#[derive(Clone)]
+pub struct JobSystemExecutor<J> where J: JobSystem {
+    job_system: J,
+    batch_size: usize,
+}
+
+const DEFAULT_JOB_BATCH_SIZE: usize = 16;
+
+impl<J> JobSystemExecutor<J> where J: JobSystem {
+    #[inline]
+    pub fn new(job_system: J) -> JobSystemExecutor<J> {
+        JobSystemExecutor { job_system, batch_size: DEFAULT_JOB_BATCH_SIZE }
+    }
+
+    /// Sets the number of items, such as paths, that each job builds. The default is 16.
+    #[inline]
+    pub fn with_batch_size(mut self, batch_size: usize) -> JobSystemExecutor<J> {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    #[inline]
+    pub fn job_system(&self) -> &J {
+        &self.job_system
+    }
+}
+
+impl<J> Executor for JobSystemExecutor<J> where J: JobSystem {
+    fn build_vector<T, F>(&self, length: usize, builder: F) -> Vec<T>
+                          where T: Send, F: Fn(usize) -> T + Send + Sync {
+        let mut results: Vec<Option<T>> = (0..length).map(|_| None).collect();
+        {
+            let builder = &builder;
+            let batch_size = self.batch_size;
+            let jobs = results.chunks_mut(batch_size).enumerate().map(|(batch_index, batch)| {
+                Box::new(move || {
+                    for (index_in_batch, result) in batch.iter_mut().enumerate() {
+                        *result = Some(builder(batch_index * batch_size + index_in_batch));
+                    }
+                }) as Job
+            }).collect();
+            self.job_system.spawn_and_join(jobs);
+        }
+        results.into_iter()
+               .map(|result| result.expect("Job system returned before all jobs finished!"))
+               .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Executor, Job, JobSystem, JobSystemExecutor, SequentialExecutor};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Runs jobs backward, counting them.
+    struct ReversingJobSystem {
+        job_count: AtomicUsize,
+    }
+
+    impl JobSystem for ReversingJobSystem {
+        fn spawn_and_join<'a>(&self, jobs: Vec<Job<'a>>) {
+            self.job_count.fetch_add(jobs.len(), Ordering::SeqCst);
+            for job in jobs.into_iter().rev() {
+                job();
+            }
+        }
+    }
+
+    #[test]
+    fn test_job_system_executor() {
+        let job_system = ReversingJobSystem { job_count: AtomicUsize::new(0) };
+        let executor = JobSystemExecutor::new(job_system).with_batch_size(3);
+        let squares = executor.build_vector(10, |index| index * index);
+        assert_eq!(squares, (0..10).map(|index| index * index).collect::<Vec<_>>());
+        assert_eq!(executor.job_system().job_count.load(Ordering::SeqCst), 4);
+
+        assert!(executor.build_vector(0, |index| index).is_empty());
+
+        let executor = JobSystemExecutor::new(SequentialExecutor);
+        assert_eq!(executor.build_vector(3, |index| index + 1), vec![1, 2, 3]);
+    }
+}
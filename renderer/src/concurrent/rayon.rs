@@ -10,9 +10,12 @@
 
 //! An implementation of the executor using the Rayon library.
 
-use crate::concurrent::executor::Executor;
+use crate::concurrent::executor::{Executor, Job, JobSystem};
+use rayon::ThreadPool;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::sync::Arc;
 
+/// Builds scenes on Rayon's global thread pool.
 pub struct RayonExecutor;
 
 impl Executor for RayonExecutor {
@@ -21,3 +24,49 @@ impl Executor for RayonExecutor {
         (0..length).into_par_iter().map(builder).collect()
     }
 }
+
+impl JobSystem for RayonExecutor {
+    fn spawn_and_join<'a>(&self, jobs: Vec<Job<'a>>) {
+        rayon::scope(|scope| {
+            for job in jobs {
+                scope.spawn(move |_| job());
+            }
+        })
+    }
+}
+
+/// Builds scenes on a Rayon thread pool supplied by the application, so that Pathfinder shares
+/// the application's thread budget instead of using the global pool.
+#[derive(Clone)]
+pub struct RayonThreadPoolExecutor {
+    thread_pool: Arc<ThreadPool>,
+}
+
+impl RayonThreadPoolExecutor {
+    #[inline]
+    pub fn new(thread_pool: Arc<ThreadPool>) -> RayonThreadPoolExecutor {
+        RayonThreadPoolExecutor { thread_pool }
+    }
+
+    #[inline]
+    pub fn thread_pool(&self) -> &Arc<ThreadPool> {
+        &self.thread_pool
+    }
+}
+
+impl Executor for RayonThreadPoolExecutor {
+    fn build_vector<T, F>(&self, length: usize, builder: F) -> Vec<T>
+                          where T: Send, F: Fn(usize) -> T + Send + Sync {
+        self.thread_pool.install(|| (0..length).into_par_iter().map(builder).collect())
+    }
+}
+
+impl JobSystem for RayonThreadPoolExecutor {
+    fn spawn_and_join<'a>(&self, jobs: Vec<Job<'a>>) {
+        self.thread_pool.scope(|scope| {
+            for job in jobs {
+                scope.spawn(move |_| job());
+            }
+        })
+    }
+}
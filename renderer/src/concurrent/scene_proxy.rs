@@ -27,6 +27,7 @@ use crate::scene::Scene;
 use crossbeam_channel::{self, Receiver, Sender};
 use pathfinder_geometry::rect::RectF;
 use pathfinder_gpu::Device;
+use pathfinder_gpu::error::RenderError;
 use std::thread;
 
 const MAX_MESSAGES_IN_FLIGHT: usize = 1024;
@@ -79,18 +80,23 @@ impl SceneProxy {
     /// Exactly equivalent to:
     ///
     /// ```norun
-    /// for command in scene_proxy.build_with_stream(options) {
+    /// renderer.begin_scene();
+    /// let result = scene_proxy.build_with_stream(options).try_for_each(|command| {
     ///     renderer.render_command(&command)
-    /// }
+    /// });
+    /// renderer.end_scene();
+    /// result
     /// ```
     #[inline]
     pub fn build_and_render<D>(&self, renderer: &mut Renderer<D>, build_options: BuildOptions)
+                               -> Result<(), RenderError>
                                where D: Device {
         renderer.begin_scene();
-        for command in self.build_with_stream(build_options) {
-            renderer.render_command(&command);
-        }
+        let result = self.build_with_stream(build_options).try_for_each(|command| {
+            renderer.render_command(&command)
+        });
         renderer.end_scene();
+        result
     }
 
     #[inline]
@@ -110,7 +116,9 @@ fn scene_thread<E>(mut scene: Scene,
             MainToWorkerMsg::ReplaceScene(new_scene) => scene = new_scene,
             MainToWorkerMsg::CopyScene(sender) => sender.send(scene.clone()).unwrap(),
             MainToWorkerMsg::SetViewBox(new_view_box) => scene.set_view_box(new_view_box),
-            MainToWorkerMsg::Build(options, listener) => scene.build(options, listener, &executor)
+            MainToWorkerMsg::Build(options, listener) => {
+                scene.build(options, listener, &executor);
+            }
         }
     }
 }
@@ -0,0 +1,279 @@
+// pathfinder/renderer/src/governor.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Automatically lowers rendering quality when frames run over budget, and raises it again when
+//! they have time to spare.
+//!
+//! Feed the governor the statistics of every frame and copy its settings into the `BuildOptions`
+//! of the next:
+//!
+//! ```ignore
+//! let mut governor = QualityGovernor::new(Duration::from_millis(16));
+//! loop {
+//!     let mut options = BuildOptions::default();
+//!     governor.apply(&mut options);
+//!     scene.build_and_render(&mut renderer, options)?;
+//!     governor.record_frame(&renderer.stats, renderer.shift_rendering_time());
+//! }
+//! ```
+
+use crate::gpu::renderer::{RenderStats, RenderTime};
+use crate::options::{BuildOptions, QualitySettings};
+use std::time::Duration;
+
+/// The settings for each quality level, from full quality down.
+const QUALITY_LEVELS: [QualitySettings; 5] = [
+    QualitySettings { flattening_tolerance: 0.25, layer_scale: 1.0, blur_enabled: true },
+    QualitySettings { flattening_tolerance: 0.5, layer_scale: 1.0, blur_enabled: true },
+    QualitySettings { flattening_tolerance: 1.0, layer_scale: 0.75, blur_enabled: true },
+    QualitySettings { flattening_tolerance: 1.0, layer_scale: 0.5, blur_enabled: false },
+    QualitySettings { flattening_tolerance: 2.0, layer_scale: 0.5, blur_enabled: false },
+];
+
+/// How many consecutive frames must run over budget before quality is lowered.
+const DEGRADE_AFTER_FRAMES: u32 = 5;
+/// How many consecutive frames must have time to spare before quality is raised. This is much
+/// longer than `DEGRADE_AFTER_FRAMES` so that the quality doesn't flicker back and forth.
+const RECOVER_AFTER_FRAMES: u32 = 60;
+/// Frames must take less than this fraction of the budget to count as having time to spare.
+const RECOVERY_BUDGET_FRACTION: f64 = 0.6;
+/// The weight of the newest frame in the running average of frame times.
+const SMOOTHING_FACTOR: f64 = 0.25;
+
+/// Watches how long frames take to build and render, and picks quality settings to keep them
+/// within a budget.
+pub struct QualityGovernor {
+    frame_budget: Duration,
+    level: usize,
+    average_frame_time: Option<f64>,
+    frames_over_budget: u32,
+    frames_under_budget: u32,
+    quality_change_callback: Option<Box<dyn FnMut(QualityChange)>>,
+}
+
+/// Describes a change in quality made by a `QualityGovernor`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityChange {
+    /// The degradation level before the change. Level 0 is full quality.
+    pub old_level: usize,
+    /// The degradation level after the change.
+    pub new_level: usize,
+    /// The settings for the new level.
+    pub settings: QualitySettings,
+}
+
+impl QualityGovernor {
+    /// Creates a governor that aims to keep frames within `frame_budget`, starting at full
+    /// quality.
+    #[inline]
+    pub fn new(frame_budget: Duration) -> QualityGovernor {
+        QualityGovernor {
+            frame_budget,
+            level: 0,
+            average_frame_time: None,
+            frames_over_budget: 0,
+            frames_under_budget: 0,
+            quality_change_callback: None,
+        }
+    }
+
+    /// Calls `callback` whenever the governor changes the quality, so that the app can show an
+    /// indicator while quality is reduced.
+    pub fn set_quality_change_callback<F>(&mut self, callback: F)
+                                          where F: FnMut(QualityChange) + 'static {
+        self.quality_change_callback = Some(Box::new(callback));
+    }
+
+    #[inline]
+    pub fn frame_budget(&self) -> Duration {
+        self.frame_budget
+    }
+
+    #[inline]
+    pub fn set_frame_budget(&mut self, new_frame_budget: Duration) {
+        self.frame_budget = new_frame_budget;
+        self.frames_over_budget = 0;
+        self.frames_under_budget = 0;
+    }
+
+    /// Returns how far quality has been lowered. Level 0 is full quality.
+    #[inline]
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Returns the number of the lowest quality level.
+    #[inline]
+    pub fn max_level(&self) -> usize {
+        QUALITY_LEVELS.len() - 1
+    }
+
+    /// Returns true if quality is currently lowered.
+    #[inline]
+    pub fn is_degraded(&self) -> bool {
+        self.level > 0
+    }
+
+    #[inline]
+    pub fn settings(&self) -> QualitySettings {
+        QUALITY_LEVELS[self.level]
+    }
+
+    /// Copies the current quality settings into `options`.
+    #[inline]
+    pub fn apply(&self, options: &mut BuildOptions) {
+        options.quality = self.settings();
+    }
+
+    /// Records the statistics of a frame and adjusts the quality if necessary.
+    ///
+    /// GPU times arrive some frames late, if at all, so pass whatever
+    /// `Renderer::shift_rendering_time()` returned this frame. Building and rendering overlap
+    /// across frames, so the slower of the two is what limits the frame rate.
+    pub fn record_frame(&mut self, stats: &RenderStats, render_time: Option<RenderTime>) {
+        let gpu_time = render_time.map_or(Duration::default(), |time| time.gpu_time);
+        self.record_frame_time(stats.cpu_build_time.max(gpu_time));
+    }
+
+    /// Records how long a frame took and adjusts the quality if necessary.
+    pub fn record_frame_time(&mut self, frame_time: Duration) {
+        let frame_time = frame_time.as_secs_f64();
+        let average_frame_time = match self.average_frame_time {
+            None => frame_time,
+            Some(average) => average + (frame_time - average) * SMOOTHING_FACTOR,
+        };
+        self.average_frame_time = Some(average_frame_time);
+
+        let frame_budget = self.frame_budget.as_secs_f64();
+        if average_frame_time > frame_budget {
+            self.frames_under_budget = 0;
+            self.frames_over_budget += 1;
+            if self.frames_over_budget >= DEGRADE_AFTER_FRAMES && self.level < self.max_level() {
+                self.set_level(self.level + 1);
+            }
+        } else if average_frame_time < frame_budget * RECOVERY_BUDGET_FRACTION {
+            self.frames_over_budget = 0;
+            self.frames_under_budget += 1;
+            if self.frames_under_budget >= RECOVER_AFTER_FRAMES && self.level > 0 {
+                self.set_level(self.level - 1);
+            }
+        } else {
+            self.frames_over_budget = 0;
+            self.frames_under_budget = 0;
+        }
+    }
+
+    /// Returns to full quality and forgets all recorded frames.
+    pub fn reset(&mut self) {
+        self.set_level(0);
+    }
+
+    fn set_level(&mut self, new_level: usize) {
+        let old_level = self.level;
+        self.level = new_level;
+
+        // Frame times at the old level say nothing about the new one.
+        self.average_frame_time = None;
+        self.frames_over_budget = 0;
+        self.frames_under_budget = 0;
+
+        if old_level == new_level {
+            return;
+        }
+        if let Some(ref mut callback) = self.quality_change_callback {
+            callback(QualityChange { old_level, new_level, settings: QUALITY_LEVELS[new_level] });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::gpu::renderer::{RenderStats, RenderTime};
+    use crate::options::QualitySettings;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use super::{DEGRADE_AFTER_FRAMES, QualityChange, QualityGovernor, RECOVER_AFTER_FRAMES};
+
+    const BUDGET: Duration = Duration::from_millis(16);
+
+    fn record_frames(governor: &mut QualityGovernor, count: u32, milliseconds: u64) {
+        for _ in 0..count {
+            governor.record_frame_time(Duration::from_millis(milliseconds));
+        }
+    }
+
+    #[test]
+    fn test_spikes_are_ignored() {
+        let mut governor = QualityGovernor::new(BUDGET);
+        record_frames(&mut governor, 10, 8);
+        record_frames(&mut governor, 1, 30);
+        record_frames(&mut governor, 10, 8);
+        assert!(!governor.is_degraded());
+    }
+
+    #[test]
+    fn test_degradation_and_recovery() {
+        let changes = Rc::new(RefCell::new(vec![]));
+        let mut governor = QualityGovernor::new(BUDGET);
+        let recorded_changes = changes.clone();
+        governor.set_quality_change_callback(move |change| {
+            recorded_changes.borrow_mut().push(change)
+        });
+        assert_eq!(governor.settings(), QualitySettings::default());
+
+        record_frames(&mut governor, DEGRADE_AFTER_FRAMES - 1, 40);
+        assert!(!governor.is_degraded());
+        record_frames(&mut governor, 1, 40);
+        assert_eq!(governor.level(), 1);
+        assert!(governor.settings().flattening_tolerance >
+                QualitySettings::default().flattening_tolerance);
+        assert_eq!(*changes.borrow(), vec![QualityChange {
+            old_level: 0,
+            new_level: 1,
+            settings: governor.settings(),
+        }]);
+
+        // Persistently slow frames lower quality all the way, and then it stays there.
+        record_frames(&mut governor, DEGRADE_AFTER_FRAMES * 10, 40);
+        assert_eq!(governor.level(), governor.max_level());
+        assert!(!governor.settings().blur_enabled);
+        assert_eq!(changes.borrow().len(), governor.max_level());
+
+        // Frames within budget but without much to spare don't raise quality.
+        record_frames(&mut governor, RECOVER_AFTER_FRAMES * 2, 14);
+        assert_eq!(governor.level(), governor.max_level());
+
+        // Fast frames raise it one level at a time, once the average catches up with them.
+        record_frames(&mut governor, RECOVER_AFTER_FRAMES + 5, 4);
+        assert_eq!(governor.level(), governor.max_level() - 1);
+
+        governor.reset();
+        assert!(!governor.is_degraded());
+        assert_eq!(changes.borrow().last().unwrap().new_level, 0);
+    }
+
+    #[test]
+    fn test_gpu_time_counts() {
+        let mut governor = QualityGovernor::new(BUDGET);
+        let stats = RenderStats {
+            cpu_build_time: Duration::from_millis(2),
+            ..RenderStats::default()
+        };
+        let render_time = RenderTime {
+            gpu_time: Duration::from_millis(30),
+            ..RenderTime::default()
+        };
+        for _ in 0..DEGRADE_AFTER_FRAMES {
+            governor.record_frame(&stats, Some(render_time));
+        }
+        assert!(governor.is_degraded());
+    }
+}
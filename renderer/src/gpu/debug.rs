@@ -16,9 +16,14 @@
 //! The debug font atlas was generated using: https://evanw.github.io/font-texture-generator/
 
 use crate::gpu::renderer::{RenderStats, RenderTime};
-use pathfinder_geometry::vector::{Vector2I, vec2i};
+use crate::scene::{ClipPath, RenderTargetInfo};
+use pathfinder_color::ColorU;
+use pathfinder_content::outline::ContourIterFlags;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2i};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_gpu::Device;
+use pathfinder_gpu::error::RenderError;
 use pathfinder_resources::ResourceLoader;
 use pathfinder_ui::{FONT_ASCENT, LINE_HEIGHT, PADDING, UIPresenter, WINDOW_COLOR};
 use std::collections::VecDeque;
@@ -28,11 +33,19 @@ use std::time::Duration;
 const SAMPLE_BUFFER_SIZE: usize = 60;
 
 const STATS_WINDOW_WIDTH: i32 = 325;
-const STATS_WINDOW_HEIGHT: i32 = LINE_HEIGHT * 4 + PADDING + 2;
+const STATS_WINDOW_HEIGHT: i32 = LINE_HEIGHT * 7 + PADDING + 2;
 
 const PERFORMANCE_WINDOW_WIDTH: i32 = 400;
 const PERFORMANCE_WINDOW_HEIGHT: i32 = LINE_HEIGHT * 4 + PADDING + 2;
 
+const RENDER_TARGET_WINDOW_WIDTH: i32 = 450;
+// Past this many render targets, the rest are summed up in one line.
+const MAX_RENDER_TARGET_ROWS: usize = 12;
+
+const CLIP_PATH_COLOR: ColorU = ColorU { r: 0, g: 255, b: 255, a: 255 };
+const INVERTED_CLIP_PATH_COLOR: ColorU = ColorU { r: 255, g: 160, b: 0, a: 255 };
+const CLIP_PATH_CURVE_SAMPLES: u32 = 8;
+
 pub struct DebugUIPresenter<D>
 where
     D: Device,
@@ -50,13 +63,13 @@ where
         device: &D,
         resources: &dyn ResourceLoader,
         framebuffer_size: Vector2I,
-    ) -> DebugUIPresenter<D> {
-        let ui_presenter = UIPresenter::new(device, resources, framebuffer_size);
-        DebugUIPresenter {
+    ) -> Result<DebugUIPresenter<D>, RenderError> {
+        let ui_presenter = UIPresenter::new(device, resources, framebuffer_size)?;
+        Ok(DebugUIPresenter {
             ui_presenter,
             cpu_samples: SampleBuffer::new(),
             gpu_samples: SampleBuffer::new(),
-        }
+        })
     }
 
     pub fn add_sample(&mut self, stats: RenderStats, rendering_time: RenderTime) {
@@ -106,6 +119,25 @@ where
             origin + vec2i(0, LINE_HEIGHT * 3),
             false,
         );
+        self.ui_presenter.draw_text(
+            device,
+            &format!("Draw Calls: {}", mean_cpu_sample.draw_call_count),
+            origin + vec2i(0, LINE_HEIGHT * 4),
+            false,
+        );
+        self.ui_presenter.draw_text(
+            device,
+            &format!("Uploaded: {:.1} KB", bytes_to_kb(mean_cpu_sample.bytes_uploaded)),
+            origin + vec2i(0, LINE_HEIGHT * 5),
+            false,
+        );
+        self.ui_presenter.draw_text(
+            device,
+            &format!("Texture Memory: {:.1} MB",
+                     bytes_to_kb(mean_cpu_sample.texture_memory_bytes) / 1024.0),
+            origin + vec2i(0, LINE_HEIGHT * 6),
+            false,
+        );
     }
 
     fn draw_performance_window(&self, device: &D) {
@@ -146,6 +178,100 @@ where
         );
     }
 
+    /// Lists `render_targets`, as returned by `Scene::render_target_hierarchy()`, in a window in
+    /// the top right corner, indented by nesting, with their sizes, memory use, and path counts.
+    pub fn draw_render_target_window(&self, device: &D, render_targets: &[RenderTargetInfo]) {
+        let row_count = render_targets.len().min(MAX_RENDER_TARGET_ROWS + 1) as i32;
+        let framebuffer_size = self.ui_presenter.framebuffer_size();
+        let window_rect = RectI::new(
+            vec2i(framebuffer_size.x() - PADDING - RENDER_TARGET_WINDOW_WIDTH, PADDING),
+            vec2i(RENDER_TARGET_WINDOW_WIDTH, LINE_HEIGHT * (row_count + 1) + PADDING + 2),
+        );
+
+        self.ui_presenter.draw_solid_rounded_rect(device, window_rect, WINDOW_COLOR);
+
+        let total_bytes: usize = render_targets.iter().map(RenderTargetInfo::memory_bytes).sum();
+        let origin = window_rect.origin() + vec2i(PADDING, PADDING + FONT_ASCENT);
+        self.ui_presenter.draw_text(
+            device,
+            &format!("Render Targets: {} ({:.1} MB)",
+                     render_targets.len(),
+                     bytes_to_kb(total_bytes) / 1024.0),
+            origin,
+            false,
+        );
+
+        let mut row_origin = origin;
+        for render_target in render_targets.iter().take(MAX_RENDER_TARGET_ROWS) {
+            row_origin += vec2i(0, LINE_HEIGHT);
+            let name = if render_target.name.is_empty() {
+                format!("#{}", render_target.id.render_target)
+            } else {
+                render_target.name.clone()
+            };
+            self.ui_presenter.draw_text(
+                device,
+                &format!("{:indent$}{} {}x{}, {:.1} KB, {} paths ({} clipped)",
+                         "",
+                         name,
+                         render_target.size.x(),
+                         render_target.size.y(),
+                         bytes_to_kb(render_target.memory_bytes()),
+                         render_target.path_count,
+                         render_target.clipped_path_count,
+                         indent = render_target.depth as usize * 2),
+                row_origin,
+                false,
+            );
+        }
+
+        if render_targets.len() > MAX_RENDER_TARGET_ROWS {
+            self.ui_presenter.draw_text(
+                device,
+                &format!("...and {} more", render_targets.len() - MAX_RENDER_TARGET_ROWS),
+                row_origin + vec2i(0, LINE_HEIGHT),
+                false,
+            );
+        }
+    }
+
+    /// Outlines `clip_paths`, such as those returned by `Scene::active_clip_paths()`, over the
+    /// frame and labels them with their names. `transform` takes them to framebuffer pixels.
+    ///
+    /// Inverted clip paths are drawn in a different color, since they clip out their insides.
+    pub fn push_clip_path_outlines<'a, I>(&mut self, clip_paths: I, transform: &Transform2F)
+                                          where I: IntoIterator<Item = &'a ClipPath> {
+        let debug_draw_list = &mut self.ui_presenter.debug_draw_list;
+        let old_transform = debug_draw_list.transform();
+        debug_draw_list.set_transform(transform);
+
+        for clip_path in clip_paths {
+            let color = if clip_path.is_inverted() {
+                INVERTED_CLIP_PATH_COLOR
+            } else {
+                CLIP_PATH_COLOR
+            };
+            for contour in clip_path.outline().contours() {
+                for segment in contour.iter(ContourIterFlags::empty()) {
+                    if segment.is_line() {
+                        debug_draw_list.line(segment.baseline.from(), segment.baseline.to(), color);
+                        continue;
+                    }
+                    let points: Vec<Vector2F> = (0..(CLIP_PATH_CURVE_SAMPLES + 1)).map(|index| {
+                        segment.sample(index as f32 / CLIP_PATH_CURVE_SAMPLES as f32)
+                    }).collect();
+                    debug_draw_list.polyline(&points, false, color);
+                }
+            }
+            if !clip_path.name().is_empty() {
+                debug_draw_list.label(clip_path.outline().bounds().origin(),
+                                      clip_path.name(),
+                                      color);
+            }
+        }
+
+        debug_draw_list.set_transform(&old_transform);
+    }
 }
 
 struct SampleBuffer<S>
@@ -215,3 +341,7 @@ impl Div<usize> for CPUSample {
 fn duration_to_ms(time: Duration) -> f64 {
     time.as_secs() as f64 * 1000.0 + time.subsec_nanos() as f64 / 1000000.0
 }
+
+fn bytes_to_kb(bytes: usize) -> f64 {
+    bytes as f64 / 1024.0
+}
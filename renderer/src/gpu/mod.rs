@@ -12,6 +12,8 @@
 
 pub mod debug;
 pub mod options;
+pub mod picking;
+pub mod recorder;
 pub mod renderer;
 
 pub(crate) mod shaders;
@@ -11,13 +11,104 @@
 use pathfinder_color::ColorF;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
-use pathfinder_gpu::Device;
+use pathfinder_gpu::{BlendFactor, BlendState, Device};
 
 /// Options that influence rendering.
-#[derive(Default)]
+///
+/// How the destination framebuffer is cleared can change with every render, so it's set with
+/// `Renderer::set_clear_mode()` instead.
+#[derive(Clone, Default)]
 pub struct RendererOptions {
-    pub background_color: Option<ColorF>,
+    /// Whether to rasterize fills even if the device supports compute shaders. This is ignored
+    /// if the device can't render to half-float textures, which rasterized fills need. Devices
+    /// without compute shaders always rasterize.
     pub no_compute: bool,
+    /// Whether to add a small amount of noise to the output to hide banding in smooth gradients
+    /// when rendering to 8-bit-per-channel targets.
+    pub dithering: bool,
+    /// Whether the output keeps a meaningful alpha channel or is flattened to be opaque.
+    pub output_alpha: OutputAlpha,
+}
+
+/// How the destination framebuffer is initialized before a scene is drawn to it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClearMode {
+    /// Clear to the given color. The color is premultiplied by its alpha before clearing.
+    Color(ColorF),
+    /// Clear to transparent black.
+    Transparent,
+    /// Don't clear at all; the scene is drawn over the existing contents of the framebuffer.
+    ///
+    /// Exotic blend modes used at the top level (outside any render target) then blend against
+    /// a transparent backdrop rather than the existing contents.
+    Preserve,
+}
+
+/// What the alpha channel of the destination framebuffer holds after a scene is drawn.
+///
+/// Either way, color values in the framebuffer are premultiplied by alpha. When reading pixels
+/// back for export to formats like PNG that expect straight alpha, unpremultiply them with
+/// `pathfinder_color::unpremultiply_u8_slice()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputAlpha {
+    /// The alpha channel holds the coverage of the scene (and of the clear color), so that the
+    /// output can be composited into another UI. This is the default.
+    Premultiplied,
+    /// The output is flattened onto the given color, whose alpha is ignored, so that every pixel
+    /// is opaque. Use this when presenting to a surface that the system compositor would
+    /// otherwise blend with what's behind the window.
+    Opaque(ColorF),
+}
+
+impl Default for OutputAlpha {
+    #[inline]
+    fn default() -> OutputAlpha {
+        OutputAlpha::Premultiplied
+    }
+}
+
+impl Default for ClearMode {
+    #[inline]
+    fn default() -> ClearMode {
+        ClearMode::Preserve
+    }
+}
+
+impl ClearMode {
+    /// Returns the premultiplied color to clear to, or `None` if the framebuffer should be left
+    /// alone.
+    #[inline]
+    pub fn clear_color(self) -> Option<ColorF> {
+        match self {
+            ClearMode::Color(color) => {
+                Some(ColorF::new(color.r() * color.a(),
+                                 color.g() * color.a(),
+                                 color.b() * color.a(),
+                                 color.a()))
+            }
+            ClearMode::Transparent => Some(ColorF::transparent_black()),
+            ClearMode::Preserve => None,
+        }
+    }
+
+    // Returns how the intermediate framebuffer, when one is needed, is blitted to the
+    // destination. If the destination contents are to be preserved, the intermediate
+    // framebuffer started out transparent, so it's composited over the destination with
+    // premultiplied source-over instead of replacing it.
+    pub(crate) fn intermediate_blit_blend_state(self) -> Option<BlendState> {
+        match self {
+            ClearMode::Preserve => {
+                Some(BlendState {
+                    src_rgb_factor: BlendFactor::One,
+                    src_alpha_factor: BlendFactor::One,
+                    dest_rgb_factor: BlendFactor::OneMinusSrcAlpha,
+                    dest_alpha_factor: BlendFactor::OneMinusSrcAlpha,
+                    ..BlendState::default()
+                })
+            }
+            ClearMode::Color(_) | ClearMode::Transparent => None,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -56,3 +147,64 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use pathfinder_color::ColorF;
+    use pathfinder_gpu::{BlendFactor, BlendOp, BlendState};
+    use super::ClearMode;
+
+    // Blends premultiplied `src` onto `dest` the way the GPU would.
+    fn blend(state: &BlendState, src: ColorF, dest: ColorF) -> ColorF {
+        let factor = |factor, component| {
+            match factor {
+                BlendFactor::Zero => 0.0,
+                BlendFactor::One => 1.0,
+                BlendFactor::SrcAlpha => src.a(),
+                BlendFactor::OneMinusSrcAlpha => 1.0 - src.a(),
+                BlendFactor::DestAlpha => dest.a(),
+                BlendFactor::OneMinusDestAlpha => 1.0 - dest.a(),
+                BlendFactor::DestColor => dest.0[component],
+                BlendFactor::OneMinusSrcColor => 1.0 - src.0[component],
+                BlendFactor::Src1Color | BlendFactor::OneMinusSrc1Color => unreachable!(),
+            }
+        };
+        assert_eq!(state.op, BlendOp::Add);
+        let mut result = ColorF::default();
+        for component in 0..4 {
+            let (src_factor, dest_factor) = if component < 3 {
+                (state.src_rgb_factor, state.dest_rgb_factor)
+            } else {
+                (state.src_alpha_factor, state.dest_alpha_factor)
+            };
+            result.0[component] = src.0[component] * factor(src_factor, component) +
+                dest.0[component] * factor(dest_factor, component);
+        }
+        result
+    }
+
+    #[test]
+    fn test_preserve_composites_source_over() {
+        let blend_state = ClearMode::Preserve.intermediate_blit_blend_state().unwrap();
+
+        // Half-transparent red, premultiplied, over opaque blue.
+        let src = ColorF::new(0.5, 0.0, 0.0, 0.5);
+        let dest = ColorF::new(0.0, 0.0, 1.0, 1.0);
+        assert_eq!(blend(&blend_state, src, dest), ColorF::new(0.5, 0.0, 0.5, 1.0));
+
+        // Where nothing was drawn, the destination shows through untouched, and opaque pixels
+        // replace it.
+        assert_eq!(blend(&blend_state, ColorF::transparent_black(), dest), dest);
+        let opaque = ColorF::new(0.25, 0.5, 0.75, 1.0);
+        assert_eq!(blend(&blend_state, opaque, dest), opaque);
+
+        // Over a translucent destination, the alpha channel accumulates coverage.
+        let dest = ColorF::new(0.0, 0.5, 0.0, 0.5);
+        assert_eq!(blend(&blend_state, src, dest), ColorF::new(0.5, 0.25, 0.0, 0.75));
+
+        // Modes that clear the destination have nothing to composite over.
+        assert!(ClearMode::Transparent.intermediate_blit_blend_state().is_none());
+        let color = ClearMode::Color(ColorF::white());
+        assert!(color.intermediate_blit_blend_state().is_none());
+    }
+}
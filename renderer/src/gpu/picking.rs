@@ -0,0 +1,322 @@
+// pathfinder/renderer/src/gpu/picking.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Pixel-exact picking on the GPU, for scenes too dense to hit test path by path on the CPU.
+//!
+//! Paths with a hit test ID (see `DrawPath::set_hit_test_id()`) are drawn into an offscreen ID
+//! buffer, each in a color that encodes its ID, and paths without one are drawn in a color that
+//! means "nothing here", so that they still hide whatever is beneath them. The buffer is read
+//! back asynchronously, and `pick()` answers from the most recent readback that has arrived.
+//!
+//! Only paths drawn directly to the scene's destination can be picked, not those drawn into
+//! render targets. Pixels on antialiased edges, where two colors blend, usually report no ID.
+
+use crate::concurrent::executor::Executor;
+use crate::gpu::options::{ClearMode, DestFramebuffer, OutputAlpha, RendererOptions};
+use crate::gpu::renderer::Renderer;
+use crate::options::BuildOptions;
+use crate::paint::{Paint, PaintId};
+use crate::scene::{DisplayItem, DrawPath, Scene};
+use pathfinder_color::ColorU;
+use pathfinder_content::effects::BlendMode;
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_gpu::{Device, RenderTarget, TextureData, TextureFormat};
+use pathfinder_gpu::error::RenderError;
+
+// Paint IDs are 16 bits wide, and one paint goes to paths without IDs, so this many distinct IDs
+// fit in one pass. Scenes with more IDs than this are drawn in several passes.
+const IDS_PER_PASS: usize = 65535;
+
+/// An offscreen buffer of hit test IDs.
+pub struct PickBuffer<D> where D: Device {
+    size: Vector2I,
+    passes: Vec<PickPass<D>>,
+}
+
+struct PickPass<D> where D: Device {
+    // Lent to the renderer while the pass is being drawn.
+    framebuffer: Option<D::Framebuffer>,
+    pending: Option<PendingReadback<D>>,
+    // The last readback that arrived, with the IDs that its pixels index.
+    pixels: Vec<u8>,
+    ids: Vec<u32>,
+}
+
+struct PendingReadback<D> where D: Device {
+    receiver: D::TextureDataReceiver,
+    ids: Vec<u32>,
+}
+
+impl<D> PickBuffer<D> where D: Device {
+    /// Creates an ID buffer of `size` pixels, which should match the size of the framebuffer
+    /// that the scene is normally drawn to.
+    #[inline]
+    pub fn new(size: Vector2I) -> PickBuffer<D> {
+        PickBuffer { size, passes: vec![] }
+    }
+
+    #[inline]
+    pub fn size(&self) -> Vector2I {
+        self.size
+    }
+
+    /// Changes the size of the buffer. Until the next `render()`, nothing can be picked.
+    pub fn set_size(&mut self, new_size: Vector2I) {
+        if new_size != self.size {
+            self.size = new_size;
+            self.passes.clear();
+        }
+    }
+
+    /// Draws the IDs of the paths in `scene` and starts reading them back.
+    ///
+    /// `build_options` should be the same as those used to draw the scene normally, so that the
+    /// IDs line up with what's on screen. The renderer's destination framebuffer and options
+    /// are restored afterward, even if rendering fails.
+    pub fn render<E>(&mut self,
+                     renderer: &mut Renderer<D>,
+                     scene: &Scene,
+                     build_options: BuildOptions,
+                     executor: &E)
+                     -> Result<(), RenderError>
+                     where E: Executor {
+        let id_scenes = build_id_scenes(scene);
+        while self.passes.len() < id_scenes.len() {
+            self.passes.push(PickPass::new(&renderer.device, self.size));
+        }
+        self.passes.truncate(id_scenes.len());
+
+        let original_options = renderer.options().clone();
+        let original_clear_mode = renderer.clear_mode();
+        renderer.set_clear_mode(ClearMode::Transparent);
+        renderer.set_options(RendererOptions {
+            dithering: false,
+            output_alpha: OutputAlpha::Premultiplied,
+            ..original_options.clone()
+        });
+
+        let viewport = RectI::new(Vector2I::zero(), self.size);
+        for (pass, (mut id_scene, ids)) in self.passes.iter_mut().zip(id_scenes) {
+            let framebuffer = pass.framebuffer.take().unwrap();
+            let original_dest_framebuffer =
+                renderer.replace_dest_framebuffer(DestFramebuffer::Other(framebuffer));
+
+            let (sender, receiver) = crossbeam_channel::unbounded();
+            let listener = Box::new(move |command| drop(sender.send(command)));
+            id_scene.build(BuildOptions {
+                subpixel_aa_enabled: false,
+                build_handle: None,
+                tile_data_recorder: None,
+                ..build_options.clone()
+            }, listener, executor);
+            renderer.begin_scene();
+            let result = receiver.try_iter().try_for_each(|command| {
+                renderer.render_command(&command)
+            });
+            renderer.end_scene();
+
+            match renderer.replace_dest_framebuffer(original_dest_framebuffer) {
+                DestFramebuffer::Other(framebuffer) => pass.framebuffer = Some(framebuffer),
+                DestFramebuffer::Default { .. } => unreachable!(),
+            }
+            if let Err(error) = result {
+                renderer.set_options(original_options);
+                renderer.set_clear_mode(original_clear_mode);
+                return Err(error);
+            }
+
+            let target = RenderTarget::Framebuffer(pass.framebuffer.as_ref().unwrap());
+            let receiver = renderer.device.read_pixels(&target, viewport);
+            pass.pending = Some(PendingReadback { receiver, ids });
+        }
+
+        renderer.set_options(original_options);
+        renderer.set_clear_mode(original_clear_mode);
+        Ok(())
+    }
+
+    /// Picks up readbacks that have arrived, without blocking. Returns true if any did.
+    pub fn poll(&mut self, device: &D) -> bool {
+        let mut received = false;
+        for pass in &mut self.passes {
+            received |= pass.receive(device, false);
+        }
+        received
+    }
+
+    /// Waits for all outstanding readbacks.
+    pub fn finish(&mut self, device: &D) {
+        for pass in &mut self.passes {
+            pass.receive(device, true);
+        }
+    }
+
+    /// Returns the ID of the topmost path covering `position`, in pixels from the top left, as
+    /// of the most recent readback.
+    pub fn pick(&self, position: Vector2I) -> Option<u32> {
+        if position.x() < 0 || position.y() < 0 ||
+                position.x() >= self.size.x() || position.y() >= self.size.y() {
+            return None;
+        }
+        let pixel_index = (position.y() * self.size.x() + position.x()) as usize;
+        self.passes.iter().filter_map(|pass| pass.pick(pixel_index)).next()
+    }
+}
+
+impl<D> PickPass<D> where D: Device {
+    fn new(device: &D, size: Vector2I) -> PickPass<D> {
+        let texture = device.create_texture(TextureFormat::RGBA8, size);
+        PickPass {
+            framebuffer: Some(device.create_framebuffer(texture)),
+            pending: None,
+            pixels: vec![],
+            ids: vec![],
+        }
+    }
+
+    fn receive(&mut self, device: &D, block: bool) -> bool {
+        let texture_data = match self.pending {
+            None => return false,
+            Some(ref pending) if block => device.recv_texture_data(&pending.receiver),
+            Some(ref pending) => {
+                match device.try_recv_texture_data(&pending.receiver) {
+                    None => return false,
+                    Some(texture_data) => texture_data,
+                }
+            }
+        };
+
+        let pending = self.pending.take().unwrap();
+        match texture_data {
+            TextureData::U8(pixels) => self.pixels = pixels,
+            _ => panic!("Unexpected pixel format for the ID buffer!"),
+        }
+        self.ids = pending.ids;
+        true
+    }
+
+    fn pick(&self, pixel_index: usize) -> Option<u32> {
+        let pixel = self.pixels.get(pixel_index * 4..pixel_index * 4 + 4)?;
+        let color = ColorU::new(pixel[0], pixel[1], pixel[2], pixel[3]);
+        match decode_id_color(color) {
+            None | Some(0) => None,
+            Some(id_index) => self.ids.get(id_index as usize - 1).cloned(),
+        }
+    }
+}
+
+// Makes one copy of `scene` per pass, in which every top-level path is painted with the color of
+// its ID within that pass, or with the "nothing here" color if its ID belongs to another pass.
+// Returns each scene along with the IDs that its colors index.
+fn build_id_scenes(scene: &Scene) -> Vec<(Scene, Vec<u32>)> {
+    let mut ids: Vec<u32> = scene.paths.iter().filter_map(DrawPath::hit_test_id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let id_chunks: Vec<&[u32]> = if ids.is_empty() {
+        vec![&[]]
+    } else {
+        ids.chunks(IDS_PER_PASS).collect()
+    };
+
+    id_chunks.into_iter().map(|pass_ids| {
+        let mut id_scene = Scene::new();
+        id_scene.set_view_box(scene.view_box());
+        for clip_path in &scene.clip_paths {
+            id_scene.push_clip_path(clip_path.clone());
+        }
+
+        let empty_paint = id_scene.push_paint(&Paint::from_color(encode_id_color(0)));
+        let id_paints: Vec<PaintId> = (0..pass_ids.len()).map(|id_index| {
+            id_scene.push_paint(&Paint::from_color(encode_id_color(id_index as u16 + 1)))
+        }).collect();
+
+        // Paths drawn into render targets aren't directly visible, so skip them.
+        let mut render_target_depth = 0;
+        for display_item in &scene.display_list {
+            match *display_item {
+                DisplayItem::PushRenderTarget(_) => render_target_depth += 1,
+                DisplayItem::PopRenderTarget => render_target_depth -= 1,
+                DisplayItem::ResolveBackdrop { .. } => {}
+                DisplayItem::DrawPaths { start_index, end_index } => {
+                    if render_target_depth > 0 {
+                        continue;
+                    }
+                    for path in &scene.paths[start_index as usize..end_index as usize] {
+                        if !path.is_visible() {
+                            continue;
+                        }
+                        let paint = match path.hit_test_id() {
+                            None => empty_paint,
+                            Some(id) => {
+                                match pass_ids.binary_search(&id) {
+                                    Ok(id_index) => id_paints[id_index],
+                                    Err(_) => empty_paint,
+                                }
+                            }
+                        };
+                        let outline = path.shared_outline().clone();
+                        let mut id_path = DrawPath::from_shared_outline(outline, paint);
+                        id_path.set_clip_path(path.clip_path());
+                        id_path.set_fill_rule(path.fill_rule());
+                        id_path.set_blend_mode(BlendMode::SrcOver);
+                        id_scene.push_path(id_path);
+                    }
+                }
+            }
+        }
+
+        (id_scene, pass_ids.to_vec())
+    }).collect()
+}
+
+// The index goes in red and green, and blue holds a check byte, so that the blend of two ID
+// colors along an antialiased edge is very unlikely to decode as a third ID.
+fn encode_id_color(id_index: u16) -> ColorU {
+    let (high, low) = ((id_index >> 8) as u8, id_index as u8);
+    ColorU::new(high, low, check_byte(high, low), 255)
+}
+
+fn decode_id_color(color: ColorU) -> Option<u16> {
+    if color.a != 255 || color.b != check_byte(color.r, color.g) {
+        return None;
+    }
+    Some(((color.r as u16) << 8) | color.g as u16)
+}
+
+fn check_byte(high: u8, low: u8) -> u8 {
+    high.wrapping_mul(31) ^ low.wrapping_mul(151) ^ 0xa5
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode_id_color, encode_id_color};
+    use pathfinder_color::ColorU;
+
+    #[test]
+    fn test_id_colors() {
+        for &id_index in &[0, 1, 255, 256, 12345, 65535] {
+            assert_eq!(decode_id_color(encode_id_color(id_index)), Some(id_index));
+        }
+
+        // Background and antialiased edges don't decode.
+        assert_eq!(decode_id_color(ColorU::transparent_black()), None);
+        let mut edge = encode_id_color(1);
+        edge.a = 128;
+        assert_eq!(decode_id_color(edge), None);
+        let (a, b) = (encode_id_color(1), encode_id_color(2));
+        let blend = ColorU::new(((a.r as u16 + b.r as u16) / 2) as u8,
+                                ((a.g as u16 + b.g as u16) / 2) as u8,
+                                ((a.b as u16 + b.b as u16) / 2) as u8,
+                                255);
+        assert_eq!(decode_id_color(blend), None);
+    }
+}
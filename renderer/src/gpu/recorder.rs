@@ -0,0 +1,137 @@
+// pathfinder/renderer/src/gpu/recorder.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Records rendered frames, for screenshots and videos.
+//!
+//! Frames are read back with the device's asynchronous `read_pixels()`, so recording doesn't
+//! stall the GPU pipeline. Completed frames are handed to a `FrameSink` in the order they were
+//! captured.
+
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_gpu::{Device, RenderTarget, TextureData};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+// The number of readbacks that may be in flight before `capture()` blocks on the oldest one.
+const MAX_FRAMES_IN_FLIGHT: usize = 3;
+
+/// Somewhere to put recorded frames.
+pub trait FrameSink {
+    /// Receives one frame of tightly-packed, non-premultiplied RGBA8 pixels, top row first.
+    fn write_frame(&mut self, frame_index: u32, size: Vector2I, pixels: &[u8]) -> io::Result<()>;
+}
+
+/// Writes frames back to back as raw RGBA8, e.g. for piping into
+/// `ffmpeg -f rawvideo -pix_fmt rgba -s WIDTHxHEIGHT -i -`.
+///
+/// The stream carries no header, so every frame must be the same size.
+pub struct RawFrameSink<W> where W: Write {
+    writer: W,
+}
+
+pub struct FrameRecorder<D, S> where D: Device, S: FrameSink {
+    sink: S,
+    pending_frames: VecDeque<PendingFrame<D>>,
+    next_frame_index: u32,
+}
+
+struct PendingFrame<D> where D: Device {
+    receiver: D::TextureDataReceiver,
+    size: Vector2I,
+}
+
+impl<D, S> FrameRecorder<D, S> where D: Device, S: FrameSink {
+    #[inline]
+    pub fn new(sink: S) -> FrameRecorder<D, S> {
+        FrameRecorder { sink, pending_frames: VecDeque::new(), next_frame_index: 0 }
+    }
+
+    /// Starts reading back `viewport` of `target`. Call this after drawing the frame and before
+    /// presenting it.
+    pub fn capture(&mut self, device: &D, target: &RenderTarget<D>, viewport: RectI)
+                   -> io::Result<()> {
+        if self.pending_frames.len() >= MAX_FRAMES_IN_FLIGHT {
+            self.write_next_frame(device, true)?;
+        }
+        let receiver = device.read_pixels(target, viewport);
+        self.pending_frames.push_back(PendingFrame { receiver, size: viewport.size() });
+        Ok(())
+    }
+
+    /// Writes out any frames whose pixels have arrived, without blocking.
+    pub fn poll(&mut self, device: &D) -> io::Result<()> {
+        while !self.pending_frames.is_empty() {
+            if !self.write_next_frame(device, false)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits for every outstanding frame and writes it out.
+    pub fn finish(&mut self, device: &D) -> io::Result<()> {
+        while !self.pending_frames.is_empty() {
+            self.write_next_frame(device, true)?;
+        }
+        Ok(())
+    }
+
+    /// The number of frames written to the sink so far.
+    #[inline]
+    pub fn frames_written(&self) -> u32 {
+        self.next_frame_index
+    }
+
+    #[inline]
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+
+    fn write_next_frame(&mut self, device: &D, block: bool) -> io::Result<bool> {
+        let texture_data = {
+            let pending_frame = self.pending_frames.front().unwrap();
+            if block {
+                device.recv_texture_data(&pending_frame.receiver)
+            } else {
+                match device.try_recv_texture_data(&pending_frame.receiver) {
+                    None => return Ok(false),
+                    Some(texture_data) => texture_data,
+                }
+            }
+        };
+
+        let pending_frame = self.pending_frames.pop_front().unwrap();
+        let pixels = match texture_data {
+            TextureData::U8(pixels) => pixels,
+            _ => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          "Only RGBA8 framebuffers can be recorded"))
+            }
+        };
+        self.sink.write_frame(self.next_frame_index, pending_frame.size, &pixels)?;
+        self.next_frame_index += 1;
+        Ok(true)
+    }
+}
+
+impl<W> RawFrameSink<W> where W: Write {
+    #[inline]
+    pub fn new(writer: W) -> RawFrameSink<W> {
+        RawFrameSink { writer }
+    }
+}
+
+impl<W> FrameSink for RawFrameSink<W> where W: Write {
+    fn write_frame(&mut self, _: u32, _: Vector2I, pixels: &[u8]) -> io::Result<()> {
+        self.writer.write_all(pixels)?;
+        self.writer.flush()
+    }
+}
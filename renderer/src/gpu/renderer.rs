@@ -8,28 +8,34 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use crate::animation::{AnimatedPathLayout, MAX_ANIMATED_PATHS};
 use crate::gpu::debug::DebugUIPresenter;
-use crate::gpu::options::{DestFramebuffer, RendererOptions};
+use crate::gpu::options::{ClearMode, DestFramebuffer, OutputAlpha, RendererOptions};
 use crate::gpu::shaders::{BlitProgram, BlitVertexArray, ClearProgram, ClearVertexArray};
 use crate::gpu::shaders::{ClipTileProgram, ClipTileVertexArray};
-use crate::gpu::shaders::{CopyTileProgram, CopyTileVertexArray, FillProgram, FillVertexArray};
+use crate::gpu::shaders::{CopyTileProgram, CopyTileVertexArray, DownsampleProgram};
+use crate::gpu::shaders::{DownsampleVertexArray, FillProgram, FillVertexArray};
 use crate::gpu::shaders::{MAX_FILLS_PER_BATCH, MAX_TILES_PER_BATCH, ReprojectionProgram};
 use crate::gpu::shaders::{ReprojectionVertexArray, StencilProgram, StencilVertexArray};
 use crate::gpu::shaders::{TileProgram, TileVertexArray};
 use crate::gpu_data::{ClipBatch, ClipBatchKey, ClipBatchKind, Fill, FillBatchEntry, RenderCommand};
 use crate::gpu_data::{TextureLocation, TextureMetadataEntry, TexturePageDescriptor, TexturePageId};
 use crate::gpu_data::{Tile, TileBatchTexture};
-use crate::options::BoundingQuad;
-use crate::paint::PaintCompositeOp;
-use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
+use crate::concurrent::executor::Executor;
+use crate::options::{BoundingQuad, BuildOptions};
+use crate::paint::{ImageTexelInfo, Paint, PaintCompositeOp, PaintId, PaintLayout, PaintUpdate};
+use crate::scene::{PathId, Scene};
+use crate::tiles::TileSize;
 use fxhash::FxHashMap;
 use half::f16;
 use pathfinder_color::{self as color, ColorF, ColorU};
 use pathfinder_content::effects::{BlendMode, BlurDirection, DefringingKernel};
 use pathfinder_content::effects::{Filter, PatternFilter};
 use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_content::yuv::{self, YuvRange, YuvStandard};
 use pathfinder_geometry::line_segment::LineSegment2F;
-use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_geometry::util;
 use pathfinder_geometry::vector::{Vector2F, Vector2I, Vector4F, vec2f, vec2i};
@@ -37,7 +43,9 @@ use pathfinder_gpu::{BlendFactor, BlendOp, BlendState, BufferData, BufferTarget,
 use pathfinder_gpu::{ClearOps, ComputeDimensions, ComputeState, DepthFunc, DepthState, Device};
 use pathfinder_gpu::{ImageAccess, Primitive, RenderOptions, RenderState, RenderTarget};
 use pathfinder_gpu::{StencilFunc, StencilState, TextureBinding, TextureDataRef, TextureFormat};
+use pathfinder_gpu::TextureSamplingFlags;
 use pathfinder_gpu::{UniformBinding, UniformData};
+use pathfinder_gpu::error::RenderError;
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::{F32x2, F32x4, I32x2};
 use std::collections::VecDeque;
@@ -45,6 +53,7 @@ use std::f32;
 use std::marker::PhantomData;
 use std::mem;
 use std::ops::{Add, Div};
+use std::sync::Arc;
 use std::time::Duration;
 use std::u32;
 
@@ -66,9 +75,15 @@ const TEXTURE_METADATA_ENTRIES_PER_ROW: i32 = 128;
 const TEXTURE_METADATA_TEXTURE_WIDTH:   i32 = TEXTURE_METADATA_ENTRIES_PER_ROW * 4;
 const TEXTURE_METADATA_TEXTURE_HEIGHT:  i32 = 65536 / TEXTURE_METADATA_ENTRIES_PER_ROW;
 
-// FIXME(pcwalton): Shrink this again!
-const MASK_FRAMEBUFFER_WIDTH:  i32 = TILE_WIDTH as i32      * MASK_TILES_ACROSS as i32;
-const MASK_FRAMEBUFFER_HEIGHT: i32 = TILE_HEIGHT as i32 / 4 * MASK_TILES_DOWN as i32;
+// Each transform slot takes two texels: the matrix, then the translation.
+const PATH_TRANSFORMS_TEXTURE_WIDTH:    i32 = (MAX_ANIMATED_PATHS as i32 + 1) * 2;
+const PATH_TRANSFORMS_TEXTURE_HEIGHT:   i32 = 1;
+
+// One 8-bit quantization step: enough noise to hide banding without being visible itself.
+const DITHER_AMOUNT: f32 = 1.0 / 255.0;
+
+// The number of frames rendered per candidate tile size when auto-tuning.
+const AUTO_TUNE_FRAME_COUNT: usize = 3;
 
 const COMBINER_CTRL_COLOR_COMBINE_SRC_IN: i32 =     0x1;
 const COMBINER_CTRL_COLOR_COMBINE_DEST_IN: i32 =    0x2;
@@ -76,6 +91,8 @@ const COMBINER_CTRL_COLOR_COMBINE_DEST_IN: i32 =    0x2;
 const COMBINER_CTRL_FILTER_RADIAL_GRADIENT: i32 =   0x1;
 const COMBINER_CTRL_FILTER_TEXT: i32 =              0x2;
 const COMBINER_CTRL_FILTER_BLUR: i32 =              0x3;
+const COMBINER_CTRL_FILTER_BLURRED_ROUNDED_RECT: i32 = 0x4;
+const COMBINER_CTRL_FILTER_YUV: i32 =               0x5;
 
 const COMBINER_CTRL_COMPOSITE_NORMAL: i32 =         0x0;
 const COMBINER_CTRL_COMPOSITE_MULTIPLY: i32 =       0x1;
@@ -94,9 +111,14 @@ const COMBINER_CTRL_COMPOSITE_SATURATION: i32 =     0xd;
 const COMBINER_CTRL_COMPOSITE_COLOR: i32 =          0xe;
 const COMBINER_CTRL_COMPOSITE_LUMINOSITY: i32 =     0xf;
 
+const COMBINER_CTRL_COMPONENT_ALPHA_DUAL_SOURCE: i32 = 0x1;
+const COMBINER_CTRL_COMPONENT_ALPHA_MASK_PASS: i32 =   0x2;
+const COMBINER_CTRL_COMPONENT_ALPHA_COLOR_PASS: i32 =  0x3;
+
 const COMBINER_CTRL_COLOR_FILTER_SHIFT: i32 =       4;
-const COMBINER_CTRL_COLOR_COMBINE_SHIFT: i32 =      6;
-const COMBINER_CTRL_COMPOSITE_SHIFT: i32 =          8;
+const COMBINER_CTRL_COLOR_COMBINE_SHIFT: i32 =      7;
+const COMBINER_CTRL_COMPOSITE_SHIFT: i32 =          9;
+const COMBINER_CTRL_COMPONENT_ALPHA_SHIFT: i32 =    13;
 
 pub struct Renderer<D> where D: Device {
     // Device
@@ -105,8 +127,10 @@ pub struct Renderer<D> where D: Device {
     // Core data
     dest_framebuffer: DestFramebuffer<D>,
     options: RendererOptions,
+    clear_mode: ClearMode,
     blit_program: BlitProgram<D>,
     clear_program: ClearProgram<D>,
+    downsample_program: DownsampleProgram<D>,
     fill_program: FillProgram<D>,
     tile_program: TileProgram<D>,
     tile_copy_program: CopyTileProgram<D>,
@@ -122,6 +146,7 @@ pub struct Renderer<D> where D: Device {
     render_target_stack: Vec<RenderTargetId>,
     area_lut_texture: D::Texture,
     gamma_lut_texture: D::Texture,
+    tile_size: TileSize,
 
     // Frames
     front_frame: Frame<D>,
@@ -131,6 +156,16 @@ pub struct Renderer<D> where D: Device {
     // Rendering state
     texture_cache: TextureCache<D>,
 
+    // Paints of the last scene rendered, and the updates made to them since.
+    paint_layout: Option<Arc<PaintLayout>>,
+    paint_updates: FxHashMap<PaintId, PaintUpdate>,
+    pending_paint_texels: Vec<ImageTexelInfo>,
+
+    // Animated paths of the last scene rendered, and the device transforms they've been moved by
+    // since.
+    animated_path_layout: Option<Arc<AnimatedPathLayout>>,
+    path_transforms: FxHashMap<PathId, Transform2F>,
+
     // Debug
     pub stats: RenderStats,
     current_cpu_build_time: Option<Duration>,
@@ -145,8 +180,11 @@ pub struct Renderer<D> where D: Device {
 
 struct Frame<D> where D: Device {
     framebuffer_flags: FramebufferFlags,
+    // The clear mode in effect when the frame began.
+    clear_mode: ClearMode,
     blit_vertex_array: BlitVertexArray<D>,
     clear_vertex_array: ClearVertexArray<D>,
+    downsample_vertex_array: DownsampleVertexArray<D>,
     fill_vertex_storage_allocator: StorageAllocator<D, FillVertexStorage<D>>,
     tile_vertex_storage_allocator: StorageAllocator<D, TileVertexStorage<D>>,
     quads_vertex_indices_buffer: D::Buffer,
@@ -158,6 +196,7 @@ struct Frame<D> where D: Device {
     dest_blend_framebuffer: D::Framebuffer,
     intermediate_dest_framebuffer: D::Framebuffer,
     texture_metadata_texture: D::Texture,
+    path_transforms_texture: D::Texture,
 }
 
 impl<D> Renderer<D> where D: Device {
@@ -165,20 +204,21 @@ impl<D> Renderer<D> where D: Device {
                resources: &dyn ResourceLoader,
                dest_framebuffer: DestFramebuffer<D>,
                options: RendererOptions)
-               -> Renderer<D> {
-        let blit_program = BlitProgram::new(&device, resources);
-        let clear_program = ClearProgram::new(&device, resources);
-        let fill_program = FillProgram::new(&device, resources, &options);
-        let tile_program = TileProgram::new(&device, resources);
-        let tile_copy_program = CopyTileProgram::new(&device, resources);
-        let tile_clip_program = ClipTileProgram::new(&device, resources);
-        let stencil_program = StencilProgram::new(&device, resources);
-        let reprojection_program = ReprojectionProgram::new(&device, resources);
+               -> Result<Renderer<D>, RenderError> {
+        let blit_program = BlitProgram::new(&device, resources)?;
+        let clear_program = ClearProgram::new(&device, resources)?;
+        let downsample_program = DownsampleProgram::new(&device, resources)?;
+        let fill_program = FillProgram::new(&device, resources, &options)?;
+        let tile_program = TileProgram::new(&device, resources)?;
+        let tile_copy_program = CopyTileProgram::new(&device, resources)?;
+        let tile_clip_program = ClipTileProgram::new(&device, resources)?;
+        let stencil_program = StencilProgram::new(&device, resources)?;
+        let reprojection_program = ReprojectionProgram::new(&device, resources)?;
 
         let area_lut_texture =
-            device.create_texture_from_png(resources, "area-lut", TextureFormat::RGBA8);
+            device.create_texture_from_png(resources, "area-lut", TextureFormat::RGBA8)?;
         let gamma_lut_texture =
-            device.create_texture_from_png(resources, "gamma-lut", TextureFormat::R8);
+            device.create_texture_from_png(resources, "gamma-lut", TextureFormat::R8)?;
 
         let quad_vertex_positions_buffer = device.create_buffer(BufferUploadMode::Static);
         device.allocate_buffer(&quad_vertex_positions_buffer,
@@ -192,11 +232,12 @@ impl<D> Renderer<D> where D: Device {
         let window_size = dest_framebuffer.window_size(&device);
 
         let timer_query_cache = TimerQueryCache::new(&device);
-        let debug_ui_presenter = DebugUIPresenter::new(&device, resources, window_size);
+        let debug_ui_presenter = DebugUIPresenter::new(&device, resources, window_size)?;
 
         let front_frame = Frame::new(&device,
                                      &blit_program,
                                      &clear_program,
+                                     &downsample_program,
                                      &tile_clip_program,
                                      &reprojection_program,
                                      &stencil_program,
@@ -206,6 +247,7 @@ impl<D> Renderer<D> where D: Device {
         let back_frame = Frame::new(&device,
                                     &blit_program,
                                     &clear_program,
+                                    &downsample_program,
                                     &tile_clip_program,
                                     &reprojection_program,
                                     &stencil_program,
@@ -213,13 +255,15 @@ impl<D> Renderer<D> where D: Device {
                                     &quad_vertex_indices_buffer,
                                     window_size);
 
-        Renderer {
+        Ok(Renderer {
             device,
 
             dest_framebuffer,
             options,
+            clear_mode: ClearMode::default(),
             blit_program,
             clear_program,
+            downsample_program,
             fill_program,
             tile_program,
             tile_copy_program,
@@ -238,6 +282,7 @@ impl<D> Renderer<D> where D: Device {
 
             area_lut_texture,
             gamma_lut_texture,
+            tile_size: TileSize::default(),
 
             stencil_program,
 
@@ -252,12 +297,20 @@ impl<D> Renderer<D> where D: Device {
 
             texture_cache: TextureCache::new(),
 
+            paint_layout: None,
+            paint_updates: FxHashMap::default(),
+            pending_paint_texels: vec![],
+            animated_path_layout: None,
+            path_transforms: FxHashMap::default(),
+
             flags: RendererFlags::empty(),
-        }
+        })
     }
 
     pub fn begin_scene(&mut self) {
+        self.flags.remove(RendererFlags::FRAME_FAILED);
         self.back_frame.framebuffer_flags = FramebufferFlags::empty();
+        self.back_frame.clear_mode = self.clear_mode;
         for alpha_tile_page in self.back_frame.alpha_tile_pages.values_mut() {
             alpha_tile_page.framebuffer_is_dirty = false;
         }
@@ -267,23 +320,54 @@ impl<D> Renderer<D> where D: Device {
         self.stats = RenderStats::default();
     }
 
-    pub fn render_command(&mut self, command: &RenderCommand) {
+    /// Executes one command from the scene builder.
+    ///
+    /// If this fails, the rest of the frame's commands are skipped, since they may refer to
+    /// textures that were never allocated. `end_scene()` must still be called, and whatever was
+    /// drawn before the failure is presented.
+    pub fn render_command(&mut self, command: &RenderCommand) -> Result<(), RenderError> {
+        if self.flags.contains(RendererFlags::FRAME_FAILED) {
+            return Ok(());
+        }
+
         debug!("render command: {:?}", command);
+        let result = self.render_command_unchecked(command);
+        if result.is_err() {
+            self.flags.insert(RendererFlags::FRAME_FAILED);
+        }
+        result
+    }
+
+    fn render_command_unchecked(&mut self, command: &RenderCommand) -> Result<(), RenderError> {
         match *command {
-            RenderCommand::Start { bounding_quad, path_count, needs_readable_framebuffer } => {
-                self.start_rendering(bounding_quad, path_count, needs_readable_framebuffer);
+            RenderCommand::Start {
+                bounding_quad,
+                path_count,
+                needs_readable_framebuffer,
+                tile_size,
+            } => {
+                self.start_rendering(bounding_quad,
+                                     path_count,
+                                     needs_readable_framebuffer,
+                                     tile_size);
             }
             RenderCommand::AllocateTexturePage { page_id, ref descriptor } => {
-                self.allocate_texture_page(page_id, descriptor)
+                self.allocate_texture_page(page_id, descriptor)?
             }
             RenderCommand::UploadTexelData { ref texels, location } => {
-                self.upload_texel_data(texels, location)
+                self.upload_texel_data(texels, location);
+                self.reupload_updated_paint_texels(location);
             }
             RenderCommand::DeclareRenderTarget { id, location } => {
                 self.declare_render_target(id, location)
             }
+            RenderCommand::DeclarePaints(ref layout) => self.declare_paints(layout),
+            RenderCommand::DeclareAnimatedPaths(ref layout) => {
+                self.declare_animated_paths(layout)
+            }
             RenderCommand::UploadTextureMetadata(ref metadata) => {
-                self.upload_texture_metadata(metadata)
+                self.upload_texture_metadata(metadata)?;
+                self.upload_pending_paint_texels();
             }
             RenderCommand::AddFills(ref fills) => self.add_fills(fills),
             RenderCommand::FlushFills => {
@@ -301,9 +385,14 @@ impl<D> Renderer<D> where D: Device {
                 self.push_render_target(render_target_id)
             }
             RenderCommand::PopRenderTarget => self.pop_render_target(),
+            RenderCommand::ResolveBackdrop { render_target, source_rect } => {
+                self.resolve_backdrop(render_target, source_rect)
+            }
             RenderCommand::DrawTiles(ref batch) => {
                 let count = batch.tiles.len();
-                self.stats.alpha_tile_count += count;
+                let solid_tile_count = batch.tiles.iter().filter(|tile| tile.is_solid()).count();
+                self.stats.solid_tile_count += solid_tile_count;
+                self.stats.alpha_tile_count += count - solid_tile_count;
                 let storage_id = self.upload_tiles(&batch.tiles);
                 self.draw_tiles(batch.tile_page,
                                 count as u32,
@@ -316,11 +405,14 @@ impl<D> Renderer<D> where D: Device {
                 self.stats.cpu_build_time = cpu_build_time;
             }
         }
+        Ok(())
     }
 
     pub fn end_scene(&mut self) {
+        self.end_async_compute_if_necessary();
         self.clear_dest_framebuffer_if_necessary();
         self.blit_intermediate_dest_framebuffer_if_necessary();
+        self.flatten_dest_framebuffer_if_necessary();
 
         let old_front_frame_fence = self.front_frame_fence.take();
         self.front_frame_fence = Some(self.device.add_fence());
@@ -333,22 +425,41 @@ impl<D> Renderer<D> where D: Device {
             self.pending_timers.push_back(timer);
         }
         self.current_cpu_build_time = None;
+        self.stats.texture_memory_bytes = self.resident_texture_memory();
 
         if let Some(old_front_frame_fence) = old_front_frame_fence {
             self.device.wait_for_fence(&old_front_frame_fence);
         }
 
+        // Masks only live for a frame, so if the device can hand their memory to the next
+        // frame's masks, recreating them each frame keeps just one frame's worth resident.
+        if self.device.capabilities().aliasable_transient_textures {
+            for (_, alpha_tile_page) in self.back_frame.alpha_tile_pages.drain() {
+                let texture = self.device.destroy_framebuffer(alpha_tile_page.framebuffer);
+                self.device.discard_transient_texture(texture);
+            }
+        }
+
         mem::swap(&mut self.front_frame, &mut self.back_frame);
     }
 
     fn start_rendering(&mut self,
                        bounding_quad: BoundingQuad,
                        path_count: usize,
-                       mut needs_readable_framebuffer: bool) {
+                       mut needs_readable_framebuffer: bool,
+                       tile_size: TileSize) {
         if let DestFramebuffer::Other(_) = self.dest_framebuffer {
             needs_readable_framebuffer = false;
         }
 
+        // Mask framebuffers are sized according to the tile size, so throw them away if it
+        // changed.
+        if tile_size != self.tile_size {
+            self.front_frame.alpha_tile_pages.clear();
+            self.back_frame.alpha_tile_pages.clear();
+            self.tile_size = tile_size;
+        }
+
         if self.flags.contains(RendererFlags::USE_DEPTH) {
             self.draw_stencil(&bounding_quad);
         }
@@ -360,6 +471,84 @@ impl<D> Renderer<D> where D: Device {
         self.render_targets.clear();
     }
 
+    /// Replaces the paint with the given ID in the scene last rendered, without rebuilding it.
+    ///
+    /// This is much cheaper than a rebuild, since no geometry is tiled again, which makes it
+    /// suitable for hover effects, fades, and gradient animations. The new paint takes effect the
+    /// next time the scene's render commands are executed, whether they're replayed from a
+    /// `FrameCapture` or the scene is built again, and stays in effect until a scene with
+    /// different paints is rendered. Call `Scene::set_paint()` as well to keep the scene in step.
+    ///
+    /// Base colors, gradient stops and geometry, pattern images and transforms, and mesh colors
+    /// can all change. The paint must otherwise have the same shape as the one it replaces: the
+    /// same kind of texture, sampling, compositing, and filter, and it can't become translucent
+    /// if it was opaque. If not, this returns `RenderError::IncompatiblePaint`, and the scene has
+    /// to be rebuilt instead.
+    pub fn update_paint(&mut self, paint_id: PaintId, paint: &Paint) -> Result<(), RenderError> {
+        let layout = match self.paint_layout {
+            None => return Err(RenderError::IncompatiblePaint("no scene has been rendered yet")),
+            Some(ref layout) => layout,
+        };
+        let slot = match layout.slots.get(paint_id.0 as usize) {
+            None => return Err(RenderError::IncompatiblePaint("the scene has no such paint")),
+            Some(slot) => slot,
+        };
+
+        let render_targets = &self.render_targets;
+        let update = paint.update_in_slot(slot, layout.render_transform, |render_target_id| {
+            render_targets.get(render_target_id.render_target as usize).map(|render_target| {
+                render_target.location
+            })
+        })?;
+
+        if let Some(ref texels) = update.texels {
+            let old_texels = self.paint_updates.get(&paint_id).and_then(|old_update| {
+                old_update.texels.as_ref()
+            });
+            let texels_changed = match old_texels {
+                None => true,
+                Some(old_texels) => {
+                    !Arc::ptr_eq(&old_texels.texels, &texels.texels) &&
+                        old_texels.texels != texels.texels
+                }
+            };
+            if texels_changed {
+                self.pending_paint_texels.push((*texels).clone());
+            }
+        }
+
+        self.paint_updates.insert(paint_id, update);
+        Ok(())
+    }
+
+    /// Moves an animated path of the scene last rendered, without rebuilding it.
+    ///
+    /// `transform` is in scene coordinates and is relative to the outline the path was built
+    /// with; it replaces any transform set before. Only the tiles of the path are moved, on the
+    /// GPU, which makes this cheap enough to call for every path every frame. Like
+    /// `update_paint()`, the transform takes effect the next time the scene's render commands are
+    /// executed, and stays in effect until a scene with different animated paths, or with them
+    /// in different places, is rendered. See `DrawPath::set_animated()`.
+    ///
+    /// Tiles can only be translated and scaled by up to `MAX_ANIMATED_PATH_SCALE_CHANGE` before
+    /// their masks would visibly blur or alias. Past that, or if the transform rotates or skews
+    /// the path, this returns `RenderError::NeedsRetiling`, and the transform has to be applied
+    /// to the path's outline and the scene rebuilt instead.
+    pub fn set_path_transform(&mut self, path_id: PathId, transform: &Transform2F)
+                              -> Result<(), RenderError> {
+        let layout = match self.animated_path_layout {
+            None => return Err(RenderError::NeedsRetiling("no scene has been rendered yet")),
+            Some(ref layout) => layout,
+        };
+        if layout.slot(path_id).is_none() {
+            return Err(RenderError::NeedsRetiling("the path wasn't built as an animated path"));
+        }
+        let device_transform = layout.device_transform(transform)
+                                     .map_err(RenderError::NeedsRetiling)?;
+        self.path_transforms.insert(path_id, device_transform);
+        Ok(())
+    }
+
     pub fn draw_debug_ui(&self) {
         self.debug_ui_presenter.draw(&self.device);
     }
@@ -369,8 +558,8 @@ impl<D> Renderer<D> where D: Device {
             for old_query in pending_timer.poll(&self.device) {
                 self.timer_query_cache.free(old_query);
             }
-            if let Some(gpu_time) = pending_timer.total_time() {
-                return Some(RenderTime { gpu_time })
+            if let Some(render_time) = pending_timer.render_time() {
+                return Some(render_time)
             }
             self.pending_timers.push_front(pending_timer);
         }
@@ -390,11 +579,91 @@ impl<D> Renderer<D> where D: Device {
         mem::replace(&mut self.dest_framebuffer, new_dest_framebuffer)
     }
 
+    #[inline]
+    pub fn options(&self) -> &RendererOptions {
+        &self.options
+    }
+
     #[inline]
     pub fn set_options(&mut self, new_options: RendererOptions) {
         self.options = new_options
     }
 
+    /// Returns how the destination framebuffer is initialized before each scene is drawn.
+    #[inline]
+    pub fn clear_mode(&self) -> ClearMode {
+        self.clear_mode
+    }
+
+    /// Changes how the destination framebuffer is initialized before each scene is drawn. The
+    /// new mode takes effect at the next `begin_scene()`, so it can differ from one render to the
+    /// next without disturbing a scene that's being drawn.
+    #[inline]
+    pub fn set_clear_mode(&mut self, new_clear_mode: ClearMode) {
+        self.clear_mode = new_clear_mode
+    }
+
+    /// Returns the tile size of the scene most recently rendered.
+    #[inline]
+    pub fn tile_size(&self) -> TileSize {
+        self.tile_size
+    }
+
+    /// Renders the given scene a few times at each supported tile size and returns the size
+    /// that took the least GPU time.
+    ///
+    /// This is intended to be called once at startup with a representative scene, before any
+    /// frames have been rendered, since it waits for all outstanding timer queries. The result
+    /// should be stored in the `tile_size` field of the `BuildOptions` used thereafter.
+    ///
+    /// Returns `None` if no timings could be collected, as happens when the device doesn't
+    /// support timer queries and every frame measures zero. Keep the default tile size then.
+    pub fn auto_tune_tile_size<E>(&mut self,
+                                  scene: &mut Scene,
+                                  options: BuildOptions,
+                                  executor: &E)
+                                  -> Result<Option<TileSize>, RenderError>
+                                  where E: Executor {
+        let mut best: Option<(TileSize, Duration)> = None;
+        for &tile_size in TileSize::ALL.iter() {
+            let mut best_gpu_time: Option<Duration> = None;
+            for _ in 0..AUTO_TUNE_FRAME_COUNT {
+                let (sender, receiver) = crossbeam_channel::unbounded();
+                let listener = Box::new(move |command| drop(sender.send(command)));
+                let options = BuildOptions { tile_size, ..options.clone() };
+                scene.build(options, listener, executor);
+
+                self.begin_scene();
+                let result = receiver.try_iter().try_for_each(|command| {
+                    self.render_command(&command)
+                });
+                self.end_scene();
+                result?;
+
+                while !self.pending_timers.is_empty() {
+                    if let Some(render_time) = self.shift_rendering_time() {
+                        // A zero time means that nothing was actually measured.
+                        if render_time.gpu_time == Duration::default() {
+                            continue;
+                        }
+                        best_gpu_time = Some(match best_gpu_time {
+                            Some(gpu_time) => gpu_time.min(render_time.gpu_time),
+                            None => render_time.gpu_time,
+                        });
+                    }
+                }
+            }
+
+            debug!("auto-tuning: {:?} took {:?}", tile_size, best_gpu_time);
+            match (best, best_gpu_time) {
+                (Some((_, best_time)), Some(gpu_time)) if gpu_time >= best_time => {}
+                (_, Some(gpu_time)) => best = Some((tile_size, gpu_time)),
+                (_, None) => {}
+            }
+        }
+        Ok(best.map(|(tile_size, _)| tile_size))
+    }
+
     #[inline]
     pub fn set_main_framebuffer_size(&mut self, new_framebuffer_size: Vector2I) {
         self.debug_ui_presenter.ui_presenter.set_framebuffer_size(new_framebuffer_size);
@@ -422,7 +691,15 @@ impl<D> Renderer<D> where D: Device {
 
     fn allocate_texture_page(&mut self,
                              page_id: TexturePageId,
-                             descriptor: &TexturePageDescriptor) {
+                             descriptor: &TexturePageDescriptor)
+                             -> Result<(), RenderError> {
+        // Check the size first, so that a failure leaves any existing page alone.
+        let texture_size = descriptor.size;
+        let max_size = self.device.capabilities().max_texture_size;
+        if texture_size.x() > max_size || texture_size.y() > max_size {
+            return Err(RenderError::TextureTooLarge { size: texture_size, max_size });
+        }
+
         // Fill in IDs up to the requested page ID.
         let page_index = page_id.0 as usize;
         while self.texture_pages.len() < page_index + 1 {
@@ -436,7 +713,6 @@ impl<D> Renderer<D> where D: Device {
         }
 
         // Allocate texture.
-        let texture_size = descriptor.size;
         let texture = self.texture_cache.create_texture(&mut self.device,
                                                         TextureFormat::RGBA8,
                                                         texture_size);
@@ -445,6 +721,7 @@ impl<D> Renderer<D> where D: Device {
             framebuffer,
             must_preserve_contents: false,
         });
+        Ok(())
     }
 
     fn upload_texel_data(&mut self, texels: &[ColorU], location: TextureLocation) {
@@ -454,9 +731,77 @@ impl<D> Renderer<D> where D: Device {
         let texture = self.device.framebuffer_texture(&texture_page.framebuffer);
         let texels = color::color_slice_to_u8_slice(texels);
         self.device.upload_to_texture(texture, location.rect, TextureDataRef::U8(texels));
+        self.stats.bytes_uploaded += texels.len();
         texture_page.must_preserve_contents = true;
     }
 
+    fn declare_paints(&mut self, layout: &Arc<PaintLayout>) {
+        // Rendering the same scene again keeps its paint updates. Anything else discards them.
+        if !self.paint_layout.as_ref().is_some_and(|old_layout| **old_layout == **layout) {
+            self.paint_updates.clear();
+            self.pending_paint_texels.clear();
+        }
+        self.paint_layout = Some((*layout).clone());
+    }
+
+    fn declare_animated_paths(&mut self, layout: &Arc<AnimatedPathLayout>) {
+        // Rendering the same scene again keeps its paths where they were moved to. Anything else
+        // puts them back.
+        let same_layout = self.animated_path_layout.as_ref().is_some_and(|old_layout| {
+            **old_layout == **layout
+        });
+        if !same_layout {
+            self.path_transforms.clear();
+        }
+        self.animated_path_layout = Some((*layout).clone());
+        if !layout.paths.is_empty() {
+            self.upload_path_transforms(layout);
+        }
+    }
+
+    fn upload_path_transforms(&mut self, layout: &AnimatedPathLayout) {
+        let mut texels = vec![0.0; PATH_TRANSFORMS_TEXTURE_WIDTH as usize * 4];
+        for (path_index, path_id) in layout.paths.iter().enumerate() {
+            let transform = self.path_transforms.get(path_id).cloned().unwrap_or_default();
+            let slot_texels = &mut texels[(path_index + 1) * 8..(path_index + 2) * 8];
+            slot_texels.copy_from_slice(&[
+                transform.m11(), transform.m21(), transform.m12(), transform.m22(),
+                transform.m13(), transform.m23(), 0.0, 0.0,
+            ]);
+        }
+
+        let texture = &mut self.back_frame.path_transforms_texture;
+        let size = vec2i(PATH_TRANSFORMS_TEXTURE_WIDTH, PATH_TRANSFORMS_TEXTURE_HEIGHT);
+        let rect = RectI::new(Vector2I::zero(), size);
+        self.device.upload_to_texture(texture, rect, TextureDataRef::F32(&texels));
+        self.stats.bytes_uploaded += mem::size_of_val(&texels[..]);
+    }
+
+    // Uploads the texels of paints updated since the last frame, as long as their pages exist.
+    // Pages that don't yet will be filled by texel uploads later in the frame, which
+    // `reupload_updated_paint_texels()` follows up on.
+    fn upload_pending_paint_texels(&mut self) {
+        for texel_info in mem::take(&mut self.pending_paint_texels) {
+            let page_index = texel_info.location.page.0 as usize;
+            if let Some(&Some(_)) = self.texture_pages.get(page_index) {
+                self.upload_texel_data(&texel_info.texels, texel_info.location);
+            }
+        }
+    }
+
+    // Restores the texels of updated paints after the scene's own texels overwrite them.
+    fn reupload_updated_paint_texels(&mut self, location: TextureLocation) {
+        let overwritten: Vec<ImageTexelInfo> = self.paint_updates.values().filter_map(|update| {
+            update.texels.as_ref().filter(|texel_info| {
+                texel_info.location.page == location.page &&
+                    texel_info.location.rect.intersects(location.rect)
+            }).cloned()
+        }).collect();
+        for texel_info in overwritten {
+            self.upload_texel_data(&texel_info.texels, texel_info.location);
+        }
+    }
+
     fn declare_render_target(&mut self,
                              render_target_id: RenderTargetId,
                              location: TextureLocation) {
@@ -470,12 +815,27 @@ impl<D> Renderer<D> where D: Device {
         render_target.location = location;
     }
 
-    fn upload_texture_metadata(&mut self, metadata: &[TextureMetadataEntry]) {
+    fn upload_texture_metadata(&mut self, metadata: &[TextureMetadataEntry])
+                               -> Result<(), RenderError> {
+        let capacity = TEXTURE_METADATA_ENTRIES_PER_ROW as usize *
+            TEXTURE_METADATA_TEXTURE_HEIGHT as usize;
+        if metadata.len() > capacity {
+            return Err(RenderError::BufferOverflow {
+                buffer: "texture metadata texture",
+                capacity,
+                requested: metadata.len(),
+            });
+        }
+
         let padded_texel_size =
             (util::alignup_i32(metadata.len() as i32, TEXTURE_METADATA_ENTRIES_PER_ROW) *
              TEXTURE_METADATA_TEXTURE_WIDTH * 4) as usize;
         let mut texels = Vec::with_capacity(padded_texel_size);
-        for entry in metadata {
+        for (paint_index, entry) in metadata.iter().enumerate() {
+            let entry = match self.paint_updates.get(&PaintId(paint_index as u16)) {
+                None => entry,
+                Some(update) => &update.metadata,
+            };
             let base_color = entry.base_color.to_f32();
             texels.extend_from_slice(&[
                 f16::from_f32(entry.color_0_transform.m11()),
@@ -505,6 +865,8 @@ impl<D> Renderer<D> where D: Device {
         let height = texels.len() as i32 / (4 * TEXTURE_METADATA_TEXTURE_WIDTH);
         let rect = RectI::new(Vector2I::zero(), Vector2I::new(width, height));
         self.device.upload_to_texture(texture, rect, TextureDataRef::F16(&texels));
+        self.stats.bytes_uploaded += mem::size_of_val(&texels[..]);
+        Ok(())
     }
 
     fn upload_tiles(&mut self, tiles: &[Tile]) -> StorageID {
@@ -530,6 +892,7 @@ impl<D> Renderer<D> where D: Device {
                                  .get(storage_id)
                                  .vertex_buffer;
         self.device.upload_to_buffer(vertex_buffer, 0, tiles, BufferTarget::Vertex);
+        self.stats.bytes_uploaded += mem::size_of_val(tiles);
 
         self.ensure_index_buffer(tiles.len());
 
@@ -554,6 +917,7 @@ impl<D> Renderer<D> where D: Device {
         self.device.allocate_buffer(&self.back_frame.quads_vertex_indices_buffer,
                                     BufferData::Memory(&indices),
                                     BufferTarget::Index);
+        self.stats.bytes_uploaded += mem::size_of_val(&indices[..]);
 
         self.back_frame.quads_vertex_indices_length = length;
     }
@@ -573,7 +937,7 @@ impl<D> Renderer<D> where D: Device {
         for fill_batch_entry in fill_batch {
             let page_index = fill_batch_entry.page;
             if !self.back_frame.alpha_tile_pages.contains_key(&page_index) {
-                let alpha_tile_page = AlphaTilePage::new(&mut self.device);
+                let alpha_tile_page = AlphaTilePage::new(&mut self.device, self.tile_size);
                 self.back_frame.alpha_tile_pages.insert(page_index, alpha_tile_page);
             }
 
@@ -613,6 +977,7 @@ impl<D> Renderer<D> where D: Device {
         };
 
         let mask_viewport = self.mask_viewport();
+        let tile_size = self.tile_size.size().to_f32();
 
         let alpha_tile_page = self.back_frame
                                   .alpha_tile_pages
@@ -648,16 +1013,17 @@ impl<D> Renderer<D> where D: Device {
                                      0,
                                      &buffered_fills,
                                      BufferTarget::Vertex);
+        self.stats.bytes_uploaded += mem::size_of_val(&buffered_fills[..]);
 
         let mut clear_color = None;
         if !alpha_tile_page.framebuffer_is_dirty {
             clear_color = Some(ColorF::default());
         };
 
-        let timer_query = self.timer_query_cache.alloc(&self.device);
-        self.device.begin_timer_query(&timer_query);
+        let timer_query = self.timer_query_cache.start_timing(&self.device);
 
         debug_assert!(buffered_fills.len() <= u32::MAX as usize);
+        self.stats.draw_call_count += 1;
         self.device.draw_elements_instanced(6, buffered_fills.len() as u32, &RenderState {
             target: &RenderTarget::Framebuffer(&alpha_tile_page.framebuffer),
             program: &fill_raster_program.program,
@@ -666,12 +1032,11 @@ impl<D> Renderer<D> where D: Device {
             textures: &[(&fill_raster_program.area_lut_texture, &self.area_lut_texture)],
             uniforms: &[
                 (&fill_raster_program.framebuffer_size_uniform,
-                 UniformData::Vec2(F32x2::new(MASK_FRAMEBUFFER_WIDTH as f32,
-                                              MASK_FRAMEBUFFER_HEIGHT as f32))),
-                (&fill_raster_program.tile_size_uniform,
-                 UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
+                 UniformData::Vec2(mask_viewport.size().to_f32().0)),
+                (&fill_raster_program.tile_size_uniform, UniformData::Vec2(tile_size.0)),
             ],
             images: &[],
+            uniform_buffers: &[],
             viewport: mask_viewport,
             options: RenderOptions {
                 blend: Some(BlendState {
@@ -686,16 +1051,26 @@ impl<D> Renderer<D> where D: Device {
             },
         });
 
-        self.device.end_timer_query(&timer_query);
-        self.current_timer.as_mut().unwrap().fill_times.push(TimerFuture::new(timer_query));
+        if let Some(timer_query) = timer_query {
+            self.device.end_timer_query(&timer_query);
+            self.current_timer.as_mut().unwrap().fill_times.push(TimerFuture::new(timer_query));
+        }
 
         alpha_tile_page.framebuffer_is_dirty = true;
         buffered_fills.clear();
     }
 
     fn draw_buffered_fills_via_compute(&mut self, page: u16) {
+        if self.back_frame.alpha_tile_pages[&page].buffered_fills.is_empty() {
+            return;
+        }
+
+        self.begin_async_compute_if_necessary();
+
         let fill_compute_program = match self.fill_program {
-            FillProgram::Compute(ref fill_compute_program) => fill_compute_program,
+            FillProgram::Compute(ref fill_compute_programs) => {
+                fill_compute_programs.get(self.tile_size)
+            }
             _ => unreachable!(),
         };
 
@@ -704,9 +1079,6 @@ impl<D> Renderer<D> where D: Device {
                                   .get_mut(&page)
                                   .expect("Where's the alpha tile page?");
         let buffered_fills = &mut alpha_tile_page.buffered_fills;
-        if buffered_fills.is_empty() {
-            return;
-        }
 
         let storage_id = {
             let fill_program = &self.fill_program;
@@ -760,14 +1132,17 @@ impl<D> Renderer<D> where D: Device {
                                      0,
                                      &self.fill_tile_map,
                                      BufferTarget::Storage);
+        self.stats.bytes_uploaded += mem::size_of_val(&buffered_fills[..]) +
+            mem::size_of_val(&self.next_fills[..]) +
+            mem::size_of_val(&self.fill_tile_map[..]);
 
         let image_texture = self.device.framebuffer_texture(&alpha_tile_page.framebuffer);
 
-        let timer_query = self.timer_query_cache.alloc(&self.device);
-        self.device.begin_timer_query(&timer_query);
+        let timer_query = self.timer_query_cache.start_timing(&self.device);
 
         debug_assert!(buffered_fills.len() <= u32::MAX as usize);
         let dimensions = ComputeDimensions { x: 1, y: 1, z: fill_tile_count as u32 };
+        self.stats.draw_call_count += 1;
         self.device.dispatch_compute(dimensions, &ComputeState {
             program: &fill_compute_program.program,
             textures: &[(&fill_compute_program.area_lut_texture, &self.area_lut_texture)],
@@ -776,6 +1151,7 @@ impl<D> Renderer<D> where D: Device {
                 (&fill_compute_program.first_tile_index_uniform,
                  UniformData::Int(first_fill_tile as i32)),
             ],
+            uniform_buffers: &[],
             storage_buffers: &[
                 (&fill_compute_program.fills_storage_buffer, &fill_vertex_storage.vertex_buffer),
                 (&fill_compute_program.next_fills_storage_buffer, next_fills_buffer),
@@ -783,13 +1159,52 @@ impl<D> Renderer<D> where D: Device {
             ],
         });
 
-        self.device.end_timer_query(&timer_query);
-        self.current_timer.as_mut().unwrap().fill_times.push(TimerFuture::new(timer_query));
+        if let Some(timer_query) = timer_query {
+            self.device.end_timer_query(&timer_query);
+            self.current_timer.as_mut().unwrap().fill_times.push(TimerFuture::new(timer_query));
+        }
 
         alpha_tile_page.framebuffer_is_dirty = true;
         buffered_fills.clear();
     }
 
+    // Starts recording fills onto the async compute queue, if the device has one, so that they can
+    // overlap with compositing still in flight from the previous frame.
+    fn begin_async_compute_if_necessary(&mut self) {
+        if self.flags.contains(RendererFlags::RECORDING_ASYNC_COMPUTE) ||
+                !self.device.supports_async_compute() {
+            return;
+        }
+
+        let main_queue_fence = if self.flags.contains(RendererFlags::MAIN_QUEUE_WROTE_MASKS) {
+            Some(self.device.signal_queue_fence())
+        } else {
+            None
+        };
+
+        self.device.begin_async_compute_commands();
+        if let Some(main_queue_fence) = main_queue_fence {
+            self.device.wait_for_queue_fence(&main_queue_fence);
+        }
+
+        self.flags.remove(RendererFlags::MAIN_QUEUE_WROTE_MASKS);
+        self.flags.insert(RendererFlags::RECORDING_ASYNC_COMPUTE);
+    }
+
+    // Submits any outstanding async fills and makes the main queue wait for them. This must be
+    // called before the main queue touches the alpha tile pages.
+    fn end_async_compute_if_necessary(&mut self) {
+        if !self.flags.contains(RendererFlags::RECORDING_ASYNC_COMPUTE) {
+            return;
+        }
+
+        let async_compute_fence = self.device.signal_queue_fence();
+        self.device.end_async_compute_commands();
+        self.device.wait_for_queue_fence(&async_compute_fence);
+
+        self.flags.remove(RendererFlags::RECORDING_ASYNC_COMPUTE);
+    }
+
     fn draw_clip_batch(&mut self, batch: &ClipBatch) {
         if batch.clips.is_empty() {
             return;
@@ -797,12 +1212,16 @@ impl<D> Renderer<D> where D: Device {
 
         let ClipBatchKey { dest_page, src_page, kind } = batch.key;
 
+        self.end_async_compute_if_necessary();
+        self.flags.insert(RendererFlags::MAIN_QUEUE_WROTE_MASKS);
+
         self.device.allocate_buffer(&self.back_frame.tile_clip_vertex_array.vertex_buffer,
                                     BufferData::Memory(&batch.clips),
                                     BufferTarget::Vertex);
+        self.stats.bytes_uploaded += mem::size_of_val(&batch.clips[..]);
 
         if !self.back_frame.alpha_tile_pages.contains_key(&dest_page) {
-            let alpha_tile_page = AlphaTilePage::new(&mut self.device);
+            let alpha_tile_page = AlphaTilePage::new(&mut self.device, self.tile_size);
             self.back_frame.alpha_tile_pages.insert(dest_page, alpha_tile_page);
         }
 
@@ -822,12 +1241,21 @@ impl<D> Renderer<D> where D: Device {
                     op: BlendOp::Min,
                 })
             }
+            ClipBatchKind::InvertedClip => {
+                // Scales the draw mask by the part of the tile the clip mask leaves uncovered.
+                Some(BlendState {
+                    src_rgb_factor: BlendFactor::Zero,
+                    src_alpha_factor: BlendFactor::Zero,
+                    dest_rgb_factor: BlendFactor::OneMinusSrcColor,
+                    dest_alpha_factor: BlendFactor::OneMinusSrcColor,
+                    op: BlendOp::Add,
+                })
+            }
         };
 
         let mask_viewport = self.mask_viewport();
 
-        let timer_query = self.timer_query_cache.alloc(&self.device);
-        self.device.begin_timer_query(&timer_query);
+        let timer_query = self.timer_query_cache.start_timing(&self.device);
 
         {
             let dest_framebuffer = &self.back_frame.alpha_tile_pages[&dest_page].framebuffer;
@@ -835,6 +1263,7 @@ impl<D> Renderer<D> where D: Device {
             let src_texture = self.device.framebuffer_texture(&src_framebuffer);
 
             debug_assert!(batch.clips.len() <= u32::MAX as usize);
+            self.stats.draw_call_count += 1;
             self.device.draw_elements_instanced(6, batch.clips.len() as u32, &RenderState {
                 target: &RenderTarget::Framebuffer(dest_framebuffer),
                 program: &self.tile_clip_program.program,
@@ -843,6 +1272,7 @@ impl<D> Renderer<D> where D: Device {
                 textures: &[(&self.tile_clip_program.src_texture, src_texture)],
                 images: &[],
                 uniforms: &[],
+                uniform_buffers: &[],
                 viewport: mask_viewport,
                 options: RenderOptions {
                     blend,
@@ -851,8 +1281,10 @@ impl<D> Renderer<D> where D: Device {
                 },
             });
 
-            self.device.end_timer_query(&timer_query);
-            self.current_timer.as_mut().unwrap().fill_times.push(TimerFuture::new(timer_query));
+            if let Some(timer_query) = timer_query {
+                self.device.end_timer_query(&timer_query);
+                self.current_timer.as_mut().unwrap().fill_times.push(TimerFuture::new(timer_query));
+            }
         }
 
         self.back_frame
@@ -877,6 +1309,9 @@ impl<D> Renderer<D> where D: Device {
                   filter: Filter) {
         // TODO(pcwalton): Disable blend for solid tiles.
 
+        self.end_async_compute_if_necessary();
+        self.stats.draw_call_count += 1;
+
         let needs_readable_framebuffer = blend_mode.needs_readable_framebuffer();
         if needs_readable_framebuffer {
             self.copy_alpha_tiles_to_dest_blend_texture(tile_count, storage_id);
@@ -885,23 +1320,29 @@ impl<D> Renderer<D> where D: Device {
         let clear_color = self.clear_color_for_draw_operation();
         let draw_viewport = self.draw_viewport();
 
-        let timer_query = self.timer_query_cache.alloc(&self.device);
-        self.device.begin_timer_query(&timer_query);
+        let timer_query = self.timer_query_cache.start_timing(&self.device);
 
         let mut textures = vec![
             (&self.tile_program.texture_metadata_texture,
              &self.back_frame.texture_metadata_texture),
+            (&self.tile_program.path_transforms_texture,
+             &self.back_frame.path_transforms_texture),
         ];
         let mut uniforms = vec![
             (&self.tile_program.transform_uniform,
              UniformData::Mat4(self.tile_transform().to_columns())),
             (&self.tile_program.tile_size_uniform,
-             UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
+             UniformData::Vec2(self.tile_size.size().to_f32().0)),
             (&self.tile_program.framebuffer_size_uniform,
              UniformData::Vec2(draw_viewport.size().to_f32().0)),
+            (&self.tile_program.dither_amount_uniform,
+             UniformData::Float(if self.options.dithering { DITHER_AMOUNT } else { 0.0 })),
             (&self.tile_program.texture_metadata_size_uniform,
              UniformData::IVec2(I32x2::new(TEXTURE_METADATA_TEXTURE_WIDTH,
                                            TEXTURE_METADATA_TEXTURE_HEIGHT))),
+            (&self.tile_program.path_transforms_size_uniform,
+             UniformData::IVec2(I32x2::new(PATH_TRANSFORMS_TEXTURE_WIDTH,
+                                           PATH_TRANSFORMS_TEXTURE_HEIGHT))),
         ];
 
         if needs_readable_framebuffer {
@@ -912,8 +1353,7 @@ impl<D> Renderer<D> where D: Device {
 
         if let Some(alpha_tile_page) = self.back_frame.alpha_tile_pages.get(&tile_page) {
             uniforms.push((&self.tile_program.mask_texture_size_0_uniform,
-                           UniformData::Vec2(F32x2::new(MASK_FRAMEBUFFER_WIDTH as f32,
-                                                        MASK_FRAMEBUFFER_HEIGHT as f32))));
+                           UniformData::Vec2(self.mask_viewport().size().to_f32().0)));
             textures.push((&self.tile_program.mask_texture_0,
                            self.device.framebuffer_texture(&alpha_tile_page.framebuffer)));
         }
@@ -947,6 +1387,15 @@ impl<D> Renderer<D> where D: Device {
                 ctrl |= COMBINER_CTRL_FILTER_RADIAL_GRADIENT << COMBINER_CTRL_COLOR_FILTER_SHIFT;
                 self.set_uniforms_for_radial_gradient_filter(&mut uniforms, line, radii, uv_origin)
             }
+            Filter::BlurredRoundedRect { rect, corner_radius, sigma, uv_origin } => {
+                ctrl |= COMBINER_CTRL_FILTER_BLURRED_ROUNDED_RECT <<
+                    COMBINER_CTRL_COLOR_FILTER_SHIFT;
+                self.set_uniforms_for_blurred_rounded_rect_filter(&mut uniforms,
+                                                                  rect,
+                                                                  corner_radius,
+                                                                  sigma,
+                                                                  uv_origin);
+            }
             Filter::PatternFilter(PatternFilter::Text {
                 fg_color,
                 bg_color,
@@ -965,9 +1414,24 @@ impl<D> Renderer<D> where D: Device {
                 ctrl |= COMBINER_CTRL_FILTER_BLUR << COMBINER_CTRL_COLOR_FILTER_SHIFT;
                 self.set_uniforms_for_blur_filter(&mut uniforms, direction, sigma);
             }
+            Filter::PatternFilter(PatternFilter::Yuv { standard, range }) => {
+                ctrl |= COMBINER_CTRL_FILTER_YUV << COMBINER_CTRL_COLOR_FILTER_SHIFT;
+                self.set_uniforms_for_yuv_filter(&mut uniforms, standard, range);
+            }
         }
 
-        uniforms.push((&self.tile_program.ctrl_uniform, UniformData::Int(ctrl)));
+        // Subpixel text over a transparent background blends each channel by its own coverage.
+        let component_alpha = match filter {
+            Filter::PatternFilter(PatternFilter::Text { bg_color, .. }) => {
+                bg_color.a() == 0.0 && blend_mode == BlendMode::SrcOver
+            }
+            _ => false,
+        };
+        let passes = if component_alpha {
+            component_alpha_passes(self.device.capabilities().dual_source_blending)
+        } else {
+            vec![(0, blend_mode.to_blend_state())]
+        };
 
         let vertex_array = &self.back_frame
                                 .tile_vertex_storage_allocator
@@ -975,30 +1439,42 @@ impl<D> Renderer<D> where D: Device {
                                 .tile_vertex_array
                                 .vertex_array;
 
-        self.device.draw_elements_instanced(6, tile_count, &RenderState {
-            target: &self.draw_render_target(),
-            program: &self.tile_program.program,
-            vertex_array,
-            primitive: Primitive::Triangles,
-            textures: &textures,
-            images: &[],
-            uniforms: &uniforms,
-            viewport: draw_viewport,
-            options: RenderOptions {
-                blend: blend_mode.to_blend_state(),
-                stencil: self.stencil_state(),
-                clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
-                ..RenderOptions::default()
-            },
-        });
+        for (pass_index, &(component_alpha_ctrl, blend)) in passes.iter().enumerate() {
+            let mut uniforms = uniforms.clone();
+            let ctrl = ctrl | component_alpha_ctrl << COMBINER_CTRL_COMPONENT_ALPHA_SHIFT;
+            uniforms.push((&self.tile_program.ctrl_uniform, UniformData::Int(ctrl)));
 
-        self.device.end_timer_query(&timer_query);
-        self.current_timer.as_mut().unwrap().tile_times.push(TimerFuture::new(timer_query));
+            let clear_color = if pass_index == 0 { clear_color } else { None };
+            self.device.draw_elements_instanced(6, tile_count, &RenderState {
+                target: &self.draw_render_target(),
+                program: &self.tile_program.program,
+                vertex_array,
+                primitive: Primitive::Triangles,
+                textures: &textures,
+                images: &[],
+                uniforms: &uniforms,
+                uniform_buffers: &[],
+                viewport: draw_viewport,
+                options: RenderOptions {
+                    blend,
+                    stencil: self.stencil_state(),
+                    clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                    ..RenderOptions::default()
+                },
+            });
+        }
+        self.stats.draw_call_count += passes.len() - 1;
+
+        if let Some(timer_query) = timer_query {
+            self.device.end_timer_query(&timer_query);
+            self.current_timer.as_mut().unwrap().tile_times.push(TimerFuture::new(timer_query));
+        }
 
         self.preserve_draw_framebuffer();
     }
 
     fn copy_alpha_tiles_to_dest_blend_texture(&mut self, tile_count: u32, storage_id: StorageID) {
+        self.stats.draw_call_count += 1;
         let draw_viewport = self.draw_viewport();
 
         let mut textures = vec![];
@@ -1006,7 +1482,7 @@ impl<D> Renderer<D> where D: Device {
             (&self.tile_copy_program.transform_uniform,
              UniformData::Mat4(self.tile_transform().to_columns())),
             (&self.tile_copy_program.tile_size_uniform,
-             UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
+             UniformData::Vec2(self.tile_size.size().to_f32().0)),
         ];
 
         let draw_framebuffer = match self.draw_render_target() {
@@ -1033,6 +1509,7 @@ impl<D> Renderer<D> where D: Device {
             textures: &textures,
             images: &[],
             uniforms: &uniforms,
+            uniform_buffers: &[],
             viewport: draw_viewport,
             options: RenderOptions {
                 clear_ops: ClearOps {
@@ -1058,7 +1535,10 @@ impl<D> Renderer<D> where D: Device {
         self.device.allocate_buffer(&self.back_frame.stencil_vertex_array.index_buffer,
                                     BufferData::Memory(&indices),
                                     BufferTarget::Index);
+        self.stats.bytes_uploaded += mem::size_of_val(quad_positions) +
+            mem::size_of_val(&indices[..]);
 
+        self.stats.draw_call_count += 1;
         self.device.draw_elements(indices.len() as u32, &RenderState {
             target: &self.draw_render_target(),
             program: &self.stencil_program.program,
@@ -1067,6 +1547,7 @@ impl<D> Renderer<D> where D: Device {
             textures: &[],
             images: &[],
             uniforms: &[],
+            uniform_buffers: &[],
             viewport: self.draw_viewport(),
             options: RenderOptions {
                 // FIXME(pcwalton): Should we really write to the depth buffer?
@@ -1105,6 +1586,7 @@ impl<D> Renderer<D> where D: Device {
                 (&self.reprojection_program.new_transform_uniform,
                  UniformData::from_transform_3d(new_transform)),
             ],
+            uniform_buffers: &[],
             viewport: self.draw_viewport(),
             options: RenderOptions {
                 blend: BlendMode::SrcOver.to_blend_state(),
@@ -1147,6 +1629,49 @@ impl<D> Renderer<D> where D: Device {
         self.render_target_stack.pop().expect("Render target stack underflow!");
     }
 
+    // Copies `source_rect` of the current draw target into the render target, scaling it to fit.
+    fn resolve_backdrop(&mut self, render_target_id: RenderTargetId, source_rect: RectI) {
+        self.stats.draw_call_count += 1;
+        let src_viewport = self.draw_viewport();
+        let src_framebuffer = match self.draw_render_target() {
+            RenderTarget::Framebuffer(framebuffer) => framebuffer,
+            RenderTarget::Default => panic!("Can't resolve a backdrop from default framebuffer!"),
+        };
+        let src_texture = self.device.framebuffer_texture(src_framebuffer);
+        self.device.set_texture_sampling_mode(src_texture, TextureSamplingFlags::empty());
+
+        let dest_location = self.render_target_location(render_target_id);
+        let dest_framebuffer = self.texture_page_framebuffer(dest_location.page);
+
+        let textures = [(&self.downsample_program.src_texture, src_texture)];
+        let uniforms = [
+            (&self.downsample_program.src_rect_uniform,
+             UniformData::Vec4(source_rect.to_f32().0)),
+            (&self.downsample_program.src_viewport_uniform,
+             UniformData::Vec4(src_viewport.to_f32().0)),
+            (&self.downsample_program.src_texture_size_uniform,
+             UniformData::Vec2(self.device.texture_size(src_texture).to_f32().0)),
+        ];
+
+        self.device.draw_elements(6, &RenderState {
+            target: &RenderTarget::Framebuffer(dest_framebuffer),
+            program: &self.downsample_program.program,
+            vertex_array: &self.back_frame.downsample_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &textures,
+            images: &[],
+            uniforms: &uniforms,
+            uniform_buffers: &[],
+            viewport: dest_location.rect,
+            options: RenderOptions::default(),
+        });
+
+        self.texture_pages[dest_location.page.0 as usize]
+            .as_mut()
+            .expect("Backdrop texture page not allocated!")
+            .must_preserve_contents = true;
+    }
+
     fn set_uniforms_for_no_filter<'a>(&'a self,
                                       uniforms: &mut Vec<(&'a D::Uniform, UniformData)>) {
         uniforms.extend_from_slice(&[
@@ -1171,6 +1696,21 @@ impl<D> Renderer<D> where D: Device {
         ]);
     }
 
+    fn set_uniforms_for_blurred_rounded_rect_filter<'a>(
+            &'a self,
+            uniforms: &mut Vec<(&'a D::Uniform, UniformData)>,
+            rect: RectF,
+            corner_radius: f32,
+            sigma: f32,
+            uv_origin: Vector2F) {
+        uniforms.extend_from_slice(&[
+            (&self.tile_program.filter_params_0_uniform, UniformData::Vec4(rect.0)),
+            (&self.tile_program.filter_params_1_uniform,
+             UniformData::Vec4(F32x4::new(corner_radius, sigma, uv_origin.x(), uv_origin.y()))),
+            (&self.tile_program.filter_params_2_uniform, UniformData::Vec4(F32x4::default())),
+        ]);
+    }
+
     fn set_uniforms_for_text_filter<'a>(
             &'a self,
             textures: &mut Vec<TextureBinding<'a, D::TextureParameter, D::Texture>>,
@@ -1192,11 +1732,13 @@ impl<D> Renderer<D> where D: Device {
             }
         }
 
+        let mut params_1 = bg_color.0;
+        params_1.set_w(fg_color.a());
         let mut params_2 = fg_color.0;
         params_2.set_w(gamma_correction as i32 as f32);
 
         uniforms.extend_from_slice(&[
-            (&self.tile_program.filter_params_1_uniform, UniformData::Vec4(bg_color.0)),
+            (&self.tile_program.filter_params_1_uniform, UniformData::Vec4(params_1)),
             (&self.tile_program.filter_params_2_uniform, UniformData::Vec4(params_2)),
         ]);
     }
@@ -1226,8 +1768,20 @@ impl<D> Renderer<D> where D: Device {
         ]);
     }
 
+    fn set_uniforms_for_yuv_filter<'a>(&'a self,
+                                       uniforms: &mut Vec<(&'a D::Uniform, UniformData)>,
+                                       standard: YuvStandard,
+                                       range: YuvRange) {
+        let matrix = yuv::yuv_to_rgb_matrix(standard, range);
+        uniforms.extend_from_slice(&[
+            (&self.tile_program.filter_params_0_uniform, UniformData::Vec4(matrix[0])),
+            (&self.tile_program.filter_params_1_uniform, UniformData::Vec4(matrix[1])),
+            (&self.tile_program.filter_params_2_uniform, UniformData::Vec4(matrix[2])),
+        ]);
+    }
+
     fn clear_dest_framebuffer_if_necessary(&mut self) {
-        let background_color = match self.options.background_color {
+        let background_color = match self.back_frame.clear_mode.clear_color() {
             None => return,
             Some(background_color) => background_color,
         };
@@ -1246,6 +1800,7 @@ impl<D> Renderer<D> where D: Device {
             (&self.clear_program.color_uniform, UniformData::Vec4(background_color.0)),
         ];
 
+        self.stats.draw_call_count += 1;
         self.device.draw_elements(6, &RenderState {
             target: &RenderTarget::Default,
             program: &self.clear_program.program,
@@ -1254,6 +1809,7 @@ impl<D> Renderer<D> where D: Device {
             textures: &[],
             images: &[],
             uniforms: &uniforms[..],
+            uniform_buffers: &[],
             viewport: main_viewport,
             options: RenderOptions::default(),
         });
@@ -1271,6 +1827,9 @@ impl<D> Renderer<D> where D: Device {
              self.device.framebuffer_texture(&self.back_frame.intermediate_dest_framebuffer))
         ];
 
+        let blend = self.back_frame.clear_mode.intermediate_blit_blend_state();
+
+        self.stats.draw_call_count += 1;
         self.device.draw_elements(6, &RenderState {
             target: &RenderTarget::Default,
             program: &self.blit_program.program,
@@ -1279,17 +1838,80 @@ impl<D> Renderer<D> where D: Device {
             textures: &textures[..],
             images: &[],
             uniforms: &[],
+            uniform_buffers: &[],
+            viewport: main_viewport,
+            options: RenderOptions { blend, ..RenderOptions::default() },
+        });
+    }
+
+    // Composites the destination framebuffer over an opaque background, so that all of its
+    // pixels end up opaque whatever the blend modes and clear mode were.
+    fn flatten_dest_framebuffer_if_necessary(&mut self) {
+        let background_color = match self.options.output_alpha {
+            OutputAlpha::Premultiplied => return,
+            OutputAlpha::Opaque(color) => ColorF::new(color.r(), color.g(), color.b(), 1.0),
+        };
+
+        let main_viewport = self.main_viewport();
+        let uniforms = [
+            (&self.clear_program.rect_uniform, UniformData::Vec4(main_viewport.to_f32().0)),
+            (&self.clear_program.framebuffer_size_uniform,
+             UniformData::Vec2(main_viewport.size().to_f32().0)),
+            (&self.clear_program.color_uniform, UniformData::Vec4(background_color.0)),
+        ];
+
+        let target = match self.dest_framebuffer {
+            DestFramebuffer::Default { .. } => RenderTarget::Default,
+            DestFramebuffer::Other(ref framebuffer) => RenderTarget::Framebuffer(framebuffer),
+        };
+
+        self.stats.draw_call_count += 1;
+        self.device.draw_elements(6, &RenderState {
+            target: &target,
+            program: &self.clear_program.program,
+            vertex_array: &self.back_frame.clear_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[],
+            images: &[],
+            uniforms: &uniforms[..],
+            uniform_buffers: &[],
             viewport: main_viewport,
             options: RenderOptions {
-                clear_ops: ClearOps {
-                    color: Some(ColorF::new(0.0, 0.0, 0.0, 1.0)),
-                    ..ClearOps::default()
-                },
+                blend: Some(BlendState {
+                    src_rgb_factor: BlendFactor::OneMinusDestAlpha,
+                    src_alpha_factor: BlendFactor::OneMinusDestAlpha,
+                    dest_rgb_factor: BlendFactor::One,
+                    dest_alpha_factor: BlendFactor::One,
+                    ..BlendState::default()
+                }),
                 ..RenderOptions::default()
             },
         });
     }
 
+    fn resident_texture_memory(&self) -> usize {
+        let device = &self.device;
+        let mut textures = vec![&self.area_lut_texture, &self.gamma_lut_texture];
+        for texture_page in self.texture_pages.iter().flatten() {
+            textures.push(device.framebuffer_texture(&texture_page.framebuffer));
+        }
+        for frame in &[&self.front_frame, &self.back_frame] {
+            for alpha_tile_page in frame.alpha_tile_pages.values() {
+                textures.push(device.framebuffer_texture(&alpha_tile_page.framebuffer));
+            }
+            textures.push(device.framebuffer_texture(&frame.dest_blend_framebuffer));
+            textures.push(device.framebuffer_texture(&frame.intermediate_dest_framebuffer));
+            textures.push(&frame.texture_metadata_texture);
+            textures.push(&frame.path_transforms_texture);
+        }
+        textures.extend(self.texture_cache.textures.iter());
+
+        textures.into_iter().map(|texture| {
+            let size = device.texture_size(texture);
+            size.x() as usize * size.y() as usize * device.texture_format(texture).bytes_per_pixel()
+        }).sum()
+    }
+
     fn stencil_state(&self) -> Option<StencilState> {
         if !self.flags.contains(RendererFlags::USE_DEPTH) {
             return None;
@@ -1321,10 +1943,14 @@ impl<D> Renderer<D> where D: Device {
 
         if must_preserve_contents {
             None
-        } else if self.render_target_stack.is_empty() {
-            self.options.background_color
-        } else {
+        } else if !self.render_target_stack.is_empty() {
             Some(ColorF::default())
+        } else if self.flags.contains(RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED) {
+            // The intermediate framebuffer holds stale contents from an earlier frame, so it must
+            // always be cleared, even if the real destination is to be preserved.
+            Some(self.back_frame.clear_mode.clear_color().unwrap_or_default())
+        } else {
+            self.back_frame.clear_mode.clear_color()
         }
     }
 
@@ -1365,7 +1991,7 @@ impl<D> Renderer<D> where D: Device {
     }
 
     fn mask_viewport(&self) -> RectI {
-        RectI::new(Vector2I::zero(), vec2i(MASK_FRAMEBUFFER_WIDTH, MASK_FRAMEBUFFER_HEIGHT))
+        RectI::new(Vector2I::zero(), mask_framebuffer_size(self.tile_size))
     }
 
     fn render_target_location(&self, render_target_id: RenderTargetId) -> TextureLocation {
@@ -1389,6 +2015,7 @@ impl<D> Frame<D> where D: Device {
     fn new(device: &D,
            blit_program: &BlitProgram<D>,
            clear_program: &ClearProgram<D>,
+           downsample_program: &DownsampleProgram<D>,
            tile_clip_program: &ClipTileProgram<D>,
            reprojection_program: &ReprojectionProgram<D>,
            stencil_program: &StencilProgram<D>,
@@ -1406,6 +2033,10 @@ impl<D> Frame<D> where D: Device {
                                                        &clear_program,
                                                        &quad_vertex_positions_buffer,
                                                        &quad_vertex_indices_buffer);
+        let downsample_vertex_array = DownsampleVertexArray::new(device,
+                                                                 downsample_program,
+                                                                 quad_vertex_positions_buffer,
+                                                                 quad_vertex_indices_buffer);
         let tile_clip_vertex_array = ClipTileVertexArray::new(device,
                                                               &tile_clip_program,
                                                               &quad_vertex_positions_buffer,
@@ -1424,6 +2055,12 @@ impl<D> Frame<D> where D: Device {
         let texture_metadata_texture = device.create_texture(TextureFormat::RGBA16F,
                                                              texture_metadata_texture_size);
 
+        // Translations are in device pixels, which half floats can't hold precisely.
+        let path_transforms_texture_size = vec2i(PATH_TRANSFORMS_TEXTURE_WIDTH,
+                                                 PATH_TRANSFORMS_TEXTURE_HEIGHT);
+        let path_transforms_texture = device.create_texture(TextureFormat::RGBA32F,
+                                                            path_transforms_texture_size);
+
         let intermediate_dest_texture = device.create_texture(TextureFormat::RGBA8, window_size);
         let intermediate_dest_framebuffer = device.create_framebuffer(intermediate_dest_texture);
 
@@ -1433,6 +2070,7 @@ impl<D> Frame<D> where D: Device {
         Frame {
             blit_vertex_array,
             clear_vertex_array,
+            downsample_vertex_array,
             tile_vertex_storage_allocator,
             fill_vertex_storage_allocator,
             tile_clip_vertex_array,
@@ -1442,9 +2080,11 @@ impl<D> Frame<D> where D: Device {
             quads_vertex_indices_length: 0,
             alpha_tile_pages: FxHashMap::default(),
             texture_metadata_texture,
+            path_transforms_texture,
             intermediate_dest_framebuffer,
             dest_blend_framebuffer,
             framebuffer_flags: FramebufferFlags::empty(),
+            clear_mode: ClearMode::default(),
         }
     }
 }
@@ -1594,6 +2234,13 @@ pub struct RenderStats {
     pub fill_count: usize,
     pub alpha_tile_count: usize,
     pub solid_tile_count: usize,
+    /// The number of draw calls and compute dispatches issued for the frame.
+    pub draw_call_count: usize,
+    /// The number of bytes of vertex, index, and texture data sent to the GPU for the frame.
+    pub bytes_uploaded: usize,
+    /// The total size of all textures and framebuffers the renderer is holding on to at the end
+    /// of the frame, including those belonging to the previous frame.
+    pub texture_memory_bytes: usize,
     pub cpu_build_time: Duration,
 }
 
@@ -1605,6 +2252,9 @@ impl Add<RenderStats> for RenderStats {
             solid_tile_count: self.solid_tile_count + other.solid_tile_count,
             alpha_tile_count: self.alpha_tile_count + other.alpha_tile_count,
             fill_count: self.fill_count + other.fill_count,
+            draw_call_count: self.draw_call_count + other.draw_call_count,
+            bytes_uploaded: self.bytes_uploaded + other.bytes_uploaded,
+            texture_memory_bytes: self.texture_memory_bytes + other.texture_memory_bytes,
             cpu_build_time: self.cpu_build_time + other.cpu_build_time,
         }
     }
@@ -1618,6 +2268,9 @@ impl Div<usize> for RenderStats {
             solid_tile_count: self.solid_tile_count / divisor,
             alpha_tile_count: self.alpha_tile_count / divisor,
             fill_count: self.fill_count / divisor,
+            draw_call_count: self.draw_call_count / divisor,
+            bytes_uploaded: self.bytes_uploaded / divisor,
+            texture_memory_bytes: self.texture_memory_bytes / divisor,
             cpu_build_time: self.cpu_build_time / divisor as u32,
         }
     }
@@ -1625,6 +2278,7 @@ impl Div<usize> for RenderStats {
 
 struct TimerQueryCache<D> where D: Device {
     free_queries: Vec<D::TimerQuery>,
+    enabled: bool,
 }
 
 struct PendingTimer<D> where D: Device {
@@ -1638,12 +2292,19 @@ enum TimerFuture<D> where D: Device {
 }
 
 impl<D> TimerQueryCache<D> where D: Device {
-    fn new(_: &D) -> TimerQueryCache<D> {
-        TimerQueryCache { free_queries: vec![] }
+    fn new(device: &D) -> TimerQueryCache<D> {
+        TimerQueryCache { free_queries: vec![], enabled: device.capabilities().timer_queries }
     }
 
-    fn alloc(&mut self, device: &D) -> D::TimerQuery {
-        self.free_queries.pop().unwrap_or_else(|| device.create_timer_query())
+    // Begins a timer query, unless the device can't time anything, in which case frames take no
+    // GPU time as far as `shift_rendering_time()` is concerned.
+    fn start_timing(&mut self, device: &D) -> Option<D::TimerQuery> {
+        if !self.enabled {
+            return None;
+        }
+        let query = self.free_queries.pop().unwrap_or_else(|| device.create_timer_query());
+        device.begin_timer_query(&query);
+        Some(query)
     }
 
     fn free(&mut self, old_query: D::TimerQuery) {
@@ -1666,16 +2327,22 @@ impl<D> PendingTimer<D> where D: Device {
         old_queries
     }
 
-    fn total_time(&self) -> Option<Duration> {
-        let mut total = Duration::default();
-        for future in self.fill_times.iter().chain(self.tile_times.iter()) {
-            match *future {
-                TimerFuture::Pending(_) => return None,
-                TimerFuture::Resolved(time) => total += time,
-            }
+    fn render_time(&self) -> Option<RenderTime> {
+        let fill_time = total_time(&self.fill_times)?;
+        let tile_time = total_time(&self.tile_times)?;
+        Some(RenderTime { gpu_time: fill_time + tile_time, fill_time, tile_time })
+    }
+}
+
+fn total_time<D>(futures: &[TimerFuture<D>]) -> Option<Duration> where D: Device {
+    let mut total = Duration::default();
+    for future in futures {
+        match *future {
+            TimerFuture::Pending(_) => return None,
+            TimerFuture::Resolved(time) => total += time,
         }
-        Some(total)
     }
+    Some(total)
 }
 
 impl<D> TimerFuture<D> where D: Device {
@@ -1702,13 +2369,22 @@ impl<D> TimerFuture<D> where D: Device {
 
 #[derive(Clone, Copy, Debug)]
 pub struct RenderTime {
+    /// The total GPU time for the frame: `fill_time` plus `tile_time`.
     pub gpu_time: Duration,
+    /// The time spent rasterizing edges into alpha tile masks and drawing clip masks.
+    pub fill_time: Duration,
+    /// The time spent compositing tiles into the destination framebuffer and render targets.
+    pub tile_time: Duration,
 }
 
 impl Default for RenderTime {
     #[inline]
     fn default() -> RenderTime {
-        RenderTime { gpu_time: Duration::new(0, 0) }
+        RenderTime {
+            gpu_time: Duration::new(0, 0),
+            fill_time: Duration::new(0, 0),
+            tile_time: Duration::new(0, 0),
+        }
     }
 }
 
@@ -1717,7 +2393,11 @@ impl Add<RenderTime> for RenderTime {
 
     #[inline]
     fn add(self, other: RenderTime) -> RenderTime {
-        RenderTime { gpu_time: self.gpu_time + other.gpu_time }
+        RenderTime {
+            gpu_time: self.gpu_time + other.gpu_time,
+            fill_time: self.fill_time + other.fill_time,
+            tile_time: self.tile_time + other.tile_time,
+        }
     }
 }
 
@@ -1726,7 +2406,11 @@ impl Div<usize> for RenderTime {
 
     #[inline]
     fn div(self, divisor: usize) -> RenderTime {
-        RenderTime { gpu_time: self.gpu_time / divisor as u32 }
+        RenderTime {
+            gpu_time: self.gpu_time / divisor as u32,
+            fill_time: self.fill_time / divisor as u32,
+            tile_time: self.tile_time / divisor as u32,
+        }
     }
 }
 
@@ -1904,7 +2588,40 @@ impl ToBlendState for BlendMode {
     }
 }
 
-pub trait BlendModeExt {
+/// Returns the passes that draw subpixel text over whatever is beneath it, scaling each channel
+/// of the destination by one minus the text's coverage in that channel and then adding the text
+/// color. Each pass is a component alpha mode for the tile shader and a blend state.
+fn component_alpha_passes(dual_source_blending: bool) -> Vec<(i32, Option<BlendState>)> {
+    if dual_source_blending {
+        // The shader outputs the coverage as the second color, so both happen at once.
+        return vec![(COMBINER_CTRL_COMPONENT_ALPHA_DUAL_SOURCE, Some(BlendState {
+            src_rgb_factor: BlendFactor::One,
+            dest_rgb_factor: BlendFactor::OneMinusSrc1Color,
+            src_alpha_factor: BlendFactor::One,
+            dest_alpha_factor: BlendFactor::OneMinusSrcAlpha,
+            ..BlendState::default()
+        }))];
+    }
+
+    vec![
+        (COMBINER_CTRL_COMPONENT_ALPHA_MASK_PASS, Some(BlendState {
+            src_rgb_factor: BlendFactor::Zero,
+            dest_rgb_factor: BlendFactor::OneMinusSrcColor,
+            src_alpha_factor: BlendFactor::Zero,
+            dest_alpha_factor: BlendFactor::OneMinusSrcAlpha,
+            ..BlendState::default()
+        })),
+        (COMBINER_CTRL_COMPONENT_ALPHA_COLOR_PASS, Some(BlendState {
+            src_rgb_factor: BlendFactor::One,
+            dest_rgb_factor: BlendFactor::One,
+            src_alpha_factor: BlendFactor::One,
+            dest_alpha_factor: BlendFactor::One,
+            ..BlendState::default()
+        })),
+    ]
+}
+
+pub(crate) trait BlendModeExt {
     fn needs_readable_framebuffer(self) -> bool;
 }
 
@@ -1950,9 +2667,10 @@ struct AlphaTilePage<D> where D: Device {
 }
 
 impl<D> AlphaTilePage<D> where D: Device {
-    fn new(device: &mut D) -> AlphaTilePage<D> {
-        let framebuffer_size = vec2i(MASK_FRAMEBUFFER_WIDTH, MASK_FRAMEBUFFER_HEIGHT);
-        let framebuffer_texture = device.create_texture(TextureFormat::RGBA16F, framebuffer_size);
+    fn new(device: &mut D, tile_size: TileSize) -> AlphaTilePage<D> {
+        let framebuffer_size = mask_framebuffer_size(tile_size);
+        let framebuffer_texture = device.create_transient_texture(TextureFormat::RGBA16F,
+                                                                  framebuffer_size);
         let framebuffer = device.create_framebuffer(framebuffer_texture);
         AlphaTilePage {
             buffered_fills: vec![],
@@ -1963,6 +2681,12 @@ impl<D> AlphaTilePage<D> where D: Device {
     }
 }
 
+// FIXME(pcwalton): Shrink this again!
+fn mask_framebuffer_size(tile_size: TileSize) -> Vector2I {
+    vec2i(tile_size.width() as i32 * MASK_TILES_ACROSS as i32,
+          tile_size.height() as i32 / 4 * MASK_TILES_DOWN as i32)
+}
+
 bitflags! {
     struct RendererFlags: u8 {
         // Whether we need a depth buffer.
@@ -1972,6 +2696,13 @@ bitflags! {
         // This will be true if any exotic blend modes are used at the top level (not inside a
         // render target), *and* the output framebuffer is the default framebuffer.
         const INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED = 0x02;
+        // Whether fills are currently being recorded onto the device's async compute queue.
+        const RECORDING_ASYNC_COMPUTE = 0x04;
+        // Whether the main queue has rendered to an alpha tile page since async compute work was
+        // last begun. If so, the next batch of async fills must wait for it.
+        const MAIN_QUEUE_WROTE_MASKS = 0x08;
+        // Whether a command failed this frame, so that the rest should be skipped.
+        const FRAME_FAILED = 0x10;
     }
 }
 
@@ -10,9 +10,10 @@
 
 use crate::gpu::options::RendererOptions;
 use crate::gpu::renderer::{MASK_TILES_ACROSS, MASK_TILES_DOWN};
-use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
-use pathfinder_gpu::{BufferTarget, BufferUploadMode, ComputeDimensions, Device, FeatureLevel};
+use crate::tiles::TileSize;
+use pathfinder_gpu::{BufferTarget, BufferUploadMode, ComputeDimensions, Device, ProgramKind};
 use pathfinder_gpu::{VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
+use pathfinder_gpu::error::RenderError;
 use pathfinder_resources::ResourceLoader;
 
 // TODO(pcwalton): Replace with `mem::size_of` calls?
@@ -52,6 +53,36 @@ impl<D> BlitVertexArray<D> where D: Device {
     }
 }
 
+pub struct DownsampleVertexArray<D> where D: Device {
+    pub vertex_array: D::VertexArray,
+}
+
+impl<D> DownsampleVertexArray<D> where D: Device {
+    pub fn new(device: &D,
+               downsample_program: &DownsampleProgram<D>,
+               quad_vertex_positions_buffer: &D::Buffer,
+               quad_vertex_indices_buffer: &D::Buffer)
+               -> DownsampleVertexArray<D> {
+        let vertex_array = device.create_vertex_array();
+        let position_attr = device.get_vertex_attr(&downsample_program.program, "Position")
+                                  .unwrap();
+
+        device.bind_buffer(&vertex_array, quad_vertex_positions_buffer, BufferTarget::Vertex);
+        device.configure_vertex_attr(&vertex_array, &position_attr, &VertexAttrDescriptor {
+            size: 2,
+            class: VertexAttrClass::Int,
+            attr_type: VertexAttrType::I16,
+            stride: 4,
+            offset: 0,
+            divisor: 0,
+            buffer_index: 0,
+        });
+        device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
+
+        DownsampleVertexArray { vertex_array }
+    }
+}
+
 pub struct ClearVertexArray<D> where D: Device {
     pub vertex_array: D::VertexArray,
 }
@@ -359,10 +390,11 @@ pub struct BlitProgram<D> where D: Device {
 }
 
 impl<D> BlitProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> BlitProgram<D> {
-        let program = device.create_raster_program(resources, "blit");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<BlitProgram<D>, RenderError> {
+        let program = device.create_raster_program(resources, "blit")?;
         let src_texture = device.get_texture_parameter(&program, "Src");
-        BlitProgram { program, src_texture }
+        Ok(BlitProgram { program, src_texture })
     }
 }
 
@@ -374,31 +406,62 @@ pub struct ClearProgram<D> where D: Device {
 }
 
 impl<D> ClearProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> ClearProgram<D> {
-        let program = device.create_raster_program(resources, "clear");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<ClearProgram<D>, RenderError> {
+        let program = device.create_raster_program(resources, "clear")?;
         let rect_uniform = device.get_uniform(&program, "Rect");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let color_uniform = device.get_uniform(&program, "Color");
-        ClearProgram { program, rect_uniform, framebuffer_size_uniform, color_uniform }
+        Ok(ClearProgram { program, rect_uniform, framebuffer_size_uniform, color_uniform })
+    }
+}
+
+pub struct DownsampleProgram<D> where D: Device {
+    pub program: D::Program,
+    pub src_texture: D::TextureParameter,
+    pub src_rect_uniform: D::Uniform,
+    pub src_viewport_uniform: D::Uniform,
+    pub src_texture_size_uniform: D::Uniform,
+}
+
+impl<D> DownsampleProgram<D> where D: Device {
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<DownsampleProgram<D>, RenderError> {
+        let program = device.create_raster_program(resources, "downsample")?;
+        let src_texture = device.get_texture_parameter(&program, "Src");
+        let src_rect_uniform = device.get_uniform(&program, "SrcRect");
+        let src_viewport_uniform = device.get_uniform(&program, "SrcViewport");
+        let src_texture_size_uniform = device.get_uniform(&program, "SrcTextureSize");
+        Ok(DownsampleProgram {
+            program,
+            src_texture,
+            src_rect_uniform,
+            src_viewport_uniform,
+            src_texture_size_uniform,
+        })
     }
 }
 
 pub enum FillProgram<D> where D: Device {
     Raster(FillRasterProgram<D>),
-    Compute(FillComputeProgram<D>),
+    Compute(FillComputePrograms<D>),
 }
 
 impl<D> FillProgram<D> where D: Device {
     pub fn new(device: &D, resources: &dyn ResourceLoader, options: &RendererOptions)
-               -> FillProgram<D> {
-        match (options.no_compute, device.feature_level()) {
-            (false, FeatureLevel::D3D11) => {
-                FillProgram::Compute(FillComputeProgram::new(device, resources))
-            }
-            (_, FeatureLevel::D3D10) | (true, _) => {
-                FillProgram::Raster(FillRasterProgram::new(device, resources))
-            }
+               -> Result<FillProgram<D>, RenderError> {
+        // Rasterized fills accumulate signed coverage in a half-float framebuffer, so if the
+        // device can't render to one, compute is the only way to draw, even if it was disabled.
+        let capabilities = device.capabilities();
+        if capabilities.compute && (!options.no_compute || !capabilities.f16_render_targets) {
+            return Ok(FillProgram::Compute(FillComputePrograms::new(device, resources)?));
         }
+        if !capabilities.f16_render_targets {
+            return Err(RenderError::UnsupportedFeature("compute shaders or half-float render \
+                                                        targets, one of which is needed to \
+                                                        rasterize fills"));
+        }
+        Ok(FillProgram::Raster(FillRasterProgram::new(device, resources)?))
     }
 }
 
@@ -410,16 +473,41 @@ pub struct FillRasterProgram<D> where D: Device {
 }
 
 impl<D> FillRasterProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> FillRasterProgram<D> {
-        let program = device.create_raster_program(resources, "fill");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<FillRasterProgram<D>, RenderError> {
+        let program = device.create_raster_program(resources, "fill")?;
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let tile_size_uniform = device.get_uniform(&program, "TileSize");
         let area_lut_texture = device.get_texture_parameter(&program, "AreaLUT");
-        FillRasterProgram {
+        Ok(FillRasterProgram {
             program,
             framebuffer_size_uniform,
             tile_size_uniform,
             area_lut_texture,
+        })
+    }
+}
+
+/// The compute fill shader, built once for each `TileSize` with the tile size compiled in.
+pub struct FillComputePrograms<D> where D: Device {
+    tile_8x8: FillComputeProgram<D>,
+    tile_16x16: FillComputeProgram<D>,
+}
+
+impl<D> FillComputePrograms<D> where D: Device {
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<FillComputePrograms<D>, RenderError> {
+        Ok(FillComputePrograms {
+            tile_8x8: FillComputeProgram::new(device, resources, TileSize::Tile8x8)?,
+            tile_16x16: FillComputeProgram::new(device, resources, TileSize::Tile16x16)?,
+        })
+    }
+
+    #[inline]
+    pub fn get(&self, tile_size: TileSize) -> &FillComputeProgram<D> {
+        match tile_size {
+            TileSize::Tile8x8 => &self.tile_8x8,
+            TileSize::Tile16x16 => &self.tile_16x16,
         }
     }
 }
@@ -435,9 +523,20 @@ pub struct FillComputeProgram<D> where D: Device {
 }
 
 impl<D> FillComputeProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> FillComputeProgram<D> {
-        let mut program = device.create_compute_program(resources, "fill");
-        let local_size = ComputeDimensions { x: TILE_WIDTH, y: TILE_HEIGHT / 4, z: 1 };
+    pub fn new(device: &D, resources: &dyn ResourceLoader, tile_size: TileSize)
+               -> Result<FillComputeProgram<D>, RenderError> {
+        let name = match tile_size {
+            TileSize::Tile8x8 => "fill_8x8",
+            TileSize::Tile16x16 => "fill",
+        };
+        let mut program = device.create_compute_program(resources, name)?;
+
+        // One thread per column of four pixels in a tile.
+        let local_size = ComputeDimensions {
+            x: tile_size.width(),
+            y: tile_size.height() / 4,
+            z: 1,
+        };
         device.set_compute_program_local_size(&mut program, local_size);
 
         let dest_image = device.get_image_parameter(&program, "Dest");
@@ -447,7 +546,7 @@ impl<D> FillComputeProgram<D> where D: Device {
         let next_fills_storage_buffer = device.get_storage_buffer(&program, "NextFills", 1);
         let fill_tile_map_storage_buffer = device.get_storage_buffer(&program, "FillTileMap", 2);
 
-        FillComputeProgram {
+        Ok(FillComputeProgram {
             program,
             dest_image,
             area_lut_texture,
@@ -455,7 +554,7 @@ impl<D> FillComputeProgram<D> where D: Device {
             fills_storage_buffer,
             next_fills_storage_buffer,
             fill_tile_map_storage_buffer,
-        }
+        })
     }
 }
 
@@ -465,6 +564,8 @@ pub struct TileProgram<D> where D: Device {
     pub tile_size_uniform: D::Uniform,
     pub texture_metadata_texture: D::TextureParameter,
     pub texture_metadata_size_uniform: D::Uniform,
+    pub path_transforms_texture: D::TextureParameter,
+    pub path_transforms_size_uniform: D::Uniform,
     pub dest_texture: D::TextureParameter,
     pub color_texture_0: D::TextureParameter,
     pub color_texture_size_0_uniform: D::Uniform,
@@ -476,16 +577,32 @@ pub struct TileProgram<D> where D: Device {
     pub filter_params_1_uniform: D::Uniform,
     pub filter_params_2_uniform: D::Uniform,
     pub framebuffer_size_uniform: D::Uniform,
+    pub dither_amount_uniform: D::Uniform,
     pub ctrl_uniform: D::Uniform,
 }
 
 impl<D> TileProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> TileProgram<D> {
-        let program = device.create_raster_program(resources, "tile");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<TileProgram<D>, RenderError> {
+        // The dual-source variant also writes the per-channel coverage of subpixel text, for
+        // blending it in one pass.
+        let fragment = if device.capabilities().dual_source_blending {
+            "tile_dual_source"
+        } else {
+            "tile"
+        };
+        let program = device.create_program_from_shader_names(resources,
+                                                              "tile",
+                                                              ProgramKind::Raster {
+                                                                  vertex: "tile",
+                                                                  fragment,
+                                                              })?;
         let transform_uniform = device.get_uniform(&program, "Transform");
         let tile_size_uniform = device.get_uniform(&program, "TileSize");
         let texture_metadata_texture = device.get_texture_parameter(&program, "TextureMetadata");
         let texture_metadata_size_uniform = device.get_uniform(&program, "TextureMetadataSize");
+        let path_transforms_texture = device.get_texture_parameter(&program, "PathTransforms");
+        let path_transforms_size_uniform = device.get_uniform(&program, "PathTransformsSize");
         let dest_texture = device.get_texture_parameter(&program, "DestTexture");
         let color_texture_0 = device.get_texture_parameter(&program, "ColorTexture0");
         let color_texture_size_0_uniform = device.get_uniform(&program, "ColorTextureSize0");
@@ -497,13 +614,16 @@ impl<D> TileProgram<D> where D: Device {
         let filter_params_1_uniform = device.get_uniform(&program, "FilterParams1");
         let filter_params_2_uniform = device.get_uniform(&program, "FilterParams2");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
+        let dither_amount_uniform = device.get_uniform(&program, "DitherAmount");
         let ctrl_uniform = device.get_uniform(&program, "Ctrl");
-        TileProgram {
+        Ok(TileProgram {
             program,
             transform_uniform,
             tile_size_uniform,
             texture_metadata_texture,
             texture_metadata_size_uniform,
+            path_transforms_texture,
+            path_transforms_size_uniform,
             dest_texture,
             color_texture_0,
             color_texture_size_0_uniform,
@@ -515,8 +635,9 @@ impl<D> TileProgram<D> where D: Device {
             filter_params_1_uniform,
             filter_params_2_uniform,
             framebuffer_size_uniform,
+            dither_amount_uniform,
             ctrl_uniform,
-        }
+        })
     }
 }
 
@@ -529,19 +650,20 @@ pub struct CopyTileProgram<D> where D: Device {
 }
 
 impl<D> CopyTileProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> CopyTileProgram<D> {
-        let program = device.create_raster_program(resources, "tile_copy");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<CopyTileProgram<D>, RenderError> {
+        let program = device.create_raster_program(resources, "tile_copy")?;
         let transform_uniform = device.get_uniform(&program, "Transform");
         let tile_size_uniform = device.get_uniform(&program, "TileSize");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let src_texture = device.get_texture_parameter(&program, "Src");
-        CopyTileProgram {
+        Ok(CopyTileProgram {
             program,
             transform_uniform,
             tile_size_uniform,
             framebuffer_size_uniform,
             src_texture,
-        }
+        })
     }
 }
 
@@ -551,10 +673,11 @@ pub struct ClipTileProgram<D> where D: Device {
 }
 
 impl<D> ClipTileProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> ClipTileProgram<D> {
-        let program = device.create_raster_program(resources, "tile_clip");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<ClipTileProgram<D>, RenderError> {
+        let program = device.create_raster_program(resources, "tile_clip")?;
         let src_texture = device.get_texture_parameter(&program, "Src");
-        ClipTileProgram { program, src_texture }
+        Ok(ClipTileProgram { program, src_texture })
     }
 }
 
@@ -569,9 +692,10 @@ impl<D> StencilProgram<D>
 where
     D: Device,
 {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> StencilProgram<D> {
-        let program = device.create_raster_program(resources, "stencil");
-        StencilProgram { program }
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<StencilProgram<D>, RenderError> {
+        let program = device.create_raster_program(resources, "stencil")?;
+        Ok(StencilProgram { program })
     }
 }
 
@@ -619,12 +743,13 @@ pub struct ReprojectionProgram<D> where D: Device {
 }
 
 impl<D> ReprojectionProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> ReprojectionProgram<D> {
-        let program = device.create_raster_program(resources, "reproject");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<ReprojectionProgram<D>, RenderError> {
+        let program = device.create_raster_program(resources, "reproject")?;
         let old_transform_uniform = device.get_uniform(&program, "OldTransform");
         let new_transform_uniform = device.get_uniform(&program, "NewTransform");
         let texture = device.get_texture_parameter(&program, "Texture");
-        ReprojectionProgram { program, old_transform_uniform, new_transform_uniform, texture }
+        Ok(ReprojectionProgram { program, old_transform_uniform, new_transform_uniform, texture })
     }
 }
 
@@ -10,9 +10,11 @@
 
 //! Packed data ready to be sent to the GPU.
 
+use crate::animation::AnimatedPathLayout;
 use crate::builder::{ALPHA_TILES_PER_LEVEL, ALPHA_TILE_LEVEL_COUNT};
 use crate::options::BoundingQuad;
-use crate::paint::PaintCompositeOp;
+use crate::paint::{PaintCompositeOp, PaintLayout};
+use crate::tiles::TileSize;
 use pathfinder_color::ColorU;
 use pathfinder_content::effects::{BlendMode, Filter};
 use pathfinder_content::render_target::RenderTargetId;
@@ -30,8 +32,12 @@ use std::u32;
 pub const TILE_CTRL_MASK_MASK:     i32 = 0x3;
 pub const TILE_CTRL_MASK_WINDING:  i32 = 0x1;
 pub const TILE_CTRL_MASK_EVEN_ODD: i32 = 0x2;
+pub const TILE_CTRL_MASK_WINDING_THRESHOLD: i32 = 0x3;
 
 pub const TILE_CTRL_MASK_0_SHIFT:  i32 = 0;
+/// Masks filled with `FillRule::WindingAtLeast` keep their threshold in these bits of the control
+/// word.
+pub const TILE_CTRL_MASK_THRESHOLD_0_SHIFT: i32 = 8;
 
 pub enum RenderCommand {
     // Starts rendering a frame.
@@ -47,6 +53,9 @@ pub enum RenderCommand {
         /// This is needed if a path that renders directly to the output framebuffer (i.e. not to a
         /// render target) uses one of the more exotic blend modes.
         needs_readable_framebuffer: bool,
+
+        /// The size of the tiles that the scene was broken into.
+        tile_size: TileSize,
     },
 
     // Allocates a texture page.
@@ -60,6 +69,12 @@ pub enum RenderCommand {
     // TODO(pcwalton): Add a rect to this so we can render to subrects of a page.
     DeclareRenderTarget { id: RenderTargetId, location: TextureLocation },
 
+    // Describes the paints of the scene, so that they can be updated in place later.
+    DeclarePaints(Arc<PaintLayout>),
+
+    // Describes the animated paths of the scene, so that they can be moved later.
+    DeclareAnimatedPaths(Arc<AnimatedPathLayout>),
+
     // Upload texture metadata.
     UploadTextureMetadata(Vec<TextureMetadataEntry>),
 
@@ -79,6 +94,10 @@ pub enum RenderCommand {
     // Pops a render target from the stack.
     PopRenderTarget,
 
+    // Copies a rectangle of the render target on top of the stack into another render target,
+    // scaling it to the size of the latter, so that it can be filtered.
+    ResolveBackdrop { render_target: RenderTargetId, source_rect: RectI },
+
     // Marks that tile compositing is about to begin.
     BeginTileDrawing,
 
@@ -173,6 +192,8 @@ pub struct ClipBatchKey {
 pub enum ClipBatchKind {
     Draw,
     Clip,
+    /// Like `Clip`, but keeps the part of the draw mask outside the clip mask.
+    InvertedClip,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -195,7 +216,9 @@ pub struct Tile {
     pub mask_0_u: u8,
     pub mask_0_v: u8,
     pub mask_0_backdrop: i8,
-    pub pad: u8,
+    /// The slot of the transform that moves this tile, or 0 if the path isn't animated. See
+    /// `AnimatedPathLayout`.
+    pub transform_slot: u8,
     pub color: u16,
     pub ctrl: u16,
 }
@@ -246,6 +269,12 @@ impl Debug for RenderCommand {
             RenderCommand::DeclareRenderTarget { id, location } => {
                 write!(formatter, "DeclareRenderTarget({:?}, {:?})", id, location)
             }
+            RenderCommand::DeclarePaints(ref layout) => {
+                write!(formatter, "DeclarePaints(x{})", layout.slots.len())
+            }
+            RenderCommand::DeclareAnimatedPaths(ref layout) => {
+                write!(formatter, "DeclareAnimatedPaths(x{})", layout.paths.len())
+            }
             RenderCommand::UploadTextureMetadata(ref metadata) => {
                 write!(formatter, "UploadTextureMetadata(x{})", metadata.len())
             }
@@ -260,6 +289,9 @@ impl Debug for RenderCommand {
                 write!(formatter, "PushRenderTarget({:?})", render_target_id)
             }
             RenderCommand::PopRenderTarget => write!(formatter, "PopRenderTarget"),
+            RenderCommand::ResolveBackdrop { render_target, source_rect } => {
+                write!(formatter, "ResolveBackdrop({:?}, {:?})", render_target, source_rect)
+            }
             RenderCommand::BeginTileDrawing => write!(formatter, "BeginTileDrawing"),
             RenderCommand::DrawTiles(ref batch) => {
                 write!(formatter,
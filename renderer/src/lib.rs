@@ -15,12 +15,19 @@ extern crate bitflags;
 #[macro_use]
 extern crate log;
 
+pub mod animation;
+pub mod archive;
+pub mod camera;
+pub mod capture;
 pub mod concurrent;
 pub mod gpu;
+pub mod governor;
 pub mod gpu_data;
 pub mod options;
 pub mod paint;
 pub mod scene;
+pub mod tile_data;
+pub mod zoom;
 
 mod allocator;
 mod builder;
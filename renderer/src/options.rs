@@ -11,11 +11,15 @@
 //! Options that control how rendering is to be performed.
 
 use crate::gpu_data::RenderCommand;
+use crate::tile_data::TileDataRecorder;
+pub use crate::tiles::TileSize;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::transform3d::Perspective;
 use pathfinder_geometry::vector::{Vector2F, Vector4F};
 use pathfinder_content::clip::PolygonClipper3D;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 pub trait RenderCommandListener: Send + Sync {
     fn send(&self, command: RenderCommand);
@@ -37,6 +41,29 @@ pub struct BuildOptions {
     pub transform: RenderTransform,
     pub dilation: Vector2F,
     pub subpixel_aa_enabled: bool,
+    /// Whether to compute the coverage of horizontal and vertical edges exactly.
+    ///
+    /// When this is set, the tiler splits such edges at tile boundaries without interpolating
+    /// along them, and ends every edge at its true endpoint. Abutting rectangles (and other shapes
+    /// with axis-aligned edges, such as the tops and bottoms of trapezoids) then compute exactly
+    /// complementary coverage along their shared edges, so no seams show between them. Without
+    /// it, rounding error can occasionally leave a faint crack. Horizontal edges that run along
+    /// tile boundaries also become backdrops rather than fills, so shapes aligned to the tile grid
+    /// need no alpha tiles. Sloped edges cost the same either way.
+    pub exact_axis_aligned_aa: bool,
+    /// The size of the tiles that paths are broken into.
+    ///
+    /// The renderer picks this up from the render command stream, so it needn't be told
+    /// separately. See `Renderer::auto_tune_tile_size()` to choose a size for the current GPU.
+    pub tile_size: TileSize,
+    /// A handle with which to cancel the build or monitor its progress.
+    pub build_handle: Option<BuildHandle>,
+    /// Settings that trade image quality for speed. See `QualityGovernor` to adjust them
+    /// automatically when frames take too long.
+    pub quality: QualitySettings,
+    /// A recorder that keeps the tiles and fills of every path for inspection. Recording copies
+    /// all of them, so leave this unset except when debugging or testing.
+    pub tile_data_recorder: Option<TileDataRecorder>,
 }
 
 impl BuildOptions {
@@ -45,6 +72,132 @@ impl BuildOptions {
             transform: self.transform.prepare(bounds),
             dilation: self.dilation,
             subpixel_aa_enabled: self.subpixel_aa_enabled,
+            exact_axis_aligned_aa: self.exact_axis_aligned_aa,
+            tile_size: self.tile_size,
+            build_handle: self.build_handle,
+            quality: self.quality,
+            tile_data_recorder: self.tile_data_recorder,
+        }
+    }
+}
+
+/// Settings that trade image quality for speed.
+///
+/// The defaults give full quality.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualitySettings {
+    /// How far, in device pixels, the line segments that curves are flattened into may stray from
+    /// the true curves. Larger values produce fewer segments.
+    pub flattening_tolerance: f32,
+    /// A factor by which to reduce the resolution of heavy layers.
+    ///
+    /// The scene builder can't resize layers after the fact, so apps should multiply the scale
+    /// that they pass to `Scene::push_layer_at_scale()` by this.
+    pub layer_scale: f32,
+    /// Whether blur filters, including backdrop blurs, are applied. If not, what would be blurred
+    /// is drawn sharp.
+    pub blur_enabled: bool,
+}
+
+impl Default for QualitySettings {
+    #[inline]
+    fn default() -> QualitySettings {
+        QualitySettings { flattening_tolerance: 0.25, layer_scale: 1.0, blur_enabled: true }
+    }
+}
+
+/// Allows a scene build to be cancelled from another thread and reports its progress.
+///
+/// Pass a clone of the handle in `BuildOptions::build_handle` and keep the original. Each handle
+/// should be used for a single build.
+///
+/// A cancelled build stops tiling paths as soon as possible and then finishes the command stream
+/// without drawing anything, so the resulting frame should be discarded.
+#[derive(Clone, Default)]
+pub struct BuildHandle {
+    data: Arc<BuildHandleData>,
+}
+
+#[derive(Default)]
+struct BuildHandleData {
+    cancelled: AtomicBool,
+    paths_built: AtomicUsize,
+    path_count: AtomicUsize,
+    progress_callback: Option<Box<dyn Fn(BuildProgress) + Send + Sync>>,
+}
+
+/// How far a scene build has gotten.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BuildProgress {
+    /// The number of paths (both draw paths and clip paths) that have been tiled so far.
+    pub paths_built: usize,
+    /// The total number of paths in the scene.
+    pub path_count: usize,
+}
+
+impl BuildHandle {
+    #[inline]
+    pub fn new() -> BuildHandle {
+        BuildHandle::default()
+    }
+
+    /// Creates a handle that calls `callback` every time a path is tiled.
+    ///
+    /// The callback is called on the scene building threads, possibly concurrently, so it should
+    /// be cheap.
+    pub fn with_progress_callback<F>(callback: F) -> BuildHandle
+                                     where F: Fn(BuildProgress) + Send + Sync + 'static {
+        BuildHandle {
+            data: Arc::new(BuildHandleData {
+                progress_callback: Some(Box::new(callback)),
+                ..BuildHandleData::default()
+            }),
+        }
+    }
+
+    /// Requests that the build stop. This may be called from any thread.
+    #[inline]
+    pub fn cancel(&self) {
+        self.data.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.data.cancelled.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub fn progress(&self) -> BuildProgress {
+        BuildProgress {
+            paths_built: self.data.paths_built.load(Ordering::Relaxed),
+            path_count: self.data.path_count.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn begin(&self, path_count: usize) {
+        self.data.paths_built.store(0, Ordering::Relaxed);
+        self.data.path_count.store(path_count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn path_built(&self) {
+        let paths_built = self.data.paths_built.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(ref callback) = self.data.progress_callback {
+            callback(BuildProgress {
+                paths_built,
+                path_count: self.data.path_count.load(Ordering::Relaxed),
+            });
+        }
+    }
+}
+
+impl BuildProgress {
+    /// Returns the fraction of paths built, from 0.0 to 1.0.
+    #[inline]
+    pub fn fraction(&self) -> f32 {
+        if self.path_count == 0 {
+            1.0
+        } else {
+            self.paths_built as f32 / self.path_count as f32
         }
     }
 }
@@ -118,6 +271,11 @@ pub(crate) struct PreparedBuildOptions {
     pub(crate) transform: PreparedRenderTransform,
     pub(crate) dilation: Vector2F,
     pub(crate) subpixel_aa_enabled: bool,
+    pub(crate) exact_axis_aligned_aa: bool,
+    pub(crate) tile_size: TileSize,
+    pub(crate) build_handle: Option<BuildHandle>,
+    pub(crate) quality: QualitySettings,
+    pub(crate) tile_data_recorder: Option<TileDataRecorder>,
 }
 
 impl PreparedBuildOptions {
@@ -128,6 +286,14 @@ impl PreparedBuildOptions {
             _ => [Vector4F::default(); 4],
         }
     }
+
+    #[inline]
+    pub(crate) fn is_cancelled(&self) -> bool {
+        match self.build_handle {
+            Some(ref build_handle) => build_handle.is_cancelled(),
+            None => false,
+        }
+    }
 }
 
 pub(crate) type BoundingQuad = [Vector4F; 4];
@@ -151,3 +317,78 @@ impl PreparedRenderTransform {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu_data::RenderCommand;
+    use crate::paint::Paint;
+    use crate::scene::{DrawPath, Scene};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::{Vector2F, vec2f};
+    use std::sync::{Arc, Mutex};
+    use super::{BuildHandle, BuildOptions, BuildProgress};
+
+    #[test]
+    fn test_cancel_from_progress_callback() {
+        const PATH_COUNT: usize = 16;
+        const CANCEL_AFTER: usize = 5;
+
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(256.0, 256.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        for path_index in 0..PATH_COUNT {
+            let origin = vec2f(path_index as f32 * 12.5, 4.5);
+            let rect = Outline::from_rect(RectF::new(origin, vec2f(30.0, 30.0)));
+            scene.push_path(DrawPath::new(rect, paint));
+        }
+
+        // The callback needs the handle that owns it in order to cancel, so hand it over once the
+        // handle exists.
+        let handle_for_callback: Arc<Mutex<Option<BuildHandle>>> = Arc::new(Mutex::new(None));
+        let progress_reports = Arc::new(Mutex::new(vec![]));
+        let build_handle = {
+            let (handle_for_callback, progress_reports) =
+                (handle_for_callback.clone(), progress_reports.clone());
+            BuildHandle::with_progress_callback(move |progress: BuildProgress| {
+                progress_reports.lock().unwrap().push(progress);
+                if progress.paths_built == CANCEL_AFTER {
+                    handle_for_callback.lock().unwrap().as_ref().unwrap().cancel();
+                }
+            })
+        };
+        *handle_for_callback.lock().unwrap() = Some(build_handle.clone());
+
+        let commands = Arc::new(Mutex::new(vec![]));
+        let listener = {
+            let commands = commands.clone();
+            Box::new(move |command| commands.lock().unwrap().push(command))
+        };
+        let stats = scene.build(BuildOptions {
+            build_handle: Some(build_handle.clone()),
+            ..BuildOptions::default()
+        }, listener, &SequentialExecutor);
+        // Break the reference cycle between the handle and its callback.
+        handle_for_callback.lock().unwrap().take();
+
+        // The build stopped as soon as it was cancelled.
+        assert!(build_handle.is_cancelled());
+        let progress_reports = progress_reports.lock().unwrap();
+        assert_eq!(progress_reports.len(), CANCEL_AFTER);
+        for (index, progress) in progress_reports.iter().enumerate() {
+            assert_eq!(progress.paths_built, index + 1);
+            assert_eq!(progress.path_count, PATH_COUNT);
+        }
+        assert!(build_handle.progress().paths_built < build_handle.progress().path_count);
+
+        // The result is cancelled: nothing was drawn, but the command stream was terminated.
+        assert_eq!(stats.draw_path_count, 0);
+        let commands = commands.lock().unwrap();
+        assert!(!commands.iter().any(|command| {
+            matches!(*command, RenderCommand::BeginTileDrawing | RenderCommand::DrawTiles(_))
+        }));
+        assert!(matches!(commands.last(), Some(RenderCommand::Finish { .. })));
+    }
+}
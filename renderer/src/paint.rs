@@ -16,23 +16,34 @@ use hashbrown::HashMap;
 use pathfinder_color::ColorU;
 use pathfinder_content::effects::{Filter, PatternFilter};
 use pathfinder_content::gradient::{Gradient, GradientGeometry};
+use pathfinder_content::mesh::GradientMesh;
 use pathfinder_content::pattern::{Pattern, PatternSource};
 use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_content::shadow::BlurredRoundedRect;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2f, vec2i};
 use pathfinder_gpu::TextureSamplingFlags;
+use pathfinder_gpu::error::RenderError;
 use pathfinder_simd::default::{F32x2, F32x4};
 use std::f32;
 use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::mem;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_PALETTE_VERSION: AtomicUsize = AtomicUsize::new(0);
 
 // The size of a gradient tile.
 //
 // TODO(pcwalton): Choose this size dynamically!
 const GRADIENT_TILE_LENGTH: u32 = 256;
 
+// The largest texture a gradient mesh is rasterized into, along either axis.
+const MAX_MESH_TEXTURE_LENGTH: i32 = 2048;
+
 #[derive(Clone)]
 pub struct Palette {
     pub paints: Vec<Paint>,
@@ -40,6 +51,7 @@ pub struct Palette {
     cache: HashMap<Paint, PaintId>,
     allocator: TextureAllocator,
     scene_id: SceneId,
+    version: u64,
 }
 
 #[derive(Clone)]
@@ -64,6 +76,8 @@ pub struct PaintOverlay {
 pub enum PaintContents {
     Gradient(Gradient),
     Pattern(Pattern),
+    Mesh(GradientMesh),
+    BlurredRoundedRect(BlurredRoundedRect),
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -84,6 +98,8 @@ impl Debug for PaintContents {
         match *self {
             PaintContents::Gradient(ref gradient) => gradient.fmt(formatter),
             PaintContents::Pattern(ref pattern) => pattern.fmt(formatter),
+            PaintContents::Mesh(ref mesh) => mesh.fmt(formatter),
+            PaintContents::BlurredRoundedRect(ref shadow) => shadow.fmt(formatter),
         }
     }
 }
@@ -97,6 +113,7 @@ impl Palette {
             cache: HashMap::new(),
             allocator: TextureAllocator::new(),
             scene_id,
+            version: next_palette_version(),
         }
     }
 }
@@ -129,6 +146,33 @@ impl Paint {
         }
     }
 
+    #[inline]
+    pub fn from_mesh(mesh: GradientMesh) -> Paint {
+        Paint {
+            base_color: ColorU::white(),
+            overlay: Some(PaintOverlay {
+                composite_op: PaintCompositeOp::SrcIn,
+                contents: PaintContents::Mesh(mesh),
+            }),
+        }
+    }
+
+    /// A paint that fills with `color` wherever the blurred rounded rectangle covers, and is
+    /// transparent elsewhere. Paths drawn with it need only cover `shadow.bounds()`.
+    ///
+    /// This is much cheaper than blurring a rendered rounded rectangle, which takes two render
+    /// targets and two passes.
+    #[inline]
+    pub fn from_blurred_rounded_rect(shadow: BlurredRoundedRect, color: ColorU) -> Paint {
+        Paint {
+            base_color: color,
+            overlay: Some(PaintOverlay {
+                composite_op: PaintCompositeOp::DestIn,
+                contents: PaintContents::BlurredRoundedRect(shadow),
+            }),
+        }
+    }
+
     #[inline]
     pub fn black() -> Paint {
         Paint::from_color(ColorU::black())
@@ -150,6 +194,8 @@ impl Paint {
                 match overlay.contents {
                     PaintContents::Gradient(ref gradient) => gradient.is_opaque(),
                     PaintContents::Pattern(ref pattern) => pattern.is_opaque(),
+                    PaintContents::Mesh(ref mesh) => mesh.is_opaque(),
+                    PaintContents::BlurredRoundedRect(_) => false,
                 }
             }
         }
@@ -166,6 +212,8 @@ impl Paint {
                 match overlay.contents {
                     PaintContents::Gradient(ref gradient) => gradient.is_fully_transparent(),
                     PaintContents::Pattern(_) => false,
+                    PaintContents::Mesh(ref mesh) => mesh.is_fully_transparent(),
+                    PaintContents::BlurredRoundedRect(_) => false,
                 }
             }
         }
@@ -185,6 +233,10 @@ impl Paint {
             match overlay.contents {
                 PaintContents::Gradient(ref mut gradient) => gradient.apply_transform(*transform),
                 PaintContents::Pattern(ref mut pattern) => pattern.apply_transform(*transform),
+                PaintContents::Mesh(ref mut mesh) => mesh.apply_transform(*transform),
+                PaintContents::BlurredRoundedRect(ref mut shadow) => {
+                    shadow.apply_transform(*transform)
+                }
             }
         }
     }
@@ -247,6 +299,19 @@ impl Paint {
             }
         }
     }
+
+    #[inline]
+    pub fn mesh(&self) -> Option<&GradientMesh> {
+        match self.overlay {
+            None => None,
+            Some(ref overlay) => {
+                match overlay.contents {
+                    PaintContents::Mesh(ref mesh) => Some(mesh),
+                    _ => None,
+                }
+            }
+        }
+    }
 }
 
 impl PaintOverlay {
@@ -328,9 +393,62 @@ pub enum PaintFilter {
         /// The radii of the two circles.
         radii: F32x2,
     },
+    BlurredRoundedRect {
+        /// The rectangle before blurring.
+        rect: RectF,
+        /// The radius of its corners.
+        corner_radius: f32,
+        /// The standard deviation of the blur.
+        sigma: f32,
+    },
     PatternFilter(PatternFilter),
 }
 
+/// The paints of a built scene, as the renderer needs them to update paints in place.
+///
+/// See `Renderer::update_paint()`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PaintLayout {
+    /// Changes whenever the scene's paints do, so that the renderer can tell a scene rebuilt with
+    /// new paints apart from the same one rendered again.
+    pub palette_version: u64,
+    /// The transform that was applied to the paints' texture transforms.
+    pub render_transform: Transform2F,
+    /// The slot each paint was built into, indexed by paint ID.
+    pub slots: Vec<PaintSlot>,
+}
+
+/// Everything about a built paint that tile batches and occlusion culling depend on, which a
+/// paint that replaces it in place must match.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PaintSlot {
+    /// Where the paint's color texture lives and how it's sampled, if it has one.
+    pub color_texture: Option<PaintSlotTexture>,
+    /// The filter applied to the color texture.
+    pub filter: Filter,
+    /// True if the paint was fully opaque, so that tiles behind it may have been culled.
+    pub is_opaque: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PaintSlotTexture {
+    /// The location of the texels.
+    pub location: TextureLocation,
+    /// The scale for the page the texels are on.
+    pub page_scale: Vector2F,
+    /// The sampling mode for the texture.
+    pub sampling_flags: TextureSamplingFlags,
+    /// How the color texture is to be composited over the base color.
+    pub composite_op: PaintCompositeOp,
+}
+
+/// The new metadata and texels for a paint updated in place.
+#[derive(Clone)]
+pub(crate) struct PaintUpdate {
+    pub(crate) metadata: TextureMetadataEntry,
+    pub(crate) texels: Option<ImageTexelInfo>,
+}
+
 impl Palette {
     #[allow(clippy::trivially_copy_pass_by_ref)]
     pub fn push_paint(&mut self, paint: &Paint) -> PaintId {
@@ -341,9 +459,63 @@ impl Palette {
         let paint_id = PaintId(self.paints.len() as u16);
         self.cache.insert((*paint).clone(), paint_id);
         self.paints.push((*paint).clone());
+        self.version = next_palette_version();
         paint_id
     }
 
+    /// Replaces the paint with the given ID. See `Scene::set_paint()`.
+    pub fn set_paint(&mut self, paint_id: PaintId, paint: Paint) {
+        let old_paint = &self.paints[paint_id.0 as usize];
+        if self.cache.get(old_paint) == Some(&paint_id) {
+            self.cache.remove(old_paint);
+        }
+        self.cache.entry(paint.clone()).or_insert(paint_id);
+        self.paints[paint_id.0 as usize] = paint;
+        self.version = next_palette_version();
+    }
+
+    /// Transforms all the paints. See `Scene::apply_transform()`.
+    pub(crate) fn apply_transform(&mut self, transform: &Transform2F) {
+        self.cache.clear();
+        for (paint_index, paint) in self.paints.iter_mut().enumerate() {
+            paint.apply_transform(transform);
+            self.cache.entry(paint.clone()).or_insert(PaintId(paint_index as u16));
+        }
+        self.version = next_palette_version();
+    }
+
+    /// Moves the palette to the scene `scene_id`, renaming the render targets that its paints
+    /// draw from to match.
+    pub(crate) fn set_scene_id(&mut self, scene_id: SceneId) {
+        let old_scene_id = mem::replace(&mut self.scene_id, scene_id);
+        self.cache.clear();
+        for (paint_index, paint) in self.paints.iter_mut().enumerate() {
+            let render_target_id = match paint.pattern().map(Pattern::source) {
+                Some(&PatternSource::RenderTarget { id, .. }) if id.scene == old_scene_id.0 => {
+                    Some(id)
+                }
+                _ => None,
+            };
+            if let Some(render_target_id) = render_target_id {
+                let pattern = paint.pattern_mut().unwrap();
+                let new_render_target_id = RenderTargetId { scene: scene_id.0, ..render_target_id };
+                *pattern = retarget_pattern(pattern, new_render_target_id);
+            }
+            self.cache.entry(paint.clone()).or_insert(PaintId(paint_index as u16));
+        }
+        self.version = next_palette_version();
+    }
+
+    /// Hashes the paints and the sizes of the render targets. See `Scene::content_hash()`.
+    pub(crate) fn hash_contents<H>(&self, state: &mut H) where H: Hasher {
+        self.paints.hash(state);
+        self.render_targets.len().hash(state);
+        for render_target_data in &self.render_targets {
+            let size = render_target_data.render_target.size();
+            (size.x(), size.y()).hash(state);
+        }
+    }
+
     pub fn push_render_target(&mut self, render_target: RenderTarget) -> RenderTargetId {
         let id = self.render_targets.len() as u32;
 
@@ -355,6 +527,16 @@ impl Palette {
         RenderTargetId { scene: self.scene_id.0, render_target: id }
     }
 
+    #[inline]
+    pub(crate) fn render_target_size(&self, id: RenderTargetId) -> Vector2I {
+        self.render_targets[id.render_target as usize].render_target.size()
+    }
+
+    #[inline]
+    pub(crate) fn render_target(&self, id: RenderTargetId) -> &RenderTarget {
+        &self.render_targets[id.render_target as usize].render_target
+    }
+
     pub fn build_paint_info(&mut self, render_transform: Transform2F) -> PaintInfo {
         let mut paint_metadata = vec![];
 
@@ -365,69 +547,54 @@ impl Palette {
             let allocator = &mut self.allocator;
             let render_targets = &self.render_targets;
             let color_texture_metadata = paint.overlay.as_ref().map(|overlay| {
-                match overlay.contents {
+                let location = match overlay.contents {
                     PaintContents::Gradient(ref gradient) => {
                         // FIXME(pcwalton): The gradient size might not be big enough. Detect this.
-                        let location = gradient_tile_builder.allocate(allocator, gradient);
-                        PaintColorTextureMetadata {
-                            location,
-                            page_scale: allocator.page_scale(location.page),
-                            sampling_flags: TextureSamplingFlags::empty(),
-                            filter: match gradient.geometry {
-                                GradientGeometry::Linear(_) => PaintFilter::None,
-                                GradientGeometry::Radial { line, radii, .. } => {
-                                    PaintFilter::RadialGradient { line, radii }
-                                }
-                            },
-                            transform: Transform2F::default(),
-                            composite_op: overlay.composite_op(),
-                        }
+                        gradient_tile_builder.allocate(allocator, |t| gradient.sample(t))
                     }
                     PaintContents::Pattern(ref pattern) => {
-                        let location;
                         match *pattern.source() {
                             PatternSource::RenderTarget { id: render_target_id, .. } => {
                                 let index = render_target_id.render_target as usize;
-                                location = render_targets[index].metadata.location;
+                                render_targets[index].metadata.location
                             }
                             PatternSource::Image(ref image) => {
                                 // TODO(pcwalton): We should be able to use tile cleverness to
                                 // repeat inside the atlas in some cases.
                                 let allocation_mode = AllocationMode::OwnPage;
-                                location = allocator.allocate(image.size(), allocation_mode);
+                                let location = allocator.allocate(image.size(), allocation_mode);
                                 image_texel_info.push(ImageTexelInfo {
                                     location,
                                     texels: (*image.pixels()).clone(),
                                 });
+                                location
                             }
                         }
-
-                        let mut sampling_flags = TextureSamplingFlags::empty();
-                        if pattern.repeat_x() {
-                            sampling_flags.insert(TextureSamplingFlags::REPEAT_U);
-                        }
-                        if pattern.repeat_y() {
-                            sampling_flags.insert(TextureSamplingFlags::REPEAT_V);
-                        }
-                        if !pattern.smoothing_enabled() {
-                            sampling_flags.insert(TextureSamplingFlags::NEAREST_MIN |
-                                                  TextureSamplingFlags::NEAREST_MAG);
-                        }
-
-                        let filter = match pattern.filter() {
-                            None => PaintFilter::None,
-                            Some(pattern_filter) => PaintFilter::PatternFilter(pattern_filter),
-                        };
-
-                        PaintColorTextureMetadata {
+                    }
+                    PaintContents::BlurredRoundedRect(_) => {
+                        // The shader computes the coverage, so the texture only needs to supply
+                        // an opaque color for the base color to be multiplied by.
+                        gradient_tile_builder.allocate(allocator, |_| ColorU::white())
+                    }
+                    PaintContents::Mesh(ref mesh) => {
+                        let (image_size, mesh_to_image) = mesh_image(mesh, render_transform);
+                        let location = allocator.allocate(image_size, AllocationMode::OwnPage);
+                        image_texel_info.push(ImageTexelInfo {
                             location,
-                            page_scale: allocator.page_scale(location.page),
-                            sampling_flags,
-                            filter,
-                            transform: Transform2F::default(),
-                            composite_op: overlay.composite_op(),
-                        }
+                            texels: Arc::new(mesh.rasterize(image_size, &mesh_to_image)),
+                        });
+                        location
                     }
+                };
+
+                let (sampling_flags, filter) = overlay.contents.sampling_flags_and_filter();
+                PaintColorTextureMetadata {
+                    location,
+                    page_scale: allocator.page_scale(location.page),
+                    sampling_flags,
+                    filter,
+                    transform: Transform2F::default(),
+                    composite_op: overlay.composite_op(),
                 }
             });
 
@@ -445,61 +612,26 @@ impl Palette {
                 Some(ref mut color_texture_metadata) => color_texture_metadata,
             };
 
-            let texture_scale = self.allocator.page_scale(color_texture_metadata.location.page);
-            let texture_rect = color_texture_metadata.location.rect;
-            color_texture_metadata.transform = match paint.overlay    
-                                                          .as_ref()
-                                                          .expect("Why do we have color texture \
-                                                                   metadata but no overlay?")
-                                                          .contents {
-                PaintContents::Gradient(Gradient {
-                    geometry: GradientGeometry::Linear(gradient_line),
-                    ..
-                }) => {
-                    // Project gradient line onto (0.0-1.0, v0).
-                    let v0 = texture_rect.to_f32().center().y() * texture_scale.y();
-                    let dp = gradient_line.vector();
-                    let m0 = dp.0.concat_xy_xy(dp.0) / F32x4::splat(gradient_line.square_length());
-                    let m13 = m0.zw() * -gradient_line.from().0;
-                    Transform2F::row_major(m0.x(), m0.y(), m13.x() + m13.y(), 0.0, 0.0, v0)
-                }
-                PaintContents::Gradient(Gradient {
-                    geometry: GradientGeometry::Radial { ref transform, .. },
-                    ..
-                }) => transform.inverse(),
-                PaintContents::Pattern(ref pattern) => {
-                    match pattern.source() {
-                        PatternSource::Image(_) => {
-                            let texture_origin_uv =
-                                rect_to_uv(texture_rect, texture_scale).origin();
-                            Transform2F::from_scale(texture_scale).translate(texture_origin_uv) *
-                                pattern.transform().inverse()
-                        }
-                        PatternSource::RenderTarget { .. } => {
-                            // FIXME(pcwalton): Only do this in GL, not Metal!
-                            let texture_origin_uv =
-                                rect_to_uv(texture_rect, texture_scale).lower_left();
-                            Transform2F::from_translation(texture_origin_uv) *
-                                Transform2F::from_scale(texture_scale * vec2f(1.0, -1.0)) *
-                                pattern.transform().inverse()
-                        }
-                    }
-                }
-            };
-            color_texture_metadata.transform *= render_transform;
+            color_texture_metadata.transform =
+                paint.overlay
+                     .as_ref()
+                     .expect("Why do we have color texture metadata but no overlay?")
+                     .contents
+                     .texture_transform(color_texture_metadata, render_transform);
         }
 
+        // Describe the paints, so that the renderer can update them in place.
+        let layout = PaintLayout {
+            palette_version: self.version,
+            render_transform,
+            slots: paint_metadata.iter().map(PaintMetadata::slot).collect(),
+        };
+        let mut render_commands = vec![RenderCommand::DeclarePaints(Arc::new(layout))];
+
         // Create texture metadata.
-        let texture_metadata = paint_metadata.iter().map(|paint_metadata| {
-            TextureMetadataEntry {
-                color_0_transform: match paint_metadata.color_texture_metadata {
-                    None => Transform2F::default(),
-                    Some(ref color_texture_metadata) => color_texture_metadata.transform,
-                },
-                base_color: paint_metadata.base_color,
-            }
-        }).collect();
-        let mut render_commands = vec![RenderCommand::UploadTextureMetadata(texture_metadata)];
+        let texture_metadata =
+            paint_metadata.iter().map(PaintMetadata::texture_metadata_entry).collect();
+        render_commands.push(RenderCommand::UploadTextureMetadata(texture_metadata));
 
         // Allocate textures.
         let mut texture_page_descriptors = vec![];
@@ -563,14 +695,9 @@ impl Palette {
                     match *overlay.contents() {
                         PaintContents::Pattern(ref pattern) => {
                             match pattern.source() {
-                                PatternSource::RenderTarget { id: old_render_target_id, size } => {
-                                    let mut new_pattern =
-                                        Pattern::from_render_target(*old_render_target_id, *size);
-                                    new_pattern.set_filter(pattern.filter());
-                                    new_pattern.apply_transform(pattern.transform());
-                                    new_pattern.set_repeat_x(pattern.repeat_x());
-                                    new_pattern.set_repeat_y(pattern.repeat_y());
-                                    new_pattern.set_smoothing_enabled(pattern.smoothing_enabled());
+                                PatternSource::RenderTarget { id: old_render_target_id, .. } => {
+                                    let new_pattern =
+                                        retarget_pattern(pattern, *old_render_target_id);
                                     self.push_paint(&Paint::from_pattern(new_pattern))
                                 }
                                 _ => self.push_paint(old_paint),
@@ -587,6 +714,17 @@ impl Palette {
     }
 }
 
+// Returns a copy of `pattern` that draws from the render target `id` instead.
+fn retarget_pattern(pattern: &Pattern, id: RenderTargetId) -> Pattern {
+    let mut new_pattern = Pattern::from_render_target(id, pattern.size());
+    new_pattern.set_filter(pattern.filter());
+    new_pattern.apply_transform(pattern.transform());
+    new_pattern.set_repeat_x(pattern.repeat_x());
+    new_pattern.set_repeat_y(pattern.repeat_y());
+    new_pattern.set_smoothing_enabled(pattern.smoothing_enabled());
+    new_pattern
+}
+
 pub(crate) struct MergedPaletteInfo {
     pub(crate) render_target_mapping: HashMap<RenderTargetId, RenderTargetId>,
     pub(crate) paint_mapping: HashMap<PaintId, PaintId>,
@@ -605,6 +743,17 @@ impl PaintMetadata {
                             vec2f(0.0, color_metadata.page_scale.y() * 0.5));
                         Filter::RadialGradient { line, radii, uv_origin: uv_rect.origin() }
                     }
+                    PaintFilter::BlurredRoundedRect { rect, corner_radius, sigma } => {
+                        let uv_rect = rect_to_uv(color_metadata.location.rect,
+                                                 color_metadata.page_scale).contract(
+                            vec2f(0.0, color_metadata.page_scale.y() * 0.5));
+                        Filter::BlurredRoundedRect {
+                            rect,
+                            corner_radius,
+                            sigma,
+                            uv_origin: uv_rect.origin(),
+                        }
+                    }
                     PaintFilter::PatternFilter(pattern_filter) => {
                         Filter::PatternFilter(pattern_filter)
                     }
@@ -616,12 +765,254 @@ impl PaintMetadata {
     pub(crate) fn tile_batch_texture(&self) -> Option<TileBatchTexture> {
         self.color_texture_metadata.as_ref().map(PaintColorTextureMetadata::as_tile_batch_texture)
     }
+
+    fn texture_metadata_entry(&self) -> TextureMetadataEntry {
+        TextureMetadataEntry {
+            color_0_transform: match self.color_texture_metadata {
+                None => Transform2F::default(),
+                Some(ref color_texture_metadata) => color_texture_metadata.transform,
+            },
+            base_color: self.base_color,
+        }
+    }
+
+    fn slot(&self) -> PaintSlot {
+        PaintSlot {
+            color_texture: self.color_texture_metadata.as_ref().map(|color_texture_metadata| {
+                PaintSlotTexture {
+                    location: color_texture_metadata.location,
+                    page_scale: color_texture_metadata.page_scale,
+                    sampling_flags: color_texture_metadata.sampling_flags,
+                    composite_op: color_texture_metadata.composite_op,
+                }
+            }),
+            filter: self.filter(),
+            is_opaque: self.is_opaque,
+        }
+    }
+}
+
+impl Paint {
+    /// Prepares this paint to replace the one that was built into `slot`, reusing its texture
+    /// location. Fails if anything that tile batches or occlusion culling depend on would change.
+    ///
+    /// `render_target_location` looks up where a render target that a pattern refers to lives.
+    pub(crate) fn update_in_slot<F>(&self,
+                                    slot: &PaintSlot,
+                                    render_transform: Transform2F,
+                                    render_target_location: F)
+                                    -> Result<PaintUpdate, RenderError>
+                                    where F: Fn(RenderTargetId) -> Option<TextureLocation> {
+        let mut texels = None;
+        let color_texture_metadata = match (self.overlay.as_ref(), slot.color_texture) {
+            (None, None) => None,
+            (Some(overlay), Some(slot_texture)) => {
+                let location = slot_texture.location;
+                match overlay.contents {
+                    PaintContents::Gradient(ref gradient) => {
+                        let mut row = vec![ColorU::black(); GRADIENT_TILE_LENGTH as usize];
+                        sample_gradient_row(&mut row, |t| gradient.sample(t));
+                        texels = Some(Arc::new(row));
+                    }
+                    PaintContents::Pattern(ref pattern) => {
+                        match *pattern.source() {
+                            PatternSource::RenderTarget { id, .. } => {
+                                if render_target_location(id) != Some(location) {
+                                    return Err(RenderError::IncompatiblePaint(
+                                        "the pattern shows a different render target"));
+                                }
+                            }
+                            PatternSource::Image(ref image) => {
+                                if image.size() != location.rect.size() {
+                                    return Err(RenderError::IncompatiblePaint(
+                                        "the pattern's image is a different size"));
+                                }
+                                texels = Some((*image.pixels()).clone());
+                            }
+                        }
+                    }
+                    PaintContents::Mesh(ref mesh) => {
+                        let (image_size, mesh_to_image) = mesh_image(mesh, render_transform);
+                        if image_size != location.rect.size() {
+                            return Err(RenderError::IncompatiblePaint(
+                                "the mesh would be rasterized at a different size"));
+                        }
+                        texels = Some(Arc::new(mesh.rasterize(image_size, &mesh_to_image)));
+                    }
+                    // The texels are plain white, so they stay the same.
+                    PaintContents::BlurredRoundedRect(_) => {}
+                }
+
+                let (sampling_flags, filter) = overlay.contents.sampling_flags_and_filter();
+                let mut color_texture_metadata = PaintColorTextureMetadata {
+                    location,
+                    page_scale: slot_texture.page_scale,
+                    sampling_flags,
+                    filter,
+                    transform: Transform2F::default(),
+                    composite_op: overlay.composite_op(),
+                };
+                color_texture_metadata.transform =
+                    overlay.contents.texture_transform(&color_texture_metadata, render_transform);
+                Some(color_texture_metadata)
+            }
+            (None, Some(_)) | (Some(_), None) => {
+                return Err(RenderError::IncompatiblePaint(
+                    "a paint with a texture can't replace one without, or vice versa"));
+            }
+        };
+
+        let metadata = PaintMetadata {
+            color_texture_metadata,
+            base_color: self.base_color(),
+            is_opaque: self.is_opaque(),
+        };
+        let new_slot = metadata.slot();
+        if new_slot.color_texture != slot.color_texture {
+            return Err(RenderError::IncompatiblePaint(
+                "the paint's texture would be sampled or composited differently"));
+        }
+        if new_slot.filter != slot.filter {
+            return Err(RenderError::IncompatiblePaint("the paint needs a different filter"));
+        }
+        if slot.is_opaque && !new_slot.is_opaque {
+            return Err(RenderError::IncompatiblePaint(
+                "the paint was opaque, so tiles behind it may have been culled"));
+        }
+
+        Ok(PaintUpdate {
+            metadata: metadata.texture_metadata_entry(),
+            texels: texels.map(|texels| {
+                ImageTexelInfo { location: slot.color_texture.unwrap().location, texels }
+            }),
+        })
+    }
+}
+
+impl PaintContents {
+    // How the color texture is sampled and filtered. Tile batches bake these in, so they can't
+    // change when a paint is updated in place.
+    fn sampling_flags_and_filter(&self) -> (TextureSamplingFlags, PaintFilter) {
+        match *self {
+            PaintContents::Gradient(ref gradient) => {
+                let filter = match gradient.geometry {
+                    GradientGeometry::Linear(_) => PaintFilter::None,
+                    GradientGeometry::Radial { line, radii, .. } => {
+                        PaintFilter::RadialGradient { line, radii }
+                    }
+                };
+                (TextureSamplingFlags::empty(), filter)
+            }
+            PaintContents::Pattern(ref pattern) => {
+                let mut sampling_flags = TextureSamplingFlags::empty();
+                if pattern.repeat_x() {
+                    sampling_flags.insert(TextureSamplingFlags::REPEAT_U);
+                }
+                if pattern.repeat_y() {
+                    sampling_flags.insert(TextureSamplingFlags::REPEAT_V);
+                }
+                if !pattern.smoothing_enabled() {
+                    sampling_flags.insert(TextureSamplingFlags::NEAREST_MIN |
+                                          TextureSamplingFlags::NEAREST_MAG);
+                }
+
+                let filter = match pattern.filter() {
+                    None => PaintFilter::None,
+                    Some(pattern_filter) => PaintFilter::PatternFilter(pattern_filter),
+                };
+                (sampling_flags, filter)
+            }
+            PaintContents::Mesh(_) => (TextureSamplingFlags::empty(), PaintFilter::None),
+            PaintContents::BlurredRoundedRect(ref shadow) => {
+                let filter = PaintFilter::BlurredRoundedRect {
+                    rect: shadow.rect,
+                    corner_radius: shadow.corner_radius,
+                    sigma: shadow.sigma,
+                };
+                (TextureSamplingFlags::empty(), filter)
+            }
+        }
+    }
+
+    // Calculates the transform from device coordinates to the UVs of the color texture.
+    fn texture_transform(&self,
+                         color_texture_metadata: &PaintColorTextureMetadata,
+                         render_transform: Transform2F)
+                         -> Transform2F {
+        let texture_scale = color_texture_metadata.page_scale;
+        let texture_rect = color_texture_metadata.location.rect;
+        let transform = match *self {
+            PaintContents::Gradient(Gradient {
+                geometry: GradientGeometry::Linear(gradient_line),
+                ..
+            }) => {
+                // Project gradient line onto (0.0-1.0, v0).
+                let v0 = texture_rect.to_f32().center().y() * texture_scale.y();
+                let dp = gradient_line.vector();
+                let m0 = dp.0.concat_xy_xy(dp.0) / F32x4::splat(gradient_line.square_length());
+                let m13 = m0.zw() * -gradient_line.from().0;
+                Transform2F::row_major(m0.x(), m0.y(), m13.x() + m13.y(), 0.0, 0.0, v0)
+            }
+            PaintContents::Gradient(Gradient {
+                geometry: GradientGeometry::Radial { ref transform, .. },
+                ..
+            }) => transform.inverse(),
+            PaintContents::Pattern(ref pattern) => {
+                match pattern.source() {
+                    PatternSource::Image(_) => {
+                        let texture_origin_uv = rect_to_uv(texture_rect, texture_scale).origin();
+                        Transform2F::from_scale(texture_scale).translate(texture_origin_uv) *
+                            pattern.transform().inverse()
+                    }
+                    PatternSource::RenderTarget { .. } => {
+                        // FIXME(pcwalton): Only do this in GL, not Metal!
+                        let texture_origin_uv =
+                            rect_to_uv(texture_rect, texture_scale).lower_left();
+                        Transform2F::from_translation(texture_origin_uv) *
+                            Transform2F::from_scale(texture_scale * vec2f(1.0, -1.0)) *
+                            pattern.transform().inverse()
+                    }
+                }
+            }
+            PaintContents::Mesh(ref mesh) => {
+                let (_, mesh_to_image) = mesh_image(mesh, render_transform);
+                let texture_origin_uv = rect_to_uv(texture_rect, texture_scale).origin();
+                Transform2F::from_scale(texture_scale).translate(texture_origin_uv) *
+                    mesh_to_image
+            }
+            PaintContents::BlurredRoundedRect(ref shadow) => shadow.transform.inverse(),
+        };
+        transform * render_transform
+    }
+}
+
+// Chooses the size of the image a gradient mesh is rasterized into and the transform from the
+// mesh to it.
+//
+// The mesh is rasterized at roughly device resolution, with a transparent border so that clamped
+// sampling outside the mesh stays transparent.
+fn mesh_image(mesh: &GradientMesh, render_transform: Transform2F) -> (Vector2I, Transform2F) {
+    let bounds = mesh.bounds();
+    let device_size = bounds.size() * render_transform.extract_scale();
+    let size = device_size.ceil()
+                          .to_i32()
+                          .max(Vector2I::splat(1))
+                          .min(Vector2I::splat(MAX_MESH_TEXTURE_LENGTH - 2));
+    let scale = size.to_f32() / bounds.size().max(Vector2F::splat(1.0e-6));
+    let mesh_to_image = Transform2F::from_translation(Vector2F::splat(1.0)) *
+        Transform2F::from_scale(scale) *
+        Transform2F::from_translation(-bounds.origin());
+    (size + Vector2I::splat(2), mesh_to_image)
 }
 
 fn rect_to_uv(rect: RectI, texture_scale: Vector2F) -> RectF {
     rect.to_f32() * texture_scale
 }
 
+fn next_palette_version() -> u64 {
+    NEXT_PALETTE_VERSION.fetch_add(1, Ordering::Relaxed) as u64
+}
+
 // Gradient allocation
 
 struct GradientTileBuilder {
@@ -639,8 +1030,9 @@ impl GradientTileBuilder {
         GradientTileBuilder { tiles: vec![] }
     }
 
-    fn allocate(&mut self, allocator: &mut TextureAllocator, gradient: &Gradient)
-                -> TextureLocation {
+    // Allocates a row of texels and fills it by sampling `sample` at each texel center.
+    fn allocate<F>(&mut self, allocator: &mut TextureAllocator, sample: F) -> TextureLocation
+                   where F: Fn(f32) -> ColorU {
         if self.tiles.is_empty() ||
                 self.tiles.last().unwrap().next_index == GRADIENT_TILE_LENGTH {
             let size = Vector2I::splat(GRADIENT_TILE_LENGTH as i32);
@@ -660,16 +1052,9 @@ impl GradientTileBuilder {
         };
         data.next_index += 1;
 
-        // FIXME(pcwalton): Paint transparent if gradient line has zero size, per spec.
-        // TODO(pcwalton): Optimize this:
-        // 1. Calculate ∇t up front and use differencing in the inner loop.
-        // 2. Go four pixels at a time with SIMD.
         let first_address = location.rect.origin_y() as usize * GRADIENT_TILE_LENGTH as usize;
-        for x in 0..(GRADIENT_TILE_LENGTH as i32) {
-            let t = (x as f32 + 0.5) / GRADIENT_TILE_LENGTH as f32;
-            data.texels[first_address + x as usize] = gradient.sample(t);
-        }
-
+        let last_address = first_address + GRADIENT_TILE_LENGTH as usize;
+        sample_gradient_row(&mut data.texels[first_address..last_address], sample);
         location
     }
 
@@ -686,9 +1071,22 @@ impl GradientTileBuilder {
     }
 }
 
-struct ImageTexelInfo {
-    location: TextureLocation,
-    texels: Arc<Vec<ColorU>>,
+// Fills a row of a gradient tile by sampling `sample` at each texel center.
+fn sample_gradient_row<F>(texels: &mut [ColorU], sample: F) where F: Fn(f32) -> ColorU {
+    // FIXME(pcwalton): Paint transparent if gradient line has zero size, per spec.
+    // TODO(pcwalton): Optimize this:
+    // 1. Calculate ∇t up front and use differencing in the inner loop.
+    // 2. Go four pixels at a time with SIMD.
+    for (x, texel) in texels.iter_mut().enumerate() {
+        let t = (x as f32 + 0.5) / GRADIENT_TILE_LENGTH as f32;
+        *texel = sample(t);
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ImageTexelInfo {
+    pub(crate) location: TextureLocation,
+    pub(crate) texels: Arc<Vec<ColorU>>,
 }
 
 impl PaintColorTextureMetadata {
@@ -15,17 +15,29 @@ use crate::concurrent::executor::Executor;
 use crate::options::{BuildOptions, PreparedBuildOptions};
 use crate::options::{PreparedRenderTransform, RenderCommandListener};
 use crate::paint::{MergedPaletteInfo, Paint, PaintId, PaintInfo, Palette};
-use pathfinder_content::effects::BlendMode;
+use pathfinder_content::effects::{BackdropFilter, BlendMode, BlurDirection, PatternFilter};
 use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::Outline;
+use pathfinder_content::pattern::Pattern;
 use pathfinder_content::render_target::RenderTargetId;
-use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::{Vector2I, vec2f};
+use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2f};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Range;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 static NEXT_SCENE_ID: AtomicUsize = AtomicUsize::new(0);
 
+// Backdrops are downsampled until their blur is no wider than this, in pixels at the downsampled
+// resolution, or until they've been downsampled this many times.
+const MAX_BACKDROP_BLUR_SIGMA: f32 = 4.0;
+const MAX_BACKDROP_DOWNSAMPLE_LEVELS: u32 = 4;
+
 #[derive(Clone)]
 pub struct Scene {
     pub(crate) display_list: Vec<DisplayItem>,
@@ -55,10 +67,13 @@ impl Scene {
         }
     }
 
-    pub fn push_path(&mut self, path: DrawPath) {
+    /// Adds `path` to the top of the scene and returns its ID, which stays valid for the lifetime
+    /// of the scene, or until paths are removed with `retain()`. See `PathId`.
+    pub fn push_path(&mut self, path: DrawPath) -> PathId {
         let path_index = self.paths.len() as u32;
         self.paths.push(path);
         self.push_path_with_index(path_index);
+        PathId(path_index)
     }
 
     fn push_path_with_index(&mut self, path_index: u32) {
@@ -94,6 +109,176 @@ impl Scene {
         self.display_list.push(DisplayItem::PopRenderTarget);
     }
 
+    /// Draws `scene` into a new render target `size` pixels large and returns a pattern that
+    /// paints with the result.
+    ///
+    /// The sub-scene is drawn in its own coordinate space, with the origin at the top left of the
+    /// render target, and the pattern starts out in that same space; transform the pattern to
+    /// place it. The sub-scene is redrawn every time this scene is built, so it can't go stale.
+    pub fn push_scene_as_pattern(&mut self, scene: Scene, size: Vector2I) -> Pattern {
+        // The sub-scene's paths don't contribute to our bounds, since they're only visible
+        // through whatever the pattern fills.
+        let bounds = self.bounds;
+        let render_target_id = self.push_render_target(RenderTarget::new(size, String::new()));
+        self.append_scene(scene);
+        self.pop_render_target();
+        self.bounds = bounds;
+        Pattern::from_render_target(render_target_id, size)
+    }
+
+    /// Draws `layer` at `scale` times the resolution of this scene and composites the result,
+    /// with bilinear filtering, on top of what has been drawn so far.
+    ///
+    /// Expensive content, such as a detailed map being panned, can be drawn at reduced resolution
+    /// this way while text and controls in other layers stay sharp. The layer shares this scene's
+    /// coordinate space. Any render targets within it keep their sizes.
+    pub fn push_layer_at_scale(&mut self, mut layer: Scene, scale: f32) {
+        let layer_rect = match layer.bounds
+                                    .round_out()
+                                    .to_i32()
+                                    .intersection(self.current_render_target_bounds()) {
+            Some(layer_rect) if layer_rect.width() > 0 && layer_rect.height() > 0 => layer_rect,
+            _ => return,
+        };
+
+        let layer_size = (layer_rect.size().to_f32() * scale).ceil().to_i32();
+        let layer_size = layer_size.max(Vector2I::splat(1));
+        let to_layer = Transform2F::from_scale(layer_size.to_f32() / layer_rect.size().to_f32()) *
+            Transform2F::from_translation(-layer_rect.origin().to_f32());
+        layer.apply_transform(&to_layer);
+
+        // Only the composited rectangle contributes to our bounds.
+        let bounds = self.bounds;
+        let mut pattern = self.push_scene_as_pattern(layer, layer_size);
+        pattern.apply_transform(to_layer.inverse());
+        pattern.set_smoothing_enabled(true);
+        let paint_id = self.push_paint(&Paint::from_pattern(pattern));
+        self.push_path(DrawPath::new(Outline::from_rect(layer_rect.to_f32()), paint_id));
+        self.bounds = bounds.union_rect(layer_rect.to_f32());
+    }
+
+    /// Applies `filter` to whatever has been drawn to the current render target so far, inside
+    /// `outline` (filled with `fill_rule`) and the clip path. Paths pushed afterward draw on top
+    /// of the filtered backdrop, so filling the same outline with a translucent color produces a
+    /// "frosted glass" look.
+    ///
+    /// The backdrop is copied out of the render target, halved in resolution until the blur is
+    /// cheap, blurred in two passes, and scaled back up as it's drawn.
+    pub fn push_backdrop_filter(&mut self,
+                                outline: Outline,
+                                fill_rule: FillRule,
+                                filter: BackdropFilter,
+                                clip_path: Option<ClipPathId>) {
+        let sigma = match filter {
+            BackdropFilter::Blur { sigma } => sigma,
+        };
+        if sigma <= 0.0 {
+            return;
+        }
+
+        // The blur reaches 3σ past the edges, so copy out that much more of the backdrop.
+        let source_rect = outline.bounds().dilate(sigma * 3.0).round_out().to_i32();
+        let source_rect = match source_rect.intersection(self.current_render_target_bounds()) {
+            Some(source_rect) if source_rect.width() > 0 && source_rect.height() > 0 => {
+                source_rect
+            }
+            _ => return,
+        };
+
+        let (mut level_count, mut level_sigma) = (0, sigma);
+        while level_sigma > MAX_BACKDROP_BLUR_SIGMA &&
+                level_count < MAX_BACKDROP_DOWNSAMPLE_LEVELS {
+            level_sigma *= 0.5;
+            level_count += 1;
+        }
+
+        // The intermediate paths are only visible through the backdrop, so they don't contribute
+        // to our bounds.
+        let bounds = self.bounds;
+
+        // The resolve performs the first halving, if any.
+        let mut level_size = source_rect.size();
+        if level_count > 0 {
+            level_size = half_size(level_size);
+        }
+        let mut level_id =
+            self.palette.push_render_target(RenderTarget::new(level_size, String::new()));
+        self.display_list.push(DisplayItem::ResolveBackdrop {
+            render_target: level_id,
+            source_rect,
+        });
+
+        for _ in 1..level_count {
+            let next_level_size = half_size(level_size);
+            let mut pattern = Pattern::from_render_target(level_id, level_size);
+            pattern.apply_transform(Transform2F::from_scale(next_level_size.to_f32() /
+                                                            level_size.to_f32()));
+            let next_level_id = self.push_render_target_with_pattern(pattern, next_level_size);
+            level_id = next_level_id;
+            level_size = next_level_size;
+        }
+
+        let mut pattern_x = Pattern::from_render_target(level_id, level_size);
+        pattern_x.set_filter(Some(PatternFilter::Blur {
+            direction: BlurDirection::X,
+            sigma: level_sigma,
+        }));
+        let blur_x_id = self.push_render_target_with_pattern(pattern_x, level_size);
+
+        self.bounds = bounds;
+
+        let mut pattern_y = Pattern::from_render_target(blur_x_id, level_size);
+        pattern_y.set_filter(Some(PatternFilter::Blur {
+            direction: BlurDirection::Y,
+            sigma: level_sigma,
+        }));
+        pattern_y.apply_transform(
+            Transform2F::from_translation(source_rect.origin().to_f32()) *
+            Transform2F::from_scale(source_rect.size().to_f32() / level_size.to_f32()));
+        let paint_id = self.push_paint(&Paint::from_pattern(pattern_y));
+        let mut path = DrawPath::new(outline, paint_id);
+        path.set_clip_path(clip_path);
+        path.set_fill_rule(fill_rule);
+        self.push_path(path);
+
+        fn half_size(size: Vector2I) -> Vector2I {
+            (size.to_f32() * 0.5).ceil().to_i32()
+        }
+    }
+
+    // Fills a new render target `size` pixels large with `pattern`.
+    fn push_render_target_with_pattern(&mut self, pattern: Pattern, size: Vector2I)
+                                       -> RenderTargetId {
+        let paint_id = self.push_paint(&Paint::from_pattern(pattern));
+        let render_target_id = self.push_render_target(RenderTarget::new(size, String::new()));
+        let outline = Outline::from_rect(RectF::new(Vector2F::zero(), size.to_f32()));
+        self.push_path(DrawPath::new(outline, paint_id));
+        self.pop_render_target();
+        render_target_id
+    }
+
+    // The area that paths pushed now would draw to, in their coordinate space.
+    fn current_render_target_bounds(&self) -> RectI {
+        let mut render_target_stack = vec![];
+        for display_item in &self.display_list {
+            match *display_item {
+                DisplayItem::PushRenderTarget(render_target_id) => {
+                    render_target_stack.push(render_target_id)
+                }
+                DisplayItem::PopRenderTarget => {
+                    render_target_stack.pop();
+                }
+                DisplayItem::DrawPaths { .. } | DisplayItem::ResolveBackdrop { .. } => {}
+            }
+        }
+        match render_target_stack.last() {
+            Some(&render_target_id) => {
+                RectI::new(Vector2I::zero(), self.palette.render_target_size(render_target_id))
+            }
+            None => self.view_box.round_out().to_i32(),
+        }
+    }
+
     pub fn append_scene(&mut self, scene: Scene) {
         let MergedPaletteInfo {
             render_target_mapping,
@@ -114,12 +299,21 @@ impl Scene {
             self.paths.push(DrawPath {
                 outline: draw_path.outline,
                 paint: paint_mapping[&draw_path.paint],
+                contour_paints: draw_path.contour_paints.into_iter().map(|contour_paint| {
+                    ContourPaint {
+                        contours: contour_paint.contours,
+                        paint: paint_mapping[&contour_paint.paint],
+                    }
+                }).collect(),
                 clip_path: draw_path.clip_path.map(|clip_path_id| {
                     ClipPathId(clip_path_mapping[clip_path_id.0 as usize] as u32)
                 }),
                 fill_rule: draw_path.fill_rule,
                 blend_mode: draw_path.blend_mode,
                 name: draw_path.name,
+                visible: draw_path.visible,
+                animated: draw_path.animated,
+                hit_test_id: draw_path.hit_test_id,
             });
         }
 
@@ -133,6 +327,12 @@ impl Scene {
                 DisplayItem::PopRenderTarget => {
                     self.display_list.push(DisplayItem::PopRenderTarget);
                 }
+                DisplayItem::ResolveBackdrop { render_target, source_rect } => {
+                    self.display_list.push(DisplayItem::ResolveBackdrop {
+                        render_target: render_target_mapping[&render_target],
+                        source_rect,
+                    });
+                }
                 DisplayItem::DrawPaths {
                     start_index: old_start_path_index,
                     end_index: old_end_path_index,
@@ -145,6 +345,29 @@ impl Scene {
         }
     }
 
+    /// Transforms all paths, clip paths, and paints in the scene, as well as its bounds and view
+    /// box.
+    pub fn apply_transform(&mut self, transform: &Transform2F) {
+        if transform.is_identity() {
+            return;
+        }
+
+        for path in &mut self.paths {
+            Arc::make_mut(&mut path.outline).transform(transform);
+        }
+        for clip_path in &mut self.clip_paths {
+            Arc::make_mut(&mut clip_path.outline).transform(transform);
+        }
+        self.palette.apply_transform(transform);
+        for display_item in &mut self.display_list {
+            if let DisplayItem::ResolveBackdrop { ref mut source_rect, .. } = *display_item {
+                *source_rect = (*transform * source_rect.to_f32()).round_out().to_i32();
+            }
+        }
+        self.bounds = *transform * self.bounds;
+        self.view_box = *transform * self.view_box;
+    }
+
     #[inline]
     pub fn build_paint_info(&mut self, render_transform: Transform2F) -> PaintInfo {
         self.palette.build_paint_info(render_transform)
@@ -155,11 +378,227 @@ impl Scene {
         self.palette.push_paint(paint)
     }
 
+    /// Replaces the paint with the given ID, which every path that uses it picks up.
+    ///
+    /// Equal paints are merged when they're pushed, so paths that were given equal paints share
+    /// an ID. To animate a paint, `Renderer::update_paint()` can update a built scene in place
+    /// with the same ID, without rebuilding it.
+    pub fn set_paint(&mut self, paint_id: PaintId, paint: Paint) {
+        self.palette.set_paint(paint_id, paint)
+    }
+
     #[inline]
     pub fn path_count(&self) -> usize {
         self.paths.len()
     }
 
+    #[inline]
+    pub fn get_path(&self, path_index: usize) -> &DrawPath {
+        &self.paths[path_index]
+    }
+
+    /// Shows or hides the path at `path_index` without disturbing the indices of the others.
+    #[inline]
+    pub fn set_path_visible(&mut self, path_index: usize, visible: bool) {
+        self.paths[path_index].set_visible(visible)
+    }
+
+    /// Replaces the geometry of a path in place, keeping its paint, clip, and position in the
+    /// display list. Other paths that shared the old outline keep it.
+    ///
+    /// IDs from before a call to `retain()` must be translated with the map it returns first.
+    pub fn set_path_outline(&mut self, path_id: PathId, new_outline: Outline) {
+        self.bounds = self.bounds.union_rect(new_outline.bounds());
+        self.paths[path_id.0 as usize].outline = Arc::new(new_outline);
+    }
+
+    /// Removes every path for which `predicate` returns false, given the path's ID, the path, and
+    /// its paint.
+    ///
+    /// The remaining paths keep their order and are renumbered from zero, as `Vec::retain()`
+    /// does, so path IDs from before the call no longer refer to the same paths. The returned map
+    /// gives each kept path's new ID. Clip paths, paints, and render targets are all kept, so their
+    /// IDs stay valid, and render targets still get drawn even if every path drawn into them was
+    /// removed. The bounds are left alone; use `set_bounds()` to tighten them.
+    pub fn retain<F>(&mut self, predicate: F) -> PathIdMap
+                     where F: FnMut(PathId, &DrawPath, &Paint) -> bool {
+        let keep = self.paths_matching(predicate);
+        self.retain_paths(&keep)
+    }
+
+    /// Splits the scene in two: the first scene has the paths for which `predicate` returns
+    /// true, and the second has the rest, each in their original order. Each scene comes with the
+    /// map from this scene's path IDs to its own, since paths are renumbered as by `retain()`.
+    ///
+    /// This is how static background content can be separated from foreground content that
+    /// changes every frame, so that each can be cached on its own. Both scenes share this one's
+    /// outlines, paints, clip paths, render targets, and view box. The first keeps this scene's
+    /// ID, and the second gets a new one, so that caches keyed on scene IDs tell them apart.
+    pub fn partition<F>(&self, predicate: F) -> ((Scene, PathIdMap), (Scene, PathIdMap))
+                        where F: FnMut(PathId, &DrawPath, &Paint) -> bool {
+        let keep = self.paths_matching(predicate);
+        let (mut matching, mut rest) = (self.clone(), self.clone());
+        let matching_ids = matching.retain_paths(&keep);
+        let rest_ids = rest.retain_paths(&keep.iter().map(|&keep| !keep).collect::<Vec<_>>());
+        rest.assign_new_id();
+        ((matching, matching_ids), (rest, rest_ids))
+    }
+
+    // Gives the scene an ID of its own, renaming its render targets to match.
+    fn assign_new_id(&mut self) {
+        let old_id = self.id;
+        self.id = SceneId(NEXT_SCENE_ID.fetch_add(1, Ordering::Relaxed) as u32);
+        self.palette.set_scene_id(self.id);
+
+        let new_id = self.id;
+        let rename = |render_target_id: &mut RenderTargetId| {
+            if render_target_id.scene == old_id.0 {
+                render_target_id.scene = new_id.0;
+            }
+        };
+        for display_item in &mut self.display_list {
+            match *display_item {
+                DisplayItem::PushRenderTarget(ref mut render_target_id) |
+                DisplayItem::ResolveBackdrop { render_target: ref mut render_target_id, .. } => {
+                    rename(render_target_id)
+                }
+                DisplayItem::PopRenderTarget | DisplayItem::DrawPaths { .. } => {}
+            }
+        }
+    }
+
+    fn paths_matching<F>(&self, mut predicate: F) -> Vec<bool>
+                         where F: FnMut(PathId, &DrawPath, &Paint) -> bool {
+        self.paths.iter().enumerate().map(|(path_index, path)| {
+            predicate(PathId(path_index as u32), path, self.get_paint(path.paint))
+        }).collect()
+    }
+
+    // Removes the paths whose entries in `keep` are false, renumbering the rest.
+    fn retain_paths(&mut self, keep: &[bool]) -> PathIdMap {
+        // Paths are in the display list in index order, so the paths kept from each range of old
+        // indices make up a range of new ones, starting at the number kept before it.
+        let mut kept_before = Vec::with_capacity(keep.len() + 1);
+        kept_before.push(0);
+        for &keep in keep {
+            kept_before.push(kept_before[kept_before.len() - 1] + keep as u32);
+        }
+
+        let display_list = mem::take(&mut self.display_list);
+        for display_item in display_list {
+            match display_item {
+                DisplayItem::DrawPaths { start_index, end_index } => {
+                    let start_index = kept_before[start_index as usize];
+                    let end_index = kept_before[end_index as usize];
+                    if start_index == end_index {
+                        continue;
+                    }
+                    if let Some(DisplayItem::DrawPaths {
+                        end_index: ref mut last_end_index,
+                        ..
+                    }) = self.display_list.last_mut() {
+                        if *last_end_index == start_index {
+                            *last_end_index = end_index;
+                            continue;
+                        }
+                    }
+                    self.display_list.push(DisplayItem::DrawPaths { start_index, end_index });
+                }
+                display_item => self.display_list.push(display_item),
+            }
+        }
+
+        let mut keep_iter = keep.iter();
+        self.paths.retain(|_| *keep_iter.next().unwrap());
+
+        let new_ids = keep.iter().zip(kept_before.iter()).map(|(&keep, &new_index)| {
+            if keep { Some(PathId(new_index)) } else { None }
+        }).collect();
+        PathIdMap { new_ids }
+    }
+
+    #[inline]
+    pub fn get_clip_path(&self, clip_path_id: ClipPathId) -> &ClipPath {
+        &self.clip_paths[clip_path_id.0 as usize]
+    }
+
+    #[inline]
+    pub fn clip_path_count(&self) -> usize {
+        self.clip_paths.len()
+    }
+
+    /// Returns the clip paths of the visible paths drawn straight to the framebuffer, outside of
+    /// any render target, in the order they're first used.
+    ///
+    /// These are the clips that can be seen directly in the output, which is what debug overlays
+    /// outline.
+    pub fn active_clip_paths(&self) -> Vec<ClipPathId> {
+        let (mut clip_path_ids, mut render_target_depth) = (vec![], 0);
+        for display_item in &self.display_list {
+            match *display_item {
+                DisplayItem::PushRenderTarget(_) => render_target_depth += 1,
+                DisplayItem::PopRenderTarget => render_target_depth -= 1,
+                DisplayItem::DrawPaths { start_index, end_index } if render_target_depth == 0 => {
+                    for path in &self.paths[start_index as usize..end_index as usize] {
+                        if let Some(clip_path_id) = path.clip_path {
+                            if path.visible && !clip_path_ids.contains(&clip_path_id) {
+                                clip_path_ids.push(clip_path_id);
+                            }
+                        }
+                    }
+                }
+                DisplayItem::DrawPaths { .. } | DisplayItem::ResolveBackdrop { .. } => {}
+            }
+        }
+        clip_path_ids
+    }
+
+    /// Returns every render target in the scene, each followed by the render targets pushed
+    /// while it was on top of the stack.
+    pub fn render_target_hierarchy(&self) -> Vec<RenderTargetInfo> {
+        let (mut render_targets, mut render_target_stack) = (vec![], vec![]);
+        for display_item in &self.display_list {
+            match *display_item {
+                DisplayItem::PushRenderTarget(id) => {
+                    let render_target = self.palette.render_target(id);
+                    render_target_stack.push(render_targets.len());
+                    render_targets.push(RenderTargetInfo {
+                        id,
+                        name: render_target.name().to_owned(),
+                        size: render_target.size(),
+                        depth: render_target_stack.len() as u32 - 1,
+                        path_count: 0,
+                        clipped_path_count: 0,
+                    });
+                }
+                DisplayItem::PopRenderTarget => {
+                    render_target_stack.pop();
+                }
+                DisplayItem::DrawPaths { start_index, end_index } => {
+                    if let Some(&index) = render_target_stack.last() {
+                        let info = &mut render_targets[index];
+                        let paths = &self.paths[start_index as usize..end_index as usize];
+                        info.path_count += paths.len();
+                        info.clipped_path_count +=
+                            paths.iter().filter(|path| path.clip_path.is_some()).count();
+                    }
+                }
+                DisplayItem::ResolveBackdrop { .. } => {}
+            }
+        }
+        render_targets
+    }
+
+    #[inline]
+    pub fn get_paint(&self, paint_id: PaintId) -> &Paint {
+        &self.palette.paints[paint_id.0 as usize]
+    }
+
+    #[inline]
+    pub fn paint_count(&self) -> usize {
+        self.palette.paints.len()
+    }
+
     #[inline]
     pub fn bounds(&self) -> RectF {
         self.bounds
@@ -180,6 +619,69 @@ impl Scene {
         self.view_box = new_view_box;
     }
 
+    /// Returns a hash of everything about the scene that affects how it's drawn: the outlines,
+    /// paints, fill rules, blend modes, clips, and visibility of its paths, its render targets,
+    /// and its view box.
+    ///
+    /// Path names, hit-testing IDs, and the scene's own ID aren't included, so two scenes built
+    /// the same way hash the same. The hash is stable from one run of a program to the next,
+    /// though not across versions of Pathfinder or Rust, so it's suitable for keying caches of
+    /// rendered output, such as image tiles saved by a map server. The build options used to
+    /// render the scene should be part of such keys as well.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.palette.hash_contents(&mut hasher);
+
+        self.paths.len().hash(&mut hasher);
+        for path in &self.paths {
+            path.outline.hash(&mut hasher);
+            path.paint.hash(&mut hasher);
+            path.contour_paints.len().hash(&mut hasher);
+            for contour_paint in &path.contour_paints {
+                contour_paint.contours.hash(&mut hasher);
+                contour_paint.paint.hash(&mut hasher);
+            }
+            path.clip_path.map(|clip_path| clip_path.0).hash(&mut hasher);
+            path.fill_rule.hash(&mut hasher);
+            path.blend_mode.hash(&mut hasher);
+            path.visible.hash(&mut hasher);
+            path.animated.hash(&mut hasher);
+        }
+
+        self.clip_paths.len().hash(&mut hasher);
+        for clip_path in &self.clip_paths {
+            clip_path.outline.hash(&mut hasher);
+            clip_path.fill_rule.hash(&mut hasher);
+        }
+
+        self.display_list.len().hash(&mut hasher);
+        for display_item in &self.display_list {
+            match *display_item {
+                DisplayItem::DrawPaths { start_index, end_index } => {
+                    (0u8, start_index, end_index).hash(&mut hasher);
+                }
+                DisplayItem::PushRenderTarget(render_target) => {
+                    (1u8, render_target).hash(&mut hasher);
+                }
+                DisplayItem::PopRenderTarget => 2u8.hash(&mut hasher),
+                DisplayItem::ResolveBackdrop { render_target, source_rect } => {
+                    (3u8, render_target).hash(&mut hasher);
+                    (source_rect.origin_x(), source_rect.origin_y()).hash(&mut hasher);
+                    (source_rect.width(), source_rect.height()).hash(&mut hasher);
+                }
+            }
+        }
+
+        for &value in &[self.view_box.origin_x(),
+                        self.view_box.origin_y(),
+                        self.view_box.width(),
+                        self.view_box.height()] {
+            value.to_bits().hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+
     pub(crate) fn apply_render_options(
         &self,
         original_outline: &Outline,
@@ -237,11 +739,14 @@ impl Scene {
         }
     }
 
+    /// Builds the scene, sending the resulting render commands to `listener`, and returns
+    /// statistics about the work done.
     #[inline]
     pub fn build<'a, E>(&mut self,
                     options: BuildOptions,
                     listener: Box<dyn RenderCommandListener + 'a>,
                     executor: &E)
+                    -> SceneStats
                     where E: Executor {
         let prepared_options = options.prepare(self.bounds);
         SceneBuilder::new(self, &prepared_options, listener).build(executor)
@@ -266,7 +771,7 @@ impl<'a> Iterator for PathIter<'a> {
         let item = self.scene.paths.get(self.pos).map(|path_object| {
             (
                 self.scene.palette.paints.get(path_object.paint.0 as usize).unwrap(),
-                &path_object.outline,
+                &*path_object.outline,
                 &*path_object.name
             )
         });
@@ -275,32 +780,101 @@ impl<'a> Iterator for PathIter<'a> {
     }
 }
 
+/// Statistics gathered while building a scene.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SceneStats {
+    pub draw_path_count: usize,
+    pub clip_path_count: usize,
+    /// The number of edge segments rasterized into alpha tile masks.
+    pub fill_count: usize,
+    /// The number of masked tiles that survived occlusion culling.
+    pub alpha_tile_count: usize,
+    /// The number of fully-covered tiles that survived occlusion culling.
+    pub solid_tile_count: usize,
+    /// The number of tiles written to clip masks.
+    pub clip_tile_count: usize,
+    /// The number of tile batches, each of which costs at least one draw call.
+    pub tile_batch_count: usize,
+    pub cpu_build_time: Duration,
+}
+
 #[derive(Clone, Debug)]
 pub struct DrawPath {
-    outline: Outline,
+    outline: Arc<Outline>,
     paint: PaintId,
+    contour_paints: Vec<ContourPaint>,
     clip_path: Option<ClipPathId>,
     fill_rule: FillRule,
     blend_mode: BlendMode,
     name: String,
+    visible: bool,
+    animated: bool,
+    hit_test_id: Option<u32>,
+}
+
+/// Paints a range of the contours of a path. See `DrawPath::set_contour_paints()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContourPaint {
+    pub contours: Range<u32>,
+    pub paint: PaintId,
 }
 
 #[derive(Clone, Debug)]
 pub struct ClipPath {
-    outline: Outline,
+    outline: Arc<Outline>,
     fill_rule: FillRule,
+    inverted: bool,
     name: String,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct ClipPathId(pub u32);
 
+/// Identifies a path within a scene.
+///
+/// IDs stay valid as paths are added and changed, but `Scene::retain()` and `Scene::partition()`
+/// renumber the paths they keep. IDs held from before then, including those given to
+/// `Scene::set_path_outline()`, `Renderer::set_path_transform()`, and animated path layouts,
+/// must be translated with the `PathIdMap` that those methods return.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PathId(pub u32);
+
+/// The new IDs of the paths of a scene after `Scene::retain()` or `Scene::partition()`.
+#[derive(Clone, Debug)]
+pub struct PathIdMap {
+    new_ids: Vec<Option<PathId>>,
+}
+
+impl PathIdMap {
+    /// Returns the ID that the path once identified by `old_path_id` now has, or `None` if the
+    /// path was removed.
+    #[inline]
+    pub fn get(&self, old_path_id: PathId) -> Option<PathId> {
+        self.new_ids.get(old_path_id.0 as usize).cloned().flatten()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct RenderTarget {
     size: Vector2I,
     name: String,
 }
 
+/// A summary of a render target and where it sits among the others, for debugging.
+#[derive(Clone, Debug)]
+pub struct RenderTargetInfo {
+    pub id: RenderTargetId,
+    pub name: String,
+    pub size: Vector2I,
+    /// How many render targets this one was pushed inside of: zero if it was pushed onto the
+    /// framebuffer.
+    pub depth: u32,
+    /// The number of paths drawn directly to this render target.
+    pub path_count: usize,
+    /// How many of those paths have a clip path.
+    pub clipped_path_count: usize,
+}
+
 /// Drawing commands.
 #[derive(Clone, Debug)]
 pub enum DisplayItem {
@@ -312,18 +886,33 @@ pub enum DisplayItem {
 
     /// Pops a render target from the stack.
     PopRenderTarget,
+
+    /// Copies `source_rect` of the render target on top of the stack into `render_target`,
+    /// scaling it to fit. This is how backdrop filters read what's beneath them.
+    ResolveBackdrop { render_target: RenderTargetId, source_rect: RectI },
 }
 
 impl DrawPath {
     #[inline]
     pub fn new(outline: Outline, paint: PaintId) -> DrawPath {
+        DrawPath::from_shared_outline(Arc::new(outline), paint)
+    }
+
+    /// Creates a path that shares its geometry with other paths, which is cheaper than copying
+    /// the outline when the same shape is drawn many times with different paints or clips.
+    #[inline]
+    pub fn from_shared_outline(outline: Arc<Outline>, paint: PaintId) -> DrawPath {
         DrawPath {
             outline,
             paint,
+            contour_paints: vec![],
             clip_path: None,
             fill_rule: FillRule::Winding,
             blend_mode: BlendMode::SrcOver,
             name: String::new(),
+            visible: true,
+            animated: false,
+            hit_test_id: None,
         }
     }
 
@@ -332,6 +921,11 @@ impl DrawPath {
         &self.outline
     }
 
+    #[inline]
+    pub fn shared_outline(&self) -> &Arc<Outline> {
+        &self.outline
+    }
+
     #[inline]
     pub(crate) fn clip_path(&self) -> Option<ClipPathId> {
         self.clip_path
@@ -343,10 +937,27 @@ impl DrawPath {
     }
 
     #[inline]
-    pub(crate) fn paint(&self) -> PaintId {
+    pub fn paint(&self) -> PaintId {
         self.paint
     }
 
+    #[inline]
+    pub fn contour_paints(&self) -> &[ContourPaint] {
+        &self.contour_paints
+    }
+
+    /// Draws ranges of the contours of this path with different paints, as layered (COLR) glyphs
+    /// and multi-part icons need.
+    ///
+    /// If this is nonempty, each range of contours is drawn with its paint, in order, instead of
+    /// drawing the whole outline with `paint()`; contours outside all of the ranges aren't drawn.
+    /// The path is transformed and culled once, and the clip, fill rule, and blend mode apply to
+    /// every range.
+    #[inline]
+    pub fn set_contour_paints(&mut self, new_contour_paints: Vec<ContourPaint>) {
+        self.contour_paints = new_contour_paints
+    }
+
     #[inline]
     pub(crate) fn fill_rule(&self) -> FillRule {
         self.fill_rule
@@ -367,16 +978,66 @@ impl DrawPath {
         self.blend_mode = new_blend_mode
     }
 
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     #[inline]
     pub fn set_name(&mut self, new_name: String) {
         self.name = new_name
     }
+
+    /// Invisible paths keep their place in the scene but produce no tiles.
+    #[inline]
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    #[inline]
+    pub fn set_visible(&mut self, new_visible: bool) {
+        self.visible = new_visible
+    }
+
+    /// Animated paths can be moved after the scene is built, without rebuilding it, with
+    /// `Renderer::set_path_transform()`.
+    ///
+    /// They're tiled in full, even where they're outside the view box, and never occlude the
+    /// paths under them, so only mark paths that actually move. Their clip paths move with them.
+    /// At most `MAX_ANIMATED_PATHS` paths per scene are animated, in order; the rest, and any
+    /// paths with blend modes that read from or clear the destination, are drawn as ordinary
+    /// paths.
+    #[inline]
+    pub fn is_animated(&self) -> bool {
+        self.animated
+    }
+
+    #[inline]
+    pub fn set_animated(&mut self, new_animated: bool) {
+        self.animated = new_animated
+    }
+
+    /// The ID that GPU picking reports for this path. See `gpu::picking::PickBuffer`.
+    #[inline]
+    pub fn hit_test_id(&self) -> Option<u32> {
+        self.hit_test_id
+    }
+
+    #[inline]
+    pub fn set_hit_test_id(&mut self, new_hit_test_id: Option<u32>) {
+        self.hit_test_id = new_hit_test_id
+    }
 }
 
 impl ClipPath {
     #[inline]
     pub fn new(outline: Outline) -> ClipPath {
-        ClipPath { outline, fill_rule: FillRule::Winding, name: String::new() }
+        ClipPath::from_shared_outline(Arc::new(outline))
+    }
+
+    #[inline]
+    pub fn from_shared_outline(outline: Arc<Outline>) -> ClipPath {
+        ClipPath { outline, fill_rule: FillRule::Winding, inverted: false, name: String::new() }
     }
 
     #[inline]
@@ -384,6 +1045,11 @@ impl ClipPath {
         &self.outline
     }
 
+    #[inline]
+    pub fn shared_outline(&self) -> &Arc<Outline> {
+        &self.outline
+    }
+
     #[inline]
     pub(crate) fn fill_rule(&self) -> FillRule {
         self.fill_rule
@@ -394,6 +1060,28 @@ impl ClipPath {
         self.fill_rule = new_fill_rule
     }
 
+    /// Returns true if this clip path keeps what lies outside its outline instead of inside.
+    #[inline]
+    pub fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+
+    /// Makes this clip path cut its outline out of the paths it clips, rather than keeping only
+    /// the part within it.
+    ///
+    /// This is how to punch holes in paths without having to surround the hole with a rectangle
+    /// covering the whole scene. Like ordinary clip paths, inverted ones are antialiased with the
+    /// nonzero winding rule where they cross a tile.
+    #[inline]
+    pub fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     #[inline]
     pub fn set_name(&mut self, new_name: String) {
         self.name = new_name
@@ -410,4 +1098,562 @@ impl RenderTarget {
     pub fn size(&self) -> Vector2I {
         self.size
     }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl RenderTargetInfo {
+    /// The size of the render target's RGBA8 texture, in bytes.
+    #[inline]
+    pub fn memory_bytes(&self) -> usize {
+        self.size.x() as usize * self.size.y() as usize * 4
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu_data::{RenderCommand, TileBatch};
+    use crate::options::{BuildOptions, QualitySettings};
+    use crate::paint::{Paint, PaintLayout};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::effects::{BackdropFilter, BlendMode, Filter, PatternFilter};
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::gradient::Gradient;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, vec2f, vec2i};
+    use pathfinder_simd::default::F32x2;
+    use std::sync::{Arc, Mutex};
+    use super::{ClipPath, ContourPaint, DisplayItem, DrawPath, PathId, RenderTarget, Scene};
+
+    #[test]
+    fn test_shared_outlines() {
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let outline = Outline::from_rect(RectF::new(Vector2F::zero(), vec2f(64.0, 64.0)));
+        let path_id = scene.push_path(DrawPath::new(outline, paint));
+
+        // Instances and clones of the scene share geometry rather than copying it.
+        let shared_outline = scene.get_path(0).shared_outline().clone();
+        let instance_id = scene.push_path(DrawPath::from_shared_outline(shared_outline, paint));
+        let clone = scene.clone();
+        assert!(Arc::ptr_eq(scene.get_path(0).shared_outline(),
+                            clone.get_path(instance_id.0 as usize).shared_outline()));
+
+        // Replacing the outline of one path leaves the other paths and the clone alone.
+        let outline = Outline::from_rect(RectF::new(vec2f(64.0, 64.0), vec2f(64.0, 64.0)));
+        scene.set_path_outline(path_id, outline);
+        assert_eq!(scene.get_path(0).outline().bounds().origin(), vec2f(64.0, 64.0));
+        assert_eq!(scene.get_path(1).outline().bounds().origin(), Vector2F::zero());
+        assert_eq!(clone.get_path(0).outline().bounds().origin(), Vector2F::zero());
+        assert_eq!(scene.bounds(), RectF::new(Vector2F::zero(), vec2f(128.0, 128.0)));
+    }
+
+    #[test]
+    fn test_set_path_outline_after_retain() {
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let rect = |x| Outline::from_rect(RectF::new(vec2f(x, 0.0), vec2f(16.0, 16.0)));
+        let path_ids: Vec<_> = (0..4).map(|index| {
+            scene.push_path(DrawPath::new(rect(index as f32 * 16.0), paint))
+        }).collect();
+
+        let new_path_ids = scene.retain(|path_id, _, _| path_id != path_ids[1]);
+        let surviving_path_id = new_path_ids.get(path_ids[2]).unwrap();
+        scene.set_path_outline(surviving_path_id, rect(128.0));
+
+        // Only the path that was third before the removal moves.
+        let origins: Vec<_> = (0..scene.path_count()).map(|path_index| {
+            scene.get_path(path_index).outline().bounds().origin_x()
+        }).collect();
+        assert_eq!(origins, vec![0.0, 128.0, 48.0]);
+        assert_eq!(new_path_ids.get(path_ids[1]), None);
+    }
+
+    #[test]
+    fn test_backdrop_filter_ordering() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(256.0, 256.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let rect = |origin| Outline::from_rect(RectF::new(origin, vec2f(64.0, 64.0)));
+
+        scene.push_path(DrawPath::new(rect(Vector2F::zero()), paint));
+        scene.push_backdrop_filter(rect(Vector2F::zero()),
+                                   FillRule::Winding,
+                                   BackdropFilter::Blur { sigma: 2.0 },
+                                   None);
+        // Far enough away that only this path has tiles at or beyond (128, 128).
+        scene.push_path(DrawPath::new(rect(vec2f(128.0, 128.0)), paint));
+
+        let commands = Mutex::new(vec![]);
+        let listener = Box::new(|command| commands.lock().unwrap().push(command));
+        scene.build(BuildOptions::default(), listener, &SequentialExecutor);
+
+        // The last path's solid tiles must not be drawn before the backdrop is copied, even
+        // though solid tiles are normally drawn first.
+        let commands = commands.into_inner().unwrap();
+        let mut resolved = false;
+        let mut late_tiles_drawn = false;
+        for command in &commands {
+            match *command {
+                RenderCommand::Start { needs_readable_framebuffer, .. } => {
+                    assert!(needs_readable_framebuffer);
+                }
+                RenderCommand::ResolveBackdrop { .. } => resolved = true,
+                RenderCommand::DrawTiles(ref batch) => {
+                    if batch.tiles.iter().any(|tile| tile.tile_x >= 8 && tile.tile_y >= 8) {
+                        assert!(resolved);
+                        late_tiles_drawn = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        assert!(late_tiles_drawn);
+    }
+
+    #[test]
+    fn test_animated_paths() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(256.0, 256.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let rect = |origin, size| Outline::from_rect(RectF::new(origin, size));
+        let animated_path = |scene: &mut Scene, outline, blend_mode, visible| {
+            let mut path = DrawPath::new(outline, paint);
+            path.set_blend_mode(blend_mode);
+            path.set_visible(visible);
+            path.set_animated(true);
+            scene.push_path(path)
+        };
+
+        // Covered by the opaque path above it, and hanging off the right of the view box.
+        animated_path(&mut scene, rect(Vector2F::zero(), vec2f(64.0, 64.0)), BlendMode::SrcOver,
+                      true);
+        scene.push_path(DrawPath::new(rect(Vector2F::zero(), vec2f(128.0, 128.0)), paint));
+        let offscreen_path = animated_path(&mut scene,
+                                           rect(vec2f(224.0, 0.0), vec2f(64.0, 16.0)),
+                                           BlendMode::SrcOver,
+                                           true);
+        // These two can't be animated.
+        animated_path(&mut scene, rect(Vector2F::zero(), vec2f(16.0, 16.0)), BlendMode::Clear,
+                      true);
+        animated_path(&mut scene, rect(Vector2F::zero(), vec2f(16.0, 16.0)), BlendMode::SrcOver,
+                      false);
+
+        let build = |scene: &mut Scene| {
+            let commands = Mutex::new(vec![]);
+            let listener = Box::new(|command| commands.lock().unwrap().push(command));
+            scene.build(BuildOptions::default(), listener, &SequentialExecutor);
+            commands.into_inner().unwrap()
+        };
+        let declared_layout = |commands: &[RenderCommand]| {
+            commands.iter().filter_map(|command| {
+                match *command {
+                    RenderCommand::DeclareAnimatedPaths(ref layout) => Some((**layout).clone()),
+                    _ => None,
+                }
+            }).next().unwrap()
+        };
+
+        let commands = build(&mut scene);
+        let layout = declared_layout(&commands);
+        assert_eq!(layout.paths, vec![PathId(0), offscreen_path]);
+        assert_eq!(layout.render_transform, Transform2F::default());
+
+        // Animated tiles are never culled, by paths in front of them or by the view box.
+        let mut slot_tile_counts = [0; 3];
+        let mut offscreen_tile_count = 0;
+        for command in &commands {
+            if let RenderCommand::DrawTiles(ref batch) = *command {
+                for tile in &batch.tiles {
+                    slot_tile_counts[tile.transform_slot as usize] += 1;
+                    if tile.tile_x >= 16 {
+                        assert_eq!(tile.transform_slot, 2);
+                        offscreen_tile_count += 1;
+                    }
+                }
+            }
+        }
+        assert_eq!(slot_tile_counts[1], 16);
+        assert_eq!(slot_tile_counts[2], 4);
+        assert_eq!(offscreen_tile_count, 2);
+
+        // Rebuilding the same scene declares the same layout, so transforms carry over, but
+        // moving an animated path changes it.
+        assert_eq!(declared_layout(&build(&mut scene)), layout);
+        scene.set_path_outline(offscreen_path, rect(vec2f(192.0, 0.0), vec2f(64.0, 16.0)));
+        assert_ne!(declared_layout(&build(&mut scene)).geometry_hash, layout.geometry_hash);
+    }
+
+    #[test]
+    fn test_blur_can_be_disabled() {
+        let blur_batch_count = |quality| {
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(256.0, 256.0)));
+            let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+            let rect = Outline::from_rect(RectF::new(Vector2F::zero(), vec2f(64.0, 64.0)));
+            scene.push_path(DrawPath::new(rect.clone(), paint));
+            let blur = BackdropFilter::Blur { sigma: 2.0 };
+            scene.push_backdrop_filter(rect, FillRule::Winding, blur, None);
+
+            let commands = Mutex::new(vec![]);
+            let listener = Box::new(|command| commands.lock().unwrap().push(command));
+            scene.build(BuildOptions { quality, ..BuildOptions::default() },
+                        listener,
+                        &SequentialExecutor);
+            commands.into_inner().unwrap().iter().filter(|command| {
+                match **command {
+                    RenderCommand::DrawTiles(TileBatch {
+                        filter: Filter::PatternFilter(PatternFilter::Blur { .. }),
+                        ..
+                    }) => true,
+                    _ => false,
+                }
+            }).count()
+        };
+
+        assert!(blur_batch_count(QualitySettings::default()) > 0);
+        let quality = QualitySettings { blur_enabled: false, ..QualitySettings::default() };
+        assert_eq!(blur_batch_count(quality), 0);
+    }
+
+    #[test]
+    fn test_contour_paints() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(256.0, 256.0)));
+        let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let blue = scene.push_paint(&Paint::from_color(ColorU::new(0, 0, 255, 255)));
+
+        let mut outline = Outline::new();
+        for &origin in &[Vector2F::zero(), vec2f(128.0, 0.0), vec2f(0.0, 128.0)] {
+            let rect = Outline::from_rect(RectF::new(origin, vec2f(64.0, 64.0)));
+            outline.push_contour(rect.contours()[0].clone());
+        }
+        let mut path = DrawPath::new(outline, red);
+        path.set_contour_paints(vec![
+            ContourPaint { contours: 0..1, paint: red },
+            ContourPaint { contours: 1..2, paint: blue },
+        ]);
+        scene.push_path(path);
+
+        let commands = Mutex::new(vec![]);
+        let listener = Box::new(|command| commands.lock().unwrap().push(command));
+        scene.build(BuildOptions::default(), listener, &SequentialExecutor);
+
+        // Each range of contours is drawn with its own paint, and the third contour, which isn't
+        // in any range, isn't drawn at all.
+        let (mut red_tile_count, mut blue_tile_count) = (0, 0);
+        for command in commands.into_inner().unwrap() {
+            if let RenderCommand::DrawTiles(batch) = command {
+                for tile in batch.tiles {
+                    assert!(tile.tile_y < 8);
+                    if tile.tile_x < 8 {
+                        assert_eq!(tile.color, red.0);
+                        red_tile_count += 1;
+                    } else {
+                        assert_eq!(tile.color, blue.0);
+                        blue_tile_count += 1;
+                    }
+                }
+            }
+        }
+        assert!(red_tile_count > 0 && blue_tile_count > 0);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        fn build(color: ColorU, name: &str) -> Scene {
+            let mut scene = Scene::new();
+            let paint = scene.push_paint(&Paint::from_color(color));
+            let outline = Outline::from_rect(RectF::new(Vector2F::zero(), vec2f(64.0, 64.0)));
+            let mut path = DrawPath::new(outline, paint);
+            path.set_name(name.to_owned());
+            scene.push_path(path);
+            scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(100.0, 100.0)));
+            scene
+        }
+
+        // Scenes built separately hash the same, and names don't matter.
+        let (red, blue) = (ColorU::new(255, 0, 0, 255), ColorU::new(0, 0, 255, 255));
+        let hash = build(red, "a").content_hash();
+        assert_eq!(hash, build(red, "b").content_hash());
+        assert_ne!(hash, build(blue, "a").content_hash());
+
+        let mut scene = build(red, "a");
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(50.0, 50.0)));
+        assert_ne!(hash, scene.content_hash());
+    }
+
+    #[test]
+    fn test_layer_at_scale() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(256.0, 256.0)));
+
+        let mut layer = Scene::new();
+        let paint = layer.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let outline = Outline::from_rect(RectF::new(vec2f(32.0, 32.0), vec2f(64.0, 32.0)));
+        let layer_bounds = outline.bounds();
+        layer.push_path(DrawPath::new(outline, paint));
+        // Bounds start out including the origin, so tighten them.
+        layer.set_bounds(layer_bounds);
+        scene.push_layer_at_scale(layer, 0.5);
+
+        // The layer is drawn at half size into its own render target, and its path is moved to
+        // the origin of the render target.
+        let render_target_id = match scene.display_list[0] {
+            DisplayItem::PushRenderTarget(render_target_id) => render_target_id,
+            _ => panic!("Expected the layer to be drawn to a render target!"),
+        };
+        assert_eq!(scene.palette.render_target_size(render_target_id), vec2i(32, 16));
+        assert_eq!(scene.get_path(0).outline().bounds(),
+                   RectF::new(Vector2F::zero(), vec2f(32.0, 16.0)));
+
+        // It's composited back over the area it covers, scaled back up with filtering.
+        let composite_path = scene.get_path(1);
+        assert_eq!(composite_path.outline().bounds(),
+                   RectF::new(vec2f(32.0, 32.0), vec2f(64.0, 32.0)));
+        let pattern = scene.get_paint(composite_path.paint()).pattern().unwrap();
+        assert!(pattern.smoothing_enabled());
+        assert_eq!(pattern.transform() * Vector2F::zero(), vec2f(32.0, 32.0));
+        assert_eq!(pattern.transform() * vec2f(32.0, 16.0), vec2f(96.0, 64.0));
+        assert!(scene.bounds().contains_rect(composite_path.outline().bounds()));
+    }
+
+    #[test]
+    fn test_exact_axis_aligned_aa() {
+        // With these coordinates, interpolating along the top edge of the rectangle to its end
+        // lands one ulp past its right edge, which is enough to change the fixed-point position.
+        let (left, right): (f32, f32) = (5.987936, 50.80273);
+        let right_in_tile = ((right - 48.0) * 256.0).round() as u32;
+
+        let fill_xs = |exact_axis_aligned_aa| {
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(64.0, 64.0)));
+            let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+            let rect = RectF::from_points(vec2f(left, 4.0), vec2f(right, 12.0));
+            scene.push_path(DrawPath::new(Outline::from_rect(rect), paint));
+
+            let commands = Mutex::new(vec![]);
+            let listener = Box::new(|command| commands.lock().unwrap().push(command));
+            let options = BuildOptions { exact_axis_aligned_aa, ..BuildOptions::default() };
+            scene.build(options, listener, &SequentialExecutor);
+
+            let mut xs = vec![];
+            for command in commands.into_inner().unwrap() {
+                if let RenderCommand::AddFills(fills) = command {
+                    for entry in fills {
+                        let (px, subpx) = (entry.fill.px, entry.fill.subpx);
+                        xs.push((px.from as u32 & 0xf) * 256 + subpx.from_x as u32);
+                        xs.push((px.to as u32 & 0xf) * 256 + subpx.to_x as u32);
+                    }
+                }
+            }
+            xs
+        };
+
+        assert!(fill_xs(false).contains(&(right_in_tile + 1)));
+        let xs = fill_xs(true);
+        assert!(xs.contains(&right_in_tile));
+        assert!(!xs.contains(&(right_in_tile + 1)));
+
+        let build_exact = |rect| {
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(64.0, 64.0)));
+            let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+            scene.push_path(DrawPath::new(Outline::from_rect(rect), paint));
+
+            let commands = Mutex::new(vec![]);
+            let listener = Box::new(|command| commands.lock().unwrap().push(command));
+            let options = BuildOptions { exact_axis_aligned_aa: true, ..BuildOptions::default() };
+            let stats = scene.build(options, listener, &SequentialExecutor);
+
+            let mut fills = vec![];
+            for command in commands.into_inner().unwrap() {
+                if let RenderCommand::AddFills(batch) = command {
+                    fills.extend(batch.into_iter().map(|entry| entry.fill));
+                }
+            }
+            (stats, fills)
+        };
+
+        // A rectangle on tile boundaries is covered by solid tiles alone.
+        let (stats, fills) = build_exact(RectF::new(vec2f(16.0, 16.0), vec2f(32.0, 32.0)));
+        assert!(fills.is_empty());
+        assert_eq!((stats.fill_count, stats.alpha_tile_count), (0, 0));
+        assert_eq!(stats.solid_tile_count, 4);
+
+        // A fractional one gets fills along its top and bottom at exactly its edges.
+        let rect = RectF::from_points(vec2f(4.25, 4.5), vec2f(11.75, 12.0));
+        let (stats, fills) = build_exact(rect);
+        assert_eq!((stats.fill_count, stats.alpha_tile_count), (2, 1));
+        let mut edges: Vec<_> = fills.iter().map(|fill| {
+            let (px, subpx) = (fill.px, fill.subpx);
+            let from = vec2i((px.from & 0xf) as i32, (px.from >> 4) as i32) * 256 +
+                vec2i(subpx.from_x as i32, subpx.from_y as i32);
+            let to = vec2i((px.to & 0xf) as i32, (px.to >> 4) as i32) * 256 +
+                vec2i(subpx.to_x as i32, subpx.to_y as i32);
+            (from, to)
+        }).collect();
+        edges.sort_by_key(|&(from, _)| from.y());
+        assert_eq!(edges, vec![(vec2i(1088, 1152), vec2i(3008, 1152)),
+                               (vec2i(3008, 3072), vec2i(1088, 3072))]);
+    }
+
+    #[test]
+    fn test_render_target_hierarchy_and_active_clips() {
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let rect = Outline::from_rect(RectF::new(Vector2F::zero(), vec2f(16.0, 16.0)));
+        let clip_a = scene.push_clip_path(ClipPath::new(rect.clone()));
+        let clip_b = scene.push_clip_path(ClipPath::new(rect.clone()));
+        let clip_c = scene.push_clip_path(ClipPath::new(rect.clone()));
+
+        let mut clipped_path = DrawPath::new(rect.clone(), paint);
+        clipped_path.set_clip_path(Some(clip_b));
+        scene.push_path(clipped_path.clone());
+
+        let outer = scene.push_render_target(RenderTarget::new(vec2i(64, 32), "outer".to_owned()));
+        scene.push_path(DrawPath::new(rect.clone(), paint));
+        let inner = scene.push_render_target(RenderTarget::new(vec2i(8, 8), String::new()));
+        clipped_path.set_clip_path(Some(clip_a));
+        scene.push_path(clipped_path.clone());
+        scene.pop_render_target();
+        scene.pop_render_target();
+
+        clipped_path.set_clip_path(Some(clip_c));
+        scene.push_path(clipped_path.clone());
+        scene.set_path_visible(3, false);
+        clipped_path.set_clip_path(Some(clip_b));
+        scene.push_path(clipped_path);
+
+        let hierarchy = scene.render_target_hierarchy();
+        assert_eq!(hierarchy.len(), 2);
+        assert_eq!((hierarchy[0].id, hierarchy[0].name.as_str()), (outer, "outer"));
+        assert_eq!((hierarchy[0].depth, hierarchy[0].path_count), (0, 1));
+        assert_eq!(hierarchy[0].clipped_path_count, 0);
+        assert_eq!(hierarchy[0].memory_bytes(), 64 * 32 * 4);
+        assert_eq!((hierarchy[1].id, hierarchy[1].depth), (inner, 1));
+        assert_eq!((hierarchy[1].path_count, hierarchy[1].clipped_path_count), (1, 1));
+
+        // Clips inside render targets and on hidden paths aren't active.
+        assert_eq!(scene.clip_path_count(), 3);
+        assert_eq!(scene.active_clip_paths(), vec![clip_b]);
+    }
+
+    #[test]
+    fn test_partition_and_retain() {
+        let mut scene = Scene::new();
+        let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let blue = scene.push_paint(&Paint::from_color(ColorU::new(0, 0, 255, 255)));
+        let rect = |x| Outline::from_rect(RectF::new(vec2f(x, 0.0), vec2f(16.0, 16.0)));
+        scene.push_path(DrawPath::new(rect(0.0), red));
+        let render_target =
+            scene.push_render_target(RenderTarget::new(vec2i(16, 16), String::new()));
+        scene.push_path(DrawPath::new(rect(0.0), blue));
+        scene.pop_render_target();
+        for &(x, paint) in &[(16.0, red), (32.0, blue), (48.0, red)] {
+            scene.push_path(DrawPath::new(rect(x), paint));
+        }
+
+        let ((red_scene, red_ids), (blue_scene, blue_ids)) = scene.partition(|_, _, paint| {
+            paint.base_color() == ColorU::new(255, 0, 0, 255)
+        });
+        assert_eq!(red_scene.path_count(), 3);
+        assert_eq!(red_scene.get_path(1).outline().bounds().origin_x(), 16.0);
+        assert_eq!((red_ids.get(PathId(2)), red_ids.get(PathId(3))), (Some(PathId(1)), None));
+        assert_eq!((blue_ids.get(PathId(3)), blue_ids.get(PathId(5))), (Some(PathId(1)), None));
+        assert_eq!(red_scene.id, scene.id);
+        assert_ne!(blue_scene.id, scene.id);
+
+        // The kept paths on either side of a removed one are drawn together, and render targets
+        // stay even when emptied.
+        assert_eq!(red_scene.display_list.len(), 4);
+        match red_scene.display_list[3] {
+            DisplayItem::DrawPaths { start_index: 1, end_index: 3 } => {}
+            ref display_item => panic!("Expected paths 1..3 to be drawn, not {:?}!", display_item),
+        }
+        assert_eq!(blue_scene.path_count(), 2);
+        assert_eq!(blue_scene.render_target_hierarchy()[0].path_count, 1);
+
+        // The scene with the new ID draws into its render target under that ID.
+        let blue_render_target = blue_scene.render_target_hierarchy()[0].id;
+        assert_eq!(blue_render_target.render_target, render_target.render_target);
+        assert_eq!(blue_render_target.scene, blue_scene.id.0);
+        assert_eq!(red_scene.render_target_hierarchy()[0].id, render_target);
+        let mut appended = Scene::new();
+        appended.append_scene(blue_scene);
+        assert_eq!(appended.path_count(), 2);
+
+        // Paths can be picked by ID or bounds as well.
+        let mut scene = scene;
+        let path_ids = scene.retain(|path_id, path, _| {
+            path_id.0 != 0 && path.outline().bounds().origin_x() < 40.0
+        });
+        assert_eq!(scene.path_count(), 3);
+        assert_eq!((path_ids.get(PathId(0)), path_ids.get(PathId(3))), (None, Some(PathId(2))));
+        assert_eq!(scene.get_path(2).paint(), blue);
+        assert_eq!(scene.render_target_hierarchy()[0].path_count, 1);
+    }
+
+    #[test]
+    fn test_update_paint_in_place() {
+        let red = ColorU::new(255, 0, 0, 255);
+        let blue = ColorU::new(0, 0, 255, 255);
+        let gradient = |length, from, to| {
+            let mut gradient = Gradient::linear_from_points(Vector2F::zero(), vec2f(length, 0.0));
+            gradient.add_color_stop(from, 0.0);
+            gradient.add_color_stop(to, 1.0);
+            gradient
+        };
+        let declared_layout = |scene: &mut Scene| -> PaintLayout {
+            match scene.build_paint_info(Transform2F::default()).render_commands[0] {
+                RenderCommand::DeclarePaints(ref layout) => (**layout).clone(),
+                ref command => panic!("Expected paints to be declared first, not {:?}!", command),
+            }
+        };
+
+        let mut scene = Scene::new();
+        let color_paint = scene.push_paint(&Paint::from_color(red));
+        let gradient_paint = scene.push_paint(&Paint::from_gradient(gradient(64.0, red, blue)));
+        let layout = declared_layout(&mut scene);
+        let color_slot = &layout.slots[color_paint.0 as usize];
+        let gradient_slot = &layout.slots[gradient_paint.0 as usize];
+        let update = |paint: Paint, slot| {
+            paint.update_in_slot(slot, layout.render_transform, |_| None)
+        };
+
+        // Colors can change, as can gradient stops and geometry.
+        let color_update = update(Paint::from_color(blue), color_slot).unwrap();
+        assert_eq!(color_update.metadata.base_color, blue);
+        assert!(color_update.texels.is_none());
+
+        let gradient_update =
+            update(Paint::from_gradient(gradient(32.0, blue, red)), gradient_slot).unwrap();
+        assert_eq!(gradient_update.metadata.color_0_transform.m11(), 1.0 / 32.0);
+        let texels = gradient_update.texels.unwrap();
+        assert_eq!(Some(texels.location),
+                   gradient_slot.color_texture.map(|color_texture| color_texture.location));
+        assert!(texels.texels[0].b > 250 && texels.texels[255].r > 250);
+
+        // Opaque paints can't become translucent, since tiles behind them may have been culled.
+        assert!(update(Paint::from_color(ColorU::new(255, 0, 0, 128)), color_slot).is_err());
+        // Nor can paints change what kind of texture or filter they need.
+        assert!(update(Paint::from_gradient(gradient(32.0, blue, red)), color_slot).is_err());
+        assert!(update(Paint::from_color(blue), gradient_slot).is_err());
+        let mut radial = Gradient::radial(Vector2F::zero(), F32x2::new(0.0, 32.0));
+        radial.add_color_stop(blue, 0.0);
+        assert!(update(Paint::from_gradient(radial), gradient_slot).is_err());
+
+        // Replacing a paint in the scene keeps its ID, but tells the renderer that the paints
+        // changed.
+        scene.set_paint(color_paint, Paint::from_color(blue));
+        assert_eq!(scene.push_paint(&Paint::from_color(blue)), color_paint);
+        assert_ne!(scene.push_paint(&Paint::from_color(red)), color_paint);
+        assert_ne!(declared_layout(&mut scene).palette_version, layout.palette_version);
+    }
 }
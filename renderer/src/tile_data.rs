@@ -0,0 +1,361 @@
+// pathfinder/renderer/src/tile_data.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Keeps the tiles and fills that the scene builder produces, so that they can be examined on the
+//! CPU.
+//!
+//! This is meant for analysis tools, custom backends, and tests that check how paths were tiled
+//! rather than what they look like. Pass a clone of a recorder in `BuildOptions` and take the
+//! data from the original once the build finishes:
+//!
+//! ```ignore
+//! let recorder = TileDataRecorder::new();
+//! let options = BuildOptions { tile_data_recorder: Some(recorder.clone()), ..default };
+//! scene.build(options, listener, &SequentialExecutor);
+//! let tile_data = recorder.take_tile_data().unwrap();
+//! ```
+
+pub use crate::builder::BuiltTile;
+use crate::builder::{BuiltPath, SolidTiles};
+use crate::gpu_data::FillBatchEntry;
+use crate::tile_map::TileMap;
+use crate::tiles::TileSize;
+use pathfinder_geometry::vector::Vector2I;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+/// Collects the tile data of scene builds. Clones share the same data.
+#[derive(Clone, Default)]
+pub struct TileDataRecorder {
+    state: Arc<Mutex<TileDataRecorderState>>,
+}
+
+#[derive(Default)]
+struct TileDataRecorderState {
+    building: SceneTileData,
+    built: Option<SceneTileData>,
+}
+
+/// The tiles and fills of every path in a scene, before occlusion culling and batching.
+#[derive(Clone, Debug, Default)]
+pub struct SceneTileData {
+    pub tile_size: TileSize,
+    /// The tiles of each clip path, indexed by clip path ID.
+    pub clip_paths: Vec<ClipPathTileData>,
+    /// The tiles of each draw path, indexed by path ID. There's one entry per paint, so paths
+    /// with contour paints have one for each range of contours.
+    pub draw_paths: Vec<Vec<PathTileData>>,
+}
+
+/// The tiles and fills of one paint of a draw path.
+#[derive(Clone, Debug, Default)]
+pub struct PathTileData {
+    /// Tiles that are partially covered and so need a mask.
+    pub alpha_tiles: Vec<BuiltTile>,
+    /// The coordinates of tiles that are entirely covered.
+    pub solid_tiles: Vec<Vector2I>,
+    /// The edges rasterized into the masks of the alpha tiles.
+    pub fills: Vec<FillBatchEntry>,
+}
+
+/// The tiles and fills of a clip path.
+///
+/// Clip paths aren't drawn themselves, so this only records which tiles have masks. Tiles
+/// without one are either entirely inside or entirely outside the clip path.
+#[derive(Clone, Debug, Default)]
+pub struct ClipPathTileData {
+    /// The coordinates of tiles that are partially covered, sorted by row and then by column.
+    pub mask_tiles: Vec<Vector2I>,
+    /// The edges rasterized into the masks of those tiles.
+    pub fills: Vec<FillBatchEntry>,
+}
+
+impl TileDataRecorder {
+    #[inline]
+    pub fn new() -> TileDataRecorder {
+        TileDataRecorder::default()
+    }
+
+    /// Returns the tile data of the last build that finished, if it hasn't been taken already.
+    ///
+    /// Cancelled builds don't produce any.
+    pub fn take_tile_data(&self) -> Option<SceneTileData> {
+        self.state.lock().unwrap().built.take()
+    }
+
+    pub(crate) fn begin(&self,
+                        tile_size: TileSize,
+                        clip_path_count: usize,
+                        draw_path_count: usize) {
+        self.state.lock().unwrap().building = SceneTileData {
+            tile_size,
+            clip_paths: vec![ClipPathTileData::default(); clip_path_count],
+            draw_paths: vec![vec![]; draw_path_count],
+        };
+    }
+
+    pub(crate) fn record_clip_path(&self, path_index: usize, tile_data: ClipPathTileData) {
+        self.state.lock().unwrap().building.clip_paths[path_index] = tile_data;
+    }
+
+    pub(crate) fn record_draw_path(&self, path_index: usize, tile_data: Vec<PathTileData>) {
+        self.state.lock().unwrap().building.draw_paths[path_index] = tile_data;
+    }
+
+    pub(crate) fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.built = Some(mem::take(&mut state.building));
+    }
+}
+
+impl SceneTileData {
+    /// The total number of alpha tiles in all draw paths.
+    pub fn alpha_tile_count(&self) -> usize {
+        self.draw_paths.iter().flatten().map(|path| path.alpha_tiles.len()).sum()
+    }
+
+    /// The total number of solid tiles in all draw paths.
+    pub fn solid_tile_count(&self) -> usize {
+        self.draw_paths.iter().flatten().map(|path| path.solid_tiles.len()).sum()
+    }
+
+    /// The total number of fills in all paths, including clip paths.
+    pub fn fill_count(&self) -> usize {
+        self.clip_paths.iter().map(|path| path.fills.len()).sum::<usize>() +
+            self.draw_paths.iter().flatten().map(|path| path.fills.len()).sum::<usize>()
+    }
+}
+
+impl PathTileData {
+    pub(crate) fn new(built_path: &BuiltPath, fills: Vec<FillBatchEntry>) -> PathTileData {
+        let solid_tiles = match built_path.solid_tiles {
+            SolidTiles::Occluders(ref occluders) => {
+                occluders.iter().map(|occluder| occluder.coords).collect()
+            }
+            SolidTiles::Regular(ref tiles) => {
+                tiles.iter().map(|tile| tile.tile.tile_position()).collect()
+            }
+        };
+        PathTileData { alpha_tiles: built_path.single_mask_tiles.clone(), solid_tiles, fills }
+    }
+}
+
+impl ClipPathTileData {
+    pub(crate) fn new(built_path: &BuiltPath, fills: Vec<FillBatchEntry>) -> ClipPathTileData {
+        let mask_tiles = match built_path.tiles {
+            TileMap::Dense(ref tiles) => {
+                let mask_tile_indices = tiles.data.iter().enumerate().filter(|(_, tile)| {
+                    !tile.is_solid()
+                });
+                mask_tile_indices.map(|(index, _)| tiles.index_to_coords(index)).collect()
+            }
+            TileMap::Sparse(ref tiles) => {
+                tiles.coords_in_row_major_order().into_iter().filter(|&coords| {
+                    !tiles.data[&coords].is_solid()
+                }).collect()
+            }
+        };
+        ClipPathTileData { mask_tiles, fills }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu_data::{ClipBatchKind, RenderCommand};
+    use crate::options::{BuildHandle, BuildOptions};
+    use crate::paint::Paint;
+    use crate::scene::{ClipPath, DrawPath, Scene};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_geometry::rect::{RectF, RectI};
+    use pathfinder_geometry::vector::{Vector2F, vec2f, vec2i};
+    use std::sync::{Arc, Mutex};
+    use super::TileDataRecorder;
+
+    fn build(scene: &mut Scene, options: BuildOptions) {
+        let listener = Box::new(|_: RenderCommand| {});
+        scene.build(options, listener, &SequentialExecutor);
+    }
+
+    #[test]
+    fn test_tile_data() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(256.0, 256.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+
+        // A rectangle covering tiles 1 to 4 entirely and a half tile around them.
+        let rect = Outline::from_rect(RectF::new(vec2f(8.0, 8.0), vec2f(80.0, 80.0)));
+        let clip_path = scene.push_clip_path(ClipPath::new(rect.clone()));
+        let mut draw_path = DrawPath::new(rect, paint);
+        draw_path.set_clip_path(Some(clip_path));
+        scene.push_path(draw_path);
+
+        let recorder = TileDataRecorder::new();
+        assert!(recorder.take_tile_data().is_none());
+        build(&mut scene, BuildOptions {
+            tile_data_recorder: Some(recorder.clone()),
+            ..BuildOptions::default()
+        });
+
+        let tile_data = recorder.take_tile_data().unwrap();
+        assert!(recorder.take_tile_data().is_none());
+        assert_eq!(tile_data.clip_paths.len(), 1);
+        assert_eq!(tile_data.draw_paths.len(), 1);
+        assert_eq!(tile_data.draw_paths[0].len(), 1);
+
+        let path_tile_data = &tile_data.draw_paths[0][0];
+        assert_eq!(path_tile_data.solid_tiles.len(), 16);
+        assert!(path_tile_data.solid_tiles.contains(&vec2i(1, 1)));
+        assert!(path_tile_data.solid_tiles.contains(&vec2i(4, 4)));
+        assert_eq!(path_tile_data.alpha_tiles.len(), 36 - 16);
+        assert!(path_tile_data.alpha_tiles.iter().all(|tile| {
+            let position = tile.tile.tile_position();
+            position.x() == 0 || position.y() == 0 || position.x() == 5 || position.y() == 5
+        }));
+        assert!(!path_tile_data.fills.is_empty());
+
+        let clip_tile_data = &tile_data.clip_paths[0];
+        assert_eq!(clip_tile_data.mask_tiles.len(), 36 - 16);
+        assert_eq!(clip_tile_data.mask_tiles[0], vec2i(0, 0));
+        assert!(!clip_tile_data.fills.is_empty());
+        assert_eq!(tile_data.fill_count(),
+                   path_tile_data.fills.len() + clip_tile_data.fills.len());
+    }
+
+    #[test]
+    fn test_winding_threshold() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(256.0, 256.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+
+        // Two tile-aligned squares that overlap in a 2×2 block of tiles.
+        let mut outline = Outline::from_rect(RectF::new(Vector2F::zero(), vec2f(64.0, 64.0)));
+        let square = Outline::from_rect(RectF::new(vec2f(32.0, 32.0), vec2f(64.0, 64.0)));
+        outline.push_contour(square.contours()[0].clone());
+        let mut draw_path = DrawPath::new(outline, paint);
+        draw_path.set_fill_rule(FillRule::WindingAtLeast(2));
+        scene.push_path(draw_path);
+
+        let recorder = TileDataRecorder::new();
+        build(&mut scene, BuildOptions {
+            tile_data_recorder: Some(recorder.clone()),
+            ..BuildOptions::default()
+        });
+
+        let tile_data = recorder.take_tile_data().unwrap();
+        let path_tile_data = &tile_data.draw_paths[0][0];
+        assert!(path_tile_data.solid_tiles.contains(&vec2i(3, 3)));
+        let overlap = RectI::new(vec2i(2, 2), vec2i(2, 2));
+        assert!(path_tile_data.solid_tiles.iter().all(|&coords| overlap.contains_point(coords)));
+    }
+
+    #[test]
+    fn test_inverted_clip() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(256.0, 256.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+
+        // Punch a hole in the middle of a square eight tiles across.
+        let hole = RectF::new(vec2f(40.0, 40.0), vec2f(48.0, 48.0));
+        let mut clip_path = ClipPath::new(Outline::from_rect(hole));
+        clip_path.set_inverted(true);
+        let clip_path = scene.push_clip_path(clip_path);
+        let rect = Outline::from_rect(RectF::new(Vector2F::zero(), vec2f(128.0, 128.0)));
+        let mut draw_path = DrawPath::new(rect, paint);
+        draw_path.set_clip_path(Some(clip_path));
+        scene.push_path(draw_path);
+
+        let recorder = TileDataRecorder::new();
+        let clip_batch_kinds = Arc::new(Mutex::new(vec![]));
+        let recorded_clip_batch_kinds = clip_batch_kinds.clone();
+        let listener = Box::new(move |command: RenderCommand| {
+            if let RenderCommand::ClipTiles(ref batches) = command {
+                let mut kinds = recorded_clip_batch_kinds.lock().unwrap();
+                kinds.extend(batches.iter().map(|batch| batch.key.kind));
+            }
+        });
+        scene.build(BuildOptions {
+            tile_data_recorder: Some(recorder.clone()),
+            ..BuildOptions::default()
+        }, listener, &SequentialExecutor);
+
+        // Tiles away from the hole are drawn as usual, tiles inside it are culled, and tiles
+        // along its edges have the clip mask cut out of them.
+        let tile_data = recorder.take_tile_data().unwrap();
+        let path_tile_data = &tile_data.draw_paths[0][0];
+        assert!(path_tile_data.solid_tiles.contains(&vec2i(1, 1)));
+        assert!(path_tile_data.solid_tiles.contains(&vec2i(6, 6)));
+        assert!(!path_tile_data.solid_tiles.contains(&vec2i(3, 3)));
+        assert!(path_tile_data.alpha_tiles.iter().all(|tile| {
+            tile.tile.tile_position() != vec2i(3, 3)
+        }));
+        assert!(path_tile_data.alpha_tiles.iter().any(|tile| {
+            tile.tile.tile_position() == vec2i(2, 2)
+        }));
+
+        let clip_batch_kinds = clip_batch_kinds.lock().unwrap();
+        assert!(clip_batch_kinds.contains(&ClipBatchKind::Draw));
+        assert!(clip_batch_kinds.contains(&ClipBatchKind::InvertedClip));
+        assert!(!clip_batch_kinds.contains(&ClipBatchKind::Clip));
+    }
+
+    #[test]
+    fn test_sparse_tile_data() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(8192.0, 8192.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+
+        // A square 257 tiles across whose edges are half a tile in, with a square hole, which is
+        // big and simple enough that its tiles are stored sparsely.
+        let mut outline = Outline::from_rect(RectF::new(vec2f(8.0, 8.0), vec2f(4096.0, 4096.0)));
+        let hole = Outline::from_rect(RectF::new(vec2f(1032.0, 1032.0), vec2f(2048.0, 2048.0)));
+        outline.push_contour(hole.contours()[0].clone());
+        let mut draw_path = DrawPath::new(outline, paint);
+        draw_path.set_fill_rule(FillRule::EvenOdd);
+        scene.push_path(draw_path);
+
+        let recorder = TileDataRecorder::new();
+        build(&mut scene, BuildOptions {
+            tile_data_recorder: Some(recorder.clone()),
+            ..BuildOptions::default()
+        });
+
+        let tile_data = recorder.take_tile_data().unwrap();
+        let path_tile_data = &tile_data.draw_paths[0][0];
+        let hole_tiles = 129 * 129;
+        assert_eq!(path_tile_data.alpha_tiles.len(), 256 * 4 + 128 * 4);
+        assert_eq!(path_tile_data.solid_tiles.len(), 255 * 255 - hole_tiles);
+        assert!(path_tile_data.solid_tiles.contains(&vec2i(1, 1)));
+        assert!(path_tile_data.solid_tiles.contains(&vec2i(255, 255)));
+        assert!(!path_tile_data.solid_tiles.contains(&vec2i(128, 128)));
+        assert!(!path_tile_data.solid_tiles.contains(&vec2i(256, 256)));
+    }
+
+    #[test]
+    fn test_cancelled_builds_record_nothing() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(256.0, 256.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let rect = Outline::from_rect(RectF::new(Vector2F::zero(), vec2f(64.0, 64.0)));
+        scene.push_path(DrawPath::new(rect, paint));
+
+        let recorder = TileDataRecorder::new();
+        let build_handle = BuildHandle::new();
+        build_handle.cancel();
+        build(&mut scene, BuildOptions {
+            build_handle: Some(build_handle),
+            tile_data_recorder: Some(recorder.clone()),
+            ..BuildOptions::default()
+        });
+        assert!(recorder.take_tile_data().is_none());
+    }
+}
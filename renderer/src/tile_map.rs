@@ -8,9 +8,26 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use fxhash::FxHashMap;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::{Vector2I, vec2i};
 
+/// Tile maps covering no more tiles than this are always dense, since they're cheap anyway.
+const MIN_SPARSE_TILE_MAP_AREA: usize = 64 * 64;
+
+/// Tile maps are stored sparsely if edges pass through fewer than one in this many of their tiles.
+/// Looking up a tile in a hash grid costs about as much as visiting this many tiles of a dense
+/// map, and solid interior tiles cost the same either way.
+const SPARSE_TILE_MAP_OCCUPANCY: usize = 16;
+
+/// A map from tile coordinates to tiles, stored densely or sparsely depending on how many of its
+/// tiles edges pass through.
+#[derive(Debug)]
+pub enum TileMap<T> {
+    Dense(DenseTileMap<T>),
+    Sparse(SparseTileMap<T>),
+}
+
 #[derive(Debug)]
 pub struct DenseTileMap<T> {
     pub data: Vec<T>,
@@ -68,3 +85,179 @@ impl<T> DenseTileMap<T> {
         self.rect.origin() + vec2i(index % width, index / width)
     }
 }
+
+/// A tile map that stores only the tiles that have been touched, in a hash grid, plus runs of
+/// identical tiles down columns.
+///
+/// Memory use is proportional to the number of tiles that edges pass through rather than to the
+/// area of the bounds, which suits paths that are large but simple, as on infinite canvases.
+#[derive(Debug)]
+pub struct SparseTileMap<T> {
+    pub data: FxHashMap<Vector2I, T>,
+    /// Runs of tiles that weren't touched, such as the solid interior of a path. These aren't in
+    /// `data`, so `get()` doesn't return them.
+    pub runs: Vec<TileRun<T>>,
+    pub rect: RectI,
+}
+
+/// A run of identical tiles down part of a column.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TileRun<T> {
+    pub column: i32,
+    pub start_row: i32,
+    pub end_row: i32,
+    pub tile: T,
+}
+
+impl<T> SparseTileMap<T> {
+    #[inline]
+    pub fn new(rect: RectI) -> SparseTileMap<T> {
+        SparseTileMap { data: FxHashMap::default(), runs: vec![], rect }
+    }
+
+    /// Returns the tile at `coords` if it has been touched.
+    #[inline]
+    pub fn get(&self, coords: Vector2I) -> Option<&T> {
+        self.data.get(&coords)
+    }
+
+    /// Returns the tile at `coords`, inserting a default one if it hasn't been touched.
+    ///
+    /// This doesn't check that `coords` is in bounds, so callers can keep tiles just outside
+    /// them.
+    #[inline]
+    pub fn get_or_insert(&mut self, coords: Vector2I) -> &mut T where T: Default {
+        self.data.entry(coords).or_default()
+    }
+
+    /// Returns the coordinates of all touched tiles, sorted by row and then by column.
+    pub fn coords_in_row_major_order(&self) -> Vec<Vector2I> {
+        let mut coords: Vec<Vector2I> = self.data.keys().cloned().collect();
+        coords.sort_by_key(|coords| (coords.y(), coords.x()));
+        coords
+    }
+
+    /// Returns the coordinates of all touched tiles, sorted by column and then by row.
+    pub fn coords_in_column_major_order(&self) -> Vec<Vector2I> {
+        let mut coords: Vec<Vector2I> = self.data.keys().cloned().collect();
+        coords.sort_by_key(|coords| (coords.x(), coords.y()));
+        coords
+    }
+}
+
+impl<T> TileMap<T> {
+    /// Creates a sparse map if edges are expected to pass through only a small fraction of the
+    /// tiles in `rect`, per `edge_tile_estimate`, and a dense one otherwise.
+    pub fn new(rect: RectI, edge_tile_estimate: usize) -> TileMap<T>
+               where T: Copy + Clone + Default {
+        let area = rect.size().x() as usize * rect.size().y() as usize;
+        if area > MIN_SPARSE_TILE_MAP_AREA &&
+                edge_tile_estimate.saturating_mul(SPARSE_TILE_MAP_OCCUPANCY) < area {
+            TileMap::Sparse(SparseTileMap::new(rect))
+        } else {
+            TileMap::Dense(DenseTileMap::new(rect))
+        }
+    }
+
+    #[inline]
+    pub fn rect(&self) -> RectI {
+        match *self {
+            TileMap::Dense(ref dense) => dense.rect,
+            TileMap::Sparse(ref sparse) => sparse.rect,
+        }
+    }
+
+    /// Returns the tile at `coords`. Dense maps have every tile in their bounds, but sparse maps
+    /// only have the tiles that have been touched.
+    #[inline]
+    pub fn get(&self, coords: Vector2I) -> Option<&T> {
+        match *self {
+            TileMap::Dense(ref dense) => dense.get(coords),
+            TileMap::Sparse(ref sparse) => sparse.get(coords),
+        }
+    }
+
+    /// Returns the tile at `coords`, or `None` if it's out of bounds. Sparse maps insert a default
+    /// tile if there isn't one.
+    #[inline]
+    pub fn get_mut(&mut self, coords: Vector2I) -> Option<&mut T> where T: Default {
+        match *self {
+            TileMap::Dense(ref mut dense) => {
+                let index = dense.coords_to_index(coords)?;
+                Some(&mut dense.data[index])
+            }
+            TileMap::Sparse(ref mut sparse) => {
+                if !sparse.rect.contains_point(coords) {
+                    return None;
+                }
+                Some(sparse.get_or_insert(coords))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pathfinder_geometry::rect::RectI;
+    use pathfinder_geometry::vector::vec2i;
+    use super::TileMap;
+
+    #[test]
+    fn test_dense_and_sparse_maps() {
+        let small_rect = RectI::new(vec2i(-2, -2), vec2i(4, 4));
+        let huge_rect = RectI::new(vec2i(-100000, -100000), vec2i(200000, 200000));
+        for &rect in &[small_rect, huge_rect] {
+            let mut map: TileMap<i32> = TileMap::new(rect, 800000);
+            assert_eq!(map.rect(), rect);
+            assert!(map.get_mut(rect.lower_right()).is_none());
+            *map.get_mut(vec2i(1, -1)).unwrap() += 3;
+            assert_eq!(map.get(vec2i(1, -1)), Some(&3));
+
+            match map {
+                TileMap::Dense(ref dense) => {
+                    assert_eq!(rect, small_rect);
+                    assert_eq!(map.get(vec2i(0, 0)), Some(&0));
+                    assert_eq!(dense.data.len(), 16);
+                }
+                TileMap::Sparse(ref sparse) => {
+                    assert_eq!(rect, huge_rect);
+                    assert_eq!(map.get(vec2i(0, 0)), None);
+                    assert_eq!(sparse.data.len(), 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sparse_ordering() {
+        let mut map: TileMap<i32> = TileMap::new(RectI::new(vec2i(0, 0), vec2i(1000, 1000)), 4000);
+        let sparse = match map {
+            TileMap::Sparse(ref mut sparse) => sparse,
+            TileMap::Dense(_) => unreachable!(),
+        };
+        for &coords in &[vec2i(5, 1), vec2i(2, 7), vec2i(3, 1)] {
+            *sparse.get_or_insert(coords) = 1;
+        }
+        assert_eq!(sparse.coords_in_row_major_order(), vec![vec2i(3, 1), vec2i(5, 1), vec2i(2, 7)]);
+        assert_eq!(sparse.coords_in_column_major_order(),
+                   vec![vec2i(2, 7), vec2i(3, 1), vec2i(5, 1)]);
+    }
+
+    #[test]
+    fn test_sparse_maps_depend_on_occupancy() {
+        let is_sparse = |rect: RectI, edge_tile_estimate: usize| {
+            match TileMap::<i32>::new(rect, edge_tile_estimate) {
+                TileMap::Sparse(_) => true,
+                TileMap::Dense(_) => false,
+            }
+        };
+
+        // A full-screen rectangle at 4K with 8×8 tiles only has edges around its border.
+        let screen_rect = RectI::new(vec2i(0, 0), vec2i(480, 270));
+        assert!(is_sparse(screen_rect, 2 * (480 + 270)));
+        assert!(!is_sparse(screen_rect, 480 * 270 / 2));
+
+        // Small maps are always dense.
+        assert!(!is_sparse(RectI::new(vec2i(0, 0), vec2i(16, 16)), 4));
+    }
+}
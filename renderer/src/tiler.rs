@@ -11,8 +11,10 @@
 //! Implements the fast lattice-clipping algorithm from Nehab and Hoppe, "Random-Access Rendering
 //! of General Vector Graphics" 2006.
 
-use crate::builder::{ObjectBuilder, Occluder, SceneBuilder, SolidTiles};
-use crate::tiles::{PackedTile, TILE_HEIGHT, TILE_WIDTH, TileType, TilingPathInfo};
+use crate::builder::{BuiltPath, ObjectBuilder, Occluder, SceneBuilder, SolidTiles};
+use crate::gpu_data::{AlphaTileId, TileObjectPrimitive};
+use crate::tile_map::{SparseTileMap, TileMap, TileRun};
+use crate::tiles::{PackedTile, TileSize, TileType, TilingPathInfo};
 use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::{ContourIterFlags, Outline};
 use pathfinder_content::segment::Segment;
@@ -20,14 +22,16 @@ use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2f, vec2i};
 use pathfinder_simd::default::{F32x2, U32x2};
-
-const FLATTENING_TOLERANCE: f32 = 0.25;
+use std::mem;
 
 pub(crate) struct Tiler<'a, 'b> {
     scene_builder: &'a SceneBuilder<'b, 'a>,
     pub(crate) object_builder: ObjectBuilder,
     outline: &'a Outline,
     path_info: TilingPathInfo<'a>,
+    // The coordinates of the tiles stored in a sparse tile map, sorted by column, once backdrops
+    // have been propagated.
+    sparse_tile_coords: Vec<Vector2I>,
 }
 
 impl<'a, 'b> Tiler<'a, 'b> {
@@ -38,8 +42,14 @@ impl<'a, 'b> Tiler<'a, 'b> {
                       path_info: TilingPathInfo<'a>)
                       -> Tiler<'a, 'b> {
         let bounds = outline.bounds().intersection(view_box).unwrap_or(RectF::default());
-        let object_builder = ObjectBuilder::new(bounds, view_box, fill_rule, &path_info);
-        Tiler { scene_builder, object_builder, outline, path_info }
+        let tile_size = scene_builder.built_options.tile_size;
+        let object_builder = ObjectBuilder::new(bounds,
+                                                view_box,
+                                                fill_rule,
+                                                tile_size,
+                                                estimate_edge_tile_count(outline, tile_size),
+                                                &path_info);
+        Tiler { scene_builder, object_builder, outline, path_info, sparse_tile_coords: vec![] }
     }
 
     pub(crate) fn generate_tiles(&mut self) {
@@ -54,13 +64,17 @@ impl<'a, 'b> Tiler<'a, 'b> {
     }
 
     fn propagate_backdrops(&mut self) {
-        let tiles_across = self.object_builder.built_path.tiles.rect.width() as usize;
-        for (draw_tile_index, draw_tile) in self.object_builder
-                                                .built_path
-                                                .tiles
-                                                .data
-                                                .iter_mut()
-                                                .enumerate() {
+        let fill_rule = self.object_builder.built_path.fill_rule;
+        let tiles = match self.object_builder.built_path.tiles {
+            TileMap::Dense(ref mut tiles) => tiles,
+            TileMap::Sparse(ref mut tiles) => {
+                self.sparse_tile_coords = propagate_sparse_backdrops(tiles, fill_rule);
+                return;
+            }
+        };
+
+        let tiles_across = tiles.rect.width() as usize;
+        for (draw_tile_index, draw_tile) in tiles.data.iter_mut().enumerate() {
             let column = draw_tile_index % tiles_across;
             let delta = draw_tile.backdrop;
             draw_tile.backdrop = self.object_builder.current_backdrops[column];
@@ -75,53 +89,143 @@ impl<'a, 'b> Tiler<'a, 'b> {
         };
 
         let blend_mode_is_destructive = draw_tiling_path_info.blend_mode.is_destructive();
-
-        for (draw_tile_index, draw_tile) in self.object_builder
-                                                .built_path
-                                                .tiles
-                                                .data
-                                                .iter()
-                                                .enumerate() {
-            let packed_tile = PackedTile::new(draw_tile_index as u32,
-                                              draw_tile,
-                                              &draw_tiling_path_info,
-                                              &self.object_builder);
+        let scene_builder = self.scene_builder;
+        let sparse_tile_coords = mem::take(&mut self.sparse_tile_coords);
+
+        let BuiltPath {
+            ref tiles,
+            ref mut solid_tiles,
+            ref mut single_mask_tiles,
+            ref mut empty_tiles,
+            ref mut clip_tiles,
+            fill_rule,
+            ..
+        } = self.object_builder.built_path;
+
+        let mut pack_tile = |tile_coords: Vector2I, draw_tile: &TileObjectPrimitive| {
+            let packed_tile =
+                PackedTile::new(tile_coords, draw_tile, &draw_tiling_path_info, fill_rule);
 
             match packed_tile.tile_type {
                 TileType::Solid => {
-                    match self.object_builder.built_path.solid_tiles {
+                    match *solid_tiles {
                         SolidTiles::Occluders(ref mut occluders) => {
                             occluders.push(Occluder::new(packed_tile.tile_coords));
                         }
                         SolidTiles::Regular(ref mut solid_tiles) => {
                             packed_tile.add_to(solid_tiles,
-                                               &mut self.object_builder.built_path.clip_tiles,
+                                               clip_tiles,
                                                &draw_tiling_path_info,
-                                               &self.scene_builder);
+                                               scene_builder);
                         }
                     }
                 }
                 TileType::SingleMask => {
-                    debug_assert_ne!(packed_tile.draw_tile.alpha_tile_id.page(), !0);
-                    packed_tile.add_to(&mut self.object_builder.built_path.single_mask_tiles,
-                                       &mut self.object_builder.built_path.clip_tiles,
+                    // Solid draw tiles only need a mask when an inverted clip is cut out of them.
+                    debug_assert!(packed_tile.draw_tile.alpha_tile_id.page() != !0 ||
+                                  packed_tile.clip_tile.is_some());
+                    packed_tile.add_to(single_mask_tiles,
+                                       clip_tiles,
                                        &draw_tiling_path_info,
-                                       &self.scene_builder);
+                                       scene_builder);
                 }
                 TileType::Empty if blend_mode_is_destructive => {
-                    packed_tile.add_to(&mut self.object_builder.built_path.empty_tiles,
-                                       &mut self.object_builder.built_path.clip_tiles,
+                    packed_tile.add_to(empty_tiles,
+                                       clip_tiles,
                                        &draw_tiling_path_info,
-                                       &self.scene_builder);
+                                       scene_builder);
                 }
                 TileType::Empty => {
                     // Just cull.
                 }
             }
+        };
+
+        match *tiles {
+            TileMap::Dense(ref tiles) => {
+                for (draw_tile_index, draw_tile) in tiles.data.iter().enumerate() {
+                    pack_tile(tiles.index_to_coords(draw_tile_index), draw_tile);
+                }
+            }
+            TileMap::Sparse(ref tiles) => {
+                for tile_coords in sparse_tile_coords {
+                    pack_tile(tile_coords, &tiles.data[&tile_coords]);
+                }
+                for run in &tiles.runs {
+                    for row in run.start_row..run.end_row {
+                        pack_tile(vec2i(run.column, row), &run.tile);
+                    }
+                }
+            }
         }
     }
 }
 
+// Only the tiles that edges pass through are stored in sparse tile maps, so this records the
+// solid tiles between them as runs as it propagates backdrops down each column. Returns the
+// coordinates of the stored tiles, sorted by column.
+fn propagate_sparse_backdrops(tiles: &mut SparseTileMap<TileObjectPrimitive>,
+                              fill_rule: FillRule)
+                              -> Vec<Vector2I> {
+    let rect = tiles.rect;
+    let mut runs = vec![];
+    let mut push_run = |column: i32, start_row: i32, end_row: i32, backdrop: i8| {
+        if start_row < end_row && fill_rule.is_filled(backdrop as i32) {
+            let tile = TileObjectPrimitive { alpha_tile_id: AlphaTileId::invalid(), backdrop };
+            runs.push(TileRun { column, start_row, end_row, tile });
+        }
+    };
+
+    let mut coords = tiles.coords_in_column_major_order();
+    let mut index = 0;
+    while index < coords.len() {
+        let column = coords[index].x();
+        let (mut backdrop, mut next_row) = (0, rect.min_y());
+        while index < coords.len() && coords[index].x() == column {
+            let tile_coords = coords[index];
+            index += 1;
+
+            // This tile is in the row above the map, which only holds backdrops.
+            if tile_coords.y() < rect.min_y() {
+                backdrop += tiles.data.remove(&tile_coords).unwrap().backdrop;
+                continue;
+            }
+
+            push_run(column, next_row, tile_coords.y(), backdrop);
+            let draw_tile = tiles.data.get_mut(&tile_coords).unwrap();
+            let delta = draw_tile.backdrop;
+            draw_tile.backdrop = backdrop;
+            backdrop += delta;
+            next_row = tile_coords.y() + 1;
+        }
+        push_run(column, next_row, rect.max_y(), backdrop);
+    }
+
+    tiles.runs = runs;
+    coords.retain(|tile_coords| tile_coords.y() >= rect.min_y());
+    coords
+}
+
+// Estimates how many tiles the edges of the outline pass through, from the lengths of its control
+// polygons, which are at least as long as the curves they control.
+fn estimate_edge_tile_count(outline: &Outline, tile_size: TileSize) -> usize {
+    let tile_size = tile_size.size().to_f32();
+    let mut tile_count = 0.0;
+    for contour in outline.contours() {
+        if contour.is_empty() {
+            continue;
+        }
+        let mut prev_point = contour.position_of(contour.len() - 1);
+        for point_index in 0..contour.len() {
+            let point = contour.position_of(point_index);
+            let tile_extent = (point - prev_point).abs() / tile_size;
+            tile_count += tile_extent.x() + tile_extent.y() + 1.0;
+            prev_point = point;
+        }
+    }
+    tile_count as usize
+}
+
 fn process_segment(segment: &Segment,
                    scene_builder: &SceneBuilder,
                    object_builder: &mut ObjectBuilder) {
@@ -131,8 +235,9 @@ fn process_segment(segment: &Segment,
         return process_segment(&cubic, scene_builder, object_builder);
     }
 
+    let tolerance = scene_builder.built_options.quality.flattening_tolerance;
     if segment.is_line() ||
-            (segment.is_cubic() && segment.as_cubic_segment().is_flat(FLATTENING_TOLERANCE)) {
+            (segment.is_cubic() && segment.as_cubic_segment().is_flat(tolerance)) {
         return process_line_segment(segment.baseline, scene_builder, object_builder);
     }
 
@@ -150,7 +255,8 @@ fn process_segment(segment: &Segment,
 fn process_line_segment(line_segment: LineSegment2F,
                         scene_builder: &SceneBuilder,
                         object_builder: &mut ObjectBuilder) {
-    let tile_size = vec2f(TILE_WIDTH as f32, TILE_HEIGHT as f32);
+    let tile_size = scene_builder.built_options.tile_size.size().to_f32();
+    let exact = scene_builder.built_options.exact_axis_aligned_aa;
     let tile_size_recip = Vector2F::splat(1.0) / tile_size;
 
     let tile_line_segment =
@@ -167,8 +273,8 @@ fn process_line_segment(line_segment: LineSegment2F,
 
     // Compute `first_tile_crossing = (from_tile_coords + vec2i(vector.x > 0 ? 1 : 0,
     // vector.y > 0 ? 1 : 0)) * tile_size`.
-    let first_tile_crossing = (from_tile_coords +
-        Vector2I((!vector_is_negative & U32x2::splat(1)).to_i32x2())).to_f32() * tile_size;
+    let crossing_offset = Vector2I((!vector_is_negative & U32x2::splat(1)).to_i32x2());
+    let first_tile_crossing = (from_tile_coords + crossing_offset).to_f32() * tile_size;
 
     let mut t_max = (first_tile_crossing - line_segment.from()) / vector;
     let t_delta = (tile_size / vector).abs();
@@ -212,9 +318,34 @@ fn process_line_segment(line_segment: LineSegment2F,
             Some(next_step_direction)
         };
 
-        let next_position = line_segment.sample(next_t);
+        let next_position = match next_step_direction {
+            // Interpolating to `t = 1.0` can miss the endpoint by an ulp, and interpolating along a
+            // horizontal or vertical edge can miss the tile boundary by one.
+            None if exact => line_segment.to(),
+            Some(StepDirection::X) if exact && vector.y() == 0.0 => {
+                let crossing = (tile_coords + crossing_offset).to_f32() * tile_size;
+                vec2f(crossing.x(), line_segment.from_y())
+            }
+            Some(StepDirection::Y) if exact && vector.x() == 0.0 => {
+                let crossing = (tile_coords + crossing_offset).to_f32() * tile_size;
+                vec2f(line_segment.from_x(), crossing.y())
+            }
+            _ => line_segment.sample(next_t),
+        };
         let clipped_line_segment = LineSegment2F::new(current_position, next_position);
-        object_builder.add_fill(scene_builder, clipped_line_segment, tile_coords);
+
+        // A horizontal edge that runs along the top of a tile from one side to the other covers
+        // the whole tile, so it can move its backdrop up a row and leave the tile solid.
+        let tile_rect = RectF::new(tile_coords.to_f32() * tile_size, tile_size);
+        let backdrop_tile_coords = if exact && vector.y() == 0.0 &&
+                current_position.y() == tile_rect.min_y() &&
+                clipped_line_segment.min_x() == tile_rect.min_x() &&
+                clipped_line_segment.max_x() == tile_rect.max_x() {
+            tile_coords - vec2i(0, 1)
+        } else {
+            object_builder.add_fill(scene_builder, clipped_line_segment, tile_coords);
+            tile_coords
+        };
 
         // Add extra fills if necessary.
         if step.y() < 0 && next_step_direction == Some(StepDirection::Y) {
@@ -232,10 +363,10 @@ fn process_line_segment(line_segment: LineSegment2F,
         // Adjust backdrop if necessary.
         if step.x() < 0 && last_step_direction == Some(StepDirection::X) {
             // Entered through right boundary.
-            object_builder.adjust_alpha_tile_backdrop(tile_coords, 1);
+            object_builder.adjust_alpha_tile_backdrop(backdrop_tile_coords, 1);
         } else if step.x() > 0 && next_step_direction == Some(StepDirection::X) {
             // Leaving through right boundary.
-            object_builder.adjust_alpha_tile_backdrop(tile_coords, -1);
+            object_builder.adjust_alpha_tile_backdrop(backdrop_tile_coords, -1);
         }
 
         // Take a step.
@@ -8,17 +8,65 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::builder::{BuiltPath, ObjectBuilder};
+use crate::builder::BuiltPath;
 use crate::gpu_data::{AlphaTileId, TileObjectPrimitive};
 use crate::paint::{PaintId, PaintMetadata};
 use pathfinder_content::effects::BlendMode;
 use pathfinder_content::fill::FillRule;
 use pathfinder_geometry::rect::{RectF, RectI};
-use pathfinder_geometry::vector::{Vector2I, vec2f};
+use pathfinder_geometry::vector::{Vector2I, vec2f, vec2i};
 
+/// The width of a tile at the default tile size.
 pub const TILE_WIDTH: u32 = 16;
+/// The height of a tile at the default tile size.
 pub const TILE_HEIGHT: u32 = 16;
 
+/// The dimensions of the tiles that paths are broken into.
+///
+/// Only the sizes listed here are supported by the shaders. Fills store their endpoints as 4-bit
+/// pixel coordinates within a tile, so tiles can be no larger than 16×16, and the fill shaders
+/// rasterize four scanlines at once, so tile heights must be a multiple of 4.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TileSize {
+    /// 8×8 tiles. These generate fewer fills per mostly-empty tile but more tiles overall.
+    Tile8x8,
+    /// 16×16 tiles. This is the default.
+    Tile16x16,
+}
+
+impl TileSize {
+    /// All tile sizes that the shaders support, in order of increasing size.
+    pub const ALL: [TileSize; 2] = [TileSize::Tile8x8, TileSize::Tile16x16];
+
+    #[inline]
+    pub fn width(self) -> u32 {
+        match self {
+            TileSize::Tile8x8 => 8,
+            TileSize::Tile16x16 => TILE_WIDTH,
+        }
+    }
+
+    #[inline]
+    pub fn height(self) -> u32 {
+        match self {
+            TileSize::Tile8x8 => 8,
+            TileSize::Tile16x16 => TILE_HEIGHT,
+        }
+    }
+
+    #[inline]
+    pub fn size(self) -> Vector2I {
+        vec2i(self.width() as i32, self.height() as i32)
+    }
+}
+
+impl Default for TileSize {
+    #[inline]
+    fn default() -> TileSize {
+        TileSize::Tile16x16
+    }
+}
+
 #[derive(Clone, Copy)]
 pub(crate) enum TilingPathInfo<'a> {
     Clip,
@@ -32,6 +80,8 @@ pub(crate) struct DrawTilingPathInfo<'a> {
     pub(crate) blend_mode: BlendMode,
     pub(crate) built_clip_path: Option<&'a BuiltPath>,
     pub(crate) fill_rule: FillRule,
+    /// The slot of the transform that moves the path on the GPU, or 0 if it isn't animated.
+    pub(crate) transform_slot: u8,
 }
 
 impl<'a> TilingPathInfo<'a> {
@@ -60,35 +110,19 @@ pub(crate) enum TileType {
 }
 
 impl<'a> PackedTile<'a> {
-    pub(crate) fn new(draw_tile_index: u32,
+    pub(crate) fn new(tile_coords: Vector2I,
                       draw_tile: &'a TileObjectPrimitive,
                       draw_tiling_path_info: &DrawTilingPathInfo<'a>,
-                      object_builder: &ObjectBuilder)
+                      fill_rule: FillRule)
                       -> PackedTile<'a> {
-        let tile_coords = object_builder.local_tile_index_to_coords(draw_tile_index as u32);
-
         // First, if the draw tile is empty, cull it regardless of clip.
-        if draw_tile.is_solid() {
-            match (object_builder.built_path.fill_rule, draw_tile.backdrop) {
-                (FillRule::Winding, 0) => {
-                    return PackedTile {
-                        tile_type: TileType::Empty,
-                        tile_coords,
-                        draw_tile,
-                        clip_tile: None,
-                    };
-                }
-                (FillRule::Winding, _) => {}
-                (FillRule::EvenOdd, backdrop) if backdrop % 2 == 0 => {
-                    return PackedTile {
-                        tile_type: TileType::Empty,
-                        tile_coords,
-                        draw_tile,
-                        clip_tile: None,
-                    };
-                }
-                (FillRule::EvenOdd, _) => {}
-            }
+        if draw_tile.is_solid() && !fill_rule.is_filled(draw_tile.backdrop as i32) {
+            return PackedTile {
+                tile_type: TileType::Empty,
+                tile_coords,
+                draw_tile,
+                clip_tile: None,
+            };
         }
 
         // Figure out what clip tile we need, if any.
@@ -96,22 +130,14 @@ impl<'a> PackedTile<'a> {
             None => None,
             Some(built_clip_path) => {
                 match built_clip_path.tiles.get(tile_coords) {
-                    None => {
-                        // This tile is outside of the bounds of the clip path entirely. We can
-                        // cull it.
-                        return PackedTile {
-                            tile_type: TileType::Empty,
-                            tile_coords,
-                            draw_tile,
-                            clip_tile: None,
-                        };
-                    }
-                    Some(clip_tile) if clip_tile.is_solid() => {
-                        if clip_tile.backdrop != 0 {
-                            // The clip tile is fully opaque, so this tile isn't clipped at
-                            // all.
-                            None
-                        } else {
+                    Some(clip_tile) if !clip_tile.is_solid() => Some(clip_tile),
+                    maybe_clip_tile => {
+                        // This tile is either entirely inside the clip path or entirely outside
+                        // it. Tiles outside of the bounds of the clip path are outside it.
+                        let inside = maybe_clip_tile.is_some_and(|clip_tile| {
+                            clip_tile.backdrop != 0
+                        });
+                        if inside == built_clip_path.clip_inverted {
                             // This tile is completely clipped out. Cull it.
                             return PackedTile {
                                 tile_type: TileType::Empty,
@@ -120,8 +146,9 @@ impl<'a> PackedTile<'a> {
                                 clip_tile: None,
                             };
                         }
+                        // Otherwise, this tile isn't clipped at all.
+                        None
                     }
-                    Some(clip_tile) => Some(clip_tile),
                 }
             }
         };
@@ -141,9 +168,11 @@ impl<'a> PackedTile<'a> {
                     clip_tile: None,
                 }
             }
-            Some(clip_tile) if draw_tile.is_solid() => {
+            Some(clip_tile) if draw_tile.is_solid() &&
+                    !draw_tiling_path_info.built_clip_path.unwrap().clip_inverted => {
                 // We have a solid draw tile and a clip tile. This is effectively the same as
-                // having a draw tile and no clip tile.
+                // having a draw tile and no clip tile. (Inverted clips can't reuse the clip mask
+                // like this, so they composite it like any other draw tile.)
                 //
                 // FIXME(pcwalton): This doesn't preserve the fill rule of the clip path!
                 PackedTile {
@@ -166,8 +195,9 @@ impl<'a> PackedTile<'a> {
     }
 }
 
-pub fn round_rect_out_to_tile_bounds(rect: RectF) -> RectI {
-    (rect * vec2f(1.0 / TILE_WIDTH as f32, 1.0 / TILE_HEIGHT as f32)).round_out().to_i32()
+pub fn round_rect_out_to_tile_bounds(rect: RectF, tile_size: TileSize) -> RectI {
+    let tile_size = tile_size.size().to_f32();
+    (rect * vec2f(1.0 / tile_size.x(), 1.0 / tile_size.y())).round_out().to_i32()
 }
 
 impl Default for TileObjectPrimitive {
@@ -14,7 +14,7 @@ use crate::builder::Occluder;
 use crate::gpu_data::{Tile, TileBatch};
 use crate::paint::{PaintId, PaintMetadata};
 use crate::tile_map::DenseTileMap;
-use crate::tiles;
+use crate::tiles::{self, TileSize};
 use pathfinder_content::effects::BlendMode;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::vector::Vector2I;
@@ -34,8 +34,8 @@ pub(crate) struct DepthMetadata {
     pub(crate) paint_id: PaintId,
 }
 impl ZBuffer {
-    pub(crate) fn new(view_box: RectF) -> ZBuffer {
-        let tile_rect = tiles::round_rect_out_to_tile_bounds(view_box);
+    pub(crate) fn new(view_box: RectF, tile_size: TileSize) -> ZBuffer {
+        let tile_rect = tiles::round_rect_out_to_tile_bounds(view_box, tile_size);
         ZBuffer {
             buffer: DenseTileMap::from_builder(|_| 0, tile_rect),
             depth_metadata: VecMap::new(),
@@ -115,8 +115,14 @@ impl Tile {
             mask_0_u: 0,
             mask_0_v: 0,
             ctrl: 0,
-            pad: 0,
+            transform_slot: 0,
             color: paint_id.0,
         }
     }
+
+    /// Returns true if this tile has no mask, i.e. it was created by `new_solid_from_paint_id()`.
+    #[inline]
+    pub(crate) fn is_solid(&self) -> bool {
+        self.ctrl == 0
+    }
 }
@@ -0,0 +1,187 @@
+// pathfinder/renderer/src/zoom.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Deciding when a zoomed scene must be rebuilt and when the last rendering can be stretched.
+//!
+//! Rebuilding a scene at every step of a pinch-zoom gesture keeps text crisp but may not keep up
+//! with the gesture. The alternative is to keep the last rendering around (in a render target, or
+//! simply the previous frame) and re-composite it with `composite_transform()`, for example via
+//! `Renderer::reproject_texture()`, which is cheap but blurry once the scale has drifted. A
+//! `ZoomCache` tracks the transform the scene was last built with and applies a `ZoomQuality`
+//! policy to pick between the two.
+
+use pathfinder_geometry::transform2d::Transform2F;
+
+/// How to trade quality for speed while the view transform changes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZoomQuality {
+    /// Rebuild whenever the transform changes. Use this for print preview and screenshots, where
+    /// every frame must be exact.
+    Exact,
+    /// Stretch the last rendering for as long as a gesture is in progress, and rebuild once it
+    /// ends.
+    Interactive,
+    /// Stretch the last rendering during a gesture until the scale has changed by more than
+    /// `max_scale_change` (a factor, so 1.25 allows zooming in by 25% or out by 20%), and rebuild
+    /// once the gesture ends.
+    Threshold { max_scale_change: f32 },
+}
+
+impl Default for ZoomQuality {
+    #[inline]
+    fn default() -> ZoomQuality {
+        ZoomQuality::Threshold { max_scale_change: 1.5 }
+    }
+}
+
+/// Tracks the transform a scene was last built with and decides when to build it again.
+#[derive(Clone, Debug, Default)]
+pub struct ZoomCache {
+    quality: ZoomQuality,
+    built_transform: Option<Transform2F>,
+    gesture_active: bool,
+}
+
+impl ZoomCache {
+    #[inline]
+    pub fn new(quality: ZoomQuality) -> ZoomCache {
+        ZoomCache { quality, built_transform: None, gesture_active: false }
+    }
+
+    #[inline]
+    pub fn quality(&self) -> ZoomQuality {
+        self.quality
+    }
+
+    /// Changes the policy. The next call to `needs_rebuild()` is answered under the new policy.
+    #[inline]
+    pub fn set_quality(&mut self, quality: ZoomQuality) {
+        self.quality = quality;
+    }
+
+    /// Call this when a pan or zoom gesture starts.
+    #[inline]
+    pub fn begin_gesture(&mut self) {
+        self.gesture_active = true;
+    }
+
+    /// Call this when a pan or zoom gesture ends. The next call to `needs_rebuild()` will ask for
+    /// an exact rendering if the last one was stretched.
+    #[inline]
+    pub fn end_gesture(&mut self) {
+        self.gesture_active = false;
+    }
+
+    #[inline]
+    pub fn gesture_active(&self) -> bool {
+        self.gesture_active
+    }
+
+    /// Forgets the last rendering, for example because the scene itself changed.
+    #[inline]
+    pub fn invalidate(&mut self) {
+        self.built_transform = None;
+    }
+
+    /// Call this after building the scene with `transform`.
+    #[inline]
+    pub fn did_build(&mut self, transform: &Transform2F) {
+        self.built_transform = Some(*transform);
+    }
+
+    /// The transform the scene was last built with, if the rendering is still usable.
+    #[inline]
+    pub fn built_transform(&self) -> Option<Transform2F> {
+        self.built_transform
+    }
+
+    /// Returns true if the scene should be built again to be displayed with `transform`, and
+    /// false if the last rendering may be re-composited with `composite_transform()` instead.
+    pub fn needs_rebuild(&self, transform: &Transform2F) -> bool {
+        let built_transform = match self.built_transform {
+            None => return true,
+            Some(built_transform) => built_transform,
+        };
+        if built_transform == *transform {
+            return false;
+        }
+
+        match self.quality {
+            ZoomQuality::Exact => true,
+            _ if !self.gesture_active => true,
+            ZoomQuality::Interactive => false,
+            ZoomQuality::Threshold { max_scale_change } => {
+                let built_scale = built_transform.scale_factor();
+                if built_scale == 0.0 {
+                    return true;
+                }
+                let ratio = transform.scale_factor() / built_scale;
+                ratio > max_scale_change || ratio * max_scale_change < 1.0
+            }
+        }
+    }
+
+    /// The transform that maps the last rendering onto where the scene would be drawn with
+    /// `transform`, or `None` if there is no usable rendering.
+    #[inline]
+    pub fn composite_transform(&self, transform: &Transform2F) -> Option<Transform2F> {
+        self.built_transform.map(|built_transform| *transform * built_transform.inverse())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ZoomCache, ZoomQuality};
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::vec2f;
+
+    #[test]
+    fn test_zoom_policies() {
+        let built = Transform2F::from_scale(2.0);
+        let slightly_zoomed = Transform2F::from_scale(2.2);
+        let very_zoomed = Transform2F::from_scale(4.0);
+
+        let mut cache = ZoomCache::new(ZoomQuality::Exact);
+        assert!(cache.needs_rebuild(&built));
+        cache.did_build(&built);
+        assert!(!cache.needs_rebuild(&built));
+        cache.begin_gesture();
+        assert!(cache.needs_rebuild(&slightly_zoomed));
+
+        cache.set_quality(ZoomQuality::Interactive);
+        assert!(!cache.needs_rebuild(&very_zoomed));
+
+        cache.set_quality(ZoomQuality::Threshold { max_scale_change: 1.5 });
+        assert!(!cache.needs_rebuild(&slightly_zoomed));
+        assert!(cache.needs_rebuild(&very_zoomed));
+        assert!(cache.needs_rebuild(&Transform2F::from_scale(1.0)));
+
+        cache.end_gesture();
+        assert!(cache.needs_rebuild(&slightly_zoomed));
+        cache.invalidate();
+        assert!(cache.needs_rebuild(&built));
+    }
+
+    #[test]
+    fn test_composite_transform() {
+        let built = Transform2F::from_scale(2.0).translate(vec2f(10.0, 20.0));
+        let current = Transform2F::from_scale(3.0).translate(vec2f(-5.0, 4.0));
+
+        let mut cache = ZoomCache::default();
+        assert!(cache.composite_transform(&current).is_none());
+        cache.did_build(&built);
+
+        let composite = cache.composite_transform(&current).unwrap();
+        let world_point = vec2f(7.0, -3.0);
+        let expected = current * world_point;
+        let actual = composite * (built * world_point);
+        assert!((expected - actual).length() < 0.001);
+    }
+}
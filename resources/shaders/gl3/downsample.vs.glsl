@@ -0,0 +1,40 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+
+
+
+
+
+uniform vec4 uSrcRect;
+
+uniform vec4 uSrcViewport;
+uniform vec2 uSrcTextureSize;
+
+in ivec2 aPosition;
+
+out vec2 vTexCoord;
+
+void main(){
+    vec2 position = uSrcViewport . xy + mix(uSrcRect . xy, uSrcRect . zw, vec2(aPosition));
+
+    position . y = uSrcViewport . y + uSrcViewport . w - position . y;
+
+    vTexCoord = position / uSrcTextureSize;
+    gl_Position = vec4(mix(vec2(- 1.0, 1.0), vec2(1.0, - 1.0), vec2(aPosition)), 0.0, 1.0);
+}
+
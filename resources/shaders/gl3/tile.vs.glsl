@@ -22,6 +22,8 @@ uniform mat4 uTransform;
 uniform vec2 uTileSize;
 uniform sampler2D uTextureMetadata;
 uniform ivec2 uTextureMetadataSize;
+uniform sampler2D uPathTransforms;
+uniform ivec2 uPathTransformsSize;
 
 in ivec2 aTileOffset;
 in ivec2 aTileOrigin;
@@ -54,6 +56,20 @@ void main(){
     vMaskTexCoord0 = vec3(maskTexCoord0, float(aMaskBackdrop . x));
     vBaseColor = baseColor;
     vTileCtrl = float(aTileCtrl);
+
+    // Tiles of animated paths are moved by their path's transform. The mask and color texture
+    // coordinates stay where they were, so that both move with the path.
+    int transformSlot = aMaskBackdrop.y & 0xff;
+    if (transformSlot != 0) {
+        vec2 pathTransformsScale = vec2(1.0) / vec2(uPathTransformsSize);
+        vec2 pathTransformCoord = vec2(float(transformSlot * 2), 0.0);
+        vec4 pathTransformMatrix =
+            texture(uPathTransforms, (pathTransformCoord + vec2(0.5, 0.5)) * pathTransformsScale);
+        vec4 pathTransformOffset =
+            texture(uPathTransforms, (pathTransformCoord + vec2(1.5, 0.5)) * pathTransformsScale);
+        position = mat2(pathTransformMatrix) * position + pathTransformOffset.xy;
+    }
+
     gl_Position = uTransform * vec4(position, 0.0, 1.0);
 }
 
@@ -50,8 +50,17 @@ vec4 computeCoverage(vec2 from, vec2 to, sampler2D areaLUT){
 }
 
 
+
+
+
+
+
+
+
+
 layout(local_size_x = 16, local_size_y = 4)in;
 
+
 uniform writeonly image2D uDest;
 uniform sampler2D uAreaLUT;
 uniform int uFirstTileIndex;
@@ -93,7 +102,8 @@ void main(){
         fillIndex = iNextFills[fillIndex];
     } while(fillIndex >= 0);
 
-    ivec2 tileOrigin = ivec2(tileIndex & 0xff,(tileIndex >> 8u)& 0xff)* ivec2(16, 4);
+    ivec2 tileOrigin = ivec2(tileIndex & 0xff,(tileIndex >> 8u)& 0xff)*
+        ivec2(16, 16 / 4);
     ivec2 destCoord = tileOrigin + ivec2(gl_LocalInvocationID . xy);
     imageStore(uDest, destCoord, coverages);
 }
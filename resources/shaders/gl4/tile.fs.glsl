@@ -76,6 +76,12 @@ precision highp float;
 
 
 
+
+
+
+
+
+
 
 
 
@@ -93,6 +99,7 @@ uniform vec4 uFilterParams0;
 uniform vec4 uFilterParams1;
 uniform vec4 uFilterParams2;
 uniform vec2 uFramebufferSize;
+uniform float uDitherAmount;
 uniform int uCtrl;
 
 in vec3 vMaskTexCoord0;
@@ -104,6 +111,9 @@ out vec4 oFragColor;
 
 
 
+
+
+
 vec4 sampleColor(sampler2D colorTexture, vec2 colorTexCoord){
     return texture(colorTexture, colorTexCoord);
 }
@@ -168,18 +178,12 @@ vec3 filterTextGammaCorrect(vec3 bgColor, vec3 fgColor, sampler2D gammaLUT){
 
 
 
-vec4 filterText(vec2 colorTexCoord,
-                sampler2D colorTexture,
-                sampler2D gammaLUT,
-                vec2 colorTextureSize,
-                vec4 filterParams0,
-                vec4 filterParams1,
-                vec4 filterParams2){
+vec3 filterTextAlpha(vec2 colorTexCoord,
+                     sampler2D colorTexture,
+                     vec2 colorTextureSize,
+                     vec4 filterParams0){
 
     vec4 kernel = filterParams0;
-    vec3 bgColor = filterParams1 . rgb;
-    vec3 fgColor = filterParams2 . rgb;
-    bool gammaCorrectionEnabled = filterParams2 . a != 0.0;
 
 
     vec3 alpha;
@@ -204,6 +208,23 @@ vec4 filterText(vec2 colorTexCoord,
 
         alpha = vec3(r, g, b);
     }
+    return alpha;
+}
+
+vec4 filterText(vec2 colorTexCoord,
+                sampler2D colorTexture,
+                sampler2D gammaLUT,
+                vec2 colorTextureSize,
+                vec4 filterParams0,
+                vec4 filterParams1,
+                vec4 filterParams2){
+
+    vec3 bgColor = filterParams1 . rgb;
+    vec3 fgColor = filterParams2 . rgb;
+    bool gammaCorrectionEnabled = filterParams2 . a != 0.0;
+
+
+    vec3 alpha = filterTextAlpha(colorTexCoord, colorTexture, colorTextureSize, filterParams0);
 
 
     if(gammaCorrectionEnabled)
@@ -378,6 +399,70 @@ vec4 filterBlur(vec2 colorTexCoord,
     return color / gaussSum;
 }
 
+
+
+
+
+
+vec2 filterBlurredRoundedRectErf(vec2 x){
+    vec2 s = sign(x), a = abs(x);
+    x = 1.0 +(0.278393 +(0.230389 + 0.078108 *(a * a))* a)* a;
+    x *= x;
+    return s - s /(x * x);
+}
+
+float filterBlurredRoundedRectGaussian(float x, float sigma){
+    return exp(-(x * x)/(2.0 * sigma * sigma))/(2.5066282746310002 * sigma);
+}
+
+float filterBlurredRoundedRectX(float x, float y, float sigma, float corner, vec2 halfSize){
+    float delta = min(halfSize . y - corner - abs(y), 0.0);
+    float curved = halfSize . x - corner + sqrt(max(0.0, corner * corner - delta * delta));
+    vec2 integral = 0.5 + 0.5 * filterBlurredRoundedRectErf((x + vec2(- curved, curved))*
+                                                            (sqrt(0.5)/ sigma));
+    return integral . y - integral . x;
+}
+
+
+
+
+
+
+vec4 filterBlurredRoundedRect(vec2 colorTexCoord,
+                              sampler2D colorTexture,
+                              vec4 filterParams0,
+                              vec4 filterParams1){
+    vec2 halfSize =(filterParams0 . zw - filterParams0 . xy)* 0.5;
+    vec2 point = colorTexCoord -(filterParams0 . xy + filterParams0 . zw)* 0.5;
+    float corner = clamp(filterParams1 . x, 0.0, min(halfSize . x, halfSize . y));
+    float sigma = max(filterParams1 . y, 0.001);
+    vec2 uvOrigin = filterParams1 . zw;
+
+
+    float low = point . y - halfSize . y, high = point . y + halfSize . y;
+    float start = clamp(- 3.0 * sigma, low, high), end = clamp(3.0 * sigma, low, high);
+    float step =(end - start)/ 4.0;
+    float y = start + step * 0.5;
+    float coverage = 0.0;
+    for(int i = 0;i < 4;i ++){
+        coverage += filterBlurredRoundedRectX(point . x, point . y - y, sigma, corner, halfSize)*
+            filterBlurredRoundedRectGaussian(y, sigma)* step;
+        y += step;
+    }
+
+    return texture(colorTexture, uvOrigin)* clamp(coverage, 0.0, 1.0);
+}
+
+vec4 filterYUV(vec2 colorTexCoord,
+               sampler2D colorTexture,
+               vec4 filterParams0,
+               vec4 filterParams1,
+               vec4 filterParams2){
+    vec4 yuv = vec4(sampleColor(colorTexture, colorTexCoord). rgb, 1.0);
+    vec3 rgb = vec3(dot(filterParams0, yuv), dot(filterParams1, yuv), dot(filterParams2, yuv));
+    return vec4(clamp(rgb, 0.0, 1.0), 1.0);
+}
+
 vec4 filterNone(vec2 colorTexCoord, sampler2D colorTexture){
     return sampleColor(colorTexture, colorTexCoord);
 }
@@ -401,6 +486,13 @@ vec4 filterColor(vec2 colorTexCoord,
                                     framebufferSize,
                                     filterParams0,
                                     filterParams1);
+    case 0x4 :
+        return filterBlurredRoundedRect(colorTexCoord,
+                                        colorTexture,
+                                        filterParams0,
+                                        filterParams1);
+    case 0x5 :
+        return filterYUV(colorTexCoord, colorTexture, filterParams0, filterParams1, filterParams2);
     case 0x3 :
         return filterBlur(colorTexCoord,
                           colorTexture,
@@ -546,11 +638,21 @@ vec4 composite(vec4 srcColor,
 
 
 
+
+
+vec3 dither(vec3 color, vec2 fragCoord, float amount){
+    float noise = fract(52.9829189 * fract(dot(fragCoord, vec2(0.06711056, 0.00583715))));
+    return clamp(color +(noise - 0.5)* amount, 0.0, 1.0);
+}
+
+
+
 float sampleMask(float maskAlpha,
                  sampler2D maskTexture,
                  vec2 maskTextureSize,
                  vec3 maskTexCoord,
-                 int maskCtrl){
+                 int maskCtrl,
+                 int maskThreshold){
     if(maskCtrl == 0)
         return maskAlpha;
 
@@ -558,7 +660,9 @@ float sampleMask(float maskAlpha,
     vec4 texel = texture(maskTexture,(vec2(maskTexCoordI / ivec2(1, 4))+ 0.5)/ maskTextureSize);
     float coverage = texel[maskTexCoordI . y % 4]+ maskTexCoord . z;
 
-    if((maskCtrl & 0x1)!= 0)
+    if(maskCtrl == 0x3)
+        coverage = max(abs(coverage)- float(maskThreshold - 1), 0.0);
+    else if((maskCtrl & 0x1)!= 0)
         coverage = abs(coverage);
     else
         coverage = 1.0 - abs(1.0 - mod(coverage, 2.0));
@@ -570,15 +674,50 @@ float sampleMask(float maskAlpha,
 void calculateColor(int tileCtrl, int ctrl){
 
     int maskCtrl0 =(tileCtrl >> 0)& 0x3;
+    int maskThreshold0 =(tileCtrl >> 8)&
+        0xff;
     float maskAlpha = 1.0;
-    maskAlpha = sampleMask(maskAlpha, uMaskTexture0, uMaskTextureSize0, vMaskTexCoord0, maskCtrl0);
+    maskAlpha = sampleMask(maskAlpha,
+                           uMaskTexture0,
+                           uMaskTextureSize0,
+                           vMaskTexCoord0,
+                           maskCtrl0,
+                           maskThreshold0);
+
+
+
+    int componentAlpha =(ctrl >> 13)&
+                                           0x3;
+    if(componentAlpha != 0){
+        vec3 alpha = filterTextAlpha(vColorTexCoord0,
+                                     uColorTexture0,
+                                     uColorTextureSize0,
+                                     uFilterParams0)* maskAlpha * uFilterParams1 . a;
+        float maxAlpha = max(alpha . r, max(alpha . g, alpha . b));
+        vec4 premultipliedColor = vec4(uFilterParams2 . rgb * alpha, maxAlpha);
+        switch(componentAlpha){
+        case 0x1 :
+            oFragColor = premultipliedColor;
+
+
+
+            break;
+        case 0x2 :
+            oFragColor = vec4(alpha, maxAlpha);
+            break;
+        default :
+            oFragColor = premultipliedColor;
+            break;
+        }
+        return;
+    }
 
 
     vec4 color = vBaseColor;
-    int color0Combine =(ctrl >> 6)&
+    int color0Combine =(ctrl >> 7)&
                                        0x3;
     if(color0Combine != 0){
-        int color0Filter =(ctrl >> 4)& 0x3;
+        int color0Filter =(ctrl >> 4)& 0x7;
         vec4 color0 = filterColor(vColorTexCoord0,
                                   uColorTexture0,
                                   uGammaLUT,
@@ -596,10 +735,13 @@ void calculateColor(int tileCtrl, int ctrl){
     color . a *= maskAlpha;
 
 
-    int compositeOp =(ctrl >> 8)& 0xf;
+    int compositeOp =(ctrl >> 9)& 0xf;
     color = composite(color, uDestTexture, uFramebufferSize, gl_FragCoord . xy, compositeOp);
 
 
+    color . rgb = dither(color . rgb, gl_FragCoord . xy, uDitherAmount);
+
+
     color . rgb *= color . a;
     oFragColor = color;
 }
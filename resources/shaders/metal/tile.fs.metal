@@ -29,7 +29,7 @@ inline Tx mod(Tx x, Ty y)
 }
 
 static inline __attribute__((always_inline))
-float sampleMask(thread const float& maskAlpha, thread const texture2d<float> maskTexture, thread const sampler maskTextureSmplr, thread const float2& maskTextureSize, thread const float3& maskTexCoord, thread const int& maskCtrl)
+float sampleMask(thread const float& maskAlpha, thread const texture2d<float> maskTexture, thread const sampler maskTextureSmplr, thread const float2& maskTextureSize, thread const float3& maskTexCoord, thread const int& maskCtrl, thread const int& maskThreshold)
 {
     if (maskCtrl == 0)
     {
@@ -38,7 +38,11 @@ float sampleMask(thread const float& maskAlpha, thread const texture2d<float> ma
     int2 maskTexCoordI = int2(floor(maskTexCoord.xy));
     float4 texel = maskTexture.sample(maskTextureSmplr, ((float2(maskTexCoordI / int2(1, 4)) + float2(0.5)) / maskTextureSize));
     float coverage = texel[maskTexCoordI.y % 4] + maskTexCoord.z;
-    if ((maskCtrl & 1) != 0)
+    if (maskCtrl == 3)
+    {
+        coverage = fast::max(abs(coverage) - float(maskThreshold - 1), 0.0);
+    }
+    else if ((maskCtrl & 1) != 0)
     {
         coverage = abs(coverage);
     }
@@ -187,12 +191,9 @@ float3 filterTextGammaCorrect(thread const float3& bgColor, thread const float3&
 }
 
 static inline __attribute__((always_inline))
-float4 filterText(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr, thread const float2& colorTextureSize, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2)
+float3 filterTextAlpha(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTextureSize, thread const float4& filterParams0)
 {
     float4 kernel0 = filterParams0;
-    float3 bgColor = filterParams1.xyz;
-    float3 fgColor = filterParams2.xyz;
-    bool gammaCorrectionEnabled = filterParams2.w != 0.0;
     float3 alpha;
     if (kernel0.w == 0.0)
     {
@@ -224,11 +225,24 @@ float4 filterText(thread const float2& colorTexCoord, thread const texture2d<flo
         float b = filterTextConvolve7Tap(param_12, param_13, param_14);
         alpha = float3(r, g, b);
     }
+    return alpha;
+}
+
+static inline __attribute__((always_inline))
+float4 filterText(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr, thread const float2& colorTextureSize, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2)
+{
+    float3 bgColor = filterParams1.xyz;
+    float3 fgColor = filterParams2.xyz;
+    bool gammaCorrectionEnabled = filterParams2.w != 0.0;
+    float2 param = colorTexCoord;
+    float2 param_1 = colorTextureSize;
+    float4 param_2 = filterParams0;
+    float3 alpha = filterTextAlpha(param, colorTexture, colorTextureSmplr, param_1, param_2);
     if (gammaCorrectionEnabled)
     {
-        float3 param_15 = bgColor;
-        float3 param_16 = alpha;
-        alpha = filterTextGammaCorrect(param_15, param_16, gammaLUT, gammaLUTSmplr);
+        float3 param_3 = bgColor;
+        float3 param_4 = alpha;
+        alpha = filterTextGammaCorrect(param_3, param_4, gammaLUT, gammaLUTSmplr);
     }
     return float4(mix(bgColor, fgColor, alpha), 1.0);
 }
@@ -239,6 +253,72 @@ float4 sampleColor(thread const texture2d<float> colorTexture, thread const samp
     return colorTexture.sample(colorTextureSmplr, colorTexCoord);
 }
 
+static inline __attribute__((always_inline))
+float2 filterBlurredRoundedRectErf(thread float2& x)
+{
+    float2 s = sign(x);
+    float2 a = abs(x);
+    x = float2(1.0) + ((float2(0.2783930003643035888671875) + ((float2(0.2303889989852905273437500) + ((a * a) * 0.07810799777507781982421875)) * a)) * a);
+    x *= x;
+    return s - (s / (x * x));
+}
+
+static inline __attribute__((always_inline))
+float filterBlurredRoundedRectGaussian(thread const float& x, thread const float& sigma)
+{
+    return exp((-(x * x)) / ((2.0 * sigma) * sigma)) / (2.5066282749176025390625 * sigma);
+}
+
+static inline __attribute__((always_inline))
+float filterBlurredRoundedRectX(thread const float& x, thread const float& y, thread const float& sigma, thread const float& corner, thread const float2& halfSize)
+{
+    float delta = fast::min((halfSize.y - corner) - abs(y), 0.0);
+    float curved = (halfSize.x - corner) + sqrt(fast::max(0.0, (corner * corner) - (delta * delta)));
+    float2 param = (float2(x) + float2(-curved, curved)) * (sqrt(0.5) / sigma);
+    float2 _1402 = filterBlurredRoundedRectErf(param);
+    float2 integral = float2(0.5) + (_1402 * 0.5);
+    return integral.y - integral.x;
+}
+
+static inline __attribute__((always_inline))
+float4 filterBlurredRoundedRect(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float4& filterParams0, thread const float4& filterParams1)
+{
+    float2 halfSize = (filterParams0.zw - filterParams0.xy) * 0.5;
+    float2 point = colorTexCoord - ((filterParams0.xy + filterParams0.zw) * 0.5);
+    float corner = fast::clamp(filterParams1.x, 0.0, fast::min(halfSize.x, halfSize.y));
+    float sigma = fast::max(filterParams1.y, 0.001000000047497451305389404296875);
+    float2 uvOrigin = filterParams1.zw;
+    float low = point.y - halfSize.y;
+    float high = point.y + halfSize.y;
+    float start = fast::clamp((-3.0) * sigma, low, high);
+    float end = fast::clamp(3.0 * sigma, low, high);
+    float step = (end - start) / 4.0;
+    float y = start + (step * 0.5);
+    float coverage = 0.0;
+    for (int i = 0; i < 4; i++)
+    {
+        float param = point.x;
+        float param_1 = point.y - y;
+        float param_2 = sigma;
+        float param_3 = corner;
+        float2 param_4 = halfSize;
+        float param_5 = y;
+        float param_6 = sigma;
+        coverage += ((filterBlurredRoundedRectX(param, param_1, param_2, param_3, param_4) * filterBlurredRoundedRectGaussian(param_5, param_6)) * step);
+        y += step;
+    }
+    return colorTexture.sample(colorTextureSmplr, uvOrigin) * fast::clamp(coverage, 0.0, 1.0);
+}
+
+static inline __attribute__((always_inline))
+float4 filterYUV(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2)
+{
+    float2 param = colorTexCoord;
+    float4 yuv = float4(sampleColor(colorTexture, colorTextureSmplr, param).xyz, 1.0);
+    float3 rgb = float3(dot(filterParams0, yuv), dot(filterParams1, yuv), dot(filterParams2, yuv));
+    return float4(fast::clamp(rgb, float3(0.0), float3(1.0)), 1.0);
+}
+
 static inline __attribute__((always_inline))
 float4 filterNone(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr)
 {
@@ -261,6 +341,21 @@ float4 filterColor(thread const float2& colorTexCoord, thread const texture2d<fl
             float4 param_5 = filterParams1;
             return filterRadialGradient(param, colorTexture, colorTextureSmplr, param_1, param_2, param_3, param_4, param_5);
         }
+        case 4:
+        {
+            float2 param_16 = colorTexCoord;
+            float4 param_17 = filterParams0;
+            float4 param_18 = filterParams1;
+            return filterBlurredRoundedRect(param_16, colorTexture, colorTextureSmplr, param_17, param_18);
+        }
+        case 5:
+        {
+            float2 param_19 = colorTexCoord;
+            float4 param_20 = filterParams0;
+            float4 param_21 = filterParams1;
+            float4 param_22 = filterParams2;
+            return filterYUV(param_19, colorTexture, colorTextureSmplr, param_20, param_21, param_22);
+        }
         case 3:
         {
             float2 param_6 = colorTexCoord;
@@ -555,52 +650,95 @@ float4 composite(thread const float4& srcColor, thread const texture2d<float> de
 }
 
 static inline __attribute__((always_inline))
-void calculateColor(thread const int& tileCtrl, thread const int& ctrl, thread texture2d<float> uMaskTexture0, thread const sampler uMaskTexture0Smplr, thread float2 uMaskTextureSize0, thread float3& vMaskTexCoord0, thread float4& vBaseColor, thread float2& vColorTexCoord0, thread texture2d<float> uColorTexture0, thread const sampler uColorTexture0Smplr, thread texture2d<float> uGammaLUT, thread const sampler uGammaLUTSmplr, thread float2 uColorTextureSize0, thread float4& gl_FragCoord, thread float2 uFramebufferSize, thread float4 uFilterParams0, thread float4 uFilterParams1, thread float4 uFilterParams2, thread texture2d<float> uDestTexture, thread const sampler uDestTextureSmplr, thread float4& oFragColor)
+float3 dither(thread const float3& color, thread const float2& fragCoord, thread const float& amount)
+{
+    float noise = fract(52.98291778564453125 * fract(dot(fragCoord, float2(0.067110560834407806396484375, 0.005837149918079376220703125))));
+    return fast::clamp(color + float3((noise - 0.5) * amount), float3(0.0), float3(1.0));
+}
+
+static inline __attribute__((always_inline))
+void calculateColor(thread const int& tileCtrl, thread const int& ctrl, thread texture2d<float> uMaskTexture0, thread const sampler uMaskTexture0Smplr, thread float2 uMaskTextureSize0, thread float3& vMaskTexCoord0, thread float4& vBaseColor, thread float2& vColorTexCoord0, thread texture2d<float> uColorTexture0, thread const sampler uColorTexture0Smplr, thread texture2d<float> uGammaLUT, thread const sampler uGammaLUTSmplr, thread float2 uColorTextureSize0, thread float4& gl_FragCoord, thread float2 uFramebufferSize, thread float4 uFilterParams0, thread float4 uFilterParams1, thread float4 uFilterParams2, thread texture2d<float> uDestTexture, thread const sampler uDestTextureSmplr, thread float uDitherAmount, thread float4& oFragColor)
 {
     int maskCtrl0 = (tileCtrl >> 0) & 3;
+    int maskThreshold0 = (tileCtrl >> 8) & 255;
     float maskAlpha = 1.0;
     float param = maskAlpha;
     float2 param_1 = uMaskTextureSize0;
     float3 param_2 = vMaskTexCoord0;
     int param_3 = maskCtrl0;
-    maskAlpha = sampleMask(param, uMaskTexture0, uMaskTexture0Smplr, param_1, param_2, param_3);
-    float4 color = vBaseColor;
-    int color0Combine = (ctrl >> 6) & 3;
-    if (color0Combine != 0)
+    int param_3_1 = maskThreshold0;
+    maskAlpha = sampleMask(param, uMaskTexture0, uMaskTexture0Smplr, param_1, param_2, param_3, param_3_1);
+    int componentAlpha = (ctrl >> 13) & 3;
+    if (componentAlpha != 0)
     {
-        int color0Filter = (ctrl >> 4) & 3;
         float2 param_4 = vColorTexCoord0;
         float2 param_5 = uColorTextureSize0;
-        float2 param_6 = gl_FragCoord.xy;
-        float2 param_7 = uFramebufferSize;
-        float4 param_8 = uFilterParams0;
-        float4 param_9 = uFilterParams1;
-        float4 param_10 = uFilterParams2;
-        int param_11 = color0Filter;
-        float4 color0 = filterColor(param_4, uColorTexture0, uColorTexture0Smplr, uGammaLUT, uGammaLUTSmplr, param_5, param_6, param_7, param_8, param_9, param_10, param_11);
-        float4 param_12 = color;
-        float4 param_13 = color0;
-        int param_14 = color0Combine;
-        color = combineColor0(param_12, param_13, param_14);
+        float4 param_6 = uFilterParams0;
+        float3 alpha = (filterTextAlpha(param_4, uColorTexture0, uColorTexture0Smplr, param_5, param_6) * maskAlpha) * uFilterParams1.w;
+        float maxAlpha = fast::max(alpha.x, fast::max(alpha.y, alpha.z));
+        float4 premultipliedColor = float4(uFilterParams2.xyz * alpha, maxAlpha);
+        switch (componentAlpha)
+        {
+            case 1:
+            {
+                oFragColor = premultipliedColor;
+                break;
+            }
+            case 2:
+            {
+                oFragColor = float4(alpha, maxAlpha);
+                break;
+            }
+            default:
+            {
+                oFragColor = premultipliedColor;
+                break;
+            }
+        }
+        return;
+    }
+    float4 color = vBaseColor;
+    int color0Combine = (ctrl >> 7) & 3;
+    if (color0Combine != 0)
+    {
+        int color0Filter = (ctrl >> 4) & 7;
+        float2 param_7 = vColorTexCoord0;
+        float2 param_8 = uColorTextureSize0;
+        float2 param_9 = gl_FragCoord.xy;
+        float2 param_10 = uFramebufferSize;
+        float4 param_11 = uFilterParams0;
+        float4 param_12 = uFilterParams1;
+        float4 param_13 = uFilterParams2;
+        int param_14 = color0Filter;
+        float4 color0 = filterColor(param_7, uColorTexture0, uColorTexture0Smplr, uGammaLUT, uGammaLUTSmplr, param_8, param_9, param_10, param_11, param_12, param_13, param_14);
+        float4 param_15 = color;
+        float4 param_16 = color0;
+        int param_17 = color0Combine;
+        color = combineColor0(param_15, param_16, param_17);
     }
     color.w *= maskAlpha;
-    int compositeOp = (ctrl >> 8) & 15;
-    float4 param_15 = color;
-    float2 param_16 = uFramebufferSize;
-    float2 param_17 = gl_FragCoord.xy;
-    int param_18 = compositeOp;
-    color = composite(param_15, uDestTexture, uDestTextureSmplr, param_16, param_17, param_18);
+    int compositeOp = (ctrl >> 9) & 15;
+    float4 param_18 = color;
+    float2 param_19 = uFramebufferSize;
+    float2 param_20 = gl_FragCoord.xy;
+    int param_21 = compositeOp;
+    color = composite(param_18, uDestTexture, uDestTextureSmplr, param_19, param_20, param_21);
+    float3 param_22 = color.xyz;
+    float2 param_23 = gl_FragCoord.xy;
+    float param_24 = uDitherAmount;
+    float3 _1361 = dither(param_22, param_23, param_24);
+    color = float4(_1361.x, _1361.y, _1361.z, color.w);
     float3 _1347 = color.xyz * color.w;
     color = float4(_1347.x, _1347.y, _1347.z, color.w);
     oFragColor = color;
 }
 
-fragment main0_out main0(main0_in in [[stage_in]], constant int& uCtrl [[buffer(6)]], constant float2& uMaskTextureSize0 [[buffer(0)]], constant float2& uColorTextureSize0 [[buffer(1)]], constant float2& uFramebufferSize [[buffer(2)]], constant float4& uFilterParams0 [[buffer(3)]], constant float4& uFilterParams1 [[buffer(4)]], constant float4& uFilterParams2 [[buffer(5)]], texture2d<float> uMaskTexture0 [[texture(0)]], texture2d<float> uColorTexture0 [[texture(1)]], texture2d<float> uGammaLUT [[texture(2)]], texture2d<float> uDestTexture [[texture(3)]], sampler uMaskTexture0Smplr [[sampler(0)]], sampler uColorTexture0Smplr [[sampler(1)]], sampler uGammaLUTSmplr [[sampler(2)]], sampler uDestTextureSmplr [[sampler(3)]], float4 gl_FragCoord [[position]])
+fragment main0_out main0(main0_in in [[stage_in]], constant int& uCtrl [[buffer(6)]], constant float2& uMaskTextureSize0 [[buffer(0)]], constant float2& uColorTextureSize0 [[buffer(1)]], constant float2& uFramebufferSize [[buffer(2)]], constant float4& uFilterParams0 [[buffer(3)]], constant float4& uFilterParams1 [[buffer(4)]], constant float4& uFilterParams2 [[buffer(5)]], constant float& uDitherAmount [[buffer(7)]], texture2d<float> uMaskTexture0 [[texture(0)]], texture2d<float> uColorTexture0 [[texture(1)]], texture2d<float> uGammaLUT [[texture(2)]], texture2d<float> uDestTexture [[texture(3)]], sampler uMaskTexture0Smplr [[sampler(0)]], sampler uColorTexture0Smplr [[sampler(1)]], sampler uGammaLUTSmplr [[sampler(2)]], sampler uDestTextureSmplr [[sampler(3)]], float4 gl_FragCoord [[position]])
 {
     main0_out out = {};
     int param = int(in.vTileCtrl);
     int param_1 = uCtrl;
-    calculateColor(param, param_1, uMaskTexture0, uMaskTexture0Smplr, uMaskTextureSize0, in.vMaskTexCoord0, in.vBaseColor, in.vColorTexCoord0, uColorTexture0, uColorTexture0Smplr, uGammaLUT, uGammaLUTSmplr, uColorTextureSize0, gl_FragCoord, uFramebufferSize, uFilterParams0, uFilterParams1, uFilterParams2, uDestTexture, uDestTextureSmplr, out.oFragColor);
+    calculateColor(param, param_1, uMaskTexture0, uMaskTexture0Smplr, uMaskTextureSize0, in.vMaskTexCoord0, in.vBaseColor, in.vColorTexCoord0, uColorTexture0, uColorTexture0Smplr, uGammaLUT, uGammaLUTSmplr, uColorTextureSize0, gl_FragCoord, uFramebufferSize, uFilterParams0, uFilterParams1, uFilterParams2, uDestTexture, uDestTextureSmplr, uDitherAmount, out.oFragColor);
     return out;
 }
 
@@ -23,7 +23,7 @@ struct main0_in
     int aTileCtrl [[attribute(5)]];
 };
 
-vertex main0_out main0(main0_in in [[stage_in]], constant int2& uTextureMetadataSize [[buffer(1)]], constant float2& uTileSize [[buffer(0)]], constant float4x4& uTransform [[buffer(2)]], texture2d<float> uTextureMetadata [[texture(0)]], sampler uTextureMetadataSmplr [[sampler(0)]])
+vertex main0_out main0(main0_in in [[stage_in]], constant int2& uTextureMetadataSize [[buffer(1)]], constant float2& uTileSize [[buffer(0)]], constant float4x4& uTransform [[buffer(2)]], constant int2& uPathTransformsSize [[buffer(3)]], texture2d<float> uTextureMetadata [[texture(0)]], texture2d<float> uPathTransforms [[texture(1)]], sampler uTextureMetadataSmplr [[sampler(0)]], sampler uPathTransformsSmplr [[sampler(1)]])
 {
     main0_out out = {};
     float2 tileOrigin = float2(in.aTileOrigin);
@@ -42,6 +42,15 @@ vertex main0_out main0(main0_in in [[stage_in]], constant int2& uTextureMetadata
     out.vMaskTexCoord0 = float3(maskTexCoord0, float(in.aMaskBackdrop.x));
     out.vBaseColor = baseColor;
     out.vTileCtrl = float(in.aTileCtrl);
+    int transformSlot = in.aMaskBackdrop.y & 255;
+    if (transformSlot != 0)
+    {
+        float2 pathTransformsScale = float2(1.0) / float2(uPathTransformsSize);
+        float2 pathTransformCoord = float2(float(transformSlot * 2), 0.0);
+        float4 pathTransformMatrix = uPathTransforms.sample(uPathTransformsSmplr, ((pathTransformCoord + float2(0.5)) * pathTransformsScale), level(0.0));
+        float4 pathTransformOffset = uPathTransforms.sample(uPathTransformsSmplr, ((pathTransformCoord + float2(1.5, 0.5)) * pathTransformsScale), level(0.0));
+        position = (float2x2(float2(pathTransformMatrix.xy), float2(pathTransformMatrix.zw)) * position) + pathTransformOffset.xy;
+    }
     out.gl_Position = uTransform * float4(position, 0.0, 1.0);
     return out;
 }
@@ -0,0 +1,201 @@
+// pathfinder/resources/src/bytes.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Immutable byte buffers that are shared rather than copied, whether their contents live on the
+//! heap, in the binary, or in a memory-mapped file.
+//!
+//! A `Bytes` owns whatever keeps its contents alive, so the slices it hands out can't outlive
+//! the mapping or allocation behind them. Cloning and slicing are cheap.
+
+use std::fmt::{self, Debug, Formatter};
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use memmap2::Mmap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+#[derive(Clone)]
+pub struct Bytes {
+    storage: Storage,
+    range: Range<usize>,
+}
+
+#[derive(Clone)]
+enum Storage {
+    Static(&'static [u8]),
+    Heap(Arc<Vec<u8>>),
+    #[cfg(not(target_arch = "wasm32"))]
+    Mapped(Arc<Mmap>),
+}
+
+impl Bytes {
+    /// Wraps data that lives for the whole program, such as `include_bytes!()` output, without
+    /// copying it.
+    #[inline]
+    pub fn from_static(data: &'static [u8]) -> Bytes {
+        Bytes { storage: Storage::Static(data), range: 0..data.len() }
+    }
+
+    /// Maps the file at `path` into memory. Pages are read from disk as they're touched, and
+    /// the OS can drop them again under memory pressure, so none of the file is copied onto the
+    /// heap.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while any `Bytes` sharing the mapping is
+    /// alive. Doing so can change data that Rust assumes is immutable, or crash the process.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub unsafe fn map_file<P>(path: P) -> io::Result<Bytes> where P: AsRef<Path> {
+        let file = File::open(path)?;
+        // Empty files can't be mapped on every platform, but there's nothing to map anyway.
+        if file.metadata()?.len() == 0 {
+            return Ok(Bytes::from_static(&[]));
+        }
+        let mapping = Mmap::map(&file)?;
+        let len = mapping.len();
+        Ok(Bytes { storage: Storage::Mapped(Arc::new(mapping)), range: 0..len })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.range.end - self.range.start
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.range.start == self.range.end
+    }
+
+    /// Returns true if these bytes are backed by a memory-mapped file.
+    #[inline]
+    pub fn is_mapped(&self) -> bool {
+        match self.storage {
+            #[cfg(not(target_arch = "wasm32"))]
+            Storage::Mapped(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns a view of `range` within these bytes that shares their storage.
+    ///
+    /// Panics if `range` is out of bounds.
+    pub fn slice(&self, range: Range<usize>) -> Bytes {
+        assert!(range.start <= range.end && range.end <= self.len(),
+                "Range {:?} is out of bounds for {} bytes!",
+                range,
+                self.len());
+        let start = self.range.start + range.start;
+        let end = self.range.start + range.end;
+        Bytes { storage: self.storage.clone(), range: start..end }
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        let data: &[u8] = match self.storage {
+            Storage::Static(data) => data,
+            Storage::Heap(ref data) => data,
+            #[cfg(not(target_arch = "wasm32"))]
+            Storage::Mapped(ref mapping) => mapping,
+        };
+        &data[self.range.clone()]
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    #[inline]
+    fn from(data: Vec<u8>) -> Bytes {
+        Bytes::from(Arc::new(data))
+    }
+}
+
+impl From<Arc<Vec<u8>>> for Bytes {
+    #[inline]
+    fn from(data: Arc<Vec<u8>>) -> Bytes {
+        let len = data.len();
+        Bytes { storage: Storage::Heap(data), range: 0..len }
+    }
+}
+
+impl From<&'static [u8]> for Bytes {
+    #[inline]
+    fn from(data: &'static [u8]) -> Bytes {
+        Bytes::from_static(data)
+    }
+}
+
+impl PartialEq for Bytes {
+    #[inline]
+    fn eq(&self, other: &Bytes) -> bool {
+        **self == **other
+    }
+}
+
+impl Debug for Bytes {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let kind = match self.storage {
+            Storage::Static(_) => "static",
+            Storage::Heap(_) => "heap",
+            #[cfg(not(target_arch = "wasm32"))]
+            Storage::Mapped(_) => "mapped",
+        };
+        write!(formatter, "Bytes({} bytes, {})", self.len(), kind)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Bytes;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_slices_share_storage() {
+        let bytes = Bytes::from(vec![1, 2, 3, 4, 5]);
+        let slice = bytes.slice(1..4);
+        assert_eq!(&*slice, &[2, 3, 4]);
+        assert_eq!(&*slice.slice(1..3), &[3, 4]);
+        assert!(slice.slice(3..3).is_empty());
+        drop(bytes);
+        assert_eq!(slice.len(), 3);
+        assert_eq!(Bytes::from_static(b"abc"), Bytes::from(b"abc".to_vec()));
+    }
+
+    #[test]
+    fn test_map_file() {
+        let path = env::temp_dir().join(format!("pathfinder-bytes-{}", std::process::id()));
+        fs::write(&path, b"mapped contents").unwrap();
+        let bytes = unsafe { Bytes::map_file(&path).unwrap() };
+        assert!(bytes.is_mapped());
+        assert_eq!(&*bytes.slice(7..15), b"contents");
+        drop(bytes);
+
+        fs::write(&path, b"").unwrap();
+        let empty = unsafe { Bytes::map_file(&path).unwrap() };
+        assert!(empty.is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -11,6 +11,7 @@
 //! Embeds needed resources statically in the binary.
 
 use crate::ResourceLoader;
+use crate::bytes::Bytes;
 use std::io::{Error as IOError, ErrorKind};
 
 include!(concat!(env!("OUT_DIR"), "/manifest.rs"));
@@ -26,8 +27,12 @@ impl EmbeddedResourceLoader {
 
 impl ResourceLoader for EmbeddedResourceLoader {
     fn slurp(&self, virtual_path: &str) -> Result<Vec<u8>, IOError> {
+        self.map(virtual_path).map(|data| data.to_vec())
+    }
+
+    fn map(&self, virtual_path: &str) -> Result<Bytes, IOError> {
         match RESOURCES.iter().filter(|&(path, _)| *path == virtual_path).next() {
-            Some((_, data)) => Ok(data.to_vec()),
+            Some((_, data)) => Ok(Bytes::from_static(data)),
             None => Err(IOError::from(ErrorKind::NotFound)),
         }
     }
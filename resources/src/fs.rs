@@ -11,6 +11,8 @@
 //! Reads resources from the filesystem.
 
 use crate::ResourceLoader;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::bytes::Bytes;
 use std::env;
 use std::fs::File;
 use std::io::{Error as IOError, Read};
@@ -48,15 +50,54 @@ impl FilesystemResourceLoader {
     }
 }
 
-impl ResourceLoader for FilesystemResourceLoader {
-    fn slurp(&self, virtual_path: &str) -> Result<Vec<u8>, IOError> {
+impl FilesystemResourceLoader {
+    fn path_for(&self, virtual_path: &str) -> PathBuf {
         let mut path = self.directory.clone();
         virtual_path
             .split('/')
             .for_each(|segment| path.push(segment));
+        path
+    }
+}
 
+impl ResourceLoader for FilesystemResourceLoader {
+    fn slurp(&self, virtual_path: &str) -> Result<Vec<u8>, IOError> {
         let mut data = vec![];
-        File::open(&path)?.read_to_end(&mut data)?;
+        File::open(self.path_for(virtual_path))?.read_to_end(&mut data)?;
         Ok(data)
     }
 }
+
+/// Like `FilesystemResourceLoader`, but memory-maps resources in `map()` instead of reading them
+/// onto the heap.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct MappingResourceLoader {
+    loader: FilesystemResourceLoader,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl MappingResourceLoader {
+    /// Makes `loader` memory-map the resources it hands out from `map()`.
+    ///
+    /// # Safety
+    ///
+    /// No file under `loader.directory` may be modified or truncated while any `Bytes` mapped from
+    /// it is alive. See `Bytes::map_file()`.
+    #[inline]
+    pub unsafe fn new(loader: FilesystemResourceLoader) -> MappingResourceLoader {
+        MappingResourceLoader { loader }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ResourceLoader for MappingResourceLoader {
+    #[inline]
+    fn slurp(&self, virtual_path: &str) -> Result<Vec<u8>, IOError> {
+        self.loader.slurp(virtual_path)
+    }
+
+    fn map(&self, virtual_path: &str) -> Result<Bytes, IOError> {
+        // Safe because the caller of `new()` promised not to modify the resource directory.
+        unsafe { Bytes::map_file(self.loader.path_for(virtual_path)) }
+    }
+}
@@ -17,8 +17,10 @@
 //! 
 //! 2. There may not be a traditional filesystem available, as for example is the case on Android.
 
+use crate::bytes::Bytes;
 use std::io::Error as IOError;
 
+pub mod bytes;
 pub mod embedded;
 pub mod fs;
 
@@ -26,4 +28,13 @@ pub trait ResourceLoader {
     /// This is deliberately not a `Path`, because these are virtual paths
     /// that do not necessarily correspond to real paths on a filesystem.
     fn slurp(&self, path: &str) -> Result<Vec<u8>, IOError>;
+
+    /// Like `slurp()`, but avoids copying the resource onto the heap where the loader can, by
+    /// memory-mapping it or borrowing data embedded in the binary. Prefer this for large
+    /// resources, such as fonts and archived scenes.
+    ///
+    /// The default implementation calls `slurp()`.
+    fn map(&self, path: &str) -> Result<Bytes, IOError> {
+        self.slurp(path).map(Bytes::from)
+    }
 }
@@ -0,0 +1,38 @@
+#version 330
+
+// pathfinder/shaders/downsample.vs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Copies a rectangle of one framebuffer to the whole viewport of another, scaling it.
+
+precision highp float;
+
+#ifdef GL_ES
+precision highp sampler2D;
+#endif
+
+// The source rectangle, in pixels from the top left of the source viewport.
+uniform vec4 uSrcRect;
+// The source viewport, as its minimum and maximum corners in pixels within the source texture.
+uniform vec4 uSrcViewport;
+uniform vec2 uSrcTextureSize;
+
+in ivec2 aPosition;
+
+out vec2 vTexCoord;
+
+void main() {
+    vec2 position = uSrcViewport.xy + mix(uSrcRect.xy, uSrcRect.zw, vec2(aPosition));
+#ifndef PF_ORIGIN_UPPER_LEFT
+    position.y = uSrcViewport.y + uSrcViewport.w - position.y;
+#endif
+    vTexCoord = position / uSrcTextureSize;
+    gl_Position = vec4(mix(vec2(-1.0, 1.0), vec2(1.0, -1.0), vec2(aPosition)), 0.0, 1.0);
+}
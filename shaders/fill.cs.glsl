@@ -20,7 +20,16 @@ precision highp sampler2D;
 
 #include "fill.inc.glsl"
 
+// Each supported tile size gets its own build of this shader; see the Makefile.
+#ifdef PF_TILE_8X8
+#define TILE_WIDTH  8
+#define TILE_HEIGHT 8
+layout(local_size_x = 8, local_size_y = 2) in;
+#else
+#define TILE_WIDTH  16
+#define TILE_HEIGHT 16
 layout(local_size_x = 16, local_size_y = 4) in;
+#endif
 
 uniform writeonly image2D uDest;
 uniform sampler2D uAreaLUT;
@@ -63,7 +72,8 @@ void main() {
         fillIndex = iNextFills[fillIndex];
     } while (fillIndex >= 0);
 
-    ivec2 tileOrigin = ivec2(tileIndex & 0xff, (tileIndex >> 8u) & 0xff) * ivec2(16, 4);
+    ivec2 tileOrigin = ivec2(tileIndex & 0xff, (tileIndex >> 8u) & 0xff) *
+        ivec2(TILE_WIDTH, TILE_HEIGHT / 4);
     ivec2 destCoord = tileOrigin + ivec2(gl_LocalInvocationID.xy);
     imageStore(uDest, destCoord, coverages);
 }
@@ -47,17 +47,22 @@ precision highp sampler2D;
 #define TILE_CTRL_MASK_MASK                     0x3
 #define TILE_CTRL_MASK_WINDING                  0x1
 #define TILE_CTRL_MASK_EVEN_ODD                 0x2
+#define TILE_CTRL_MASK_WINDING_THRESHOLD        0x3
 
 #define TILE_CTRL_MASK_0_SHIFT                  0
+#define TILE_CTRL_MASK_THRESHOLD_0_SHIFT        8
+#define TILE_CTRL_MASK_THRESHOLD_MASK           0xff
 
 #define COMBINER_CTRL_COLOR_COMBINE_MASK        0x3
 #define COMBINER_CTRL_COLOR_COMBINE_SRC_IN      0x1
 #define COMBINER_CTRL_COLOR_COMBINE_DEST_IN     0x2
 
-#define COMBINER_CTRL_FILTER_MASK               0x3
+#define COMBINER_CTRL_FILTER_MASK               0x7
 #define COMBINER_CTRL_FILTER_RADIAL_GRADIENT    0x1
 #define COMBINER_CTRL_FILTER_TEXT               0x2
 #define COMBINER_CTRL_FILTER_BLUR               0x3
+#define COMBINER_CTRL_FILTER_BLURRED_ROUNDED_RECT 0x4
+#define COMBINER_CTRL_FILTER_YUV                0x5
 
 #define COMBINER_CTRL_COMPOSITE_MASK            0xf
 #define COMBINER_CTRL_COMPOSITE_NORMAL          0x0
@@ -77,9 +82,15 @@ precision highp sampler2D;
 #define COMBINER_CTRL_COMPOSITE_COLOR           0xe
 #define COMBINER_CTRL_COMPOSITE_LUMINOSITY      0xf
 
+#define COMBINER_CTRL_COMPONENT_ALPHA_MASK        0x3
+#define COMBINER_CTRL_COMPONENT_ALPHA_DUAL_SOURCE 0x1
+#define COMBINER_CTRL_COMPONENT_ALPHA_MASK_PASS   0x2
+#define COMBINER_CTRL_COMPONENT_ALPHA_COLOR_PASS  0x3
+
 #define COMBINER_CTRL_COLOR_FILTER_SHIFT        4
-#define COMBINER_CTRL_COLOR_COMBINE_SHIFT       6
-#define COMBINER_CTRL_COMPOSITE_SHIFT           8
+#define COMBINER_CTRL_COLOR_COMBINE_SHIFT       7
+#define COMBINER_CTRL_COMPOSITE_SHIFT           9
+#define COMBINER_CTRL_COMPONENT_ALPHA_SHIFT     13
 
 uniform sampler2D uColorTexture0;
 uniform sampler2D uMaskTexture0;
@@ -91,6 +102,7 @@ uniform vec4 uFilterParams0;
 uniform vec4 uFilterParams1;
 uniform vec4 uFilterParams2;
 uniform vec2 uFramebufferSize;
+uniform float uDitherAmount;
 uniform int uCtrl;
 
 in vec3 vMaskTexCoord0;
@@ -99,6 +111,9 @@ in vec4 vBaseColor;
 in float vTileCtrl;
 
 out vec4 oFragColor;
+#ifdef PF_DUAL_SOURCE_BLENDING
+out vec4 oFragBlend;
+#endif
 
 // Color sampling
 
@@ -164,20 +179,14 @@ vec3 filterTextGammaCorrect(vec3 bgColor, vec3 fgColor, sampler2D gammaLUT) {
 //                | x          y          z          w
 //  --------------+--------------------------------------------------------
 //  filterParams0 | kernel[0]  kernel[1]  kernel[2]  kernel[3]
-//  filterParams1 | bgColor.r  bgColor.g  bgColor.b  -
+//  filterParams1 | bgColor.r  bgColor.g  bgColor.b  fgColor.a
 //  filterParams2 | fgColor.r  fgColor.g  fgColor.b  gammaCorrectionEnabled
-vec4 filterText(vec2 colorTexCoord,
-                sampler2D colorTexture,
-                sampler2D gammaLUT,
-                vec2 colorTextureSize,
-                vec4 filterParams0,
-                vec4 filterParams1,
-                vec4 filterParams2) {
+vec3 filterTextAlpha(vec2 colorTexCoord,
+                     sampler2D colorTexture,
+                     vec2 colorTextureSize,
+                     vec4 filterParams0) {
     // Unpack.
     vec4 kernel = filterParams0;
-    vec3 bgColor = filterParams1.rgb;
-    vec3 fgColor = filterParams2.rgb;
-    bool gammaCorrectionEnabled = filterParams2.a != 0.0;
 
     // Apply defringing if necessary.
     vec3 alpha;
@@ -202,6 +211,23 @@ vec4 filterText(vec2 colorTexCoord,
 
         alpha = vec3(r, g, b);
     }
+    return alpha;
+}
+
+vec4 filterText(vec2 colorTexCoord,
+                sampler2D colorTexture,
+                sampler2D gammaLUT,
+                vec2 colorTextureSize,
+                vec4 filterParams0,
+                vec4 filterParams1,
+                vec4 filterParams2) {
+    // Unpack.
+    vec3 bgColor = filterParams1.rgb;
+    vec3 fgColor = filterParams2.rgb;
+    bool gammaCorrectionEnabled = filterParams2.a != 0.0;
+
+    // Apply defringing if necessary.
+    vec3 alpha = filterTextAlpha(colorTexCoord, colorTexture, colorTextureSize, filterParams0);
 
     // Apply gamma correction if necessary.
     if (gammaCorrectionEnabled)
@@ -376,6 +402,77 @@ vec4 filterBlur(vec2 colorTexCoord,
     return color / gaussSum;
 }
 
+// Evan Wallace, "Fast Rounded Rectangle Shadows", 2015. The blur is exact horizontally, using
+// the error function, and sampled vertically. This must match `BlurredRoundedRect::coverage()`.
+
+// Abramowitz and Stegun 7.1.27.
+vec2 filterBlurredRoundedRectErf(vec2 x) {
+    vec2 s = sign(x), a = abs(x);
+    x = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    x *= x;
+    return s - s / (x * x);
+}
+
+float filterBlurredRoundedRectGaussian(float x, float sigma) {
+    return exp(-(x * x) / (2.0 * sigma * sigma)) / (2.5066282746310002 * sigma);
+}
+
+float filterBlurredRoundedRectX(float x, float y, float sigma, float corner, vec2 halfSize) {
+    float delta = min(halfSize.y - corner - abs(y), 0.0);
+    float curved = halfSize.x - corner + sqrt(max(0.0, corner * corner - delta * delta));
+    vec2 integral = 0.5 + 0.5 * filterBlurredRoundedRectErf((x + vec2(-curved, curved)) *
+                                                            (sqrt(0.5) / sigma));
+    return integral.y - integral.x;
+}
+
+//                | x             y             z             w
+//  --------------+----------------------------------------------------
+//  filterParams0 | rect.min.x    rect.min.y    rect.max.x    rect.max.y
+//  filterParams1 | cornerRadius  sigma         uvOrigin.x    uvOrigin.y
+//  filterParams2 | -             -             -             -
+vec4 filterBlurredRoundedRect(vec2 colorTexCoord,
+                              sampler2D colorTexture,
+                              vec4 filterParams0,
+                              vec4 filterParams1) {
+    vec2 halfSize = (filterParams0.zw - filterParams0.xy) * 0.5;
+    vec2 point = colorTexCoord - (filterParams0.xy + filterParams0.zw) * 0.5;
+    float corner = clamp(filterParams1.x, 0.0, min(halfSize.x, halfSize.y));
+    float sigma = max(filterParams1.y, 0.001);
+    vec2 uvOrigin = filterParams1.zw;
+
+    // Integrate vertically over the part of the kernel that overlaps the rectangle.
+    float low = point.y - halfSize.y, high = point.y + halfSize.y;
+    float start = clamp(-3.0 * sigma, low, high), end = clamp(3.0 * sigma, low, high);
+    float step = (end - start) / 4.0;
+    float y = start + step * 0.5;
+    float coverage = 0.0;
+    for (int i = 0; i < 4; i++) {
+        coverage += filterBlurredRoundedRectX(point.x, point.y - y, sigma, corner, halfSize) *
+            filterBlurredRoundedRectGaussian(y, sigma) * step;
+        y += step;
+    }
+
+    return texture(colorTexture, uvOrigin) * clamp(coverage, 0.0, 1.0);
+}
+
+// Converts texels holding YUV in their color channels to RGB with an affine color matrix. The
+// matrix encodes both the YUV standard and the range of the values.
+//
+//                | x             y             z             w
+//  --------------+----------------------------------------------------
+//  filterParams0 | r from y      r from u      r from v      r offset
+//  filterParams1 | g from y      g from u      g from v      g offset
+//  filterParams2 | b from y      b from u      b from v      b offset
+vec4 filterYUV(vec2 colorTexCoord,
+               sampler2D colorTexture,
+               vec4 filterParams0,
+               vec4 filterParams1,
+               vec4 filterParams2) {
+    vec4 yuv = vec4(sampleColor(colorTexture, colorTexCoord).rgb, 1.0);
+    vec3 rgb = vec3(dot(filterParams0, yuv), dot(filterParams1, yuv), dot(filterParams2, yuv));
+    return vec4(clamp(rgb, 0.0, 1.0), 1.0);
+}
+
 vec4 filterNone(vec2 colorTexCoord, sampler2D colorTexture) {
     return sampleColor(colorTexture, colorTexCoord);
 }
@@ -399,6 +496,13 @@ vec4 filterColor(vec2 colorTexCoord,
                                     framebufferSize,
                                     filterParams0,
                                     filterParams1);
+    case COMBINER_CTRL_FILTER_BLURRED_ROUNDED_RECT:
+        return filterBlurredRoundedRect(colorTexCoord,
+                                        colorTexture,
+                                        filterParams0,
+                                        filterParams1);
+    case COMBINER_CTRL_FILTER_YUV:
+        return filterYUV(colorTexCoord, colorTexture, filterParams0, filterParams1, filterParams2);
     case COMBINER_CTRL_FILTER_BLUR:
         return filterBlur(colorTexCoord,
                           colorTexture,
@@ -542,13 +646,23 @@ vec4 composite(vec4 srcColor,
                 1.0);
 }
 
+// Dithering
+
+// Interleaved gradient noise, from Jimenez, "Next Generation Post Processing in Call of Duty:
+// Advanced Warfare", SIGGRAPH 2014.
+vec3 dither(vec3 color, vec2 fragCoord, float amount) {
+    float noise = fract(52.9829189 * fract(dot(fragCoord, vec2(0.06711056, 0.00583715))));
+    return clamp(color + (noise - 0.5) * amount, 0.0, 1.0);
+}
+
 // Masks
 
 float sampleMask(float maskAlpha,
                  sampler2D maskTexture,
                  vec2 maskTextureSize,
                  vec3 maskTexCoord,
-                 int maskCtrl) {
+                 int maskCtrl,
+                 int maskThreshold) {
     if (maskCtrl == 0)
         return maskAlpha;
 
@@ -556,7 +670,9 @@ float sampleMask(float maskAlpha,
     vec4 texel = texture(maskTexture, (vec2(maskTexCoordI / ivec2(1, 4)) + 0.5) / maskTextureSize);
     float coverage = texel[maskTexCoordI.y % 4] + maskTexCoord.z;
 
-    if ((maskCtrl & TILE_CTRL_MASK_WINDING) != 0)
+    if (maskCtrl == TILE_CTRL_MASK_WINDING_THRESHOLD)
+        coverage = max(abs(coverage) - float(maskThreshold - 1), 0.0);
+    else if ((maskCtrl & TILE_CTRL_MASK_WINDING) != 0)
         coverage = abs(coverage);
     else
         coverage = 1.0 - abs(1.0 - mod(coverage, 2.0));
@@ -568,8 +684,43 @@ float sampleMask(float maskAlpha,
 void calculateColor(int tileCtrl, int ctrl) {
     // Sample mask.
     int maskCtrl0 = (tileCtrl >> TILE_CTRL_MASK_0_SHIFT) & TILE_CTRL_MASK_MASK;
+    int maskThreshold0 = (tileCtrl >> TILE_CTRL_MASK_THRESHOLD_0_SHIFT) &
+        TILE_CTRL_MASK_THRESHOLD_MASK;
     float maskAlpha = 1.0;
-    maskAlpha = sampleMask(maskAlpha, uMaskTexture0, uMaskTextureSize0, vMaskTexCoord0, maskCtrl0);
+    maskAlpha = sampleMask(maskAlpha,
+                           uMaskTexture0,
+                           uMaskTextureSize0,
+                           vMaskTexCoord0,
+                           maskCtrl0,
+                           maskThreshold0);
+
+    // Subpixel text over an unknown background is blended per channel, using the coverage of
+    // each channel as its alpha. There's no background to gamma correct against.
+    int componentAlpha = (ctrl >> COMBINER_CTRL_COMPONENT_ALPHA_SHIFT) &
+        COMBINER_CTRL_COMPONENT_ALPHA_MASK;
+    if (componentAlpha != 0) {
+        vec3 alpha = filterTextAlpha(vColorTexCoord0,
+                                     uColorTexture0,
+                                     uColorTextureSize0,
+                                     uFilterParams0) * maskAlpha * uFilterParams1.a;
+        float maxAlpha = max(alpha.r, max(alpha.g, alpha.b));
+        vec4 premultipliedColor = vec4(uFilterParams2.rgb * alpha, maxAlpha);
+        switch (componentAlpha) {
+        case COMBINER_CTRL_COMPONENT_ALPHA_DUAL_SOURCE:
+            oFragColor = premultipliedColor;
+#ifdef PF_DUAL_SOURCE_BLENDING
+            oFragBlend = vec4(alpha, maxAlpha);
+#endif
+            break;
+        case COMBINER_CTRL_COMPONENT_ALPHA_MASK_PASS:
+            oFragColor = vec4(alpha, maxAlpha);
+            break;
+        default:
+            oFragColor = premultipliedColor;
+            break;
+        }
+        return;
+    }
 
     // Sample color.
     vec4 color = vBaseColor;
@@ -597,6 +748,9 @@ void calculateColor(int tileCtrl, int ctrl) {
     int compositeOp = (ctrl >> COMBINER_CTRL_COMPOSITE_SHIFT) & COMBINER_CTRL_COMPOSITE_MASK;
     color = composite(color, uDestTexture, uFramebufferSize, gl_FragCoord.xy, compositeOp);
 
+    // Dither to break up banding.
+    color.rgb = dither(color.rgb, gl_FragCoord.xy, uDitherAmount);
+
     // Premultiply alpha.
     color.rgb *= color.a;
     oFragColor = color;
@@ -8,12 +8,16 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::arch::aarch64::{self, float32x2_t, float32x4_t, int32x2_t, int32x4_t};
-use std::arch::aarch64::{uint32x2_t, uint32x4_t};
-use std::f32;
-use std::fmt::{self, Debug, Formatter};
-use std::mem;
-use std::ops::{Add, BitAnd, BitOr, Div, Index, IndexMut, Mul, Not, Shr, Sub};
+//! NEON implementations of the vector types, for AArch64.
+//!
+//! Advanced SIMD is a mandatory part of AArch64, so these need no runtime detection and work on
+//! stable Rust.
+
+use crate::cast::{cast, cast_mut, cast_ref};
+use core::arch::aarch64::{self, float32x2_t, float32x4_t, int32x2_t, int32x4_t};
+use core::arch::aarch64::{int16x8_t, uint16x8_t, uint32x2_t, uint32x4_t, uint8x16_t};
+use core::fmt::{self, Debug, Formatter};
+use core::ops::{Add, BitAnd, BitOr, BitXor, Div, Index, IndexMut, Mul, Not, Shl, Shr, Sub};
 
 mod swizzle_f32x4;
 mod swizzle_i32x4;
@@ -21,6 +25,7 @@ mod swizzle_i32x4;
 // Two 32-bit floats
 
 #[derive(Clone, Copy)]
+#[repr(transparent)]
 pub struct F32x2(pub float32x2_t);
 
 impl F32x2 {
@@ -28,29 +33,35 @@ impl F32x2 {
 
     #[inline]
     pub fn new(a: f32, b: f32) -> F32x2 {
-        unsafe { F32x2(mem::transmute([a, b])) }
+        F32x2(cast::<[f32; 2], float32x2_t>([a, b]))
     }
 
     #[inline]
     pub fn splat(x: f32) -> F32x2 {
-        F32x2::new(x, x)
+        unsafe { F32x2(aarch64::vdup_n_f32(x)) }
     }
 
     // Basic operations
 
     #[inline]
     pub fn approx_recip(self) -> F32x2 {
-        unsafe { F32x2(vrecpe_v2f32(self.0)) }
+        unsafe { F32x2(aarch64::vrecpe_f32(self.0)) }
+    }
+
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x2 {
+        unsafe { F32x2(aarch64::vrsqrte_f32(self.0)) }
     }
 
     #[inline]
     pub fn min(self, other: F32x2) -> F32x2 {
-        unsafe { F32x2(simd_fmin(self.0, other.0)) }
+        unsafe { F32x2(aarch64::vminnm_f32(self.0, other.0)) }
     }
 
     #[inline]
     pub fn max(self, other: F32x2) -> F32x2 {
-        unsafe { F32x2(simd_fmax(self.0, other.0)) }
+        unsafe { F32x2(aarch64::vmaxnm_f32(self.0, other.0)) }
     }
 
     #[inline]
@@ -60,44 +71,127 @@ impl F32x2 {
 
     #[inline]
     pub fn abs(self) -> F32x2 {
-        unsafe { F32x2(fabs_v2f32(self.0)) }
+        unsafe { F32x2(aarch64::vabs_f32(self.0)) }
+    }
+
+    /// Returns the magnitude of each lane with the sign of the same lane of `sign`. See
+    /// `F32x4::copysign()`.
+    #[inline]
+    pub fn copysign(self, sign: F32x2) -> F32x2 {
+        unsafe { F32x2(aarch64::vbsl_f32(aarch64::vdup_n_u32(0x8000_0000), sign.0, self.0)) }
+    }
+
+    /// Returns the sign bits of the lanes packed into the low two bits, with the first lane's in
+    /// bit 0. See `F32x4::sign_bits()`.
+    #[inline]
+    pub fn sign_bits(self) -> u32 {
+        unsafe {
+            // Shift each sign bit down to the bit for its lane, and add them up.
+            let bits = aarch64::vreinterpret_u32_f32(self.0);
+            let signs = aarch64::vand_u32(bits, aarch64::vdup_n_u32(0x8000_0000));
+            aarch64::vaddv_u32(aarch64::vshl_u32(signs, I32x2::new(-31, -30).0))
+        }
     }
 
     #[inline]
     pub fn floor(self) -> F32x2 {
-        unsafe { F32x2(floor_v2f32(self.0)) }
+        unsafe { F32x2(aarch64::vrndm_f32(self.0)) }
     }
 
     #[inline]
     pub fn ceil(self) -> F32x2 {
-        unsafe { F32x2(ceil_v2f32(self.0)) }
+        unsafe { F32x2(aarch64::vrndp_f32(self.0)) }
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x2 {
+        unsafe { F32x2(aarch64::vrndn_f32(self.0)) }
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x2 {
+        self - self.floor()
     }
 
     #[inline]
     pub fn sqrt(self) -> F32x2 {
-        unsafe { F32x2(sqrt_v2f32(self.0)) }
+        unsafe { F32x2(aarch64::vsqrt_f32(self.0)) }
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F32x2, b: F32x2) -> F32x2 {
+        unsafe { F32x2(aarch64::vfma_f32(b.0, self.0, a.0)) }
+    }
+
+    // Horizontal operations
+
+    /// Returns the sum of both lanes.
+    #[inline]
+    pub fn sum(self) -> f32 {
+        unsafe { aarch64::vaddv_f32(self.0) }
+    }
+
+    /// Returns the smaller of the two lanes.
+    #[inline]
+    pub fn min_element(self) -> f32 {
+        unsafe { aarch64::vminnmv_f32(self.0) }
+    }
+
+    /// Returns the larger of the two lanes.
+    #[inline]
+    pub fn max_element(self) -> f32 {
+        unsafe { aarch64::vmaxnmv_f32(self.0) }
+    }
+
+    /// Returns the dot product of this vector and `other`.
+    #[inline]
+    pub fn dot(self, other: F32x2) -> f32 {
+        (self * other).sum()
     }
 
     // Packed comparisons
 
     #[inline]
     pub fn packed_eq(self, other: F32x2) -> U32x2 {
-        unsafe { U32x2(simd_eq(self.0, other.0)) }
+        unsafe { U32x2(aarch64::vceq_f32(self.0, other.0)) }
     }
 
     #[inline]
     pub fn packed_gt(self, other: F32x2) -> U32x2 {
-        unsafe { U32x2(simd_gt(self.0, other.0)) }
+        unsafe { U32x2(aarch64::vcgt_f32(self.0, other.0)) }
     }
 
     #[inline]
     pub fn packed_lt(self, other: F32x2) -> U32x2 {
-        unsafe { U32x2(simd_lt(self.0, other.0)) }
+        unsafe { U32x2(aarch64::vclt_f32(self.0, other.0)) }
     }
 
     #[inline]
     pub fn packed_le(self, other: F32x2) -> U32x2 {
-        unsafe { U32x2(simd_le(self.0, other.0)) }
+        unsafe { U32x2(aarch64::vcle_f32(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: F32x2) -> U32x2 {
+        unsafe { U32x2(aarch64::vcge_f32(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: F32x2) -> U32x2 {
+        !self.packed_eq(other)
+    }
+
+    /// Returns the lanes of `a` where `mask` is true and those of `b` where it's false.
+    ///
+    /// The result is *undefined* if both values in `mask` are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn select(mask: U32x2, a: F32x2, b: F32x2) -> F32x2 {
+        unsafe { F32x2(aarch64::vbsl_f32(mask.0, a.0, b.0)) }
     }
 
     // Conversions
@@ -107,10 +201,11 @@ impl F32x2 {
         self.concat_xy_xy(F32x2::default())
     }
 
-    /// Converts these packed floats to integers via rounding.
+    /// Converts these packed floats to integers via rounding, with halfway cases rounded to
+    /// even.
     #[inline]
     pub fn to_i32x2(self) -> I32x2 {
-        unsafe { I32x2(simd_cast(round_v2f32(self.0))) }
+        unsafe { I32x2(aarch64::vcvtn_s32_f32(self.0)) }
     }
 
     #[inline]
@@ -122,21 +217,21 @@ impl F32x2 {
 
     #[inline]
     pub fn yx(self) -> F32x2 {
-        unsafe { F32x2(simd_shuffle2(self.0, self.0, [1, 0])) }
+        unsafe { F32x2(aarch64::vrev64_f32(self.0)) }
     }
 
     // Concatenations
 
     #[inline]
     pub fn concat_xy_xy(self, other: F32x2) -> F32x4 {
-        unsafe { F32x4(simd_shuffle4(self.0, other.0, [0, 1, 0, 1])) }
+        unsafe { F32x4(aarch64::vcombine_f32(self.0, other.0)) }
     }
 }
 
 impl Default for F32x2 {
     #[inline]
     fn default() -> F32x2 {
-        F32x2::new(0.0, 0.0)
+        F32x2::splat(0.0)
     }
 }
 
@@ -151,31 +246,29 @@ impl Index<usize> for F32x2 {
     type Output = f32;
     #[inline]
     fn index(&self, index: usize) -> &f32 {
-        unsafe {
-            assert!(index < 2);
-            let ptr = &self.0 as *const float32x2_t as *const f32;
-            mem::transmute::<*const f32, &f32>(ptr.offset(index as isize))
-        }
+        &cast_ref::<float32x2_t, [f32; 2]>(&self.0)[index]
     }
 }
 
 impl IndexMut<usize> for F32x2 {
     #[inline]
     fn index_mut(&mut self, index: usize) -> &mut f32 {
-        unsafe {
-            assert!(index < 2);
-            let ptr = &mut self.0 as *mut float32x2_t as *mut f32;
-            mem::transmute::<*mut f32, &mut f32>(ptr.offset(index as isize))
-        }
+        &mut cast_mut::<float32x2_t, [f32; 2]>(&mut self.0)[index]
     }
 }
 
+impl PartialEq for F32x2 {
+    #[inline]
+    fn eq(&self, other: &F32x2) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
 
 impl Add<F32x2> for F32x2 {
     type Output = F32x2;
     #[inline]
     fn add(self, other: F32x2) -> F32x2 {
-        unsafe { F32x2(simd_add(self.0, other.0)) }
+        unsafe { F32x2(aarch64::vadd_f32(self.0, other.0)) }
     }
 }
 
@@ -183,7 +276,7 @@ impl Div<F32x2> for F32x2 {
     type Output = F32x2;
     #[inline]
     fn div(self, other: F32x2) -> F32x2 {
-        unsafe { F32x2(simd_div(self.0, other.0)) }
+        unsafe { F32x2(aarch64::vdiv_f32(self.0, other.0)) }
     }
 }
 
@@ -191,7 +284,7 @@ impl Mul<F32x2> for F32x2 {
     type Output = F32x2;
     #[inline]
     fn mul(self, other: F32x2) -> F32x2 {
-        unsafe { F32x2(simd_mul(self.0, other.0)) }
+        unsafe { F32x2(aarch64::vmul_f32(self.0, other.0)) }
     }
 }
 
@@ -199,41 +292,99 @@ impl Sub<F32x2> for F32x2 {
     type Output = F32x2;
     #[inline]
     fn sub(self, other: F32x2) -> F32x2 {
-        unsafe { F32x2(simd_sub(self.0, other.0)) }
+        unsafe { F32x2(aarch64::vsub_f32(self.0, other.0)) }
     }
 }
 
 // Four 32-bit floats
 
 #[derive(Clone, Copy)]
+#[repr(transparent)]
 pub struct F32x4(pub float32x4_t);
 
 impl F32x4 {
+    // Constructors
+
     #[inline]
     pub fn new(a: f32, b: f32, c: f32, d: f32) -> F32x4 {
-        unsafe { F32x4(mem::transmute([a, b, c, d])) }
+        F32x4(cast::<[f32; 4], float32x4_t>([a, b, c, d]))
     }
 
     #[inline]
     pub fn splat(x: f32) -> F32x4 {
-        F32x4::new(x, x, x, x)
+        unsafe { F32x4(aarch64::vdupq_n_f32(x)) }
+    }
+
+    /// Loads the first four elements of `slice`, which needn't be aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn from_slice(slice: &[f32]) -> F32x4 {
+        let slice = &slice[..4];
+        unsafe { F32x4(aarch64::vld1q_f32(slice.as_ptr())) }
+    }
+
+    /// Loads the first four elements of `slice`, which must start on a 16-byte boundary. This can
+    /// be faster than `from_slice` on some CPUs.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn from_slice_aligned(slice: &[f32]) -> F32x4 {
+        let slice = &slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        F32x4::from_slice(slice)
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which needn't be
+    /// aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn write_to_slice(self, slice: &mut [f32]) {
+        let slice = &mut slice[..4];
+        unsafe { aarch64::vst1q_f32(slice.as_mut_ptr(), self.0) }
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which must start
+    /// on a 16-byte boundary.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_mut_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn write_to_slice_aligned(self, slice: &mut [f32]) {
+        let slice = &mut slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        self.write_to_slice(slice)
     }
 
     // Basic operations
 
     #[inline]
     pub fn approx_recip(self) -> F32x4 {
-        unsafe { F32x4(vrecpe_v4f32(self.0)) }
+        unsafe { F32x4(aarch64::vrecpeq_f32(self.0)) }
+    }
+
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x4 {
+        unsafe { F32x4(aarch64::vrsqrteq_f32(self.0)) }
     }
 
     #[inline]
     pub fn min(self, other: F32x4) -> F32x4 {
-        unsafe { F32x4(simd_fmin(self.0, other.0)) }
+        unsafe { F32x4(aarch64::vminnmq_f32(self.0, other.0)) }
     }
 
     #[inline]
     pub fn max(self, other: F32x4) -> F32x4 {
-        unsafe { F32x4(simd_fmax(self.0, other.0)) }
+        unsafe { F32x4(aarch64::vmaxnmq_f32(self.0, other.0)) }
     }
 
     #[inline]
@@ -243,103 +394,250 @@ impl F32x4 {
 
     #[inline]
     pub fn abs(self) -> F32x4 {
-        unsafe { F32x4(fabs_v4f32(self.0)) }
+        unsafe { F32x4(aarch64::vabsq_f32(self.0)) }
+    }
+
+    /// Returns the magnitude of each lane with the sign of the same lane of `sign`.
+    ///
+    /// Only the sign bit of `sign` matters, so negative zero and NaNs with the sign bit set count
+    /// as negative.
+    #[inline]
+    pub fn copysign(self, sign: F32x4) -> F32x4 {
+        unsafe { F32x4(aarch64::vbslq_f32(aarch64::vdupq_n_u32(0x8000_0000), sign.0, self.0)) }
+    }
+
+    /// Returns the sign bits of the lanes packed into the low four bits, with the first lane's in
+    /// bit 0, like `movmskps`. As with `copysign()`, negative zero and negative NaNs count.
+    #[inline]
+    pub fn sign_bits(self) -> u32 {
+        unsafe {
+            // Shift each sign bit down to the bit for its lane, and add them up.
+            let bits = aarch64::vreinterpretq_u32_f32(self.0);
+            let signs = aarch64::vandq_u32(bits, aarch64::vdupq_n_u32(0x8000_0000));
+            aarch64::vaddvq_u32(aarch64::vshlq_u32(signs, I32x4::new(-31, -30, -29, -28).0))
+        }
     }
 
     #[inline]
     pub fn floor(self) -> F32x4 {
-        unsafe { F32x4(floor_v4f32(self.0)) }
+        unsafe { F32x4(aarch64::vrndmq_f32(self.0)) }
     }
 
     #[inline]
     pub fn ceil(self) -> F32x4 {
-        unsafe { F32x4(ceil_v4f32(self.0)) }
+        unsafe { F32x4(aarch64::vrndpq_f32(self.0)) }
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x4 {
+        unsafe { F32x4(aarch64::vrndnq_f32(self.0)) }
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x4 {
+        self - self.floor()
     }
 
     #[inline]
     pub fn sqrt(self) -> F32x4 {
-        unsafe { F32x4(sqrt_v4f32(self.0)) }
+        unsafe { F32x4(aarch64::vsqrtq_f32(self.0)) }
+    }
+
+    /// Returns `self * a + b`, rounded once.
+    #[inline]
+    pub fn mul_add(self, a: F32x4, b: F32x4) -> F32x4 {
+        unsafe { F32x4(aarch64::vfmaq_f32(b.0, self.0, a.0)) }
+    }
+
+    /// Returns the cross product of the first three lanes of this vector and `other`.
+    ///
+    /// The w lanes are ignored, and the w lane of the result is zero.
+    #[inline]
+    pub fn cross(self, other: F32x4) -> F32x4 {
+        let product = (self * other.yzxw() - self.yzxw() * other).yzxw();
+        unsafe { F32x4(aarch64::vsetq_lane_f32::<3>(0.0, product.0)) }
+    }
+
+    // Horizontal operations
+
+    /// Returns the sum of all four lanes.
+    #[inline]
+    pub fn sum(self) -> f32 {
+        unsafe { aarch64::vaddvq_f32(self.0) }
+    }
+
+    /// Returns the smallest of the four lanes.
+    #[inline]
+    pub fn min_element(self) -> f32 {
+        unsafe { aarch64::vminnmvq_f32(self.0) }
+    }
+
+    /// Returns the largest of the four lanes.
+    #[inline]
+    pub fn max_element(self) -> f32 {
+        unsafe { aarch64::vmaxnmvq_f32(self.0) }
+    }
+
+    /// Returns the dot product of this vector and `other`, using all four lanes.
+    #[inline]
+    pub fn dot(self, other: F32x4) -> f32 {
+        (self * other).sum()
     }
 
     // Packed comparisons
 
     #[inline]
     pub fn packed_eq(self, other: F32x4) -> U32x4 {
-        unsafe { U32x4(simd_eq(self.0, other.0)) }
+        unsafe { U32x4(aarch64::vceqq_f32(self.0, other.0)) }
     }
 
     #[inline]
     pub fn packed_gt(self, other: F32x4) -> U32x4 {
-        unsafe { U32x4(simd_gt(self.0, other.0)) }
+        unsafe { U32x4(aarch64::vcgtq_f32(self.0, other.0)) }
     }
 
     #[inline]
     pub fn packed_le(self, other: F32x4) -> U32x4 {
-        unsafe { U32x4(simd_le(self.0, other.0)) }
+        unsafe { U32x4(aarch64::vcleq_f32(self.0, other.0)) }
     }
 
     #[inline]
     pub fn packed_lt(self, other: F32x4) -> U32x4 {
-        unsafe { U32x4(simd_lt(self.0, other.0)) }
+        unsafe { U32x4(aarch64::vcltq_f32(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: F32x4) -> U32x4 {
+        unsafe { U32x4(aarch64::vcgeq_f32(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: F32x4) -> U32x4 {
+        !self.packed_eq(other)
+    }
+
+    /// Returns the lanes of `a` where `mask` is true and those of `b` where it's false.
+    ///
+    /// The result is *undefined* if all four values in `mask` are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn select(mask: U32x4, a: F32x4, b: F32x4) -> F32x4 {
+        unsafe { F32x4(aarch64::vbslq_f32(mask.0, a.0, b.0)) }
+    }
+
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order.
+    ///
+    /// This covers every permutation, so it's handy when the lanes are chosen by other generic
+    /// code. `v.shuffle::<2, 3, 0, 1>()` is the same as `v.zwxy()`. Every index must be less
+    /// than 4.
+    #[inline]
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> F32x4 {
+        debug_assert!(A < 4 && B < 4 && C < 4 && D < 4, "shuffle lane index out of range");
+        self.shuffle_lanes([A as u8, B as u8, C as u8, D as u8])
     }
 
     // Swizzle conversions
 
     #[inline]
     pub fn xy(self) -> F32x2 {
-        unsafe { F32x2(simd_shuffle2(self.0, self.0, [0, 1])) }
+        unsafe { F32x2(aarch64::vget_low_f32(self.0)) }
     }
 
     #[inline]
     pub fn yx(self) -> F32x2 {
-        unsafe { F32x2(simd_shuffle2(self.0, self.0, [1, 0])) }
+        self.xy().yx()
     }
 
     #[inline]
     pub fn xw(self) -> F32x2 {
-        unsafe { F32x2(simd_shuffle2(self.0, self.0, [0, 3])) }
+        self.xwyz().xy()
     }
 
     #[inline]
     pub fn zy(self) -> F32x2 {
-        unsafe { F32x2(simd_shuffle2(self.0, self.0, [2, 1])) }
+        self.zyxw().xy()
     }
 
     #[inline]
     pub fn zw(self) -> F32x2 {
-        unsafe { F32x2(simd_shuffle2(self.0, self.0, [2, 3])) }
+        unsafe { F32x2(aarch64::vget_high_f32(self.0)) }
     }
 
     // Concatenations
 
     #[inline]
     pub fn concat_xy_xy(self, other: F32x4) -> F32x4 {
-        unsafe { F32x4(simd_shuffle4(self.0, other.0, [0, 1, 0, 1])) }
+        self.xy().concat_xy_xy(other.xy())
     }
 
     #[inline]
     pub fn concat_xy_zw(self, other: F32x4) -> F32x4 {
-        unsafe { F32x4(simd_shuffle4(self.0, other.0, [0, 1, 2, 3])) }
+        self.xy().concat_xy_xy(other.zw())
     }
 
     #[inline]
     pub fn concat_zw_zw(self, other: F32x4) -> F32x4 {
-        unsafe { F32x4(simd_shuffle4(self.0, other.0, [2, 3, 2, 3])) }
+        self.zw().concat_xy_xy(other.zw())
+    }
+
+    #[inline]
+    pub fn concat_wz_yx(self, other: F32x4) -> F32x4 {
+        self.zw().yx().concat_xy_xy(other.xy().yx())
     }
 
     // Conversions
 
-    /// Converts these packed floats to integers via rounding.
+    /// Converts these packed floats to integers via rounding. This is the same as
+    /// `to_i32x4_round()`.
     #[inline]
     pub fn to_i32x4(self) -> I32x4 {
-        unsafe { I32x4(simd_cast(round_v4f32(self.0))) }
+        self.to_i32x4_round()
+    }
+
+    /// Converts these packed floats to integers, rounding to the nearest integer and halfway cases
+    /// to the even one.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_round(self) -> I32x4 {
+        unsafe { I32x4(aarch64::vcvtnq_s32_f32(self.0)) }
+    }
+
+    /// Converts these packed floats to integers, rounding toward zero.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_truncate(self) -> I32x4 {
+        unsafe { I32x4(aarch64::vcvtq_s32_f32(self.0)) }
+    }
+
+    /// Converts these packed floats to integers, rounding toward negative infinity.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_floor(self) -> I32x4 {
+        unsafe { I32x4(aarch64::vcvtmq_s32_f32(self.0)) }
+    }
+
+    #[inline]
+    fn shuffle_lanes(self, lanes: [u8; 4]) -> F32x4 {
+        unsafe {
+            let bytes = shuffle_lanes(aarch64::vreinterpretq_u8_f32(self.0), lanes);
+            F32x4(aarch64::vreinterpretq_f32_u8(bytes))
+        }
     }
 }
 
 impl Default for F32x4 {
     #[inline]
     fn default() -> F32x4 {
-        F32x4::new(0.0, 0.0, 0.0, 0.0)
+        F32x4::splat(0.0)
     }
 }
 
@@ -347,22 +645,14 @@ impl Index<usize> for F32x4 {
     type Output = f32;
     #[inline]
     fn index(&self, index: usize) -> &f32 {
-        unsafe {
-            assert!(index < 4);
-            let ptr = &self.0 as *const float32x4_t as *const f32;
-            mem::transmute::<*const f32, &f32>(ptr.offset(index as isize))
-        }
+        &cast_ref::<float32x4_t, [f32; 4]>(&self.0)[index]
     }
 }
 
 impl IndexMut<usize> for F32x4 {
     #[inline]
     fn index_mut(&mut self, index: usize) -> &mut f32 {
-        unsafe {
-            assert!(index < 4);
-            let ptr = &mut self.0 as *mut float32x4_t as *mut f32;
-            mem::transmute::<*mut f32, &mut f32>(ptr.offset(index as isize))
-        }
+        &mut cast_mut::<float32x4_t, [f32; 4]>(&mut self.0)[index]
     }
 }
 
@@ -384,7 +674,7 @@ impl Add<F32x4> for F32x4 {
     type Output = F32x4;
     #[inline]
     fn add(self, other: F32x4) -> F32x4 {
-        unsafe { F32x4(simd_add(self.0, other.0)) }
+        unsafe { F32x4(aarch64::vaddq_f32(self.0, other.0)) }
     }
 }
 
@@ -392,7 +682,7 @@ impl Div<F32x4> for F32x4 {
     type Output = F32x4;
     #[inline]
     fn div(self, other: F32x4) -> F32x4 {
-        unsafe { F32x4(simd_div(self.0, other.0)) }
+        unsafe { F32x4(aarch64::vdivq_f32(self.0, other.0)) }
     }
 }
 
@@ -400,7 +690,7 @@ impl Mul<F32x4> for F32x4 {
     type Output = F32x4;
     #[inline]
     fn mul(self, other: F32x4) -> F32x4 {
-        unsafe { F32x4(simd_mul(self.0, other.0)) }
+        unsafe { F32x4(aarch64::vmulq_f32(self.0, other.0)) }
     }
 }
 
@@ -408,484 +698,1513 @@ impl Sub<F32x4> for F32x4 {
     type Output = F32x4;
     #[inline]
     fn sub(self, other: F32x4) -> F32x4 {
-        unsafe { F32x4(simd_sub(self.0, other.0)) }
+        unsafe { F32x4(aarch64::vsubq_f32(self.0, other.0)) }
     }
 }
 
-// Two 32-bit signed integers
+// Eight 32-bit floats
 
-#[derive(Clone, Copy, Debug)]
-pub struct I32x2(pub int32x2_t);
+/// Eight 32-bit floats, processed as two `F32x4` halves.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(C)]
+pub struct F32x8(pub F32x4, pub F32x4);
+
+impl F32x8 {
+    // Constructors
 
-impl I32x2 {
     #[inline]
-    pub fn new(x: i32, y: i32) -> I32x2 {
-        unsafe { I32x2(mem::transmute([x, y])) }
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32, g: f32, h: f32) -> F32x8 {
+        F32x8(F32x4::new(a, b, c, d), F32x4::new(e, f, g, h))
     }
 
     #[inline]
-    pub fn splat(x: i32) -> I32x2 {
-        I32x2::new(x, x)
+    pub fn splat(x: f32) -> F32x8 {
+        F32x8(F32x4::splat(x), F32x4::splat(x))
+    }
+
+    #[inline]
+    pub fn from_halves(lo: F32x4, hi: F32x4) -> F32x8 {
+        F32x8(lo, hi)
     }
 
     // Accessors
 
+    /// Returns the first four lanes.
     #[inline]
-    pub fn x(self) -> i32 {
-        self[0]
+    pub fn lo(self) -> F32x4 {
+        self.0
     }
 
+    /// Returns the last four lanes.
     #[inline]
-    pub fn y(self) -> i32 {
-        self[1]
+    pub fn hi(self) -> F32x4 {
+        self.1
     }
 
+    // Basic operations
+
     #[inline]
-    pub fn packed_eq(self, other: I32x2) -> U32x2 {
-        unsafe { U32x2(simd_eq(self.0, other.0)) }
+    pub fn approx_recip(self) -> F32x8 {
+        F32x8(self.0.approx_recip(), self.1.approx_recip())
     }
 
-    // Basic operations
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x8 {
+        F32x8(self.0.approx_rsqrt(), self.1.approx_rsqrt())
+    }
 
     #[inline]
-    pub fn max(self, other: I32x2) -> I32x2 {
-        self.to_i32x4().max(other.to_i32x4()).xy()
+    pub fn min(self, other: F32x8) -> F32x8 {
+        F32x8(self.0.min(other.0), self.1.min(other.1))
     }
 
     #[inline]
-    pub fn min(self, other: I32x2) -> I32x2 {
-        self.to_i32x4().min(other.to_i32x4()).xy()
+    pub fn max(self, other: F32x8) -> F32x8 {
+        F32x8(self.0.max(other.0), self.1.max(other.1))
     }
 
-    // Concatenations
+    #[inline]
+    pub fn clamp(self, min: F32x8, max: F32x8) -> F32x8 {
+        self.max(min).min(max)
+    }
 
     #[inline]
-    pub fn concat_xy_xy(self, other: I32x2) -> I32x4 {
-        unsafe { I32x4(simd_shuffle4(self.0, other.0, [0, 1, 0, 1])) }
+    pub fn abs(self) -> F32x8 {
+        F32x8(self.0.abs(), self.1.abs())
     }
 
-    // Conversions
+    #[inline]
+    pub fn floor(self) -> F32x8 {
+        F32x8(self.0.floor(), self.1.floor())
+    }
 
-    /// Converts these packed integers to floats.
     #[inline]
-    pub fn to_f32x2(self) -> F32x2 {
-        unsafe { F32x2(simd_cast(self.0)) }
+    pub fn ceil(self) -> F32x8 {
+        F32x8(self.0.ceil(), self.1.ceil())
     }
-    
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
     #[inline]
-    pub fn to_i32x4(self) -> I32x4 {
-        self.concat_xy_xy(I32x2::default())
+    pub fn round(self) -> F32x8 {
+        F32x8(self.0.round(), self.1.round())
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x8 {
+        self - self.floor()
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F32x8 {
+        F32x8(self.0.sqrt(), self.1.sqrt())
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F32x8, b: F32x8) -> F32x8 {
+        F32x8(self.0.mul_add(a.0, b.0), self.1.mul_add(a.1, b.1))
     }
 }
 
-impl Default for I32x2 {
+impl Index<usize> for F32x8 {
+    type Output = f32;
     #[inline]
-    fn default() -> I32x2 {
-        I32x2::splat(0)
+    fn index(&self, index: usize) -> &f32 {
+        if index < 4 { &self.0[index] } else { &self.1[index - 4] }
     }
 }
 
-impl PartialEq for I32x2 {
+impl IndexMut<usize> for F32x8 {
     #[inline]
-    fn eq(&self, other: &I32x2) -> bool {
-        self.packed_eq(*other).all_true()
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        if index < 4 { &mut self.0[index] } else { &mut self.1[index - 4] }
     }
 }
 
-impl Index<usize> for I32x2 {
-    type Output = i32;
+impl Debug for F32x8 {
     #[inline]
-    fn index(&self, index: usize) -> &i32 {
-        unsafe {
-            assert!(index < 2);
-            let ptr = &self.0 as *const int32x2_t as *const i32;
-            mem::transmute::<*const i32, &i32>(ptr.offset(index as isize))
-        }
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f,
+               "<{}, {}, {}, {}, {}, {}, {}, {}>",
+               self[0], self[1], self[2], self[3], self[4], self[5], self[6], self[7])
     }
 }
 
-impl IndexMut<usize> for I32x2 {
+impl Add<F32x8> for F32x8 {
+    type Output = F32x8;
     #[inline]
-    fn index_mut(&mut self, index: usize) -> &mut i32 {
-        unsafe {
-            assert!(index < 2);
-            let ptr = &mut self.0 as *mut int32x2_t as *mut i32;
-            mem::transmute::<*mut i32, &mut i32>(ptr.offset(index as isize))
-        }
+    fn add(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 + other.0, self.1 + other.1)
     }
 }
 
-impl Add<I32x2> for I32x2 {
-    type Output = I32x2;
+impl Div<F32x8> for F32x8 {
+    type Output = F32x8;
     #[inline]
-    fn add(self, other: I32x2) -> I32x2 {
-        unsafe { I32x2(simd_add(self.0, other.0)) }
+    fn div(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 / other.0, self.1 / other.1)
     }
 }
 
-impl Sub<I32x2> for I32x2 {
-    type Output = I32x2;
+impl Mul<F32x8> for F32x8 {
+    type Output = F32x8;
     #[inline]
-    fn sub(self, other: I32x2) -> I32x2 {
-        unsafe { I32x2(simd_sub(self.0, other.0)) }
+    fn mul(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 * other.0, self.1 * other.1)
     }
 }
 
-impl Mul<I32x2> for I32x2 {
-    type Output = I32x2;
+impl Sub<F32x8> for F32x8 {
+    type Output = F32x8;
     #[inline]
-    fn mul(self, other: I32x2) -> I32x2 {
-        unsafe { I32x2(simd_mul(self.0, other.0)) }
+    fn sub(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 - other.0, self.1 - other.1)
     }
 }
 
-// Four 32-bit signed integers
+// Two and four 64-bit floats
+//
+// There are no double-precision versions of these yet, so this backend shares the scalar ones.
 
-#[derive(Clone, Copy, Debug)]
-pub struct I32x4(pub int32x4_t);
+pub use crate::scalar::{F64x2, F64x4};
+
+// Two 32-bit signed integers
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct I32x2(pub int32x2_t);
+
+impl I32x2 {
+    // Constructors
 
-impl I32x4 {
     #[inline]
-    pub fn new(a: i32, b: i32, c: i32, d: i32) -> I32x4 {
-        unsafe { I32x4(mem::transmute([a, b, c, d])) }
+    pub fn new(x: i32, y: i32) -> I32x2 {
+        I32x2(cast::<[i32; 2], int32x2_t>([x, y]))
     }
 
     #[inline]
-    pub fn splat(x: i32) -> I32x4 {
-        I32x4::new(x, x, x, x)
+    pub fn splat(x: i32) -> I32x2 {
+        unsafe { I32x2(aarch64::vdup_n_s32(x)) }
     }
 
-    // Basic operations
+    // Accessors
 
     #[inline]
-    pub fn max(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(simd_cast(simd_fmax(self.to_f32x4().0, other.to_f32x4().0))) }
+    pub fn x(self) -> i32 {
+        self[0]
     }
 
     #[inline]
-    pub fn min(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(simd_cast(simd_fmin(self.to_f32x4().0, other.to_f32x4().0))) }
+    pub fn y(self) -> i32 {
+        self[1]
     }
 
-    // Packed comparisons
+    // Basic operations
 
     #[inline]
-    pub fn packed_eq(self, other: I32x4) -> U32x4 {
-        unsafe { U32x4(simd_eq(self.0, other.0)) }
+    pub fn max(self, other: I32x2) -> I32x2 {
+        unsafe { I32x2(aarch64::vmax_s32(self.0, other.0)) }
     }
 
     #[inline]
-    pub fn packed_le(self, other: I32x4) -> U32x4 {
-        unsafe { U32x4(simd_le(self.0, other.0)) }
+    pub fn min(self, other: I32x2) -> I32x2 {
+        unsafe { I32x2(aarch64::vmin_s32(self.0, other.0)) }
     }
 
+    // Horizontal operations
+
+    /// Returns the smaller of the two lanes.
     #[inline]
-    pub fn packed_lt(self, other: I32x4) -> U32x4 {
-        unsafe { U32x4(simd_lt(self.0, other.0)) }
+    pub fn min_element(self) -> i32 {
+        unsafe { aarch64::vminv_s32(self.0) }
     }
 
-    // Concatenations
-
+    /// Returns the larger of the two lanes.
     #[inline]
-    pub fn concat_xy_xy(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(simd_shuffle4(self.0, other.0, [0, 1, 4, 5])) }
+    pub fn max_element(self) -> i32 {
+        unsafe { aarch64::vmaxv_s32(self.0) }
     }
 
+    // Packed comparisons
+
     #[inline]
-    pub fn concat_zw_zw(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(simd_shuffle4(self.0, other.0, [2, 3, 6, 7])) }
+    pub fn packed_eq(self, other: I32x2) -> U32x2 {
+        unsafe { U32x2(aarch64::vceq_s32(self.0, other.0)) }
     }
 
-    // Swizzle conversions
+    #[inline]
+    pub fn packed_gt(self, other: I32x2) -> U32x2 {
+        unsafe { U32x2(aarch64::vcgt_s32(self.0, other.0)) }
+    }
 
     #[inline]
-    pub fn xy(self) -> I32x2 {
-        unsafe { I32x2(simd_shuffle2(self.0, self.0, [0, 1])) }
+    pub fn packed_le(self, other: I32x2) -> U32x2 {
+        unsafe { U32x2(aarch64::vcle_s32(self.0, other.0)) }
     }
 
     #[inline]
-    pub fn yx(self) -> I32x2 {
-        unsafe { I32x2(simd_shuffle2(self.0, self.0, [1, 0])) }
+    pub fn packed_lt(self, other: I32x2) -> U32x2 {
+        unsafe { U32x2(aarch64::vclt_s32(self.0, other.0)) }
     }
 
     #[inline]
-    pub fn xw(self) -> I32x2 {
-        unsafe { I32x2(simd_shuffle2(self.0, self.0, [0, 3])) }
+    pub fn packed_ge(self, other: I32x2) -> U32x2 {
+        unsafe { U32x2(aarch64::vcge_s32(self.0, other.0)) }
     }
 
     #[inline]
-    pub fn zy(self) -> I32x2 {
-        unsafe { I32x2(simd_shuffle2(self.0, self.0, [2, 1])) }
+    pub fn packed_ne(self, other: I32x2) -> U32x2 {
+        !self.packed_eq(other)
     }
 
+    // Concatenations
+
     #[inline]
-    pub fn zw(self) -> I32x2 {
-        unsafe { I32x2(simd_shuffle2(self.0, self.0, [2, 3])) }
+    pub fn concat_xy_xy(self, other: I32x2) -> I32x4 {
+        unsafe { I32x4(aarch64::vcombine_s32(self.0, other.0)) }
     }
 
     // Conversions
 
     /// Converts these packed integers to floats.
+    #[inline]
+    pub fn to_f32x2(self) -> F32x2 {
+        unsafe { F32x2(aarch64::vcvt_f32_s32(self.0)) }
+    }
+
+    #[inline]
+    pub fn to_u32x2(self) -> U32x2 {
+        unsafe { U32x2(aarch64::vreinterpret_u32_s32(self.0)) }
+    }
+
     #[inline]
     pub fn to_f32x4(self) -> F32x4 {
-        unsafe { F32x4(simd_cast(self.0)) }
+        self.to_i32x4().to_f32x4()
+    }
+
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        self.concat_xy_xy(I32x2::default())
     }
 }
 
-impl Default for I32x4 {
+impl Default for I32x2 {
     #[inline]
-    fn default() -> I32x4 {
-        I32x4::new(0, 0, 0, 0)
+    fn default() -> I32x2 {
+        I32x2::splat(0)
     }
 }
 
-impl Index<usize> for I32x4 {
+impl Index<usize> for I32x2 {
     type Output = i32;
     #[inline]
     fn index(&self, index: usize) -> &i32 {
-        unsafe {
-            assert!(index < 4);
-            let ptr = &self.0 as *const int32x4_t as *const i32;
-            mem::transmute::<*const i32, &i32>(ptr.offset(index as isize))
-        }
+        &cast_ref::<int32x2_t, [i32; 2]>(&self.0)[index]
     }
 }
 
-impl IndexMut<usize> for I32x4 {
+impl IndexMut<usize> for I32x2 {
     #[inline]
     fn index_mut(&mut self, index: usize) -> &mut i32 {
-        unsafe {
-            assert!(index < 4);
-            let ptr = &mut self.0 as *mut int32x4_t as *mut i32;
-            mem::transmute::<*mut i32, &mut i32>(ptr.offset(index as isize))
-        }
+        &mut cast_mut::<int32x2_t, [i32; 2]>(&mut self.0)[index]
     }
 }
 
-impl Add<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Debug for I32x2 {
     #[inline]
-    fn add(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(simd_add(self.0, other.0)) }
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}>", self[0], self[1])
     }
 }
 
-impl Sub<I32x4> for I32x4 {
-    type Output = I32x4;
+impl PartialEq for I32x2 {
     #[inline]
-    fn sub(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(simd_sub(self.0, other.0)) }
+    fn eq(&self, other: &I32x2) -> bool {
+        self.packed_eq(*other).all_true()
     }
 }
 
-impl Mul<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Add<I32x2> for I32x2 {
+    type Output = I32x2;
     #[inline]
-    fn mul(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(simd_mul(self.0, other.0)) }
+    fn add(self, other: I32x2) -> I32x2 {
+        unsafe { I32x2(aarch64::vadd_s32(self.0, other.0)) }
     }
 }
 
-impl PartialEq for I32x4 {
+impl Sub<I32x2> for I32x2 {
+    type Output = I32x2;
     #[inline]
-    fn eq(&self, other: &I32x4) -> bool {
-        self.packed_eq(*other).all_true()
+    fn sub(self, other: I32x2) -> I32x2 {
+        unsafe { I32x2(aarch64::vsub_s32(self.0, other.0)) }
     }
 }
 
-impl BitAnd<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Mul<I32x2> for I32x2 {
+    type Output = I32x2;
     #[inline]
-    fn bitand(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(simd_and(self.0, other.0)) }
+    fn mul(self, other: I32x2) -> I32x2 {
+        unsafe { I32x2(aarch64::vmul_s32(self.0, other.0)) }
     }
 }
 
-impl BitOr<I32x4> for I32x4 {
-    type Output = I32x4;
+impl BitAnd<I32x2> for I32x2 {
+    type Output = I32x2;
     #[inline]
-    fn bitor(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(simd_or(self.0, other.0)) }
+    fn bitand(self, other: I32x2) -> I32x2 {
+        unsafe { I32x2(aarch64::vand_s32(self.0, other.0)) }
     }
 }
 
-impl Shr<I32x4> for I32x4 {
-    type Output = I32x4;
+impl BitOr<I32x2> for I32x2 {
+    type Output = I32x2;
     #[inline]
-    fn shr(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(simd_shr(self.0, other.0)) }
+    fn bitor(self, other: I32x2) -> I32x2 {
+        unsafe { I32x2(aarch64::vorr_s32(self.0, other.0)) }
     }
 }
 
-// Two 32-bit unsigned integers
+impl Shl<u32> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn shl(self, amount: u32) -> I32x2 {
+        unsafe {
+            let amount = u32::min(amount, 32) as i32;
+            I32x2(aarch64::vshl_s32(self.0, aarch64::vdup_n_s32(amount)))
+        }
+    }
+}
+
+impl Shr<u32> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn shr(self, amount: u32) -> I32x2 {
+        // NEON only shifts left; negative amounts shift right, arithmetically.
+        unsafe {
+            let amount = u32::min(amount, 32) as i32;
+            I32x2(aarch64::vshl_s32(self.0, aarch64::vdup_n_s32(-amount)))
+        }
+    }
+}
+
+// Four 32-bit signed integers
 
 #[derive(Clone, Copy)]
-pub struct U32x2(pub uint32x2_t);
+#[repr(transparent)]
+pub struct I32x4(pub int32x4_t);
+
+impl I32x4 {
+    // Constructors
 
-impl U32x2 {
     #[inline]
-    pub fn new(x: u32, y: u32) -> U32x2 {
-        unsafe { U32x2(mem::transmute([x, y])) }
+    pub fn new(a: i32, b: i32, c: i32, d: i32) -> I32x4 {
+        I32x4(cast::<[i32; 4], int32x4_t>([a, b, c, d]))
     }
 
     #[inline]
-    pub fn splat(x: u32) -> U32x2 {
-        U32x2::new(x, x)
+    pub fn splat(x: i32) -> I32x4 {
+        unsafe { I32x4(aarch64::vdupq_n_s32(x)) }
     }
 
-    /// Returns true if both booleans in this vector are true.
+    /// Loads the first four elements of `slice`, which needn't be aligned.
     ///
-    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
-    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    /// Panics if `slice` has fewer than four elements.
     #[inline]
-    pub fn all_true(&self) -> bool {
-        unsafe { aarch64::vminv_u32(self.0) == !0 }
+    pub fn from_slice(slice: &[i32]) -> I32x4 {
+        let slice = &slice[..4];
+        unsafe { I32x4(aarch64::vld1q_s32(slice.as_ptr())) }
     }
 
-    /// Returns true if both booleans in this vector are false.
+    /// Loads the first four elements of `slice`, which must start on a 16-byte boundary. This can
+    /// be faster than `from_slice` on some CPUs.
     ///
-    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
-    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_ptr()` must be 16-byte aligned. This is only checked in debug builds.
     #[inline]
-    pub fn all_false(&self) -> bool {
-        unsafe { aarch64::vmaxv_u32(self.0) == 0 }
+    pub unsafe fn from_slice_aligned(slice: &[i32]) -> I32x4 {
+        let slice = &slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        I32x4::from_slice(slice)
     }
 
+    /// Stores the lanes of this vector into the first four elements of `slice`, which needn't be
+    /// aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
     #[inline]
-    pub fn to_i32x2(self) -> I32x2 {
-        unsafe { I32x2(simd_cast(self.0)) }
+    pub fn write_to_slice(self, slice: &mut [i32]) {
+        let slice = &mut slice[..4];
+        unsafe { aarch64::vst1q_s32(slice.as_mut_ptr(), self.0) }
     }
-}
 
-impl Index<usize> for U32x2 {
-    type Output = u32;
+    /// Stores the lanes of this vector into the first four elements of `slice`, which must start
+    /// on a 16-byte boundary.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_mut_ptr()` must be 16-byte aligned. This is only checked in debug builds.
     #[inline]
-    fn index(&self, index: usize) -> &u32 {
-        unsafe {
-            assert!(index < 2);
-            let ptr = &self.0 as *const uint32x2_t as *const u32;
-            mem::transmute::<*const u32, &u32>(ptr.offset(index as isize))
-        }
+    pub unsafe fn write_to_slice_aligned(self, slice: &mut [i32]) {
+        let slice = &mut slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        self.write_to_slice(slice)
     }
-}
 
-impl Not for U32x2 {
-    type Output = U32x2;
+    // Basic operations
+
     #[inline]
-    fn not(self) -> U32x2 {
-        // FIXME(pcwalton): Is there a better way to do this?
-        unsafe { U32x2(simd_xor(self.0, U32x2::splat(!0).0)) }
+    pub fn max(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(aarch64::vmaxq_s32(self.0, other.0)) }
     }
-}
 
-impl BitAnd<U32x2> for U32x2 {
-    type Output = U32x2;
+    /// Returns the smallest of the four lanes.
     #[inline]
-    fn bitand(self, other: U32x2) -> U32x2 {
-        unsafe { U32x2(simd_and(self.0, other.0)) }
+    pub fn min_element(self) -> i32 {
+        unsafe { aarch64::vminvq_s32(self.0) }
     }
-}
 
-impl BitOr<U32x2> for U32x2 {
-    type Output = U32x2;
+    /// Returns the largest of the four lanes.
     #[inline]
-    fn bitor(self, other: U32x2) -> U32x2 {
-        unsafe { U32x2(simd_or(self.0, other.0)) }
+    pub fn max_element(self) -> i32 {
+        unsafe { aarch64::vmaxvq_s32(self.0) }
     }
-}
 
+    #[inline]
+    pub fn min(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(aarch64::vminq_s32(self.0, other.0)) }
+    }
 
-// Four 32-bit unsigned integers
+    // Packed comparisons
 
-#[derive(Clone, Copy)]
-pub struct U32x4(pub uint32x4_t);
+    #[inline]
+    pub fn packed_eq(self, other: I32x4) -> U32x4 {
+        unsafe { U32x4(aarch64::vceqq_s32(self.0, other.0)) }
+    }
 
-impl U32x4 {
-    /// Returns true if all four booleans in this vector are true.
-    ///
-    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
-    /// a value with all bits set or all bits clear (i.e. !0 or 0).
     #[inline]
-    pub fn all_true(&self) -> bool {
-        unsafe { aarch64::vminvq_u32(self.0) == !0 }
+    pub fn packed_gt(self, other: I32x4) -> U32x4 {
+        unsafe { U32x4(aarch64::vcgtq_s32(self.0, other.0)) }
     }
 
-    /// Returns true if all four booleans in this vector are false.
+    #[inline]
+    pub fn packed_le(self, other: I32x4) -> U32x4 {
+        unsafe { U32x4(aarch64::vcleq_s32(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I32x4) -> U32x4 {
+        unsafe { U32x4(aarch64::vcltq_s32(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: I32x4) -> U32x4 {
+        unsafe { U32x4(aarch64::vcgeq_s32(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: I32x4) -> U32x4 {
+        !self.packed_eq(other)
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: I32x4) -> I32x4 {
+        self.xy().concat_xy_xy(other.xy())
+    }
+
+    #[inline]
+    pub fn concat_zw_zw(self, other: I32x4) -> I32x4 {
+        self.zw().concat_xy_xy(other.zw())
+    }
+
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order.
     ///
-    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
-    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    /// This covers every permutation, so it's handy when the lanes are chosen by other generic
+    /// code. `v.shuffle::<2, 3, 0, 1>()` is the same as `v.zwxy()`. Every index must be less
+    /// than 4.
     #[inline]
-    pub fn all_false(&self) -> bool {
-        unsafe { aarch64::vmaxvq_u32(self.0) == 0 }
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> I32x4 {
+        debug_assert!(A < 4 && B < 4 && C < 4 && D < 4, "shuffle lane index out of range");
+        self.shuffle_lanes([A as u8, B as u8, C as u8, D as u8])
     }
-}
 
-impl Index<usize> for U32x4 {
-    type Output = u32;
+    // Swizzle conversions
+
     #[inline]
-    fn index(&self, index: usize) -> &u32 {
-        unsafe {
-            assert!(index < 4);
-            let ptr = &self.0 as *const uint32x4_t as *const u32;
-            mem::transmute::<*const u32, &u32>(ptr.offset(index as isize))
-        }
+    pub fn xy(self) -> I32x2 {
+        unsafe { I32x2(aarch64::vget_low_s32(self.0)) }
+    }
+
+    #[inline]
+    pub fn yx(self) -> I32x2 {
+        unsafe { I32x2(aarch64::vrev64_s32(aarch64::vget_low_s32(self.0))) }
+    }
+
+    #[inline]
+    pub fn xw(self) -> I32x2 {
+        self.xwyz().xy()
     }
-}
 
-// Intrinsics
+    #[inline]
+    pub fn zy(self) -> I32x2 {
+        self.zyxw().xy()
+    }
 
-extern "platform-intrinsic" {
-    fn simd_add<T>(x: T, y: T) -> T;
-    fn simd_div<T>(x: T, y: T) -> T;
-    fn simd_mul<T>(x: T, y: T) -> T;
-    fn simd_sub<T>(x: T, y: T) -> T;
+    #[inline]
+    pub fn zw(self) -> I32x2 {
+        unsafe { I32x2(aarch64::vget_high_s32(self.0)) }
+    }
 
-    fn simd_shr<T>(x: T, y: T) -> T;
+    // Conversions
 
-    fn simd_and<T>(x: T, y: T) -> T;
-    fn simd_or<T>(x: T, y: T) -> T;
-    fn simd_xor<T>(x: T, y: T) -> T;
+    /// Converts these packed integers to floats.
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        unsafe { F32x4(aarch64::vcvtq_f32_s32(self.0)) }
+    }
 
-    fn simd_fmin<T>(x: T, y: T) -> T;
-    fn simd_fmax<T>(x: T, y: T) -> T;
+    /// Converts these packed signed integers to unsigned integers.
+    ///
+    /// Overflowing values will wrap around.
+    #[inline]
+    pub fn to_u32x4(self) -> U32x4 {
+        unsafe { U32x4(aarch64::vreinterpretq_u32_s32(self.0)) }
+    }
 
-    fn simd_eq<T, U>(x: T, y: T) -> U;
-    fn simd_gt<T, U>(x: T, y: T) -> U;
-    fn simd_le<T, U>(x: T, y: T) -> U;
-    fn simd_lt<T, U>(x: T, y: T) -> U;
+    // NEON only looks at the low byte of shift amounts, and treats it as signed. Other backends
+    // treat amounts as unsigned, so clamp them to the lane width to match.
+    #[inline]
+    fn clamp_shift_amounts(self) -> I32x4 {
+        unsafe {
+            let amounts = aarch64::vreinterpretq_u32_s32(self.0);
+            let amounts = aarch64::vminq_u32(amounts, aarch64::vdupq_n_u32(32));
+            I32x4(aarch64::vreinterpretq_s32_u32(amounts))
+        }
+    }
 
-    fn simd_shuffle2<T, U>(x: T, y: T, idx: [u32; 2]) -> U;
-    fn simd_shuffle4<T, U>(x: T, y: T, idx: [u32; 4]) -> U;
+    #[inline]
+    fn shuffle_lanes(self, lanes: [u8; 4]) -> I32x4 {
+        unsafe {
+            let bytes = shuffle_lanes(aarch64::vreinterpretq_u8_s32(self.0), lanes);
+            I32x4(aarch64::vreinterpretq_s32_u8(bytes))
+        }
+    }
+}
 
-    fn simd_cast<T, U>(x: T) -> U;
+impl Default for I32x4 {
+    #[inline]
+    fn default() -> I32x4 {
+        I32x4::splat(0)
+    }
 }
 
-extern "C" {
-    #[link_name = "llvm.fabs.v2f32"]
-    fn fabs_v2f32(a: float32x2_t) -> float32x2_t;
-    #[link_name = "llvm.floor.v2f32"]
-    fn floor_v2f32(a: float32x2_t) -> float32x2_t;
-    #[link_name = "llvm.ceil.v2f32"]
-    fn ceil_v2f32(a: float32x2_t) -> float32x2_t;
-    #[link_name = "llvm.round.v2f32"]
-    fn round_v2f32(a: float32x2_t) -> float32x2_t;
-    #[link_name = "llvm.sqrt.v2f32"]
-    fn sqrt_v2f32(a: float32x2_t) -> float32x2_t;
+impl Index<usize> for I32x4 {
+    type Output = i32;
+    #[inline]
+    fn index(&self, index: usize) -> &i32 {
+        &cast_ref::<int32x4_t, [i32; 4]>(&self.0)[index]
+    }
+}
 
-    #[link_name = "llvm.fabs.v4f32"]
-    fn fabs_v4f32(a: float32x4_t) -> float32x4_t;
-    #[link_name = "llvm.floor.v4f32"]
-    fn floor_v4f32(a: float32x4_t) -> float32x4_t;
-    #[link_name = "llvm.ceil.v4f32"]
-    fn ceil_v4f32(a: float32x4_t) -> float32x4_t;
-    #[link_name = "llvm.round.v4f32"]
-    fn round_v4f32(a: float32x4_t) -> float32x4_t;
-    #[link_name = "llvm.sqrt.v4f32"]
-    fn sqrt_v4f32(a: float32x4_t) -> float32x4_t;
+impl IndexMut<usize> for I32x4 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut i32 {
+        &mut cast_mut::<int32x4_t, [i32; 4]>(&mut self.0)[index]
+    }
+}
 
-    #[link_name = "llvm.aarch64.neon.frecpe.v2f32"]
-    fn vrecpe_v2f32(a: float32x2_t) -> float32x2_t;
+impl Debug for I32x4 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    }
+}
 
-    #[link_name = "llvm.aarch64.neon.frecpe.v4f32"]
-    fn vrecpe_v4f32(a: float32x4_t) -> float32x4_t;
+impl PartialEq for I32x4 {
+    #[inline]
+    fn eq(&self, other: &I32x4) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
+
+impl Add<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn add(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(aarch64::vaddq_s32(self.0, other.0)) }
+    }
+}
+
+impl Sub<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn sub(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(aarch64::vsubq_s32(self.0, other.0)) }
+    }
+}
+
+impl Mul<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn mul(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(aarch64::vmulq_s32(self.0, other.0)) }
+    }
+}
+
+impl BitAnd<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn bitand(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(aarch64::vandq_s32(self.0, other.0)) }
+    }
+}
+
+impl BitOr<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn bitor(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(aarch64::vorrq_s32(self.0, other.0)) }
+    }
+}
+
+impl Shl<u32> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shl(self, amount: u32) -> I32x4 {
+        unsafe {
+            let amount = u32::min(amount, 32) as i32;
+            I32x4(aarch64::vshlq_s32(self.0, aarch64::vdupq_n_s32(amount)))
+        }
+    }
+}
+
+impl Shr<u32> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shr(self, amount: u32) -> I32x4 {
+        // NEON only shifts left; negative amounts shift right, arithmetically.
+        unsafe {
+            let amount = u32::min(amount, 32) as i32;
+            I32x4(aarch64::vshlq_s32(self.0, aarch64::vdupq_n_s32(-amount)))
+        }
+    }
+}
+
+impl Shl<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shl(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(aarch64::vshlq_s32(self.0, other.clamp_shift_amounts().0)) }
+    }
+}
+
+impl Shr<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shr(self, other: I32x4) -> I32x4 {
+        // NEON only shifts left by a vector; negative amounts shift right, arithmetically.
+        let amounts = other.clamp_shift_amounts();
+        unsafe { I32x4(aarch64::vshlq_s32(self.0, aarch64::vnegq_s32(amounts.0))) }
+    }
+}
+
+// Two 32-bit unsigned integers
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct U32x2(pub uint32x2_t);
+
+impl U32x2 {
+    #[inline]
+    pub fn new(x: u32, y: u32) -> U32x2 {
+        U32x2(cast::<[u32; 2], uint32x2_t>([x, y]))
+    }
+
+    #[inline]
+    pub fn splat(x: u32) -> U32x2 {
+        unsafe { U32x2(aarch64::vdup_n_u32(x)) }
+    }
+
+    /// Returns true if both booleans in this vector are true.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        unsafe { aarch64::vminv_u32(self.0) == !0 }
+    }
+
+    /// Returns true if both booleans in this vector are false.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        unsafe { aarch64::vmaxv_u32(self.0) == 0 }
+    }
+
+    /// Returns true if either boolean in this vector is true.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn any_true(self) -> bool {
+        unsafe { aarch64::vmaxv_u32(self.0) != 0 }
+    }
+
+    #[inline]
+    pub fn to_i32x2(self) -> I32x2 {
+        unsafe { I32x2(aarch64::vreinterpret_s32_u32(self.0)) }
+    }
+}
+
+impl Debug for U32x2 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}>", self[0], self[1])
+    }
+}
+
+impl Index<usize> for U32x2 {
+    type Output = u32;
+    #[inline]
+    fn index(&self, index: usize) -> &u32 {
+        &cast_ref::<uint32x2_t, [u32; 2]>(&self.0)[index]
+    }
+}
+
+impl PartialEq for U32x2 {
+    #[inline]
+    fn eq(&self, other: &U32x2) -> bool {
+        cast::<uint32x2_t, u64>(self.0) == cast::<uint32x2_t, u64>(other.0)
+    }
+}
+
+impl Not for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn not(self) -> U32x2 {
+        unsafe { U32x2(aarch64::vmvn_u32(self.0)) }
+    }
+}
+
+impl BitAnd<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitand(self, other: U32x2) -> U32x2 {
+        unsafe { U32x2(aarch64::vand_u32(self.0, other.0)) }
+    }
+}
+
+impl BitOr<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitor(self, other: U32x2) -> U32x2 {
+        unsafe { U32x2(aarch64::vorr_u32(self.0, other.0)) }
+    }
+}
+
+impl BitXor<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitxor(self, other: U32x2) -> U32x2 {
+        unsafe { U32x2(aarch64::veor_u32(self.0, other.0)) }
+    }
+}
+
+// Four 32-bit unsigned integers
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct U32x4(pub uint32x4_t);
+
+impl U32x4 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: u32, b: u32, c: u32, d: u32) -> U32x4 {
+        U32x4(cast::<[u32; 4], uint32x4_t>([a, b, c, d]))
+    }
+
+    #[inline]
+    pub fn splat(x: u32) -> U32x4 {
+        unsafe { U32x4(aarch64::vdupq_n_u32(x)) }
+    }
+
+    // Conversions
+
+    /// Converts these packed unsigned integers to signed integers.
+    ///
+    /// Overflowing values will wrap around.
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        unsafe { I32x4(aarch64::vreinterpretq_s32_u32(self.0)) }
+    }
+
+    /// Reinterprets the bits of these integers as sixteen bytes, in memory order.
+    #[inline]
+    pub fn to_u8x16(self) -> U8x16 {
+        unsafe { U8x16(aarch64::vreinterpretq_u8_u32(self.0)) }
+    }
+
+    // Basic operations
+
+    /// Returns true if all four booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        unsafe { aarch64::vminvq_u32(self.0) == !0 }
+    }
+
+    /// Returns true if all four booleans in this vector are false.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        unsafe { aarch64::vmaxvq_u32(self.0) == 0 }
+    }
+
+    /// Returns true if any of the four booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn any_true(self) -> bool {
+        unsafe { aarch64::vmaxvq_u32(self.0) != 0 }
+    }
+
+    // Extraction
+
+    #[inline]
+    pub fn xy(self) -> U32x2 {
+        unsafe { U32x2(aarch64::vget_low_u32(self.0)) }
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U32x4) -> U32x4 {
+        unsafe { U32x4(aarch64::vceqq_u32(self.0, other.0)) }
+    }
+}
+
+impl Debug for U32x4 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    }
+}
+
+impl Index<usize> for U32x4 {
+    type Output = u32;
+    #[inline]
+    fn index(&self, index: usize) -> &u32 {
+        &cast_ref::<uint32x4_t, [u32; 4]>(&self.0)[index]
+    }
+}
+
+impl PartialEq for U32x4 {
+    #[inline]
+    fn eq(&self, other: &U32x4) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
+
+impl Not for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn not(self) -> U32x4 {
+        unsafe { U32x4(aarch64::vmvnq_u32(self.0)) }
+    }
+}
+
+impl BitAnd<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitand(self, other: U32x4) -> U32x4 {
+        unsafe { U32x4(aarch64::vandq_u32(self.0, other.0)) }
+    }
+}
+
+impl BitOr<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitor(self, other: U32x4) -> U32x4 {
+        unsafe { U32x4(aarch64::vorrq_u32(self.0, other.0)) }
+    }
+}
+
+impl BitXor<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitxor(self, other: U32x4) -> U32x4 {
+        unsafe { U32x4(aarch64::veorq_u32(self.0, other.0)) }
+    }
+}
+
+impl Shr<u32> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn shr(self, amount: u32) -> U32x4 {
+        // NEON only shifts left by a vector; negative amounts shift right, logically.
+        unsafe {
+            let amount = aarch64::vdupq_n_s32(-(amount.min(32) as i32));
+            U32x4(aarch64::vshlq_u32(self.0, amount))
+        }
+    }
+}
+
+// Sixteen 8-bit unsigned integers
+
+/// Sixteen bytes, such as the channels of four RGBA pixels. Arithmetic wraps around.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct U8x16(pub uint8x16_t);
+
+impl U8x16 {
+    // Constructors
+
+    #[inline]
+    pub fn from_array(bytes: [u8; 16]) -> U8x16 {
+        unsafe { U8x16(aarch64::vld1q_u8(bytes.as_ptr())) }
+    }
+
+    #[inline]
+    pub fn splat(x: u8) -> U8x16 {
+        unsafe { U8x16(aarch64::vdupq_n_u8(x)) }
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_array(self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        unsafe { aarch64::vst1q_u8(bytes.as_mut_ptr(), self.0) }
+        bytes
+    }
+
+    /// Reinterprets these bytes as four 32-bit integers, in memory order.
+    #[inline]
+    pub fn to_u32x4(self) -> U32x4 {
+        unsafe { U32x4(aarch64::vreinterpretq_u32_u8(self.0)) }
+    }
+
+    /// Zero-extends the first eight bytes to 16 bits, for arithmetic that would overflow a byte.
+    #[inline]
+    pub fn widen_low(self) -> U16x8 {
+        unsafe { U16x8(aarch64::vmovl_u8(aarch64::vget_low_u8(self.0))) }
+    }
+
+    /// Zero-extends the last eight bytes to 16 bits.
+    #[inline]
+    pub fn widen_high(self) -> U16x8 {
+        unsafe { U16x8(aarch64::vmovl_high_u8(self.0)) }
+    }
+
+    /// Narrows sixteen 16-bit integers, `low` followed by `high`, to bytes. Values above 255
+    /// become 255.
+    #[inline]
+    pub fn narrow(low: U16x8, high: U16x8) -> U8x16 {
+        unsafe { U8x16(aarch64::vqmovn_high_u16(aarch64::vqmovn_u16(low.0), high.0)) }
+    }
+
+    /// Narrows sixteen signed 16-bit integers, `low` followed by `high`, to bytes. Values below 0
+    /// become 0, and values above 255 become 255.
+    #[inline]
+    pub fn narrow_i16x8(low: I16x8, high: I16x8) -> U8x16 {
+        unsafe { U8x16(aarch64::vqmovun_high_s16(aarch64::vqmovun_s16(low.0), high.0)) }
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(aarch64::vminq_u8(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn max(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(aarch64::vmaxq_u8(self.0, other.0)) }
+    }
+
+    /// Adds, clamping sums above 255 to 255.
+    #[inline]
+    pub fn saturating_add(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(aarch64::vqaddq_u8(self.0, other.0)) }
+    }
+
+    /// Subtracts, clamping differences below 0 to 0.
+    #[inline]
+    pub fn saturating_sub(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(aarch64::vqsubq_u8(self.0, other.0)) }
+    }
+
+    /// Returns true if all sixteen booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all sixteen values in this vector are not booleans. A boolean
+    /// is a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        unsafe { aarch64::vminvq_u8(self.0) == !0 }
+    }
+
+    /// Returns true if all sixteen booleans in this vector are false.
+    ///
+    /// The result is *undefined* if all sixteen values in this vector are not booleans. A boolean
+    /// is a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        unsafe { aarch64::vmaxvq_u8(self.0) == 0 }
+    }
+
+    // Swizzle
+
+    /// Rearranges the bytes of this vector the way `pshufb` does: each lane of the result is the
+    /// byte of `self` at the index in the low four bits of the same lane of `indices`, or zero
+    /// if the high bit of that lane is set.
+    #[inline]
+    pub fn shuffle(self, indices: U8x16) -> U8x16 {
+        // `tbl` zeroes lanes whose indices are out of range, so keep the high bit, which makes the
+        // index at least 128 if it's set, and drop the others.
+        let indices = indices & U8x16::splat(0x8f);
+        unsafe { U8x16(aarch64::vqtbl1q_u8(self.0, indices.0)) }
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(aarch64::vceqq_u8(self.0, other.0)) }
+    }
+}
+
+impl Default for U8x16 {
+    #[inline]
+    fn default() -> U8x16 {
+        U8x16::splat(0)
+    }
+}
+
+impl Index<usize> for U8x16 {
+    type Output = u8;
+    #[inline]
+    fn index(&self, index: usize) -> &u8 {
+        &cast_ref::<uint8x16_t, [u8; 16]>(&self.0)[index]
+    }
+}
+
+impl IndexMut<usize> for U8x16 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut u8 {
+        &mut cast_mut::<uint8x16_t, [u8; 16]>(&mut self.0)[index]
+    }
+}
+
+impl Debug for U8x16 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::U8x16(self.to_array()).fmt(f)
+    }
+}
+
+impl PartialEq for U8x16 {
+    #[inline]
+    fn eq(&self, other: &U8x16) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
+
+impl Add<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn add(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(aarch64::vaddq_u8(self.0, other.0)) }
+    }
+}
+
+impl Sub<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn sub(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(aarch64::vsubq_u8(self.0, other.0)) }
+    }
+}
+
+impl BitAnd<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn bitand(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(aarch64::vandq_u8(self.0, other.0)) }
+    }
+}
+
+impl BitOr<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn bitor(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(aarch64::vorrq_u8(self.0, other.0)) }
+    }
+}
+
+// Eight 16-bit unsigned integers
+
+/// Eight 16-bit unsigned integers, for intermediate results of byte arithmetic. Arithmetic wraps
+/// around.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct U16x8(pub uint16x8_t);
+
+impl U16x8 {
+    // Constructors
+
+    #[inline]
+    pub fn from_array(values: [u16; 8]) -> U16x8 {
+        unsafe { U16x8(aarch64::vld1q_u16(values.as_ptr())) }
+    }
+
+    #[inline]
+    pub fn splat(x: u16) -> U16x8 {
+        unsafe { U16x8(aarch64::vdupq_n_u16(x)) }
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_array(self) -> [u16; 8] {
+        let mut values = [0; 8];
+        unsafe { aarch64::vst1q_u16(values.as_mut_ptr(), self.0) }
+        values
+    }
+
+    /// Reinterprets these integers as signed. Values above 32767 wrap around.
+    #[inline]
+    pub fn to_i16x8(self) -> I16x8 {
+        unsafe { I16x8(aarch64::vreinterpretq_s16_u16(self.0)) }
+    }
+
+    /// Zero-extends the first four integers to 32 bits.
+    #[inline]
+    pub fn widen_low(self) -> I32x4 {
+        unsafe {
+            let wide = aarch64::vmovl_u16(aarch64::vget_low_u16(self.0));
+            I32x4(aarch64::vreinterpretq_s32_u32(wide))
+        }
+    }
+
+    /// Zero-extends the last four integers to 32 bits.
+    #[inline]
+    pub fn widen_high(self) -> I32x4 {
+        unsafe { I32x4(aarch64::vreinterpretq_s32_u32(aarch64::vmovl_high_u16(self.0))) }
+    }
+
+    /// Narrows eight 32-bit integers, `low` followed by `high`, to 16 bits. Values below 0 become
+    /// 0, and values above 65535 become 65535.
+    #[inline]
+    pub fn narrow(low: I32x4, high: I32x4) -> U16x8 {
+        unsafe { U16x8(aarch64::vqmovun_high_s32(aarch64::vqmovun_s32(low.0), high.0)) }
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(aarch64::vminq_u16(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn max(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(aarch64::vmaxq_u16(self.0, other.0)) }
+    }
+
+    /// Adds, clamping sums above 65535 to 65535.
+    #[inline]
+    pub fn saturating_add(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(aarch64::vqaddq_u16(self.0, other.0)) }
+    }
+
+    /// Subtracts, clamping differences below 0 to 0.
+    #[inline]
+    pub fn saturating_sub(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(aarch64::vqsubq_u16(self.0, other.0)) }
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(aarch64::vceqq_u16(self.0, other.0)) }
+    }
+}
+
+impl Default for U16x8 {
+    #[inline]
+    fn default() -> U16x8 {
+        U16x8::splat(0)
+    }
+}
+
+impl Index<usize> for U16x8 {
+    type Output = u16;
+    #[inline]
+    fn index(&self, index: usize) -> &u16 {
+        &cast_ref::<uint16x8_t, [u16; 8]>(&self.0)[index]
+    }
+}
+
+impl Debug for U16x8 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::U16x8(self.to_array()).fmt(f)
+    }
+}
+
+impl PartialEq for U16x8 {
+    #[inline]
+    fn eq(&self, other: &U16x8) -> bool {
+        unsafe { aarch64::vminvq_u16(self.packed_eq(*other).0) == !0 }
+    }
+}
+
+impl Add<U16x8> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn add(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(aarch64::vaddq_u16(self.0, other.0)) }
+    }
+}
+
+impl Sub<U16x8> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn sub(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(aarch64::vsubq_u16(self.0, other.0)) }
+    }
+}
+
+impl Mul<U16x8> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn mul(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(aarch64::vmulq_u16(self.0, other.0)) }
+    }
+}
+
+impl Shl<u32> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn shl(self, amount: u32) -> U16x8 {
+        unsafe {
+            let amount = u32::min(amount, 16) as i16;
+            U16x8(aarch64::vshlq_u16(self.0, aarch64::vdupq_n_s16(amount)))
+        }
+    }
+}
+
+impl Shr<u32> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn shr(self, amount: u32) -> U16x8 {
+        unsafe { U16x8(aarch64::vshlq_u16(self.0, aarch64::vdupq_n_s16(-(amount as i16)))) }
+    }
+}
+
+// Eight 16-bit signed integers
+
+/// Eight 16-bit signed integers, for intermediate results of byte arithmetic that can go
+/// negative, such as coverage deltas. Arithmetic wraps around.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct I16x8(pub int16x8_t);
+
+impl I16x8 {
+    // Constructors
+
+    #[inline]
+    pub fn from_array(values: [i16; 8]) -> I16x8 {
+        unsafe { I16x8(aarch64::vld1q_s16(values.as_ptr())) }
+    }
+
+    #[inline]
+    pub fn splat(x: i16) -> I16x8 {
+        unsafe { I16x8(aarch64::vdupq_n_s16(x)) }
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_array(self) -> [i16; 8] {
+        let mut values = [0; 8];
+        unsafe { aarch64::vst1q_s16(values.as_mut_ptr(), self.0) }
+        values
+    }
+
+    /// Reinterprets these integers as unsigned. Negative values wrap around.
+    #[inline]
+    pub fn to_u16x8(self) -> U16x8 {
+        unsafe { U16x8(aarch64::vreinterpretq_u16_s16(self.0)) }
+    }
+
+    /// Sign-extends the first four integers to 32 bits.
+    #[inline]
+    pub fn widen_low(self) -> I32x4 {
+        unsafe { I32x4(aarch64::vmovl_s16(aarch64::vget_low_s16(self.0))) }
+    }
+
+    /// Sign-extends the last four integers to 32 bits.
+    #[inline]
+    pub fn widen_high(self) -> I32x4 {
+        unsafe { I32x4(aarch64::vmovl_high_s16(self.0)) }
+    }
+
+    /// Narrows eight 32-bit integers, `low` followed by `high`, to 16 bits. Values outside the
+    /// range of `i16` are clamped to it.
+    #[inline]
+    pub fn narrow(low: I32x4, high: I32x4) -> I16x8 {
+        unsafe { I16x8(aarch64::vqmovn_high_s32(aarch64::vqmovn_s32(low.0), high.0)) }
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(aarch64::vminq_s16(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn max(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(aarch64::vmaxq_s16(self.0, other.0)) }
+    }
+
+    /// Adds, clamping sums to the range of `i16`.
+    #[inline]
+    pub fn saturating_add(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(aarch64::vqaddq_s16(self.0, other.0)) }
+    }
+
+    /// Subtracts, clamping differences to the range of `i16`.
+    #[inline]
+    pub fn saturating_sub(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(aarch64::vqsubq_s16(self.0, other.0)) }
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(aarch64::vreinterpretq_s16_u16(aarch64::vceqq_s16(self.0, other.0))) }
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(aarch64::vreinterpretq_s16_u16(aarch64::vcgtq_s16(self.0, other.0))) }
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(aarch64::vreinterpretq_s16_u16(aarch64::vcltq_s16(self.0, other.0))) }
+    }
+}
+
+impl Default for I16x8 {
+    #[inline]
+    fn default() -> I16x8 {
+        I16x8::splat(0)
+    }
+}
+
+impl Index<usize> for I16x8 {
+    type Output = i16;
+    #[inline]
+    fn index(&self, index: usize) -> &i16 {
+        &cast_ref::<int16x8_t, [i16; 8]>(&self.0)[index]
+    }
+}
+
+impl Debug for I16x8 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::I16x8(self.to_array()).fmt(f)
+    }
+}
+
+impl PartialEq for I16x8 {
+    #[inline]
+    fn eq(&self, other: &I16x8) -> bool {
+        unsafe { aarch64::vminvq_u16(aarch64::vceqq_s16(self.0, other.0)) == !0 }
+    }
+}
+
+impl Add<I16x8> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn add(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(aarch64::vaddq_s16(self.0, other.0)) }
+    }
+}
+
+impl Sub<I16x8> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn sub(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(aarch64::vsubq_s16(self.0, other.0)) }
+    }
+}
+
+impl Mul<I16x8> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn mul(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(aarch64::vmulq_s16(self.0, other.0)) }
+    }
+}
+
+impl Shl<u32> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn shl(self, amount: u32) -> I16x8 {
+        unsafe {
+            let amount = u32::min(amount, 16) as i16;
+            I16x8(aarch64::vshlq_s16(self.0, aarch64::vdupq_n_s16(amount)))
+        }
+    }
+}
+
+impl Shr<u32> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn shr(self, amount: u32) -> I16x8 {
+        // NEON only shifts left; negative amounts shift right, arithmetically.
+        unsafe {
+            let amount = u32::min(amount, 16) as i16;
+            I16x8(aarch64::vshlq_s16(self.0, aarch64::vdupq_n_s16(-amount)))
+        }
+    }
+}
+
+// Shuffles
+
+/// Rearranges the 32-bit lanes of `vector`, taking lane `i` of the result from lane `lanes[i]`.
+///
+/// The lanes are constants at every call site, so after inlining the byte table folds away and
+/// LLVM picks the cheapest permute instruction for each swizzle.
+#[inline]
+fn shuffle_lanes(vector: uint8x16_t, lanes: [u8; 4]) -> uint8x16_t {
+    let mut table = [0; 16];
+    for (byte_index, byte) in table.iter_mut().enumerate() {
+        *byte = lanes[byte_index / 4] * 4 + (byte_index % 4) as u8;
+    }
+    unsafe { aarch64::vqtbl1q_u8(vector, aarch64::vld1q_u8(table.as_ptr())) }
 }
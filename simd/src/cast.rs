@@ -0,0 +1,99 @@
+// pathfinder/simd/src/cast.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reinterpreting casts between plain-old-data types, which the backends use to get at the lanes
+//! of their native vector types.
+//!
+//! The casts themselves are safe: sizes and alignments are checked, and those checks fold away
+//! at compile time. Soundness rests on the `Plain` impls below, which are the only place a type
+//! is vouched for.
+
+use core::mem;
+use core::ptr;
+
+/// A type with no padding and no invalid bit patterns, so that any bytes of the right size can be
+/// read as one.
+///
+/// # Safety
+///
+/// Implementors must have no padding bytes, and every bit pattern must be a valid value.
+pub(crate) unsafe trait Plain: Copy + 'static {}
+
+macro_rules! impl_plain {
+    ($($ty:ty),*) => {
+        $(unsafe impl Plain for $ty {})*
+    }
+}
+
+impl_plain!(f32, f64, i16, i32, u8, u16, u32, u64);
+impl_plain!([f32; 2], [f32; 4], [f32; 8], [i32; 2], [i32; 4], [u32; 2], [u32; 4]);
+impl_plain!([u8; 16], [u16; 8], [i16; 8], [f64; 2], [f64; 4]);
+
+#[cfg(target_arch = "x86")]
+impl_plain!(core::arch::x86::__m128,
+            core::arch::x86::__m128i,
+            core::arch::x86::__m256,
+            [core::arch::x86::__m128; 2],
+            core::arch::x86::__m128d,
+            core::arch::x86::__m256d,
+            [core::arch::x86::__m128d; 2]);
+#[cfg(target_arch = "x86_64")]
+impl_plain!(core::arch::x86_64::__m128,
+            core::arch::x86_64::__m128i,
+            core::arch::x86_64::__m256,
+            [core::arch::x86_64::__m128; 2],
+            core::arch::x86_64::__m128d,
+            core::arch::x86_64::__m256d,
+            [core::arch::x86_64::__m128d; 2]);
+
+#[cfg(target_arch = "aarch64")]
+impl_plain!(core::arch::aarch64::float32x2_t,
+            core::arch::aarch64::float32x4_t,
+            core::arch::aarch64::int32x2_t,
+            core::arch::aarch64::int32x4_t,
+            core::arch::aarch64::uint32x2_t,
+            core::arch::aarch64::uint32x4_t,
+            core::arch::aarch64::uint8x16_t,
+            core::arch::aarch64::uint16x8_t,
+            core::arch::aarch64::int16x8_t);
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+impl_plain!(core::arch::wasm32::v128);
+
+#[cfg(feature = "pf-portable-simd")]
+impl_plain!(core::simd::u32x4, core::simd::u8x16);
+
+/// Reinterprets `value` as a `B` of the same size.
+#[inline]
+pub(crate) fn cast<A, B>(value: A) -> B where A: Plain, B: Plain {
+    assert_eq!(mem::size_of::<A>(), mem::size_of::<B>());
+    // Both types are plain and the same size, and the read doesn't assume alignment.
+    unsafe { ptr::read_unaligned(&value as *const A as *const B) }
+}
+
+/// Reinterprets the start of `value` as a `B`, which must fit inside it and need no stricter
+/// alignment.
+#[inline]
+pub(crate) fn cast_ref<A, B>(value: &A) -> &B where A: Plain, B: Plain {
+    assert!(mem::size_of::<B>() <= mem::size_of::<A>());
+    assert!(mem::align_of::<B>() <= mem::align_of::<A>());
+    // Both types are plain, and the size and alignment were checked above.
+    unsafe { &*(value as *const A as *const B) }
+}
+
+/// Like `cast_ref()`, but for mutable references. Writes through the result change only the bytes
+/// it covers.
+#[inline]
+pub(crate) fn cast_mut<A, B>(value: &mut A) -> &mut B where A: Plain, B: Plain {
+    assert!(mem::size_of::<B>() <= mem::size_of::<A>());
+    assert!(mem::align_of::<B>() <= mem::align_of::<A>());
+    // As for `cast_ref()`. Since `A` is plain too, whatever is written stays valid.
+    unsafe { &mut *(value as *mut A as *mut B) }
+}
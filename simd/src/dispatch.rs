@@ -0,0 +1,134 @@
+// pathfinder/simd/src/dispatch.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Runtime CPU feature detection, for choosing among versions of a function at startup.
+//!
+//! The backend in `default` is chosen when the crate is compiled, so a binary built for baseline
+//! x86-64 only ever uses SSE2 and the SSE4.1 instructions the x86 backend assumes. Functions
+//! wrapped in `multiversion!` are instead compiled once per `SimdLevel`, and each call runs the
+//! best version the CPU supports, though only code LLVM vectorizes itself gains from that.
+//!
+//! Detection needs the standard library. Without it, the level is whatever the crate was compiled
+//! for.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+#[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+macro_rules! has_feature {
+    ($feature:tt) => (std::is_x86_feature_detected!($feature))
+}
+
+#[cfg(not(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64"))))]
+macro_rules! has_feature {
+    ($feature:tt) => (cfg!(target_feature = $feature))
+}
+
+/// The instruction set extensions a version of a function may use, from worst to best.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SimdLevel {
+    /// Whatever the crate was compiled for.
+    Baseline,
+    /// SSE4.1 and everything before it.
+    Sse41,
+    /// AVX2 and FMA, as on Haswell and later.
+    Avx2,
+}
+
+// Zero means not yet detected; otherwise this is the `SimdLevel` plus one.
+static DETECTED_SIMD_LEVEL: AtomicU8 = AtomicU8::new(0);
+
+/// Returns the best `SimdLevel` this CPU supports.
+///
+/// Detection runs on the first call; later calls are a single relaxed atomic load.
+#[inline]
+pub fn simd_level() -> SimdLevel {
+    match DETECTED_SIMD_LEVEL.load(Ordering::Relaxed) {
+        0 => {
+            let level = detect_simd_level();
+            DETECTED_SIMD_LEVEL.store(level as u8 + 1, Ordering::Relaxed);
+            level
+        }
+        1 => SimdLevel::Baseline,
+        2 => SimdLevel::Sse41,
+        _ => SimdLevel::Avx2,
+    }
+}
+
+/// Queries the CPU for the best `SimdLevel` it supports, without caching the result.
+pub fn detect_simd_level() -> SimdLevel {
+    if has_feature!("avx2") && has_feature!("fma") {
+        SimdLevel::Avx2
+    } else if has_feature!("sse4.1") {
+        SimdLevel::Sse41
+    } else {
+        SimdLevel::Baseline
+    }
+}
+
+/// Defines a function that is compiled once for each `SimdLevel` and, when called, runs the
+/// version for `dispatch::simd_level()`.
+///
+/// Only scalar code that LLVM auto-vectorizes in the body benefits, such as loops over slices of
+/// `f32`. The `pathfinder_simd` types pick their instructions with `#[cfg(target_feature)]` when
+/// the crate is compiled, so they behave the same in every version; `F32x4::mul_add()`, for
+/// instance, doesn't use FMA in the AVX2 version unless the crate was built with FMA enabled.
+///
+/// Parameters must be plain `name: Type` pairs, and the function can't be generic or take `self`.
+///
+/// ```
+/// pathfinder_simd::multiversion! {
+///     pub fn sum(values: &[f32]) -> f32 {
+///         values.iter().sum()
+///     }
+/// }
+///
+/// assert_eq!(sum(&[1.0, 2.0, 3.0]), 6.0);
+/// ```
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[macro_export]
+macro_rules! multiversion {
+    ($(#[$attr:meta])* $vis:vis fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?
+     $body:block) => {
+        $(#[$attr])*
+        $vis fn $name($($arg: $ty),*) $(-> $ret)? {
+            #[target_feature(enable = "avx2,fma")]
+            #[allow(unused_unsafe)]
+            unsafe fn avx2($($arg: $ty),*) $(-> $ret)? $body
+
+            #[target_feature(enable = "sse4.1")]
+            #[allow(unused_unsafe)]
+            unsafe fn sse41($($arg: $ty),*) $(-> $ret)? $body
+
+            #[inline(always)]
+            fn baseline($($arg: $ty),*) $(-> $ret)? $body
+
+            match $crate::dispatch::simd_level() {
+                // Safe because `simd_level()` only returns levels the CPU supports.
+                $crate::dispatch::SimdLevel::Avx2 => unsafe { avx2($($arg),*) },
+                $crate::dispatch::SimdLevel::Sse41 => unsafe { sse41($($arg),*) },
+                $crate::dispatch::SimdLevel::Baseline => baseline($($arg),*),
+            }
+        }
+    };
+}
+
+/// Defines a function that is compiled once for each `SimdLevel` and, when called, runs the
+/// version for `dispatch::simd_level()`.
+///
+/// There is nothing to detect on this architecture, so this defines the function as written.
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+#[macro_export]
+macro_rules! multiversion {
+    ($(#[$attr:meta])* $vis:vis fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?
+     $body:block) => {
+        $(#[$attr])*
+        $vis fn $name($($arg: $ty),*) $(-> $ret)? $body
+    };
+}
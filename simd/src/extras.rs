@@ -8,8 +8,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::default::{F32x2, F32x4, I32x2, I32x4};
-use std::ops::{AddAssign, MulAssign, Neg, SubAssign};
+use crate::default::{F32x2, F32x4, F32x8, F64x2, F64x4, I32x2, I32x4, U16x8, U8x16};
+use core::ops::{AddAssign, Div, DivAssign, Mul, MulAssign, Neg, SubAssign};
 
 // Two 32-bit floats
 
@@ -82,16 +82,46 @@ impl Neg for F32x2 {
     }
 }
 
-// Four 32-bit floats
+impl DivAssign for F32x2 {
+    #[inline]
+    fn div_assign(&mut self, other: F32x2) {
+        *self = *self / other
+    }
+}
 
-impl F32x4 {
-    // Constructors
+impl Mul<f32> for F32x2 {
+    type Output = F32x2;
+    #[inline]
+    fn mul(self, other: f32) -> F32x2 {
+        self * F32x2::splat(other)
+    }
+}
+
+impl Div<f32> for F32x2 {
+    type Output = F32x2;
+    #[inline]
+    fn div(self, other: f32) -> F32x2 {
+        self / F32x2::splat(other)
+    }
+}
+
+impl MulAssign<f32> for F32x2 {
+    #[inline]
+    fn mul_assign(&mut self, other: f32) {
+        *self = *self * other
+    }
+}
 
+impl DivAssign<f32> for F32x2 {
     #[inline]
-    pub fn from_slice(slice: &[f32]) -> F32x4 {
-        F32x4::new(slice[0], slice[1], slice[2], slice[3])
+    fn div_assign(&mut self, other: f32) {
+        *self = *self / other
     }
+}
 
+// Four 32-bit floats
+
+impl F32x4 {
     // Accessors
 
     #[inline]
@@ -142,6 +172,17 @@ impl F32x4 {
     pub fn approx_eq(self, other: F32x4, epsilon: f32) -> bool {
         (self - other).abs().packed_gt(F32x4::splat(epsilon)).all_false()
     }
+
+    // Interpolation
+
+    /// Linearly interpolates each lane from `self`, where `t` is 0, to `other`, where `t` is 1.
+    /// Values of `t` outside that range extrapolate.
+    ///
+    /// This is a single `mul_add()`, so it's fused on backends that fuse those.
+    #[inline]
+    pub fn lerp(self, other: F32x4, t: F32x4) -> F32x4 {
+        (other - self).mul_add(t, self)
+    }
 }
 
 impl AddAssign for F32x4 {
@@ -173,6 +214,245 @@ impl Neg for F32x4 {
     }
 }
 
+impl DivAssign for F32x4 {
+    #[inline]
+    fn div_assign(&mut self, other: F32x4) {
+        *self = *self / other
+    }
+}
+
+impl Mul<f32> for F32x4 {
+    type Output = F32x4;
+    #[inline]
+    fn mul(self, other: f32) -> F32x4 {
+        self * F32x4::splat(other)
+    }
+}
+
+impl Div<f32> for F32x4 {
+    type Output = F32x4;
+    #[inline]
+    fn div(self, other: f32) -> F32x4 {
+        self / F32x4::splat(other)
+    }
+}
+
+impl MulAssign<f32> for F32x4 {
+    #[inline]
+    fn mul_assign(&mut self, other: f32) {
+        *self = *self * other
+    }
+}
+
+impl DivAssign<f32> for F32x4 {
+    #[inline]
+    fn div_assign(&mut self, other: f32) {
+        *self = *self / other
+    }
+}
+
+// Eight 32-bit floats
+
+impl AddAssign for F32x8 {
+    #[inline]
+    fn add_assign(&mut self, other: F32x8) {
+        *self = *self + other
+    }
+}
+
+impl SubAssign for F32x8 {
+    #[inline]
+    fn sub_assign(&mut self, other: F32x8) {
+        *self = *self - other
+    }
+}
+
+impl MulAssign for F32x8 {
+    #[inline]
+    fn mul_assign(&mut self, other: F32x8) {
+        *self = *self * other
+    }
+}
+
+impl DivAssign for F32x8 {
+    #[inline]
+    fn div_assign(&mut self, other: F32x8) {
+        *self = *self / other
+    }
+}
+
+impl Neg for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn neg(self) -> F32x8 {
+        F32x8::default() - self
+    }
+}
+
+// Two 64-bit floats
+
+impl F64x2 {
+    // Accessors
+
+    #[inline]
+    pub fn x(self) -> f64 {
+        self[0]
+    }
+
+    #[inline]
+    pub fn y(self) -> f64 {
+        self[1]
+    }
+
+    // Mutators
+
+    #[inline]
+    pub fn set_x(&mut self, x: f64) {
+        self[0] = x
+    }
+
+    #[inline]
+    pub fn set_y(&mut self, y: f64) {
+        self[1] = y
+    }
+
+    // Conversions
+
+    /// Widens each lane of `vector`, which is exact.
+    #[inline]
+    pub fn from_f32x2(vector: F32x2) -> F64x2 {
+        F64x2::new(vector[0] as f64, vector[1] as f64)
+    }
+
+    /// Narrows each lane to the nearest `f32`.
+    #[inline]
+    pub fn to_f32x2(self) -> F32x2 {
+        F32x2::new(self[0] as f32, self[1] as f32)
+    }
+}
+
+impl AddAssign for F64x2 {
+    #[inline]
+    fn add_assign(&mut self, other: F64x2) {
+        *self = *self + other
+    }
+}
+
+impl SubAssign for F64x2 {
+    #[inline]
+    fn sub_assign(&mut self, other: F64x2) {
+        *self = *self - other
+    }
+}
+
+impl MulAssign for F64x2 {
+    #[inline]
+    fn mul_assign(&mut self, other: F64x2) {
+        *self = *self * other
+    }
+}
+
+impl DivAssign for F64x2 {
+    #[inline]
+    fn div_assign(&mut self, other: F64x2) {
+        *self = *self / other
+    }
+}
+
+impl Neg for F64x2 {
+    type Output = F64x2;
+    #[inline]
+    fn neg(self) -> F64x2 {
+        F64x2::default() - self
+    }
+}
+
+impl Mul<f64> for F64x2 {
+    type Output = F64x2;
+    #[inline]
+    fn mul(self, other: f64) -> F64x2 {
+        self * F64x2::splat(other)
+    }
+}
+
+impl Div<f64> for F64x2 {
+    type Output = F64x2;
+    #[inline]
+    fn div(self, other: f64) -> F64x2 {
+        self / F64x2::splat(other)
+    }
+}
+
+// Four 64-bit floats
+
+impl F64x4 {
+    // Conversions
+
+    /// Widens each lane of `vector`, which is exact.
+    #[inline]
+    pub fn from_f32x4(vector: F32x4) -> F64x4 {
+        F64x4::new(vector[0] as f64, vector[1] as f64, vector[2] as f64, vector[3] as f64)
+    }
+
+    /// Narrows each lane to the nearest `f32`.
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        F32x4::new(self[0] as f32, self[1] as f32, self[2] as f32, self[3] as f32)
+    }
+}
+
+impl AddAssign for F64x4 {
+    #[inline]
+    fn add_assign(&mut self, other: F64x4) {
+        *self = *self + other
+    }
+}
+
+impl SubAssign for F64x4 {
+    #[inline]
+    fn sub_assign(&mut self, other: F64x4) {
+        *self = *self - other
+    }
+}
+
+impl MulAssign for F64x4 {
+    #[inline]
+    fn mul_assign(&mut self, other: F64x4) {
+        *self = *self * other
+    }
+}
+
+impl DivAssign for F64x4 {
+    #[inline]
+    fn div_assign(&mut self, other: F64x4) {
+        *self = *self / other
+    }
+}
+
+impl Neg for F64x4 {
+    type Output = F64x4;
+    #[inline]
+    fn neg(self) -> F64x4 {
+        F64x4::default() - self
+    }
+}
+
+impl Mul<f64> for F64x4 {
+    type Output = F64x4;
+    #[inline]
+    fn mul(self, other: f64) -> F64x4 {
+        self * F64x4::splat(other)
+    }
+}
+
+impl Div<f64> for F64x4 {
+    type Output = F64x4;
+    #[inline]
+    fn div(self, other: f64) -> F64x4 {
+        self / F64x4::splat(other)
+    }
+}
+
 // Two 32-bit integers
 
 impl AddAssign for I32x2 {
@@ -258,3 +538,36 @@ impl Neg for I32x4 {
         I32x4::default() - self
     }
 }
+
+// Sixteen 8-bit unsigned integers
+
+impl U8x16 {
+    // Pixel math
+
+    /// Multiplies each channel by the corresponding alpha, treating both as fractions of 255, and
+    /// rounds to the nearest integer. This is the premultiplication used by software blending.
+    #[inline]
+    pub fn mul_alpha(self, alpha: U8x16) -> U8x16 {
+        let rounding = U16x8::splat(127);
+        let low = div_255(self.widen_low() * alpha.widen_low() + rounding);
+        let high = div_255(self.widen_high() * alpha.widen_high() + rounding);
+        U8x16::narrow(low, high)
+    }
+
+    /// Linearly interpolates each channel from `self`, where `t` is 0, to `other`, where `t` is
+    /// 255, rounding to the nearest integer.
+    #[inline]
+    pub fn lerp(self, other: U8x16, t: U8x16) -> U8x16 {
+        let (rounding, max) = (U16x8::splat(127), U16x8::splat(255));
+        let (t_low, t_high) = (t.widen_low(), t.widen_high());
+        let low = self.widen_low() * (max - t_low) + other.widen_low() * t_low + rounding;
+        let high = self.widen_high() * (max - t_high) + other.widen_high() * t_high + rounding;
+        U8x16::narrow(div_255(low), div_255(high))
+    }
+}
+
+/// Divides by 255, rounding down. Exact for values up to 255 × 255 + 127.
+#[inline]
+fn div_255(x: U16x8) -> U16x8 {
+    (x + U16x8::splat(1) + (x >> 8)) >> 8
+}
@@ -0,0 +1,106 @@
+// pathfinder/simd/src/float.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Scalar floating-point functions that work with or without the standard library.
+//!
+//! With the `std` feature, these forward to the inherent `f32` and `f64` methods. Without it,
+//! they're implemented by `libm`. The `f64` versions have an `_f64` suffix.
+
+macro_rules! unary_float_fns {
+    ($($name:ident => $libm_name:ident),*) => {
+        $(
+            #[cfg(feature = "std")]
+            #[inline]
+            pub fn $name(x: f32) -> f32 {
+                x.$name()
+            }
+
+            #[cfg(not(feature = "std"))]
+            #[inline]
+            pub fn $name(x: f32) -> f32 {
+                libm::$libm_name(x)
+            }
+        )*
+    }
+}
+
+unary_float_fns! {
+    abs => fabsf,
+    floor => floorf,
+    ceil => ceilf,
+    round => roundf,
+    trunc => truncf,
+    sqrt => sqrtf,
+    sin => sinf,
+    cos => cosf,
+    tan => tanf
+}
+
+/// Rounds to the nearest integer, rounding halfway cases to the even one as SIMD hardware does
+/// by default. (`round()` rounds them away from zero.)
+#[inline]
+pub fn round_ties_even(x: f32) -> f32 {
+    if abs(x - trunc(x)) == 0.5 {
+        2.0 * round(x * 0.5)
+    } else {
+        round(x)
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    y.atan2(x)
+}
+
+#[cfg(not(feature = "std"))]
+#[inline]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    libm::atan2f(y, x)
+}
+
+macro_rules! unary_f64_fns {
+    ($($name:ident => $method:ident, $libm_name:ident);*) => {
+        $(
+            #[cfg(feature = "std")]
+            #[inline]
+            pub fn $name(x: f64) -> f64 {
+                x.$method()
+            }
+
+            #[cfg(not(feature = "std"))]
+            #[inline]
+            pub fn $name(x: f64) -> f64 {
+                libm::$libm_name(x)
+            }
+        )*
+    }
+}
+
+unary_f64_fns! {
+    abs_f64 => abs, fabs;
+    floor_f64 => floor, floor;
+    ceil_f64 => ceil, ceil;
+    round_f64 => round, round;
+    trunc_f64 => trunc, trunc;
+    sqrt_f64 => sqrt, sqrt;
+    sin_f64 => sin, sin;
+    cos_f64 => cos, cos
+}
+
+/// Like `round_ties_even()`, for `f64`.
+#[inline]
+pub fn round_ties_even_f64(x: f64) -> f64 {
+    if abs_f64(x - trunc_f64(x)) == 0.5 {
+        2.0 * round_f64(x * 0.5)
+    } else {
+        round_f64(x)
+    }
+}
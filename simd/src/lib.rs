@@ -8,34 +8,76 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![cfg_attr(pf_rustc_nightly, feature(link_llvm_intrinsics, platform_intrinsics))]
-#![cfg_attr(pf_rustc_nightly, feature(simd_ffi, stdsimd))]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(feature = "pf-portable-simd", feature(portable_simd))]
 
 //! A minimal SIMD abstraction, usable outside of Pathfinder.
+//!
+//! The standard library is optional: disable the default `std` feature and enable `libm` to use
+//! this crate in `no_std` environments.
+//!
+//! On WebAssembly, the SIMD128 backend is used when the crate is built with the `simd128` target
+//! feature enabled (`-C target-feature=+simd128`); otherwise the scalar fallback is.
+//!
+//! The `pf-portable-simd` feature replaces the architecture-specific backends with one built on
+//! `core::simd`, which vectorizes wherever LLVM can, including targets with no hand-written
+//! backend such as RISC-V and s390x. It needs nightly Rust.
+//!
+//! The backend is chosen at compile time. To pick among versions of a function by what the CPU
+//! running it supports, see the `dispatch` module and the `multiversion!` macro.
+//!
+//! With the `pod` feature, every vector type implements `bytemuck::Pod`, so slices of them can be
+//! cast to bytes and uploaded to the GPU without copying.
 
-#[cfg(all(not(feature = "pf-no-simd"), pf_rustc_nightly, target_arch = "aarch64"))]
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("pathfinder_simd requires either the `std` or the `libm` feature");
+
+#[cfg(all(
+    not(any(feature = "pf-no-simd", feature = "pf-portable-simd")),
+    target_arch = "aarch64"
+))]
 pub use crate::arm as default;
+#[cfg(all(not(feature = "pf-no-simd"), feature = "pf-portable-simd"))]
+pub use crate::portable as default;
 #[cfg(any(
     feature = "pf-no-simd",
     not(any(
+        feature = "pf-portable-simd",
         target_arch = "x86",
         target_arch = "x86_64",
-        all(pf_rustc_nightly, target_arch = "aarch64")
+        target_arch = "aarch64",
+        all(target_arch = "wasm32", target_feature = "simd128")
     ))
 ))]
 pub use crate::scalar as default;
 #[cfg(all(
-    not(feature = "pf-no-simd"),
+    not(any(feature = "pf-no-simd", feature = "pf-portable-simd")),
     any(target_arch = "x86", target_arch = "x86_64")
 ))]
 pub use crate::x86 as default;
+#[cfg(all(
+    not(any(feature = "pf-no-simd", feature = "pf-portable-simd")),
+    target_arch = "wasm32",
+    target_feature = "simd128"
+))]
+pub use crate::wasm32 as default;
 
-#[cfg(all(pf_rustc_nightly, target_arch = "aarch64"))]
+#[cfg(target_arch = "aarch64")]
 pub mod arm;
+mod cast;
+pub mod dispatch;
 mod extras;
+pub mod float;
+#[cfg(feature = "pod")]
+mod pod;
+#[cfg(feature = "pf-portable-simd")]
+pub mod portable;
 pub mod scalar;
+mod trig;
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 pub mod x86;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+pub mod wasm32;
 
 #[cfg(test)]
 mod test;
@@ -0,0 +1,43 @@
+// pathfinder/simd/src/pod.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! `bytemuck::Pod` impls for the vector types of every backend that's compiled in.
+//!
+//! Every vector type is a `#[repr(transparent)]` wrapper around a native vector or an array of
+//! lanes, or, for the two-register `F32x8`s, a `#[repr(C)]` pair of `F32x4`s with no padding
+//! between them. Any bit pattern is a valid vector, and all zeroes is the zero vector.
+//!
+//! Only the scalar and x86 backends have their own `F64x2` and `F64x4`; the others reuse the
+//! scalar ones, which are covered already.
+
+macro_rules! impl_pod {
+    ($backend:ident) => {
+        impl_pod!($backend, F32x2, F32x4, F32x8, I32x2, I32x4, U32x2, U32x4, U8x16, U16x8, I16x8);
+    };
+    ($backend:ident, $($ty:ident),*) => {
+        $(
+            unsafe impl bytemuck::Zeroable for crate::$backend::$ty {}
+            unsafe impl bytemuck::Pod for crate::$backend::$ty {}
+        )*
+    };
+}
+
+impl_pod!(scalar);
+impl_pod!(scalar, F64x2, F64x4);
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl_pod!(x86);
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl_pod!(x86, F64x2, F64x4);
+#[cfg(target_arch = "aarch64")]
+impl_pod!(arm);
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+impl_pod!(wasm32);
+#[cfg(feature = "pf-portable-simd")]
+impl_pod!(portable);
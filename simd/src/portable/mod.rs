@@ -0,0 +1,2086 @@
+// pathfinder/simd/src/portable/mod.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Implementations of the vector types on `core::simd`, for any architecture LLVM can vectorize
+//! for.
+//!
+//! This needs nightly Rust and the `pf-portable-simd` feature. Results match the scalar backend
+//! lane for lane: in particular, `mul_add()` rounds twice and rounding is done in software,
+//! because `core::simd` leaves fused multiply-add and rounding to the standard library.
+
+use crate::cast::cast;
+use crate::float;
+use core::fmt::{self, Debug, Formatter};
+use core::ops::{Add, BitAnd, BitOr, BitXor, Div, Index, IndexMut, Mul, Not, Shl, Shr, Sub};
+use core::simd::prelude::*;
+use core::simd::{Swizzle, simd_swizzle};
+
+mod swizzle_f32x4;
+mod swizzle_i32x4;
+
+// Floats at least this large in magnitude have no fractional part.
+const MIN_INTEGRAL_F32: f32 = 8388608.0;
+
+// Two 32-bit floats
+
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct F32x2(pub f32x2);
+
+impl F32x2 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: f32, b: f32) -> F32x2 {
+        F32x2(f32x2::from_array([a, b]))
+    }
+
+    #[inline]
+    pub fn splat(x: f32) -> F32x2 {
+        F32x2(f32x2::splat(x))
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn approx_recip(self) -> F32x2 {
+        F32x2(self.0.recip())
+    }
+
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x2 {
+        F32x2(self.sqrt().0.recip())
+    }
+
+    #[inline]
+    pub fn min(self, other: F32x2) -> F32x2 {
+        F32x2(self.0.simd_min(other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: F32x2) -> F32x2 {
+        F32x2(self.0.simd_max(other.0))
+    }
+
+    #[inline]
+    pub fn clamp(self, min: F32x2, max: F32x2) -> F32x2 {
+        self.max(min).min(max)
+    }
+
+    #[inline]
+    pub fn abs(self) -> F32x2 {
+        F32x2(self.0.abs())
+    }
+
+    /// Returns the magnitude of each lane with the sign of the same lane of `sign`. See
+    /// `F32x4::copysign()`.
+    #[inline]
+    pub fn copysign(self, sign: F32x2) -> F32x2 {
+        F32x2(self.0.copysign(sign.0))
+    }
+
+    /// Returns the sign bits of the lanes packed into the low two bits, with the first lane's in
+    /// bit 0. See `F32x4::sign_bits()`.
+    #[inline]
+    pub fn sign_bits(self) -> u32 {
+        self.0.is_sign_negative().to_bitmask() as u32
+    }
+
+    #[inline]
+    pub fn floor(self) -> F32x2 {
+        self.to_f32x4().floor().xy()
+    }
+
+    #[inline]
+    pub fn ceil(self) -> F32x2 {
+        self.to_f32x4().ceil().xy()
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x2 {
+        self.to_f32x4().round().xy()
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x2 {
+        self - self.floor()
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F32x2 {
+        F32x2(f32x2::from_array(self.0.to_array().map(float::sqrt)))
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F32x2, b: F32x2) -> F32x2 {
+        self * a + b
+    }
+
+    // Horizontal operations
+
+    /// Returns the sum of both lanes.
+    #[inline]
+    pub fn sum(self) -> f32 {
+        self[0] + self[1]
+    }
+
+    /// Returns the smaller of the two lanes.
+    #[inline]
+    pub fn min_element(self) -> f32 {
+        self.0.reduce_min()
+    }
+
+    /// Returns the larger of the two lanes.
+    #[inline]
+    pub fn max_element(self) -> f32 {
+        self.0.reduce_max()
+    }
+
+    /// Returns the dot product of this vector and `other`.
+    #[inline]
+    pub fn dot(self, other: F32x2) -> f32 {
+        (self * other).sum()
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: F32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_eq(other.0))
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: F32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_gt(other.0))
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: F32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_lt(other.0))
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: F32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_le(other.0))
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: F32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_ge(other.0))
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: F32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_ne(other.0))
+    }
+
+    /// Returns the lanes of `a` where `mask` is true and those of `b` where it's false.
+    ///
+    /// The result is *undefined* if both values in `mask` are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn select(mask: U32x2, a: F32x2, b: F32x2) -> F32x2 {
+        F32x2(mask.to_mask().select(a.0, b.0))
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        self.concat_xy_xy(F32x2::default())
+    }
+
+    /// Converts these packed floats to integers via rounding, with halfway cases rounded to
+    /// even.
+    #[inline]
+    pub fn to_i32x2(self) -> I32x2 {
+        I32x2(self.round().0.cast())
+    }
+
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        self.to_i32x2().concat_xy_xy(I32x2::default())
+    }
+
+    // Swizzle
+
+    #[inline]
+    pub fn yx(self) -> F32x2 {
+        F32x2(self.0.reverse())
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: F32x2) -> F32x4 {
+        F32x4(simd_swizzle!(self.0, other.0, [0, 1, 2, 3]))
+    }
+}
+
+impl Debug for F32x2 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}>", self[0], self[1])
+    }
+}
+
+impl Index<usize> for F32x2 {
+    type Output = f32;
+    #[inline]
+    fn index(&self, index: usize) -> &f32 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for F32x2 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        &mut self.0[index]
+    }
+}
+
+impl Add<F32x2> for F32x2 {
+    type Output = F32x2;
+    #[inline]
+    fn add(self, other: F32x2) -> F32x2 {
+        F32x2(self.0 + other.0)
+    }
+}
+
+impl Div<F32x2> for F32x2 {
+    type Output = F32x2;
+    #[inline]
+    fn div(self, other: F32x2) -> F32x2 {
+        F32x2(self.0 / other.0)
+    }
+}
+
+impl Mul<F32x2> for F32x2 {
+    type Output = F32x2;
+    #[inline]
+    fn mul(self, other: F32x2) -> F32x2 {
+        F32x2(self.0 * other.0)
+    }
+}
+
+impl Sub<F32x2> for F32x2 {
+    type Output = F32x2;
+    #[inline]
+    fn sub(self, other: F32x2) -> F32x2 {
+        F32x2(self.0 - other.0)
+    }
+}
+
+// Four 32-bit floats
+
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct F32x4(pub f32x4);
+
+impl F32x4 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: f32, b: f32, c: f32, d: f32) -> F32x4 {
+        F32x4(f32x4::from_array([a, b, c, d]))
+    }
+
+    #[inline]
+    pub fn splat(x: f32) -> F32x4 {
+        F32x4(f32x4::splat(x))
+    }
+
+    /// Loads the first four elements of `slice`, which needn't be aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn from_slice(slice: &[f32]) -> F32x4 {
+        let slice = &slice[..4];
+        F32x4(f32x4::from_slice(slice))
+    }
+
+    /// Loads the first four elements of `slice`, which must start on a 16-byte boundary. This can
+    /// be faster than `from_slice` on some CPUs.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn from_slice_aligned(slice: &[f32]) -> F32x4 {
+        let slice = &slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        F32x4::from_slice(slice)
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which needn't be
+    /// aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn write_to_slice(self, slice: &mut [f32]) {
+        let slice = &mut slice[..4];
+        self.0.copy_to_slice(slice)
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which must start
+    /// on a 16-byte boundary.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_mut_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn write_to_slice_aligned(self, slice: &mut [f32]) {
+        let slice = &mut slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        self.write_to_slice(slice)
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn approx_recip(self) -> F32x4 {
+        F32x4(self.0.recip())
+    }
+
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x4 {
+        F32x4(self.sqrt().0.recip())
+    }
+
+    #[inline]
+    pub fn min(self, other: F32x4) -> F32x4 {
+        F32x4(self.0.simd_min(other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: F32x4) -> F32x4 {
+        F32x4(self.0.simd_max(other.0))
+    }
+
+    #[inline]
+    pub fn clamp(self, min: F32x4, max: F32x4) -> F32x4 {
+        self.max(min).min(max)
+    }
+
+    #[inline]
+    pub fn abs(self) -> F32x4 {
+        F32x4(self.0.abs())
+    }
+
+    /// Returns the magnitude of each lane with the sign of the same lane of `sign`.
+    ///
+    /// Only the sign bit of `sign` matters, so negative zero and NaNs with the sign bit set count
+    /// as negative.
+    #[inline]
+    pub fn copysign(self, sign: F32x4) -> F32x4 {
+        F32x4(self.0.copysign(sign.0))
+    }
+
+    /// Returns the sign bits of the lanes packed into the low four bits, with the first lane's in
+    /// bit 0, like `movmskps`. As with `copysign()`, negative zero and negative NaNs count.
+    #[inline]
+    pub fn sign_bits(self) -> u32 {
+        self.0.is_sign_negative().to_bitmask() as u32
+    }
+
+    #[inline]
+    pub fn floor(self) -> F32x4 {
+        let rounded = self.round();
+        F32x4::select(rounded.packed_gt(self), rounded - F32x4::splat(1.0), rounded)
+    }
+
+    #[inline]
+    pub fn ceil(self) -> F32x4 {
+        let rounded = self.round();
+        F32x4::select(rounded.packed_lt(self), rounded + F32x4::splat(1.0), rounded)
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x4 {
+        // Adding 2^23 leaves no bits for the fraction, so the add rounds it away, to even.
+        let magnitude = self.0.abs();
+        let min_integral = f32x4::splat(MIN_INTEGRAL_F32);
+        let rounded = ((magnitude + min_integral) - min_integral).copysign(self.0);
+        F32x4(magnitude.simd_lt(min_integral).select(rounded, self.0))
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x4 {
+        self - self.floor()
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F32x4 {
+        F32x4(f32x4::from_array(self.0.to_array().map(float::sqrt)))
+    }
+
+    /// Returns `self * a + b`.
+    ///
+    /// Fusing the operations isn't fast everywhere this backend runs, so this is an ordinary
+    /// multiply followed by an add, which rounds twice.
+    #[inline]
+    pub fn mul_add(self, a: F32x4, b: F32x4) -> F32x4 {
+        self * a + b
+    }
+
+    /// Returns the cross product of the first three lanes of this vector and `other`.
+    ///
+    /// The w lanes are ignored, and the w lane of the result is zero.
+    #[inline]
+    pub fn cross(self, other: F32x4) -> F32x4 {
+        let mut product = (self * other.yzxw() - self.yzxw() * other).yzxw();
+        product[3] = 0.0;
+        product
+    }
+
+    // Horizontal operations
+
+    /// Returns the sum of all four lanes.
+    #[inline]
+    pub fn sum(self) -> f32 {
+        (self[0] + self[1]) + (self[2] + self[3])
+    }
+
+    /// Returns the smallest of the four lanes.
+    #[inline]
+    pub fn min_element(self) -> f32 {
+        self.0.reduce_min()
+    }
+
+    /// Returns the largest of the four lanes.
+    #[inline]
+    pub fn max_element(self) -> f32 {
+        self.0.reduce_max()
+    }
+
+    /// Returns the dot product of this vector and `other`, using all four lanes.
+    #[inline]
+    pub fn dot(self, other: F32x4) -> f32 {
+        (self * other).sum()
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: F32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_eq(other.0))
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: F32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_gt(other.0))
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: F32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_le(other.0))
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: F32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_lt(other.0))
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: F32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_ge(other.0))
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: F32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_ne(other.0))
+    }
+
+    /// Returns the lanes of `a` where `mask` is true and those of `b` where it's false.
+    ///
+    /// The result is *undefined* if all four values in `mask` are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn select(mask: U32x4, a: F32x4, b: F32x4) -> F32x4 {
+        F32x4(mask.to_mask().select(a.0, b.0))
+    }
+
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order.
+    ///
+    /// This covers every permutation, so it's handy when the lanes are chosen by other generic
+    /// code. `v.shuffle::<2, 3, 0, 1>()` is the same as `v.zwxy()`. Every index must be less
+    /// than 4.
+    #[inline]
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> F32x4 {
+        F32x4(Lanes::<A, B, C, D>::swizzle(self.0))
+    }
+
+    // Swizzle conversions
+
+    #[inline]
+    pub fn xy(self) -> F32x2 {
+        F32x2(simd_swizzle!(self.0, [0, 1]))
+    }
+
+    #[inline]
+    pub fn yx(self) -> F32x2 {
+        F32x2(simd_swizzle!(self.0, [1, 0]))
+    }
+
+    #[inline]
+    pub fn xw(self) -> F32x2 {
+        F32x2(simd_swizzle!(self.0, [0, 3]))
+    }
+
+    #[inline]
+    pub fn zy(self) -> F32x2 {
+        F32x2(simd_swizzle!(self.0, [2, 1]))
+    }
+
+    #[inline]
+    pub fn zw(self) -> F32x2 {
+        F32x2(simd_swizzle!(self.0, [2, 3]))
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: F32x4) -> F32x4 {
+        F32x4(simd_swizzle!(self.0, other.0, [0, 1, 4, 5]))
+    }
+
+    #[inline]
+    pub fn concat_xy_zw(self, other: F32x4) -> F32x4 {
+        F32x4(simd_swizzle!(self.0, other.0, [0, 1, 6, 7]))
+    }
+
+    #[inline]
+    pub fn concat_zw_zw(self, other: F32x4) -> F32x4 {
+        F32x4(simd_swizzle!(self.0, other.0, [2, 3, 6, 7]))
+    }
+
+    #[inline]
+    pub fn concat_wz_yx(self, other: F32x4) -> F32x4 {
+        F32x4(simd_swizzle!(self.0, other.0, [3, 2, 5, 4]))
+    }
+
+    // Conversions
+
+    /// Converts these packed floats to integers via rounding. This is the same as
+    /// `to_i32x4_round()`.
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        self.to_i32x4_round()
+    }
+
+    /// Converts these packed floats to integers, rounding to the nearest integer and halfway cases
+    /// to the even one.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_round(self) -> I32x4 {
+        I32x4(self.round().0.cast())
+    }
+
+    /// Converts these packed floats to integers, rounding toward zero.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_truncate(self) -> I32x4 {
+        I32x4(self.0.cast())
+    }
+
+    /// Converts these packed floats to integers, rounding toward negative infinity.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_floor(self) -> I32x4 {
+        I32x4(self.floor().0.cast())
+    }
+}
+
+impl Index<usize> for F32x4 {
+    type Output = f32;
+    #[inline]
+    fn index(&self, index: usize) -> &f32 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for F32x4 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        &mut self.0[index]
+    }
+}
+
+impl Debug for F32x4 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    }
+}
+
+impl Add<F32x4> for F32x4 {
+    type Output = F32x4;
+    #[inline]
+    fn add(self, other: F32x4) -> F32x4 {
+        F32x4(self.0 + other.0)
+    }
+}
+
+impl Div<F32x4> for F32x4 {
+    type Output = F32x4;
+    #[inline]
+    fn div(self, other: F32x4) -> F32x4 {
+        F32x4(self.0 / other.0)
+    }
+}
+
+impl Mul<F32x4> for F32x4 {
+    type Output = F32x4;
+    #[inline]
+    fn mul(self, other: F32x4) -> F32x4 {
+        F32x4(self.0 * other.0)
+    }
+}
+
+impl Sub<F32x4> for F32x4 {
+    type Output = F32x4;
+    #[inline]
+    fn sub(self, other: F32x4) -> F32x4 {
+        F32x4(self.0 - other.0)
+    }
+}
+
+// Eight 32-bit floats
+
+/// Eight 32-bit floats, processed as wide as the target allows.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct F32x8(pub f32x8);
+
+impl F32x8 {
+    // Constructors
+
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32, g: f32, h: f32) -> F32x8 {
+        F32x8(f32x8::from_array([a, b, c, d, e, f, g, h]))
+    }
+
+    #[inline]
+    pub fn splat(x: f32) -> F32x8 {
+        F32x8(f32x8::splat(x))
+    }
+
+    #[inline]
+    pub fn from_halves(lo: F32x4, hi: F32x4) -> F32x8 {
+        F32x8(simd_swizzle!(lo.0, hi.0, [0, 1, 2, 3, 4, 5, 6, 7]))
+    }
+
+    // Accessors
+
+    /// Returns the first four lanes.
+    #[inline]
+    pub fn lo(self) -> F32x4 {
+        F32x4(simd_swizzle!(self.0, [0, 1, 2, 3]))
+    }
+
+    /// Returns the last four lanes.
+    #[inline]
+    pub fn hi(self) -> F32x4 {
+        F32x4(simd_swizzle!(self.0, [4, 5, 6, 7]))
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn approx_recip(self) -> F32x8 {
+        F32x8(self.0.recip())
+    }
+
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x8 {
+        F32x8(self.sqrt().0.recip())
+    }
+
+    #[inline]
+    pub fn min(self, other: F32x8) -> F32x8 {
+        F32x8(self.0.simd_min(other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: F32x8) -> F32x8 {
+        F32x8(self.0.simd_max(other.0))
+    }
+
+    #[inline]
+    pub fn clamp(self, min: F32x8, max: F32x8) -> F32x8 {
+        self.max(min).min(max)
+    }
+
+    #[inline]
+    pub fn abs(self) -> F32x8 {
+        F32x8(self.0.abs())
+    }
+
+    #[inline]
+    pub fn floor(self) -> F32x8 {
+        F32x8::from_halves(self.lo().floor(), self.hi().floor())
+    }
+
+    #[inline]
+    pub fn ceil(self) -> F32x8 {
+        F32x8::from_halves(self.lo().ceil(), self.hi().ceil())
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x8 {
+        F32x8::from_halves(self.lo().round(), self.hi().round())
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x8 {
+        self - self.floor()
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F32x8 {
+        F32x8(f32x8::from_array(self.0.to_array().map(float::sqrt)))
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F32x8, b: F32x8) -> F32x8 {
+        self * a + b
+    }
+}
+
+impl Index<usize> for F32x8 {
+    type Output = f32;
+    #[inline]
+    fn index(&self, index: usize) -> &f32 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for F32x8 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        &mut self.0[index]
+    }
+}
+
+impl Debug for F32x8 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f,
+               "<{}, {}, {}, {}, {}, {}, {}, {}>",
+               self[0], self[1], self[2], self[3], self[4], self[5], self[6], self[7])
+    }
+}
+
+impl Add<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn add(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 + other.0)
+    }
+}
+
+impl Div<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn div(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 / other.0)
+    }
+}
+
+impl Mul<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn mul(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 * other.0)
+    }
+}
+
+impl Sub<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn sub(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 - other.0)
+    }
+}
+
+// Two and four 64-bit floats
+//
+// There are no double-precision versions of these yet, so this backend shares the scalar ones.
+
+pub use crate::scalar::{F64x2, F64x4};
+
+// Two 32-bit signed integers
+
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct I32x2(pub i32x2);
+
+impl I32x2 {
+    // Constructors
+
+    #[inline]
+    pub fn new(x: i32, y: i32) -> I32x2 {
+        I32x2(i32x2::from_array([x, y]))
+    }
+
+    #[inline]
+    pub fn splat(x: i32) -> I32x2 {
+        I32x2(i32x2::splat(x))
+    }
+
+    // Accessors
+
+    #[inline]
+    pub fn x(self) -> i32 {
+        self[0]
+    }
+
+    #[inline]
+    pub fn y(self) -> i32 {
+        self[1]
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn max(self, other: I32x2) -> I32x2 {
+        I32x2(self.0.simd_max(other.0))
+    }
+
+    #[inline]
+    pub fn min(self, other: I32x2) -> I32x2 {
+        I32x2(self.0.simd_min(other.0))
+    }
+
+    // Horizontal operations
+
+    /// Returns the smaller of the two lanes.
+    #[inline]
+    pub fn min_element(self) -> i32 {
+        self.0.reduce_min()
+    }
+
+    /// Returns the larger of the two lanes.
+    #[inline]
+    pub fn max_element(self) -> i32 {
+        self.0.reduce_max()
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: I32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_eq(other.0))
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: I32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_gt(other.0))
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: I32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_le(other.0))
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_lt(other.0))
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: I32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_ge(other.0))
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: I32x2) -> U32x2 {
+        U32x2::from_mask(self.0.simd_ne(other.0))
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: I32x2) -> I32x4 {
+        I32x4(simd_swizzle!(self.0, other.0, [0, 1, 2, 3]))
+    }
+
+    // Conversions
+
+    /// Converts these packed integers to floats.
+    #[inline]
+    pub fn to_f32x2(self) -> F32x2 {
+        F32x2(self.0.cast())
+    }
+
+    #[inline]
+    pub fn to_u32x2(self) -> U32x2 {
+        U32x2(self.0.cast())
+    }
+
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        self.to_i32x4().to_f32x4()
+    }
+
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        self.concat_xy_xy(I32x2::default())
+    }
+}
+
+impl Index<usize> for I32x2 {
+    type Output = i32;
+    #[inline]
+    fn index(&self, index: usize) -> &i32 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for I32x2 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut i32 {
+        &mut self.0[index]
+    }
+}
+
+impl Debug for I32x2 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}>", self[0], self[1])
+    }
+}
+
+impl Add<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn add(self, other: I32x2) -> I32x2 {
+        I32x2(self.0 + other.0)
+    }
+}
+
+impl Sub<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn sub(self, other: I32x2) -> I32x2 {
+        I32x2(self.0 - other.0)
+    }
+}
+
+impl Mul<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn mul(self, other: I32x2) -> I32x2 {
+        I32x2(self.0 * other.0)
+    }
+}
+
+impl BitAnd<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn bitand(self, other: I32x2) -> I32x2 {
+        I32x2(self.0 & other.0)
+    }
+}
+
+impl BitOr<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn bitor(self, other: I32x2) -> I32x2 {
+        I32x2(self.0 | other.0)
+    }
+}
+
+impl Shl<u32> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn shl(self, amount: u32) -> I32x2 {
+        if amount >= 32 {
+            return I32x2::default();
+        }
+        I32x2(self.0 << i32x2::splat(amount as i32))
+    }
+}
+
+impl Shr<u32> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn shr(self, amount: u32) -> I32x2 {
+        I32x2(self.0 >> i32x2::splat(amount.min(31) as i32))
+    }
+}
+
+// Four 32-bit signed integers
+
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct I32x4(pub i32x4);
+
+impl I32x4 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: i32, b: i32, c: i32, d: i32) -> I32x4 {
+        I32x4(i32x4::from_array([a, b, c, d]))
+    }
+
+    #[inline]
+    pub fn splat(x: i32) -> I32x4 {
+        I32x4(i32x4::splat(x))
+    }
+
+    /// Loads the first four elements of `slice`, which needn't be aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn from_slice(slice: &[i32]) -> I32x4 {
+        let slice = &slice[..4];
+        I32x4(i32x4::from_slice(slice))
+    }
+
+    /// Loads the first four elements of `slice`, which must start on a 16-byte boundary. This can
+    /// be faster than `from_slice` on some CPUs.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn from_slice_aligned(slice: &[i32]) -> I32x4 {
+        let slice = &slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        I32x4::from_slice(slice)
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which needn't be
+    /// aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn write_to_slice(self, slice: &mut [i32]) {
+        let slice = &mut slice[..4];
+        self.0.copy_to_slice(slice)
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which must start
+    /// on a 16-byte boundary.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_mut_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn write_to_slice_aligned(self, slice: &mut [i32]) {
+        let slice = &mut slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        self.write_to_slice(slice)
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn max(self, other: I32x4) -> I32x4 {
+        I32x4(self.0.simd_max(other.0))
+    }
+
+    /// Returns the smallest of the four lanes.
+    #[inline]
+    pub fn min_element(self) -> i32 {
+        self.0.reduce_min()
+    }
+
+    /// Returns the largest of the four lanes.
+    #[inline]
+    pub fn max_element(self) -> i32 {
+        self.0.reduce_max()
+    }
+
+    #[inline]
+    pub fn min(self, other: I32x4) -> I32x4 {
+        I32x4(self.0.simd_min(other.0))
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: I32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_eq(other.0))
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: I32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_gt(other.0))
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: I32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_le(other.0))
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_lt(other.0))
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: I32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_ge(other.0))
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: I32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_ne(other.0))
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: I32x4) -> I32x4 {
+        I32x4(simd_swizzle!(self.0, other.0, [0, 1, 4, 5]))
+    }
+
+    #[inline]
+    pub fn concat_zw_zw(self, other: I32x4) -> I32x4 {
+        I32x4(simd_swizzle!(self.0, other.0, [2, 3, 6, 7]))
+    }
+
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order.
+    ///
+    /// This covers every permutation, so it's handy when the lanes are chosen by other generic
+    /// code. `v.shuffle::<2, 3, 0, 1>()` is the same as `v.zwxy()`. Every index must be less
+    /// than 4.
+    #[inline]
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> I32x4 {
+        I32x4(Lanes::<A, B, C, D>::swizzle(self.0))
+    }
+
+    // Swizzle conversions
+
+    #[inline]
+    pub fn xy(self) -> I32x2 {
+        I32x2(simd_swizzle!(self.0, [0, 1]))
+    }
+
+    #[inline]
+    pub fn yx(self) -> I32x2 {
+        I32x2(simd_swizzle!(self.0, [1, 0]))
+    }
+
+    #[inline]
+    pub fn xw(self) -> I32x2 {
+        I32x2(simd_swizzle!(self.0, [0, 3]))
+    }
+
+    #[inline]
+    pub fn zy(self) -> I32x2 {
+        I32x2(simd_swizzle!(self.0, [2, 1]))
+    }
+
+    #[inline]
+    pub fn zw(self) -> I32x2 {
+        I32x2(simd_swizzle!(self.0, [2, 3]))
+    }
+
+    // Conversions
+
+    /// Converts these packed integers to floats.
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        F32x4(self.0.cast())
+    }
+
+    /// Converts these packed signed integers to unsigned integers.
+    ///
+    /// Overflowing values will wrap around.
+    #[inline]
+    pub fn to_u32x4(self) -> U32x4 {
+        U32x4(self.0.cast())
+    }
+}
+
+impl Index<usize> for I32x4 {
+    type Output = i32;
+    #[inline]
+    fn index(&self, index: usize) -> &i32 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for I32x4 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut i32 {
+        &mut self.0[index]
+    }
+}
+
+impl Debug for I32x4 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    }
+}
+
+impl Add<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn add(self, other: I32x4) -> I32x4 {
+        I32x4(self.0 + other.0)
+    }
+}
+
+impl Sub<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn sub(self, other: I32x4) -> I32x4 {
+        I32x4(self.0 - other.0)
+    }
+}
+
+impl Mul<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn mul(self, other: I32x4) -> I32x4 {
+        I32x4(self.0 * other.0)
+    }
+}
+
+impl BitAnd<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn bitand(self, other: I32x4) -> I32x4 {
+        I32x4(self.0 & other.0)
+    }
+}
+
+impl BitOr<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn bitor(self, other: I32x4) -> I32x4 {
+        I32x4(self.0 | other.0)
+    }
+}
+
+// `core::simd` shifts wrap the amount around the lane width, where the other backends shift every
+// bit out, so amounts are clamped first.
+
+impl Shl<u32> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shl(self, amount: u32) -> I32x4 {
+        if amount >= 32 {
+            return I32x4::default();
+        }
+        I32x4(self.0 << i32x4::splat(amount as i32))
+    }
+}
+
+impl Shr<u32> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shr(self, amount: u32) -> I32x4 {
+        I32x4(self.0 >> i32x4::splat(amount.min(31) as i32))
+    }
+}
+
+impl Shl<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shl(self, other: I32x4) -> I32x4 {
+        let amounts: u32x4 = other.0.cast();
+        let shifted = self.0 << (other.0 & i32x4::splat(31));
+        I32x4(amounts.simd_lt(u32x4::splat(32)).select(shifted, i32x4::splat(0)))
+    }
+}
+
+impl Shr<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shr(self, other: I32x4) -> I32x4 {
+        let amounts: u32x4 = other.0.cast();
+        I32x4(self.0 >> amounts.simd_min(u32x4::splat(31)).cast())
+    }
+}
+
+// Two 32-bit unsigned integers
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct U32x2(pub u32x2);
+
+impl U32x2 {
+    #[inline]
+    pub fn new(x: u32, y: u32) -> U32x2 {
+        U32x2(u32x2::from_array([x, y]))
+    }
+
+    #[inline]
+    pub fn splat(x: u32) -> U32x2 {
+        U32x2(u32x2::splat(x))
+    }
+
+    /// Returns true if both booleans in this vector are true.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        self.0 == u32x2::splat(!0)
+    }
+
+    /// Returns true if both booleans in this vector are false.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        self.0 == u32x2::splat(0)
+    }
+
+    /// Returns true if either boolean in this vector is true.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn any_true(self) -> bool {
+        self.0 != u32x2::splat(0)
+    }
+
+    #[inline]
+    pub fn to_i32x2(self) -> I32x2 {
+        I32x2(self.0.cast())
+    }
+
+    #[inline]
+    fn from_mask(mask: mask32x2) -> U32x2 {
+        U32x2(mask.select(u32x2::splat(!0), u32x2::splat(0)))
+    }
+
+    // Nonzero lanes are true, as in the scalar backend.
+    #[inline]
+    fn to_mask(self) -> mask32x2 {
+        self.0.simd_ne(u32x2::splat(0))
+    }
+}
+
+impl Debug for U32x2 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}>", self[0], self[1])
+    }
+}
+
+impl Index<usize> for U32x2 {
+    type Output = u32;
+    #[inline]
+    fn index(&self, index: usize) -> &u32 {
+        &self.0[index]
+    }
+}
+
+impl Not for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn not(self) -> U32x2 {
+        U32x2(!self.0)
+    }
+}
+
+impl BitAnd<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitand(self, other: U32x2) -> U32x2 {
+        U32x2(self.0 & other.0)
+    }
+}
+
+impl BitOr<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitor(self, other: U32x2) -> U32x2 {
+        U32x2(self.0 | other.0)
+    }
+}
+
+impl BitXor<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitxor(self, other: U32x2) -> U32x2 {
+        U32x2(self.0 ^ other.0)
+    }
+}
+
+// Four 32-bit unsigned integers
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct U32x4(pub u32x4);
+
+impl U32x4 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: u32, b: u32, c: u32, d: u32) -> U32x4 {
+        U32x4(u32x4::from_array([a, b, c, d]))
+    }
+
+    #[inline]
+    pub fn splat(x: u32) -> U32x4 {
+        U32x4(u32x4::splat(x))
+    }
+
+    // Conversions
+
+    /// Converts these packed unsigned integers to signed integers.
+    ///
+    /// Overflowing values will wrap around.
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        I32x4(self.0.cast())
+    }
+
+    /// Reinterprets the bits of these integers as sixteen bytes, in memory order.
+    #[inline]
+    pub fn to_u8x16(self) -> U8x16 {
+        U8x16(cast::<u32x4, u8x16>(self.0))
+    }
+
+    // Basic operations
+
+    /// Returns true if all four booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        self.0 == u32x4::splat(!0)
+    }
+
+    /// Returns true if all four booleans in this vector are false.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        self.0 == u32x4::splat(0)
+    }
+
+    /// Returns true if any of the four booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn any_true(self) -> bool {
+        self.0 != u32x4::splat(0)
+    }
+
+    // Extraction
+
+    #[inline]
+    pub fn xy(self) -> U32x2 {
+        U32x2(simd_swizzle!(self.0, [0, 1]))
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U32x4) -> U32x4 {
+        U32x4::from_mask(self.0.simd_eq(other.0))
+    }
+
+    #[inline]
+    fn from_mask(mask: mask32x4) -> U32x4 {
+        U32x4(mask.select(u32x4::splat(!0), u32x4::splat(0)))
+    }
+
+    // Nonzero lanes are true, as in the scalar backend.
+    #[inline]
+    fn to_mask(self) -> mask32x4 {
+        self.0.simd_ne(u32x4::splat(0))
+    }
+}
+
+impl Debug for U32x4 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    }
+}
+
+impl Index<usize> for U32x4 {
+    type Output = u32;
+    #[inline]
+    fn index(&self, index: usize) -> &u32 {
+        &self.0[index]
+    }
+}
+
+impl Not for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn not(self) -> U32x4 {
+        U32x4(!self.0)
+    }
+}
+
+impl BitAnd<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitand(self, other: U32x4) -> U32x4 {
+        U32x4(self.0 & other.0)
+    }
+}
+
+impl BitOr<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitor(self, other: U32x4) -> U32x4 {
+        U32x4(self.0 | other.0)
+    }
+}
+
+impl BitXor<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitxor(self, other: U32x4) -> U32x4 {
+        U32x4(self.0 ^ other.0)
+    }
+}
+
+impl Shr<u32> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn shr(self, amount: u32) -> U32x4 {
+        if amount >= 32 {
+            return U32x4::splat(0);
+        }
+        U32x4(self.0 >> u32x4::splat(amount))
+    }
+}
+
+// Sixteen 8-bit unsigned integers
+
+/// Sixteen bytes, such as the channels of four RGBA pixels. Arithmetic wraps around.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct U8x16(pub u8x16);
+
+impl U8x16 {
+    // Constructors
+
+    #[inline]
+    pub fn from_array(bytes: [u8; 16]) -> U8x16 {
+        U8x16(u8x16::from_array(bytes))
+    }
+
+    #[inline]
+    pub fn splat(x: u8) -> U8x16 {
+        U8x16(u8x16::splat(x))
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_array(self) -> [u8; 16] {
+        self.0.to_array()
+    }
+
+    /// Reinterprets these bytes as four 32-bit integers, in memory order.
+    #[inline]
+    pub fn to_u32x4(self) -> U32x4 {
+        U32x4(cast::<u8x16, u32x4>(self.0))
+    }
+
+    /// Zero-extends the first eight bytes to 16 bits, for arithmetic that would overflow a byte.
+    #[inline]
+    pub fn widen_low(self) -> U16x8 {
+        U16x8(simd_swizzle!(self.0, [0, 1, 2, 3, 4, 5, 6, 7]).cast())
+    }
+
+    /// Zero-extends the last eight bytes to 16 bits.
+    #[inline]
+    pub fn widen_high(self) -> U16x8 {
+        U16x8(simd_swizzle!(self.0, [8, 9, 10, 11, 12, 13, 14, 15]).cast())
+    }
+
+    /// Narrows sixteen 16-bit integers, `low` followed by `high`, to bytes. Values above 255
+    /// become 255.
+    #[inline]
+    pub fn narrow(low: U16x8, high: U16x8) -> U8x16 {
+        let wide: u16x16 = simd_swizzle!(low.0, high.0, CONCAT_8_8);
+        U8x16(wide.simd_min(u16x16::splat(255)).cast())
+    }
+
+    /// Narrows sixteen signed 16-bit integers, `low` followed by `high`, to bytes. Values below 0
+    /// become 0, and values above 255 become 255.
+    #[inline]
+    pub fn narrow_i16x8(low: I16x8, high: I16x8) -> U8x16 {
+        let wide: i16x16 = simd_swizzle!(low.0, high.0, CONCAT_8_8);
+        U8x16(wide.simd_clamp(i16x16::splat(0), i16x16::splat(255)).cast())
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: U8x16) -> U8x16 {
+        U8x16(self.0.simd_min(other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: U8x16) -> U8x16 {
+        U8x16(self.0.simd_max(other.0))
+    }
+
+    /// Adds, clamping sums above 255 to 255.
+    #[inline]
+    pub fn saturating_add(self, other: U8x16) -> U8x16 {
+        U8x16(self.0.saturating_add(other.0))
+    }
+
+    /// Subtracts, clamping differences below 0 to 0.
+    #[inline]
+    pub fn saturating_sub(self, other: U8x16) -> U8x16 {
+        U8x16(self.0.saturating_sub(other.0))
+    }
+
+    /// Returns true if all sixteen booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all sixteen values in this vector are not booleans. A boolean
+    /// is a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        self.0 == u8x16::splat(!0)
+    }
+
+    /// Returns true if all sixteen booleans in this vector are false.
+    ///
+    /// The result is *undefined* if all sixteen values in this vector are not booleans. A boolean
+    /// is a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        self.0 == u8x16::splat(0)
+    }
+
+    // Swizzle
+
+    /// Rearranges the bytes of this vector the way `pshufb` does: each lane of the result is the
+    /// byte of `self` at the index in the low four bits of the same lane of `indices`, or zero
+    /// if the high bit of that lane is set.
+    #[inline]
+    pub fn shuffle(self, indices: U8x16) -> U8x16 {
+        // `swizzle_dyn()` zeroes lanes whose indices are out of range, so keep the high bit, which
+        // makes the index at least 128 if it's set, and drop the others.
+        let indices = indices & U8x16::splat(0x8f);
+        U8x16(self.0.swizzle_dyn(indices.0))
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U8x16) -> U8x16 {
+        U8x16(self.0.simd_eq(other.0).select(u8x16::splat(!0), u8x16::splat(0)))
+    }
+}
+
+impl Index<usize> for U8x16 {
+    type Output = u8;
+    #[inline]
+    fn index(&self, index: usize) -> &u8 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for U8x16 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut u8 {
+        &mut self.0[index]
+    }
+}
+
+impl Debug for U8x16 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::U8x16(self.to_array()).fmt(f)
+    }
+}
+
+impl Add<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn add(self, other: U8x16) -> U8x16 {
+        U8x16(self.0 + other.0)
+    }
+}
+
+impl Sub<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn sub(self, other: U8x16) -> U8x16 {
+        U8x16(self.0 - other.0)
+    }
+}
+
+impl BitAnd<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn bitand(self, other: U8x16) -> U8x16 {
+        U8x16(self.0 & other.0)
+    }
+}
+
+impl BitOr<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn bitor(self, other: U8x16) -> U8x16 {
+        U8x16(self.0 | other.0)
+    }
+}
+
+// Eight 16-bit unsigned integers
+
+/// Eight 16-bit unsigned integers, for intermediate results of byte arithmetic. Arithmetic wraps
+/// around.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct U16x8(pub u16x8);
+
+impl U16x8 {
+    // Constructors
+
+    #[inline]
+    pub fn from_array(values: [u16; 8]) -> U16x8 {
+        U16x8(u16x8::from_array(values))
+    }
+
+    #[inline]
+    pub fn splat(x: u16) -> U16x8 {
+        U16x8(u16x8::splat(x))
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_array(self) -> [u16; 8] {
+        self.0.to_array()
+    }
+
+    /// Reinterprets these integers as signed. Values above 32767 wrap around.
+    #[inline]
+    pub fn to_i16x8(self) -> I16x8 {
+        I16x8(self.0.cast())
+    }
+
+    /// Zero-extends the first four integers to 32 bits.
+    #[inline]
+    pub fn widen_low(self) -> I32x4 {
+        I32x4(simd_swizzle!(self.0, [0, 1, 2, 3]).cast())
+    }
+
+    /// Zero-extends the last four integers to 32 bits.
+    #[inline]
+    pub fn widen_high(self) -> I32x4 {
+        I32x4(simd_swizzle!(self.0, [4, 5, 6, 7]).cast())
+    }
+
+    /// Narrows eight 32-bit integers, `low` followed by `high`, to 16 bits. Values below 0 become
+    /// 0, and values above 65535 become 65535.
+    #[inline]
+    pub fn narrow(low: I32x4, high: I32x4) -> U16x8 {
+        let wide: i32x8 = simd_swizzle!(low.0, high.0, [0, 1, 2, 3, 4, 5, 6, 7]);
+        U16x8(wide.simd_clamp(i32x8::splat(0), i32x8::splat(u16::MAX as i32)).cast())
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: U16x8) -> U16x8 {
+        U16x8(self.0.simd_min(other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: U16x8) -> U16x8 {
+        U16x8(self.0.simd_max(other.0))
+    }
+
+    /// Adds, clamping sums above 65535 to 65535.
+    #[inline]
+    pub fn saturating_add(self, other: U16x8) -> U16x8 {
+        U16x8(self.0.saturating_add(other.0))
+    }
+
+    /// Subtracts, clamping differences below 0 to 0.
+    #[inline]
+    pub fn saturating_sub(self, other: U16x8) -> U16x8 {
+        U16x8(self.0.saturating_sub(other.0))
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U16x8) -> U16x8 {
+        U16x8(self.0.simd_eq(other.0).select(u16x8::splat(!0), u16x8::splat(0)))
+    }
+}
+
+impl Index<usize> for U16x8 {
+    type Output = u16;
+    #[inline]
+    fn index(&self, index: usize) -> &u16 {
+        &self.0[index]
+    }
+}
+
+impl Debug for U16x8 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::U16x8(self.to_array()).fmt(f)
+    }
+}
+
+impl Add<U16x8> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn add(self, other: U16x8) -> U16x8 {
+        U16x8(self.0 + other.0)
+    }
+}
+
+impl Sub<U16x8> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn sub(self, other: U16x8) -> U16x8 {
+        U16x8(self.0 - other.0)
+    }
+}
+
+impl Mul<U16x8> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn mul(self, other: U16x8) -> U16x8 {
+        U16x8(self.0 * other.0)
+    }
+}
+
+impl Shl<u32> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn shl(self, amount: u32) -> U16x8 {
+        if amount >= 16 {
+            return U16x8::default();
+        }
+        U16x8(self.0 << u16x8::splat(amount as u16))
+    }
+}
+
+impl Shr<u32> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn shr(self, amount: u32) -> U16x8 {
+        if amount >= 16 {
+            return U16x8::default();
+        }
+        U16x8(self.0 >> u16x8::splat(amount as u16))
+    }
+}
+
+// Eight 16-bit signed integers
+
+/// Eight 16-bit signed integers, for intermediate results of byte arithmetic that can go
+/// negative, such as coverage deltas. Arithmetic wraps around.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct I16x8(pub i16x8);
+
+impl I16x8 {
+    // Constructors
+
+    #[inline]
+    pub fn from_array(values: [i16; 8]) -> I16x8 {
+        I16x8(i16x8::from_array(values))
+    }
+
+    #[inline]
+    pub fn splat(x: i16) -> I16x8 {
+        I16x8(i16x8::splat(x))
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_array(self) -> [i16; 8] {
+        self.0.to_array()
+    }
+
+    /// Reinterprets these integers as unsigned. Negative values wrap around.
+    #[inline]
+    pub fn to_u16x8(self) -> U16x8 {
+        U16x8(self.0.cast())
+    }
+
+    /// Sign-extends the first four integers to 32 bits.
+    #[inline]
+    pub fn widen_low(self) -> I32x4 {
+        I32x4(simd_swizzle!(self.0, [0, 1, 2, 3]).cast())
+    }
+
+    /// Sign-extends the last four integers to 32 bits.
+    #[inline]
+    pub fn widen_high(self) -> I32x4 {
+        I32x4(simd_swizzle!(self.0, [4, 5, 6, 7]).cast())
+    }
+
+    /// Narrows eight 32-bit integers, `low` followed by `high`, to 16 bits. Values outside the
+    /// range of `i16` are clamped to it.
+    #[inline]
+    pub fn narrow(low: I32x4, high: I32x4) -> I16x8 {
+        let wide: i32x8 = simd_swizzle!(low.0, high.0, [0, 1, 2, 3, 4, 5, 6, 7]);
+        let (min, max) = (i32x8::splat(i16::MIN as i32), i32x8::splat(i16::MAX as i32));
+        I16x8(wide.simd_clamp(min, max).cast())
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: I16x8) -> I16x8 {
+        I16x8(self.0.simd_min(other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: I16x8) -> I16x8 {
+        I16x8(self.0.simd_max(other.0))
+    }
+
+    /// Adds, clamping sums to the range of `i16`.
+    #[inline]
+    pub fn saturating_add(self, other: I16x8) -> I16x8 {
+        I16x8(self.0.saturating_add(other.0))
+    }
+
+    /// Subtracts, clamping differences to the range of `i16`.
+    #[inline]
+    pub fn saturating_sub(self, other: I16x8) -> I16x8 {
+        I16x8(self.0.saturating_sub(other.0))
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: I16x8) -> I16x8 {
+        I16x8(self.0.simd_eq(other.0).select(i16x8::splat(!0), i16x8::splat(0)))
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: I16x8) -> I16x8 {
+        I16x8(self.0.simd_gt(other.0).select(i16x8::splat(!0), i16x8::splat(0)))
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I16x8) -> I16x8 {
+        other.packed_gt(self)
+    }
+}
+
+impl Index<usize> for I16x8 {
+    type Output = i16;
+    #[inline]
+    fn index(&self, index: usize) -> &i16 {
+        &self.0[index]
+    }
+}
+
+impl Debug for I16x8 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::I16x8(self.to_array()).fmt(f)
+    }
+}
+
+impl Add<I16x8> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn add(self, other: I16x8) -> I16x8 {
+        I16x8(self.0 + other.0)
+    }
+}
+
+impl Sub<I16x8> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn sub(self, other: I16x8) -> I16x8 {
+        I16x8(self.0 - other.0)
+    }
+}
+
+impl Mul<I16x8> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn mul(self, other: I16x8) -> I16x8 {
+        I16x8(self.0 * other.0)
+    }
+}
+
+impl Shl<u32> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn shl(self, amount: u32) -> I16x8 {
+        if amount >= 16 {
+            return I16x8::default();
+        }
+        I16x8(self.0 << i16x8::splat(amount as i16))
+    }
+}
+
+impl Shr<u32> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn shr(self, amount: u32) -> I16x8 {
+        I16x8(self.0 >> i16x8::splat(amount.min(15) as i16))
+    }
+}
+
+// Shuffles
+
+// The lanes of `low` followed by those of `high`, for `simd_swizzle!()`.
+const CONCAT_8_8: [usize; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+/// Takes lane `i` of the result from lane `[A, B, C, D][i]`, for shuffles whose lanes are const
+/// generic parameters, which `simd_swizzle!()` can't take.
+struct Lanes<const A: usize, const B: usize, const C: usize, const D: usize>;
+
+impl<const A: usize, const B: usize, const C: usize, const D: usize> Swizzle<4>
+        for Lanes<A, B, C, D> {
+    const INDEX: [usize; 4] = [A, B, C, D];
+}
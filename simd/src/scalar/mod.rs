@@ -8,9 +8,12 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::f32;
-use std::fmt::{self, Debug, Formatter};
-use std::ops::{Add, BitAnd, BitOr, Div, Index, IndexMut, Mul, Shr, Sub};
+use crate::float;
+use core::f32;
+use core::fmt::{self, Debug, Formatter};
+use core::ops::{Add, BitAnd, BitOr, BitXor, Div, Index, IndexMut, Mul, Not, Shl, Shr, Sub};
+
+const SIGN_BIT: u32 = 0x8000_0000;
 
 mod swizzle_f32x4;
 mod swizzle_i32x4;
@@ -18,6 +21,7 @@ mod swizzle_i32x4;
 // Two 32-bit floats
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(transparent)]
 pub struct F32x2(pub [f32; 2]);
 
 impl F32x2 {
@@ -40,6 +44,12 @@ impl F32x2 {
         F32x2([1.0 / self[0], 1.0 / self[1]])
     }
 
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x2 {
+        F32x2([1.0 / float::sqrt(self[0]), 1.0 / float::sqrt(self[1])])
+    }
+
     #[inline]
     pub fn min(self, other: F32x2) -> F32x2 {
         F32x2([f32::min(self[0], other[0]), f32::min(self[1], other[1])])
@@ -57,22 +67,81 @@ impl F32x2 {
 
     #[inline]
     pub fn abs(self) -> F32x2 {
-        F32x2([self[0].abs(), self[1].abs()])
+        F32x2([float::abs(self[0]), float::abs(self[1])])
+    }
+
+    /// Returns the magnitude of each lane with the sign of the same lane of `sign`. See
+    /// `F32x4::copysign()`.
+    #[inline]
+    pub fn copysign(self, sign: F32x2) -> F32x2 {
+        self.to_f32x4().copysign(sign.to_f32x4()).xy()
+    }
+
+    /// Returns the sign bits of the lanes packed into the low two bits, with the first lane's in
+    /// bit 0. See `F32x4::sign_bits()`.
+    #[inline]
+    pub fn sign_bits(self) -> u32 {
+        self.to_f32x4().sign_bits()
     }
 
     #[inline]
     pub fn floor(self) -> F32x2 {
-        F32x2([self[0].floor(), self[1].floor()])
+        F32x2([float::floor(self[0]), float::floor(self[1])])
     }
 
     #[inline]
     pub fn ceil(self) -> F32x2 {
-        F32x2([self[0].ceil(), self[1].ceil()])
+        F32x2([float::ceil(self[0]), float::ceil(self[1])])
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x2 {
+        F32x2([float::round_ties_even(self[0]), float::round_ties_even(self[1])])
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x2 {
+        self - self.floor()
     }
 
     #[inline]
     pub fn sqrt(self) -> F32x2 {
-        F32x2([self[0].sqrt(), self[1].sqrt()])
+        F32x2([float::sqrt(self[0]), float::sqrt(self[1])])
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F32x2, b: F32x2) -> F32x2 {
+        self * a + b
+    }
+
+    // Horizontal operations
+
+    /// Returns the sum of both lanes.
+    #[inline]
+    pub fn sum(self) -> f32 {
+        self[0] + self[1]
+    }
+
+    /// Returns the smaller of the two lanes.
+    #[inline]
+    pub fn min_element(self) -> f32 {
+        f32::min(self[0], self[1])
+    }
+
+    /// Returns the larger of the two lanes.
+    #[inline]
+    pub fn max_element(self) -> f32 {
+        f32::max(self[0], self[1])
+    }
+
+    /// Returns the dot product of this vector and `other`.
+    #[inline]
+    pub fn dot(self, other: F32x2) -> f32 {
+        (self * other).sum()
     }
 
     // Packed comparisons
@@ -109,6 +178,34 @@ impl F32x2 {
         ])
     }
 
+    #[inline]
+    pub fn packed_ge(self, other: F32x2) -> U32x2 {
+        U32x2([
+            if self[0] >= other[0] { !0 } else { 0 },
+            if self[1] >= other[1] { !0 } else { 0 },
+        ])
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: F32x2) -> U32x2 {
+        U32x2([
+            if self[0] != other[0] { !0 } else { 0 },
+            if self[1] != other[1] { !0 } else { 0 },
+        ])
+    }
+
+    /// Returns the lanes of `a` where `mask` is true and those of `b` where it's false.
+    ///
+    /// The result is *undefined* if both values in `mask` are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn select(mask: U32x2, a: F32x2, b: F32x2) -> F32x2 {
+        F32x2([
+            if mask[0] != 0 { a[0] } else { b[0] },
+            if mask[1] != 0 { a[1] } else { b[1] },
+        ])
+    }
+
     // Conversions
 
     #[inline]
@@ -116,16 +213,19 @@ impl F32x2 {
         F32x4([self[0] as f32, self[1] as f32, 0.0, 0.0])
     }
 
-    /// Converts these packed floats to integers via rounding.
+    /// Converts these packed floats to integers via rounding, with halfway cases rounded to
+    /// even.
     #[inline]
     pub fn to_i32x2(self) -> I32x2 {
-        I32x2([self[0].round() as i32, self[1].round() as i32])
+        I32x2([float::round_ties_even(self[0]) as i32, float::round_ties_even(self[1]) as i32])
     }
 
-    /// Converts these packed floats to integers via rounding.
+    /// Converts these packed floats to integers via rounding, with halfway cases rounded to
+    /// even.
     #[inline]
     pub fn to_i32x4(self) -> I32x4 {
-        I32x4([self[0].round() as i32, self[1].round() as i32, 0, 0])
+        let [x, y] = self.to_i32x2().0;
+        I32x4([x, y, 0, 0])
     }
 
     // Swizzle
@@ -193,6 +293,7 @@ impl Sub<F32x2> for F32x2 {
 // Four 32-bit floats
 
 #[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
 pub struct F32x4(pub [f32; 4]);
 
 impl F32x4 {
@@ -206,6 +307,55 @@ impl F32x4 {
         F32x4([x; 4])
     }
 
+    /// Loads the first four elements of `slice`, which needn't be aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn from_slice(slice: &[f32]) -> F32x4 {
+        let slice = &slice[..4];
+        F32x4([slice[0], slice[1], slice[2], slice[3]])
+    }
+
+    /// Loads the first four elements of `slice`, which must start on a 16-byte boundary. This can
+    /// be faster than `from_slice` on some CPUs.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn from_slice_aligned(slice: &[f32]) -> F32x4 {
+        let slice = &slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        F32x4::from_slice(slice)
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which needn't be
+    /// aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn write_to_slice(self, slice: &mut [f32]) {
+        let slice = &mut slice[..4];
+        slice.copy_from_slice(&self.0)
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which must start
+    /// on a 16-byte boundary.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_mut_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn write_to_slice_aligned(self, slice: &mut [f32]) {
+        let slice = &mut slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        self.write_to_slice(slice)
+    }
+
     // Basic operations
 
     #[inline]
@@ -213,6 +363,17 @@ impl F32x4 {
         F32x4([1.0 / self[0], 1.0 / self[1], 1.0 / self[2], 1.0 / self[3]])
     }
 
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x4 {
+        F32x4([
+            1.0 / float::sqrt(self[0]),
+            1.0 / float::sqrt(self[1]),
+            1.0 / float::sqrt(self[2]),
+            1.0 / float::sqrt(self[3]),
+        ])
+    }
+
     #[inline]
     pub fn min(self, other: F32x4) -> F32x4 {
         F32x4([
@@ -240,39 +401,132 @@ impl F32x4 {
 
     #[inline]
     pub fn abs(self) -> F32x4 {
-        F32x4([self[0].abs(), self[1].abs(), self[2].abs(), self[3].abs()])
+        F32x4([float::abs(self[0]), float::abs(self[1]), float::abs(self[2]), float::abs(self[3])])
+    }
+
+    /// Returns the magnitude of each lane with the sign of the same lane of `sign`.
+    ///
+    /// Only the sign bit of `sign` matters, so negative zero and NaNs with the sign bit set count
+    /// as negative.
+    #[inline]
+    pub fn copysign(self, sign: F32x4) -> F32x4 {
+        let copysign = |x: f32, sign: f32| {
+            f32::from_bits((x.to_bits() & !SIGN_BIT) | (sign.to_bits() & SIGN_BIT))
+        };
+        F32x4([
+            copysign(self[0], sign[0]),
+            copysign(self[1], sign[1]),
+            copysign(self[2], sign[2]),
+            copysign(self[3], sign[3]),
+        ])
+    }
+
+    /// Returns the sign bits of the lanes packed into the low four bits, with the first lane's in
+    /// bit 0, like `movmskps`. As with `copysign()`, negative zero and negative NaNs count.
+    #[inline]
+    pub fn sign_bits(self) -> u32 {
+        (self[0].is_sign_negative() as u32) |
+            (self[1].is_sign_negative() as u32) << 1 |
+            (self[2].is_sign_negative() as u32) << 2 |
+            (self[3].is_sign_negative() as u32) << 3
     }
 
     #[inline]
     pub fn floor(self) -> F32x4 {
         F32x4([
-            self[0].floor(),
-            self[1].floor(),
-            self[2].floor(),
-            self[3].floor(),
+            float::floor(self[0]),
+            float::floor(self[1]),
+            float::floor(self[2]),
+            float::floor(self[3]),
         ])
     }
 
     #[inline]
     pub fn ceil(self) -> F32x4 {
         F32x4([
-            self[0].ceil(),
-            self[1].ceil(),
-            self[2].ceil(),
-            self[3].ceil(),
+            float::ceil(self[0]),
+            float::ceil(self[1]),
+            float::ceil(self[2]),
+            float::ceil(self[3]),
+        ])
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x4 {
+        F32x4([
+            float::round_ties_even(self[0]),
+            float::round_ties_even(self[1]),
+            float::round_ties_even(self[2]),
+            float::round_ties_even(self[3]),
         ])
     }
 
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x4 {
+        self - self.floor()
+    }
+
     #[inline]
     pub fn sqrt(self) -> F32x4 {
         F32x4([
-            self[0].sqrt(),
-            self[1].sqrt(),
-            self[2].sqrt(),
-            self[3].sqrt(),
+            float::sqrt(self[0]),
+            float::sqrt(self[1]),
+            float::sqrt(self[2]),
+            float::sqrt(self[3]),
+        ])
+    }
+
+    /// Returns `self * a + b`.
+    ///
+    /// Fusing the operations in software would be slow, so this is an ordinary multiply followed
+    /// by an add, which rounds twice.
+    #[inline]
+    pub fn mul_add(self, a: F32x4, b: F32x4) -> F32x4 {
+        self * a + b
+    }
+
+    /// Returns the cross product of the first three lanes of this vector and `other`.
+    ///
+    /// The w lanes are ignored, and the w lane of the result is zero.
+    #[inline]
+    pub fn cross(self, other: F32x4) -> F32x4 {
+        F32x4([
+            self[1] * other[2] - self[2] * other[1],
+            self[2] * other[0] - self[0] * other[2],
+            self[0] * other[1] - self[1] * other[0],
+            0.0,
         ])
     }
 
+    // Horizontal operations
+
+    /// Returns the sum of all four lanes.
+    #[inline]
+    pub fn sum(self) -> f32 {
+        (self[0] + self[1]) + (self[2] + self[3])
+    }
+
+    /// Returns the smallest of the four lanes.
+    #[inline]
+    pub fn min_element(self) -> f32 {
+        f32::min(f32::min(self[0], self[1]), f32::min(self[2], self[3]))
+    }
+
+    /// Returns the largest of the four lanes.
+    #[inline]
+    pub fn max_element(self) -> f32 {
+        f32::max(f32::max(self[0], self[1]), f32::max(self[2], self[3]))
+    }
+
+    /// Returns the dot product of this vector and `other`, using all four lanes.
+    #[inline]
+    pub fn dot(self, other: F32x4) -> f32 {
+        (self * other).sum()
+    }
+
     // Packed comparisons
 
     #[inline]
@@ -315,17 +569,96 @@ impl F32x4 {
         ])
     }
 
-    /// Converts these packed floats to integers via rounding.
+    #[inline]
+    pub fn packed_ge(self, other: F32x4) -> U32x4 {
+        U32x4([
+            if self[0] >= other[0] { !0 } else { 0 },
+            if self[1] >= other[1] { !0 } else { 0 },
+            if self[2] >= other[2] { !0 } else { 0 },
+            if self[3] >= other[3] { !0 } else { 0 },
+        ])
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: F32x4) -> U32x4 {
+        U32x4([
+            if self[0] != other[0] { !0 } else { 0 },
+            if self[1] != other[1] { !0 } else { 0 },
+            if self[2] != other[2] { !0 } else { 0 },
+            if self[3] != other[3] { !0 } else { 0 },
+        ])
+    }
+
+    /// Returns the lanes of `a` where `mask` is true and those of `b` where it's false.
+    ///
+    /// The result is *undefined* if all four values in `mask` are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn select(mask: U32x4, a: F32x4, b: F32x4) -> F32x4 {
+        F32x4([
+            if mask[0] != 0 { a[0] } else { b[0] },
+            if mask[1] != 0 { a[1] } else { b[1] },
+            if mask[2] != 0 { a[2] } else { b[2] },
+            if mask[3] != 0 { a[3] } else { b[3] },
+        ])
+    }
+
+    /// Converts these packed floats to integers via rounding. This is the same as
+    /// `to_i32x4_round()`.
     #[inline]
     pub fn to_i32x4(self) -> I32x4 {
+        self.to_i32x4_round()
+    }
+
+    /// Converts these packed floats to integers, rounding to the nearest integer and halfway cases
+    /// to the even one.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_round(self) -> I32x4 {
+        I32x4([
+            float::round_ties_even(self[0]) as i32,
+            float::round_ties_even(self[1]) as i32,
+            float::round_ties_even(self[2]) as i32,
+            float::round_ties_even(self[3]) as i32,
+        ])
+    }
+
+    /// Converts these packed floats to integers, rounding toward zero.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_truncate(self) -> I32x4 {
+        I32x4([self[0] as i32, self[1] as i32, self[2] as i32, self[3] as i32])
+    }
+
+    /// Converts these packed floats to integers, rounding toward negative infinity.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_floor(self) -> I32x4 {
         I32x4([
-            self[0].round() as i32,
-            self[1].round() as i32,
-            self[2].round() as i32,
-            self[3].round() as i32,
+            float::floor(self[0]) as i32,
+            float::floor(self[1]) as i32,
+            float::floor(self[2]) as i32,
+            float::floor(self[3]) as i32,
         ])
     }
 
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order.
+    ///
+    /// This covers every permutation, so it's handy when the lanes are chosen by other generic
+    /// code. `v.shuffle::<2, 3, 0, 1>()` is the same as `v.zwxy()`. Every index must be less
+    /// than 4.
+    #[inline]
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> F32x4 {
+        debug_assert!(A < 4 && B < 4 && C < 4 && D < 4, "shuffle lane index out of range");
+        F32x4([self[A], self[B], self[C], self[D]])
+    }
+
     // Swizzle conversions
 
     #[inline]
@@ -450,461 +783,1830 @@ impl Sub<F32x4> for F32x4 {
     }
 }
 
-// Two 32-bit signed integers
+// Eight 32-bit floats
 
-#[derive(Clone, Copy, Default, Debug, PartialEq)]
-pub struct I32x2([i32; 2]);
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct F32x8(pub [f32; 8]);
 
-impl I32x2 {
+impl F32x8 {
     #[inline]
-    pub fn new(x: i32, y: i32) -> I32x2 {
-        I32x2([x, y])
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32, g: f32, h: f32) -> F32x8 {
+        F32x8([a, b, c, d, e, f, g, h])
     }
 
     #[inline]
-    pub fn splat(x: i32) -> I32x2 {
-        I32x2([x, x])
+    pub fn splat(x: f32) -> F32x8 {
+        F32x8([x; 8])
+    }
+
+    #[inline]
+    pub fn from_halves(lo: F32x4, hi: F32x4) -> F32x8 {
+        F32x8([lo[0], lo[1], lo[2], lo[3], hi[0], hi[1], hi[2], hi[3]])
     }
 
     // Accessors
 
+    /// Returns the first four lanes.
     #[inline]
-    pub fn x(self) -> i32 {
-        self[0]
+    pub fn lo(self) -> F32x4 {
+        F32x4([self[0], self[1], self[2], self[3]])
     }
 
+    /// Returns the last four lanes.
     #[inline]
-    pub fn y(self) -> i32 {
-        self[1]
+    pub fn hi(self) -> F32x4 {
+        F32x4([self[4], self[5], self[6], self[7]])
     }
 
+    // Basic operations
+
     #[inline]
-    pub fn concat_xy_xy(self, other: I32x2) -> I32x4 {
-        I32x4([self[0], self[1], other[0], other[1]])
+    pub fn approx_recip(self) -> F32x8 {
+        self.map(|x| 1.0 / x)
     }
 
+    /// Estimates the reciprocal square root of each lane.
     #[inline]
-    pub fn min(self, other: I32x2) -> I32x2 {
-        I32x2([
-            self[0].min(other[0]),
-            self[1].min(other[1]),
-        ])
+    pub fn approx_rsqrt(self) -> F32x8 {
+        self.map(|x| 1.0 / float::sqrt(x))
     }
 
     #[inline]
-    pub fn max(self, other: I32x2) -> I32x2 {
-        I32x2([
-            self[0].max(other[0]),
-            self[1].max(other[1]),
-        ])
+    pub fn min(self, other: F32x8) -> F32x8 {
+        self.zip(other, f32::min)
     }
 
-    // Packed comparisons
+    #[inline]
+    pub fn max(self, other: F32x8) -> F32x8 {
+        self.zip(other, f32::max)
+    }
 
     #[inline]
-    pub fn packed_eq(self, other: I32x2) -> U32x2 {
-        U32x2([
-            if self[0] == other[0] { !0 } else { 0 },
-            if self[1] == other[1] { !0 } else { 0 },
-        ])
+    pub fn clamp(self, min: F32x8, max: F32x8) -> F32x8 {
+        self.max(min).min(max)
     }
 
     #[inline]
-    pub fn packed_gt(self, other: I32x2) -> U32x2 {
-        U32x2([
-            if self[0] > other[0] { !0 } else { 0 },
-            if self[1] > other[1] { !0 } else { 0 },
-        ])
+    pub fn abs(self) -> F32x8 {
+        self.map(float::abs)
     }
 
     #[inline]
-    pub fn packed_le(self, other: I32x2) -> U32x2 {
-        U32x2([
-            if self[0] <= other[0] { !0 } else { 0 },
-            if self[1] <= other[1] { !0 } else { 0 },
-        ])
+    pub fn floor(self) -> F32x8 {
+        self.map(float::floor)
     }
 
     #[inline]
-    pub fn packed_lt(self, other: I32x2) -> U32x2 {
-        U32x2([
-            if self[0] < other[0] { !0 } else { 0 },
-            if self[1] < other[1] { !0 } else { 0 },
-        ])
-    } 
+    pub fn ceil(self) -> F32x8 {
+        self.map(float::ceil)
+    }
 
-    // Conversions
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x8 {
+        self.map(float::round_ties_even)
+    }
 
-    /// Converts these packed integers to floats.
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
     #[inline]
-    pub fn to_f32x2(self) -> F32x2 {
-        F32x2([self[0] as f32, self[1] as f32])
+    pub fn fract(self) -> F32x8 {
+        self - self.floor()
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F32x8 {
+        self.map(float::sqrt)
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F32x8, b: F32x8) -> F32x8 {
+        self * a + b
+    }
+
+    #[inline]
+    fn map<F>(self, f: F) -> F32x8 where F: Fn(f32) -> f32 {
+        let mut result = self;
+        for lane in &mut result.0 {
+            *lane = f(*lane);
+        }
+        result
+    }
+
+    #[inline]
+    fn zip<F>(self, other: F32x8, f: F) -> F32x8 where F: Fn(f32, f32) -> f32 {
+        let mut result = self;
+        for (lane, &other) in result.0.iter_mut().zip(other.0.iter()) {
+            *lane = f(*lane, other);
+        }
+        result
     }
 }
 
-impl Index<usize> for I32x2 {
-    type Output = i32;
+impl Index<usize> for F32x8 {
+    type Output = f32;
     #[inline]
-    fn index(&self, index: usize) -> &i32 {
+    fn index(&self, index: usize) -> &f32 {
         &self.0[index]
     }
 }
 
-impl IndexMut<usize> for I32x2 {
+impl IndexMut<usize> for F32x8 {
     #[inline]
-    fn index_mut(&mut self, index: usize) -> &mut i32 {
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
         &mut self.0[index]
     }
 }
 
-impl Add<I32x2> for I32x2 {
-    type Output = I32x2;
+impl Debug for F32x8 {
     #[inline]
-    fn add(self, other: I32x2) -> I32x2 {
-        I32x2([self[0] + other[0], self[1] + other[1]])
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f,
+               "<{}, {}, {}, {}, {}, {}, {}, {}>",
+               self[0], self[1], self[2], self[3], self[4], self[5], self[6], self[7])
     }
 }
 
-impl Sub<I32x2> for I32x2 {
-    type Output = I32x2;
+impl Add<F32x8> for F32x8 {
+    type Output = F32x8;
     #[inline]
-    fn sub(self, other: I32x2) -> I32x2 {
-        I32x2([self[0] - other[0], self[1] - other[1]])
+    fn add(self, other: F32x8) -> F32x8 {
+        self.zip(other, |a, b| a + b)
     }
 }
 
-impl Mul<I32x2> for I32x2 {
-    type Output = I32x2;
+impl Div<F32x8> for F32x8 {
+    type Output = F32x8;
     #[inline]
-    fn mul(self, other: I32x2) -> I32x2 {
-        I32x2([self[0] * other[0], self[1] * other[1]])
+    fn div(self, other: F32x8) -> F32x8 {
+        self.zip(other, |a, b| a / b)
     }
 }
 
-// Four 32-bit signed integers
-
-#[derive(Clone, Copy, Default, Debug, PartialEq)]
-pub struct I32x4([i32; 4]);
-
-impl I32x4 {
+impl Mul<F32x8> for F32x8 {
+    type Output = F32x8;
     #[inline]
-    pub fn new(a: i32, b: i32, c: i32, d: i32) -> I32x4 {
-        I32x4([a, b, c, d])
+    fn mul(self, other: F32x8) -> F32x8 {
+        self.zip(other, |a, b| a * b)
     }
+}
 
+impl Sub<F32x8> for F32x8 {
+    type Output = F32x8;
     #[inline]
-    pub fn splat(x: i32) -> I32x4 {
+    fn sub(self, other: F32x8) -> F32x8 {
+        self.zip(other, |a, b| a - b)
+    }
+}
+
+// Two 64-bit floats
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(transparent)]
+pub struct F64x2(pub [f64; 2]);
+
+impl F64x2 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: f64, b: f64) -> F64x2 {
+        F64x2([a, b])
+    }
+
+    #[inline]
+    pub fn splat(x: f64) -> F64x2 {
+        F64x2([x, x])
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: F64x2) -> F64x2 {
+        F64x2([f64::min(self[0], other[0]), f64::min(self[1], other[1])])
+    }
+
+    #[inline]
+    pub fn max(self, other: F64x2) -> F64x2 {
+        F64x2([f64::max(self[0], other[0]), f64::max(self[1], other[1])])
+    }
+
+    #[inline]
+    pub fn clamp(self, min: F64x2, max: F64x2) -> F64x2 {
+        self.max(min).min(max)
+    }
+
+    #[inline]
+    pub fn abs(self) -> F64x2 {
+        F64x2([float::abs_f64(self[0]), float::abs_f64(self[1])])
+    }
+
+    #[inline]
+    pub fn floor(self) -> F64x2 {
+        F64x2([float::floor_f64(self[0]), float::floor_f64(self[1])])
+    }
+
+    #[inline]
+    pub fn ceil(self) -> F64x2 {
+        F64x2([float::ceil_f64(self[0]), float::ceil_f64(self[1])])
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F64x2 {
+        F64x2([float::round_ties_even_f64(self[0]), float::round_ties_even_f64(self[1])])
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F64x2 {
+        F64x2([float::sqrt_f64(self[0]), float::sqrt_f64(self[1])])
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F64x2, b: F64x2) -> F64x2 {
+        self * a + b
+    }
+
+    // Horizontal operations
+
+    /// Returns the sum of both lanes.
+    #[inline]
+    pub fn sum(self) -> f64 {
+        self[0] + self[1]
+    }
+
+    /// Returns the dot product of this vector and `other`.
+    #[inline]
+    pub fn dot(self, other: F64x2) -> f64 {
+        (self * other).sum()
+    }
+
+    // Swizzle
+
+    #[inline]
+    pub fn yx(self) -> F64x2 {
+        F64x2([self[1], self[0]])
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: F64x2) -> F64x4 {
+        F64x4([self[0], self[1], other[0], other[1]])
+    }
+}
+
+impl Index<usize> for F64x2 {
+    type Output = f64;
+    #[inline]
+    fn index(&self, index: usize) -> &f64 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for F64x2 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut f64 {
+        &mut self.0[index]
+    }
+}
+
+impl Add<F64x2> for F64x2 {
+    type Output = F64x2;
+    #[inline]
+    fn add(self, other: F64x2) -> F64x2 {
+        F64x2([self[0] + other[0], self[1] + other[1]])
+    }
+}
+
+impl Div<F64x2> for F64x2 {
+    type Output = F64x2;
+    #[inline]
+    fn div(self, other: F64x2) -> F64x2 {
+        F64x2([self[0] / other[0], self[1] / other[1]])
+    }
+}
+
+impl Mul<F64x2> for F64x2 {
+    type Output = F64x2;
+    #[inline]
+    fn mul(self, other: F64x2) -> F64x2 {
+        F64x2([self[0] * other[0], self[1] * other[1]])
+    }
+}
+
+impl Sub<F64x2> for F64x2 {
+    type Output = F64x2;
+    #[inline]
+    fn sub(self, other: F64x2) -> F64x2 {
+        F64x2([self[0] - other[0], self[1] - other[1]])
+    }
+}
+
+// Four 64-bit floats
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(transparent)]
+pub struct F64x4(pub [f64; 4]);
+
+impl F64x4 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: f64, b: f64, c: f64, d: f64) -> F64x4 {
+        F64x4([a, b, c, d])
+    }
+
+    #[inline]
+    pub fn splat(x: f64) -> F64x4 {
+        F64x4([x; 4])
+    }
+
+    #[inline]
+    pub fn from_halves(lo: F64x2, hi: F64x2) -> F64x4 {
+        lo.concat_xy_xy(hi)
+    }
+
+    // Accessors
+
+    /// Returns the first two lanes.
+    #[inline]
+    pub fn lo(self) -> F64x2 {
+        F64x2([self[0], self[1]])
+    }
+
+    /// Returns the last two lanes.
+    #[inline]
+    pub fn hi(self) -> F64x2 {
+        F64x2([self[2], self[3]])
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: F64x4) -> F64x4 {
+        self.zip(other, f64::min)
+    }
+
+    #[inline]
+    pub fn max(self, other: F64x4) -> F64x4 {
+        self.zip(other, f64::max)
+    }
+
+    #[inline]
+    pub fn clamp(self, min: F64x4, max: F64x4) -> F64x4 {
+        self.max(min).min(max)
+    }
+
+    #[inline]
+    pub fn abs(self) -> F64x4 {
+        self.map(float::abs_f64)
+    }
+
+    #[inline]
+    pub fn floor(self) -> F64x4 {
+        self.map(float::floor_f64)
+    }
+
+    #[inline]
+    pub fn ceil(self) -> F64x4 {
+        self.map(float::ceil_f64)
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F64x4 {
+        self.map(float::round_ties_even_f64)
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F64x4 {
+        self.map(float::sqrt_f64)
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F64x4, b: F64x4) -> F64x4 {
+        self * a + b
+    }
+
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order. See `F32x4::shuffle()`.
+    #[inline]
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> F64x4 {
+        F64x4([self[A], self[B], self[C], self[D]])
+    }
+
+    #[inline]
+    fn map<F>(self, f: F) -> F64x4 where F: Fn(f64) -> f64 {
+        F64x4([f(self[0]), f(self[1]), f(self[2]), f(self[3])])
+    }
+
+    #[inline]
+    fn zip<F>(self, other: F64x4, f: F) -> F64x4 where F: Fn(f64, f64) -> f64 {
+        F64x4([
+            f(self[0], other[0]),
+            f(self[1], other[1]),
+            f(self[2], other[2]),
+            f(self[3], other[3]),
+        ])
+    }
+}
+
+impl Index<usize> for F64x4 {
+    type Output = f64;
+    #[inline]
+    fn index(&self, index: usize) -> &f64 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for F64x4 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut f64 {
+        &mut self.0[index]
+    }
+}
+
+impl Add<F64x4> for F64x4 {
+    type Output = F64x4;
+    #[inline]
+    fn add(self, other: F64x4) -> F64x4 {
+        self.zip(other, |a, b| a + b)
+    }
+}
+
+impl Div<F64x4> for F64x4 {
+    type Output = F64x4;
+    #[inline]
+    fn div(self, other: F64x4) -> F64x4 {
+        self.zip(other, |a, b| a / b)
+    }
+}
+
+impl Mul<F64x4> for F64x4 {
+    type Output = F64x4;
+    #[inline]
+    fn mul(self, other: F64x4) -> F64x4 {
+        self.zip(other, |a, b| a * b)
+    }
+}
+
+impl Sub<F64x4> for F64x4 {
+    type Output = F64x4;
+    #[inline]
+    fn sub(self, other: F64x4) -> F64x4 {
+        self.zip(other, |a, b| a - b)
+    }
+}
+
+// Two 32-bit signed integers
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[repr(transparent)]
+pub struct I32x2([i32; 2]);
+
+impl I32x2 {
+    #[inline]
+    pub fn new(x: i32, y: i32) -> I32x2 {
+        I32x2([x, y])
+    }
+
+    #[inline]
+    pub fn splat(x: i32) -> I32x2 {
+        I32x2([x, x])
+    }
+
+    // Accessors
+
+    #[inline]
+    pub fn x(self) -> i32 {
+        self[0]
+    }
+
+    #[inline]
+    pub fn y(self) -> i32 {
+        self[1]
+    }
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: I32x2) -> I32x4 {
+        I32x4([self[0], self[1], other[0], other[1]])
+    }
+
+    #[inline]
+    pub fn min(self, other: I32x2) -> I32x2 {
+        I32x2([
+            self[0].min(other[0]),
+            self[1].min(other[1]),
+        ])
+    }
+
+    #[inline]
+    pub fn max(self, other: I32x2) -> I32x2 {
+        I32x2([
+            self[0].max(other[0]),
+            self[1].max(other[1]),
+        ])
+    }
+
+    // Horizontal operations
+
+    /// Returns the smaller of the two lanes.
+    #[inline]
+    pub fn min_element(self) -> i32 {
+        i32::min(self[0], self[1])
+    }
+
+    /// Returns the larger of the two lanes.
+    #[inline]
+    pub fn max_element(self) -> i32 {
+        i32::max(self[0], self[1])
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: I32x2) -> U32x2 {
+        U32x2([
+            if self[0] == other[0] { !0 } else { 0 },
+            if self[1] == other[1] { !0 } else { 0 },
+        ])
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: I32x2) -> U32x2 {
+        U32x2([
+            if self[0] > other[0] { !0 } else { 0 },
+            if self[1] > other[1] { !0 } else { 0 },
+        ])
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: I32x2) -> U32x2 {
+        U32x2([
+            if self[0] <= other[0] { !0 } else { 0 },
+            if self[1] <= other[1] { !0 } else { 0 },
+        ])
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I32x2) -> U32x2 {
+        U32x2([
+            if self[0] < other[0] { !0 } else { 0 },
+            if self[1] < other[1] { !0 } else { 0 },
+        ])
+    } 
+
+    // Conversions
+
+    /// Converts these packed integers to floats.
+    #[inline]
+    pub fn to_f32x2(self) -> F32x2 {
+        F32x2([self[0] as f32, self[1] as f32])
+    }
+
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        self.to_f32x2().to_f32x4()
+    }
+
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        I32x4([self[0], self[1], 0, 0])
+    }
+
+    #[inline]
+    pub fn to_u32x2(self) -> U32x2 {
+        U32x2([self[0] as u32, self[1] as u32])
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: I32x2) -> U32x2 {
+        U32x2([
+            if self[0] >= other[0] { !0 } else { 0 },
+            if self[1] >= other[1] { !0 } else { 0 },
+        ])
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: I32x2) -> U32x2 {
+        U32x2([
+            if self[0] != other[0] { !0 } else { 0 },
+            if self[1] != other[1] { !0 } else { 0 },
+        ])
+    }
+}
+
+impl Index<usize> for I32x2 {
+    type Output = i32;
+    #[inline]
+    fn index(&self, index: usize) -> &i32 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for I32x2 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut i32 {
+        &mut self.0[index]
+    }
+}
+
+impl Add<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn add(self, other: I32x2) -> I32x2 {
+        I32x2([self[0] + other[0], self[1] + other[1]])
+    }
+}
+
+impl Sub<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn sub(self, other: I32x2) -> I32x2 {
+        I32x2([self[0] - other[0], self[1] - other[1]])
+    }
+}
+
+impl Mul<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn mul(self, other: I32x2) -> I32x2 {
+        I32x2([self[0] * other[0], self[1] * other[1]])
+    }
+}
+
+impl BitAnd<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn bitand(self, other: I32x2) -> I32x2 {
+        I32x2([self[0] & other[0], self[1] & other[1]])
+    }
+}
+
+impl BitOr<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn bitor(self, other: I32x2) -> I32x2 {
+        I32x2([self[0] | other[0], self[1] | other[1]])
+    }
+}
+
+impl Shl<u32> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn shl(self, amount: u32) -> I32x2 {
+        I32x2([shl_i32(self[0], amount), shl_i32(self[1], amount)])
+    }
+}
+
+impl Shr<u32> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn shr(self, amount: u32) -> I32x2 {
+        I32x2([shr_i32(self[0], amount), shr_i32(self[1], amount)])
+    }
+}
+
+// Four 32-bit signed integers
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[repr(transparent)]
+pub struct I32x4([i32; 4]);
+
+impl I32x4 {
+    #[inline]
+    pub fn new(a: i32, b: i32, c: i32, d: i32) -> I32x4 {
+        I32x4([a, b, c, d])
+    }
+
+    #[inline]
+    pub fn splat(x: i32) -> I32x4 {
         I32x4([x; 4])
     }
 
-    // Basic operations
+    /// Loads the first four elements of `slice`, which needn't be aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn from_slice(slice: &[i32]) -> I32x4 {
+        let slice = &slice[..4];
+        I32x4([slice[0], slice[1], slice[2], slice[3]])
+    }
+
+    /// Loads the first four elements of `slice`, which must start on a 16-byte boundary. This can
+    /// be faster than `from_slice` on some CPUs.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn from_slice_aligned(slice: &[i32]) -> I32x4 {
+        let slice = &slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        I32x4::from_slice(slice)
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which needn't be
+    /// aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn write_to_slice(self, slice: &mut [i32]) {
+        let slice = &mut slice[..4];
+        slice.copy_from_slice(&self.0)
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which must start
+    /// on a 16-byte boundary.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_mut_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn write_to_slice_aligned(self, slice: &mut [i32]) {
+        let slice = &mut slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        self.write_to_slice(slice)
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: I32x4) -> I32x4 {
+        I32x4([
+            self[0].min(other[0]),
+            self[1].min(other[1]),
+            self[2].min(other[2]),
+            self[3].min(other[3]),
+        ])
+    }
+
+    #[inline]
+    pub fn max(self, other: I32x4) -> I32x4 {
+        I32x4([
+            self[0].max(other[0]),
+            self[1].max(other[1]),
+            self[2].max(other[2]),
+            self[3].max(other[3]),
+        ])
+    }
+
+    /// Returns the smallest of the four lanes.
+    #[inline]
+    pub fn min_element(self) -> i32 {
+        self[0].min(self[1]).min(self[2]).min(self[3])
+    }
+
+    /// Returns the largest of the four lanes.
+    #[inline]
+    pub fn max_element(self) -> i32 {
+        self[0].max(self[1]).max(self[2]).max(self[3])
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: I32x4) -> U32x4 {
+        U32x4([
+            if self[0] == other[0] { !0 } else { 0 },
+            if self[1] == other[1] { !0 } else { 0 },
+            if self[2] == other[2] { !0 } else { 0 },
+            if self[3] == other[3] { !0 } else { 0 },
+        ])
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: I32x4) -> U32x4 {
+        U32x4([
+            if self[0] > other[0] { !0 } else { 0 },
+            if self[1] > other[1] { !0 } else { 0 },
+            if self[2] > other[2] { !0 } else { 0 },
+            if self[3] > other[3] { !0 } else { 0 },
+        ])
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: I32x4) -> U32x4 {
+        U32x4([
+            if self[0] <= other[0] { !0 } else { 0 },
+            if self[1] <= other[1] { !0 } else { 0 },
+            if self[2] <= other[2] { !0 } else { 0 },
+            if self[3] <= other[3] { !0 } else { 0 },
+        ])
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I32x4) -> U32x4 {
+        U32x4([
+            if self[0] < other[0] { !0 } else { 0 },
+            if self[1] < other[1] { !0 } else { 0 },
+            if self[2] < other[2] { !0 } else { 0 },
+            if self[3] < other[3] { !0 } else { 0 },
+        ])
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: I32x4) -> U32x4 {
+        U32x4([
+            if self[0] >= other[0] { !0 } else { 0 },
+            if self[1] >= other[1] { !0 } else { 0 },
+            if self[2] >= other[2] { !0 } else { 0 },
+            if self[3] >= other[3] { !0 } else { 0 },
+        ])
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: I32x4) -> U32x4 {
+        U32x4([
+            if self[0] != other[0] { !0 } else { 0 },
+            if self[1] != other[1] { !0 } else { 0 },
+            if self[2] != other[2] { !0 } else { 0 },
+            if self[3] != other[3] { !0 } else { 0 },
+        ])
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: I32x4) -> I32x4 {
+        I32x4([self[0], self[1], other[0], other[1]])
+    }
+
+    #[inline]
+    pub fn concat_zw_zw(self, other: I32x4) -> I32x4 {
+        I32x4([self[2], self[3], other[2], other[3]])
+    }
+
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order.
+    ///
+    /// This covers every permutation, so it's handy when the lanes are chosen by other generic
+    /// code. `v.shuffle::<2, 3, 0, 1>()` is the same as `v.zwxy()`. Every index must be less
+    /// than 4.
+    #[inline]
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> I32x4 {
+        debug_assert!(A < 4 && B < 4 && C < 4 && D < 4, "shuffle lane index out of range");
+        I32x4([self[A], self[B], self[C], self[D]])
+    }
+
+    // Swizzle conversions
+
+    #[inline]
+    pub fn xy(self) -> I32x2 {
+        I32x2([self[0], self[1]])
+    }
+
+    #[inline]
+    pub fn xw(self) -> I32x2 {
+        I32x2([self[0], self[3]])
+    }
+
+    #[inline]
+    pub fn zy(self) -> I32x2 {
+        I32x2([self[2], self[1]])
+    }
+
+    #[inline]
+    pub fn zw(self) -> I32x2 {
+        I32x2([self[2], self[3]])
+    }
+
+    // Conversions
+
+    /// Converts these packed integers to floats.
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        F32x4([
+            self[0] as f32,
+            self[1] as f32,
+            self[2] as f32,
+            self[3] as f32,
+        ])
+    }
+
+    /// Converts these packed signed integers to unsigned integers.
+    ///
+    /// Overflowing values will wrap around.
+    ///
+    /// FIXME(pcwalton): Should they? This will assert on overflow in debug.
+    #[inline]
+    pub fn to_u32x4(self) -> U32x4 {
+        U32x4([self[0] as u32, self[1] as u32, self[2] as u32, self[3] as u32])
+    }
+}
+
+impl Index<usize> for I32x4 {
+    type Output = i32;
+    #[inline]
+    fn index(&self, index: usize) -> &i32 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for I32x4 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut i32 {
+        &mut self.0[index]
+    }
+}
+
+impl Add<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn add(self, other: I32x4) -> I32x4 {
+        I32x4([
+            self[0] + other[0],
+            self[1] + other[1],
+            self[2] + other[2],
+            self[3] + other[3],
+        ])
+    }
+}
+
+impl Sub<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn sub(self, other: I32x4) -> I32x4 {
+        I32x4([
+            self[0] - other[0],
+            self[1] - other[1],
+            self[2] - other[2],
+            self[3] - other[3],
+        ])
+    }
+}
+
+impl Mul<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn mul(self, other: I32x4) -> I32x4 {
+        I32x4([
+            self[0] * other[0],
+            self[1] * other[1],
+            self[2] * other[2],
+            self[3] * other[3],
+        ])
+    }
+}
+
+impl BitAnd<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn bitand(self, other: I32x4) -> I32x4 {
+        I32x4([self[0] & other[0], self[1] & other[1], self[2] & other[2], self[3] & other[3]])
+    }
+}
+
+impl BitOr<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn bitor(self, other: I32x4) -> I32x4 {
+        I32x4([self[0] | other[0], self[1] | other[1], self[2] | other[2], self[3] | other[3]])
+    }
+}
+
+impl Shl<u32> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shl(self, amount: u32) -> I32x4 {
+        I32x4([
+            shl_i32(self[0], amount),
+            shl_i32(self[1], amount),
+            shl_i32(self[2], amount),
+            shl_i32(self[3], amount),
+        ])
+    }
+}
+
+impl Shr<u32> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shr(self, amount: u32) -> I32x4 {
+        I32x4([
+            shr_i32(self[0], amount),
+            shr_i32(self[1], amount),
+            shr_i32(self[2], amount),
+            shr_i32(self[3], amount),
+        ])
+    }
+}
+
+impl Shl<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shl(self, other: I32x4) -> I32x4 {
+        I32x4([
+            shl_i32(self[0], other[0] as u32),
+            shl_i32(self[1], other[1] as u32),
+            shl_i32(self[2], other[2] as u32),
+            shl_i32(self[3], other[3] as u32),
+        ])
+    }
+}
+
+impl Shr<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shr(self, other: I32x4) -> I32x4 {
+        I32x4([
+            shr_i32(self[0], other[0] as u32),
+            shr_i32(self[1], other[1] as u32),
+            shr_i32(self[2], other[2] as u32),
+            shr_i32(self[3], other[3] as u32),
+        ])
+    }
+}
+
+// Two 32-bit unsigned integers
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct U32x2(pub [u32; 2]);
+
+impl U32x2 {
+    #[inline]
+    pub fn new(x: u32, y: u32) -> U32x2 {
+        U32x2([x, y])
+    }
+
+    #[inline]
+    pub fn splat(x: u32) -> U32x2 {
+        U32x2::new(x, x)
+    }
+
+    /// Returns true if both booleans in this vector are true.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(&self) -> bool {
+        self[0] == !0 && self[1] == !0
+    }
+
+    /// Returns true if both booleans in this vector are false.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(&self) -> bool {
+        self[0] == 0 && self[1] == 0
+    }
+
+    /// Returns true if either boolean in this vector is true.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn any_true(&self) -> bool {
+        self[0] != 0 || self[1] != 0
+    }
+
+    #[inline]
+    pub fn to_i32x2(self) -> I32x2 {
+        I32x2::new(self[0] as i32, self[1] as i32)
+    }
+}
+
+impl Index<usize> for U32x2 {
+    type Output = u32;
+    #[inline]
+    fn index(&self, index: usize) -> &u32 {
+        &self.0[index]
+    }
+}
+
+impl Not for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn not(self) -> U32x2 {
+        U32x2([!self[0], !self[1]])
+    }
+}
+
+impl BitAnd<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitand(self, other: U32x2) -> U32x2 {
+        U32x2([self[0] & other[0], self[1] & other[1]])
+    }
+}
+
+impl BitOr<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitor(self, other: U32x2) -> U32x2 {
+        U32x2([self[0] | other[0], self[1] | other[1]])
+    }
+}
+
+impl BitXor<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitxor(self, other: U32x2) -> U32x2 {
+        U32x2([self[0] ^ other[0], self[1] ^ other[1]])
+    }
+}
+
+// Four 32-bit unsigned integers
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct U32x4(pub [u32; 4]);
+
+impl U32x4 {
+    pub fn new(a: u32, b: u32, c: u32, d: u32) -> U32x4 {
+        U32x4([a, b, c, d])
+    }
+
+    #[inline]
+    pub fn splat(x: u32) -> U32x4 {
+        U32x4([x; 4])
+    }
+
+    // Conversions
+
+    /// Converts these packed unsigned integers to signed integers.
+    ///
+    /// Overflowing values will wrap around.
+    ///
+    /// FIXME(pcwalton): Should they? This will assert on overflow in debug.
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        I32x4([self[0] as i32, self[1] as i32, self[2] as i32, self[3] as i32])
+    }
+
+    /// Reinterprets the bits of these integers as sixteen bytes, in memory order.
+    #[inline]
+    pub fn to_u8x16(self) -> U8x16 {
+        let mut bytes = [0; 16];
+        for (chunk, value) in bytes.chunks_mut(4).zip(self.0.iter()) {
+            chunk.copy_from_slice(&value.to_ne_bytes());
+        }
+        U8x16(bytes)
+    }
+
+    // Basic operations
+
+    /// Returns true if all four booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(&self) -> bool {
+        self[0] == !0 && self[1] == !0 && self[2] == !0 && self[3] == !0
+    }
+
+    /// Returns true if all four booleans in this vector are false.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(&self) -> bool {
+        self[0] == 0 && self[1] == 0 && self[2] == 0 && self[3] == 0
+    }
+
+    /// Returns true if any of the four booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn any_true(&self) -> bool {
+        self[0] != 0 || self[1] != 0 || self[2] != 0 || self[3] != 0
+    }
+}
+
+impl Index<usize> for U32x4 {
+    type Output = u32;
+    #[inline]
+    fn index(&self, index: usize) -> &u32 {
+        &self.0[index]
+    }
+}
+
+impl Not for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn not(self) -> U32x4 {
+        U32x4([!self[0], !self[1], !self[2], !self[3]])
+    }
+}
+
+impl BitAnd<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitand(self, other: U32x4) -> U32x4 {
+        U32x4([self[0] & other[0], self[1] & other[1], self[2] & other[2], self[3] & other[3]])
+    }
+}
+
+impl BitOr<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitor(self, other: U32x4) -> U32x4 {
+        U32x4([self[0] | other[0], self[1] | other[1], self[2] | other[2], self[3] | other[3]])
+    }
+}
+
+impl BitXor<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitxor(self, other: U32x4) -> U32x4 {
+        U32x4([self[0] ^ other[0], self[1] ^ other[1], self[2] ^ other[2], self[3] ^ other[3]])
+    }
+}
+
+impl Shr<u32> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn shr(self, amount: u32) -> U32x4 {
+        U32x4([self[0] >> amount, self[1] >> amount, self[2] >> amount, self[3] >> amount])
+    }
+}
+
+// Sixteen 8-bit unsigned integers
+
+/// Sixteen bytes, such as the channels of four RGBA pixels. Arithmetic wraps around.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct U8x16(pub [u8; 16]);
+
+impl U8x16 {
+    // Constructors
+
+    #[inline]
+    pub fn from_array(bytes: [u8; 16]) -> U8x16 {
+        U8x16(bytes)
+    }
+
+    #[inline]
+    pub fn splat(x: u8) -> U8x16 {
+        U8x16([x; 16])
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_array(self) -> [u8; 16] {
+        self.0
+    }
+
+    /// Reinterprets these bytes as four 32-bit integers, in memory order.
+    #[inline]
+    pub fn to_u32x4(self) -> U32x4 {
+        let mut values = [0; 4];
+        for (value, chunk) in values.iter_mut().zip(self.0.chunks(4)) {
+            *value = u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        U32x4(values)
+    }
+
+    /// Zero-extends the first eight bytes to 16 bits, for arithmetic that would overflow a byte.
+    #[inline]
+    pub fn widen_low(self) -> U16x8 {
+        let mut values = [0; 8];
+        for (value, &byte) in values.iter_mut().zip(self.0[0..8].iter()) {
+            *value = byte as u16;
+        }
+        U16x8(values)
+    }
+
+    /// Zero-extends the last eight bytes to 16 bits.
+    #[inline]
+    pub fn widen_high(self) -> U16x8 {
+        let mut values = [0; 8];
+        for (value, &byte) in values.iter_mut().zip(self.0[8..16].iter()) {
+            *value = byte as u16;
+        }
+        U16x8(values)
+    }
+
+    /// Narrows sixteen 16-bit integers, `low` followed by `high`, to bytes. Values above 255
+    /// become 255.
+    #[inline]
+    pub fn narrow(low: U16x8, high: U16x8) -> U8x16 {
+        let mut bytes = [0; 16];
+        for (byte, &value) in bytes.iter_mut().zip(low.0.iter().chain(high.0.iter())) {
+            *byte = value.min(255) as u8;
+        }
+        U8x16(bytes)
+    }
+
+    /// Narrows sixteen signed 16-bit integers, `low` followed by `high`, to bytes. Values below 0
+    /// become 0, and values above 255 become 255.
+    #[inline]
+    pub fn narrow_i16x8(low: I16x8, high: I16x8) -> U8x16 {
+        let mut bytes = [0; 16];
+        for (byte, &value) in bytes.iter_mut().zip(low.0.iter().chain(high.0.iter())) {
+            *byte = value.clamp(0, 255) as u8;
+        }
+        U8x16(bytes)
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: U8x16) -> U8x16 {
+        self.zip(other, |a, b| a.min(b))
+    }
+
+    #[inline]
+    pub fn max(self, other: U8x16) -> U8x16 {
+        self.zip(other, |a, b| a.max(b))
+    }
+
+    /// Adds, clamping sums above 255 to 255.
+    #[inline]
+    pub fn saturating_add(self, other: U8x16) -> U8x16 {
+        self.zip(other, u8::saturating_add)
+    }
+
+    /// Subtracts, clamping differences below 0 to 0.
+    #[inline]
+    pub fn saturating_sub(self, other: U8x16) -> U8x16 {
+        self.zip(other, u8::saturating_sub)
+    }
+
+    /// Returns true if all sixteen booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all sixteen values in this vector are not booleans. A boolean
+    /// is a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        self.0.iter().all(|&x| x == !0)
+    }
+
+    /// Returns true if all sixteen booleans in this vector are false.
+    ///
+    /// The result is *undefined* if all sixteen values in this vector are not booleans. A boolean
+    /// is a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        self.0.iter().all(|&x| x == 0)
+    }
+
+    // Swizzle
+
+    /// Rearranges the bytes of this vector the way `pshufb` does: each lane of the result is the
+    /// byte of `self` at the index in the low four bits of the same lane of `indices`, or zero
+    /// if the high bit of that lane is set.
+    #[inline]
+    pub fn shuffle(self, indices: U8x16) -> U8x16 {
+        let mut bytes = [0; 16];
+        for (byte, &index) in bytes.iter_mut().zip(indices.0.iter()) {
+            if index & 0x80 == 0 {
+                *byte = self[(index & 0x0f) as usize];
+            }
+        }
+        U8x16(bytes)
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U8x16) -> U8x16 {
+        self.zip(other, |a, b| if a == b { !0 } else { 0 })
+    }
+
+    #[inline]
+    fn zip<F>(self, other: U8x16, f: F) -> U8x16 where F: Fn(u8, u8) -> u8 {
+        let mut result = self;
+        for (lane, &other) in result.0.iter_mut().zip(other.0.iter()) {
+            *lane = f(*lane, other);
+        }
+        result
+    }
+}
+
+impl Index<usize> for U8x16 {
+    type Output = u8;
+    #[inline]
+    fn index(&self, index: usize) -> &u8 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for U8x16 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut u8 {
+        &mut self.0[index]
+    }
+}
+
+impl Debug for U8x16 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}", self[0])?;
+        for byte in &self.0[1..] {
+            write!(f, ", {}", byte)?;
+        }
+        write!(f, ">")
+    }
+}
 
+impl Add<U8x16> for U8x16 {
+    type Output = U8x16;
     #[inline]
-    pub fn min(self, other: I32x4) -> I32x4 {
-        I32x4([
-            self[0].min(other[0]),
-            self[1].min(other[1]),
-            self[2].min(other[2]),
-            self[3].min(other[3]),
-        ])
+    fn add(self, other: U8x16) -> U8x16 {
+        self.zip(other, u8::wrapping_add)
     }
+}
 
+impl Sub<U8x16> for U8x16 {
+    type Output = U8x16;
     #[inline]
-    pub fn max(self, other: I32x4) -> I32x4 {
-        I32x4([
-            self[0].max(other[0]),
-            self[1].max(other[1]),
-            self[2].max(other[2]),
-            self[3].max(other[3]),
-        ])
+    fn sub(self, other: U8x16) -> U8x16 {
+        self.zip(other, u8::wrapping_sub)
     }
+}
 
-    // Packed comparisons
+impl BitAnd<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn bitand(self, other: U8x16) -> U8x16 {
+        self.zip(other, |a, b| a & b)
+    }
+}
 
+impl BitOr<U8x16> for U8x16 {
+    type Output = U8x16;
     #[inline]
-    pub fn packed_eq(self, other: I32x4) -> U32x4 {
-        U32x4([
-            if self[0] == other[0] { !0 } else { 0 },
-            if self[1] == other[1] { !0 } else { 0 },
-            if self[2] == other[2] { !0 } else { 0 },
-            if self[3] == other[3] { !0 } else { 0 },
-        ])
+    fn bitor(self, other: U8x16) -> U8x16 {
+        self.zip(other, |a, b| a | b)
     }
+}
+
+// Eight 16-bit unsigned integers
+
+/// Eight 16-bit unsigned integers, for intermediate results of byte arithmetic. Arithmetic wraps
+/// around.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct U16x8(pub [u16; 8]);
+
+impl U16x8 {
+    // Constructors
 
     #[inline]
-    pub fn packed_gt(self, other: I32x4) -> U32x4 {
-        U32x4([
-            if self[0] > other[0] { !0 } else { 0 },
-            if self[1] > other[1] { !0 } else { 0 },
-            if self[2] > other[2] { !0 } else { 0 },
-            if self[3] > other[3] { !0 } else { 0 },
-        ])
+    pub fn from_array(values: [u16; 8]) -> U16x8 {
+        U16x8(values)
     }
 
     #[inline]
-    pub fn packed_le(self, other: I32x4) -> U32x4 {
-        U32x4([
-            if self[0] <= other[0] { !0 } else { 0 },
-            if self[1] <= other[1] { !0 } else { 0 },
-            if self[2] <= other[2] { !0 } else { 0 },
-            if self[3] <= other[3] { !0 } else { 0 },
-        ])
+    pub fn splat(x: u16) -> U16x8 {
+        U16x8([x; 8])
     }
 
+    // Conversions
+
     #[inline]
-    pub fn packed_lt(self, other: I32x4) -> U32x4 {
-        U32x4([
-            if self[0] < other[0] { !0 } else { 0 },
-            if self[1] < other[1] { !0 } else { 0 },
-            if self[2] < other[2] { !0 } else { 0 },
-            if self[3] < other[3] { !0 } else { 0 },
-        ])
+    pub fn to_array(self) -> [u16; 8] {
+        self.0
     }
 
-    // Concatenations
+    /// Reinterprets these integers as signed. Values above 32767 wrap around.
+    #[inline]
+    pub fn to_i16x8(self) -> I16x8 {
+        let mut values = [0; 8];
+        for (value, &lane) in values.iter_mut().zip(self.0.iter()) {
+            *value = lane as i16;
+        }
+        I16x8(values)
+    }
 
+    /// Zero-extends the first four integers to 32 bits.
     #[inline]
-    pub fn concat_xy_xy(self, other: I32x4) -> I32x4 {
-        I32x4([self[0], self[1], other[0], other[1]])
+    pub fn widen_low(self) -> I32x4 {
+        I32x4([self[0] as i32, self[1] as i32, self[2] as i32, self[3] as i32])
     }
 
+    /// Zero-extends the last four integers to 32 bits.
     #[inline]
-    pub fn concat_zw_zw(self, other: I32x4) -> I32x4 {
-        I32x4([self[2], self[3], other[2], other[3]])
+    pub fn widen_high(self) -> I32x4 {
+        I32x4([self[4] as i32, self[5] as i32, self[6] as i32, self[7] as i32])
     }
 
-    // Swizzle conversions
+    /// Narrows eight 32-bit integers, `low` followed by `high`, to 16 bits. Values below 0 become
+    /// 0, and values above 65535 become 65535.
+    #[inline]
+    pub fn narrow(low: I32x4, high: I32x4) -> U16x8 {
+        let mut values = [0; 8];
+        for (value, &wide) in values.iter_mut().zip(low.0.iter().chain(high.0.iter())) {
+            *value = wide.clamp(0, u16::MAX as i32) as u16;
+        }
+        U16x8(values)
+    }
+
+    // Basic operations
 
     #[inline]
-    pub fn xy(self) -> I32x2 {
-        I32x2([self[0], self[1]])
+    pub fn min(self, other: U16x8) -> U16x8 {
+        self.zip(other, |a, b| a.min(b))
     }
 
     #[inline]
-    pub fn xw(self) -> I32x2 {
-        I32x2([self[0], self[3]])
+    pub fn max(self, other: U16x8) -> U16x8 {
+        self.zip(other, |a, b| a.max(b))
     }
 
+    /// Adds, clamping sums above 65535 to 65535.
     #[inline]
-    pub fn zy(self) -> I32x2 {
-        I32x2([self[2], self[1]])
+    pub fn saturating_add(self, other: U16x8) -> U16x8 {
+        self.zip(other, u16::saturating_add)
     }
 
+    /// Subtracts, clamping differences below 0 to 0.
     #[inline]
-    pub fn zw(self) -> I32x2 {
-        I32x2([self[2], self[3]])
+    pub fn saturating_sub(self, other: U16x8) -> U16x8 {
+        self.zip(other, u16::saturating_sub)
     }
 
-    // Conversions
+    // Packed comparisons
 
-    /// Converts these packed integers to floats.
     #[inline]
-    pub fn to_f32x4(self) -> F32x4 {
-        F32x4([
-            self[0] as f32,
-            self[1] as f32,
-            self[2] as f32,
-            self[3] as f32,
-        ])
+    pub fn packed_eq(self, other: U16x8) -> U16x8 {
+        self.zip(other, |a, b| if a == b { !0 } else { 0 })
     }
 
-    /// Converts these packed signed integers to unsigned integers.
-    ///
-    /// Overflowing values will wrap around.
-    ///
-    /// FIXME(pcwalton): Should they? This will assert on overflow in debug.
     #[inline]
-    pub fn to_u32x4(self) -> U32x4 {
-        U32x4([self[0] as u32, self[1] as u32, self[2] as u32, self[3] as u32])
+    fn zip<F>(self, other: U16x8, f: F) -> U16x8 where F: Fn(u16, u16) -> u16 {
+        let mut result = self;
+        for (lane, &other) in result.0.iter_mut().zip(other.0.iter()) {
+            *lane = f(*lane, other);
+        }
+        result
     }
 }
 
-impl Index<usize> for I32x4 {
-    type Output = i32;
+impl Index<usize> for U16x8 {
+    type Output = u16;
     #[inline]
-    fn index(&self, index: usize) -> &i32 {
+    fn index(&self, index: usize) -> &u16 {
         &self.0[index]
     }
 }
 
-impl IndexMut<usize> for I32x4 {
-    #[inline]
-    fn index_mut(&mut self, index: usize) -> &mut i32 {
-        &mut self.0[index]
+impl Debug for U16x8 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}", self[0])?;
+        for value in &self.0[1..] {
+            write!(f, ", {}", value)?;
+        }
+        write!(f, ">")
     }
 }
 
-impl Add<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Add<U16x8> for U16x8 {
+    type Output = U16x8;
     #[inline]
-    fn add(self, other: I32x4) -> I32x4 {
-        I32x4([
-            self[0] + other[0],
-            self[1] + other[1],
-            self[2] + other[2],
-            self[3] + other[3],
-        ])
+    fn add(self, other: U16x8) -> U16x8 {
+        self.zip(other, u16::wrapping_add)
     }
 }
 
-impl Sub<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Sub<U16x8> for U16x8 {
+    type Output = U16x8;
     #[inline]
-    fn sub(self, other: I32x4) -> I32x4 {
-        I32x4([
-            self[0] - other[0],
-            self[1] - other[1],
-            self[2] - other[2],
-            self[3] - other[3],
-        ])
+    fn sub(self, other: U16x8) -> U16x8 {
+        self.zip(other, u16::wrapping_sub)
     }
 }
 
-impl Mul<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Mul<U16x8> for U16x8 {
+    type Output = U16x8;
     #[inline]
-    fn mul(self, other: I32x4) -> I32x4 {
-        I32x4([
-            self[0] * other[0],
-            self[1] * other[1],
-            self[2] * other[2],
-            self[3] * other[3],
-        ])
+    fn mul(self, other: U16x8) -> U16x8 {
+        self.zip(other, u16::wrapping_mul)
     }
 }
 
-impl BitAnd<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Shl<u32> for U16x8 {
+    type Output = U16x8;
     #[inline]
-    fn bitand(self, other: I32x4) -> I32x4 {
-        I32x4([self[0] & other[0], self[1] & other[1], self[2] & other[2], self[3] & other[3]])
+    fn shl(self, amount: u32) -> U16x8 {
+        let mut result = self;
+        for lane in &mut result.0 {
+            *lane = lane.checked_shl(amount).unwrap_or(0);
+        }
+        result
     }
 }
 
-impl BitOr<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Shr<u32> for U16x8 {
+    type Output = U16x8;
     #[inline]
-    fn bitor(self, other: I32x4) -> I32x4 {
-        I32x4([self[0] | other[0], self[1] | other[1], self[2] | other[2], self[3] | other[3]])
+    fn shr(self, amount: u32) -> U16x8 {
+        let mut result = self;
+        for lane in &mut result.0 {
+            *lane = lane.checked_shr(amount).unwrap_or(0);
+        }
+        result
     }
 }
 
-impl Shr<I32x4> for I32x4 {
-    type Output = I32x4;
+// Eight 16-bit signed integers
+
+/// Eight 16-bit signed integers, for intermediate results of byte arithmetic that can go
+/// negative, such as coverage deltas. Arithmetic wraps around.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct I16x8(pub [i16; 8]);
+
+impl I16x8 {
+    // Constructors
+
     #[inline]
-    fn shr(self, other: I32x4) -> I32x4 {
-        I32x4([
-            self[0] >> other[0],
-            self[1] >> other[1],
-            self[2] >> other[2],
-            self[3] >> other[3],
-        ])
+    pub fn from_array(values: [i16; 8]) -> I16x8 {
+        I16x8(values)
     }
-}
 
-// Two 32-bit unsigned integers
+    #[inline]
+    pub fn splat(x: i16) -> I16x8 {
+        I16x8([x; 8])
+    }
 
-#[derive(Clone, Copy)]
-pub struct U32x2(pub [u32; 2]);
+    // Conversions
 
-impl U32x2 {
     #[inline]
-    pub fn new(x: u32, y: u32) -> U32x2 {
-        U32x2([x, y])
+    pub fn to_array(self) -> [i16; 8] {
+        self.0
     }
 
+    /// Reinterprets these integers as unsigned. Negative values wrap around.
     #[inline]
-    pub fn splat(x: u32) -> U32x2 {
-        U32x2::new(x, x)
+    pub fn to_u16x8(self) -> U16x8 {
+        let mut values = [0; 8];
+        for (value, &lane) in values.iter_mut().zip(self.0.iter()) {
+            *value = lane as u16;
+        }
+        U16x8(values)
     }
 
-    /// Returns true if both booleans in this vector are true.
-    ///
-    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
-    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    /// Sign-extends the first four integers to 32 bits.
     #[inline]
-    pub fn all_true(&self) -> bool {
-        self[0] == !0 && self[1] == !0
+    pub fn widen_low(self) -> I32x4 {
+        I32x4([self[0] as i32, self[1] as i32, self[2] as i32, self[3] as i32])
     }
 
-    /// Returns true if both booleans in this vector are false.
-    ///
-    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
-    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    /// Sign-extends the last four integers to 32 bits.
     #[inline]
-    pub fn all_false(&self) -> bool {
-        self[0] == 0 && self[1] == 0
+    pub fn widen_high(self) -> I32x4 {
+        I32x4([self[4] as i32, self[5] as i32, self[6] as i32, self[7] as i32])
     }
 
+    /// Narrows eight 32-bit integers, `low` followed by `high`, to 16 bits. Values outside the
+    /// range of `i16` are clamped to it.
     #[inline]
-    pub fn to_i32x2(self) -> I32x2 {
-        I32x2::new(self[0] as i32, self[1] as i32)
+    pub fn narrow(low: I32x4, high: I32x4) -> I16x8 {
+        let mut values = [0; 8];
+        for (value, &wide) in values.iter_mut().zip(low.0.iter().chain(high.0.iter())) {
+            *value = wide.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        }
+        I16x8(values)
     }
-}
 
-impl Index<usize> for U32x2 {
-    type Output = u32;
+    // Basic operations
+
     #[inline]
-    fn index(&self, index: usize) -> &u32 {
-        &self.0[index]
+    pub fn min(self, other: I16x8) -> I16x8 {
+        self.zip(other, |a, b| a.min(b))
     }
-}
 
-// Four 32-bit unsigned integers
+    #[inline]
+    pub fn max(self, other: I16x8) -> I16x8 {
+        self.zip(other, |a, b| a.max(b))
+    }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub struct U32x4(pub [u32; 4]);
+    /// Adds, clamping sums to the range of `i16`.
+    #[inline]
+    pub fn saturating_add(self, other: I16x8) -> I16x8 {
+        self.zip(other, i16::saturating_add)
+    }
 
-impl U32x4 {
-    pub fn new(a: u32, b: u32, c: u32, d: u32) -> U32x4 {
-        U32x4([a, b, c, d])
+    /// Subtracts, clamping differences to the range of `i16`.
+    #[inline]
+    pub fn saturating_sub(self, other: I16x8) -> I16x8 {
+        self.zip(other, i16::saturating_sub)
     }
 
-    // Conversions
+    // Packed comparisons
 
-    /// Converts these packed unsigned integers to signed integers.
-    ///
-    /// Overflowing values will wrap around.
-    ///
-    /// FIXME(pcwalton): Should they? This will assert on overflow in debug.
     #[inline]
-    pub fn to_i32x4(self) -> I32x4 {
-        I32x4([self[0] as i32, self[1] as i32, self[2] as i32, self[3] as i32])
+    pub fn packed_eq(self, other: I16x8) -> I16x8 {
+        self.zip(other, |a, b| if a == b { !0 } else { 0 })
     }
 
-    // Basic operations
+    #[inline]
+    pub fn packed_gt(self, other: I16x8) -> I16x8 {
+        self.zip(other, |a, b| if a > b { !0 } else { 0 })
+    }
 
-    /// Returns true if all four booleans in this vector are true.
-    ///
-    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
-    /// a value with all bits set or all bits clear (i.e. !0 or 0).
     #[inline]
-    pub fn all_true(&self) -> bool {
-        self[0] == !0 && self[1] == !0 && self[2] == !0 && self[3] == !0
+    pub fn packed_lt(self, other: I16x8) -> I16x8 {
+        other.packed_gt(self)
     }
 
-    /// Returns true if all four booleans in this vector are false.
-    ///
-    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
-    /// a value with all bits set or all bits clear (i.e. !0 or 0).
     #[inline]
-    pub fn all_false(&self) -> bool {
-        self[0] == 0 && self[1] == 0 && self[2] == 0 && self[3] == 0
+    fn zip<F>(self, other: I16x8, f: F) -> I16x8 where F: Fn(i16, i16) -> i16 {
+        let mut result = self;
+        for (lane, &other) in result.0.iter_mut().zip(other.0.iter()) {
+            *lane = f(*lane, other);
+        }
+        result
     }
 }
 
-impl Index<usize> for U32x4 {
-    type Output = u32;
+impl Index<usize> for I16x8 {
+    type Output = i16;
     #[inline]
-    fn index(&self, index: usize) -> &u32 {
+    fn index(&self, index: usize) -> &i16 {
         &self.0[index]
     }
 }
 
-impl Shr<u32> for U32x4 {
-    type Output = U32x4;
+impl Debug for I16x8 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}", self[0])?;
+        for value in &self.0[1..] {
+            write!(f, ", {}", value)?;
+        }
+        write!(f, ">")
+    }
+}
+
+impl Add<I16x8> for I16x8 {
+    type Output = I16x8;
     #[inline]
-    fn shr(self, amount: u32) -> U32x4 {
-        U32x4([self[0] >> amount, self[1] >> amount, self[2] >> amount, self[3] >> amount])
+    fn add(self, other: I16x8) -> I16x8 {
+        self.zip(other, i16::wrapping_add)
+    }
+}
+
+impl Sub<I16x8> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn sub(self, other: I16x8) -> I16x8 {
+        self.zip(other, i16::wrapping_sub)
+    }
+}
+
+impl Mul<I16x8> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn mul(self, other: I16x8) -> I16x8 {
+        self.zip(other, i16::wrapping_mul)
+    }
+}
+
+impl Shl<u32> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn shl(self, amount: u32) -> I16x8 {
+        let mut result = self;
+        for lane in &mut result.0 {
+            *lane = lane.checked_shl(amount).unwrap_or(0);
+        }
+        result
+    }
+}
+
+impl Shr<u32> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn shr(self, amount: u32) -> I16x8 {
+        let mut result = self;
+        for lane in &mut result.0 {
+            *lane = lane.checked_shr(amount).unwrap_or(*lane >> 15);
+        }
+        result
     }
 }
+
+// Shifts
+//
+// Shift amounts are unsigned, and shifting by the lane width or more shifts every bit out, as SSE
+// does. The other backends share these for the shifts they have no instructions for.
+
+/// Shifts `x` left by `amount` bits, giving zero if every bit is shifted out.
+#[inline]
+pub(crate) fn shl_i32(x: i32, amount: u32) -> i32 {
+    x.checked_shl(amount).unwrap_or(0)
+}
+
+/// Shifts `x` right by `amount` bits arithmetically, filling every bit with the sign if every bit
+/// is shifted out.
+#[inline]
+pub(crate) fn shr_i32(x: i32, amount: u32) -> i32 {
+    x.checked_shr(amount).unwrap_or(x >> 31)
+}
@@ -8,8 +8,13 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::default::{F32x4, I32x4, U32x4};
+use crate::default::{F32x2, F32x4, F32x8, F64x2, F64x4, I16x8, I32x2, I32x4, U16x8, U32x2, U32x4};
+use crate::default::U8x16;
+use crate::dispatch::{self, SimdLevel};
 use crate::scalar::F32x4 as F32x4S;
+use crate::scalar::F32x8 as F32x8S;
+use crate::scalar::U8x16 as U8x16S;
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
 
 // F32x4
 
@@ -21,6 +26,34 @@ fn test_f32x4_constructors() {
     assert_eq!(b, F32x4::new(10.0, 10.0, 10.0, 10.0));
 }
 
+#[repr(align(16))]
+struct Aligned<T>([T; 8]);
+
+#[test]
+fn test_f32x4_slices() {
+    let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    assert_eq!(F32x4::from_slice(&values), F32x4::new(1.0, 2.0, 3.0, 4.0));
+    assert_eq!(F32x4::from_slice(&values[1..]), F32x4::new(2.0, 3.0, 4.0, 5.0));
+    let mut output = [0.0; 6];
+    F32x4::new(7.0, 8.0, 9.0, 10.0).write_to_slice(&mut output[1..]);
+    assert_eq!(output, [0.0, 7.0, 8.0, 9.0, 10.0, 0.0]);
+
+    let mut aligned = Aligned([1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+    unsafe {
+        let a = F32x4::from_slice_aligned(&aligned.0);
+        let b = F32x4::from_slice_aligned(&aligned.0[4..]);
+        assert_eq!((a, b), (F32x4::new(1.0, 2.0, 3.0, 4.0), F32x4::new(5.0, 6.0, 7.0, 8.0)));
+        (a + b).write_to_slice_aligned(&mut aligned.0[4..]);
+    }
+    assert_eq!(aligned.0, [1.0, 2.0, 3.0, 4.0, 6.0, 8.0, 10.0, 12.0]);
+}
+
+#[test]
+#[should_panic]
+fn test_f32x4_from_short_slice() {
+    F32x4::from_slice(&[1.0, 2.0, 3.0]);
+}
+
 #[test]
 fn test_f32x4_accessors_and_mutators() {
     let a = F32x4::new(5.0, 6.0, 7.0, 8.0);
@@ -50,6 +83,296 @@ fn test_f32x4_basic_ops() {
     assert_eq!(d.sqrt(), F32x4::new(1.0, 1.4142135, 1.7320508, 2.0));
 }
 
+#[test]
+fn test_f32x4_rounding() {
+    let a = F32x4::new(-2.5, -1.5, 0.5, 2.5);
+    assert_eq!(a.round(), F32x4::new(-2.0, -2.0, 0.0, 2.0));
+    assert_eq!(a.round(), a.to_i32x4_round().to_f32x4());
+    assert_eq!(a.fract(), F32x4::splat(0.5));
+    let b = F32x4::new(-3.75, -0.25, 1.25, 3.0);
+    assert_eq!(b.round(), F32x4::new(-4.0, -0.0, 1.0, 3.0));
+    assert_eq!(b.fract(), F32x4::new(0.25, 0.75, 0.25, 0.0));
+    assert_eq!(b.floor() + b.fract(), b);
+}
+
+#[test]
+fn test_f32x4_signs() {
+    let a = F32x4::new(1.5, -2.0, 0.0, -0.0);
+    assert_eq!(a.sign_bits(), 0b1010);
+    assert_eq!(F32x4::new(-1.0, -1.0, -1.0, -1.0).sign_bits(), 0b1111);
+    assert_eq!(F32x4::new(f32::NAN, -f32::NAN, f32::INFINITY, f32::NEG_INFINITY).sign_bits(),
+               0b1010);
+
+    let signs = F32x4::new(-1.0, 1.0, -0.0, 3.0);
+    assert_eq!(a.copysign(signs), F32x4::new(-1.5, 2.0, -0.0, 0.0));
+    assert_eq!(a.copysign(signs).sign_bits(), signs.sign_bits());
+    assert_eq!(F32x4::splat(1.0).copysign(a), F32x4::new(1.0, -1.0, 1.0, -1.0));
+    assert_eq!(a.abs().sign_bits(), 0);
+}
+
+#[test]
+fn test_i32x4_shifts() {
+    let a = I32x4::new(1, -8, 0x4000_0000, -1);
+    assert_eq!(a << 2, I32x4::new(4, -32, 0, -4));
+    assert_eq!(a >> 2, I32x4::new(0, -2, 0x1000_0000, -1));
+    assert_eq!(a << 32, I32x4::splat(0));
+    assert_eq!(a >> 40, I32x4::new(0, -1, 0, -1));
+
+    let amounts = I32x4::new(0, 1, 30, 33);
+    assert_eq!(a << amounts, I32x4::new(1, -16, 0, 0));
+    assert_eq!(a >> amounts, I32x4::new(1, -4, 1, -1));
+    // Amounts are unsigned, so negative ones shift every bit out.
+    assert_eq!(a << I32x4::splat(-1), I32x4::splat(0));
+    assert_eq!(a >> I32x4::splat(-1), I32x4::new(0, -1, 0, -1));
+}
+
+#[test]
+fn test_f32x4_shuffle() {
+    let a = F32x4::new(1.0, 2.0, 3.0, 4.0);
+    assert_eq!(a.shuffle::<0, 1, 2, 3>(), a);
+    assert_eq!(a.shuffle::<3, 2, 1, 0>(), F32x4::new(4.0, 3.0, 2.0, 1.0));
+    assert_eq!(a.shuffle::<1, 1, 3, 0>(), F32x4::new(2.0, 2.0, 4.0, 1.0));
+    assert_eq!(a.shuffle::<2, 3, 0, 1>(), a.zwxy());
+    assert_eq!(a.shuffle::<0, 0, 1, 1>(), a.xxyy());
+    assert_eq!(a.shuffle::<3, 0, 1, 2>(), a.wxyz());
+}
+
+#[test]
+fn test_i32x4_shuffle() {
+    let a = I32x4::new(-1, 2, -3, 4);
+    assert_eq!(a.shuffle::<0, 1, 2, 3>(), a);
+    assert_eq!(a.shuffle::<3, 3, 3, 3>(), I32x4::splat(4));
+    assert_eq!(a.shuffle::<2, 0, 3, 1>(), I32x4::new(-3, -1, 4, 2));
+    assert_eq!(a.shuffle::<1, 0, 3, 2>(), a.yxwz());
+}
+
+#[test]
+fn test_i32x4_slices() {
+    let values = [-1, 2, -3, 4, -5];
+    assert_eq!(I32x4::from_slice(&values[1..]), I32x4::new(2, -3, 4, -5));
+    let mut output = [0; 5];
+    I32x4::new(6, 7, 8, 9).write_to_slice(&mut output);
+    assert_eq!(output, [6, 7, 8, 9, 0]);
+
+    let mut aligned = Aligned([0, 1, 2, 3, 4, 5, 6, 7]);
+    unsafe {
+        let a = I32x4::from_slice_aligned(&aligned.0[4..]);
+        assert_eq!(a, I32x4::new(4, 5, 6, 7));
+        (a << 1).write_to_slice_aligned(&mut aligned.0);
+    }
+    assert_eq!(aligned.0, [8, 10, 12, 14, 4, 5, 6, 7]);
+}
+
+#[test]
+fn test_f32x4_mul_add() {
+    let a = F32x4::new(1.0, -2.0, 3.0, 0.5);
+    let b = F32x4::new(4.0, 5.0, -6.0, 8.0);
+    let c = F32x4::new(0.5, 1.0, 2.0, -4.0);
+    assert_eq!(a.mul_add(b, c), F32x4::new(4.5, -9.0, -16.0, 0.0));
+    let d = F32x8::new(1.0, -2.0, 3.0, 0.5, 2.0, 0.0, -1.0, 10.0);
+    assert_eq!(d.mul_add(F32x8::splat(2.0), F32x8::splat(1.0)),
+               F32x8::new(3.0, -3.0, 7.0, 2.0, 5.0, 1.0, -1.0, 21.0));
+
+    // With fused multiply-add, the product isn't rounded before the add, so this comes out to
+    // the tiny rounding error of the product instead of zero.
+    let e = F32x4::splat(1.0 + f32::EPSILON);
+    let product = e * e;
+    let error = e.mul_add(e, -product);
+    if cfg!(all(any(target_feature = "fma", target_arch = "aarch64"),
+                not(feature = "pf-portable-simd"))) {
+        assert_eq!(error, F32x4::splat(f32::EPSILON * f32::EPSILON));
+    } else {
+        assert_eq!(error, F32x4::default());
+    }
+}
+
+#[test]
+fn test_f32x4_lerp_and_clamp() {
+    let a = F32x4::new(0.0, 1.0, -2.0, 10.0);
+    let b = F32x4::new(1.0, 3.0, 2.0, 10.0);
+    assert_eq!(a.lerp(b, F32x4::splat(0.0)), a);
+    assert_eq!(a.lerp(b, F32x4::splat(1.0)), b);
+    assert_eq!(a.lerp(b, F32x4::new(0.5, 0.25, 0.75, 0.5)), F32x4::new(0.5, 1.5, 1.0, 10.0));
+    assert_eq!(a.lerp(b, F32x4::splat(2.0)), F32x4::new(2.0, 5.0, 6.0, 10.0));
+
+    let t = F32x4::new(-0.5, 0.25, 1.5, 1.0);
+    assert_eq!(t.clamp(F32x4::default(), F32x4::splat(1.0)), F32x4::new(0.0, 0.25, 1.0, 1.0));
+}
+
+#[test]
+fn test_f32x4_reciprocal_estimates() {
+    // NEON estimates are only accurate to about 8 bits; SSE ones to about 12.
+    fn assert_close(actual: F32x4, expected: [f32; 4]) {
+        for (index, &expected) in expected.iter().enumerate() {
+            assert!(((actual[index] - expected) / expected).abs() < 1.0 / 256.0,
+                    "{} isn't close to {}", actual[index], expected);
+        }
+    }
+    let a = F32x4::new(1.0, 3.0, 0.25, 1000.0);
+    assert_close(a.approx_recip(), [1.0, 1.0 / 3.0, 4.0, 0.001]);
+    assert_close(a.approx_rsqrt(), [1.0, 0.57735026, 2.0, 0.031622775]);
+    assert_eq!(a.sqrt(), F32x4::new(1.0, 1.7320508, 0.5, 31.622776));
+
+    let b = F32x8::new(1.0, 4.0, 16.0, 64.0, 0.0625, 9.0, 100.0, 2.0);
+    let (low, high) = (b.approx_rsqrt().lo(), b.approx_rsqrt().hi());
+    assert_close(low, [1.0, 0.5, 0.25, 0.125]);
+    assert_close(high, [4.0, 1.0 / 3.0, 0.1, 0.70710677]);
+    assert!(F32x4::splat(0.0).approx_rsqrt()[0].is_infinite());
+}
+
+const CROSS_OPERANDS: [([f32; 4], [f32; 4]); 4] = [
+    ([1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0]),
+    ([2.0, -3.0, 4.0, 5.0], [-6.0, 7.0, 8.0, -9.0]),
+    ([0.5, 0.25, -1.5, 0.0], [0.5, 0.25, -1.5, 0.0]),
+    ([10.0, 20.0, 30.0, f32::NAN], [-1.0, 4.0, 2.5, f32::INFINITY]),
+];
+
+fn naive_cross(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0], 0.0]
+}
+
+#[test]
+fn test_f32x4_sin_cos() {
+    let mut max_error: f64 = 0.0;
+    for index in -250_000..=250_000 {
+        let x = index as f32 * 0.04 + 0.013;
+        let (sin, cos) = F32x4::splat(x).sin_cos();
+        max_error = max_error.max((sin[0] as f64 - (x as f64).sin()).abs())
+                             .max((cos[0] as f64 - (x as f64).cos()).abs());
+    }
+    assert!(max_error <= 1.5e-7);
+
+    let x = F32x4::new(0.0, FRAC_PI_2, PI, -FRAC_PI_2);
+    assert_eq!(x.sin()[0], 0.0);
+    assert_eq!(x.cos()[0], 1.0);
+    assert_eq!(x.sin()[1], 1.0);
+    assert_eq!(x.sin()[3], -1.0);
+    assert_eq!(x.cos()[2], -1.0);
+    let special = F32x4::new(f32::INFINITY, f32::NEG_INFINITY, f32::NAN, 1.0).sin();
+    assert!(special[0].is_nan() && special[1].is_nan() && special[2].is_nan());
+}
+
+#[test]
+fn test_f32x4_atan() {
+    let mut max_error: f64 = 0.0;
+    for index in -100_000..=100_000 {
+        let x = (index as f32 * 0.001).powi(3);
+        let atan = F32x4::splat(x).atan()[0];
+        max_error = max_error.max((atan as f64 - (x as f64).atan()).abs());
+    }
+    assert!(max_error <= 2.0e-7);
+
+    let x = F32x4::new(f32::INFINITY, f32::NEG_INFINITY, -0.0, 1.0).atan();
+    assert_eq!((x[0], x[1], x[3]), (FRAC_PI_2, -FRAC_PI_2, FRAC_PI_4));
+    assert!(x[2] == 0.0 && x[2].is_sign_negative());
+}
+
+#[test]
+fn test_f32x4_atan2() {
+    let mut max_error: f64 = 0.0;
+    for step in 0..=20_000 {
+        let theta = (step as f64 / 10_000.0 - 1.0) * std::f64::consts::PI;
+        for &radius in &[1.0e-3, 1.0, 7.5, 1.0e6] {
+            let (x, y) = ((theta.cos() * radius) as f32, (theta.sin() * radius) as f32);
+            let atan2 = F32x4::splat(y).atan2(F32x4::splat(x))[0];
+            max_error = max_error.max((atan2 as f64 - (y as f64).atan2(x as f64)).abs());
+        }
+    }
+    assert!(max_error <= 4.0e-7);
+
+    // Zeros, infinities, and NaNs behave as `f32::atan2()` does.
+    let values = [0.0, -0.0, 1.0, -1.0, f32::INFINITY, f32::NEG_INFINITY];
+    for &y in &values {
+        for &x in &values {
+            let (expected, actual) = (y.atan2(x), F32x4::splat(y).atan2(F32x4::splat(x))[0]);
+            assert!((expected - actual).abs() <= 4.0e-7, "atan2({}, {}) = {}", y, x, actual);
+            assert_eq!(expected.is_sign_negative(), actual.is_sign_negative());
+        }
+    }
+    let nans = F32x4::new(f32::NAN, 1.0, f32::NAN, 0.0).atan2(F32x4::new(1.0, f32::NAN, 0.0, 0.0));
+    assert!(nans[0].is_nan() && nans[1].is_nan() && nans[2].is_nan() && nans[3] == 0.0);
+}
+
+#[test]
+fn test_f32x4_cross() {
+    for &(a, b) in &CROSS_OPERANDS {
+        let expected = naive_cross(a, b);
+        let (a, b) = (F32x4::new(a[0], a[1], a[2], a[3]), F32x4::new(b[0], b[1], b[2], b[3]));
+        assert_eq!(a.cross(b), F32x4::new(expected[0], expected[1], expected[2], expected[3]));
+    }
+}
+
+#[test]
+fn test_f32x4_arithmetic_operators() {
+    let a = F32x4::new(1.0, -2.0, 3.0, -4.0);
+    let b = F32x4::new(2.0, 4.0, -8.0, 16.0);
+    assert_eq!(-a, F32x4::new(-1.0, 2.0, -3.0, 4.0));
+    assert_eq!(a * 2.0, F32x4::new(2.0, -4.0, 6.0, -8.0));
+    assert_eq!(b / 2.0, F32x4::new(1.0, 2.0, -4.0, 8.0));
+    assert_eq!(a / b, F32x4::new(0.5, -0.5, -0.375, -0.25));
+
+    let mut c = a;
+    c += b;
+    assert_eq!(c, a + b);
+    c -= a;
+    assert_eq!(c, b);
+    c *= a;
+    assert_eq!(c, a * b);
+    c /= b;
+    assert_eq!(c, a);
+    c *= 4.0;
+    c /= 2.0;
+    assert_eq!(c, a * 2.0);
+}
+
+#[test]
+fn test_f32x4_conversions_with_rounding_modes() {
+    let a = F32x4::new(-2.5, -1.5, 0.5, 2.5);
+    assert_eq!(a.to_i32x4_round(), I32x4::new(-2, -2, 0, 2));
+    assert_eq!(a.to_i32x4_truncate(), I32x4::new(-2, -1, 0, 2));
+    assert_eq!(a.to_i32x4_floor(), I32x4::new(-3, -2, 0, 2));
+    assert_eq!(a.to_i32x4(), a.to_i32x4_round());
+    let b = F32x4::new(-3.7, -0.2, 1.3, 3.5);
+    assert_eq!(b.to_i32x4_round(), I32x4::new(-4, 0, 1, 4));
+    assert_eq!(b.to_i32x4_truncate(), I32x4::new(-3, 0, 1, 3));
+    assert_eq!(b.to_i32x4_floor(), I32x4::new(-4, -1, 1, 3));
+
+    // Integers survive the round trip through floats in every mode.
+    let c = I32x4::new(-16777216, -7, 0, 16777216);
+    assert_eq!(c.to_f32x4().to_i32x4_round(), c);
+    assert_eq!(c.to_f32x4().to_i32x4_truncate(), c);
+    assert_eq!(c.to_f32x4().to_i32x4_floor(), c);
+}
+
+#[test]
+fn test_f32x4_conversions_match_scalar() {
+    for step in -40..40 {
+        let value = step as f32 * 0.25;
+        let (a, b) = (F32x4::new(value, -value, value + 0.1, value - 0.1),
+                      F32x4S::new(value, -value, value + 0.1, value - 0.1));
+        for &(result, expected) in &[
+            (a.to_i32x4_round(), b.to_i32x4_round()),
+            (a.to_i32x4_truncate(), b.to_i32x4_truncate()),
+            (a.to_i32x4_floor(), b.to_i32x4_floor()),
+        ] {
+            assert_eq!((result[0], result[1], result[2], result[3]),
+                       (expected[0], expected[1], expected[2], expected[3]));
+        }
+    }
+}
+
+#[test]
+fn test_f32x4_horizontal_operations() {
+    let a = F32x4::new(3.0, -1.5, 8.0, 0.25);
+    assert_eq!(a.sum(), 9.75);
+    assert_eq!(a.min_element(), -1.5);
+    assert_eq!(a.max_element(), 8.0);
+    assert_eq!(a.dot(F32x4::new(2.0, 2.0, 0.5, -4.0)), 6.0);
+    assert_eq!(F32x4::splat(-7.0).min_element(), -7.0);
+    assert_eq!(F32x4::new(1.0, 2.0, 3.0, 4.0).max_element(), 4.0);
+    assert_eq!(F32x4::new(4.0, 3.0, 2.0, 1.0).min_element(), 1.0);
+}
+
 #[test]
 fn test_f32x4_packed_comparisons() {
     let a = F32x4::new(7.0, 3.0, 6.0, -2.0);
@@ -57,6 +380,22 @@ fn test_f32x4_packed_comparisons() {
     assert_eq!(a.packed_eq(b), U32x4::new(0, !0, 0, !0));
     assert_eq!(a.packed_gt(b), U32x4::new(0, 0, !0, 0));
     assert_eq!(a.packed_le(b), U32x4::new(!0, !0, 0, !0));
+    assert_eq!(a.packed_lt(b), U32x4::new(!0, 0, 0, 0));
+    assert_eq!(a.packed_ge(b), U32x4::new(0, !0, !0, !0));
+    assert_eq!(a.packed_ne(b), U32x4::new(!0, 0, !0, 0));
+}
+
+#[test]
+fn test_f32x4_packed_comparisons_with_nan() {
+    // Every comparison with NaN is false, except `!=`.
+    let a = F32x4::new(f32::NAN, 1.0, f32::NAN, 0.0);
+    let b = F32x4::new(1.0, f32::NAN, f32::NAN, -0.0);
+    assert_eq!(a.packed_eq(b), U32x4::new(0, 0, 0, !0));
+    assert_eq!(a.packed_gt(b), U32x4::new(0, 0, 0, 0));
+    assert_eq!(a.packed_lt(b), U32x4::new(0, 0, 0, 0));
+    assert_eq!(a.packed_le(b), U32x4::new(0, 0, 0, !0));
+    assert_eq!(a.packed_ge(b), U32x4::new(0, 0, 0, !0));
+    assert_eq!(a.packed_ne(b), U32x4::new(!0, !0, !0, 0));
 }
 
 #[test]
@@ -378,6 +717,157 @@ fn test_f32x4_debug() {
     assert_eq!("<48, -4, 200, 7>", format!("{:?}", a));
 }
 
+// F32x2
+
+#[test]
+fn test_f32x2_signs() {
+    let a = F32x2::new(-1.5, 0.0);
+    assert_eq!(a.sign_bits(), 0b01);
+    assert_eq!(F32x2::new(0.5, -0.0).sign_bits(), 0b10);
+    assert_eq!(a.copysign(F32x2::new(1.0, -1.0)), F32x2::new(1.5, -0.0));
+    assert_eq!(F32x2::splat(2.0).copysign(a), F32x2::new(-2.0, 2.0));
+    assert_eq!(a.abs().sign_bits(), 0);
+}
+
+#[test]
+fn test_f32x2_horizontal_operations() {
+    let a = F32x2::new(3.0, -1.5);
+    assert_eq!(a.sum(), 1.5);
+    assert_eq!(a.min_element(), -1.5);
+    assert_eq!(a.max_element(), 3.0);
+    assert_eq!(a.dot(F32x2::new(2.0, 4.0)), 0.0);
+    assert_eq!(a.yx().min_element(), -1.5);
+    assert_eq!(a.yx().max_element(), 3.0);
+}
+
+#[test]
+fn test_f32x2_packed_comparisons_and_select() {
+    let a = F32x2::new(7.0, 3.0);
+    let b = F32x2::new(10.0, 3.0);
+    assert_eq!(a.packed_eq(b), U32x2::new(0, !0));
+    assert_eq!(a.packed_gt(b), U32x2::new(0, 0));
+    assert_eq!(a.packed_lt(b), U32x2::new(!0, 0));
+    assert_eq!(a.packed_le(b), U32x2::new(!0, !0));
+    assert_eq!(a.packed_ge(b), U32x2::new(0, !0));
+    assert_eq!(a.packed_ne(b), U32x2::new(!0, 0));
+    assert_eq!(F32x2::new(f32::NAN, 1.0).packed_ne(F32x2::new(f32::NAN, 1.0)), U32x2::new(!0, 0));
+
+    assert_eq!(F32x2::select(U32x2::new(!0, 0), a, b), F32x2::new(7.0, 3.0));
+    assert_eq!(F32x2::select(U32x2::new(0, !0), a, -b), F32x2::new(-10.0, 3.0));
+    assert_eq!(F32x2::select(a.packed_lt(b), -a, a), F32x2::new(-7.0, 3.0));
+}
+
+// F32x8
+
+#[test]
+fn test_f32x8_constructors() {
+    let a = F32x8::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0);
+    assert_eq!((a[0], a[3], a[4], a[7]), (1.0, 4.0, 5.0, 8.0));
+    assert_eq!(a.lo(), F32x4::new(1.0, 2.0, 3.0, 4.0));
+    assert_eq!(a.hi(), F32x4::new(5.0, 6.0, 7.0, 8.0));
+    assert_eq!(F32x8::from_halves(a.lo(), a.hi()), a);
+    assert_eq!(F32x8::splat(10.0), F32x8::from_halves(F32x4::splat(10.0), F32x4::splat(10.0)));
+    let mut b = F32x8::default();
+    b[6] = 3.0;
+    assert_eq!(b, F32x8::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 3.0, 0.0));
+}
+
+#[test]
+fn test_f32x8_basic_ops() {
+    let a = F32x8::new(1.0, 3.0, 5.0, 7.0, -1.0, -3.0, -5.0, -7.0);
+    let b = F32x8::new(2.0, 2.0, 6.0, 6.0, -2.0, -2.0, -6.0, -6.0);
+    assert_eq!(a.min(b), F32x8::new(1.0, 2.0, 5.0, 6.0, -2.0, -3.0, -6.0, -7.0));
+    assert_eq!(a.max(b), F32x8::new(2.0, 3.0, 6.0, 7.0, -1.0, -2.0, -5.0, -6.0));
+    let c = F32x8::new(-1.0, 1.3, -20.0, 3.6, 0.5, -0.5, 9.0, -9.9);
+    assert_eq!(c.clamp(F32x8::splat(-1.0), F32x8::splat(1.0)),
+               F32x8::new(-1.0, 1.0, -1.0, 1.0, 0.5, -0.5, 1.0, -1.0));
+    assert_eq!(c.abs(), F32x8::new(1.0, 1.3, 20.0, 3.6, 0.5, 0.5, 9.0, 9.9));
+    assert_eq!(c.floor(), F32x8::new(-1.0, 1.0, -20.0, 3.0, 0.0, -1.0, 9.0, -10.0));
+    assert_eq!(c.ceil(), F32x8::new(-1.0, 2.0, -20.0, 4.0, 1.0, -0.0, 9.0, -9.0));
+    assert_eq!(c.round(), F32x8::new(-1.0, 1.0, -20.0, 4.0, 0.0, -0.0, 9.0, -10.0));
+    let e = F32x8::new(-3.75, -0.25, 1.25, 3.0, 0.5, -0.5, 9.0, -9.5);
+    assert_eq!(e.fract(), F32x8::new(0.25, 0.75, 0.25, 0.0, 0.5, 0.5, 0.0, 0.5));
+    let d = F32x8::new(1.0, 4.0, 9.0, 16.0, 25.0, 36.0, 49.0, 64.0);
+    assert_eq!(d.sqrt(), F32x8::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0));
+    let error = (d.approx_recip() * d - F32x8::splat(1.0)).abs();
+    assert_eq!(error.min(F32x8::splat(0.001)), error);
+}
+
+#[test]
+fn test_f32x8_arithmetic() {
+    let a = F32x8::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0);
+    let b = F32x8::splat(2.0);
+    assert_eq!(a + b, F32x8::new(3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0));
+    assert_eq!(a - b, F32x8::new(-1.0, 0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0));
+    assert_eq!(a * b, F32x8::new(2.0, 4.0, 6.0, 8.0, 10.0, 12.0, 14.0, 16.0));
+    assert_eq!(a / b, F32x8::new(0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.5, 4.0));
+    assert_eq!(-a, F32x8::new(-1.0, -2.0, -3.0, -4.0, -5.0, -6.0, -7.0, -8.0));
+    let mut c = a;
+    c += b;
+    c *= b;
+    c -= a;
+    assert_eq!(c, F32x8::new(5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0));
+}
+
+#[test]
+fn test_f32x8_debug() {
+    let a = F32x8::new(48.0, -4.0, 200.0, 7.0, 1.0, 2.0, 3.0, 4.0);
+    assert_eq!("<48, -4, 200, 7, 1, 2, 3, 4>", format!("{:?}", a));
+}
+
+// F64x2 and F64x4
+
+#[test]
+fn test_f64x2_basic_ops() {
+    // Coordinates in the millions keep their fractional parts, unlike in `F32x2`.
+    let a = F64x2::new(30_000_000.25, -1.5);
+    let b = F64x2::new(1.0, 2.5);
+    assert_eq!(a + b, F64x2::new(30_000_001.25, 1.0));
+    assert_eq!(a - b, F64x2::new(29_999_999.25, -4.0));
+    assert_eq!(a * b, F64x2::new(30_000_000.25, -3.75));
+    assert_eq!(a / F64x2::splat(0.5), F64x2::new(60_000_000.5, -3.0));
+    assert_eq!(a.min(b), F64x2::new(1.0, -1.5));
+    assert_eq!(a.max(b), F64x2::new(30_000_000.25, 2.5));
+    assert_eq!(a.abs(), F64x2::new(30_000_000.25, 1.5));
+    assert_eq!(a.floor(), F64x2::new(30_000_000.0, -2.0));
+    assert_eq!(a.ceil(), F64x2::new(30_000_001.0, -1.0));
+    assert_eq!(F64x2::new(2.5, -3.5).round(), F64x2::new(2.0, -4.0));
+    assert_eq!(F64x2::new(16.0, 0.25).sqrt(), F64x2::new(4.0, 0.5));
+    assert_eq!(a.mul_add(b, b), F64x2::new(30_000_001.25, -1.25));
+    assert_eq!(a.dot(b), 29_999_996.5);
+    assert_eq!(a.yx(), F64x2::new(-1.5, 30_000_000.25));
+    assert_eq!(-b, F64x2::new(-1.0, -2.5));
+
+    assert_eq!(a.to_f32x2(), F32x2::new(30_000_000.0, -1.5));
+    assert_eq!(F64x2::from_f32x2(F32x2::new(0.1, 2.0)).x(), 0.1f32 as f64);
+}
+
+#[test]
+fn test_f64x4_basic_ops() {
+    let a = F64x4::new(1.0, -2.0, 1.0e9 + 0.5, 4.0);
+    let b = F64x4::from_halves(F64x2::new(2.0, 2.0), F64x2::new(0.5, -1.0));
+    assert_eq!(b, F64x4::new(2.0, 2.0, 0.5, -1.0));
+    assert_eq!(a.lo(), F64x2::new(1.0, -2.0));
+    assert_eq!(a.hi(), F64x2::new(1.0e9 + 0.5, 4.0));
+    assert_eq!(a + b, F64x4::new(3.0, 0.0, 1.0e9 + 1.0, 3.0));
+    assert_eq!(a - b, F64x4::new(-1.0, -4.0, 1.0e9, 5.0));
+    assert_eq!(a * b, F64x4::new(2.0, -4.0, 5.0e8 + 0.25, -4.0));
+    assert_eq!(a / b, F64x4::new(0.5, -1.0, 2.0e9 + 1.0, -4.0));
+    assert_eq!(a.min(b), F64x4::new(1.0, -2.0, 0.5, -1.0));
+    assert_eq!(a.max(b), F64x4::new(2.0, 2.0, 1.0e9 + 0.5, 4.0));
+    assert_eq!(a.abs(), F64x4::new(1.0, 2.0, 1.0e9 + 0.5, 4.0));
+    assert_eq!(a.round(), F64x4::new(1.0, -2.0, 1.0e9, 4.0));
+    assert_eq!(a.floor(), F64x4::new(1.0, -2.0, 1.0e9, 4.0));
+    assert_eq!(a.ceil(), F64x4::new(1.0, -2.0, 1.0e9 + 1.0, 4.0));
+    assert_eq!(a.mul_add(b, a), F64x4::new(3.0, -6.0, 1.5e9 + 0.75, 0.0));
+    assert_eq!(a.shuffle::<3, 2, 1, 0>(), F64x4::new(4.0, 1.0e9 + 0.5, -2.0, 1.0));
+    assert_eq!(a.shuffle::<0, 0, 2, 2>(), F64x4::new(1.0, 1.0, 1.0e9 + 0.5, 1.0e9 + 0.5));
+    assert_eq!(F64x4::splat(9.0).sqrt(), F64x4::splat(3.0));
+    assert_eq!(a.to_f32x4(), F32x4::new(1.0, -2.0, 1.0e9, 4.0));
+    assert_eq!(F64x4::from_f32x4(F32x4::new(1.0, -2.0, 0.5, 4.0)),
+               F64x4::new(1.0, -2.0, 0.5, 4.0));
+}
+
 // I32x4
 
 #[test]
@@ -395,11 +885,25 @@ fn test_i32x4_basic_ops() {
     assert_eq!(a.min(b), I32x4::new(6, -5, -40, 2));
 }
 
+#[test]
+fn test_i32x4_horizontal_operations() {
+    let a = I32x4::new(59, -1, 5, 63);
+    assert_eq!(a.min_element(), -1);
+    assert_eq!(a.max_element(), 63);
+    assert_eq!(I32x4::new(i32::MIN, 0, 0, i32::MAX).min_element(), i32::MIN);
+    assert_eq!(I32x4::new(i32::MIN, 0, 0, i32::MAX).max_element(), i32::MAX);
+}
+
 #[test]
 fn test_i32x4_packed_comparisons() {
     let a = I32x4::new(59, 1, 5, 63);
     let b = I32x4::new(-59, 1, 5, 104);
     assert_eq!(a.packed_eq(b), U32x4::new(0, !0, !0, 0));
+    assert_eq!(a.packed_gt(b), U32x4::new(!0, 0, 0, 0));
+    assert_eq!(a.packed_lt(b), U32x4::new(0, 0, 0, !0));
+    assert_eq!(a.packed_le(b), U32x4::new(0, !0, !0, !0));
+    assert_eq!(a.packed_ge(b), U32x4::new(!0, !0, !0, 0));
+    assert_eq!(a.packed_ne(b), U32x4::new(!0, 0, 0, !0));
 }
 
 #[test]
@@ -667,6 +1171,294 @@ fn test_i32x4_swizzles() {
     assert_eq!(a.zwww(), I32x4::new(3, 4, 4, 4));
 }
 
+// I32x2 and U32x2
+
+#[test]
+fn test_i32x2_basic_ops() {
+    let a = I32x2::new(59, -1);
+    let b = I32x2::new(-59, -1);
+    assert_eq!(a.min_element(), -1);
+    assert_eq!(a.max_element(), 59);
+    assert_eq!(a.packed_eq(b), U32x2::new(0, !0));
+    assert_eq!(a.packed_gt(b), U32x2::new(!0, 0));
+    assert_eq!(a.packed_lt(b), U32x2::new(0, 0));
+    assert_eq!(a.packed_le(b), U32x2::new(0, !0));
+    assert_eq!(a.packed_ge(b), U32x2::new(!0, !0));
+    assert_eq!(a.packed_ne(b), U32x2::new(!0, 0));
+
+    assert_eq!(a & I32x2::splat(0xf), I32x2::new(11, 15));
+    assert_eq!(a | I32x2::new(4, 0), I32x2::new(63, -1));
+    assert_eq!(a << 2, I32x2::new(236, -4));
+    assert_eq!(a >> 2, I32x2::new(14, -1));
+    assert_eq!(a << 32, I32x2::splat(0));
+    assert_eq!(a >> 40, I32x2::new(0, -1));
+
+    assert_eq!(a.to_u32x2(), U32x2::new(59, !0));
+    assert_eq!(a.to_u32x2().to_i32x2(), a);
+    assert_eq!(a.to_i32x4(), I32x4::new(59, -1, 0, 0));
+    assert_eq!(a.to_f32x4(), F32x4::new(59.0, -1.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_u32x2_masks() {
+    let a = U32x2::new(!0, 0);
+    let b = U32x2::new(!0, !0);
+    assert_eq!(a & b, a);
+    assert_eq!(a | b, b);
+    assert_eq!(a ^ b, U32x2::new(0, !0));
+    assert_eq!(!a, U32x2::new(0, !0));
+    assert_eq!(a[0], !0);
+    assert_eq!(a[1], 0);
+
+    assert!(a.any_true() && !a.all_true() && !a.all_false());
+    assert!((a & !a).all_false() && !(a & !a).any_true());
+    assert!(b.all_true() && b.any_true());
+}
+
+// U32x4
+
+#[test]
+fn test_u32x4_masks() {
+    let a = U32x4::new(!0, 0, !0, 0);
+    let b = U32x4::new(!0, !0, 0, 0);
+    assert_eq!(a & b, U32x4::new(!0, 0, 0, 0));
+    assert_eq!(a | b, U32x4::new(!0, !0, !0, 0));
+    assert_eq!(a ^ b, U32x4::new(0, !0, !0, 0));
+    assert_eq!(!a, U32x4::new(0, !0, 0, !0));
+    assert_eq!(U32x4::new(0xf0f0, 1, 2, 3) & U32x4::splat(0xff), U32x4::new(0xf0, 1, 2, 3));
+
+    assert!(a.any_true() && !a.all_true() && !a.all_false());
+    assert!((a & !a).all_false() && !(a & !a).any_true());
+    assert!((a | !a).all_true() && (a | !a).any_true());
+    assert!(U32x4::new(0, 0, 0, !0).any_true());
+}
+
+#[test]
+fn test_f32x4_select() {
+    let a = F32x4::new(1.0, 2.0, 3.0, 4.0);
+    let b = F32x4::new(-1.0, -2.0, -3.0, -4.0);
+    assert_eq!(F32x4::select(U32x4::new(!0, 0, 0, !0), a, b), F32x4::new(1.0, -2.0, -3.0, 4.0));
+    assert_eq!(F32x4::select(U32x4::splat(0), a, b), b);
+    assert_eq!(F32x4::select(U32x4::splat(!0), a, b), a);
+
+    // Clamps negative lanes to zero without branching.
+    let mask = b.packed_lt(F32x4::default()) ^ U32x4::new(0, !0, 0, 0);
+    assert_eq!(F32x4::select(mask, F32x4::default(), b), F32x4::new(0.0, -2.0, 0.0, 0.0));
+}
+
+// U8x16
+
+const BYTES: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 250, 251, 252, 253, 254, 255, 128, 127];
+
+#[test]
+fn test_u8x16_constructors() {
+    let a = U8x16::from_array(BYTES);
+    assert_eq!((a[0], a[7], a[8], a[15]), (0, 7, 250, 127));
+    assert_eq!(a.to_array(), BYTES);
+    assert_eq!(U8x16::splat(9).to_array(), [9; 16]);
+    assert_eq!(U8x16::default(), U8x16::splat(0));
+}
+
+#[test]
+fn test_u8x16_basic_ops() {
+    let a = U8x16::from_array(BYTES);
+    let b = U8x16::splat(6);
+    assert_eq!((a + b).to_array(),
+               [6, 7, 8, 9, 10, 11, 12, 13, 0, 1, 2, 3, 4, 5, 134, 133]);
+    assert_eq!((a - b).to_array(),
+               [250, 251, 252, 253, 254, 255, 0, 1, 244, 245, 246, 247, 248, 249, 122, 121]);
+    assert_eq!(a.min(b).to_array(), [0, 1, 2, 3, 4, 5, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6]);
+    assert_eq!(a.max(b)[3], 6);
+    assert_eq!(a.max(b)[8], 250);
+    assert_eq!((a & U8x16::splat(0x0f))[9], 0x0b);
+    assert_eq!((a | U8x16::splat(0x80))[1], 0x81);
+
+    let mask = a.packed_eq(b);
+    assert_eq!(mask[6], !0);
+    assert_eq!(mask[7], 0);
+    assert!(!mask.all_true() && !mask.all_false());
+    assert!(a.packed_eq(a).all_true());
+    assert!(a.packed_eq(a + U8x16::splat(1)).all_false());
+}
+
+#[test]
+fn test_u8x16_saturating_ops() {
+    let a = U8x16::from_array(BYTES);
+    let b = U8x16::splat(6);
+    assert_eq!(a.saturating_add(b).to_array(),
+               [6, 7, 8, 9, 10, 11, 12, 13, 255, 255, 255, 255, 255, 255, 134, 133]);
+    assert_eq!(a.saturating_sub(b).to_array(),
+               [0, 0, 0, 0, 0, 0, 0, 1, 244, 245, 246, 247, 248, 249, 122, 121]);
+    assert_eq!(a.saturating_add(U8x16::splat(255)), U8x16::splat(255));
+    assert_eq!(a.saturating_sub(U8x16::splat(255)), U8x16::splat(0));
+}
+
+#[test]
+fn test_u8x16_shuffle() {
+    let a = U8x16::from_array(BYTES);
+    let reverse = U8x16::from_array([15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+    assert_eq!(a.shuffle(reverse).to_array(),
+               [127, 128, 255, 254, 253, 252, 251, 250, 7, 6, 5, 4, 3, 2, 1, 0]);
+    assert_eq!(a.shuffle(U8x16::splat(8)), U8x16::splat(250));
+
+    // Only the low four bits select a byte, unless the high bit is set, which selects zero.
+    let indices = U8x16::from_array([0x10, 0x21, 0x72, 0x80, 0x81, 0xff, 0x8f, 0x7f,
+                                     0, 0, 0, 0, 0, 0, 0, 13]);
+    assert_eq!(a.shuffle(indices).to_array(),
+               [0, 1, 2, 0, 0, 0, 0, 127, 0, 0, 0, 0, 0, 0, 0, 255]);
+}
+
+#[test]
+fn test_u8x16_reinterpretation() {
+    let a = U8x16::from_array(BYTES);
+    let words = a.to_u32x4();
+    assert_eq!(words[0], u32::from_ne_bytes([0, 1, 2, 3]));
+    assert_eq!(words[3], u32::from_ne_bytes([254, 255, 128, 127]));
+    assert_eq!(words.to_u8x16(), a);
+}
+
+#[test]
+fn test_u8x16_debug() {
+    let a = U8x16::from_array(BYTES);
+    assert_eq!("<0, 1, 2, 3, 4, 5, 6, 7, 250, 251, 252, 253, 254, 255, 128, 127>",
+               format!("{:?}", a));
+}
+
+#[test]
+fn test_u8x16_widening_and_narrowing() {
+    let a = U8x16::from_array(BYTES);
+    let (low, high) = (a.widen_low(), a.widen_high());
+    assert_eq!(low.to_array(), [0, 1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(high.to_array(), [250, 251, 252, 253, 254, 255, 128, 127]);
+    assert_eq!(U8x16::narrow(low, high), a);
+
+    // Products of bytes don't wrap around until they're narrowed, which saturates.
+    let squares = high * high;
+    assert_eq!(squares[5], 65025);
+    assert_eq!((squares >> 8)[6], 64);
+    assert_eq!(U8x16::narrow(low * low, squares)[2], 4);
+    assert_eq!(U8x16::narrow(low * low, squares)[15], 255);
+    assert_eq!(U8x16::narrow(U16x8::splat(0xffff), low).to_array(),
+               [255, 255, 255, 255, 255, 255, 255, 255, 0, 1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn test_u16x8_basic_ops() {
+    let a = U16x8::from_array([0, 1, 2, 3, 1000, 40000, 65534, 65535]);
+    let b = U16x8::splat(2);
+    assert_eq!((a + b).to_array(), [2, 3, 4, 5, 1002, 40002, 0, 1]);
+    assert_eq!((a - b).to_array(), [65534, 65535, 0, 1, 998, 39998, 65532, 65533]);
+    assert_eq!((a * b).to_array(), [0, 2, 4, 6, 2000, 14464, 65532, 65534]);
+    assert_eq!((a >> 1).to_array(), [0, 0, 1, 1, 500, 20000, 32767, 32767]);
+    assert_eq!(a.min(b).to_array(), [0, 1, 2, 2, 2, 2, 2, 2]);
+    assert_eq!(a.max(b)[7], 65535);
+    assert_eq!(a.packed_eq(b).to_array(), [0, 0, !0, 0, 0, 0, 0, 0]);
+    assert_eq!(U16x8::default(), U16x8::splat(0));
+    assert_eq!("<0, 2, 4, 6, 2000, 14464, 65532, 65534>", format!("{:?}", a * b));
+}
+
+#[test]
+fn test_u16x8_saturating_ops_and_shifts() {
+    let a = U16x8::from_array([0, 1, 2, 3, 1000, 40000, 65534, 65535]);
+    let b = U16x8::splat(2);
+    assert_eq!(a.saturating_add(b).to_array(), [2, 3, 4, 5, 1002, 40002, 65535, 65535]);
+    assert_eq!(a.saturating_sub(b).to_array(), [0, 0, 0, 1, 998, 39998, 65532, 65533]);
+    assert_eq!((a << 1).to_array(), [0, 2, 4, 6, 2000, 14464, 65532, 65534]);
+    assert_eq!(a << 16, U16x8::splat(0));
+}
+
+#[test]
+fn test_u16x8_widening_and_narrowing() {
+    let a = U16x8::from_array([0, 1, 2, 3, 1000, 40000, 65534, 65535]);
+    let (low, high) = (a.widen_low(), a.widen_high());
+    assert_eq!(low, I32x4::new(0, 1, 2, 3));
+    assert_eq!(high, I32x4::new(1000, 40000, 65534, 65535));
+    assert_eq!(U16x8::narrow(low, high), a);
+    assert_eq!(U16x8::narrow(I32x4::new(-1, i32::MIN, 65536, i32::MAX), low).to_array(),
+               [0, 0, 65535, 65535, 0, 1, 2, 3]);
+
+    assert_eq!(a.to_i16x8().to_array(), [0, 1, 2, 3, 1000, -25536, -2, -1]);
+    assert_eq!(a.to_i16x8().to_u16x8(), a);
+}
+
+#[test]
+fn test_i16x8_basic_ops() {
+    let a = I16x8::from_array([0, 1, -2, 3, 1000, -1000, 32767, -32768]);
+    let b = I16x8::splat(-2);
+    assert_eq!((a[2], a[7]), (-2, -32768));
+    assert_eq!(I16x8::default(), I16x8::splat(0));
+    assert_eq!((a + b).to_array(), [-2, -1, -4, 1, 998, -1002, 32765, 32766]);
+    assert_eq!((a - b).to_array(), [2, 3, 0, 5, 1002, -998, -32767, -32766]);
+    assert_eq!((a * b).to_array(), [0, -2, 4, -6, -2000, 2000, 2, 0]);
+    assert_eq!(a.min(b).to_array(), [-2, -2, -2, -2, -2, -1000, -2, -32768]);
+    assert_eq!(a.max(b).to_array(), [0, 1, -2, 3, 1000, -2, 32767, -2]);
+    assert_eq!(a.packed_eq(b).to_array(), [0, 0, -1, 0, 0, 0, 0, 0]);
+    assert_eq!(a.packed_gt(b).to_array(), [-1, -1, 0, -1, -1, 0, -1, 0]);
+    assert_eq!(a.packed_lt(b).to_array(), [0, 0, 0, 0, 0, -1, 0, -1]);
+    assert_eq!("<0, 1, -2, 3, 1000, -1000, 32767, -32768>", format!("{:?}", a));
+}
+
+#[test]
+fn test_i16x8_saturating_ops_and_shifts() {
+    let a = I16x8::from_array([0, 1, -2, 3, 1000, -1000, 32767, -32768]);
+    let b = I16x8::splat(-2);
+    assert_eq!(a.saturating_add(b).to_array(), [-2, -1, -4, 1, 998, -1002, 32765, -32768]);
+    assert_eq!(a.saturating_sub(b).to_array(), [2, 3, 0, 5, 1002, -998, 32767, -32766]);
+    assert_eq!((a << 1).to_array(), [0, 2, -4, 6, 2000, -2000, -2, 0]);
+    assert_eq!((a >> 1).to_array(), [0, 0, -1, 1, 500, -500, 16383, -16384]);
+    assert_eq!(a << 16, I16x8::splat(0));
+    assert_eq!((a >> 16).to_array(), [0, 0, -1, 0, 0, -1, 0, -1]);
+}
+
+#[test]
+fn test_i16x8_widening_and_narrowing() {
+    let a = I16x8::from_array([0, 1, -2, 3, 1000, -1000, 32767, -32768]);
+    let (low, high) = (a.widen_low(), a.widen_high());
+    assert_eq!(low, I32x4::new(0, 1, -2, 3));
+    assert_eq!(high, I32x4::new(1000, -1000, 32767, -32768));
+    assert_eq!(I16x8::narrow(low, high), a);
+    assert_eq!(I16x8::narrow(I32x4::new(40000, -40000, i32::MAX, i32::MIN), low).to_array(),
+               [32767, -32768, 32767, -32768, 0, 1, -2, 3]);
+
+    // Bytes widened to 16 bits can go negative, and narrowing them back clamps to a byte.
+    let bytes = U8x16::from_array(BYTES);
+    let deltas = bytes.widen_high().to_i16x8() - bytes.widen_low().to_i16x8();
+    assert_eq!(deltas.to_array(), [250, 250, 250, 250, 250, 250, 122, 120]);
+    let negated = I16x8::splat(0) - deltas;
+    assert_eq!(U8x16::narrow_i16x8(deltas, negated).to_array(),
+               [250, 250, 250, 250, 250, 250, 122, 120, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(U8x16::narrow_i16x8(a, a).to_array(),
+               [0, 1, 0, 3, 255, 0, 255, 0, 0, 1, 0, 3, 255, 0, 255, 0]);
+}
+
+#[test]
+fn test_u8x16_pixel_math() {
+    fn expected_mul_alpha(x: u8, a: u8) -> u8 {
+        ((x as f32 * a as f32) / 255.0).round() as u8
+    }
+    for alpha in 0..=255 {
+        let result = U8x16::from_array(BYTES).mul_alpha(U8x16::splat(alpha)).to_array();
+        for (&x, &result) in BYTES.iter().zip(result.iter()) {
+            assert_eq!(result, expected_mul_alpha(x, alpha));
+        }
+    }
+    let all: Vec<u8> = (0..=255).collect();
+    for chunk in all.chunks(16) {
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(chunk);
+        let x = U8x16::from_array(bytes);
+        assert_eq!(x.mul_alpha(U8x16::splat(255)), x);
+        assert_eq!(x.mul_alpha(U8x16::splat(0)), U8x16::splat(0));
+        assert_eq!(U8x16::splat(255).mul_alpha(x), x);
+    }
+
+    let (a, b) = (U8x16::from_array(BYTES), U8x16::splat(100));
+    assert_eq!(a.lerp(b, U8x16::splat(0)), a);
+    assert_eq!(a.lerp(b, U8x16::splat(255)), b);
+    assert_eq!(U8x16::splat(0).lerp(U8x16::splat(255), U8x16::from_array(BYTES)), a);
+    assert_eq!(U8x16::splat(200).lerp(U8x16::splat(100), U8x16::splat(128))[0], 150);
+}
+
 // Scalar F32x4
 
 #[test]
@@ -689,3 +1481,117 @@ fn test_f32x4s_basic_ops() {
     assert_eq!(c.ceil(), F32x4S::new(-1.0, 2.0, -20.0, 4.0));
     assert_eq!(c.to_i32x4().to_f32x4(), F32x4S::new(-1.0, 1.0, -20.0, 4.0));
 }
+
+#[test]
+fn test_f32x4s_cross() {
+    for &(a, b) in &CROSS_OPERANDS {
+        assert_eq!(F32x4S(a).cross(F32x4S(b)), F32x4S(naive_cross(a, b)));
+    }
+}
+
+#[test]
+fn test_f32x4s_conversions_with_rounding_modes() {
+    let a = F32x4S::new(-2.5, -1.5, 0.5, 2.5);
+    assert_eq!(a.to_i32x4_round().to_f32x4(), F32x4S::new(-2.0, -2.0, 0.0, 2.0));
+    assert_eq!(a.to_i32x4_truncate().to_f32x4(), F32x4S::new(-2.0, -1.0, 0.0, 2.0));
+    assert_eq!(a.to_i32x4_floor().to_f32x4(), F32x4S::new(-3.0, -2.0, 0.0, 2.0));
+    assert_eq!(a.round(), F32x4S::new(-2.0, -2.0, 0.0, 2.0));
+    assert_eq!(a.fract(), F32x4S::splat(0.5));
+    let b = F32x4S::new(-3.7, -0.2, 1.3, 3.5);
+    assert_eq!(b.to_i32x4().to_f32x4(), F32x4S::new(-4.0, 0.0, 1.0, 4.0));
+    assert_eq!(b.to_i32x4_truncate().to_f32x4(), F32x4S::new(-3.0, 0.0, 1.0, 3.0));
+    assert_eq!(b.to_i32x4_floor().to_f32x4(), F32x4S::new(-4.0, -1.0, 1.0, 3.0));
+}
+
+// Scalar F32x8
+
+#[test]
+fn test_f32x8s_basic_ops() {
+    let a = F32x8S::new(1.0, 3.0, 5.0, 7.0, -1.0, -3.0, -5.0, -7.0);
+    let b = F32x8S::new(2.0, 2.0, 6.0, 6.0, -2.0, -2.0, -6.0, -6.0);
+    assert_eq!(a.min(b), F32x8S::new(1.0, 2.0, 5.0, 6.0, -2.0, -3.0, -6.0, -7.0));
+    assert_eq!(a.max(b), F32x8S::new(2.0, 3.0, 6.0, 7.0, -1.0, -2.0, -5.0, -6.0));
+    assert_eq!(a + b, F32x8S::new(3.0, 5.0, 11.0, 13.0, -3.0, -5.0, -11.0, -13.0));
+    assert_eq!(a.hi(), F32x4S::new(-1.0, -3.0, -5.0, -7.0));
+    assert_eq!(F32x8S::from_halves(a.lo(), a.hi()), a);
+    let c = F32x8S::new(-1.0, 1.3, -20.0, 3.6, 0.5, -0.5, 9.0, -9.9);
+    assert_eq!(c.abs(), F32x8S::new(1.0, 1.3, 20.0, 3.6, 0.5, 0.5, 9.0, 9.9));
+    assert_eq!(c.floor(), F32x8S::new(-1.0, 1.0, -20.0, 3.0, 0.0, -1.0, 9.0, -10.0));
+    assert_eq!(c.ceil(), F32x8S::new(-1.0, 2.0, -20.0, 4.0, 1.0, -0.0, 9.0, -9.0));
+    assert_eq!(c.round(), F32x8S::new(-1.0, 1.0, -20.0, 4.0, 0.0, -0.0, 9.0, -10.0));
+}
+
+// Scalar U8x16
+
+#[test]
+fn test_u8x16s_basic_ops() {
+    let a = U8x16S::from_array(BYTES);
+    let b = U8x16S::splat(6);
+    assert_eq!((a + b)[8], 0);
+    assert_eq!((a - b)[0], 250);
+    assert_eq!(a.min(b)[15], 6);
+    assert_eq!(a.max(b)[0], 6);
+    assert!(a.packed_eq(a).all_true());
+    assert_eq!(a.to_u32x4().to_u8x16(), a);
+    assert_eq!(a.to_u32x4().0[1], u32::from_ne_bytes([4, 5, 6, 7]));
+}
+
+#[test]
+fn test_u8x16s_widening_and_narrowing() {
+    let a = U8x16S::from_array(BYTES);
+    let high = a.widen_high();
+    assert_eq!(high.to_array(), [250, 251, 252, 253, 254, 255, 128, 127]);
+    assert_eq!(U8x16S::narrow(a.widen_low(), high), a);
+    assert_eq!(U8x16S::narrow(high * high, high)[5], 255);
+    assert_eq!(U8x16S::narrow(high >> 4, high)[0], 15);
+}
+
+// Runtime dispatch
+
+crate::multiversion! {
+    fn dot_products(a: &[F32x4], b: &[F32x4], results: &mut [f32]) {
+        for ((a, b), result) in a.iter().zip(b.iter()).zip(results.iter_mut()) {
+            let product = *a * *b;
+            *result = product[0] + product[1] + product[2] + product[3];
+        }
+    }
+}
+
+#[test]
+fn test_simd_level_detection() {
+    let level = dispatch::simd_level();
+    assert_eq!(level, dispatch::detect_simd_level());
+    assert_eq!(dispatch::simd_level(), level);
+
+    // The CPU must support at least what the crate was compiled for.
+    if cfg!(all(target_feature = "avx2", target_feature = "fma")) {
+        assert_eq!(level, SimdLevel::Avx2);
+    } else if cfg!(target_feature = "sse4.1") {
+        assert!(level >= SimdLevel::Sse41);
+    }
+}
+
+#[test]
+fn test_multiversion_dispatch() {
+    let a = [F32x4::new(1.0, 2.0, 3.0, 4.0), F32x4::splat(0.5)];
+    let b = [F32x4::new(4.0, 3.0, 2.0, 1.0), F32x4::splat(-2.0)];
+    let mut results = [0.0; 2];
+    dot_products(&a, &b, &mut results);
+    assert_eq!(results, [20.0, -4.0]);
+}
+
+#[cfg(feature = "pod")]
+#[test]
+fn test_pod_casts() {
+    let vectors = [F32x4::new(1.0, 2.0, 3.0, 4.0), F32x4::new(5.0, 6.0, 7.0, 8.0)];
+    let floats: &[f32] = bytemuck::cast_slice(&vectors);
+    assert_eq!(floats, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+    assert_eq!(bytemuck::bytes_of(&vectors[0]), bytemuck::bytes_of(&[1.0f32, 2.0, 3.0, 4.0]));
+
+    let bytes: [u8; 16] = bytemuck::cast(U32x4::new(1, 2, 3, 4));
+    assert_eq!(bytes, U32x4::new(1, 2, 3, 4).to_u8x16().to_array());
+    assert_eq!(bytemuck::cast::<F32x8, [F32x4; 2]>(F32x8::new(1.0, 2.0, 3.0, 4.0,
+                                                              5.0, 6.0, 7.0, 8.0))[1],
+               vectors[1]);
+    assert_eq!(<F32x4S as bytemuck::Zeroable>::zeroed(), F32x4S::default());
+}
@@ -0,0 +1,141 @@
+// pathfinder/simd/src/trig.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Polynomial approximations of trigonometric functions, four lanes at a time.
+//!
+//! These are built from the basic operations every backend provides, so they behave the same
+//! everywhere, and they don't need the standard library. The polynomials are the single-precision
+//! ones from Cephes.
+
+use crate::default::{F32x4, I32x4};
+use core::f32::consts::{FRAC_2_PI, FRAC_PI_2, FRAC_PI_4, PI};
+
+// π/2 split into three parts for Cody-Waite range reduction. The first two have few enough
+// significant bits that multiplying them by a quadrant number below 2^13 is exact.
+const FRAC_PI_2_HI: f32 = 1.5703125;
+const FRAC_PI_2_MID: f32 = 4.837513e-4;
+const FRAC_PI_2_LO: f32 = 7.54979e-8;
+
+// tan(π/8).
+const TAN_FRAC_PI_8: f32 = 0.41421356;
+
+// tan(3π/8).
+const TAN_3_FRAC_PI_8: f32 = 2.4142137;
+
+impl F32x4 {
+    /// Computes the sine of each lane, in radians.
+    ///
+    /// For `|x| ≤ 10000`, the absolute error is at most 1.5×10⁻⁷, or about 2 ULP near 1.
+    /// Accuracy falls off beyond that, as range reduction loses precision. Infinities and NaNs
+    /// produce NaN.
+    #[inline]
+    pub fn sin(self) -> F32x4 {
+        self.sin_cos().0
+    }
+
+    /// Computes the cosine of each lane, in radians, with the same accuracy as `sin()`.
+    #[inline]
+    pub fn cos(self) -> F32x4 {
+        self.sin_cos().1
+    }
+
+    /// Computes the sine and cosine of each lane at once, which costs little more than either.
+    ///
+    /// See `sin()` for the error bounds.
+    pub fn sin_cos(self) -> (F32x4, F32x4) {
+        // Reduce to `r` in [-π/4, π/4], with `self = r + quadrant * π/2`.
+        let quadrant = (self * F32x4::splat(FRAC_2_PI)).round();
+        let r = quadrant.mul_add(F32x4::splat(-FRAC_PI_2_HI), self);
+        let r = quadrant.mul_add(F32x4::splat(-FRAC_PI_2_MID), r);
+        let r = quadrant.mul_add(F32x4::splat(-FRAC_PI_2_LO), r);
+
+        let r2 = r * r;
+        let sin_r = r2.mul_add(F32x4::splat(-1.9515296e-4), F32x4::splat(8.332161e-3))
+                      .mul_add(r2, F32x4::splat(-1.6666655e-1))
+                      .mul_add(r2 * r, r);
+        let cos_r = r2.mul_add(F32x4::splat(2.4433157e-5), F32x4::splat(-1.3887316e-3))
+                      .mul_add(r2, F32x4::splat(4.1666646e-2))
+                      .mul_add(r2 * r2, r2.mul_add(F32x4::splat(-0.5), F32x4::splat(1.0)));
+
+        // Odd quadrants swap sine and cosine. Sine is negative in quadrants 2 and 3, and cosine
+        // in quadrants 1 and 2.
+        let quadrant = quadrant.to_i32x4();
+        let swap = (quadrant & I32x4::splat(1)).packed_eq(I32x4::splat(1));
+        let negate_sin = (quadrant & I32x4::splat(2)).packed_eq(I32x4::splat(2));
+        let negate_cos = swap ^ negate_sin;
+        let sin = F32x4::select(swap, cos_r, sin_r);
+        let cos = F32x4::select(swap, sin_r, cos_r);
+        (F32x4::select(negate_sin, -sin, sin), F32x4::select(negate_cos, -cos, cos))
+    }
+
+    /// Computes the arctangent of each lane, in radians, in [-π/2, π/2].
+    ///
+    /// The absolute error is at most 2×10⁻⁷.
+    pub fn atan(self) -> F32x4 {
+        // Reduce to [0, tan(π/8)] using atan(t) = π/2 - atan(1/t) and
+        // atan(t) = π/4 + atan((t - 1) / (t + 1)).
+        let t = self.abs();
+        let is_large = t.packed_gt(F32x4::splat(TAN_3_FRAC_PI_8));
+        let is_medium = t.packed_gt(F32x4::splat(TAN_FRAC_PI_8)) & !is_large;
+        let reduced = F32x4::select(is_large,
+                                    F32x4::splat(-1.0) / t,
+                                    F32x4::select(is_medium,
+                                                  (t - F32x4::splat(1.0)) /
+                                                  (t + F32x4::splat(1.0)),
+                                                  t));
+        let offset = F32x4::select(is_large,
+                                   F32x4::splat(FRAC_PI_2),
+                                   F32x4::select(is_medium,
+                                                 F32x4::splat(FRAC_PI_4),
+                                                 F32x4::default()));
+        (offset + atan_kernel(reduced)).copysign(self)
+    }
+
+    /// Computes the angle of the vector (`x`, `y`) for each lane, where `self` is `y`, in
+    /// [-π, π]. This matches `f32::atan2()`, including for zeros and infinities.
+    ///
+    /// The absolute error is at most 4×10⁻⁷. NaN in either input produces NaN.
+    pub fn atan2(self, x: F32x4) -> F32x4 {
+        let y = self;
+        let (abs_x, abs_y) = (x.abs(), y.abs());
+        let (min, max) = (abs_x.min(abs_y), abs_x.max(abs_y));
+
+        // Find the angle to the nearer axis, as an arctangent in [0, 1]. Equal magnitudes make
+        // it exactly 1, even if they're infinite, and zeros make it 0.
+        let ratio = F32x4::select(min.packed_eq(max), F32x4::splat(1.0), min / max);
+        let ratio = F32x4::select(max.packed_eq(F32x4::default()), F32x4::default(), ratio);
+        let is_medium = ratio.packed_gt(F32x4::splat(TAN_FRAC_PI_8));
+        let reduced = F32x4::select(is_medium,
+                                    (ratio - F32x4::splat(1.0)) / (ratio + F32x4::splat(1.0)),
+                                    ratio);
+        let angle = F32x4::select(is_medium, F32x4::splat(FRAC_PI_4), F32x4::default()) +
+            atan_kernel(reduced);
+
+        // Reflect into the right octant, then the right half-plane. Negative zero counts as
+        // negative here, as it does for `f32::atan2()`.
+        let angle = F32x4::select(abs_y.packed_gt(abs_x), F32x4::splat(FRAC_PI_2) - angle, angle);
+        let x_is_negative = F32x4::splat(1.0).copysign(x).packed_lt(F32x4::default());
+        let angle = F32x4::select(x_is_negative, F32x4::splat(PI) - angle, angle);
+        let angle = angle.copysign(y);
+
+        let is_nan = x.packed_ne(x) | y.packed_ne(y);
+        F32x4::select(is_nan, F32x4::splat(f32::NAN), angle)
+    }
+}
+
+// The arctangent of `t` in [-tan(π/8), tan(π/8)].
+#[inline]
+fn atan_kernel(t: F32x4) -> F32x4 {
+    let t2 = t * t;
+    t2.mul_add(F32x4::splat(8.0537446e-2), F32x4::splat(-1.3877685e-1))
+      .mul_add(t2, F32x4::splat(1.9977711e-1))
+      .mul_add(t2, F32x4::splat(-3.333295e-1))
+      .mul_add(t2 * t, t)
+}
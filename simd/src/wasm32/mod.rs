@@ -0,0 +1,2146 @@
+// pathfinder/simd/src/wasm32/mod.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! WebAssembly SIMD128 implementations of the vector types.
+//!
+//! WebAssembly has no runtime feature detection, so this backend is only compiled in when the
+//! `simd128` target feature is enabled for the whole build.
+
+use crate::cast::{cast, cast_mut, cast_ref};
+use core::arch::wasm32::{self, v128};
+use core::fmt::{self, Debug, Formatter};
+use core::ops::{Add, BitAnd, BitOr, BitXor, Div, Index, IndexMut, Mul, Not, Shl, Shr, Sub};
+use crate::scalar;
+
+mod swizzle_f32x4;
+mod swizzle_i32x4;
+
+// Two 32-bit floats
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct F32x2(pub u64);
+
+impl F32x2 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: f32, b: f32) -> F32x2 {
+        F32x2(a.to_bits() as u64 | ((b.to_bits() as u64) << 32))
+    }
+
+    #[inline]
+    pub fn splat(x: f32) -> F32x2 {
+        F32x2::new(x, x)
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn approx_recip(self) -> F32x2 {
+        self.to_f32x4().approx_recip().xy()
+    }
+
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x2 {
+        self.to_f32x4().approx_rsqrt().xy()
+    }
+
+    #[inline]
+    pub fn min(self, other: F32x2) -> F32x2 {
+        self.to_f32x4().min(other.to_f32x4()).xy()
+    }
+
+    #[inline]
+    pub fn max(self, other: F32x2) -> F32x2 {
+        self.to_f32x4().max(other.to_f32x4()).xy()
+    }
+
+    #[inline]
+    pub fn clamp(self, min: F32x2, max: F32x2) -> F32x2 {
+        self.to_f32x4().clamp(min.to_f32x4(), max.to_f32x4()).xy()
+    }
+
+    #[inline]
+    pub fn abs(self) -> F32x2 {
+        self.to_f32x4().abs().xy()
+    }
+
+    /// Returns the magnitude of each lane with the sign of the same lane of `sign`. See
+    /// `F32x4::copysign()`.
+    #[inline]
+    pub fn copysign(self, sign: F32x2) -> F32x2 {
+        self.to_f32x4().copysign(sign.to_f32x4()).xy()
+    }
+
+    /// Returns the sign bits of the lanes packed into the low two bits, with the first lane's in
+    /// bit 0. See `F32x4::sign_bits()`.
+    #[inline]
+    pub fn sign_bits(self) -> u32 {
+        self.to_f32x4().sign_bits()
+    }
+
+    #[inline]
+    pub fn floor(self) -> F32x2 {
+        self.to_f32x4().floor().xy()
+    }
+
+    #[inline]
+    pub fn ceil(self) -> F32x2 {
+        self.to_f32x4().ceil().xy()
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x2 {
+        self.to_f32x4().round().xy()
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x2 {
+        self - self.floor()
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F32x2 {
+        self.to_f32x4().sqrt().xy()
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F32x2, b: F32x2) -> F32x2 {
+        self.to_f32x4().mul_add(a.to_f32x4(), b.to_f32x4()).xy()
+    }
+
+    // Horizontal operations
+
+    /// Returns the sum of both lanes.
+    #[inline]
+    pub fn sum(self) -> f32 {
+        self[0] + self[1]
+    }
+
+    /// Returns the smaller of the two lanes.
+    #[inline]
+    pub fn min_element(self) -> f32 {
+        f32::min(self[0], self[1])
+    }
+
+    /// Returns the larger of the two lanes.
+    #[inline]
+    pub fn max_element(self) -> f32 {
+        f32::max(self[0], self[1])
+    }
+
+    /// Returns the dot product of this vector and `other`.
+    #[inline]
+    pub fn dot(self, other: F32x2) -> f32 {
+        (self * other).sum()
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: F32x2) -> U32x2 {
+        self.to_f32x4().packed_eq(other.to_f32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: F32x2) -> U32x2 {
+        self.to_f32x4().packed_gt(other.to_f32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: F32x2) -> U32x2 {
+        self.to_f32x4().packed_lt(other.to_f32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: F32x2) -> U32x2 {
+        self.to_f32x4().packed_le(other.to_f32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: F32x2) -> U32x2 {
+        self.to_f32x4().packed_ge(other.to_f32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: F32x2) -> U32x2 {
+        self.to_f32x4().packed_ne(other.to_f32x4()).xy()
+    }
+
+    /// Returns the lanes of `a` where `mask` is true and those of `b` where it's false.
+    ///
+    /// The result is *undefined* if both values in `mask` are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn select(mask: U32x2, a: F32x2, b: F32x2) -> F32x2 {
+        F32x2((mask.0 & a.0) | (!mask.0 & b.0))
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        F32x4(wasm32::u64x2(self.0, 0))
+    }
+
+    #[inline]
+    pub fn to_i32x2(self) -> I32x2 {
+        self.to_i32x4().xy()
+    }
+
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        self.to_f32x4().to_i32x4()
+    }
+
+    // Swizzle
+
+    #[inline]
+    pub fn yx(self) -> F32x2 {
+        self.to_f32x4().yx()
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: F32x2) -> F32x4 {
+        F32x4(wasm32::u64x2(self.0, other.0))
+    }
+}
+
+impl Default for F32x2 {
+    #[inline]
+    fn default() -> F32x2 {
+        F32x2(0)
+    }
+}
+
+impl Index<usize> for F32x2 {
+    type Output = f32;
+    #[inline]
+    fn index(&self, index: usize) -> &f32 {
+        &cast_ref::<u64, [f32; 2]>(&self.0)[index]
+    }
+}
+
+impl IndexMut<usize> for F32x2 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        &mut cast_mut::<u64, [f32; 2]>(&mut self.0)[index]
+    }
+}
+
+impl Debug for F32x2 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}>", self[0], self[1])
+    }
+}
+
+impl PartialEq for F32x2 {
+    #[inline]
+    fn eq(&self, other: &F32x2) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
+
+impl Add<F32x2> for F32x2 {
+    type Output = F32x2;
+    #[inline]
+    fn add(self, other: F32x2) -> F32x2 {
+        (self.to_f32x4() + other.to_f32x4()).xy()
+    }
+}
+
+impl Div<F32x2> for F32x2 {
+    type Output = F32x2;
+    #[inline]
+    fn div(self, other: F32x2) -> F32x2 {
+        (self.to_f32x4() / other.to_f32x4()).xy()
+    }
+}
+
+impl Mul<F32x2> for F32x2 {
+    type Output = F32x2;
+    #[inline]
+    fn mul(self, other: F32x2) -> F32x2 {
+        (self.to_f32x4() * other.to_f32x4()).xy()
+    }
+}
+
+impl Sub<F32x2> for F32x2 {
+    type Output = F32x2;
+    #[inline]
+    fn sub(self, other: F32x2) -> F32x2 {
+        (self.to_f32x4() - other.to_f32x4()).xy()
+    }
+}
+
+// Four 32-bit floats
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct F32x4(pub v128);
+
+impl F32x4 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: f32, b: f32, c: f32, d: f32) -> F32x4 {
+        F32x4(wasm32::f32x4(a, b, c, d))
+    }
+
+    #[inline]
+    pub fn splat(x: f32) -> F32x4 {
+        F32x4(wasm32::f32x4_splat(x))
+    }
+
+    /// Loads the first four elements of `slice`, which needn't be aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn from_slice(slice: &[f32]) -> F32x4 {
+        let slice = &slice[..4];
+        unsafe { F32x4(wasm32::v128_load(slice.as_ptr() as *const v128)) }
+    }
+
+    /// Loads the first four elements of `slice`, which must start on a 16-byte boundary. This can
+    /// be faster than `from_slice` on some CPUs.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn from_slice_aligned(slice: &[f32]) -> F32x4 {
+        let slice = &slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        F32x4::from_slice(slice)
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which needn't be
+    /// aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn write_to_slice(self, slice: &mut [f32]) {
+        let slice = &mut slice[..4];
+        unsafe { wasm32::v128_store(slice.as_mut_ptr() as *mut v128, self.0) }
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which must start
+    /// on a 16-byte boundary.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_mut_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn write_to_slice_aligned(self, slice: &mut [f32]) {
+        let slice = &mut slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        self.write_to_slice(slice)
+    }
+
+    // Basic operations
+
+    /// WebAssembly has no reciprocal estimate, so this is an exact division.
+    #[inline]
+    pub fn approx_recip(self) -> F32x4 {
+        F32x4::splat(1.0) / self
+    }
+
+    /// WebAssembly has no reciprocal square root estimate, so this is exact.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x4 {
+        F32x4::splat(1.0) / self.sqrt()
+    }
+
+    #[inline]
+    pub fn min(self, other: F32x4) -> F32x4 {
+        F32x4(wasm32::f32x4_pmin(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: F32x4) -> F32x4 {
+        F32x4(wasm32::f32x4_pmax(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn clamp(self, min: F32x4, max: F32x4) -> F32x4 {
+        self.max(min).min(max)
+    }
+
+    #[inline]
+    pub fn abs(self) -> F32x4 {
+        F32x4(wasm32::f32x4_abs(self.0))
+    }
+
+    /// Returns the magnitude of each lane with the sign of the same lane of `sign`.
+    ///
+    /// Only the sign bit of `sign` matters, so negative zero and NaNs with the sign bit set count
+    /// as negative.
+    #[inline]
+    pub fn copysign(self, sign: F32x4) -> F32x4 {
+        F32x4(wasm32::v128_bitselect(sign.0, self.0, wasm32::u32x4_splat(0x8000_0000)))
+    }
+
+    /// Returns the sign bits of the lanes packed into the low four bits, with the first lane's in
+    /// bit 0, like `movmskps`. As with `copysign()`, negative zero and negative NaNs count.
+    #[inline]
+    pub fn sign_bits(self) -> u32 {
+        wasm32::i32x4_bitmask(self.0) as u32
+    }
+
+    #[inline]
+    pub fn floor(self) -> F32x4 {
+        F32x4(wasm32::f32x4_floor(self.0))
+    }
+
+    #[inline]
+    pub fn ceil(self) -> F32x4 {
+        F32x4(wasm32::f32x4_ceil(self.0))
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x4 {
+        F32x4(wasm32::f32x4_nearest(self.0))
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x4 {
+        self - self.floor()
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F32x4 {
+        F32x4(wasm32::f32x4_sqrt(self.0))
+    }
+
+    /// Returns `self * a + b`.
+    ///
+    /// WebAssembly SIMD has no fused multiply-add, so this is an ordinary multiply followed by an
+    /// add, which rounds twice.
+    #[inline]
+    pub fn mul_add(self, a: F32x4, b: F32x4) -> F32x4 {
+        self * a + b
+    }
+
+    /// Returns the cross product of the first three lanes of this vector and `other`.
+    ///
+    /// The w lanes are ignored, and the w lane of the result is zero.
+    #[inline]
+    pub fn cross(self, other: F32x4) -> F32x4 {
+        let product = (self * other.yzxw() - self.yzxw() * other).yzxw();
+        F32x4(wasm32::f32x4_replace_lane::<3>(product.0, 0.0))
+    }
+
+    // Horizontal operations
+
+    /// Returns the sum of all four lanes.
+    #[inline]
+    pub fn sum(self) -> f32 {
+        let pairs = self + self.yxwz();
+        (pairs + pairs.zwxy())[0]
+    }
+
+    /// Returns the smallest of the four lanes.
+    #[inline]
+    pub fn min_element(self) -> f32 {
+        let pairs = self.min(self.yxwz());
+        pairs.min(pairs.zwxy())[0]
+    }
+
+    /// Returns the largest of the four lanes.
+    #[inline]
+    pub fn max_element(self) -> f32 {
+        let pairs = self.max(self.yxwz());
+        pairs.max(pairs.zwxy())[0]
+    }
+
+    /// Returns the dot product of this vector and `other`, using all four lanes.
+    #[inline]
+    pub fn dot(self, other: F32x4) -> f32 {
+        (self * other).sum()
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: F32x4) -> U32x4 {
+        U32x4(wasm32::f32x4_eq(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: F32x4) -> U32x4 {
+        U32x4(wasm32::f32x4_gt(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: F32x4) -> U32x4 {
+        U32x4(wasm32::f32x4_lt(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: F32x4) -> U32x4 {
+        U32x4(wasm32::f32x4_le(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: F32x4) -> U32x4 {
+        U32x4(wasm32::f32x4_ge(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: F32x4) -> U32x4 {
+        U32x4(wasm32::f32x4_ne(self.0, other.0))
+    }
+
+    /// Returns the lanes of `a` where `mask` is true and those of `b` where it's false.
+    ///
+    /// The result is *undefined* if all four values in `mask` are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn select(mask: U32x4, a: F32x4, b: F32x4) -> F32x4 {
+        F32x4(wasm32::v128_bitselect(a.0, b.0, mask.0))
+    }
+
+    // Conversions
+
+    /// Converts these packed floats to integers via rounding. This is the same as
+    /// `to_i32x4_round()`.
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        self.to_i32x4_round()
+    }
+
+    /// Converts these packed floats to integers, rounding to the nearest integer and halfway cases
+    /// to the even one.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_round(self) -> I32x4 {
+        I32x4(wasm32::i32x4_trunc_sat_f32x4(wasm32::f32x4_nearest(self.0)))
+    }
+
+    /// Converts these packed floats to integers, rounding toward zero.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_truncate(self) -> I32x4 {
+        I32x4(wasm32::i32x4_trunc_sat_f32x4(self.0))
+    }
+
+    /// Converts these packed floats to integers, rounding toward negative infinity.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_floor(self) -> I32x4 {
+        I32x4(wasm32::i32x4_trunc_sat_f32x4(wasm32::f32x4_floor(self.0)))
+    }
+
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order.
+    ///
+    /// This covers every permutation, so it's handy when the lanes are chosen by other generic
+    /// code. `v.shuffle::<2, 3, 0, 1>()` is the same as `v.zwxy()`. Every index must be less
+    /// than 4.
+    #[inline]
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> F32x4 {
+        debug_assert!(A < 4 && B < 4 && C < 4 && D < 4, "shuffle lane index out of range");
+        F32x4(wasm32::i32x4_shuffle::<A, B, C, D>(self.0, self.0))
+    }
+
+    // Extraction
+
+    #[inline]
+    pub fn xy(self) -> F32x2 {
+        F32x2(wasm32::u64x2_extract_lane::<0>(self.0))
+    }
+
+    #[inline]
+    pub fn xw(self) -> F32x2 {
+        self.xwyz().xy()
+    }
+
+    #[inline]
+    pub fn yx(self) -> F32x2 {
+        self.yxwz().xy()
+    }
+
+    #[inline]
+    pub fn zy(self) -> F32x2 {
+        self.zyxw().xy()
+    }
+
+    #[inline]
+    pub fn zw(self) -> F32x2 {
+        F32x2(wasm32::u64x2_extract_lane::<1>(self.0))
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: F32x4) -> F32x4 {
+        F32x4(wasm32::i32x4_shuffle::<0, 1, 4, 5>(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn concat_xy_zw(self, other: F32x4) -> F32x4 {
+        F32x4(wasm32::i32x4_shuffle::<0, 1, 6, 7>(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn concat_zw_zw(self, other: F32x4) -> F32x4 {
+        F32x4(wasm32::i32x4_shuffle::<2, 3, 6, 7>(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn concat_wz_yx(self, other: F32x4) -> F32x4 {
+        F32x4(wasm32::i32x4_shuffle::<3, 2, 5, 4>(self.0, other.0))
+    }
+}
+
+impl Default for F32x4 {
+    #[inline]
+    fn default() -> F32x4 {
+        F32x4::splat(0.0)
+    }
+}
+
+impl Index<usize> for F32x4 {
+    type Output = f32;
+    #[inline]
+    fn index(&self, index: usize) -> &f32 {
+        &cast_ref::<v128, [f32; 4]>(&self.0)[index]
+    }
+}
+
+impl IndexMut<usize> for F32x4 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        &mut cast_mut::<v128, [f32; 4]>(&mut self.0)[index]
+    }
+}
+
+impl Debug for F32x4 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    }
+}
+
+impl PartialEq for F32x4 {
+    #[inline]
+    fn eq(&self, other: &F32x4) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
+
+impl Add<F32x4> for F32x4 {
+    type Output = F32x4;
+    #[inline]
+    fn add(self, other: F32x4) -> F32x4 {
+        F32x4(wasm32::f32x4_add(self.0, other.0))
+    }
+}
+
+impl Div<F32x4> for F32x4 {
+    type Output = F32x4;
+    #[inline]
+    fn div(self, other: F32x4) -> F32x4 {
+        F32x4(wasm32::f32x4_div(self.0, other.0))
+    }
+}
+
+impl Mul<F32x4> for F32x4 {
+    type Output = F32x4;
+    #[inline]
+    fn mul(self, other: F32x4) -> F32x4 {
+        F32x4(wasm32::f32x4_mul(self.0, other.0))
+    }
+}
+
+impl Sub<F32x4> for F32x4 {
+    type Output = F32x4;
+    #[inline]
+    fn sub(self, other: F32x4) -> F32x4 {
+        F32x4(wasm32::f32x4_sub(self.0, other.0))
+    }
+}
+
+// Eight 32-bit floats
+
+/// Eight 32-bit floats, processed as two `F32x4` halves.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(C)]
+pub struct F32x8(pub F32x4, pub F32x4);
+
+impl F32x8 {
+    // Constructors
+
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32, g: f32, h: f32) -> F32x8 {
+        F32x8(F32x4::new(a, b, c, d), F32x4::new(e, f, g, h))
+    }
+
+    #[inline]
+    pub fn splat(x: f32) -> F32x8 {
+        F32x8(F32x4::splat(x), F32x4::splat(x))
+    }
+
+    #[inline]
+    pub fn from_halves(lo: F32x4, hi: F32x4) -> F32x8 {
+        F32x8(lo, hi)
+    }
+
+    // Accessors
+
+    /// Returns the first four lanes.
+    #[inline]
+    pub fn lo(self) -> F32x4 {
+        self.0
+    }
+
+    /// Returns the last four lanes.
+    #[inline]
+    pub fn hi(self) -> F32x4 {
+        self.1
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn approx_recip(self) -> F32x8 {
+        F32x8(self.0.approx_recip(), self.1.approx_recip())
+    }
+
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x8 {
+        F32x8(self.0.approx_rsqrt(), self.1.approx_rsqrt())
+    }
+
+    #[inline]
+    pub fn min(self, other: F32x8) -> F32x8 {
+        F32x8(self.0.min(other.0), self.1.min(other.1))
+    }
+
+    #[inline]
+    pub fn max(self, other: F32x8) -> F32x8 {
+        F32x8(self.0.max(other.0), self.1.max(other.1))
+    }
+
+    #[inline]
+    pub fn clamp(self, min: F32x8, max: F32x8) -> F32x8 {
+        self.max(min).min(max)
+    }
+
+    #[inline]
+    pub fn abs(self) -> F32x8 {
+        F32x8(self.0.abs(), self.1.abs())
+    }
+
+    #[inline]
+    pub fn floor(self) -> F32x8 {
+        F32x8(self.0.floor(), self.1.floor())
+    }
+
+    #[inline]
+    pub fn ceil(self) -> F32x8 {
+        F32x8(self.0.ceil(), self.1.ceil())
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x8 {
+        F32x8(self.0.round(), self.1.round())
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x8 {
+        self - self.floor()
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F32x8 {
+        F32x8(self.0.sqrt(), self.1.sqrt())
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F32x8, b: F32x8) -> F32x8 {
+        F32x8(self.0.mul_add(a.0, b.0), self.1.mul_add(a.1, b.1))
+    }
+}
+
+impl Index<usize> for F32x8 {
+    type Output = f32;
+    #[inline]
+    fn index(&self, index: usize) -> &f32 {
+        if index < 4 { &self.0[index] } else { &self.1[index - 4] }
+    }
+}
+
+impl IndexMut<usize> for F32x8 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        if index < 4 { &mut self.0[index] } else { &mut self.1[index - 4] }
+    }
+}
+
+impl Debug for F32x8 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f,
+               "<{}, {}, {}, {}, {}, {}, {}, {}>",
+               self[0], self[1], self[2], self[3], self[4], self[5], self[6], self[7])
+    }
+}
+
+impl Add<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn add(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 + other.0, self.1 + other.1)
+    }
+}
+
+impl Div<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn div(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 / other.0, self.1 / other.1)
+    }
+}
+
+impl Mul<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn mul(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 * other.0, self.1 * other.1)
+    }
+}
+
+impl Sub<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn sub(self, other: F32x8) -> F32x8 {
+        F32x8(self.0 - other.0, self.1 - other.1)
+    }
+}
+
+// Two and four 64-bit floats
+//
+// There are no double-precision versions of these yet, so this backend shares the scalar ones.
+
+pub use crate::scalar::{F64x2, F64x4};
+
+// Two 32-bit signed integers
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct I32x2(pub u64);
+
+impl I32x2 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: i32, b: i32) -> I32x2 {
+        I32x2(a as u32 as u64 | ((b as u32 as u64) << 32))
+    }
+
+    #[inline]
+    pub fn splat(x: i32) -> I32x2 {
+        I32x2::new(x, x)
+    }
+
+    // Accessors
+
+    #[inline]
+    pub fn x(self) -> i32 {
+        self[0]
+    }
+
+    #[inline]
+    pub fn y(self) -> i32 {
+        self[1]
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: I32x2) -> I32x4 {
+        I32x4(wasm32::u64x2(self.0, other.0))
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        I32x4(wasm32::u64x2(self.0, 0))
+    }
+
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        self.to_i32x4().to_f32x4()
+    }
+
+    /// Converts these packed integers to floats.
+    #[inline]
+    pub fn to_f32x2(self) -> F32x2 {
+        self.to_f32x4().xy()
+    }
+
+    #[inline]
+    pub fn to_u32x2(self) -> U32x2 {
+        U32x2(self.0)
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn max(self, other: I32x2) -> I32x2 {
+        self.to_i32x4().max(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn min(self, other: I32x2) -> I32x2 {
+        self.to_i32x4().min(other.to_i32x4()).xy()
+    }
+
+    // Horizontal operations
+
+    /// Returns the smaller of the two lanes.
+    #[inline]
+    pub fn min_element(self) -> i32 {
+        i32::min(self[0], self[1])
+    }
+
+    /// Returns the larger of the two lanes.
+    #[inline]
+    pub fn max_element(self) -> i32 {
+        i32::max(self[0], self[1])
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_eq(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_gt(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_le(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_lt(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_ge(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_ne(other.to_i32x4()).xy()
+    }
+}
+
+impl Default for I32x2 {
+    #[inline]
+    fn default() -> I32x2 {
+        I32x2(0)
+    }
+}
+
+impl Index<usize> for I32x2 {
+    type Output = i32;
+    #[inline]
+    fn index(&self, index: usize) -> &i32 {
+        &cast_ref::<u64, [i32; 2]>(&self.0)[index]
+    }
+}
+
+impl IndexMut<usize> for I32x2 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut i32 {
+        &mut cast_mut::<u64, [i32; 2]>(&mut self.0)[index]
+    }
+}
+
+impl Add<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn add(self, other: I32x2) -> I32x2 {
+        (self.to_i32x4() + other.to_i32x4()).xy()
+    }
+}
+
+impl Sub<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn sub(self, other: I32x2) -> I32x2 {
+        (self.to_i32x4() - other.to_i32x4()).xy()
+    }
+}
+
+impl Mul<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn mul(self, other: I32x2) -> I32x2 {
+        (self.to_i32x4() * other.to_i32x4()).xy()
+    }
+}
+
+impl BitAnd<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn bitand(self, other: I32x2) -> I32x2 {
+        I32x2(self.0 & other.0)
+    }
+}
+
+impl BitOr<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn bitor(self, other: I32x2) -> I32x2 {
+        I32x2(self.0 | other.0)
+    }
+}
+
+impl Shl<u32> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn shl(self, amount: u32) -> I32x2 {
+        (self.to_i32x4() << amount).xy()
+    }
+}
+
+impl Shr<u32> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn shr(self, amount: u32) -> I32x2 {
+        (self.to_i32x4() >> amount).xy()
+    }
+}
+
+impl Debug for I32x2 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}>", self[0], self[1])
+    }
+}
+
+impl PartialEq for I32x2 {
+    #[inline]
+    fn eq(&self, other: &I32x2) -> bool {
+        self.0 == other.0
+    }
+}
+
+// Four 32-bit signed integers
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct I32x4(pub v128);
+
+impl I32x4 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: i32, b: i32, c: i32, d: i32) -> I32x4 {
+        I32x4(wasm32::i32x4(a, b, c, d))
+    }
+
+    #[inline]
+    pub fn splat(x: i32) -> I32x4 {
+        I32x4(wasm32::i32x4_splat(x))
+    }
+
+    /// Loads the first four elements of `slice`, which needn't be aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn from_slice(slice: &[i32]) -> I32x4 {
+        let slice = &slice[..4];
+        unsafe { I32x4(wasm32::v128_load(slice.as_ptr() as *const v128)) }
+    }
+
+    /// Loads the first four elements of `slice`, which must start on a 16-byte boundary. This can
+    /// be faster than `from_slice` on some CPUs.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn from_slice_aligned(slice: &[i32]) -> I32x4 {
+        let slice = &slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        I32x4::from_slice(slice)
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which needn't be
+    /// aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn write_to_slice(self, slice: &mut [i32]) {
+        let slice = &mut slice[..4];
+        unsafe { wasm32::v128_store(slice.as_mut_ptr() as *mut v128, self.0) }
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which must start
+    /// on a 16-byte boundary.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_mut_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn write_to_slice_aligned(self, slice: &mut [i32]) {
+        let slice = &mut slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        self.write_to_slice(slice)
+    }
+
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order.
+    ///
+    /// This covers every permutation, so it's handy when the lanes are chosen by other generic
+    /// code. `v.shuffle::<2, 3, 0, 1>()` is the same as `v.zwxy()`. Every index must be less
+    /// than 4.
+    #[inline]
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> I32x4 {
+        debug_assert!(A < 4 && B < 4 && C < 4 && D < 4, "shuffle lane index out of range");
+        I32x4(wasm32::i32x4_shuffle::<A, B, C, D>(self.0, self.0))
+    }
+
+    // Extraction
+
+    #[inline]
+    pub fn xy(self) -> I32x2 {
+        I32x2(wasm32::u64x2_extract_lane::<0>(self.0))
+    }
+
+    #[inline]
+    pub fn xw(self) -> I32x2 {
+        self.xwyz().xy()
+    }
+
+    #[inline]
+    pub fn yx(self) -> I32x2 {
+        self.yxwz().xy()
+    }
+
+    #[inline]
+    pub fn zy(self) -> I32x2 {
+        self.zyxw().xy()
+    }
+
+    #[inline]
+    pub fn zw(self) -> I32x2 {
+        I32x2(wasm32::u64x2_extract_lane::<1>(self.0))
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: I32x4) -> I32x4 {
+        I32x4(wasm32::i32x4_shuffle::<0, 1, 4, 5>(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn concat_zw_zw(self, other: I32x4) -> I32x4 {
+        I32x4(wasm32::i32x4_shuffle::<2, 3, 6, 7>(self.0, other.0))
+    }
+
+    // Conversions
+
+    /// Converts these packed integers to floats.
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        F32x4(wasm32::f32x4_convert_i32x4(self.0))
+    }
+
+    /// Converts these packed signed integers to unsigned integers.
+    ///
+    /// Overflowing values will wrap around.
+    #[inline]
+    pub fn to_u32x4(self) -> U32x4 {
+        U32x4(self.0)
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn max(self, other: I32x4) -> I32x4 {
+        I32x4(wasm32::i32x4_max(self.0, other.0))
+    }
+
+    /// Returns the smallest of the four lanes.
+    #[inline]
+    pub fn min_element(self) -> i32 {
+        let pairs = self.min(self.yxwz());
+        pairs.min(pairs.zwxy())[0]
+    }
+
+    /// Returns the largest of the four lanes.
+    #[inline]
+    pub fn max_element(self) -> i32 {
+        let pairs = self.max(self.yxwz());
+        pairs.max(pairs.zwxy())[0]
+    }
+
+    #[inline]
+    pub fn min(self, other: I32x4) -> I32x4 {
+        I32x4(wasm32::i32x4_min(self.0, other.0))
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: I32x4) -> U32x4 {
+        U32x4(wasm32::i32x4_eq(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: I32x4) -> U32x4 {
+        U32x4(wasm32::i32x4_gt(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I32x4) -> U32x4 {
+        U32x4(wasm32::i32x4_lt(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: I32x4) -> U32x4 {
+        U32x4(wasm32::i32x4_le(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: I32x4) -> U32x4 {
+        U32x4(wasm32::i32x4_ge(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: I32x4) -> U32x4 {
+        U32x4(wasm32::i32x4_ne(self.0, other.0))
+    }
+}
+
+impl Default for I32x4 {
+    #[inline]
+    fn default() -> I32x4 {
+        I32x4::splat(0)
+    }
+}
+
+impl Index<usize> for I32x4 {
+    type Output = i32;
+    #[inline]
+    fn index(&self, index: usize) -> &i32 {
+        &cast_ref::<v128, [i32; 4]>(&self.0)[index]
+    }
+}
+
+impl IndexMut<usize> for I32x4 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut i32 {
+        &mut cast_mut::<v128, [i32; 4]>(&mut self.0)[index]
+    }
+}
+
+impl Add<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn add(self, other: I32x4) -> I32x4 {
+        I32x4(wasm32::i32x4_add(self.0, other.0))
+    }
+}
+
+impl Sub<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn sub(self, other: I32x4) -> I32x4 {
+        I32x4(wasm32::i32x4_sub(self.0, other.0))
+    }
+}
+
+impl Mul<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn mul(self, other: I32x4) -> I32x4 {
+        I32x4(wasm32::i32x4_mul(self.0, other.0))
+    }
+}
+
+impl BitAnd<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn bitand(self, other: I32x4) -> I32x4 {
+        I32x4(wasm32::v128_and(self.0, other.0))
+    }
+}
+
+impl BitOr<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn bitor(self, other: I32x4) -> I32x4 {
+        I32x4(wasm32::v128_or(self.0, other.0))
+    }
+}
+
+impl Shl<u32> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shl(self, amount: u32) -> I32x4 {
+        // WebAssembly takes shift amounts modulo the lane width, unlike SSE.
+        if amount >= 32 {
+            return I32x4::splat(0);
+        }
+        I32x4(wasm32::i32x4_shl(self.0, amount))
+    }
+}
+
+impl Shr<u32> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shr(self, amount: u32) -> I32x4 {
+        // Shifting right by 31 already fills every bit with the sign.
+        I32x4(wasm32::i32x4_shr(self.0, u32::min(amount, 31)))
+    }
+}
+
+impl Shl<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shl(self, other: I32x4) -> I32x4 {
+        // WebAssembly has no per-lane shifts.
+        I32x4::new(scalar::shl_i32(self[0], other[0] as u32),
+                   scalar::shl_i32(self[1], other[1] as u32),
+                   scalar::shl_i32(self[2], other[2] as u32),
+                   scalar::shl_i32(self[3], other[3] as u32))
+    }
+}
+
+impl Shr<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shr(self, other: I32x4) -> I32x4 {
+        I32x4::new(scalar::shr_i32(self[0], other[0] as u32),
+                   scalar::shr_i32(self[1], other[1] as u32),
+                   scalar::shr_i32(self[2], other[2] as u32),
+                   scalar::shr_i32(self[3], other[3] as u32))
+    }
+}
+
+impl Debug for I32x4 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    }
+}
+
+impl PartialEq for I32x4 {
+    #[inline]
+    fn eq(&self, other: &I32x4) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
+
+// Two 32-bit unsigned integers
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct U32x2(pub u64);
+
+impl U32x2 {
+    #[inline]
+    pub fn new(x: u32, y: u32) -> U32x2 {
+        U32x2(x as u64 | ((y as u64) << 32))
+    }
+
+    #[inline]
+    pub fn splat(x: u32) -> U32x2 {
+        U32x2::new(x, x)
+    }
+
+    /// Returns true if both booleans in this vector are true.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        self.0 == !0
+    }
+
+    /// Returns true if both booleans in this vector are false.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if either boolean in this vector is true.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn any_true(self) -> bool {
+        self.0 != 0
+    }
+
+    #[inline]
+    pub fn to_i32x2(self) -> I32x2 {
+        I32x2(self.0)
+    }
+}
+
+impl Debug for U32x2 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}>", self[0], self[1])
+    }
+}
+
+impl Index<usize> for U32x2 {
+    type Output = u32;
+    #[inline]
+    fn index(&self, index: usize) -> &u32 {
+        &cast_ref::<u64, [u32; 2]>(&self.0)[index]
+    }
+}
+
+impl Not for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn not(self) -> U32x2 {
+        U32x2(!self.0)
+    }
+}
+
+impl BitAnd<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitand(self, other: U32x2) -> U32x2 {
+        U32x2(self.0 & other.0)
+    }
+}
+
+impl BitOr<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitor(self, other: U32x2) -> U32x2 {
+        U32x2(self.0 | other.0)
+    }
+}
+
+impl BitXor<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitxor(self, other: U32x2) -> U32x2 {
+        U32x2(self.0 ^ other.0)
+    }
+}
+
+// Four 32-bit unsigned integers
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct U32x4(pub v128);
+
+impl U32x4 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: u32, b: u32, c: u32, d: u32) -> U32x4 {
+        U32x4(wasm32::u32x4(a, b, c, d))
+    }
+
+    #[inline]
+    pub fn splat(x: u32) -> U32x4 {
+        U32x4(wasm32::u32x4_splat(x))
+    }
+
+    // Conversions
+
+    /// Converts these packed unsigned integers to signed integers.
+    ///
+    /// Overflowing values will wrap around.
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        I32x4(self.0)
+    }
+
+    /// Reinterprets the bits of these integers as sixteen bytes, in memory order.
+    #[inline]
+    pub fn to_u8x16(self) -> U8x16 {
+        U8x16(self.0)
+    }
+
+    // Basic operations
+
+    /// Returns true if all four booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        wasm32::u32x4_all_true(self.0)
+    }
+
+    /// Returns true if all four booleans in this vector are false.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        !wasm32::v128_any_true(self.0)
+    }
+
+    /// Returns true if any of the four booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn any_true(self) -> bool {
+        wasm32::v128_any_true(self.0)
+    }
+
+    // Extraction
+
+    #[inline]
+    pub fn xy(self) -> U32x2 {
+        U32x2(wasm32::u64x2_extract_lane::<0>(self.0))
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U32x4) -> U32x4 {
+        U32x4(wasm32::i32x4_eq(self.0, other.0))
+    }
+}
+
+impl Debug for U32x4 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    }
+}
+
+impl Index<usize> for U32x4 {
+    type Output = u32;
+    #[inline]
+    fn index(&self, index: usize) -> &u32 {
+        &cast_ref::<v128, [u32; 4]>(&self.0)[index]
+    }
+}
+
+impl PartialEq for U32x4 {
+    #[inline]
+    fn eq(&self, other: &U32x4) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
+
+impl Not for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn not(self) -> U32x4 {
+        U32x4(wasm32::v128_not(self.0))
+    }
+}
+
+impl BitAnd<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitand(self, other: U32x4) -> U32x4 {
+        U32x4(wasm32::v128_and(self.0, other.0))
+    }
+}
+
+impl BitOr<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitor(self, other: U32x4) -> U32x4 {
+        U32x4(wasm32::v128_or(self.0, other.0))
+    }
+}
+
+impl BitXor<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitxor(self, other: U32x4) -> U32x4 {
+        U32x4(wasm32::v128_xor(self.0, other.0))
+    }
+}
+
+impl Shr<u32> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn shr(self, amount: u32) -> U32x4 {
+        // WebAssembly takes shift amounts modulo the lane width, unlike SSE.
+        if amount >= 32 {
+            return U32x4::splat(0);
+        }
+        U32x4(wasm32::u32x4_shr(self.0, amount))
+    }
+}
+
+// Sixteen 8-bit unsigned integers
+
+/// Sixteen bytes, such as the channels of four RGBA pixels. Arithmetic wraps around.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct U8x16(pub v128);
+
+impl U8x16 {
+    // Constructors
+
+    #[inline]
+    pub fn from_array(bytes: [u8; 16]) -> U8x16 {
+        unsafe { U8x16(wasm32::v128_load(bytes.as_ptr() as *const v128)) }
+    }
+
+    #[inline]
+    pub fn splat(x: u8) -> U8x16 {
+        U8x16(wasm32::u8x16_splat(x))
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_array(self) -> [u8; 16] {
+        cast::<v128, [u8; 16]>(self.0)
+    }
+
+    /// Reinterprets these bytes as four 32-bit integers, in memory order.
+    #[inline]
+    pub fn to_u32x4(self) -> U32x4 {
+        U32x4(self.0)
+    }
+
+    /// Zero-extends the first eight bytes to 16 bits, for arithmetic that would overflow a byte.
+    #[inline]
+    pub fn widen_low(self) -> U16x8 {
+        U16x8(wasm32::u16x8_extend_low_u8x16(self.0))
+    }
+
+    /// Zero-extends the last eight bytes to 16 bits.
+    #[inline]
+    pub fn widen_high(self) -> U16x8 {
+        U16x8(wasm32::u16x8_extend_high_u8x16(self.0))
+    }
+
+    /// Narrows sixteen 16-bit integers, `low` followed by `high`, to bytes. Values above 255
+    /// become 255.
+    #[inline]
+    pub fn narrow(low: U16x8, high: U16x8) -> U8x16 {
+        // The narrowing instruction saturates signed values, so clamp the unsigned ones first.
+        let (low, high) = (low.min(U16x8::splat(255)), high.min(U16x8::splat(255)));
+        U8x16(wasm32::u8x16_narrow_i16x8(low.0, high.0))
+    }
+
+    /// Narrows sixteen signed 16-bit integers, `low` followed by `high`, to bytes. Values below 0
+    /// become 0, and values above 255 become 255.
+    #[inline]
+    pub fn narrow_i16x8(low: I16x8, high: I16x8) -> U8x16 {
+        U8x16(wasm32::u8x16_narrow_i16x8(low.0, high.0))
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: U8x16) -> U8x16 {
+        U8x16(wasm32::u8x16_min(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: U8x16) -> U8x16 {
+        U8x16(wasm32::u8x16_max(self.0, other.0))
+    }
+
+    /// Adds, clamping sums above 255 to 255.
+    #[inline]
+    pub fn saturating_add(self, other: U8x16) -> U8x16 {
+        U8x16(wasm32::u8x16_add_sat(self.0, other.0))
+    }
+
+    /// Subtracts, clamping differences below 0 to 0.
+    #[inline]
+    pub fn saturating_sub(self, other: U8x16) -> U8x16 {
+        U8x16(wasm32::u8x16_sub_sat(self.0, other.0))
+    }
+
+    /// Returns true if all sixteen booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all sixteen values in this vector are not booleans. A boolean
+    /// is a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        wasm32::u8x16_all_true(self.0)
+    }
+
+    /// Returns true if all sixteen booleans in this vector are false.
+    ///
+    /// The result is *undefined* if all sixteen values in this vector are not booleans. A boolean
+    /// is a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        !wasm32::v128_any_true(self.0)
+    }
+
+    // Swizzle
+
+    /// Rearranges the bytes of this vector the way `pshufb` does: each lane of the result is the
+    /// byte of `self` at the index in the low four bits of the same lane of `indices`, or zero
+    /// if the high bit of that lane is set.
+    #[inline]
+    pub fn shuffle(self, indices: U8x16) -> U8x16 {
+        // `swizzle` zeroes lanes whose indices are out of range, so keep the high bit, which makes
+        // the index at least 128 if it's set, and drop the others.
+        let indices = indices & U8x16::splat(0x8f);
+        U8x16(wasm32::u8x16_swizzle(self.0, indices.0))
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U8x16) -> U8x16 {
+        U8x16(wasm32::u8x16_eq(self.0, other.0))
+    }
+}
+
+impl Default for U8x16 {
+    #[inline]
+    fn default() -> U8x16 {
+        U8x16::splat(0)
+    }
+}
+
+impl Index<usize> for U8x16 {
+    type Output = u8;
+    #[inline]
+    fn index(&self, index: usize) -> &u8 {
+        &cast_ref::<v128, [u8; 16]>(&self.0)[index]
+    }
+}
+
+impl IndexMut<usize> for U8x16 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut u8 {
+        &mut cast_mut::<v128, [u8; 16]>(&mut self.0)[index]
+    }
+}
+
+impl Debug for U8x16 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::U8x16(self.to_array()).fmt(f)
+    }
+}
+
+impl PartialEq for U8x16 {
+    #[inline]
+    fn eq(&self, other: &U8x16) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
+
+impl Add<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn add(self, other: U8x16) -> U8x16 {
+        U8x16(wasm32::u8x16_add(self.0, other.0))
+    }
+}
+
+impl Sub<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn sub(self, other: U8x16) -> U8x16 {
+        U8x16(wasm32::u8x16_sub(self.0, other.0))
+    }
+}
+
+impl BitAnd<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn bitand(self, other: U8x16) -> U8x16 {
+        U8x16(wasm32::v128_and(self.0, other.0))
+    }
+}
+
+impl BitOr<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn bitor(self, other: U8x16) -> U8x16 {
+        U8x16(wasm32::v128_or(self.0, other.0))
+    }
+}
+
+// Eight 16-bit unsigned integers
+
+/// Eight 16-bit unsigned integers, for intermediate results of byte arithmetic. Arithmetic wraps
+/// around.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct U16x8(pub v128);
+
+impl U16x8 {
+    // Constructors
+
+    #[inline]
+    pub fn from_array(values: [u16; 8]) -> U16x8 {
+        unsafe { U16x8(wasm32::v128_load(values.as_ptr() as *const v128)) }
+    }
+
+    #[inline]
+    pub fn splat(x: u16) -> U16x8 {
+        U16x8(wasm32::u16x8_splat(x))
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_array(self) -> [u16; 8] {
+        cast::<v128, [u16; 8]>(self.0)
+    }
+
+    /// Reinterprets these integers as signed. Values above 32767 wrap around.
+    #[inline]
+    pub fn to_i16x8(self) -> I16x8 {
+        I16x8(self.0)
+    }
+
+    /// Zero-extends the first four integers to 32 bits.
+    #[inline]
+    pub fn widen_low(self) -> I32x4 {
+        I32x4(wasm32::i32x4_extend_low_u16x8(self.0))
+    }
+
+    /// Zero-extends the last four integers to 32 bits.
+    #[inline]
+    pub fn widen_high(self) -> I32x4 {
+        I32x4(wasm32::i32x4_extend_high_u16x8(self.0))
+    }
+
+    /// Narrows eight 32-bit integers, `low` followed by `high`, to 16 bits. Values below 0 become
+    /// 0, and values above 65535 become 65535.
+    #[inline]
+    pub fn narrow(low: I32x4, high: I32x4) -> U16x8 {
+        U16x8(wasm32::u16x8_narrow_i32x4(low.0, high.0))
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: U16x8) -> U16x8 {
+        U16x8(wasm32::u16x8_min(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: U16x8) -> U16x8 {
+        U16x8(wasm32::u16x8_max(self.0, other.0))
+    }
+
+    /// Adds, clamping sums above 65535 to 65535.
+    #[inline]
+    pub fn saturating_add(self, other: U16x8) -> U16x8 {
+        U16x8(wasm32::u16x8_add_sat(self.0, other.0))
+    }
+
+    /// Subtracts, clamping differences below 0 to 0.
+    #[inline]
+    pub fn saturating_sub(self, other: U16x8) -> U16x8 {
+        U16x8(wasm32::u16x8_sub_sat(self.0, other.0))
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U16x8) -> U16x8 {
+        U16x8(wasm32::u16x8_eq(self.0, other.0))
+    }
+}
+
+impl Default for U16x8 {
+    #[inline]
+    fn default() -> U16x8 {
+        U16x8::splat(0)
+    }
+}
+
+impl Index<usize> for U16x8 {
+    type Output = u16;
+    #[inline]
+    fn index(&self, index: usize) -> &u16 {
+        &cast_ref::<v128, [u16; 8]>(&self.0)[index]
+    }
+}
+
+impl Debug for U16x8 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::U16x8(self.to_array()).fmt(f)
+    }
+}
+
+impl PartialEq for U16x8 {
+    #[inline]
+    fn eq(&self, other: &U16x8) -> bool {
+        wasm32::u16x8_all_true(self.packed_eq(*other).0)
+    }
+}
+
+impl Add<U16x8> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn add(self, other: U16x8) -> U16x8 {
+        U16x8(wasm32::u16x8_add(self.0, other.0))
+    }
+}
+
+impl Sub<U16x8> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn sub(self, other: U16x8) -> U16x8 {
+        U16x8(wasm32::u16x8_sub(self.0, other.0))
+    }
+}
+
+impl Mul<U16x8> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn mul(self, other: U16x8) -> U16x8 {
+        U16x8(wasm32::u16x8_mul(self.0, other.0))
+    }
+}
+
+impl Shl<u32> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn shl(self, amount: u32) -> U16x8 {
+        // WebAssembly takes shift amounts modulo the lane width, unlike SSE.
+        if amount >= 16 {
+            return U16x8::splat(0);
+        }
+        U16x8(wasm32::u16x8_shl(self.0, amount))
+    }
+}
+
+impl Shr<u32> for U16x8 {
+    type Output = U16x8;
+    #[inline]
+    fn shr(self, amount: u32) -> U16x8 {
+        U16x8(wasm32::u16x8_shr(self.0, amount))
+    }
+}
+
+// Eight 16-bit signed integers
+
+/// Eight 16-bit signed integers, for intermediate results of byte arithmetic that can go
+/// negative, such as coverage deltas. Arithmetic wraps around.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct I16x8(pub v128);
+
+impl I16x8 {
+    // Constructors
+
+    #[inline]
+    pub fn from_array(values: [i16; 8]) -> I16x8 {
+        unsafe { I16x8(wasm32::v128_load(values.as_ptr() as *const v128)) }
+    }
+
+    #[inline]
+    pub fn splat(x: i16) -> I16x8 {
+        I16x8(wasm32::i16x8_splat(x))
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_array(self) -> [i16; 8] {
+        cast::<v128, [i16; 8]>(self.0)
+    }
+
+    /// Reinterprets these integers as unsigned. Negative values wrap around.
+    #[inline]
+    pub fn to_u16x8(self) -> U16x8 {
+        U16x8(self.0)
+    }
+
+    /// Sign-extends the first four integers to 32 bits.
+    #[inline]
+    pub fn widen_low(self) -> I32x4 {
+        I32x4(wasm32::i32x4_extend_low_i16x8(self.0))
+    }
+
+    /// Sign-extends the last four integers to 32 bits.
+    #[inline]
+    pub fn widen_high(self) -> I32x4 {
+        I32x4(wasm32::i32x4_extend_high_i16x8(self.0))
+    }
+
+    /// Narrows eight 32-bit integers, `low` followed by `high`, to 16 bits. Values outside the
+    /// range of `i16` are clamped to it.
+    #[inline]
+    pub fn narrow(low: I32x4, high: I32x4) -> I16x8 {
+        I16x8(wasm32::i16x8_narrow_i32x4(low.0, high.0))
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: I16x8) -> I16x8 {
+        I16x8(wasm32::i16x8_min(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: I16x8) -> I16x8 {
+        I16x8(wasm32::i16x8_max(self.0, other.0))
+    }
+
+    /// Adds, clamping sums to the range of `i16`.
+    #[inline]
+    pub fn saturating_add(self, other: I16x8) -> I16x8 {
+        I16x8(wasm32::i16x8_add_sat(self.0, other.0))
+    }
+
+    /// Subtracts, clamping differences to the range of `i16`.
+    #[inline]
+    pub fn saturating_sub(self, other: I16x8) -> I16x8 {
+        I16x8(wasm32::i16x8_sub_sat(self.0, other.0))
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: I16x8) -> I16x8 {
+        I16x8(wasm32::i16x8_eq(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: I16x8) -> I16x8 {
+        I16x8(wasm32::i16x8_gt(self.0, other.0))
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I16x8) -> I16x8 {
+        I16x8(wasm32::i16x8_lt(self.0, other.0))
+    }
+}
+
+impl Default for I16x8 {
+    #[inline]
+    fn default() -> I16x8 {
+        I16x8::splat(0)
+    }
+}
+
+impl Index<usize> for I16x8 {
+    type Output = i16;
+    #[inline]
+    fn index(&self, index: usize) -> &i16 {
+        &cast_ref::<v128, [i16; 8]>(&self.0)[index]
+    }
+}
+
+impl Debug for I16x8 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::I16x8(self.to_array()).fmt(f)
+    }
+}
+
+impl PartialEq for I16x8 {
+    #[inline]
+    fn eq(&self, other: &I16x8) -> bool {
+        wasm32::i16x8_all_true(self.packed_eq(*other).0)
+    }
+}
+
+impl Add<I16x8> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn add(self, other: I16x8) -> I16x8 {
+        I16x8(wasm32::i16x8_add(self.0, other.0))
+    }
+}
+
+impl Sub<I16x8> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn sub(self, other: I16x8) -> I16x8 {
+        I16x8(wasm32::i16x8_sub(self.0, other.0))
+    }
+}
+
+impl Mul<I16x8> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn mul(self, other: I16x8) -> I16x8 {
+        I16x8(wasm32::i16x8_mul(self.0, other.0))
+    }
+}
+
+impl Shl<u32> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn shl(self, amount: u32) -> I16x8 {
+        // WebAssembly takes shift amounts modulo the lane width, unlike SSE.
+        if amount >= 16 {
+            return I16x8::splat(0);
+        }
+        I16x8(wasm32::i16x8_shl(self.0, amount))
+    }
+}
+
+impl Shr<u32> for I16x8 {
+    type Output = I16x8;
+    #[inline]
+    fn shr(self, amount: u32) -> I16x8 {
+        // Shifting right by 15 already fills every bit with the sign.
+        I16x8(wasm32::i16x8_shr(self.0, u32::min(amount, 15)))
+    }
+}
@@ -8,19 +8,19 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::cmp::PartialEq;
-use std::fmt::{self, Debug, Formatter};
-use std::mem;
-use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Index, IndexMut, Mul, Not, Shr, Sub};
+use crate::cast::{cast, cast_mut, cast_ref};
+use core::cmp::PartialEq;
+use core::fmt::{self, Debug, Formatter};
+use core::ops::{Add, BitAnd, BitOr, BitXor, Div, Index, IndexMut, Mul, Not, Shl, Shr, Sub};
 
 #[cfg(target_pointer_width = "32")]
-use std::arch::x86::{__m128, __m128i};
+use core::arch::x86::{__m128, __m128d, __m128i, __m256, __m256d};
 #[cfg(target_pointer_width = "32")]
-use std::arch::x86;
+use core::arch::x86;
 #[cfg(target_pointer_width = "64")]
-use std::arch::x86_64::{__m128, __m128i};
+use core::arch::x86_64::{__m128, __m128d, __m128i, __m256, __m256d};
 #[cfg(target_pointer_width = "64")]
-use std::arch::x86_64 as x86;
+use core::arch::x86_64 as x86;
 
 mod swizzle_f32x4;
 mod swizzle_i32x4;
@@ -28,6 +28,7 @@ mod swizzle_i32x4;
 // Two 32-bit floats
 
 #[derive(Clone, Copy)]
+#[repr(transparent)]
 pub struct F32x2(pub u64);
 
 impl F32x2 {
@@ -35,11 +36,7 @@ impl F32x2 {
 
     #[inline]
     pub fn new(a: f32, b: f32) -> F32x2 {
-        unsafe {
-            let a = mem::transmute::<*const f32, *const u32>(&a);
-            let b = mem::transmute::<*const f32, *const u32>(&b);
-            F32x2((*a as u64) | ((*b as u64) << 32))
-        }
+        F32x2((a.to_bits() as u64) | ((b.to_bits() as u64) << 32))
     }
 
     #[inline]
@@ -54,6 +51,12 @@ impl F32x2 {
         self.to_f32x4().approx_recip().xy()
     }
 
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x2 {
+        self.to_f32x4().approx_rsqrt().xy()
+    }
+
     #[inline]
     pub fn min(self, other: F32x2) -> F32x2 {
         self.to_f32x4().min(other.to_f32x4()).xy()
@@ -74,6 +77,20 @@ impl F32x2 {
         self.to_f32x4().abs().xy()
     }
 
+    /// Returns the magnitude of each lane with the sign of the same lane of `sign`. See
+    /// `F32x4::copysign()`.
+    #[inline]
+    pub fn copysign(self, sign: F32x2) -> F32x2 {
+        self.to_f32x4().copysign(sign.to_f32x4()).xy()
+    }
+
+    /// Returns the sign bits of the lanes packed into the low two bits, with the first lane's in
+    /// bit 0. See `F32x4::sign_bits()`.
+    #[inline]
+    pub fn sign_bits(self) -> u32 {
+        self.to_f32x4().sign_bits()
+    }
+
     #[inline]
     pub fn floor(self) -> F32x2 {
         self.to_f32x4().floor().xy()
@@ -84,11 +101,56 @@ impl F32x2 {
         self.to_f32x4().ceil().xy()
     }
 
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x2 {
+        self.to_f32x4().round().xy()
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x2 {
+        self - self.floor()
+    }
+
     #[inline]
     pub fn sqrt(self) -> F32x2 {
         self.to_f32x4().sqrt().xy()
     }
 
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F32x2, b: F32x2) -> F32x2 {
+        self.to_f32x4().mul_add(a.to_f32x4(), b.to_f32x4()).xy()
+    }
+
+    // Horizontal operations
+
+    /// Returns the sum of both lanes.
+    #[inline]
+    pub fn sum(self) -> f32 {
+        self[0] + self[1]
+    }
+
+    /// Returns the smaller of the two lanes.
+    #[inline]
+    pub fn min_element(self) -> f32 {
+        f32::min(self[0], self[1])
+    }
+
+    /// Returns the larger of the two lanes.
+    #[inline]
+    pub fn max_element(self) -> f32 {
+        f32::max(self[0], self[1])
+    }
+
+    /// Returns the dot product of this vector and `other`.
+    #[inline]
+    pub fn dot(self, other: F32x2) -> f32 {
+        (self * other).sum()
+    }
+
     // Packed comparisons
 
     #[inline]
@@ -111,15 +173,32 @@ impl F32x2 {
         self.to_f32x4().packed_le(other.to_f32x4()).xy()
     }
 
+    #[inline]
+    pub fn packed_ge(self, other: F32x2) -> U32x2 {
+        self.to_f32x4().packed_ge(other.to_f32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: F32x2) -> U32x2 {
+        self.to_f32x4().packed_ne(other.to_f32x4()).xy()
+    }
+
+    /// Returns the lanes of `a` where `mask` is true and those of `b` where it's false.
+    ///
+    /// The result is *undefined* if both values in `mask` are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn select(mask: U32x2, a: F32x2, b: F32x2) -> F32x2 {
+        F32x2((mask.0 & a.0) | (!mask.0 & b.0))
+    }
+
     // Conversions
 
     #[inline]
     pub fn to_f32x4(self) -> F32x4 {
-        unsafe {
-            let mut result = F32x4::default();
-            *mem::transmute::<&mut __m128, &mut u64>(&mut result.0) = self.0;
-            result
-        }
+        let mut result = F32x4::default();
+        *cast_mut::<__m128, u64>(&mut result.0) = self.0;
+        result
     }
 
     #[inline]
@@ -158,14 +237,14 @@ impl Index<usize> for F32x2 {
     type Output = f32;
     #[inline]
     fn index(&self, index: usize) -> &f32 {
-        unsafe { &mem::transmute::<&u64, &[f32; 2]>(&self.0)[index] }
+        &cast_ref::<u64, [f32; 2]>(&self.0)[index]
     }
 }
 
 impl IndexMut<usize> for F32x2 {
     #[inline]
     fn index_mut(&mut self, index: usize) -> &mut f32 {
-        unsafe { &mut mem::transmute::<&mut u64, &mut [f32; 2]>(&mut self.0)[index] }
+        &mut cast_mut::<u64, [f32; 2]>(&mut self.0)[index]
     }
 }
 
@@ -218,6 +297,7 @@ impl Sub<F32x2> for F32x2 {
 // Four 32-bit floats
 
 #[derive(Clone, Copy)]
+#[repr(transparent)]
 pub struct F32x4(pub __m128);
 
 impl F32x4 {
@@ -236,6 +316,55 @@ impl F32x4 {
         unsafe { F32x4(x86::_mm_set1_ps(x)) }
     }
 
+    /// Loads the first four elements of `slice`, which needn't be aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn from_slice(slice: &[f32]) -> F32x4 {
+        let slice = &slice[..4];
+        unsafe { F32x4(x86::_mm_loadu_ps(slice.as_ptr())) }
+    }
+
+    /// Loads the first four elements of `slice`, which must start on a 16-byte boundary. This can
+    /// be faster than `from_slice` on some CPUs.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn from_slice_aligned(slice: &[f32]) -> F32x4 {
+        let slice = &slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        F32x4(x86::_mm_load_ps(slice.as_ptr()))
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which needn't be
+    /// aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn write_to_slice(self, slice: &mut [f32]) {
+        let slice = &mut slice[..4];
+        unsafe { x86::_mm_storeu_ps(slice.as_mut_ptr(), self.0) }
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which must start
+    /// on a 16-byte boundary.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_mut_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn write_to_slice_aligned(self, slice: &mut [f32]) {
+        let slice = &mut slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        x86::_mm_store_ps(slice.as_mut_ptr(), self.0)
+    }
+
     // Basic operations
 
     #[inline]
@@ -243,6 +372,12 @@ impl F32x4 {
         unsafe { F32x4(x86::_mm_rcp_ps(self.0)) }
     }
 
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x4 {
+        unsafe { F32x4(x86::_mm_rsqrt_ps(self.0)) }
+    }
+
     #[inline]
     pub fn min(self, other: F32x4) -> F32x4 {
         unsafe { F32x4(x86::_mm_min_ps(self.0, other.0)) }
@@ -266,6 +401,26 @@ impl F32x4 {
         }
     }
 
+    /// Returns the magnitude of each lane with the sign of the same lane of `sign`.
+    ///
+    /// Only the sign bit of `sign` matters, so negative zero and NaNs with the sign bit set count
+    /// as negative.
+    #[inline]
+    pub fn copysign(self, sign: F32x4) -> F32x4 {
+        unsafe {
+            let sign_bit = x86::_mm_set1_ps(-0.0);
+            let magnitude = x86::_mm_andnot_ps(sign_bit, self.0);
+            F32x4(x86::_mm_or_ps(magnitude, x86::_mm_and_ps(sign_bit, sign.0)))
+        }
+    }
+
+    /// Returns the sign bits of the lanes packed into the low four bits, with the first lane's in
+    /// bit 0, like `movmskps`. As with `copysign()`, negative zero and negative NaNs count.
+    #[inline]
+    pub fn sign_bits(self) -> u32 {
+        unsafe { x86::_mm_movemask_ps(self.0) as u32 }
+    }
+
     #[inline]
     pub fn floor(self) -> F32x4 {
         unsafe { F32x4(x86::_mm_floor_ps(self.0)) }
@@ -276,11 +431,82 @@ impl F32x4 {
         unsafe { F32x4(x86::_mm_ceil_ps(self.0)) }
     }
 
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x4 {
+        unsafe {
+            F32x4(x86::_mm_round_ps(self.0,
+                                    x86::_MM_FROUND_TO_NEAREST_INT | x86::_MM_FROUND_NO_EXC))
+        }
+    }
+
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
+    #[inline]
+    pub fn fract(self) -> F32x4 {
+        self - self.floor()
+    }
+
     #[inline]
     pub fn sqrt(self) -> F32x4 {
         unsafe { F32x4(x86::_mm_sqrt_ps(self.0)) }
     }
 
+    /// Returns `self * a + b`, rounded once if the target supports fused multiply-add.
+    ///
+    /// On x86, that requires building with the `fma` target feature. Otherwise, this is an
+    /// ordinary multiply followed by an add, which rounds twice.
+    #[inline]
+    pub fn mul_add(self, a: F32x4, b: F32x4) -> F32x4 {
+        #[cfg(target_feature = "fma")]
+        unsafe {
+            F32x4(x86::_mm_fmadd_ps(self.0, a.0, b.0))
+        }
+        #[cfg(not(target_feature = "fma"))]
+        {
+            self * a + b
+        }
+    }
+
+    /// Returns the cross product of the first three lanes of this vector and `other`.
+    ///
+    /// The w lanes are ignored, and the w lane of the result is zero.
+    #[inline]
+    pub fn cross(self, other: F32x4) -> F32x4 {
+        // Computes (z, x, y) and rotates it into place, saving a shuffle.
+        let product = (self * other.yzxw() - self.yzxw() * other).yzxw();
+        unsafe { F32x4(x86::_mm_blend_ps(product.0, x86::_mm_setzero_ps(), 0b1000)) }
+    }
+
+    // Horizontal operations
+
+    /// Returns the sum of all four lanes.
+    #[inline]
+    pub fn sum(self) -> f32 {
+        let pairs = self + self.yxwz();
+        (pairs + pairs.zwxy())[0]
+    }
+
+    /// Returns the smallest of the four lanes.
+    #[inline]
+    pub fn min_element(self) -> f32 {
+        let pairs = self.min(self.yxwz());
+        pairs.min(pairs.zwxy())[0]
+    }
+
+    /// Returns the largest of the four lanes.
+    #[inline]
+    pub fn max_element(self) -> f32 {
+        let pairs = self.max(self.yxwz());
+        pairs.max(pairs.zwxy())[0]
+    }
+
+    /// Returns the dot product of this vector and `other`, using all four lanes.
+    #[inline]
+    pub fn dot(self, other: F32x4) -> f32 {
+        (self * other).sum()
+    }
+
     // Packed comparisons
 
     #[inline]
@@ -308,27 +534,99 @@ impl F32x4 {
 
     #[inline]
     pub fn packed_le(self, other: F32x4) -> U32x4 {
-        !self.packed_gt(other)
+        unsafe {
+            U32x4(x86::_mm_castps_si128(x86::_mm_cmple_ps(
+                self.0, other.0,
+            )))
+        }
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: F32x4) -> U32x4 {
+        unsafe {
+            U32x4(x86::_mm_castps_si128(x86::_mm_cmpge_ps(
+                self.0, other.0,
+            )))
+        }
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: F32x4) -> U32x4 {
+        unsafe {
+            U32x4(x86::_mm_castps_si128(x86::_mm_cmpneq_ps(
+                self.0, other.0,
+            )))
+        }
+    }
+
+    /// Returns the lanes of `a` where `mask` is true and those of `b` where it's false.
+    ///
+    /// The result is *undefined* if all four values in `mask` are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn select(mask: U32x4, a: F32x4, b: F32x4) -> F32x4 {
+        unsafe { F32x4(x86::_mm_blendv_ps(b.0, a.0, x86::_mm_castsi128_ps(mask.0))) }
     }
 
     // Conversions
 
-    /// Converts these packed floats to integers via rounding.
+    /// Converts these packed floats to integers via rounding. This is the same as
+    /// `to_i32x4_round()`.
     #[inline]
     pub fn to_i32x4(self) -> I32x4 {
+        self.to_i32x4_round()
+    }
+
+    /// Converts these packed floats to integers, rounding to the nearest integer and halfway cases
+    /// to the even one.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_round(self) -> I32x4 {
+        // This assumes the default rounding mode in MXCSR, which Rust code never changes.
         unsafe { I32x4(x86::_mm_cvtps_epi32(self.0)) }
     }
 
-    // Extraction
+    /// Converts these packed floats to integers, rounding toward zero.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
+    #[inline]
+    pub fn to_i32x4_truncate(self) -> I32x4 {
+        unsafe { I32x4(x86::_mm_cvttps_epi32(self.0)) }
+    }
 
+    /// Converts these packed floats to integers, rounding toward negative infinity.
+    ///
+    /// Values that don't fit in an `i32`, and NaNs, give unspecified results.
     #[inline]
-    pub fn xy(self) -> F32x2 {
+    pub fn to_i32x4_floor(self) -> I32x4 {
+        unsafe { I32x4(x86::_mm_cvttps_epi32(x86::_mm_floor_ps(self.0))) }
+    }
+
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order.
+    ///
+    /// This covers every permutation, so it's handy when the lanes are chosen by other generic
+    /// code. `v.shuffle::<2, 3, 0, 1>()` is the same as `v.zwxy()`. Every index must be less
+    /// than 4.
+    #[inline]
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> F32x4 {
+        debug_assert!(A < 4 && B < 4 && C < 4 && D < 4, "shuffle lane index out of range");
         unsafe {
-            let swizzled = self.0;
-            F32x2(*mem::transmute::<&__m128, &u64>(&swizzled))
+            let lanes = x86::_mm_castps_si128(self.0);
+            F32x4(x86::_mm_castsi128_ps(shuffle_lanes(lanes, [A, B, C, D])))
         }
     }
 
+    // Extraction
+
+    #[inline]
+    pub fn xy(self) -> F32x2 {
+        F32x2(*cast_ref::<__m128, u64>(&self.0))
+    }
+
     #[inline]
     pub fn xw(self) -> F32x2 {
         self.xwyz().xy()
@@ -398,14 +696,14 @@ impl Index<usize> for F32x4 {
     type Output = f32;
     #[inline]
     fn index(&self, index: usize) -> &f32 {
-        unsafe { &mem::transmute::<&__m128, &[f32; 4]>(&self.0)[index] }
+        &cast_ref::<__m128, [f32; 4]>(&self.0)[index]
     }
 }
 
 impl IndexMut<usize> for F32x4 {
     #[inline]
     fn index_mut(&mut self, index: usize) -> &mut f32 {
-        unsafe { &mut mem::transmute::<&mut __m128, &mut [f32; 4]>(&mut self.0)[index] }
+        &mut cast_mut::<__m128, [f32; 4]>(&mut self.0)[index]
     }
 }
 
@@ -455,537 +753,1995 @@ impl Sub<F32x4> for F32x4 {
     }
 }
 
-// Two 32-bit signed integers
+// Eight 32-bit floats
 
+/// Eight 32-bit floats, processed with AVX if the target supports it and as two `F32x4` halves
+/// otherwise.
 #[derive(Clone, Copy)]
-pub struct I32x2(pub u64);
+#[repr(transparent)]
+pub struct F32x8(pub __m256);
 
-impl I32x2 {
+impl F32x8 {
     // Constructors
 
     #[inline]
-    pub fn new(a: i32, b: i32) -> I32x2 {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32, g: f32, h: f32) -> F32x8 {
+        F32x8(cast::<[f32; 8], __m256>([a, b, c, d, e, f, g, h]))
+    }
+
+    #[inline]
+    pub fn splat(x: f32) -> F32x8 {
+        #[cfg(target_feature = "avx")]
         unsafe {
-            let a = mem::transmute::<*const i32, *const u32>(&a);
-            let b = mem::transmute::<*const i32, *const u32>(&b);
-            I32x2((*a as u64) | ((*b as u64) << 32))
+            F32x8(x86::_mm256_set1_ps(x))
         }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(F32x4::splat(x), F32x4::splat(x))
     }
 
     #[inline]
-    pub fn splat(x: i32) -> I32x2 {
-        I32x2::new(x, x)
+    pub fn from_halves(lo: F32x4, hi: F32x4) -> F32x8 {
+        F32x8(cast::<[__m128; 2], __m256>([lo.0, hi.0]))
     }
 
     // Accessors
 
+    /// Returns the first four lanes.
     #[inline]
-    pub fn x(self) -> i32 {
-        self[0]
+    pub fn lo(self) -> F32x4 {
+        F32x4(cast::<__m256, [__m128; 2]>(self.0)[0])
     }
 
+    /// Returns the last four lanes.
     #[inline]
-    pub fn y(self) -> i32 {
-        self[1]
+    pub fn hi(self) -> F32x4 {
+        F32x4(cast::<__m256, [__m128; 2]>(self.0)[1])
     }
 
-    // Concatenations
+    // Basic operations
 
     #[inline]
-    pub fn concat_xy_xy(self, other: I32x2) -> I32x4 {
-        self.to_i32x4().concat_xy_xy(other.to_i32x4())
+    pub fn approx_recip(self) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F32x8(x86::_mm256_rcp_ps(self.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo().approx_recip(), self.hi().approx_recip())
     }
 
-    // Conversions
+    /// Estimates the reciprocal square root of each lane.
+    #[inline]
+    pub fn approx_rsqrt(self) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F32x8(x86::_mm256_rsqrt_ps(self.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo().approx_rsqrt(), self.hi().approx_rsqrt())
+    }
 
     #[inline]
-    pub fn to_i32x4(self) -> I32x4 {
+    pub fn min(self, other: F32x8) -> F32x8 {
+        #[cfg(target_feature = "avx")]
         unsafe {
-            let mut result = I32x4::default();
-            *mem::transmute::<&mut __m128i, &mut u64>(&mut result.0) = self.0;
-            result
+            F32x8(x86::_mm256_min_ps(self.0, other.0))
         }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo().min(other.lo()), self.hi().min(other.hi()))
     }
 
     #[inline]
-    pub fn to_f32x4(self) -> F32x4 {
-        self.to_i32x4().to_f32x4()
+    pub fn max(self, other: F32x8) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F32x8(x86::_mm256_max_ps(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo().max(other.lo()), self.hi().max(other.hi()))
     }
 
-    /// Converts these packed integers to floats.
     #[inline]
-    pub fn to_f32x2(self) -> F32x2 {
-        self.to_f32x4().xy()
+    pub fn clamp(self, min: F32x8, max: F32x8) -> F32x8 {
+        self.max(min).min(max)
     }
 
-    // Basic operations
+    #[inline]
+    pub fn abs(self) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            let mask = x86::_mm256_castsi256_ps(x86::_mm256_set1_epi32(0x7fff_ffff));
+            F32x8(x86::_mm256_and_ps(mask, self.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo().abs(), self.hi().abs())
+    }
 
     #[inline]
-    pub fn max(self, other: I32x2) -> I32x2 {
-        self.to_i32x4().max(other.to_i32x4()).xy()
+    pub fn floor(self) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F32x8(x86::_mm256_floor_ps(self.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo().floor(), self.hi().floor())
     }
 
     #[inline]
-    pub fn min(self, other: I32x2) -> I32x2 {
-        self.to_i32x4().min(other.to_i32x4()).xy()
+    pub fn ceil(self) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F32x8(x86::_mm256_ceil_ps(self.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo().ceil(), self.hi().ceil())
     }
 
-    // Comparisons
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F32x8(x86::_mm256_round_ps(self.0,
+                                       x86::_MM_FROUND_TO_NEAREST_INT | x86::_MM_FROUND_NO_EXC))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo().round(), self.hi().round())
+    }
 
-    // TODO(pcwalton): Use the `U32x2` type!
+    /// Returns the fractional part of each lane, `self - self.floor()`, which is always in
+    /// [0, 1) for finite lanes.
     #[inline]
-    pub fn packed_eq(self, other: I32x2) -> U32x4 {
-        self.to_i32x4().packed_eq(other.to_i32x4())
+    pub fn fract(self) -> F32x8 {
+        self - self.floor()
     }
 
     #[inline]
-    pub fn packed_gt(self, other: I32x2) -> U32x4 {
-        self.to_i32x4().packed_gt(other.to_i32x4())
+    pub fn sqrt(self) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F32x8(x86::_mm256_sqrt_ps(self.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo().sqrt(), self.hi().sqrt())
     }
 
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
     #[inline]
-    pub fn packed_le(self, other: I32x2) -> U32x4 {
-        self.to_i32x4().packed_le(other.to_i32x4())
+    pub fn mul_add(self, a: F32x8, b: F32x8) -> F32x8 {
+        #[cfg(all(target_feature = "avx", target_feature = "fma"))]
+        unsafe {
+            F32x8(x86::_mm256_fmadd_ps(self.0, a.0, b.0))
+        }
+        #[cfg(not(all(target_feature = "avx", target_feature = "fma")))]
+        F32x8::from_halves(self.lo().mul_add(a.lo(), b.lo()), self.hi().mul_add(a.hi(), b.hi()))
     }
 }
 
-impl Default for I32x2 {
+impl Default for F32x8 {
     #[inline]
-    fn default() -> I32x2 {
-        I32x2(0)
+    fn default() -> F32x8 {
+        F32x8::splat(0.0)
     }
 }
 
-impl Index<usize> for I32x2 {
-    type Output = i32;
+impl Index<usize> for F32x8 {
+    type Output = f32;
     #[inline]
-    fn index(&self, index: usize) -> &i32 {
-        unsafe { &mem::transmute::<&u64, &[i32; 2]>(&self.0)[index] }
+    fn index(&self, index: usize) -> &f32 {
+        &cast_ref::<__m256, [f32; 8]>(&self.0)[index]
     }
 }
 
-impl IndexMut<usize> for I32x2 {
+impl IndexMut<usize> for F32x8 {
     #[inline]
-    fn index_mut(&mut self, index: usize) -> &mut i32 {
-        unsafe { &mut mem::transmute::<&mut u64, &mut [i32; 2]>(&mut self.0)[index] }
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        &mut cast_mut::<__m256, [f32; 8]>(&mut self.0)[index]
     }
 }
 
-impl Add<I32x2> for I32x2 {
-    type Output = I32x2;
+impl Debug for F32x8 {
     #[inline]
-    fn add(self, other: I32x2) -> I32x2 {
-        (self.to_i32x4() + other.to_i32x4()).xy()
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f,
+               "<{}, {}, {}, {}, {}, {}, {}, {}>",
+               self[0], self[1], self[2], self[3], self[4], self[5], self[6], self[7])
     }
 }
 
-impl Sub<I32x2> for I32x2 {
-    type Output = I32x2;
+impl PartialEq for F32x8 {
     #[inline]
-    fn sub(self, other: I32x2) -> I32x2 {
-        (self.to_i32x4() - other.to_i32x4()).xy()
+    fn eq(&self, other: &F32x8) -> bool {
+        self.lo() == other.lo() && self.hi() == other.hi()
     }
 }
 
-impl Mul<I32x2> for I32x2 {
-    type Output = I32x2;
+impl Add<F32x8> for F32x8 {
+    type Output = F32x8;
     #[inline]
-    fn mul(self, other: I32x2) -> I32x2 {
-        (self.to_i32x4() * other.to_i32x4()).xy()
+    fn add(self, other: F32x8) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F32x8(x86::_mm256_add_ps(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo() + other.lo(), self.hi() + other.hi())
     }
 }
 
-impl Debug for I32x2 {
+impl Div<F32x8> for F32x8 {
+    type Output = F32x8;
     #[inline]
-    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        write!(f, "<{}, {}>", self[0], self[1])
+    fn div(self, other: F32x8) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F32x8(x86::_mm256_div_ps(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo() / other.lo(), self.hi() / other.hi())
     }
 }
 
-impl PartialEq for I32x2 {
+impl Mul<F32x8> for F32x8 {
+    type Output = F32x8;
     #[inline]
-    fn eq(&self, other: &I32x2) -> bool {
-        self.packed_eq(*other).all_true()
+    fn mul(self, other: F32x8) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F32x8(x86::_mm256_mul_ps(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo() * other.lo(), self.hi() * other.hi())
     }
 }
 
-// Four 32-bit signed integers
+impl Sub<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn sub(self, other: F32x8) -> F32x8 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F32x8(x86::_mm256_sub_ps(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F32x8::from_halves(self.lo() - other.lo(), self.hi() - other.hi())
+    }
+}
+
+// Two 64-bit floats
 
 #[derive(Clone, Copy)]
-pub struct I32x4(pub __m128i);
+#[repr(transparent)]
+pub struct F64x2(pub __m128d);
 
-impl I32x4 {
+impl F64x2 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: f64, b: f64) -> F64x2 {
+        F64x2(cast::<[f64; 2], __m128d>([a, b]))
+    }
+
+    #[inline]
+    pub fn splat(x: f64) -> F64x2 {
+        unsafe { F64x2(x86::_mm_set1_pd(x)) }
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: F64x2) -> F64x2 {
+        unsafe { F64x2(x86::_mm_min_pd(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn max(self, other: F64x2) -> F64x2 {
+        unsafe { F64x2(x86::_mm_max_pd(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn clamp(self, min: F64x2, max: F64x2) -> F64x2 {
+        self.max(min).min(max)
+    }
+
+    #[inline]
+    pub fn abs(self) -> F64x2 {
+        unsafe {
+            let mask = x86::_mm_srli_epi64(I32x4::splat(-1).0, 1);
+            F64x2(x86::_mm_and_pd(x86::_mm_castsi128_pd(mask), self.0))
+        }
+    }
+
+    #[inline]
+    pub fn floor(self) -> F64x2 {
+        unsafe { F64x2(x86::_mm_floor_pd(self.0)) }
+    }
+
+    #[inline]
+    pub fn ceil(self) -> F64x2 {
+        unsafe { F64x2(x86::_mm_ceil_pd(self.0)) }
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F64x2 {
+        unsafe {
+            F64x2(x86::_mm_round_pd(self.0,
+                                    x86::_MM_FROUND_TO_NEAREST_INT | x86::_MM_FROUND_NO_EXC))
+        }
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F64x2 {
+        unsafe { F64x2(x86::_mm_sqrt_pd(self.0)) }
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F64x2, b: F64x2) -> F64x2 {
+        #[cfg(target_feature = "fma")]
+        unsafe {
+            F64x2(x86::_mm_fmadd_pd(self.0, a.0, b.0))
+        }
+        #[cfg(not(target_feature = "fma"))]
+        {
+            self * a + b
+        }
+    }
+
+    // Horizontal operations
+
+    /// Returns the sum of both lanes.
+    #[inline]
+    pub fn sum(self) -> f64 {
+        self[0] + self[1]
+    }
+
+    /// Returns the dot product of this vector and `other`.
+    #[inline]
+    pub fn dot(self, other: F64x2) -> f64 {
+        (self * other).sum()
+    }
+
+    // Swizzle
+
+    #[inline]
+    pub fn yx(self) -> F64x2 {
+        unsafe { F64x2(x86::_mm_shuffle_pd(self.0, self.0, 0b01)) }
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: F64x2) -> F64x4 {
+        F64x4::from_halves(self, other)
+    }
+}
+
+impl Default for F64x2 {
+    #[inline]
+    fn default() -> F64x2 {
+        unsafe { F64x2(x86::_mm_setzero_pd()) }
+    }
+}
+
+impl Index<usize> for F64x2 {
+    type Output = f64;
+    #[inline]
+    fn index(&self, index: usize) -> &f64 {
+        &cast_ref::<__m128d, [f64; 2]>(&self.0)[index]
+    }
+}
+
+impl IndexMut<usize> for F64x2 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut f64 {
+        &mut cast_mut::<__m128d, [f64; 2]>(&mut self.0)[index]
+    }
+}
+
+impl Debug for F64x2 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}>", self[0], self[1])
+    }
+}
+
+impl PartialEq for F64x2 {
+    #[inline]
+    fn eq(&self, other: &F64x2) -> bool {
+        unsafe { x86::_mm_movemask_pd(x86::_mm_cmpeq_pd(self.0, other.0)) == 0b11 }
+    }
+}
+
+impl Add<F64x2> for F64x2 {
+    type Output = F64x2;
+    #[inline]
+    fn add(self, other: F64x2) -> F64x2 {
+        unsafe { F64x2(x86::_mm_add_pd(self.0, other.0)) }
+    }
+}
+
+impl Div<F64x2> for F64x2 {
+    type Output = F64x2;
+    #[inline]
+    fn div(self, other: F64x2) -> F64x2 {
+        unsafe { F64x2(x86::_mm_div_pd(self.0, other.0)) }
+    }
+}
+
+impl Mul<F64x2> for F64x2 {
+    type Output = F64x2;
+    #[inline]
+    fn mul(self, other: F64x2) -> F64x2 {
+        unsafe { F64x2(x86::_mm_mul_pd(self.0, other.0)) }
+    }
+}
+
+impl Sub<F64x2> for F64x2 {
+    type Output = F64x2;
+    #[inline]
+    fn sub(self, other: F64x2) -> F64x2 {
+        unsafe { F64x2(x86::_mm_sub_pd(self.0, other.0)) }
+    }
+}
+
+// Four 64-bit floats
+
+/// Four 64-bit floats, processed with AVX if the target supports it and as two `F64x2` halves
+/// otherwise.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct F64x4(pub __m256d);
+
+impl F64x4 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: f64, b: f64, c: f64, d: f64) -> F64x4 {
+        F64x4(cast::<[f64; 4], __m256d>([a, b, c, d]))
+    }
+
+    #[inline]
+    pub fn splat(x: f64) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F64x4(x86::_mm256_set1_pd(x))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(F64x2::splat(x), F64x2::splat(x))
+    }
+
+    #[inline]
+    pub fn from_halves(lo: F64x2, hi: F64x2) -> F64x4 {
+        F64x4(cast::<[__m128d; 2], __m256d>([lo.0, hi.0]))
+    }
+
+    // Accessors
+
+    /// Returns the first two lanes.
+    #[inline]
+    pub fn lo(self) -> F64x2 {
+        F64x2(cast::<__m256d, [__m128d; 2]>(self.0)[0])
+    }
+
+    /// Returns the last two lanes.
+    #[inline]
+    pub fn hi(self) -> F64x2 {
+        F64x2(cast::<__m256d, [__m128d; 2]>(self.0)[1])
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: F64x4) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F64x4(x86::_mm256_min_pd(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(self.lo().min(other.lo()), self.hi().min(other.hi()))
+    }
+
+    #[inline]
+    pub fn max(self, other: F64x4) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F64x4(x86::_mm256_max_pd(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(self.lo().max(other.lo()), self.hi().max(other.hi()))
+    }
+
+    #[inline]
+    pub fn clamp(self, min: F64x4, max: F64x4) -> F64x4 {
+        self.max(min).min(max)
+    }
+
+    #[inline]
+    pub fn abs(self) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            let mask = x86::_mm256_castsi256_pd(x86::_mm256_set1_epi64x(0x7fff_ffff_ffff_ffff));
+            F64x4(x86::_mm256_and_pd(mask, self.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(self.lo().abs(), self.hi().abs())
+    }
+
+    #[inline]
+    pub fn floor(self) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F64x4(x86::_mm256_floor_pd(self.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(self.lo().floor(), self.hi().floor())
+    }
+
+    #[inline]
+    pub fn ceil(self) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F64x4(x86::_mm256_ceil_pd(self.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(self.lo().ceil(), self.hi().ceil())
+    }
+
+    /// Rounds each lane to the nearest integer, with halfway cases rounded to the even one.
+    #[inline]
+    pub fn round(self) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F64x4(x86::_mm256_round_pd(self.0,
+                                       x86::_MM_FROUND_TO_NEAREST_INT | x86::_MM_FROUND_NO_EXC))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(self.lo().round(), self.hi().round())
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F64x4(x86::_mm256_sqrt_pd(self.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(self.lo().sqrt(), self.hi().sqrt())
+    }
+
+    /// Returns `self * a + b`. See `F32x4::mul_add()`.
+    #[inline]
+    pub fn mul_add(self, a: F64x4, b: F64x4) -> F64x4 {
+        #[cfg(all(target_feature = "avx", target_feature = "fma"))]
+        unsafe {
+            F64x4(x86::_mm256_fmadd_pd(self.0, a.0, b.0))
+        }
+        #[cfg(not(all(target_feature = "avx", target_feature = "fma")))]
+        F64x4::from_halves(self.lo().mul_add(a.lo(), b.lo()), self.hi().mul_add(a.hi(), b.hi()))
+    }
+
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order. See `F32x4::shuffle()`.
+    #[inline]
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> F64x4 {
+        // Only AVX2 can permute 64-bit lanes across halves, and its immediate can't be built from
+        // const parameters on stable Rust. The indices are constants, so LLVM turns this into
+        // shuffles anyway.
+        F64x4::new(self[A], self[B], self[C], self[D])
+    }
+}
+
+impl Default for F64x4 {
+    #[inline]
+    fn default() -> F64x4 {
+        F64x4::splat(0.0)
+    }
+}
+
+impl Index<usize> for F64x4 {
+    type Output = f64;
+    #[inline]
+    fn index(&self, index: usize) -> &f64 {
+        &cast_ref::<__m256d, [f64; 4]>(&self.0)[index]
+    }
+}
+
+impl IndexMut<usize> for F64x4 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut f64 {
+        &mut cast_mut::<__m256d, [f64; 4]>(&mut self.0)[index]
+    }
+}
+
+impl Debug for F64x4 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    }
+}
+
+impl PartialEq for F64x4 {
+    #[inline]
+    fn eq(&self, other: &F64x4) -> bool {
+        self.lo() == other.lo() && self.hi() == other.hi()
+    }
+}
+
+impl Add<F64x4> for F64x4 {
+    type Output = F64x4;
+    #[inline]
+    fn add(self, other: F64x4) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F64x4(x86::_mm256_add_pd(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(self.lo() + other.lo(), self.hi() + other.hi())
+    }
+}
+
+impl Div<F64x4> for F64x4 {
+    type Output = F64x4;
+    #[inline]
+    fn div(self, other: F64x4) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F64x4(x86::_mm256_div_pd(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(self.lo() / other.lo(), self.hi() / other.hi())
+    }
+}
+
+impl Mul<F64x4> for F64x4 {
+    type Output = F64x4;
+    #[inline]
+    fn mul(self, other: F64x4) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F64x4(x86::_mm256_mul_pd(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(self.lo() * other.lo(), self.hi() * other.hi())
+    }
+}
+
+impl Sub<F64x4> for F64x4 {
+    type Output = F64x4;
+    #[inline]
+    fn sub(self, other: F64x4) -> F64x4 {
+        #[cfg(target_feature = "avx")]
+        unsafe {
+            F64x4(x86::_mm256_sub_pd(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx"))]
+        F64x4::from_halves(self.lo() - other.lo(), self.hi() - other.hi())
+    }
+}
+
+// Two 32-bit signed integers
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct I32x2(pub u64);
+
+impl I32x2 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: i32, b: i32) -> I32x2 {
+        I32x2((a as u32 as u64) | ((b as u32 as u64) << 32))
+    }
+
+    #[inline]
+    pub fn splat(x: i32) -> I32x2 {
+        I32x2::new(x, x)
+    }
+
+    // Accessors
+
+    #[inline]
+    pub fn x(self) -> i32 {
+        self[0]
+    }
+
+    #[inline]
+    pub fn y(self) -> i32 {
+        self[1]
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: I32x2) -> I32x4 {
+        self.to_i32x4().concat_xy_xy(other.to_i32x4())
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        let mut result = I32x4::default();
+        *cast_mut::<__m128i, u64>(&mut result.0) = self.0;
+        result
+    }
+
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        self.to_i32x4().to_f32x4()
+    }
+
+    /// Converts these packed integers to floats.
+    #[inline]
+    pub fn to_f32x2(self) -> F32x2 {
+        self.to_f32x4().xy()
+    }
+
+    #[inline]
+    pub fn to_u32x2(self) -> U32x2 {
+        U32x2(self.0)
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn max(self, other: I32x2) -> I32x2 {
+        self.to_i32x4().max(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn min(self, other: I32x2) -> I32x2 {
+        self.to_i32x4().min(other.to_i32x4()).xy()
+    }
+
+    // Horizontal operations
+
+    /// Returns the smaller of the two lanes.
+    #[inline]
+    pub fn min_element(self) -> i32 {
+        i32::min(self[0], self[1])
+    }
+
+    /// Returns the larger of the two lanes.
+    #[inline]
+    pub fn max_element(self) -> i32 {
+        i32::max(self[0], self[1])
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_eq(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_gt(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_gt(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_le(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_lt(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_ge(other.to_i32x4()).xy()
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: I32x2) -> U32x2 {
+        self.to_i32x4().packed_ne(other.to_i32x4()).xy()
+    }
+}
+
+impl Default for I32x2 {
+    #[inline]
+    fn default() -> I32x2 {
+        I32x2(0)
+    }
+}
+
+impl Index<usize> for I32x2 {
+    type Output = i32;
+    #[inline]
+    fn index(&self, index: usize) -> &i32 {
+        &cast_ref::<u64, [i32; 2]>(&self.0)[index]
+    }
+}
+
+impl IndexMut<usize> for I32x2 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut i32 {
+        &mut cast_mut::<u64, [i32; 2]>(&mut self.0)[index]
+    }
+}
+
+impl Add<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn add(self, other: I32x2) -> I32x2 {
+        (self.to_i32x4() + other.to_i32x4()).xy()
+    }
+}
+
+impl Sub<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn sub(self, other: I32x2) -> I32x2 {
+        (self.to_i32x4() - other.to_i32x4()).xy()
+    }
+}
+
+impl Mul<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn mul(self, other: I32x2) -> I32x2 {
+        (self.to_i32x4() * other.to_i32x4()).xy()
+    }
+}
+
+impl BitAnd<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn bitand(self, other: I32x2) -> I32x2 {
+        I32x2(self.0 & other.0)
+    }
+}
+
+impl BitOr<I32x2> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn bitor(self, other: I32x2) -> I32x2 {
+        I32x2(self.0 | other.0)
+    }
+}
+
+impl Shl<u32> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn shl(self, amount: u32) -> I32x2 {
+        (self.to_i32x4() << amount).xy()
+    }
+}
+
+impl Shr<u32> for I32x2 {
+    type Output = I32x2;
+    #[inline]
+    fn shr(self, amount: u32) -> I32x2 {
+        (self.to_i32x4() >> amount).xy()
+    }
+}
+
+impl Debug for I32x2 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}>", self[0], self[1])
+    }
+}
+
+impl PartialEq for I32x2 {
+    #[inline]
+    fn eq(&self, other: &I32x2) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
+
+// Four 32-bit signed integers
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct I32x4(pub __m128i);
+
+impl I32x4 {
     // Constructors
 
     #[inline]
     pub fn new(a: i32, b: i32, c: i32, d: i32) -> I32x4 {
         unsafe {
             let vector = [a, b, c, d];
-            I32x4(x86::_mm_loadu_si128(vector.as_ptr() as *const __m128i))
+            I32x4(x86::_mm_loadu_si128(vector.as_ptr() as *const __m128i))
+        }
+    }
+
+    #[inline]
+    pub fn splat(x: i32) -> I32x4 {
+        unsafe { I32x4(x86::_mm_set1_epi32(x)) }
+    }
+
+    /// Loads the first four elements of `slice`, which needn't be aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn from_slice(slice: &[i32]) -> I32x4 {
+        let slice = &slice[..4];
+        unsafe { I32x4(x86::_mm_loadu_si128(slice.as_ptr() as *const __m128i)) }
+    }
+
+    /// Loads the first four elements of `slice`, which must start on a 16-byte boundary. This can
+    /// be faster than `from_slice` on some CPUs.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn from_slice_aligned(slice: &[i32]) -> I32x4 {
+        let slice = &slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        I32x4(x86::_mm_load_si128(slice.as_ptr() as *const __m128i))
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which needn't be
+    /// aligned.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    #[inline]
+    pub fn write_to_slice(self, slice: &mut [i32]) {
+        let slice = &mut slice[..4];
+        unsafe { x86::_mm_storeu_si128(slice.as_mut_ptr() as *mut __m128i, self.0) }
+    }
+
+    /// Stores the lanes of this vector into the first four elements of `slice`, which must start
+    /// on a 16-byte boundary.
+    ///
+    /// Panics if `slice` has fewer than four elements.
+    ///
+    /// # Safety
+    ///
+    /// `slice.as_mut_ptr()` must be 16-byte aligned. This is only checked in debug builds.
+    #[inline]
+    pub unsafe fn write_to_slice_aligned(self, slice: &mut [i32]) {
+        let slice = &mut slice[..4];
+        debug_assert_eq!(slice.as_ptr() as usize % 16, 0);
+        x86::_mm_store_si128(slice.as_mut_ptr() as *mut __m128i, self.0)
+    }
+
+    // Swizzle
+
+    /// Returns a vector made of the lanes of this one at indices `A`, `B`, `C`, and `D`, in that
+    /// order.
+    ///
+    /// This covers every permutation, so it's handy when the lanes are chosen by other generic
+    /// code. `v.shuffle::<2, 3, 0, 1>()` is the same as `v.zwxy()`. Every index must be less
+    /// than 4.
+    #[inline]
+    pub fn shuffle<const A: usize, const B: usize, const C: usize, const D: usize>(self) -> I32x4 {
+        debug_assert!(A < 4 && B < 4 && C < 4 && D < 4, "shuffle lane index out of range");
+        I32x4(shuffle_lanes(self.0, [A, B, C, D]))
+    }
+
+    // Extraction
+
+    #[inline]
+    pub fn xy(self) -> I32x2 {
+        I32x2(*cast_ref::<__m128i, u64>(&self.0))
+    }
+
+    #[inline]
+    pub fn xw(self) -> I32x2 {
+        self.xwyz().xy()
+    }
+
+    #[inline]
+    pub fn yx(self) -> I32x2 {
+        self.yxwz().xy()
+    }
+
+    #[inline]
+    pub fn zy(self) -> I32x2 {
+        self.zyxw().xy()
+    }
+
+    #[inline]
+    pub fn zw(self) -> I32x2 {
+        self.zwxy().xy()
+    }
+
+    // Concatenations
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: I32x4) -> I32x4 {
+        unsafe {
+            let this = x86::_mm_castsi128_pd(self.0);
+            let other = x86::_mm_castsi128_pd(other.0);
+            let result = x86::_mm_unpacklo_pd(this, other);
+            I32x4(x86::_mm_castpd_si128(result))
+        }
+    }
+
+    #[inline]
+    pub fn concat_zw_zw(self, other: I32x4) -> I32x4 {
+        unsafe {
+            let this = x86::_mm_castsi128_pd(self.0);
+            let other = x86::_mm_castsi128_pd(other.0);
+            let result = x86::_mm_unpackhi_pd(this, other);
+            I32x4(x86::_mm_castpd_si128(result))
+        }
+    }
+
+    // Conversions
+
+    /// Converts these packed integers to floats.
+    #[inline]
+    pub fn to_f32x4(self) -> F32x4 {
+        unsafe { F32x4(x86::_mm_cvtepi32_ps(self.0)) }
+    }
+
+    /// Converts these packed signed integers to unsigned integers.
+    ///
+    /// Overflowing values will wrap around.
+    #[inline]
+    pub fn to_u32x4(self) -> U32x4 {
+        U32x4(self.0)
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn max(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(x86::_mm_max_epi32(self.0, other.0)) }
+    }
+
+    /// Returns the smallest of the four lanes.
+    #[inline]
+    pub fn min_element(self) -> i32 {
+        let pairs = self.min(self.yxwz());
+        pairs.min(pairs.zwxy())[0]
+    }
+
+    /// Returns the largest of the four lanes.
+    #[inline]
+    pub fn max_element(self) -> i32 {
+        let pairs = self.max(self.yxwz());
+        pairs.max(pairs.zwxy())[0]
+    }
+
+    #[inline]
+    pub fn min(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(x86::_mm_min_epi32(self.0, other.0)) }
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: I32x4) -> U32x4 {
+        unsafe { U32x4(x86::_mm_cmpeq_epi32(self.0, other.0)) }
+    }
+
+    // Comparisons
+
+    #[inline]
+    pub fn packed_gt(self, other: I32x4) -> U32x4 {
+        unsafe { U32x4(x86::_mm_cmpgt_epi32(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn packed_lt(self, other: I32x4) -> U32x4 {
+        other.packed_gt(self)
+    }
+
+    #[inline]
+    pub fn packed_le(self, other: I32x4) -> U32x4 {
+        !self.packed_gt(other)
+    }
+
+    #[inline]
+    pub fn packed_ge(self, other: I32x4) -> U32x4 {
+        !other.packed_gt(self)
+    }
+
+    #[inline]
+    pub fn packed_ne(self, other: I32x4) -> U32x4 {
+        !self.packed_eq(other)
+    }
+}
+
+impl Default for I32x4 {
+    #[inline]
+    fn default() -> I32x4 {
+        unsafe { I32x4(x86::_mm_setzero_si128()) }
+    }
+}
+
+impl Index<usize> for I32x4 {
+    type Output = i32;
+    #[inline]
+    fn index(&self, index: usize) -> &i32 {
+        &cast_ref::<__m128i, [i32; 4]>(&self.0)[index]
+    }
+}
+
+impl IndexMut<usize> for I32x4 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut i32 {
+        &mut cast_mut::<__m128i, [i32; 4]>(&mut self.0)[index]
+    }
+}
+
+impl Add<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn add(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(x86::_mm_add_epi32(self.0, other.0)) }
+    }
+}
+
+impl Sub<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn sub(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(x86::_mm_sub_epi32(self.0, other.0)) }
+    }
+}
+
+impl Mul<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn mul(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(x86::_mm_mullo_epi32(self.0, other.0)) }
+    }
+}
+
+impl BitAnd<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn bitand(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(x86::_mm_and_si128(self.0, other.0)) }
+    }
+}
+
+impl BitOr<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn bitor(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(x86::_mm_or_si128(self.0, other.0)) }
+    }
+}
+
+impl Shl<u32> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shl(self, amount: u32) -> I32x4 {
+        unsafe { I32x4(x86::_mm_sll_epi32(self.0, U32x4::new(amount, 0, 0, 0).0)) }
+    }
+}
+
+impl Shr<u32> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shr(self, amount: u32) -> I32x4 {
+        unsafe { I32x4(x86::_mm_sra_epi32(self.0, U32x4::new(amount, 0, 0, 0).0)) }
+    }
+}
+
+impl Shl<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shl(self, other: I32x4) -> I32x4 {
+        // Per-lane shifts need AVX2.
+        #[cfg(target_feature = "avx2")]
+        unsafe {
+            I32x4(x86::_mm_sllv_epi32(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx2"))]
+        {
+            use crate::scalar::shl_i32;
+            I32x4::new(shl_i32(self[0], other[0] as u32),
+                       shl_i32(self[1], other[1] as u32),
+                       shl_i32(self[2], other[2] as u32),
+                       shl_i32(self[3], other[3] as u32))
+        }
+    }
+}
+
+impl Shr<I32x4> for I32x4 {
+    type Output = I32x4;
+    #[inline]
+    fn shr(self, other: I32x4) -> I32x4 {
+        // Per-lane shifts need AVX2.
+        #[cfg(target_feature = "avx2")]
+        unsafe {
+            I32x4(x86::_mm_srav_epi32(self.0, other.0))
+        }
+        #[cfg(not(target_feature = "avx2"))]
+        {
+            use crate::scalar::shr_i32;
+            I32x4::new(shr_i32(self[0], other[0] as u32),
+                       shr_i32(self[1], other[1] as u32),
+                       shr_i32(self[2], other[2] as u32),
+                       shr_i32(self[3], other[3] as u32))
+        }
+    }
+}
+
+impl Debug for I32x4 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    }
+}
+
+impl PartialEq for I32x4 {
+    #[inline]
+    fn eq(&self, other: &I32x4) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
+
+// Two 32-bit unsigned integers
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct U32x2(pub u64);
+
+impl U32x2 {
+    #[inline]
+    pub fn new(x: u32, y: u32) -> U32x2 {
+        U32x2(x as u64 | ((y as u64) << 32))
+    }
+
+    #[inline]
+    pub fn splat(x: u32) -> U32x2 {
+        U32x2::new(x, x)
+    }
+
+    /// Returns true if both booleans in this vector are true.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        self.0 == !0
+    }
+
+    /// Returns true if both booleans in this vector are false.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if either boolean in this vector is true.
+    ///
+    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
+    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn any_true(self) -> bool {
+        self.0 != 0
+    }
+
+    #[inline]
+    pub fn to_i32x2(self) -> I32x2 {
+        I32x2(self.0)
+    }
+}
+
+impl Debug for U32x2 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}>", self[0], self[1])
+    }
+}
+
+impl Index<usize> for U32x2 {
+    type Output = u32;
+    #[inline]
+    fn index(&self, index: usize) -> &u32 {
+        &cast_ref::<u64, [u32; 2]>(&self.0)[index]
+    }
+}
+
+impl Not for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn not(self) -> U32x2 {
+        U32x2(!self.0)
+    }
+}
+
+impl BitAnd<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitand(self, other: U32x2) -> U32x2 {
+        U32x2(self.0 & other.0)
+    }
+}
+
+impl BitOr<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitor(self, other: U32x2) -> U32x2 {
+        U32x2(self.0 | other.0)
+    }
+}
+
+impl BitXor<U32x2> for U32x2 {
+    type Output = U32x2;
+    #[inline]
+    fn bitxor(self, other: U32x2) -> U32x2 {
+        U32x2(self.0 ^ other.0)
+    }
+}
+
+// Four 32-bit unsigned integers
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct U32x4(pub __m128i);
+
+impl U32x4 {
+    // Constructors
+
+    #[inline]
+    pub fn new(a: u32, b: u32, c: u32, d: u32) -> U32x4 {
+        unsafe {
+            let vector = [a, b, c, d];
+            U32x4(x86::_mm_loadu_si128(vector.as_ptr() as *const __m128i))
         }
     }
 
     #[inline]
-    pub fn splat(x: i32) -> I32x4 {
-        unsafe { I32x4(x86::_mm_set1_epi32(x)) }
+    pub fn splat(x: u32) -> U32x4 {
+        unsafe { U32x4(x86::_mm_set1_epi32(x as i32)) }
+    }
+
+    // Conversions
+
+    /// Converts these packed unsigned integers to signed integers.
+    ///
+    /// Overflowing values will wrap around.
+    #[inline]
+    pub fn to_i32x4(self) -> I32x4 {
+        I32x4(self.0)
+    }
+
+    /// Reinterprets the bits of these integers as sixteen bytes, in memory order.
+    #[inline]
+    pub fn to_u8x16(self) -> U8x16 {
+        U8x16(self.0)
+    }
+
+    // Basic operations
+
+    /// Returns true if all four booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        unsafe { x86::_mm_movemask_ps(x86::_mm_castsi128_ps(self.0)) == 0x0f }
+    }
+
+    /// Returns true if all four booleans in this vector are false.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        unsafe { x86::_mm_movemask_ps(x86::_mm_castsi128_ps(self.0)) == 0x00 }
+    }
+
+    /// Returns true if any of the four booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
+    /// a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn any_true(self) -> bool {
+        unsafe { x86::_mm_movemask_ps(x86::_mm_castsi128_ps(self.0)) != 0x00 }
+    }
+
+    // Extraction
+
+    #[inline]
+    pub fn xy(self) -> U32x2 {
+        U32x2(*cast_ref::<__m128i, u64>(&self.0))
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U32x4) -> U32x4 {
+        unsafe { U32x4(x86::_mm_cmpeq_epi32(self.0, other.0)) }
+    }
+}
+
+impl Debug for U32x4 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    }
+}
+
+impl Index<usize> for U32x4 {
+    type Output = u32;
+    #[inline]
+    fn index(&self, index: usize) -> &u32 {
+        &cast_ref::<__m128i, [u32; 4]>(&self.0)[index]
+    }
+}
+
+impl PartialEq for U32x4 {
+    #[inline]
+    fn eq(&self, other: &U32x4) -> bool {
+        self.packed_eq(*other).all_true()
+    }
+}
+
+impl Not for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn not(self) -> U32x4 {
+        self ^ U32x4::splat(!0)
+    }
+}
+
+impl BitAnd<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitand(self, other: U32x4) -> U32x4 {
+        unsafe { U32x4(x86::_mm_and_si128(self.0, other.0)) }
+    }
+}
+
+impl BitOr<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitor(self, other: U32x4) -> U32x4 {
+        unsafe { U32x4(x86::_mm_or_si128(self.0, other.0)) }
+    }
+}
+
+impl BitXor<U32x4> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn bitxor(self, other: U32x4) -> U32x4 {
+        unsafe { U32x4(x86::_mm_xor_si128(self.0, other.0)) }
+    }
+}
+
+impl Shr<u32> for U32x4 {
+    type Output = U32x4;
+    #[inline]
+    fn shr(self, amount: u32) -> U32x4 {
+        unsafe { U32x4(x86::_mm_srl_epi32(self.0, U32x4::new(amount, 0, 0, 0).0)) }
+    }
+}
+
+// Sixteen 8-bit unsigned integers
+
+/// Sixteen bytes, such as the channels of four RGBA pixels. Arithmetic wraps around.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct U8x16(pub __m128i);
+
+impl U8x16 {
+    // Constructors
+
+    #[inline]
+    pub fn from_array(bytes: [u8; 16]) -> U8x16 {
+        unsafe { U8x16(x86::_mm_loadu_si128(bytes.as_ptr() as *const __m128i)) }
+    }
+
+    #[inline]
+    pub fn splat(x: u8) -> U8x16 {
+        unsafe { U8x16(x86::_mm_set1_epi8(x as i8)) }
+    }
+
+    // Conversions
+
+    #[inline]
+    pub fn to_array(self) -> [u8; 16] {
+        cast::<__m128i, [u8; 16]>(self.0)
+    }
+
+    /// Reinterprets these bytes as four 32-bit integers, in memory order.
+    #[inline]
+    pub fn to_u32x4(self) -> U32x4 {
+        U32x4(self.0)
+    }
+
+    /// Zero-extends the first eight bytes to 16 bits, for arithmetic that would overflow a byte.
+    #[inline]
+    pub fn widen_low(self) -> U16x8 {
+        unsafe { U16x8(x86::_mm_unpacklo_epi8(self.0, x86::_mm_setzero_si128())) }
+    }
+
+    /// Zero-extends the last eight bytes to 16 bits.
+    #[inline]
+    pub fn widen_high(self) -> U16x8 {
+        unsafe { U16x8(x86::_mm_unpackhi_epi8(self.0, x86::_mm_setzero_si128())) }
+    }
+
+    /// Narrows sixteen 16-bit integers, `low` followed by `high`, to bytes. Values above 255
+    /// become 255.
+    #[inline]
+    pub fn narrow(low: U16x8, high: U16x8) -> U8x16 {
+        // `packus` saturates signed values, so clamp the unsigned ones first.
+        let (low, high) = (low.min(U16x8::splat(255)), high.min(U16x8::splat(255)));
+        unsafe { U8x16(x86::_mm_packus_epi16(low.0, high.0)) }
+    }
+
+    /// Narrows sixteen signed 16-bit integers, `low` followed by `high`, to bytes. Values below 0
+    /// become 0, and values above 255 become 255.
+    #[inline]
+    pub fn narrow_i16x8(low: I16x8, high: I16x8) -> U8x16 {
+        unsafe { U8x16(x86::_mm_packus_epi16(low.0, high.0)) }
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(x86::_mm_min_epu8(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn max(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(x86::_mm_max_epu8(self.0, other.0)) }
+    }
+
+    /// Adds, clamping sums above 255 to 255.
+    #[inline]
+    pub fn saturating_add(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(x86::_mm_adds_epu8(self.0, other.0)) }
+    }
+
+    /// Subtracts, clamping differences below 0 to 0.
+    #[inline]
+    pub fn saturating_sub(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(x86::_mm_subs_epu8(self.0, other.0)) }
+    }
+
+    /// Returns true if all sixteen booleans in this vector are true.
+    ///
+    /// The result is *undefined* if all sixteen values in this vector are not booleans. A boolean
+    /// is a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_true(self) -> bool {
+        unsafe { x86::_mm_movemask_epi8(self.0) == 0xffff }
     }
 
-    // Extraction
+    /// Returns true if all sixteen booleans in this vector are false.
+    ///
+    /// The result is *undefined* if all sixteen values in this vector are not booleans. A boolean
+    /// is a value with all bits set or all bits clear (i.e. !0 or 0).
+    #[inline]
+    pub fn all_false(self) -> bool {
+        unsafe { x86::_mm_movemask_epi8(self.0) == 0 }
+    }
+
+    // Swizzle
 
+    /// Rearranges the bytes of this vector the way `pshufb` does: each lane of the result is the
+    /// byte of `self` at the index in the low four bits of the same lane of `indices`, or zero
+    /// if the high bit of that lane is set.
     #[inline]
-    pub fn xy(self) -> I32x2 {
-        unsafe {
-            let swizzled = self.0;
-            I32x2(*mem::transmute::<&__m128i, &u64>(&swizzled))
-        }
+    pub fn shuffle(self, indices: U8x16) -> U8x16 {
+        unsafe { U8x16(x86::_mm_shuffle_epi8(self.0, indices.0)) }
     }
 
+    // Packed comparisons
+
     #[inline]
-    pub fn xw(self) -> I32x2 {
-        self.xwyz().xy()
+    pub fn packed_eq(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(x86::_mm_cmpeq_epi8(self.0, other.0)) }
     }
+}
 
+impl Default for U8x16 {
     #[inline]
-    pub fn yx(self) -> I32x2 {
-        self.yxwz().xy()
+    fn default() -> U8x16 {
+        unsafe { U8x16(x86::_mm_setzero_si128()) }
     }
+}
 
+impl Index<usize> for U8x16 {
+    type Output = u8;
     #[inline]
-    pub fn zy(self) -> I32x2 {
-        self.zyxw().xy()
+    fn index(&self, index: usize) -> &u8 {
+        &cast_ref::<__m128i, [u8; 16]>(&self.0)[index]
     }
+}
 
+impl IndexMut<usize> for U8x16 {
     #[inline]
-    pub fn zw(self) -> I32x2 {
-        self.zwxy().xy()
+    fn index_mut(&mut self, index: usize) -> &mut u8 {
+        &mut cast_mut::<__m128i, [u8; 16]>(&mut self.0)[index]
     }
+}
 
-    // Concatenations
+impl Debug for U8x16 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::U8x16(self.to_array()).fmt(f)
+    }
+}
 
+impl PartialEq for U8x16 {
     #[inline]
-    pub fn concat_xy_xy(self, other: I32x4) -> I32x4 {
-        unsafe {
-            let this = x86::_mm_castsi128_pd(self.0);
-            let other = x86::_mm_castsi128_pd(other.0);
-            let result = x86::_mm_unpacklo_pd(this, other);
-            I32x4(x86::_mm_castpd_si128(result))
-        }
+    fn eq(&self, other: &U8x16) -> bool {
+        self.packed_eq(*other).all_true()
     }
+}
 
+impl Add<U8x16> for U8x16 {
+    type Output = U8x16;
     #[inline]
-    pub fn concat_zw_zw(self, other: I32x4) -> I32x4 {
-        unsafe {
-            let this = x86::_mm_castsi128_pd(self.0);
-            let other = x86::_mm_castsi128_pd(other.0);
-            let result = x86::_mm_unpackhi_pd(this, other);
-            I32x4(x86::_mm_castpd_si128(result))
-        }
+    fn add(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(x86::_mm_add_epi8(self.0, other.0)) }
     }
+}
 
-    // Conversions
+impl Sub<U8x16> for U8x16 {
+    type Output = U8x16;
+    #[inline]
+    fn sub(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(x86::_mm_sub_epi8(self.0, other.0)) }
+    }
+}
 
-    /// Converts these packed integers to floats.
+impl BitAnd<U8x16> for U8x16 {
+    type Output = U8x16;
     #[inline]
-    pub fn to_f32x4(self) -> F32x4 {
-        unsafe { F32x4(x86::_mm_cvtepi32_ps(self.0)) }
+    fn bitand(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(x86::_mm_and_si128(self.0, other.0)) }
     }
+}
 
-    /// Converts these packed signed integers to unsigned integers.
-    ///
-    /// Overflowing values will wrap around.
+impl BitOr<U8x16> for U8x16 {
+    type Output = U8x16;
     #[inline]
-    pub fn to_u32x4(self) -> U32x4 {
-        U32x4(self.0)
+    fn bitor(self, other: U8x16) -> U8x16 {
+        unsafe { U8x16(x86::_mm_or_si128(self.0, other.0)) }
     }
+}
 
-    // Basic operations
+// Eight 16-bit unsigned integers
+
+/// Eight 16-bit unsigned integers, for intermediate results of byte arithmetic. Arithmetic wraps
+/// around.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct U16x8(pub __m128i);
+
+impl U16x8 {
+    // Constructors
 
     #[inline]
-    pub fn max(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(x86::_mm_max_epi32(self.0, other.0)) }
+    pub fn from_array(values: [u16; 8]) -> U16x8 {
+        unsafe { U16x8(x86::_mm_loadu_si128(values.as_ptr() as *const __m128i)) }
     }
 
     #[inline]
-    pub fn min(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(x86::_mm_min_epi32(self.0, other.0)) }
+    pub fn splat(x: u16) -> U16x8 {
+        unsafe { U16x8(x86::_mm_set1_epi16(x as i16)) }
     }
 
-    // Packed comparisons
+    // Conversions
 
     #[inline]
-    pub fn packed_eq(self, other: I32x4) -> U32x4 {
-        unsafe { U32x4(x86::_mm_cmpeq_epi32(self.0, other.0)) }
+    pub fn to_array(self) -> [u16; 8] {
+        cast::<__m128i, [u16; 8]>(self.0)
     }
 
-    // Comparisons
+    /// Reinterprets these integers as signed. Values above 32767 wrap around.
+    #[inline]
+    pub fn to_i16x8(self) -> I16x8 {
+        I16x8(self.0)
+    }
 
+    /// Zero-extends the first four integers to 32 bits.
     #[inline]
-    pub fn packed_gt(self, other: I32x4) -> U32x4 {
-        unsafe { U32x4(x86::_mm_cmpgt_epi32(self.0, other.0)) }
+    pub fn widen_low(self) -> I32x4 {
+        unsafe { I32x4(x86::_mm_cvtepu16_epi32(self.0)) }
     }
 
+    /// Zero-extends the last four integers to 32 bits.
     #[inline]
-    pub fn packed_lt(self, other: I32x4) -> U32x4 {
-        other.packed_gt(self)
+    pub fn widen_high(self) -> I32x4 {
+        unsafe { I32x4(x86::_mm_unpackhi_epi16(self.0, x86::_mm_setzero_si128())) }
     }
 
+    /// Narrows eight 32-bit integers, `low` followed by `high`, to 16 bits. Values below 0 become
+    /// 0, and values above 65535 become 65535.
     #[inline]
-    pub fn packed_le(self, other: I32x4) -> U32x4 {
-        !self.packed_gt(other)
+    pub fn narrow(low: I32x4, high: I32x4) -> U16x8 {
+        unsafe { U16x8(x86::_mm_packus_epi32(low.0, high.0)) }
     }
-}
 
-impl Default for I32x4 {
+    // Basic operations
+
     #[inline]
-    fn default() -> I32x4 {
-        unsafe { I32x4(x86::_mm_setzero_si128()) }
+    pub fn min(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(x86::_mm_min_epu16(self.0, other.0)) }
     }
-}
 
-impl Index<usize> for I32x4 {
-    type Output = i32;
     #[inline]
-    fn index(&self, index: usize) -> &i32 {
-        unsafe { &mem::transmute::<&__m128i, &[i32; 4]>(&self.0)[index] }
+    pub fn max(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(x86::_mm_max_epu16(self.0, other.0)) }
+    }
+
+    /// Adds, clamping sums above 65535 to 65535.
+    #[inline]
+    pub fn saturating_add(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(x86::_mm_adds_epu16(self.0, other.0)) }
+    }
+
+    /// Subtracts, clamping differences below 0 to 0.
+    #[inline]
+    pub fn saturating_sub(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(x86::_mm_subs_epu16(self.0, other.0)) }
+    }
+
+    // Packed comparisons
+
+    #[inline]
+    pub fn packed_eq(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(x86::_mm_cmpeq_epi16(self.0, other.0)) }
     }
 }
 
-impl IndexMut<usize> for I32x4 {
+impl Default for U16x8 {
     #[inline]
-    fn index_mut(&mut self, index: usize) -> &mut i32 {
-        unsafe { &mut mem::transmute::<&mut __m128i, &mut [i32; 4]>(&mut self.0)[index] }
+    fn default() -> U16x8 {
+        unsafe { U16x8(x86::_mm_setzero_si128()) }
     }
 }
 
-impl Add<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Index<usize> for U16x8 {
+    type Output = u16;
     #[inline]
-    fn add(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(x86::_mm_add_epi32(self.0, other.0)) }
+    fn index(&self, index: usize) -> &u16 {
+        &cast_ref::<__m128i, [u16; 8]>(&self.0)[index]
     }
 }
 
-impl Sub<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Debug for U16x8 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::U16x8(self.to_array()).fmt(f)
+    }
+}
+
+impl PartialEq for U16x8 {
     #[inline]
-    fn sub(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(x86::_mm_sub_epi32(self.0, other.0)) }
+    fn eq(&self, other: &U16x8) -> bool {
+        unsafe { x86::_mm_movemask_epi8(self.packed_eq(*other).0) == 0xffff }
     }
 }
 
-impl Mul<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Add<U16x8> for U16x8 {
+    type Output = U16x8;
     #[inline]
-    fn mul(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(x86::_mm_mullo_epi32(self.0, other.0)) }
+    fn add(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(x86::_mm_add_epi16(self.0, other.0)) }
     }
 }
 
-impl BitAnd<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Sub<U16x8> for U16x8 {
+    type Output = U16x8;
     #[inline]
-    fn bitand(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(x86::_mm_and_si128(self.0, other.0)) }
+    fn sub(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(x86::_mm_sub_epi16(self.0, other.0)) }
     }
 }
 
-impl BitOr<I32x4> for I32x4 {
-    type Output = I32x4;
+impl Mul<U16x8> for U16x8 {
+    type Output = U16x8;
     #[inline]
-    fn bitor(self, other: I32x4) -> I32x4 {
-        unsafe { I32x4(x86::_mm_or_si128(self.0, other.0)) }
+    fn mul(self, other: U16x8) -> U16x8 {
+        unsafe { U16x8(x86::_mm_mullo_epi16(self.0, other.0)) }
     }
 }
 
-impl Debug for I32x4 {
+impl Shl<u32> for U16x8 {
+    type Output = U16x8;
     #[inline]
-    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    fn shl(self, amount: u32) -> U16x8 {
+        unsafe { U16x8(x86::_mm_sll_epi16(self.0, U32x4::new(amount, 0, 0, 0).0)) }
     }
 }
 
-impl PartialEq for I32x4 {
+impl Shr<u32> for U16x8 {
+    type Output = U16x8;
     #[inline]
-    fn eq(&self, other: &I32x4) -> bool {
-        self.packed_eq(*other).all_true()
+    fn shr(self, amount: u32) -> U16x8 {
+        unsafe { U16x8(x86::_mm_srl_epi16(self.0, U32x4::new(amount, 0, 0, 0).0)) }
     }
 }
 
-// Two 32-bit unsigned integers
+// Eight 16-bit signed integers
 
+/// Eight 16-bit signed integers, for intermediate results of byte arithmetic that can go
+/// negative, such as coverage deltas. Arithmetic wraps around.
 #[derive(Clone, Copy)]
-pub struct U32x2(pub u64);
+#[repr(transparent)]
+pub struct I16x8(pub __m128i);
 
-impl U32x2 {
-    #[inline]
-    pub fn new(x: u32, y: u32) -> U32x2 {
-        U32x2(x as u64 | ((y as u64) << 32))
-    }
+impl I16x8 {
+    // Constructors
 
     #[inline]
-    pub fn splat(x: u32) -> U32x2 {
-        U32x2::new(x, x)
+    pub fn from_array(values: [i16; 8]) -> I16x8 {
+        unsafe { I16x8(x86::_mm_loadu_si128(values.as_ptr() as *const __m128i)) }
     }
 
-    /// Returns true if both booleans in this vector are true.
-    ///
-    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
-    /// value with all bits set or all bits clear (i.e. !0 or 0).
     #[inline]
-    pub fn all_true(self) -> bool {
-        self.0 == !0
+    pub fn splat(x: i16) -> I16x8 {
+        unsafe { I16x8(x86::_mm_set1_epi16(x)) }
     }
 
-    /// Returns true if both booleans in this vector are false.
-    ///
-    /// The result is *undefined* if both values in this vector are not booleans. A boolean is a
-    /// value with all bits set or all bits clear (i.e. !0 or 0).
+    // Conversions
+
     #[inline]
-    pub fn all_false(self) -> bool {
-        self.0 == 0
+    pub fn to_array(self) -> [i16; 8] {
+        cast::<__m128i, [i16; 8]>(self.0)
     }
 
+    /// Reinterprets these integers as unsigned. Negative values wrap around.
     #[inline]
-    pub fn to_i32x2(self) -> I32x2 {
-        I32x2(self.0)
+    pub fn to_u16x8(self) -> U16x8 {
+        U16x8(self.0)
     }
-}
 
-impl Not for U32x2 {
-    type Output = U32x2;
+    /// Sign-extends the first four integers to 32 bits.
     #[inline]
-    fn not(self) -> U32x2 {
-        U32x2(!self.0)
+    pub fn widen_low(self) -> I32x4 {
+        unsafe { I32x4(x86::_mm_cvtepi16_epi32(self.0)) }
     }
-}
 
-impl BitAnd<U32x2> for U32x2 {
-    type Output = U32x2;
+    /// Sign-extends the last four integers to 32 bits.
     #[inline]
-    fn bitand(self, other: U32x2) -> U32x2 {
-        U32x2(self.0 & other.0)
+    pub fn widen_high(self) -> I32x4 {
+        // Put each integer in the high half of a 32-bit lane, then shift the sign down.
+        unsafe { I32x4(x86::_mm_srai_epi32(x86::_mm_unpackhi_epi16(self.0, self.0), 16)) }
     }
-}
 
-impl BitOr<U32x2> for U32x2 {
-    type Output = U32x2;
+    /// Narrows eight 32-bit integers, `low` followed by `high`, to 16 bits. Values outside the
+    /// range of `i16` are clamped to it.
     #[inline]
-    fn bitor(self, other: U32x2) -> U32x2 {
-        U32x2(self.0 | other.0)
+    pub fn narrow(low: I32x4, high: I32x4) -> I16x8 {
+        unsafe { I16x8(x86::_mm_packs_epi32(low.0, high.0)) }
     }
-}
 
-// Four 32-bit unsigned integers
+    // Basic operations
 
-#[derive(Clone, Copy)]
-pub struct U32x4(pub __m128i);
+    #[inline]
+    pub fn min(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(x86::_mm_min_epi16(self.0, other.0)) }
+    }
 
-impl U32x4 {
-    // Constructors
+    #[inline]
+    pub fn max(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(x86::_mm_max_epi16(self.0, other.0)) }
+    }
 
+    /// Adds, clamping sums to the range of `i16`.
     #[inline]
-    pub fn new(a: u32, b: u32, c: u32, d: u32) -> U32x4 {
-        unsafe {
-            let vector = [a, b, c, d];
-            U32x4(x86::_mm_loadu_si128(vector.as_ptr() as *const __m128i))
-        }
+    pub fn saturating_add(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(x86::_mm_adds_epi16(self.0, other.0)) }
     }
 
+    /// Subtracts, clamping differences to the range of `i16`.
     #[inline]
-    pub fn splat(x: u32) -> U32x4 {
-        unsafe { U32x4(x86::_mm_set1_epi32(x as i32)) }
+    pub fn saturating_sub(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(x86::_mm_subs_epi16(self.0, other.0)) }
     }
 
-    // Conversions
+    // Packed comparisons
 
-    /// Converts these packed unsigned integers to signed integers.
-    ///
-    /// Overflowing values will wrap around.
     #[inline]
-    pub fn to_i32x4(self) -> I32x4 {
-        I32x4(self.0)
+    pub fn packed_eq(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(x86::_mm_cmpeq_epi16(self.0, other.0)) }
     }
 
-    // Basic operations
-
-    /// Returns true if all four booleans in this vector are true.
-    ///
-    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
-    /// a value with all bits set or all bits clear (i.e. !0 or 0).
     #[inline]
-    pub fn all_true(self) -> bool {
-        unsafe { x86::_mm_movemask_ps(x86::_mm_castsi128_ps(self.0)) == 0x0f }
+    pub fn packed_gt(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(x86::_mm_cmpgt_epi16(self.0, other.0)) }
     }
 
-    /// Returns true if all four booleans in this vector are false.
-    ///
-    /// The result is *undefined* if all four values in this vector are not booleans. A boolean is
-    /// a value with all bits set or all bits clear (i.e. !0 or 0).
     #[inline]
-    pub fn all_false(self) -> bool {
-        unsafe { x86::_mm_movemask_ps(x86::_mm_castsi128_ps(self.0)) == 0x00 }
+    pub fn packed_lt(self, other: I16x8) -> I16x8 {
+        other.packed_gt(self)
     }
+}
 
-    // Extraction
+impl Default for I16x8 {
+    #[inline]
+    fn default() -> I16x8 {
+        unsafe { I16x8(x86::_mm_setzero_si128()) }
+    }
+}
 
+impl Index<usize> for I16x8 {
+    type Output = i16;
     #[inline]
-    pub fn xy(self) -> U32x2 {
-        unsafe {
-            let swizzled = self.0;
-            U32x2(*mem::transmute::<&__m128i, &u64>(&swizzled))
-        }
+    fn index(&self, index: usize) -> &i16 {
+        &cast_ref::<__m128i, [i16; 8]>(&self.0)[index]
     }
+}
 
-    // Packed comparisons
+impl Debug for I16x8 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        crate::scalar::I16x8(self.to_array()).fmt(f)
+    }
+}
 
+impl PartialEq for I16x8 {
     #[inline]
-    pub fn packed_eq(self, other: U32x4) -> U32x4 {
-        unsafe { U32x4(x86::_mm_cmpeq_epi32(self.0, other.0)) }
+    fn eq(&self, other: &I16x8) -> bool {
+        unsafe { x86::_mm_movemask_epi8(self.packed_eq(*other).0) == 0xffff }
     }
 }
 
-impl Debug for U32x4 {
+impl Add<I16x8> for I16x8 {
+    type Output = I16x8;
     #[inline]
-    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        write!(f, "<{}, {}, {}, {}>", self[0], self[1], self[2], self[3])
+    fn add(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(x86::_mm_add_epi16(self.0, other.0)) }
     }
 }
 
-impl Index<usize> for U32x4 {
-    type Output = u32;
+impl Sub<I16x8> for I16x8 {
+    type Output = I16x8;
     #[inline]
-    fn index(&self, index: usize) -> &u32 {
-        unsafe { &mem::transmute::<&__m128i, &[u32; 4]>(&self.0)[index] }
+    fn sub(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(x86::_mm_sub_epi16(self.0, other.0)) }
     }
 }
 
-impl PartialEq for U32x4 {
+impl Mul<I16x8> for I16x8 {
+    type Output = I16x8;
     #[inline]
-    fn eq(&self, other: &U32x4) -> bool {
-        self.packed_eq(*other).all_true()
+    fn mul(self, other: I16x8) -> I16x8 {
+        unsafe { I16x8(x86::_mm_mullo_epi16(self.0, other.0)) }
     }
 }
 
-impl Not for U32x4 {
-    type Output = U32x4;
+impl Shl<u32> for I16x8 {
+    type Output = I16x8;
     #[inline]
-    fn not(self) -> U32x4 {
-        self ^ U32x4::splat(!0)
+    fn shl(self, amount: u32) -> I16x8 {
+        unsafe { I16x8(x86::_mm_sll_epi16(self.0, U32x4::new(amount, 0, 0, 0).0)) }
     }
 }
 
-impl BitXor<U32x4> for U32x4 {
-    type Output = U32x4;
+impl Shr<u32> for I16x8 {
+    type Output = I16x8;
     #[inline]
-    fn bitxor(self, other: U32x4) -> U32x4 {
-        unsafe { U32x4(x86::_mm_xor_si128(self.0, other.0)) }
+    fn shr(self, amount: u32) -> I16x8 {
+        unsafe { I16x8(x86::_mm_sra_epi16(self.0, U32x4::new(amount, 0, 0, 0).0)) }
     }
 }
 
-impl Shr<u32> for U32x4 {
-    type Output = U32x4;
-    #[inline]
-    fn shr(self, amount: u32) -> U32x4 {
-        unsafe { U32x4(x86::_mm_srl_epi32(self.0, U32x4::new(amount, 0, 0, 0).0)) }
+// Shuffles
+
+/// Rearranges the 32-bit lanes of `vector`, taking lane `i` of the result from lane `lanes[i]`.
+///
+/// The lanes are constants at every call site, so after inlining the `pshufb` control mask folds
+/// away and LLVM picks the cheapest permute instruction for each shuffle.
+#[inline]
+fn shuffle_lanes(vector: __m128i, lanes: [usize; 4]) -> __m128i {
+    // Each byte of the control mask selects one byte of `vector`.
+    let lane_bytes = |lane: usize| (lane as i32 * 4) * 0x0101_0101 + 0x0302_0100;
+    unsafe {
+        let control = x86::_mm_setr_epi32(lane_bytes(lanes[0]),
+                                          lane_bytes(lanes[1]),
+                                          lane_bytes(lanes[2]),
+                                          lane_bytes(lanes[3]));
+        x86::_mm_shuffle_epi8(vector, control)
     }
 }
@@ -11,9 +11,9 @@
 use crate::x86::I32x4;
 
 #[cfg(target_pointer_width = "32")]
-use std::arch::x86;
+use core::arch::x86;
 #[cfg(target_pointer_width = "64")]
-use std::arch::x86_64 as x86;
+use core::arch::x86_64 as x86;
 
 impl I32x4 {
     #[inline]
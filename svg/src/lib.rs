@@ -19,21 +19,24 @@ use pathfinder_content::dash::OutlineDash;
 use pathfinder_content::fill::FillRule;
 use pathfinder_content::gradient::{ColorStop, Gradient};
 use pathfinder_content::outline::Outline;
+use pathfinder_content::pattern::Pattern;
 use pathfinder_content::segment::{Segment, SegmentFlags};
-use pathfinder_content::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
+use pathfinder_content::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeAlignment};
+use pathfinder_content::stroke::StrokeStyle;
 use pathfinder_content::transform::Transform2FPathIter;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::{Vector2F, vec2f};
+use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2f};
 use pathfinder_renderer::paint::Paint;
 use pathfinder_renderer::scene::{ClipPath, ClipPathId, DrawPath, Scene};
 use pathfinder_simd::default::F32x2;
 use std::fmt::{Display, Formatter, Result as FormatResult};
+use std::mem;
 use usvg::{BaseGradient, Color as SvgColor, FillRule as UsvgFillRule, LineCap as UsvgLineCap};
 use usvg::{LineJoin as UsvgLineJoin, Node, NodeExt, NodeKind, Opacity, Paint as UsvgPaint};
-use usvg::{PathSegment as UsvgPathSegment, Rect as UsvgRect, SpreadMethod, Stop};
-use usvg::{Transform as UsvgTransform, Tree, Visibility};
+use usvg::{PathSegment as UsvgPathSegment, Pattern as UsvgPattern, Rect as UsvgRect};
+use usvg::{SpreadMethod, Stop, Transform as UsvgTransform, Tree, Units, Visibility};
 
 const HAIRLINE_STROKE_WIDTH: f32 = 0.0333;
 
@@ -42,6 +45,7 @@ pub struct BuiltSVG {
     pub result_flags: BuildResultFlags,
     pub clip_paths: HashMap<String, ClipPathId>,
     gradients: HashMap<String, GradientInfo>,
+    patterns: HashMap<String, Pattern>,
 }
 
 bitflags! {
@@ -77,6 +81,7 @@ impl BuiltSVG {
             result_flags: BuildResultFlags::empty(),
             clip_paths: HashMap::new(),
             gradients: HashMap::new(),
+            patterns: HashMap::new(),
         };
 
         let root = &tree.root();
@@ -155,6 +160,7 @@ impl BuiltSVG {
                         line_cap: LineCap::from_usvg_line_cap(stroke.linecap),
                         line_join: LineJoin::from_usvg_line_join(stroke.linejoin,
                                                                  stroke.miterlimit.value() as f32),
+                        alignment: StrokeAlignment::Center,
                     };
 
                     let path = UsvgPathToSegments::new(path.data.iter().cloned());
@@ -232,10 +238,7 @@ impl BuiltSVG {
                 self.result_flags
                     .insert(BuildResultFlags::UNSUPPORTED_MASK_NODE);
             }
-            NodeKind::Pattern(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_PATTERN_NODE);
-            }
+            NodeKind::Pattern(ref svg_pattern) => self.add_pattern(node, svg_pattern),
             NodeKind::Svg(..) => {
                 self.result_flags
                     .insert(BuildResultFlags::UNSUPPORTED_NESTED_SVG_NODE);
@@ -261,6 +264,46 @@ impl BuiltSVG {
         self.gradients.insert(id, GradientInfo { gradient, transform });
     }
 
+    fn add_pattern(&mut self, node: &Node, svg_pattern: &UsvgPattern) {
+        // TODO: Support bounding box units, which need the bounds of each path the
+        // pattern fills.
+        if svg_pattern.units != Units::UserSpaceOnUse ||
+                svg_pattern.content_units != Units::UserSpaceOnUse {
+            self.result_flags.insert(BuildResultFlags::UNSUPPORTED_PATTERN_NODE);
+            return;
+        }
+
+        // Draw one tile's worth of contents into a scene of its own, at one pixel per user
+        // unit.
+        let tile_rect = usvg_rect_to_euclid_rect(&svg_pattern.rect);
+        let tile_size = tile_rect.size().ceil().to_i32().max(Vector2I::splat(1));
+        let tile_scale = tile_size.to_f32() / tile_rect.size().max(Vector2F::splat(1.0e-6));
+        let mut state = State::new();
+        state.transform = Transform2F::from_scale(tile_scale);
+        if let Some(ref view_box) = svg_pattern.view_box {
+            // FIXME: Respect `preserveAspectRatio`.
+            let view_box = usvg_rect_to_euclid_rect(&view_box.rect);
+            state.transform *= Transform2F::from_scale(tile_rect.size() / view_box.size()) *
+                Transform2F::from_translation(-view_box.origin());
+        }
+
+        let scene = mem::replace(&mut self.scene, Scene::new());
+        let clip_paths = mem::replace(&mut self.clip_paths, HashMap::new());
+        for kid in node.children() {
+            self.process_node(&kid, &state, &mut None);
+        }
+        let tile_scene = mem::replace(&mut self.scene, scene);
+        self.clip_paths = clip_paths;
+
+        let mut pattern = self.scene.push_scene_as_pattern(tile_scene, tile_size);
+        pattern.set_repeat_x(true);
+        pattern.set_repeat_y(true);
+        pattern.apply_transform(usvg_transform_to_transform_2d(&svg_pattern.transform) *
+                                Transform2F::from_translation(tile_rect.origin()) *
+                                Transform2F::from_scale(tile_scale).inverse());
+        self.patterns.insert(svg_pattern.id.clone(), pattern);
+    }
+
     fn push_draw_path(&mut self,
                       mut outline: Outline,
                       name: String,
@@ -273,6 +316,7 @@ impl BuiltSVG {
                                           &state.transform,
                                           opacity,
                                           &self.gradients,
+                                          &self.patterns,
                                           &mut self.result_flags);
         let style = self.scene.push_paint(&paint);
         let fill_rule = FillRule::from_usvg_fill_rule(fill_rule);
@@ -326,6 +370,7 @@ trait PaintExt {
                       transform: &Transform2F,
                       opacity: Opacity,
                       gradients: &HashMap<String, GradientInfo>,
+                      patterns: &HashMap<String, Pattern>,
                       result_flags: &mut BuildResultFlags)
                       -> Self;
 }
@@ -336,6 +381,7 @@ impl PaintExt for Paint {
                       transform: &Transform2F,
                       opacity: Opacity,
                       gradients: &HashMap<String, GradientInfo>,
+                      patterns: &HashMap<String, Pattern>,
                       result_flags: &mut BuildResultFlags)
                       -> Paint {
         let mut paint;
@@ -348,9 +394,17 @@ impl PaintExt for Paint {
                         paint.apply_transform(&(*transform * gradient_info.transform));
                     }
                     None => {
-                        // TODO(pcwalton)
-                        result_flags.insert(BuildResultFlags::UNSUPPORTED_LINK_PAINT);
-                        paint = Paint::from_color(ColorU::black());
+                        match patterns.get(id) {
+                            Some(pattern) => {
+                                paint = Paint::from_pattern(pattern.clone());
+                                paint.apply_transform(transform);
+                            }
+                            None => {
+                                // TODO(pcwalton)
+                                result_flags.insert(BuildResultFlags::UNSUPPORTED_LINK_PAINT);
+                                paint = Paint::from_color(ColorU::black());
+                            }
+                        }
                     }
                 }
             }
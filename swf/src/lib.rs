@@ -12,7 +12,7 @@ use std::ops::Add;
 use pathfinder_color::{ColorF, ColorU};
 use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::{Outline, Contour};
-use pathfinder_content::stroke::{OutlineStrokeToFill, StrokeStyle};
+use pathfinder_content::stroke::{OutlineStrokeToFill, StrokeAlignment, StrokeStyle};
 use pathfinder_geometry::vector::vec2f;
 use pathfinder_renderer::scene::{DrawPath, Scene};
 
@@ -187,6 +187,7 @@ pub fn draw_paths_into_scene(library: &SymbolLibrary, scene: &mut Scene) {
                         line_width: line.width.as_f32(),
                         line_cap: line.cap,
                         line_join: line.join,
+                        alignment: StrokeAlignment::Center,
                     });
                     stroke_to_fill.offset();
                     path = stroke_to_fill.into_outline();
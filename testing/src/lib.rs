@@ -0,0 +1,102 @@
+// pathfinder/testing/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers for writing rendering regression tests against Pathfinder.
+//!
+//! Scenes are rendered offscreen with OpenGL on a CPU rasterizer, such as llvmpipe or
+//! SwiftShader, so that tests give the same results on machines without a GPU, as on CI. The
+//! rasterizer is whichever one `surfman` finds; on Linux that needs Mesa and an X server, which
+//! can be a virtual one like Xvfb.
+//!
+//! Tools that need more control, such as reference tests and benchmarks, can use
+//! `OffscreenRenderer` directly, which also runs on the GPU's OpenGL implementation.
+//!
+//! The usual test renders a scene built the way under test and one built some other, known-good
+//! way, and checks that they look alike:
+//!
+//! ```no_run
+//! # use pathfinder_renderer::scene::Scene;
+//! # fn build_with_my_library() -> Scene { unimplemented!() }
+//! # fn build_by_hand() -> Scene { unimplemented!() }
+//! pathfinder_testing::assert_scenes_match(&build_with_my_library(), &build_by_hand(), 0.01);
+//! ```
+
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_renderer::scene::Scene;
+
+pub use crate::render::{Backend, OffscreenRenderer, SoftwareRenderer};
+pub use crate::ssim::ssim;
+
+mod render;
+pub mod ssim;
+
+/// Renders both scenes and panics unless they look alike.
+///
+/// Each scene is drawn over white at one pixel per unit, with the origin of its view box at the
+/// top left corner, so the two must have the same view box. `tolerance` is how far their
+/// structural similarity (see `ssim()`) may fall below 1.0: 0.0 requires them to be identical,
+/// and around 0.01 allows for differences in antialiasing.
+///
+/// This sets up a new renderer for each call. To compare many scenes, create a
+/// `SoftwareRenderer` once and call `ssim()` on the images it renders instead.
+pub fn assert_scenes_match(scene_a: &Scene, scene_b: &Scene, tolerance: f32) {
+    let view_box = scene_a.view_box();
+    assert_eq!(view_box, scene_b.view_box(), "The scenes have different view boxes!");
+
+    let size = view_box.size().ceil().to_i32().max(Vector2I::splat(1));
+    let mut renderer = SoftwareRenderer::new(size).unwrap_or_else(|message| {
+        panic!("Failed to create the software renderer: {}", message)
+    });
+    let image_a = renderer.render(scene_a).expect("Failed to render the first scene!");
+    let image_b = renderer.render(scene_b).expect("Failed to render the second scene!");
+
+    let similarity = ssim(&image_a, &image_b).unwrap();
+    assert!(1.0 - similarity <= tolerance,
+            "The scenes don't match: their similarity is {}, below the minimum of {}",
+            similarity,
+            1.0 - tolerance);
+}
+
+/// An RGBA image with 8 bits per channel, premultiplied, with its top row first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub size: Vector2I,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Creates an image of the given size filled with `color`.
+    pub fn new(size: Vector2I, color: [u8; 4]) -> Image {
+        let pixel_count = size.x().max(0) as usize * size.y().max(0) as usize;
+        let pixels = color.iter().cloned().cycle().take(pixel_count * 4).collect();
+        Image { size, pixels }
+    }
+
+    #[inline]
+    pub fn pixel(&self, position: Vector2I) -> [u8; 4] {
+        let offset = self.offset_of(position);
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[offset..(offset + 4)]);
+        pixel
+    }
+
+    #[inline]
+    pub fn put_pixel(&mut self, position: Vector2I, color: [u8; 4]) {
+        let offset = self.offset_of(position);
+        self.pixels[offset..(offset + 4)].copy_from_slice(&color);
+    }
+
+    #[inline]
+    fn offset_of(&self, position: Vector2I) -> usize {
+        assert!(position.x() >= 0 && position.x() < self.size.x() &&
+                position.y() >= 0 && position.y() < self.size.y());
+        (position.y() * self.size.x() + position.x()) as usize * 4
+    }
+}
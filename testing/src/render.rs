@@ -0,0 +1,188 @@
+// pathfinder/testing/src/render.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Offscreen rendering with OpenGL.
+
+use crate::Image;
+use euclid::default::Size2D;
+use pathfinder_color::ColorF;
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_gl::{GLDevice, GLVersion};
+use pathfinder_gpu::error::RenderError;
+use pathfinder_gpu::{Device, RenderTarget, TextureData};
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
+use pathfinder_renderer::gpu::options::{ClearMode, DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use pathfinder_renderer::scene::Scene;
+use pathfinder_resources::embedded::EmbeddedResourceLoader;
+use surfman::{Connection, Context, ContextAttributeFlags, ContextAttributes};
+use surfman::{Device as SurfmanDevice, GLVersion as SurfmanGLVersion, SurfaceAccess};
+use surfman::SurfaceType;
+
+/// Which OpenGL implementation to render with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    /// OpenGL on a CPU rasterizer such as llvmpipe or SwiftShader, as `surfman` finds it.
+    Software,
+    /// OpenGL on the system GPU.
+    Hardware,
+}
+
+impl Backend {
+    pub fn from_name(name: &str) -> Option<Backend> {
+        match name {
+            "software" => Some(Backend::Software),
+            "hardware" => Some(Backend::Hardware),
+            _ => None,
+        }
+    }
+}
+
+/// A renderer drawing into an offscreen OpenGL surface.
+///
+/// This is the building block for tools that render without a window. The renderer draws into the
+/// whole surface; to draw into part of it, point it at a smaller viewport with
+/// `Renderer::replace_dest_framebuffer()`.
+pub struct OffscreenRenderer {
+    device: SurfmanDevice,
+    context: Context,
+    pub renderer: Renderer<GLDevice>,
+    size: Vector2I,
+}
+
+impl OffscreenRenderer {
+    /// Creates a renderer whose surface is `size` pixels.
+    ///
+    /// Fails if the requested OpenGL implementation can't be found.
+    pub fn new(backend: Backend, size: Vector2I, options: RendererOptions)
+               -> Result<OffscreenRenderer, String> {
+        let connection = Connection::new().map_err(|error| {
+            format!("couldn't connect to the display server: {:?}", error)
+        })?;
+        let adapter = match backend {
+            Backend::Software => connection.create_software_adapter(),
+            Backend::Hardware => connection.create_hardware_adapter(),
+        }.map_err(|error| format!("couldn't create an OpenGL adapter: {:?}", error))?;
+        let mut device = connection.create_device(&adapter).unwrap();
+
+        let context_attributes = ContextAttributes {
+            version: SurfmanGLVersion::new(3, 0),
+            flags: ContextAttributeFlags::ALPHA,
+        };
+        let context_descriptor = device.create_context_descriptor(&context_attributes).unwrap();
+        let mut context = device.create_context(&context_descriptor).unwrap();
+        bind_new_surface(&mut device, &mut context, size);
+        device.make_context_current(&context).unwrap();
+        gl::load_with(|symbol_name| device.get_proc_address(&context, symbol_name));
+
+        let default_framebuffer = default_framebuffer(&device, &context);
+        let renderer = match Renderer::new(GLDevice::new(GLVersion::GL3, default_framebuffer),
+                                           &EmbeddedResourceLoader::new(),
+                                           DestFramebuffer::full_window(size),
+                                           options) {
+            Ok(renderer) => renderer,
+            Err(error) => {
+                drop(device.destroy_context(&mut context));
+                return Err(format!("couldn't create the renderer: {}", error));
+            }
+        };
+
+        Ok(OffscreenRenderer { device, context, renderer, size })
+    }
+
+    #[inline]
+    pub fn size(&self) -> Vector2I {
+        self.size
+    }
+
+    /// Replaces the surface with one of `new_size` pixels, and has the renderer draw into all of
+    /// it.
+    pub fn resize(&mut self, new_size: Vector2I) {
+        let mut surface = self.device.unbind_surface_from_context(&mut self.context)
+                                     .unwrap()
+                                     .unwrap();
+        self.device.destroy_surface(&mut self.context, &mut surface).unwrap();
+        bind_new_surface(&mut self.device, &mut self.context, new_size);
+
+        let default_framebuffer = default_framebuffer(&self.device, &self.context);
+        self.renderer.device.set_default_framebuffer(default_framebuffer);
+        self.renderer.replace_dest_framebuffer(DestFramebuffer::full_window(new_size));
+        self.size = new_size;
+    }
+
+    /// Reads back the `size` pixels in the top left corner of the surface. They're premultiplied,
+    /// as the renderer draws them.
+    pub fn read_pixels(&mut self, size: Vector2I) -> Image {
+        let viewport = RectI::new(Vector2I::default(), size);
+        let device = &self.renderer.device;
+        match device.recv_texture_data(&device.read_pixels(&RenderTarget::Default, viewport)) {
+            TextureData::U8(pixels) => Image { size, pixels },
+            _ => panic!("Unexpected pixel format for the default framebuffer!"),
+        }
+    }
+}
+
+impl Drop for OffscreenRenderer {
+    fn drop(&mut self) {
+        drop(self.device.destroy_context(&mut self.context));
+    }
+}
+
+/// Renders scenes into an offscreen surface with OpenGL on a CPU rasterizer, and reads them back.
+///
+/// Scenes are built sequentially and drawn without dithering, so that the same scene always
+/// renders to the same pixels.
+pub struct SoftwareRenderer {
+    offscreen: OffscreenRenderer,
+}
+
+impl SoftwareRenderer {
+    /// Creates a renderer whose surface is `size` pixels.
+    ///
+    /// Fails if no software OpenGL implementation can be found.
+    pub fn new(size: Vector2I) -> Result<SoftwareRenderer, String> {
+        let options = RendererOptions { dithering: false, ..RendererOptions::default() };
+        let mut offscreen = OffscreenRenderer::new(Backend::Software, size, options)?;
+        offscreen.renderer.set_clear_mode(ClearMode::Color(ColorF::white()));
+        Ok(SoftwareRenderer { offscreen })
+    }
+
+    #[inline]
+    pub fn size(&self) -> Vector2I {
+        self.offscreen.size()
+    }
+
+    /// Draws `scene` over white at one pixel per unit, with the origin of its view box at the top
+    /// left corner, and returns the result.
+    pub fn render(&mut self, scene: &Scene) -> Result<Image, RenderError> {
+        let transform = Transform2F::from_translation(-scene.view_box().origin());
+        let build_options = BuildOptions {
+            transform: RenderTransform::Transform2D(transform),
+            ..BuildOptions::default()
+        };
+        let scene = SceneProxy::from_scene(scene.clone(), SequentialExecutor);
+        scene.build_and_render(&mut self.offscreen.renderer, build_options)?;
+        Ok(self.offscreen.read_pixels(self.size()))
+    }
+}
+
+fn bind_new_surface(device: &mut SurfmanDevice, context: &mut Context, size: Vector2I) {
+    let surface_type = SurfaceType::Generic { size: Size2D::new(size.x(), size.y()) };
+    let surface = device.create_surface(context, SurfaceAccess::GPUOnly, surface_type).unwrap();
+    device.bind_surface_to_context(context, surface).unwrap();
+}
+
+fn default_framebuffer(device: &SurfmanDevice, context: &Context) -> u32 {
+    device.context_surface_info(context).unwrap().unwrap().framebuffer_object
+}
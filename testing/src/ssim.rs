@@ -0,0 +1,162 @@
+// pathfinder/testing/src/ssim.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The structural similarity index (SSIM), a perceptual measure of how alike two images are.
+//!
+//! This is the simple form of SSIM: it compares the luma of the images over 8×8 windows, each
+//! overlapping the last by half, and averages the scores. Unlike a count of differing pixels, it
+//! barely notices antialiasing that's rounded differently, but it does notice shapes that have
+//! moved or changed color.
+
+use crate::Image;
+
+// The width and height of each window, and how far apart windows start.
+const WINDOW_SIZE: u32 = 8;
+const WINDOW_STRIDE: u32 = 4;
+
+// The constants that keep the score stable in flat regions, for 8-bit channels.
+const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+/// Computes the structural similarity of `a` and `b`, from 1.0 for identical images down to 0.0
+/// or, for images that are negatives of each other, below.
+///
+/// Pixels are composited onto white first, so the colors of transparent pixels don't matter.
+/// Returns `None` if the images aren't the same size.
+pub fn ssim(a: &Image, b: &Image) -> Option<f32> {
+    if a.size != b.size {
+        return None;
+    }
+    let (width, height) = (a.size.x() as u32, a.size.y() as u32);
+    if width == 0 || height == 0 {
+        return Some(1.0);
+    }
+
+    let (luma_a, luma_b) = (luma(a), luma(b));
+    let (mut total, mut window_count) = (0.0, 0);
+    for &y in &window_origins(height) {
+        for &x in &window_origins(width) {
+            total += window_ssim(&luma_a, &luma_b, width, x, y);
+            window_count += 1;
+        }
+    }
+    Some((total / window_count as f64) as f32)
+}
+
+fn window_ssim(a: &[f64], b: &[f64], width: u32, origin_x: u32, origin_y: u32) -> f64 {
+    let (mut sum_a, mut sum_b) = (0.0, 0.0);
+    let (mut sum_aa, mut sum_bb, mut sum_ab) = (0.0, 0.0, 0.0);
+    let height = a.len() as u32 / width;
+    let (window_width, window_height) = (WINDOW_SIZE.min(width), WINDOW_SIZE.min(height));
+    for y in origin_y..(origin_y + window_height) {
+        for x in origin_x..(origin_x + window_width) {
+            let index = (y * width + x) as usize;
+            let (a, b) = (a[index], b[index]);
+            sum_a += a;
+            sum_b += b;
+            sum_aa += a * a;
+            sum_bb += b * b;
+            sum_ab += a * b;
+        }
+    }
+
+    let count = (window_width * window_height) as f64;
+    let (mean_a, mean_b) = (sum_a / count, sum_b / count);
+    let variance_a = sum_aa / count - mean_a * mean_a;
+    let variance_b = sum_bb / count - mean_b * mean_b;
+    let covariance = sum_ab / count - mean_a * mean_b;
+    (2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2) /
+        ((mean_a * mean_a + mean_b * mean_b + C1) * (variance_a + variance_b + C2))
+}
+
+// Returns where windows start along an axis of the given length. The last window is moved back
+// to end at the edge, so that every pixel is covered.
+fn window_origins(length: u32) -> Vec<u32> {
+    if length <= WINDOW_SIZE {
+        return vec![0];
+    }
+    let last = length - WINDOW_SIZE;
+    let mut origins: Vec<u32> = (0..=last).step_by(WINDOW_STRIDE as usize).collect();
+    if origins.last() != Some(&last) {
+        origins.push(last);
+    }
+    origins
+}
+
+// Rec. 601 luma, after compositing onto white.
+fn luma(image: &Image) -> Vec<f64> {
+    image.pixels.chunks(4).map(|pixel| {
+        let alpha = pixel[3] as f64 / 255.0;
+        let blend = |channel: u8| 255.0 + (channel as f64 - 255.0) * alpha;
+        0.299 * blend(pixel[0]) + 0.587 * blend(pixel[1]) + 0.114 * blend(pixel[2])
+    }).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ssim, window_origins};
+    use crate::Image;
+    use pathfinder_geometry::vector::vec2i;
+
+    // A 16×16 image, white with a black square in its top left corner.
+    fn square_image(square_size: i32) -> Image {
+        let mut image = Image::new(vec2i(16, 16), [255, 255, 255, 255]);
+        for y in 0..square_size {
+            for x in 0..square_size {
+                image.put_pixel(vec2i(x, y), [0, 0, 0, 255]);
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn test_identical_images() {
+        let image = square_image(6);
+        assert_eq!(ssim(&image, &image), Some(1.0));
+        let blank = Image::new(vec2i(3, 2), [10, 20, 30, 255]);
+        assert_eq!(ssim(&blank, &blank), Some(1.0));
+    }
+
+    #[test]
+    fn test_similarity_falls_with_difference() {
+        let image = square_image(6);
+        let mut one_pixel_off = image.clone();
+        one_pixel_off.put_pixel(vec2i(15, 15), [224, 224, 224, 255]);
+        let one_pixel_off = ssim(&image, &one_pixel_off).unwrap();
+        let bigger_square = ssim(&image, &square_image(8)).unwrap();
+        let no_square = ssim(&image, &square_image(0)).unwrap();
+        assert!(one_pixel_off < 1.0 && one_pixel_off > 0.95);
+        assert!(bigger_square < one_pixel_off);
+        assert!(no_square < bigger_square);
+        assert_eq!(ssim(&square_image(0), &image), Some(no_square));
+    }
+
+    #[test]
+    fn test_transparent_pixels_are_white() {
+        let white = Image::new(vec2i(8, 8), [255, 255, 255, 255]);
+        let transparent = Image::new(vec2i(8, 8), [255, 0, 0, 0]);
+        assert_eq!(ssim(&white, &transparent), Some(1.0));
+    }
+
+    #[test]
+    fn test_size_mismatch() {
+        let a = Image::new(vec2i(4, 4), [0, 0, 0, 255]);
+        let b = Image::new(vec2i(4, 5), [0, 0, 0, 255]);
+        assert!(ssim(&a, &b).is_none());
+    }
+
+    #[test]
+    fn test_window_origins() {
+        assert_eq!(window_origins(5), vec![0]);
+        assert_eq!(window_origins(8), vec![0]);
+        assert_eq!(window_origins(16), vec![0, 4, 8]);
+        assert_eq!(window_origins(18), vec![0, 4, 8, 10]);
+    }
+}
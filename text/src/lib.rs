@@ -9,31 +9,40 @@
 // except according to those terms.
 
 use font_kit::error::GlyphLoadingError;
+use font_kit::handle::Handle;
 use font_kit::hinting::HintingOptions;
 use font_kit::loader::Loader;
 use font_kit::loaders::default::Font as DefaultLoader;
 use font_kit::outline::OutlineSink;
 use pathfinder_content::effects::BlendMode;
+use pathfinder_content::lru::LruMap;
 use pathfinder_content::outline::{Contour, Outline};
 use pathfinder_content::stroke::{OutlineStrokeToFill, StrokeStyle};
+use pathfinder_content::stroke_cache::{StrokeCacheKey, StrokeSourceId};
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{Vector2F, vec2f};
+use pathfinder_renderer::concurrent::executor::Executor;
 use pathfinder_renderer::paint::PaintId;
 use pathfinder_renderer::scene::{ClipPathId, DrawPath, Scene};
 use skribo::{FontCollection, Layout, TextStyle};
 use std::collections::HashMap;
 use std::mem;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Weak};
+
+const DEFAULT_TEXT_RUN_CACHE_CAPACITY: usize = 256;
+const DEFAULT_GLYPH_OUTLINE_CACHE_CAPACITY: usize = 4096;
 
 #[derive(Clone)]
 pub struct FontContext<F> where F: Loader {
     font_info: HashMap<String, FontInfo<F>>, 
+    glyph_outline_cache: Arc<GlyphOutlineCache>,
 }
 
 #[derive(Clone)]
 struct FontInfo<F> where F: Loader {
     font: F,
-    outline_cache: HashMap<GlyphId, Outline>,
 }
 
 #[derive(Clone, Copy)]
@@ -63,10 +72,39 @@ impl Default for FontRenderOptions {
 #[derive(Clone, Copy, PartialEq, Debug, Eq, Hash)]
 pub struct GlyphId(pub u32);
 
+/// A run of glyphs that has already been shaped, for example by HarfBuzz.
+///
+/// This borrows the shaper's output directly, so no string or layout needs to be constructed.
+#[derive(Clone, Copy)]
+pub struct GlyphRun<'a, F> where F: Loader {
+    /// The font that all glyphs in this run come from.
+    pub font: &'a F,
+    /// The font size in pixels per em.
+    pub font_size: f32,
+    /// The glyph IDs, as returned by the shaper.
+    pub glyph_ids: &'a [u32],
+    /// The position of each glyph's origin relative to the origin of the run, in pixels, with the
+    /// Y axis pointing down. Must be the same length as `glyph_ids`.
+    pub offsets: &'a [Vector2F],
+}
+
 impl<F> FontContext<F> where F: Loader {
     #[inline]
     pub fn new() -> FontContext<F> {
-        FontContext { font_info: HashMap::new() }
+        FontContext {
+            font_info: HashMap::new(),
+            glyph_outline_cache: Arc::new(GlyphOutlineCache::new()),
+        }
+    }
+
+    /// Creates a font context that loads glyph outlines into, and looks them up in, the given
+    /// shared cache.
+    #[inline]
+    pub fn with_cache(cache: &FontCache) -> FontContext<F> {
+        FontContext {
+            font_info: HashMap::new(),
+            glyph_outline_cache: cache.glyph_outline_cache().clone(),
+        }
     }
 
     pub fn push_glyph(&mut self,
@@ -75,8 +113,21 @@ impl<F> FontContext<F> where F: Loader {
                       glyph_id: GlyphId,
                       render_options: &FontRenderOptions)
                       -> Result<(), GlyphLoadingError> {
+        let outline = self.glyph_outline(font,
+                                         glyph_id,
+                                         &render_options.transform,
+                                         render_options.hinting_options)?;
+        push_glyph_outline(scene, outline, render_options);
+        Ok(())
+    }
+
+    fn glyph_outline(&mut self,
+                     font: &F,
+                     glyph_id: GlyphId,
+                     glyph_transform: &Transform2F,
+                     hinting_options: HintingOptions)
+                     -> Result<Outline, GlyphLoadingError> {
         let font_key = font.postscript_name();
-        let metrics = font.metrics();
 
         // Insert the font into the cache if needed.
         if let Some(ref font_key) = font_key {
@@ -85,60 +136,123 @@ impl<F> FontContext<F> where F: Loader {
             }
         }
 
-        // See if we have a cached outline.
+        // Unhinted outlines are cached at one pixel per em, with the Y axis pointing down, and
+        // scaled from there.
         //
         // TODO(pcwalton): Cache hinted outlines too.
-        let mut cached_outline = None;
-        let can_cache_outline = font_key.is_some() &&
-            render_options.hinting_options == HintingOptions::None;
-        if can_cache_outline {
-            if let Some(ref font_info) = self.font_info.get(&*font_key.as_ref().unwrap()) {
-                if let Some(ref outline) = font_info.outline_cache.get(&glyph_id) {
-                    cached_outline = Some((*outline).clone());
-                }
-            }
+        if hinting_options == HintingOptions::None {
+            let mut outline = (*self.glyph_outline_cache.glyph_outline(font,
+                                                                       glyph_id,
+                                                                       1.0,
+                                                                       hinting_options)?).clone();
+            let units_per_em = font.metrics().units_per_em as f32;
+            let scale = Transform2F::from_scale(vec2f(units_per_em, -units_per_em));
+            outline.transform(&(*glyph_transform * scale));
+            return Ok(outline);
         }
 
-        let mut outline = match cached_outline {
-            Some(mut cached_outline) => {
-                let scale = 1.0 / metrics.units_per_em as f32;
-                cached_outline.transform(&(render_options.transform *
-                                           Transform2F::from_scale(scale)));
-                cached_outline
+        let mut outline_builder = OutlinePathBuilder::new(glyph_transform);
+        font.outline(glyph_id.0, hinting_options, &mut outline_builder)?;
+        Ok(outline_builder.build())
+    }
+
+    /// Pushes a pre-shaped glyph run onto the scene, bypassing shaping entirely.
+    ///
+    /// The transform in `render_options` maps the origin of the run to its position in the
+    /// scene.
+    pub fn push_glyph_run(&mut self,
+                          scene: &mut Scene,
+                          run: &GlyphRun<F>,
+                          render_options: &FontRenderOptions)
+                          -> Result<(), GlyphLoadingError> {
+        debug_assert_eq!(run.glyph_ids.len(), run.offsets.len());
+
+        let scale = run.font_size / (run.font.metrics().units_per_em as f32);
+        let scale = Transform2F::from_scale(vec2f(scale, -scale));
+        for (&glyph_id, &offset) in run.glyph_ids.iter().zip(run.offsets.iter()) {
+            let render_options = FontRenderOptions {
+                transform: render_options.transform * Transform2F::from_translation(offset) *
+                    scale,
+                ..*render_options
+            };
+            self.push_glyph(scene, run.font, GlyphId(glyph_id), &render_options)?;
+        }
+        Ok(())
+    }
+
+    /// Pushes many pre-shaped glyph runs onto the scene, each with its own render options,
+    /// transforming and stroking their glyph outlines in parallel on `executor`.
+    ///
+    /// Outlines are looked up in (and loaded into) the context's glyph outline cache first, on
+    /// the calling thread, since fonts generally can't be shared between threads. Glyphs are
+    /// pushed in order, so the result is the same as pushing the runs one at a time.
+    pub fn push_glyph_runs<E>(&mut self,
+                              scene: &mut Scene,
+                              runs: &[(GlyphRun<F>, FontRenderOptions)],
+                              executor: &E)
+                              -> Result<(), GlyphLoadingError>
+                              where E: Executor {
+        let mut run_glyphs = Vec::with_capacity(runs.len());
+        for (run, render_options) in runs {
+            debug_assert_eq!(run.glyph_ids.len(), run.offsets.len());
+
+            // Unhinted outlines are the same shape at every size, so they're cached at one pixel
+            // per em and scaled when they're transformed.
+            let (cached_size, scale) = match render_options.hinting_options {
+                HintingOptions::None => (1.0, Transform2F::from_scale(run.font_size)),
+                _ => (run.font_size, Transform2F::default()),
+            };
+            let mut glyphs = Vec::with_capacity(run.glyph_ids.len());
+            for (&glyph_id, &offset) in run.glyph_ids.iter().zip(run.offsets.iter()) {
+                let outline = self.glyph_outline_cache
+                                  .glyph_outline(run.font,
+                                                 GlyphId(glyph_id),
+                                                 cached_size,
+                                                 render_options.hinting_options)?;
+                glyphs.push((outline, offset));
             }
-            None => {
-                let transform = if can_cache_outline {
-                    Transform2F::from_scale(metrics.units_per_em as f32)
-                } else {
-                    render_options.transform
-                };
-                let mut outline_builder = OutlinePathBuilder::new(&transform);
-                font.outline(glyph_id.0, render_options.hinting_options, &mut outline_builder)?;
-                let mut outline = outline_builder.build();
-                if can_cache_outline {
-                    let font_key = font_key.as_ref().unwrap();
-                    let font_info = self.font_info.get_mut(&*font_key).unwrap();
-                    font_info.outline_cache.insert(glyph_id, outline.clone());
-                    let scale = 1.0 / metrics.units_per_em as f32;
-                    outline.transform(&(render_options.transform *
-                                        Transform2F::from_scale(scale)));
+            run_glyphs.push((glyphs, scale, *render_options));
+        }
+
+        let run_outlines = executor.build_vector(run_glyphs.len(), |run_index| {
+            let (glyphs, scale, render_options) = &run_glyphs[run_index];
+            glyphs.iter().map(|(outline, offset)| {
+                let mut outline = (**outline).clone();
+                outline.transform(&(render_options.transform *
+                                    Transform2F::from_translation(*offset) *
+                                    *scale));
+                if let TextRenderMode::Stroke(stroke_style) = render_options.render_mode {
+                    let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
+                    stroke_to_fill.offset();
+                    outline = stroke_to_fill.into_outline();
                 }
                 outline
-            }
-        };
+            }).collect::<Vec<_>>()
+        });
 
-        if let TextRenderMode::Stroke(stroke_style) = render_options.render_mode {
-            let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
-            stroke_to_fill.offset();
-            outline = stroke_to_fill.into_outline();
+        for (outlines, (_, render_options)) in run_outlines.into_iter().zip(runs.iter()) {
+            let render_options = FontRenderOptions {
+                render_mode: TextRenderMode::Fill,
+                ..*render_options
+            };
+            for outline in outlines {
+                push_glyph_outline(scene, outline, &render_options);
+            }
         }
+        Ok(())
+    }
 
-        let mut path = DrawPath::new(outline, render_options.paint_id);
-        path.set_clip_path(render_options.clip_path);
-        path.set_blend_mode(render_options.blend_mode);
+    /// The cache of glyph outlines that `push_glyph_runs()` uses.
+    #[inline]
+    pub fn glyph_outline_cache(&self) -> &Arc<GlyphOutlineCache> {
+        &self.glyph_outline_cache
+    }
 
-        scene.push_path(path);
-        Ok(())
+    /// Replaces the glyph outline cache, for example with one shared between font contexts on
+    /// different threads.
+    #[inline]
+    pub fn set_glyph_outline_cache(&mut self, new_cache: Arc<GlyphOutlineCache>) {
+        self.glyph_outline_cache = new_cache;
     }
 
     /// Attempts to look up a font in the font cache.
@@ -171,6 +285,100 @@ impl FontContext<DefaultLoader> {
         Ok(())
     }
 
+    /// Returns the shaped and outlined text run for the given string, consulting and updating
+    /// `cache` so that repeatedly-drawn text is shaped and outlined only once.
+    ///
+    /// Only unhinted outlines are cached, so the run is always rendered without hinting.
+    pub fn layout_text_run(&mut self,
+                           cache: &TextRunCache,
+                           text: &str,
+                           style: &TextStyle,
+                           collection: &Arc<FontCollection>)
+                           -> Result<Arc<TextRun>, GlyphLoadingError> {
+        let key = TextRunKey::new(text, style, collection);
+        if let Some(run) = cache.get(&key) {
+            return Ok(run);
+        }
+
+        let layout = skribo::layout(style, collection, text);
+        let mut outlines = Vec::with_capacity(layout.glyphs.len());
+        for glyph in &layout.glyphs {
+            let font = &*glyph.font.font;
+            let scale = style.size / (font.metrics().units_per_em as f32);
+            let transform = Transform2F::from_scale(vec2f(scale, -scale)).translate(glyph.offset);
+            outlines.push(self.glyph_outline(font,
+                                             GlyphId(glyph.glyph_id),
+                                             &transform,
+                                             HintingOptions::None)?);
+        }
+
+        let run = Arc::new(TextRun {
+            layout,
+            outlines,
+            id: StrokeSourceId::new(),
+            last_stroke: Mutex::new(None),
+        });
+        cache.insert(key, collection.clone(), run.clone());
+        Ok(run)
+    }
+
+    /// Pushes a text run previously returned by `layout_text_run()` onto the scene.
+    ///
+    /// If the run is stroked the same way as the last time it was pushed, apart from translation,
+    /// the glyph outlines aren't stroked again.
+    pub fn push_text_run(&mut self,
+                         scene: &mut Scene,
+                         run: &TextRun,
+                         render_options: &FontRenderOptions) {
+        let stroke_style = match render_options.render_mode {
+            TextRenderMode::Fill => {
+                for outline in &run.outlines {
+                    let mut outline = (*outline).clone();
+                    outline.transform(&render_options.transform);
+                    push_glyph_outline(scene, outline, render_options);
+                }
+                return;
+            }
+            TextRenderMode::Stroke(stroke_style) => stroke_style,
+        };
+
+        // Glyphs are stroked after they're transformed, so their strokes depend on the linear part
+        // of the transform, but translating them afterward is the same as stroking them in place.
+        let matrix = render_options.transform.matrix;
+        let key = StrokeCacheKey::new(run.id, &stroke_style).with_transform(matrix);
+        let stroked_outlines = {
+            let mut last_stroke = run.last_stroke.lock().unwrap();
+            match *last_stroke {
+                Some((ref last_key, ref stroked_outlines)) if *last_key == key => {
+                    stroked_outlines.clone()
+                }
+                _ => {
+                    let linear_transform = Transform2F { matrix, vector: Vector2F::zero() };
+                    let stroked_outlines = Arc::new(run.outlines.iter().map(|outline| {
+                        let mut outline = (*outline).clone();
+                        outline.transform(&linear_transform);
+                        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
+                        stroke_to_fill.offset();
+                        stroke_to_fill.into_outline()
+                    }).collect::<Vec<_>>());
+                    *last_stroke = Some((key, stroked_outlines.clone()));
+                    stroked_outlines
+                }
+            }
+        };
+
+        let translation = Transform2F::from_translation(render_options.transform.vector);
+        let render_options = FontRenderOptions {
+            render_mode: TextRenderMode::Fill,
+            ..*render_options
+        };
+        for outline in stroked_outlines.iter() {
+            let mut outline = (*outline).clone();
+            outline.transform(&translation);
+            push_glyph_outline(scene, outline, &render_options);
+        }
+    }
+
     #[inline]
     pub fn push_text(&mut self,
                      scene: &mut Scene,
@@ -184,9 +392,361 @@ impl FontContext<DefaultLoader> {
     }
 }
 
+/// A string that has been shaped and had its glyph outlines extracted.
+pub struct TextRun {
+    layout: Layout,
+    // One outline per glyph, relative to the origin of the run.
+    outlines: Vec<Outline>,
+    id: StrokeSourceId,
+    // The most recent stroke of the run, which text that's redrawn unchanged every frame reuses.
+    // The outlines have been transformed by the linear part of the transform, but not translated.
+    last_stroke: Mutex<Option<(StrokeCacheKey, Arc<Vec<Outline>>)>>,
+}
+
+impl TextRun {
+    #[inline]
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+}
+
+/// A least-recently-used cache of text runs, keyed by the string, font collection, and font size.
+///
+/// This is useful for labels that are redrawn every frame with the same contents. Entries keep
+/// their font collection alive, so a collection is never confused with a new one allocated at the
+/// same address.
+///
+/// The cache locks itself, so one cache can be shared by any number of font contexts through an
+/// `Arc`, and between threads if the platform's fonts can be.
+pub struct TextRunCache {
+    state: Mutex<TextRunCacheState>,
+}
+
+struct TextRunCacheState {
+    entries: LruMap<TextRunKey, TextRunCacheEntry>,
+    capacity: usize,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TextRunKey {
+    text: String,
+    font_collection: usize,
+    font_size: u32,
+}
+
+impl TextRunKey {
+    fn new(text: &str, style: &TextStyle, collection: &Arc<FontCollection>) -> TextRunKey {
+        TextRunKey {
+            text: text.to_owned(),
+            font_collection: &**collection as *const FontCollection as usize,
+            font_size: style.size.to_bits(),
+        }
+    }
+}
+
+struct TextRunCacheEntry {
+    font_collection: Arc<FontCollection>,
+    run: Arc<TextRun>,
+}
+
+impl TextRunCache {
+    #[inline]
+    pub fn new() -> TextRunCache {
+        TextRunCache::with_capacity(DEFAULT_TEXT_RUN_CACHE_CAPACITY)
+    }
+
+    /// Creates a cache that holds at most `capacity` text runs.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> TextRunCache {
+        TextRunCache {
+            state: Mutex::new(TextRunCacheState { entries: LruMap::new(), capacity }),
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.state.lock().unwrap().capacity
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().entries.is_empty()
+    }
+
+    /// Changes the maximum number of runs, evicting the least recently used ones if necessary.
+    pub fn set_capacity(&self, new_capacity: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.capacity = new_capacity;
+        while state.entries.len() > state.capacity {
+            state.entries.pop_least_recently_used();
+        }
+    }
+
+    /// Removes all cached runs. Call this when the contents of fonts change.
+    #[inline]
+    pub fn clear(&self) {
+        self.state.lock().unwrap().entries.clear();
+    }
+
+    /// Removes all cached runs that were shaped with the given font collection.
+    pub fn invalidate_font_collection(&self, collection: &Arc<FontCollection>) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|_, entry| !Arc::ptr_eq(&entry.font_collection, collection));
+    }
+
+    fn get(&self, key: &TextRunKey) -> Option<Arc<TextRun>> {
+        let mut state = self.state.lock().unwrap();
+        state.entries.get(key).map(|entry| entry.run.clone())
+    }
+
+    fn insert(&self, key: TextRunKey, font_collection: Arc<FontCollection>, run: Arc<TextRun>) {
+        let mut state = self.state.lock().unwrap();
+        if state.capacity == 0 {
+            return;
+        }
+        while state.entries.len() >= state.capacity {
+            state.entries.pop_least_recently_used();
+        }
+        state.entries.insert(key, TextRunCacheEntry { font_collection, run });
+    }
+}
+
+impl Default for TextRunCache {
+    #[inline]
+    fn default() -> TextRunCache {
+        TextRunCache::new()
+    }
+}
+
+/// A least-recently-used cache of glyph outlines at particular sizes, which can be shared between
+/// threads.
+///
+/// Outlines are keyed by the font they come from, their glyph ID, their size, and how they're
+/// hinted. Fonts are told apart by the file or the buffer that they were loaded from, not by name,
+/// since different versions of a font often share a PostScript name. Fonts that can't say where
+/// they came from aren't cached, so their outlines are loaded every time they're needed.
+pub struct GlyphOutlineCache {
+    state: Mutex<GlyphOutlineCacheState>,
+}
+
+struct GlyphOutlineCacheState {
+    entries: LruMap<GlyphOutlineKey, GlyphOutlineCacheEntry>,
+    capacity: usize,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct GlyphOutlineKey {
+    font: FontIdentity,
+    glyph_id: GlyphId,
+    font_size: u32,
+    hinting: (u8, u32),
+}
+
+// Where a font was loaded from.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum FontIdentity {
+    Path { path: PathBuf, font_index: u32 },
+    // The address of the font data, which the cache entry keeps from being reused.
+    Memory { address: usize, font_index: u32 },
+}
+
+struct GlyphOutlineCacheEntry {
+    outline: Arc<Outline>,
+    // Keeps the allocation of in-memory font data alive, without keeping the data itself alive,
+    // so that no other font can be loaded at the same address while the entry exists.
+    #[allow(dead_code)]
+    font_data: Option<Weak<Vec<u8>>>,
+}
+
+impl GlyphOutlineCache {
+    #[inline]
+    pub fn new() -> GlyphOutlineCache {
+        GlyphOutlineCache::with_capacity(DEFAULT_GLYPH_OUTLINE_CACHE_CAPACITY)
+    }
+
+    /// Creates a cache that holds at most `capacity` glyph outlines.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> GlyphOutlineCache {
+        GlyphOutlineCache {
+            state: Mutex::new(GlyphOutlineCacheState { entries: LruMap::new(), capacity }),
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.state.lock().unwrap().capacity
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().entries.is_empty()
+    }
+
+    /// Changes the maximum number of outlines, evicting the least recently used ones if
+    /// necessary.
+    pub fn set_capacity(&self, new_capacity: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.capacity = new_capacity;
+        while state.entries.len() > state.capacity {
+            state.entries.pop_least_recently_used();
+        }
+    }
+
+    #[inline]
+    pub fn clear(&self) {
+        self.state.lock().unwrap().entries.clear();
+    }
+
+    /// Returns the outline of a glyph at `font_size` pixels per em, with its origin at the origin
+    /// and the Y axis pointing down, loading it from the font if it isn't cached.
+    pub fn glyph_outline<F>(&self,
+                            font: &F,
+                            glyph_id: GlyphId,
+                            font_size: f32,
+                            hinting_options: HintingOptions)
+                            -> Result<Arc<Outline>, GlyphLoadingError>
+                            where F: Loader {
+        let (font_identity, font_data) = match font.handle() {
+            Some(Handle::Path { path, font_index }) => {
+                (Some(FontIdentity::Path { path, font_index }), None)
+            }
+            Some(Handle::Memory { bytes, font_index }) => {
+                let address = &*bytes as *const Vec<u8> as usize;
+                (Some(FontIdentity::Memory { address, font_index }), Some(Arc::downgrade(&bytes)))
+            }
+            None => (None, None),
+        };
+        let key = font_identity.map(|font| {
+            GlyphOutlineKey {
+                font,
+                glyph_id,
+                font_size: font_size.to_bits(),
+                hinting: match hinting_options {
+                    HintingOptions::None => (0, 0),
+                    HintingOptions::Vertical(size) => (1, size.to_bits()),
+                    HintingOptions::VerticalSubpixel(size) => (2, size.to_bits()),
+                    HintingOptions::Full(size) => (3, size.to_bits()),
+                },
+            }
+        });
+        if let Some(ref key) = key {
+            if let Some(outline) = self.get(key) {
+                return Ok(outline);
+            }
+        }
+
+        let scale = font_size / (font.metrics().units_per_em as f32);
+        let mut outline_builder =
+            OutlinePathBuilder::new(&Transform2F::from_scale(vec2f(scale, -scale)));
+        font.outline(glyph_id.0, hinting_options, &mut outline_builder)?;
+        let outline = Arc::new(outline_builder.build());
+
+        if let Some(key) = key {
+            self.insert(key, font_data, outline.clone());
+        }
+        Ok(outline)
+    }
+
+    fn get(&self, key: &GlyphOutlineKey) -> Option<Arc<Outline>> {
+        let mut state = self.state.lock().unwrap();
+        state.entries.get(key).map(|entry| entry.outline.clone())
+    }
+
+    fn insert(&self,
+              key: GlyphOutlineKey,
+              font_data: Option<Weak<Vec<u8>>>,
+              outline: Arc<Outline>) {
+        let mut state = self.state.lock().unwrap();
+        if state.capacity == 0 {
+            return;
+        }
+        while state.entries.len() >= state.capacity {
+            state.entries.pop_least_recently_used();
+        }
+        state.entries.insert(key, GlyphOutlineCacheEntry { outline, font_data });
+    }
+}
+
+impl Default for GlyphOutlineCache {
+    #[inline]
+    fn default() -> GlyphOutlineCache {
+        GlyphOutlineCache::new()
+    }
+}
+
+/// The caches that font contexts fill as they shape text and load glyph outlines, bundled so
+/// that several contexts can share them.
+///
+/// Cloning a `FontCache` makes another handle to the same caches. Give one to each canvas or
+/// scene that draws text, and text that appears on more than one of them is shaped and outlined
+/// only once. Glyph outlines can always be shared between threads; text runs can be if the
+/// platform's fonts can be.
+#[derive(Clone, Default)]
+pub struct FontCache {
+    glyph_outline_cache: Arc<GlyphOutlineCache>,
+    text_run_cache: Arc<TextRunCache>,
+}
+
+impl FontCache {
+    #[inline]
+    pub fn new() -> FontCache {
+        FontCache::default()
+    }
+
+    /// Creates caches that hold at most `text_run_capacity` text runs.
+    #[inline]
+    pub fn with_text_run_capacity(text_run_capacity: usize) -> FontCache {
+        FontCache {
+            glyph_outline_cache: Arc::new(GlyphOutlineCache::new()),
+            text_run_cache: Arc::new(TextRunCache::with_capacity(text_run_capacity)),
+        }
+    }
+
+    #[inline]
+    pub fn glyph_outline_cache(&self) -> &Arc<GlyphOutlineCache> {
+        &self.glyph_outline_cache
+    }
+
+    /// The cache to pass to `FontContext::layout_text_run()`.
+    #[inline]
+    pub fn text_run_cache(&self) -> &Arc<TextRunCache> {
+        &self.text_run_cache
+    }
+
+    /// Empties both caches. Call this when the contents of fonts change.
+    pub fn clear(&self) {
+        self.glyph_outline_cache.clear();
+        self.text_run_cache.clear();
+    }
+}
+
+fn push_glyph_outline(scene: &mut Scene, mut outline: Outline, render_options: &FontRenderOptions) {
+    if let TextRenderMode::Stroke(stroke_style) = render_options.render_mode {
+        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
+        stroke_to_fill.offset();
+        outline = stroke_to_fill.into_outline();
+    }
+
+    let mut path = DrawPath::new(outline, render_options.paint_id);
+    path.set_clip_path(render_options.clip_path);
+    path.set_blend_mode(render_options.blend_mode);
+
+    scene.push_path(path);
+}
+
 impl<F> FontInfo<F> where F: Loader {
     fn new(font: F) -> FontInfo<F> {
-        FontInfo { font, outline_cache: HashMap::new() }
+        FontInfo { font }
     }
 }
 
@@ -247,3 +807,109 @@ impl OutlineSink for OutlinePathBuilder {
         self.current_contour.close();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use pathfinder_content::outline::Outline;
+    use pathfinder_content::stroke_cache::StrokeSourceId;
+    use skribo::{FontCollection, TextStyle};
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use super::{DefaultLoader, FontContext, FontIdentity, GlyphId, GlyphOutlineCache};
+    use super::{GlyphOutlineKey, TextRun, TextRunCache, TextRunKey};
+
+    const STYLE: TextStyle = TextStyle { size: 16.0 };
+
+    fn glyph_key(glyph_id: u32) -> GlyphOutlineKey {
+        GlyphOutlineKey {
+            font: FontIdentity::Path { path: PathBuf::from("font.ttf"), font_index: 0 },
+            glyph_id: GlyphId(glyph_id),
+            font_size: STYLE.size.to_bits(),
+            hinting: (0, 0),
+        }
+    }
+
+    fn text_run() -> Arc<TextRun> {
+        Arc::new(TextRun {
+            layout: skribo::layout(&STYLE, &FontCollection::new(), ""),
+            outlines: vec![],
+            id: StrokeSourceId::new(),
+            last_stroke: Mutex::new(None),
+        })
+    }
+
+    #[test]
+    fn test_glyph_outline_eviction_order() {
+        let cache = GlyphOutlineCache::with_capacity(3);
+        for glyph_id in 0..3 {
+            cache.insert(glyph_key(glyph_id), None, Arc::new(Outline::new()));
+        }
+
+        // Using the oldest outline makes the second the least recently used.
+        assert!(cache.get(&glyph_key(0)).is_some());
+        cache.insert(glyph_key(3), None, Arc::new(Outline::new()));
+        assert_eq!(cache.len(), 3);
+        assert!(cache.get(&glyph_key(1)).is_none());
+
+        // Shrinking the cache evicts in order of last use.
+        cache.set_capacity(2);
+        assert!(cache.get(&glyph_key(2)).is_none());
+        assert!(cache.get(&glyph_key(0)).is_some());
+        assert!(cache.get(&glyph_key(3)).is_some());
+        cache.set_capacity(1);
+        assert!(cache.get(&glyph_key(0)).is_none());
+        assert!(cache.get(&glyph_key(3)).is_some());
+    }
+
+    #[test]
+    fn test_invalidate_font_collection() {
+        let cache = TextRunCache::with_capacity(8);
+        let (collection_a, collection_b) =
+            (Arc::new(FontCollection::new()), Arc::new(FontCollection::new()));
+        for text in &["one", "two"] {
+            for collection in &[&collection_a, &collection_b] {
+                cache.insert(TextRunKey::new(text, &STYLE, collection),
+                             (*collection).clone(),
+                             text_run());
+            }
+        }
+        assert_eq!(cache.len(), 4);
+
+        cache.invalidate_font_collection(&collection_a);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&TextRunKey::new("one", &STYLE, &collection_a)).is_none());
+        assert!(cache.get(&TextRunKey::new("one", &STYLE, &collection_b)).is_some());
+        assert!(cache.get(&TextRunKey::new("two", &STYLE, &collection_b)).is_some());
+    }
+
+    #[test]
+    fn test_text_run_cache() {
+        let mut font_context: FontContext<DefaultLoader> = FontContext::new();
+        let cache = TextRunCache::with_capacity(2);
+        let collection = Arc::new(FontCollection::new());
+        let mut layout = |size: f32| {
+            let style = TextStyle { size };
+            font_context.layout_text_run(&cache, "", &style, &collection).unwrap()
+        };
+
+        // A miss shapes the run; a hit returns the same run.
+        let small = layout(12.0);
+        assert_eq!(cache.len(), 1);
+        assert!(Arc::ptr_eq(&small, &layout(12.0)));
+        let large = layout(24.0);
+        assert!(!Arc::ptr_eq(&small, &large));
+        assert_eq!(cache.len(), 2);
+
+        // The small run was used less recently, so it makes room for a third.
+        let huge = layout(48.0);
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(&huge, &layout(48.0)));
+        assert!(Arc::ptr_eq(&large, &layout(24.0)));
+        assert!(!Arc::ptr_eq(&small, &layout(12.0)));
+
+        // A cache without room caches nothing.
+        cache.set_capacity(0);
+        assert!(cache.is_empty());
+        assert!(!Arc::ptr_eq(&layout(12.0), &layout(12.0)));
+    }
+}
@@ -0,0 +1,155 @@
+// pathfinder/ui/src/debug_draw.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Fire-and-forget lines, shapes, and labels drawn over a frame, for debugging geometry.
+//!
+//! Push primitives onto a `DebugDrawList` from anywhere while building a frame, and
+//! `UIPresenter::flush_debug_draw_list()` draws them all on top of it, in a few draw calls, and
+//! clears the list. Nothing goes through the scene, so this works even when scene rendering
+//! doesn't.
+
+use pathfinder_color::ColorU;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, vec2f};
+use std::f32::consts::PI;
+
+const CIRCLE_SEGMENT_COUNT: u32 = 32;
+
+/// A list of debug primitives to draw over the next frame.
+#[derive(Clone, Default)]
+pub struct DebugDrawList {
+    transform: Transform2F,
+    pub(crate) lines: Vec<DebugLine>,
+    pub(crate) labels: Vec<DebugLabel>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct DebugLine {
+    pub(crate) segment: LineSegment2F,
+    pub(crate) color: ColorU,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct DebugLabel {
+    pub(crate) origin: Vector2F,
+    pub(crate) text: String,
+    pub(crate) color: ColorU,
+}
+
+impl DebugDrawList {
+    #[inline]
+    pub fn new() -> DebugDrawList {
+        DebugDrawList::default()
+    }
+
+    /// Returns the transform from the coordinates primitives are given in to framebuffer pixels.
+    #[inline]
+    pub fn transform(&self) -> Transform2F {
+        self.transform
+    }
+
+    /// Sets the transform applied to primitives pushed from now on, so that they can be given in,
+    /// for example, scene coordinates. Line widths and text sizes are always one framebuffer pixel
+    /// and the debug font size respectively.
+    #[inline]
+    pub fn set_transform(&mut self, new_transform: &Transform2F) {
+        self.transform = *new_transform;
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.labels.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.labels.clear();
+    }
+
+    pub fn line(&mut self, from: Vector2F, to: Vector2F, color: ColorU) {
+        let segment = LineSegment2F::new(self.transform * from, self.transform * to);
+        self.lines.push(DebugLine { segment, color });
+    }
+
+    /// Draws lines through `points` in order, and back to the first if `closed` is true.
+    pub fn polyline(&mut self, points: &[Vector2F], closed: bool, color: ColorU) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1], color);
+        }
+        if closed && points.len() > 2 {
+            self.line(points[points.len() - 1], points[0], color);
+        }
+    }
+
+    /// Marks `center` with an X whose arms are `size` framebuffer pixels across, whatever the
+    /// transform.
+    pub fn cross(&mut self, center: Vector2F, size: f32, color: ColorU) {
+        let center = self.transform * center;
+        let half_size = size * 0.5;
+        for &arm in &[vec2f(half_size, half_size), vec2f(half_size, -half_size)] {
+            let segment = LineSegment2F::new(center - arm, center + arm);
+            self.lines.push(DebugLine { segment, color });
+        }
+    }
+
+    pub fn rect(&mut self, rect: RectF, color: ColorU) {
+        let corners = [rect.origin(), rect.upper_right(), rect.lower_right(), rect.lower_left()];
+        self.polyline(&corners, true, color);
+    }
+
+    pub fn circle(&mut self, center: Vector2F, radius: f32, color: ColorU) {
+        let points: Vec<Vector2F> = (0..CIRCLE_SEGMENT_COUNT).map(|index| {
+            let angle = index as f32 * 2.0 * PI / CIRCLE_SEGMENT_COUNT as f32;
+            center + vec2f(angle.cos(), angle.sin()) * radius
+        }).collect();
+        self.polyline(&points, true, color);
+    }
+
+    /// Draws `text` with its baseline starting at `origin`.
+    pub fn label(&mut self, origin: Vector2F, text: &str, color: ColorU) {
+        let origin = self.transform * origin;
+        self.labels.push(DebugLabel { origin, text: text.to_owned(), color });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pathfinder_color::ColorU;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::vec2f;
+    use super::DebugDrawList;
+
+    #[test]
+    fn test_primitives_are_transformed_when_pushed() {
+        let mut list = DebugDrawList::new();
+        list.set_transform(&Transform2F::from_scale(2.0));
+        list.rect(RectF::new(vec2f(1.0, 1.0), vec2f(2.0, 3.0)), ColorU::white());
+        list.cross(vec2f(5.0, 5.0), 4.0, ColorU::black());
+        list.label(vec2f(1.0, 2.0), "hello", ColorU::white());
+        list.set_transform(&Transform2F::default());
+        list.circle(vec2f(0.0, 0.0), 10.0, ColorU::white());
+
+        assert_eq!(list.lines.len(), 4 + 2 + super::CIRCLE_SEGMENT_COUNT as usize);
+        assert_eq!(list.lines[1].segment.from(), vec2f(6.0, 2.0));
+        assert_eq!(list.lines[1].segment.to(), vec2f(6.0, 8.0));
+
+        // Crosses keep their size in framebuffer pixels.
+        assert_eq!(list.lines[4].segment.from(), vec2f(8.0, 8.0));
+        assert_eq!(list.lines[4].segment.to(), vec2f(12.0, 12.0));
+        assert_eq!(list.labels[0].origin, vec2f(2.0, 4.0));
+
+        list.clear();
+        assert!(list.is_empty());
+    }
+}
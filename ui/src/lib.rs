@@ -16,6 +16,10 @@
 #[macro_use]
 extern crate serde_derive;
 
+pub mod debug_draw;
+pub mod widgets;
+
+use crate::debug_draw::DebugDrawList;
 use hashbrown::HashMap;
 use pathfinder_color::ColorU;
 use pathfinder_geometry::rect::RectI;
@@ -23,6 +27,7 @@ use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2i};
 use pathfinder_gpu::{BlendFactor, BlendState, BufferData, BufferTarget, BufferUploadMode, Device};
 use pathfinder_gpu::{Primitive, RenderOptions, RenderState, RenderTarget, TextureFormat};
 use pathfinder_gpu::{UniformData, VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
+use pathfinder_gpu::error::RenderError;
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::F32x4;
 use serde_json;
@@ -67,6 +72,8 @@ static OUTLINE_RECT_LINE_INDICES: [u32; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
 pub struct UIPresenter<D> where D: Device {
     pub event_queue: UIEventQueue,
     pub mouse_position: Vector2F,
+    /// Debug primitives to draw over the frame at the next `flush_debug_draw_list()`.
+    pub debug_draw_list: DebugDrawList,
 
     framebuffer_size: Vector2I,
 
@@ -83,27 +90,28 @@ pub struct UIPresenter<D> where D: Device {
 
 impl<D> UIPresenter<D> where D: Device {
     pub fn new(device: &D, resources: &dyn ResourceLoader, framebuffer_size: Vector2I)
-               -> UIPresenter<D> {
-        let texture_program = DebugTextureProgram::new(device, resources);
+               -> Result<UIPresenter<D>, RenderError> {
+        let texture_program = DebugTextureProgram::new(device, resources)?;
         let texture_vertex_array = DebugTextureVertexArray::new(device, &texture_program);
-        let font = DebugFont::load(resources);
+        let font = DebugFont::load(resources)?;
 
-        let solid_program = DebugSolidProgram::new(device, resources);
+        let solid_program = DebugSolidProgram::new(device, resources)?;
         let solid_vertex_array = DebugSolidVertexArray::new(device, &solid_program);
 
         let font_texture = device.create_texture_from_png(resources,
                                                           FONT_PNG_NAME,
-                                                          TextureFormat::R8);
+                                                          TextureFormat::R8)?;
         let corner_fill_texture = device.create_texture_from_png(resources,
                                                                  CORNER_FILL_PNG_NAME,
-                                                                 TextureFormat::R8);
+                                                                 TextureFormat::R8)?;
         let corner_outline_texture = device.create_texture_from_png(resources,
                                                                     CORNER_OUTLINE_PNG_NAME,
-                                                                    TextureFormat::R8);
+                                                                    TextureFormat::R8)?;
 
-        UIPresenter {
+        Ok(UIPresenter {
             event_queue: UIEventQueue::new(),
             mouse_position: Vector2F::zero(),
+            debug_draw_list: DebugDrawList::new(),
 
             framebuffer_size,
 
@@ -116,7 +124,7 @@ impl<D> UIPresenter<D> where D: Device {
             font_texture,
             corner_fill_texture,
             corner_outline_texture,
-        }
+        })
     }
 
     pub fn framebuffer_size(&self) -> Vector2I {
@@ -189,6 +197,7 @@ impl<D> UIPresenter<D> where D: Device {
             ],
             textures: &[],
             images: &[],
+            uniform_buffers: &[],
             viewport: RectI::new(Vector2I::default(), self.framebuffer_size),
             options: RenderOptions {
                 blend: Some(alpha_blend_state()),
@@ -198,10 +207,25 @@ impl<D> UIPresenter<D> where D: Device {
     }
 
     pub fn draw_text(&self, device: &D, string: &str, origin: Vector2I, invert: bool) {
-        let mut next = origin;
         let char_count = string.chars().count();
         let mut vertex_data = Vec::with_capacity(char_count * 4);
         let mut index_data = Vec::with_capacity(char_count * 6);
+        self.push_text_vertices(string, origin, &mut vertex_data, &mut index_data);
+
+        let color = if invert { INVERTED_TEXT_COLOR } else { TEXT_COLOR };
+        self.draw_texture_with_vertex_data(device,
+                                           &vertex_data,
+                                           &index_data,
+                                           &self.font_texture,
+                                           color);
+    }
+
+    fn push_text_vertices(&self,
+                          string: &str,
+                          origin: Vector2I,
+                          vertex_data: &mut Vec<DebugTextureVertex>,
+                          index_data: &mut Vec<u32>) {
+        let mut next = origin;
         for mut character in string.chars() {
             if !self.font.characters.contains_key(&character) {
                 character = '?';
@@ -224,13 +248,52 @@ impl<D> UIPresenter<D> where D: Device {
             let next_x = next.x() + info.advance;
             next.set_x(next_x);
         }
+    }
 
-        let color = if invert { INVERTED_TEXT_COLOR } else { TEXT_COLOR };
-        self.draw_texture_with_vertex_data(device,
-                                           &vertex_data,
-                                           &index_data,
-                                           &self.font_texture,
-                                           color);
+    /// Draws the primitives in `debug_draw_list` over everything drawn so far, then clears it.
+    ///
+    /// Lines and labels are batched by color, so this takes a few draw calls however many
+    /// primitives there are.
+    pub fn flush_debug_draw_list(&mut self, device: &D) {
+        let mut list = mem::take(&mut self.debug_draw_list);
+        self.draw_debug_draw_list(device, &list);
+        list.clear();
+        self.debug_draw_list = list;
+    }
+
+    fn draw_debug_draw_list(&self, device: &D, list: &DebugDrawList) {
+        let mut lines: Vec<_> = list.lines.iter().collect();
+        lines.sort_by_key(|line| line.color);
+        for batch in batches_by_color(&lines, |line| line.color) {
+            let mut vertex_data = Vec::with_capacity(batch.len() * 2);
+            for line in batch {
+                vertex_data.push(DebugSolidVertex::new(line.segment.from().to_i32()));
+                vertex_data.push(DebugSolidVertex::new(line.segment.to().to_i32()));
+            }
+            let index_data: Vec<u32> = (0..vertex_data.len() as u32).collect();
+            self.draw_solid_rects_with_vertex_data(device,
+                                                   &vertex_data,
+                                                   &index_data,
+                                                   batch[0].color,
+                                                   false);
+        }
+
+        let mut labels: Vec<_> = list.labels.iter().collect();
+        labels.sort_by_key(|label| label.color);
+        for batch in batches_by_color(&labels, |label| label.color) {
+            let (mut vertex_data, mut index_data) = (vec![], vec![]);
+            for label in batch {
+                self.push_text_vertices(&label.text,
+                                        label.origin.to_i32(),
+                                        &mut vertex_data,
+                                        &mut index_data);
+            }
+            self.draw_texture_with_vertex_data(device,
+                                               &vertex_data,
+                                               &index_data,
+                                               &self.font_texture,
+                                               batch[0].color);
+        }
     }
 
     pub fn draw_texture(&self,
@@ -421,6 +484,7 @@ impl<D> UIPresenter<D> where D: Device {
                 (&self.texture_program.texture_size_uniform,
                  UniformData::Vec2(device.texture_size(&texture).0.to_f32x2()))
             ],
+            uniform_buffers: &[],
             viewport: RectI::new(Vector2I::default(), self.framebuffer_size),
             options: RenderOptions {
                 blend: Some(alpha_blend_state()),
@@ -570,19 +634,20 @@ struct DebugTextureProgram<D> where D: Device {
 }
 
 impl<D> DebugTextureProgram<D> where D: Device {
-    fn new(device: &D, resources: &dyn ResourceLoader) -> DebugTextureProgram<D> {
-        let program = device.create_raster_program(resources, "debug_texture");
+    fn new(device: &D, resources: &dyn ResourceLoader)
+           -> Result<DebugTextureProgram<D>, RenderError> {
+        let program = device.create_raster_program(resources, "debug_texture")?;
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let texture_size_uniform = device.get_uniform(&program, "TextureSize");
         let color_uniform = device.get_uniform(&program, "Color");
         let texture = device.get_texture_parameter(&program, "Texture");
-        DebugTextureProgram {
+        Ok(DebugTextureProgram {
             program,
             framebuffer_size_uniform,
             texture_size_uniform,
             color_uniform,
             texture,
-        }
+        })
     }
 }
 
@@ -666,11 +731,12 @@ struct DebugSolidProgram<D> where D: Device {
 }
 
 impl<D> DebugSolidProgram<D> where D: Device {
-    fn new(device: &D, resources: &dyn ResourceLoader) -> DebugSolidProgram<D> {
-        let program = device.create_raster_program(resources, "debug_solid");
+    fn new(device: &D, resources: &dyn ResourceLoader)
+           -> Result<DebugSolidProgram<D>, RenderError> {
+        let program = device.create_raster_program(resources, "debug_solid")?;
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let color_uniform = device.get_uniform(&program, "Color");
-        DebugSolidProgram { program, framebuffer_size_uniform, color_uniform }
+        Ok(DebugSolidProgram { program, framebuffer_size_uniform, color_uniform })
     }
 }
 
@@ -728,6 +794,19 @@ impl CornerRects {
     }
 }
 
+// Splits `items`, sorted by color, into runs of the same color.
+fn batches_by_color<T, F>(items: &[T], get_color: F) -> impl Iterator<Item = &[T]>
+                          where F: Fn(&T) -> ColorU {
+    let mut rest = items;
+    std::iter::from_fn(move || {
+        let color = get_color(rest.first()?);
+        let batch_len = rest.iter().take_while(|&item| get_color(item) == color).count();
+        let (batch, next_rest) = rest.split_at(batch_len);
+        rest = next_rest;
+        Some(batch)
+    })
+}
+
 fn get_color_uniform(color: ColorU) -> UniformData {
     let color = F32x4::new(color.r as f32, color.g as f32, color.b as f32, color.a as f32);
     UniformData::Vec4(color * F32x4::splat(1.0 / 255.0))
@@ -819,8 +898,13 @@ struct DebugCharacter {
 
 impl DebugFont {
     #[inline]
-    fn load(resources: &dyn ResourceLoader) -> DebugFont {
-        serde_json::from_slice(&resources.slurp(FONT_JSON_VIRTUAL_PATH).unwrap()).unwrap()
+    fn load(resources: &dyn ResourceLoader) -> Result<DebugFont, RenderError> {
+        let missing_resource = |reason: String| {
+            RenderError::MissingResource { path: FONT_JSON_VIRTUAL_PATH.to_owned(), reason }
+        };
+        let json = resources.slurp(FONT_JSON_VIRTUAL_PATH)
+                            .map_err(|error| missing_resource(error.to_string()))?;
+        serde_json::from_slice(&json).map_err(|error| missing_resource(error.to_string()))
     }
 }
 
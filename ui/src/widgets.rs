@@ -0,0 +1,183 @@
+// pathfinder/ui/src/widgets.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Retained, vector-drawn UI primitives: panels, progress bars, and toggles.
+//!
+//! Unlike the debug overlay in `UIPresenter`, these draw through a `WidgetPainter`, which
+//! `pathfinder_canvas` implements for `CanvasRenderingContext2D`, so they're antialiased, can be
+//! any size, and cast real blurred shadows. Widgets hold their own state and are drawn anew each
+//! frame.
+
+use crate::UIEventQueue;
+use pathfinder_color::ColorU;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::{Vector2F, vec2f};
+
+/// Something widgets can be drawn to.
+pub trait WidgetPainter {
+    fn fill_rounded_rect(&mut self,
+                         rect: RectF,
+                         corner_radius: f32,
+                         color: ColorU,
+                         shadow: Option<Shadow>);
+    fn fill_circle(&mut self,
+                   center: Vector2F,
+                   radius: f32,
+                   color: ColorU,
+                   shadow: Option<Shadow>);
+}
+
+/// A drop shadow, as in the CSS `box-shadow` property.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shadow {
+    pub offset: Vector2F,
+    pub blur: f32,
+    pub color: ColorU,
+}
+
+impl Shadow {
+    /// The shadow cast by a surface raised `elevation` units above the one beneath it, in the
+    /// style of Material Design. Returns `None` for an elevation of zero or less.
+    pub fn from_elevation(elevation: f32) -> Option<Shadow> {
+        if elevation <= 0.0 {
+            return None;
+        }
+        let alpha = (48.0 + elevation * 4.0).min(96.0) as u8;
+        Some(Shadow {
+            offset: vec2f(0.0, elevation * 0.5),
+            blur: elevation * 2.0,
+            color: ColorU::new(0, 0, 0, alpha),
+        })
+    }
+}
+
+/// Colors shared by all widgets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub surface: ColorU,
+    pub track: ColorU,
+    pub accent: ColorU,
+    pub knob: ColorU,
+}
+
+impl Default for Theme {
+    #[inline]
+    fn default() -> Theme {
+        Theme {
+            surface: ColorU::new(250, 250, 250, 255),
+            track: ColorU::new(189, 189, 189, 255),
+            accent: ColorU::new(33, 150, 243, 255),
+            knob: ColorU::white(),
+        }
+    }
+}
+
+/// A rectangular surface with rounded corners, optionally raised with a shadow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Panel {
+    pub rect: RectF,
+    pub corner_radius: f32,
+    pub elevation: f32,
+    pub color: ColorU,
+}
+
+impl Panel {
+    #[inline]
+    pub fn new(rect: RectF, theme: &Theme) -> Panel {
+        Panel { rect, corner_radius: 8.0, elevation: 2.0, color: theme.surface }
+    }
+
+    pub fn draw<P>(&self, painter: &mut P) where P: WidgetPainter {
+        let shadow = Shadow::from_elevation(self.elevation);
+        painter.fill_rounded_rect(self.rect, self.corner_radius, self.color, shadow);
+    }
+}
+
+/// A horizontal bar that fills from left to right.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProgressBar {
+    pub rect: RectF,
+    pub track_color: ColorU,
+    pub fill_color: ColorU,
+    progress: f32,
+}
+
+impl ProgressBar {
+    #[inline]
+    pub fn new(rect: RectF, theme: &Theme) -> ProgressBar {
+        ProgressBar { rect, track_color: theme.track, fill_color: theme.accent, progress: 0.0 }
+    }
+
+    /// The fraction of the bar that is filled, from 0.0 to 1.0.
+    #[inline]
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// Sets the filled fraction. Values outside 0.0 to 1.0 are clamped.
+    #[inline]
+    pub fn set_progress(&mut self, new_progress: f32) {
+        self.progress = new_progress.clamp(0.0, 1.0);
+    }
+
+    pub fn draw<P>(&self, painter: &mut P) where P: WidgetPainter {
+        let corner_radius = self.rect.height() * 0.5;
+        painter.fill_rounded_rect(self.rect, corner_radius, self.track_color, None);
+        if self.progress > 0.0 {
+            // Never draw the fill narrower than the bar is tall, so the rounded ends stay round.
+            let width = f32::max(self.rect.width() * self.progress, self.rect.height());
+            let fill_rect = RectF::new(self.rect.origin(), vec2f(width, self.rect.height()));
+            painter.fill_rounded_rect(fill_rect, corner_radius, self.fill_color, None);
+        }
+    }
+}
+
+/// An on/off switch: a rounded track with a sliding knob.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Toggle {
+    pub rect: RectF,
+    pub on: bool,
+    pub theme: Theme,
+}
+
+impl Toggle {
+    #[inline]
+    pub fn new(rect: RectF, on: bool, theme: &Theme) -> Toggle {
+        Toggle { rect, on, theme: *theme }
+    }
+
+    /// Flips the toggle if the queue contains a click inside it. Returns true if the state
+    /// changed.
+    pub fn handle_events(&mut self, event_queue: &mut UIEventQueue) -> bool {
+        let rect = self.rect.round_out().to_i32();
+        if event_queue.handle_mouse_down_in_rect(rect).is_none() {
+            return false;
+        }
+        self.on = !self.on;
+        true
+    }
+
+    pub fn draw<P>(&self, painter: &mut P) where P: WidgetPainter {
+        let track_color = if self.on { self.theme.accent } else { self.theme.track };
+        let radius = self.rect.height() * 0.5;
+        painter.fill_rounded_rect(self.rect, radius, track_color, None);
+
+        let knob_x = if self.on {
+            self.rect.max_x() - radius
+        } else {
+            self.rect.min_x() + radius
+        };
+        let knob_center = vec2f(knob_x, self.rect.min_y() + radius);
+        painter.fill_circle(knob_center,
+                            radius - 2.0,
+                            self.theme.knob,
+                            Shadow::from_elevation(1.0));
+    }
+}
@@ -0,0 +1,257 @@
+// pathfinder/utils/rasterize/src/main.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Renders SVG files to PNG headlessly.
+
+use crate::options::{DEFAULT_DPI, OutputFormat, Sizing};
+use crate::render::Rasterizer;
+use clap::{App, Arg, ArgMatches};
+use image::{DynamicImage, RgbaImage};
+use pathfinder_color::ColorU;
+use pathfinder_geometry::vector::vec2f;
+use pathfinder_svg::BuiltSVG;
+use pathfinder_testing::Backend;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use usvg::{NodeKind, Options as UsvgOptions, Tree};
+
+mod options;
+mod render;
+
+struct Job {
+    input: PathBuf,
+    output: PathBuf,
+}
+
+fn main() {
+    let matches = App::new("pathfinder-rasterize")
+        .arg(
+            Arg::with_name("backend")
+                .short("b")
+                .long("backend")
+                .takes_value(true)
+                .possible_values(&["software", "hardware"])
+                .default_value("hardware")
+                .help("The OpenGL implementation to render with"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("The output directory, or the output file if there's a single input"),
+        )
+        .arg(
+            Arg::with_name("scale")
+                .short("s")
+                .long("scale")
+                .value_name("FACTOR")
+                .takes_value(true)
+                .default_value("1")
+                .help("Scale the SVG's own size by this factor"),
+        )
+        .arg(
+            Arg::with_name("dpi")
+                .short("d")
+                .long("dpi")
+                .takes_value(true)
+                .default_value("96")
+                .help("Output pixel density; 96 DPI renders one SVG pixel per output pixel"),
+        )
+        .arg(
+            Arg::with_name("width")
+                .long("width")
+                .value_name("PIXELS")
+                .takes_value(true)
+                .conflicts_with_all(&["height", "size"])
+                .help("Scale to this width, preserving the aspect ratio"),
+        )
+        .arg(
+            Arg::with_name("height")
+                .long("height")
+                .value_name("PIXELS")
+                .takes_value(true)
+                .conflicts_with("size")
+                .help("Scale to this height, preserving the aspect ratio"),
+        )
+        .arg(
+            Arg::with_name("size")
+                .long("size")
+                .value_name("WIDTHxHEIGHT")
+                .takes_value(true)
+                .help("Fit the SVG into an image of exactly this size"),
+        )
+        .arg(
+            Arg::with_name("background")
+                .long("background")
+                .value_name("COLOR")
+                .takes_value(true)
+                .help("`transparent`, `white`, `black`, or a hex color such as `#336699`"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["rgba", "rgb", "gray"])
+                .default_value("rgba")
+                .help("The PNG pixel format"),
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .required(true)
+                .multiple(true)
+                .help("SVG files or glob patterns, such as `icons/*.svg`"),
+        )
+        .get_matches();
+
+    let backend = Backend::from_name(matches.value_of("backend").unwrap()).unwrap();
+    let format = OutputFormat::from_name(matches.value_of("format").unwrap()).unwrap();
+    let sizing = parse_sizing(&matches).unwrap_or_else(|message| exit_with_error(&message));
+
+    // Formats without alpha get a white background unless told otherwise.
+    let background = match matches.value_of("background") {
+        Some(color) => {
+            options::parse_color(color).unwrap_or_else(|| exit_with_error("Invalid color"))
+        }
+        None if format.has_alpha() => ColorU::transparent_black(),
+        None => ColorU::white(),
+    };
+
+    let inputs = expand_inputs(matches.values_of("INPUT").unwrap());
+    if inputs.is_empty() {
+        exit_with_error("No SVG files matched");
+    }
+    let jobs = plan_jobs(inputs, matches.value_of("output").map(Path::new));
+    for job in &jobs {
+        if let Some(output_dir) = job.output.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            if let Err(error) = fs::create_dir_all(output_dir) {
+                exit_with_error(&format!("{}: {}", output_dir.display(), error));
+            }
+        }
+    }
+
+    let mut renderer = Rasterizer::new(backend);
+    let mut failure_count = 0;
+    for job in &jobs {
+        match rasterize(&mut renderer, job, sizing, background, format) {
+            Ok(()) => println!("{} -> {}", job.input.display(), job.output.display()),
+            Err(error) => {
+                eprintln!("{}: {}", job.input.display(), error);
+                failure_count += 1;
+            }
+        }
+    }
+
+    if failure_count > 0 {
+        eprintln!("{} of {} files failed", failure_count, jobs.len());
+        process::exit(1);
+    }
+}
+
+fn rasterize(renderer: &mut Rasterizer,
+             job: &Job,
+             sizing: Sizing,
+             background: ColorU,
+             format: OutputFormat)
+             -> Result<(), Box<dyn Error>> {
+    let data = fs::read(&job.input)?;
+    let tree = Tree::from_data(&data, &UsvgOptions::default())?;
+    let svg_size = match *tree.root().borrow() {
+        NodeKind::Svg(ref svg) => vec2f(svg.size.width() as f32, svg.size.height() as f32),
+        _ => unreachable!(),
+    };
+    let scene = BuiltSVG::from_tree(&tree).scene;
+
+    let (size, transform) = options::fit(scene.view_box(), svg_size, sizing);
+    let image = renderer.render(scene, size, transform, background);
+    save(image, format, &job.output)
+}
+
+fn save(image: RgbaImage, format: OutputFormat, path: &Path) -> Result<(), Box<dyn Error>> {
+    let image = DynamicImage::ImageRgba8(image);
+    match format {
+        OutputFormat::Rgba => image.save(path)?,
+        OutputFormat::Rgb => image.to_rgb().save(path)?,
+        OutputFormat::Gray => image.to_luma().save(path)?,
+    }
+    Ok(())
+}
+
+fn parse_sizing(matches: &ArgMatches) -> Result<Sizing, String> {
+    if let Some(size) = matches.value_of("size") {
+        return options::parse_size(size).map(Sizing::Exact)
+                                        .ok_or_else(|| "Sizes must be WIDTHxHEIGHT".to_owned());
+    }
+    if let Some(width) = matches.value_of("width") {
+        return parse_positive(width).map(|width| Sizing::Width(width.ceil() as i32));
+    }
+    if let Some(height) = matches.value_of("height") {
+        return parse_positive(height).map(|height| Sizing::Height(height.ceil() as i32));
+    }
+    let scale = parse_positive(matches.value_of("scale").unwrap())?;
+    let dpi = parse_positive(matches.value_of("dpi").unwrap())?;
+    Ok(Sizing::Scale(scale * dpi / DEFAULT_DPI))
+}
+
+fn parse_positive(string: &str) -> Result<f32, String> {
+    match string.parse::<f32>() {
+        Ok(value) if value > 0.0 => Ok(value),
+        _ => Err(format!("Expected a positive number, found `{}`", string)),
+    }
+}
+
+// Shells on Windows don't expand globs, so do it here too. Arguments that aren't valid patterns
+// or match nothing are passed through, so that the error names the missing file.
+fn expand_inputs<'a, I>(arguments: I) -> Vec<PathBuf> where I: Iterator<Item = &'a str> {
+    let mut inputs = vec![];
+    for argument in arguments {
+        let mut matched = false;
+        if let Ok(paths) = glob::glob(argument) {
+            for path in paths.filter_map(Result::ok) {
+                inputs.push(path);
+                matched = true;
+            }
+        }
+        if !matched {
+            inputs.push(PathBuf::from(argument));
+        }
+    }
+    inputs
+}
+
+fn plan_jobs(inputs: Vec<PathBuf>, output: Option<&Path>) -> Vec<Job> {
+    let single_output_file = match output {
+        Some(output) if inputs.len() == 1 && !output.is_dir() => {
+            output.extension().map_or(false, |extension| extension == "png")
+        }
+        _ => false,
+    };
+
+    inputs.into_iter().map(|input| {
+        let output = match output {
+            Some(output) if single_output_file => output.to_owned(),
+            Some(output_dir) => {
+                let file_name = input.with_extension("png");
+                output_dir.join(file_name.file_name().unwrap())
+            }
+            None => input.with_extension("png"),
+        };
+        Job { input, output }
+    }).collect()
+}
+
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1);
+}
@@ -0,0 +1,171 @@
+// pathfinder/utils/rasterize/src/options.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parsing of command-line values, and working out where the SVG lands in the output image.
+
+use pathfinder_color::ColorU;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2f, vec2i};
+
+/// The CSS reference pixel density: at this DPI, one SVG user unit is one output pixel.
+pub const DEFAULT_DPI: f32 = 96.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Rgba,
+    Rgb,
+    Gray,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "rgba" => Some(OutputFormat::Rgba),
+            "rgb" => Some(OutputFormat::Rgb),
+            "gray" => Some(OutputFormat::Gray),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn has_alpha(self) -> bool {
+        self == OutputFormat::Rgba
+    }
+}
+
+/// How big the output image should be.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sizing {
+    /// Multiply the SVG's own size by this factor.
+    Scale(f32),
+    /// This many pixels wide, preserving the aspect ratio.
+    Width(i32),
+    /// This many pixels tall, preserving the aspect ratio.
+    Height(i32),
+    /// Exactly this size. The SVG is centered and scaled to fit, as with
+    /// `preserveAspectRatio="xMidYMid meet"`.
+    Exact(Vector2I),
+}
+
+/// Parses `transparent`, `white`, `black`, `#rgb`, `#rrggbb`, or `#rrggbbaa`.
+pub fn parse_color(string: &str) -> Option<ColorU> {
+    match string {
+        "transparent" => return Some(ColorU::transparent_black()),
+        "white" => return Some(ColorU::white()),
+        "black" => return Some(ColorU::black()),
+        _ => {}
+    }
+
+    let hex = string.trim_start_matches('#');
+    if hex.len() == string.len() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits: Vec<u8> = hex.chars().map(|c| c.to_digit(16).unwrap() as u8).collect();
+    match digits.len() {
+        3 => Some(ColorU::new(digits[0] * 17, digits[1] * 17, digits[2] * 17, 255)),
+        6 | 8 => {
+            let mut channels = digits.chunks(2).map(|pair| pair[0] * 16 + pair[1]);
+            let (r, g, b) = (channels.next()?, channels.next()?, channels.next()?);
+            Some(ColorU::new(r, g, b, channels.next().unwrap_or(255)))
+        }
+        _ => None,
+    }
+}
+
+/// Parses `WIDTHxHEIGHT`.
+pub fn parse_size(string: &str) -> Option<Vector2I> {
+    let mut dimensions = string.split('x').map(|dimension| dimension.parse::<i32>());
+    match (dimensions.next(), dimensions.next(), dimensions.next()) {
+        (Some(Ok(width)), Some(Ok(height)), None) if width > 0 && height > 0 => {
+            Some(vec2i(width, height))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the size of the output image and the transform from SVG user space into it.
+///
+/// `svg_size` is the size given by the root element's `width` and `height`, to which the
+/// `view_box` is fitted.
+pub fn fit(view_box: RectF, svg_size: Vector2F, sizing: Sizing) -> (Vector2I, Transform2F) {
+    let to_svg_size = fit_rect(view_box, svg_size);
+    let (output_size, scale) = match sizing {
+        Sizing::Scale(scale) => ((svg_size * scale).ceil().to_i32(), scale),
+        Sizing::Width(width) => {
+            let scale = width as f32 / svg_size.x();
+            (vec2i(width, (svg_size.y() * scale).ceil() as i32), scale)
+        }
+        Sizing::Height(height) => {
+            let scale = height as f32 / svg_size.y();
+            (vec2i((svg_size.x() * scale).ceil() as i32, height), scale)
+        }
+        Sizing::Exact(size) => {
+            let svg_rect = RectF::new(Vector2F::zero(), svg_size);
+            return (size, fit_rect(svg_rect, size.to_f32()) * to_svg_size);
+        }
+    };
+    (output_size.max(vec2i(1, 1)), to_svg_size.scale(scale))
+}
+
+// Scales `rect` uniformly to fit within `size` and centers it.
+fn fit_rect(rect: RectF, size: Vector2F) -> Transform2F {
+    if rect.width() <= 0.0 || rect.height() <= 0.0 {
+        return Transform2F::default();
+    }
+    let scale = f32::min(size.x() / rect.width(), size.y() / rect.height());
+    let offset = (size - rect.size() * scale) * 0.5;
+    Transform2F::from_translation(-rect.origin()).scale(vec2f(scale, scale)).translate(offset)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Sizing, fit, parse_color, parse_size};
+    use pathfinder_color::ColorU;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::{vec2f, vec2i};
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("transparent"), Some(ColorU::transparent_black()));
+        assert_eq!(parse_color("#fff"), Some(ColorU::white()));
+        assert_eq!(parse_color("#102030"), Some(ColorU::new(0x10, 0x20, 0x30, 255)));
+        assert_eq!(parse_color("#10203040"), Some(ColorU::new(0x10, 0x20, 0x30, 0x40)));
+        assert_eq!(parse_color("102030"), None);
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("#ggg"), None);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("640x480"), Some(vec2i(640, 480)));
+        assert_eq!(parse_size("640"), None);
+        assert_eq!(parse_size("0x480"), None);
+    }
+
+    #[test]
+    fn test_fit() {
+        let view_box = RectF::new(vec2f(10.0, 10.0), vec2f(100.0, 50.0));
+        let svg_size = vec2f(200.0, 100.0);
+
+        let (size, transform) = fit(view_box, svg_size, Sizing::Scale(2.0));
+        assert_eq!(size, vec2i(400, 200));
+        assert_eq!(transform * vec2f(110.0, 60.0), vec2f(400.0, 200.0));
+
+        let (size, _) = fit(view_box, svg_size, Sizing::Width(100));
+        assert_eq!(size, vec2i(100, 50));
+
+        // A wide SVG in a square image is letterboxed vertically.
+        let (size, transform) = fit(view_box, svg_size, Sizing::Exact(vec2i(100, 100)));
+        assert_eq!(size, vec2i(100, 100));
+        assert_eq!(transform * vec2f(10.0, 10.0), vec2f(0.0, 25.0));
+        assert_eq!(transform * vec2f(110.0, 60.0), vec2f(100.0, 75.0));
+    }
+}
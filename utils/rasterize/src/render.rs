@@ -0,0 +1,73 @@
+// pathfinder/utils/rasterize/src/render.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Headless rendering into an offscreen surface.
+
+use image::RgbaImage;
+use pathfinder_color::{self as color, ColorU};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2i};
+use pathfinder_renderer::concurrent::rayon::RayonExecutor;
+use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
+use pathfinder_renderer::gpu::options::{ClearMode, RendererOptions};
+use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use pathfinder_renderer::scene::Scene;
+use pathfinder_testing::{Backend, OffscreenRenderer};
+
+/// Renders scenes into an offscreen surface, resized as needed, and reads them back.
+pub struct Rasterizer {
+    offscreen: OffscreenRenderer,
+}
+
+impl Rasterizer {
+    pub fn new(backend: Backend) -> Rasterizer {
+        let offscreen = OffscreenRenderer::new(backend, vec2i(1, 1), RendererOptions::default())
+            .unwrap_or_else(|message| panic!("Failed to create the renderer: {}", message));
+        Rasterizer { offscreen }
+    }
+
+    /// Renders `scene` through `transform` into an image of `size` pixels.
+    ///
+    /// The pixels are unpremultiplied, as PNG expects.
+    pub fn render(&mut self,
+                  mut scene: Scene,
+                  size: Vector2I,
+                  transform: Transform2F,
+                  background: ColorU)
+                  -> RgbaImage {
+        if size != self.offscreen.size() {
+            self.offscreen.resize(size);
+        }
+
+        let clear_mode = if background.a == 0 {
+            ClearMode::Transparent
+        } else {
+            ClearMode::Color(background.to_f32())
+        };
+        self.offscreen.renderer.set_clear_mode(clear_mode);
+        self.offscreen.renderer.set_options(RendererOptions {
+            dithering: false,
+            ..RendererOptions::default()
+        });
+
+        scene.set_view_box(RectF::new(Vector2F::zero(), size.to_f32()));
+        let scene = SceneProxy::from_scene(scene, RayonExecutor);
+        let build_options = BuildOptions {
+            transform: RenderTransform::Transform2D(transform),
+            ..BuildOptions::default()
+        };
+        scene.build_and_render(&mut self.offscreen.renderer, build_options).unwrap();
+
+        let mut pixels = self.offscreen.read_pixels(size).pixels;
+        color::unpremultiply_u8_slice(&mut pixels);
+        RgbaImage::from_raw(size.x() as u32, size.y() as u32, pixels).unwrap()
+    }
+}
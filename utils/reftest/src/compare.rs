@@ -0,0 +1,161 @@
+// pathfinder/utils/reftest/src/compare.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Perceptual image comparison.
+//!
+//! Pixels are compared in YIQ space after compositing onto white, as `pixelmatch` does, so that
+//! differences in hue and in nearly-transparent pixels count for less than differences in
+//! brightness.
+
+use image::RgbaImage;
+use pathfinder_testing::Backend;
+
+/// The largest possible value of `color_delta()`, used for normalization.
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+/// How different an image may be from its reference and still pass.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    /// The perceptual difference, from 0.0 to 1.0, above which a pixel counts as different.
+    pub threshold: f32,
+    /// The number of pixels that may differ before the test fails.
+    pub max_differing_pixels: u32,
+}
+
+impl Tolerance {
+    /// How far images from `backend` may stray from the references.
+    ///
+    /// The references are rendered on hardware. Software rasterizers round differently in the
+    /// blend and gradient shaders, so they get a looser threshold.
+    pub fn for_backend(backend: Backend) -> Tolerance {
+        match backend {
+            Backend::Hardware => Tolerance { threshold: 0.005, max_differing_pixels: 16 },
+            Backend::Software => Tolerance { threshold: 0.02, max_differing_pixels: 64 },
+        }
+    }
+
+    #[inline]
+    pub fn allow_pixels(self, extra_pixels: u32) -> Tolerance {
+        Tolerance { max_differing_pixels: self.max_differing_pixels + extra_pixels, ..self }
+    }
+}
+
+/// The result of comparing an image against its reference.
+pub struct Comparison {
+    pub differing_pixels: u32,
+    /// The largest perceptual difference of any pixel, from 0.0 to 1.0.
+    pub max_delta: f32,
+    /// The reference, faded, with differing pixels drawn in red.
+    pub diff_image: RgbaImage,
+}
+
+impl Comparison {
+    #[inline]
+    pub fn passes(&self, tolerance: Tolerance) -> bool {
+        self.differing_pixels <= tolerance.max_differing_pixels
+    }
+}
+
+/// Compares `actual` against `expected`.
+///
+/// Returns `None` if the images aren't the same size.
+pub fn compare(actual: &RgbaImage, expected: &RgbaImage, threshold: f32) -> Option<Comparison> {
+    if actual.dimensions() != expected.dimensions() {
+        return None;
+    }
+
+    let mut diff_image = RgbaImage::new(expected.width(), expected.height());
+    let (mut differing_pixels, mut max_delta) = (0, 0.0f32);
+    for ((actual_pixel, expected_pixel), diff_pixel) in actual.pixels()
+                                                              .zip(expected.pixels())
+                                                              .zip(diff_image.pixels_mut()) {
+        let delta = color_delta(actual_pixel.0, expected_pixel.0);
+        max_delta = max_delta.max(delta);
+        diff_pixel.0 = if delta > threshold {
+            differing_pixels += 1;
+            [255, 0, 0, 255]
+        } else {
+            let gray = 255 - ((255 - luma(expected_pixel.0)) as u32 * 64 / 255) as u8;
+            [gray, gray, gray, 255]
+        };
+    }
+
+    Some(Comparison { differing_pixels, max_delta, diff_image })
+}
+
+/// The perceptual difference between two colors, from 0.0 (identical) to 1.0.
+pub fn color_delta(a: [u8; 4], b: [u8; 4]) -> f32 {
+    if a == b {
+        return 0.0;
+    }
+    let (a, b) = (blend_with_white(a), blend_with_white(b));
+    let (ay, ai, aq) = rgb_to_yiq(a);
+    let (by, bi, bq) = rgb_to_yiq(b);
+    let (dy, di, dq) = (ay - by, ai - bi, aq - bq);
+    (0.5053 * dy * dy + 0.299 * di * di + 0.1957 * dq * dq) / MAX_YIQ_DELTA
+}
+
+fn blend_with_white(color: [u8; 4]) -> [f32; 3] {
+    let alpha = color[3] as f32 / 255.0;
+    let blend = |channel: u8| 255.0 + (channel as f32 - 255.0) * alpha;
+    [blend(color[0]), blend(color[1]), blend(color[2])]
+}
+
+fn rgb_to_yiq(rgb: [f32; 3]) -> (f32, f32, f32) {
+    let [r, g, b] = rgb;
+    (r * 0.298_895_3 + g * 0.586_622_5 + b * 0.114_482_23,
+     r * 0.595_977_96 - g * 0.274_176_1 - b * 0.321_801_9,
+     r * 0.211_470_17 - g * 0.522_617_1 + b * 0.311_146_94)
+}
+
+fn luma(color: [u8; 4]) -> u8 {
+    let [r, g, b] = blend_with_white(color);
+    rgb_to_yiq([r, g, b]).0.round().clamp(0.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Tolerance, color_delta, compare};
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn test_color_delta_range() {
+        assert_eq!(color_delta([12, 34, 56, 78], [12, 34, 56, 78]), 0.0);
+        let black_white = color_delta([0, 0, 0, 255], [255, 255, 255, 255]);
+        assert!(black_white > 0.9 && black_white <= 1.0);
+    }
+
+    #[test]
+    fn test_color_delta_ignores_color_of_transparent_pixels() {
+        assert_eq!(color_delta([255, 0, 0, 0], [0, 0, 255, 0]), 0.0);
+    }
+
+    #[test]
+    fn test_compare_counts_differing_pixels() {
+        let expected = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(1, 2, Rgba([0, 0, 0, 255]));
+        actual.put_pixel(3, 3, Rgba([254, 254, 254, 255]));
+
+        let comparison = compare(&actual, &expected, 0.01).unwrap();
+        assert_eq!(comparison.differing_pixels, 1);
+        assert_eq!(comparison.diff_image.get_pixel(1, 2), &Rgba([255, 0, 0, 255]));
+        let tolerance = Tolerance { threshold: 0.01, max_differing_pixels: 0 };
+        assert!(!comparison.passes(tolerance));
+        assert!(comparison.passes(tolerance.allow_pixels(1)));
+    }
+
+    #[test]
+    fn test_compare_rejects_size_mismatch() {
+        let a = RgbaImage::new(4, 4);
+        let b = RgbaImage::new(4, 5);
+        assert!(compare(&a, &b, 0.0).is_none());
+    }
+}
@@ -0,0 +1,156 @@
+// pathfinder/utils/reftest/src/main.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Renders the reference test corpus and compares the results against stored images.
+//!
+//! Failing tests write `<name>-actual.png`, `<name>-expected.png`, and `<name>-diff.png` to the
+//! output directory so that CI can upload them as artifacts. Tests without a reference image yet
+//! write `<name>-actual.png` and are reported as new rather than failed, so that a scene can be
+//! added before its reference is blessed.
+
+use crate::compare::{Comparison, Tolerance};
+use crate::render::ReftestRenderer;
+use crate::scenes::REFTESTS;
+use clap::{App, Arg};
+use image::RgbaImage;
+use pathfinder_testing::Backend;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+mod compare;
+mod render;
+mod scenes;
+
+const DEFAULT_OUTPUT_DIR: &str = "target/reftest";
+
+enum Outcome {
+    Pass(Comparison),
+    Fail(Comparison),
+    SizeMismatch,
+    New,
+    Blessed,
+}
+
+fn main() {
+    let matches = App::new("reftest")
+        .arg(
+            Arg::with_name("backend")
+                .short("b")
+                .long("backend")
+                .takes_value(true)
+                .possible_values(&["software", "hardware"])
+                .default_value("hardware")
+                .help("The OpenGL implementation to render with"),
+        )
+        .arg(
+            Arg::with_name("bless")
+                .long("bless")
+                .help("Overwrite the reference images with the rendered results"),
+        )
+        .arg(
+            Arg::with_name("output-dir")
+                .short("o")
+                .long("output-dir")
+                .value_name("DIR")
+                .takes_value(true)
+                .help("Where to write images for failing tests"),
+        )
+        .arg(Arg::with_name("FILTER").help("Only run tests whose names contain this string"))
+        .get_matches();
+
+    let backend = Backend::from_name(matches.value_of("backend").unwrap()).unwrap();
+    let bless = matches.is_present("bless");
+    let output_dir = PathBuf::from(matches.value_of("output-dir").unwrap_or(DEFAULT_OUTPUT_DIR));
+    let filter = matches.value_of("FILTER").unwrap_or("");
+    let reference_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("references");
+
+    let mut renderer = ReftestRenderer::new(backend);
+    let (mut failure_count, mut new_count) = (0, 0);
+    for reftest in REFTESTS.iter().filter(|reftest| reftest.name.contains(filter)) {
+        let actual = renderer.render(reftest);
+        let reference_path = reference_dir.join(format!("{}.png", reftest.name));
+        let tolerance = Tolerance::for_backend(backend);
+        let tolerance = tolerance.allow_pixels(reftest.extra_differing_pixels);
+
+        let outcome = if bless {
+            fs::create_dir_all(&reference_dir).expect("Failed to create the reference directory!");
+            actual.save(&reference_path).expect("Failed to write the reference image!");
+            Outcome::Blessed
+        } else {
+            match image::open(&reference_path) {
+                Err(_) => {
+                    write_artifacts(&output_dir, reftest.name, &[("actual", &actual)]);
+                    Outcome::New
+                }
+                Ok(expected) => {
+                    let expected = expected.to_rgba();
+                    match compare::compare(&actual, &expected, tolerance.threshold) {
+                        None => Outcome::SizeMismatch,
+                        Some(comparison) if comparison.passes(tolerance) => {
+                            Outcome::Pass(comparison)
+                        }
+                        Some(comparison) => {
+                            write_artifacts(&output_dir,
+                                            reftest.name,
+                                            &[("actual", &actual),
+                                              ("expected", &expected),
+                                              ("diff", &comparison.diff_image)]);
+                            Outcome::Fail(comparison)
+                        }
+                    }
+                }
+            }
+        };
+
+        match outcome {
+            Outcome::Pass(ref comparison) | Outcome::Fail(ref comparison) => {
+                let status = if let Outcome::Pass(_) = outcome { "PASS" } else { "FAIL" };
+                println!("{} {}: {} pixels differ (max {}), max delta {:.4}",
+                         status,
+                         reftest.name,
+                         comparison.differing_pixels,
+                         tolerance.max_differing_pixels,
+                         comparison.max_delta);
+            }
+            Outcome::SizeMismatch => println!("FAIL {}: reference size differs", reftest.name),
+            Outcome::New => {
+                println!("NEW {}: no reference image at {}",
+                         reftest.name,
+                         reference_path.display());
+            }
+            Outcome::Blessed => println!("BLESS {}", reftest.name),
+        }
+
+        match outcome {
+            Outcome::Pass(_) | Outcome::Blessed => {}
+            Outcome::New => new_count += 1,
+            Outcome::Fail(_) | Outcome::SizeMismatch => failure_count += 1,
+        }
+    }
+
+    if new_count > 0 {
+        println!("{} reftest(s) have no reference image; run with `--bless` to add them",
+                 new_count);
+    }
+
+    if failure_count > 0 {
+        println!("{} reftest(s) failed; images are in {}", failure_count, output_dir.display());
+        process::exit(1);
+    }
+}
+
+fn write_artifacts(output_dir: &Path, name: &str, images: &[(&str, &RgbaImage)]) {
+    fs::create_dir_all(output_dir).expect("Failed to create the output directory!");
+    for &(suffix, image) in images {
+        let path = output_dir.join(format!("{}-{}.png", name, suffix));
+        image.save(&path).expect("Failed to write a test artifact!");
+    }
+}
@@ -0,0 +1,69 @@
+// pathfinder/utils/reftest/src/render.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Offscreen rendering of test scenes.
+
+use crate::scenes::{REFTEST_SIZE, RefTest};
+use font_kit::handle::Handle;
+use image::RgbaImage;
+use pathfinder_canvas::{Canvas, CanvasFontContext};
+use pathfinder_color::ColorF;
+use pathfinder_geometry::vector::{Vector2I, vec2i};
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
+use pathfinder_renderer::gpu::options::{ClearMode, RendererOptions};
+use pathfinder_renderer::options::BuildOptions;
+use pathfinder_resources::ResourceLoader;
+use pathfinder_resources::fs::FilesystemResourceLoader;
+use pathfinder_testing::{Backend, OffscreenRenderer};
+use std::iter;
+use std::sync::Arc;
+
+/// Renders test scenes into an offscreen surface and reads them back.
+pub struct ReftestRenderer {
+    offscreen: OffscreenRenderer,
+    font_context: CanvasFontContext,
+}
+
+impl ReftestRenderer {
+    pub fn new(backend: Backend) -> ReftestRenderer {
+        let options = RendererOptions { dithering: false, ..RendererOptions::default() };
+        let mut offscreen = OffscreenRenderer::new(backend, framebuffer_size(), options)
+            .unwrap_or_else(|message| panic!("Failed to create the renderer: {}", message));
+        offscreen.renderer.set_clear_mode(ClearMode::Color(ColorF::white()));
+
+        // Load the test font from the resources directory so that results don't depend on the
+        // fonts installed on the system. Fonts aren't embedded, so this needs the filesystem.
+        let font_data = FilesystemResourceLoader::locate().slurp("fonts/Overpass-Regular.otf")
+                                                          .unwrap();
+        let font = Handle::from_memory(Arc::new(font_data), 0);
+        let font_context = CanvasFontContext::from_fonts(iter::once(font));
+
+        ReftestRenderer { offscreen, font_context }
+    }
+
+    pub fn render(&mut self, reftest: &RefTest) -> RgbaImage {
+        let canvas = Canvas::new(framebuffer_size().to_f32());
+        let mut context = canvas.get_context_2d(self.font_context.clone());
+        (reftest.draw)(&mut context);
+
+        // Build sequentially so that results are deterministic.
+        let scene = SceneProxy::from_scene(context.into_canvas().into_scene(), SequentialExecutor);
+        scene.build_and_render(&mut self.offscreen.renderer, BuildOptions::default()).unwrap();
+
+        let image = self.offscreen.read_pixels(framebuffer_size());
+        RgbaImage::from_raw(REFTEST_SIZE as u32, REFTEST_SIZE as u32, image.pixels).unwrap()
+    }
+}
+
+#[inline]
+fn framebuffer_size() -> Vector2I {
+    vec2i(REFTEST_SIZE, REFTEST_SIZE)
+}
@@ -0,0 +1,257 @@
+// pathfinder/utils/reftest/src/scenes.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The test corpus.
+//!
+//! To add a test, add an entry to `REFTESTS`, run `reftest --bless <name>` on a machine with a
+//! trustworthy GPU driver, check the image by eye, and commit it.
+
+use pathfinder_canvas::{ArcDirection, CanvasRenderingContext2D, ColorU, CompositeOperation};
+use pathfinder_canvas::{FillRule, LineCap, LineJoin, Path2D, RectF, TextAlign, vec2f};
+use pathfinder_content::gradient::Gradient;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::vector::Vector2F;
+use pathfinder_simd::default::F32x2;
+use std::f32::consts::PI;
+
+pub struct RefTest {
+    pub name: &'static str,
+    pub draw: fn(&mut CanvasRenderingContext2D),
+    /// Differing pixels to allow on top of the backend tolerance, for tests whose antialiasing is
+    /// known to vary between drivers.
+    pub extra_differing_pixels: u32,
+}
+
+/// The width and height of every test image, in device pixels.
+pub const REFTEST_SIZE: i32 = 256;
+
+pub static REFTESTS: &[RefTest] = &[
+    RefTest { name: "shapes", draw: draw_shapes, extra_differing_pixels: 0 },
+    RefTest { name: "strokes", draw: draw_strokes, extra_differing_pixels: 0 },
+    RefTest { name: "line-joins", draw: draw_line_joins, extra_differing_pixels: 0 },
+    RefTest { name: "fill-rules", draw: draw_fill_rules, extra_differing_pixels: 0 },
+    RefTest { name: "linear-gradients", draw: draw_linear_gradients, extra_differing_pixels: 0 },
+    RefTest { name: "radial-gradients", draw: draw_radial_gradients, extra_differing_pixels: 0 },
+    RefTest { name: "text", draw: draw_text, extra_differing_pixels: 64 },
+    RefTest { name: "clips", draw: draw_clips, extra_differing_pixels: 0 },
+    RefTest { name: "blend-modes", draw: draw_blend_modes, extra_differing_pixels: 0 },
+    RefTest { name: "global-alpha", draw: draw_global_alpha, extra_differing_pixels: 0 },
+];
+
+fn draw_shapes(context: &mut CanvasRenderingContext2D) {
+    context.set_fill_style(ColorU::new(200, 40, 40, 255));
+    context.fill_rect(RectF::new(vec2f(16.0, 16.0), vec2f(96.0, 96.0)));
+
+    let mut path = Path2D::new();
+    path.arc(vec2f(192.0, 64.0), 48.0, 0.0, PI * 2.0, ArcDirection::CW);
+    context.set_fill_style(ColorU::new(40, 160, 40, 255));
+    context.fill_path(path, FillRule::Winding);
+
+    let mut path = Path2D::new();
+    path.move_to(vec2f(16.0, 240.0));
+    path.bezier_curve_to(vec2f(48.0, 128.0), vec2f(80.0, 128.0), vec2f(112.0, 240.0));
+    path.close_path();
+    context.set_fill_style(ColorU::new(40, 40, 200, 255));
+    context.fill_path(path, FillRule::Winding);
+
+    let mut path = Path2D::new();
+    path.move_to(vec2f(144.0, 240.0));
+    path.quadratic_curve_to(vec2f(192.0, 96.0), vec2f(240.0, 240.0));
+    path.close_path();
+    context.set_fill_style(ColorU::new(200, 160, 40, 128));
+    context.fill_path(path, FillRule::Winding);
+}
+
+fn draw_strokes(context: &mut CanvasRenderingContext2D) {
+    context.set_stroke_style(ColorU::black());
+    let caps = [LineCap::Butt, LineCap::Square, LineCap::Round];
+    let joins = [LineJoin::Miter, LineJoin::Bevel, LineJoin::Round];
+    for (index, (&cap, &join)) in caps.iter().zip(joins.iter()).enumerate() {
+        let x = 32.0 + index as f32 * 80.0;
+        context.set_line_width(12.0);
+        context.set_line_cap(cap);
+        context.set_line_join(join);
+        let mut path = Path2D::new();
+        path.move_to(vec2f(x, 32.0));
+        path.line_to(vec2f(x + 48.0, 80.0));
+        path.line_to(vec2f(x, 128.0));
+        context.stroke_path(path);
+    }
+
+    context.set_line_width(1.0);
+    for index in 0..8 {
+        let y = 160.0 + index as f32 * 10.0 + 0.5;
+        let mut path = Path2D::new();
+        path.move_to(vec2f(16.0, y));
+        path.line_to(vec2f(240.0, y + index as f32 * 2.0));
+        context.stroke_path(path);
+    }
+}
+
+// Each row strokes the same sharp corners with one join: a V whose miter is just within the limit,
+// one whose miter is just past it, and a lens whose sides curve into its tip.
+fn draw_line_joins(context: &mut CanvasRenderingContext2D) {
+    context.set_stroke_style(ColorU::black());
+    context.set_line_width(8.0);
+    context.set_miter_limit(4.0);
+    let joins = [LineJoin::Miter, LineJoin::MiterClip, LineJoin::Arcs, LineJoin::Bevel];
+    for (index, &join) in joins.iter().enumerate() {
+        context.set_line_join(join);
+        let y = 32.0 + index as f32 * 60.0;
+
+        // The miter of a V with half-angle θ is 1 / sin θ times the line width long.
+        for (&x, &half_angle) in [8.0, 88.0].iter().zip([0.26, 0.24].iter()) {
+            let mut path = Path2D::new();
+            let tip = vec2f(x + 48.0, y);
+            path.move_to(tip + vec2f(-48.0, -48.0 * f32::tan(half_angle)));
+            path.line_to(tip);
+            path.line_to(tip + vec2f(-48.0, 48.0 * f32::tan(half_angle)));
+            context.stroke_path(path);
+        }
+
+        let mut path = Path2D::new();
+        path.move_to(vec2f(168.0, y - 20.0));
+        path.quadratic_curve_to(vec2f(196.0, y - 20.0), vec2f(216.0, y));
+        path.quadratic_curve_to(vec2f(196.0, y + 20.0), vec2f(168.0, y + 20.0));
+        context.stroke_path(path);
+    }
+}
+
+fn draw_fill_rules(context: &mut CanvasRenderingContext2D) {
+    let star = |center: Vector2F| {
+        let mut path = Path2D::new();
+        for point_index in 0..5 {
+            let angle = point_index as f32 * PI * 4.0 / 5.0 - PI * 0.5;
+            let point = center + vec2f(angle.cos(), angle.sin()) * 56.0;
+            if point_index == 0 { path.move_to(point) } else { path.line_to(point) }
+        }
+        path.close_path();
+        path
+    };
+
+    context.set_fill_style(ColorU::new(120, 40, 160, 255));
+    context.fill_path(star(vec2f(64.0, 128.0)), FillRule::Winding);
+    context.fill_path(star(vec2f(192.0, 128.0)), FillRule::EvenOdd);
+}
+
+fn draw_linear_gradients(context: &mut CanvasRenderingContext2D) {
+    let mut gradient = Gradient::linear_from_points(vec2f(16.0, 0.0), vec2f(240.0, 0.0));
+    gradient.add_color_stop(ColorU::new(255, 0, 0, 255), 0.0);
+    gradient.add_color_stop(ColorU::new(0, 255, 0, 255), 0.5);
+    gradient.add_color_stop(ColorU::new(0, 0, 255, 255), 1.0);
+    context.set_fill_style(gradient);
+    context.fill_rect(RectF::new(vec2f(16.0, 16.0), vec2f(224.0, 96.0)));
+
+    let mut gradient = Gradient::linear(LineSegment2F::new(vec2f(16.0, 144.0),
+                                                           vec2f(240.0, 240.0)));
+    gradient.add_color_stop(ColorU::new(0, 0, 0, 255), 0.0);
+    gradient.add_color_stop(ColorU::new(255, 255, 255, 0), 1.0);
+    context.set_fill_style(gradient);
+    context.fill_rect(RectF::new(vec2f(16.0, 144.0), vec2f(224.0, 96.0)));
+}
+
+fn draw_radial_gradients(context: &mut CanvasRenderingContext2D) {
+    let mut gradient = Gradient::radial(vec2f(128.0, 128.0), F32x2::new(0.0, 112.0));
+    gradient.add_color_stop(ColorU::new(255, 255, 0, 255), 0.0);
+    gradient.add_color_stop(ColorU::new(255, 0, 128, 255), 0.6);
+    gradient.add_color_stop(ColorU::new(0, 0, 64, 255), 1.0);
+    context.set_fill_style(gradient);
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(256.0, 256.0)));
+
+    // A two-point conical gradient.
+    let line = LineSegment2F::new(vec2f(96.0, 96.0), vec2f(160.0, 160.0));
+    let mut gradient = Gradient::radial(line, F32x2::new(8.0, 48.0));
+    gradient.add_color_stop(ColorU::white(), 0.0);
+    gradient.add_color_stop(ColorU::new(0, 0, 0, 0), 1.0);
+    context.set_fill_style(gradient);
+    context.fill_rect(RectF::new(vec2f(48.0, 48.0), vec2f(160.0, 160.0)));
+}
+
+fn draw_text(context: &mut CanvasRenderingContext2D) {
+    context.set_fill_style(ColorU::black());
+    context.set_font("Overpass-Regular");
+    for (index, &size) in [10.0, 14.0, 20.0, 32.0].iter().enumerate() {
+        context.set_font_size(size);
+        context.fill_text("Pathfinder", vec2f(8.0, 24.0 + index as f32 * 40.0));
+    }
+
+    context.set_font_size(28.0);
+    context.set_text_align(TextAlign::Right);
+    context.set_line_width(1.0);
+    context.set_stroke_style(ColorU::new(200, 40, 40, 255));
+    context.stroke_text("Stroked", vec2f(248.0, 232.0));
+}
+
+fn draw_clips(context: &mut CanvasRenderingContext2D) {
+    let mut clip = Path2D::new();
+    clip.arc(vec2f(128.0, 128.0), 96.0, 0.0, PI * 2.0, ArcDirection::CW);
+    context.clip_path(clip, FillRule::Winding);
+
+    for index in 0..8 {
+        let color = if index % 2 == 0 {
+            ColorU::new(40, 80, 200, 255)
+        } else {
+            ColorU::new(240, 200, 40, 255)
+        };
+        context.set_fill_style(color);
+        context.fill_rect(RectF::new(vec2f(index as f32 * 32.0, 0.0), vec2f(32.0, 256.0)));
+    }
+
+    // A second clip, which replaces the first until the state is restored.
+    context.save();
+    let mut clip = Path2D::new();
+    clip.rect(RectF::new(vec2f(0.0, 0.0), vec2f(128.0, 128.0)));
+    context.clip_path(clip, FillRule::Winding);
+    context.set_fill_style(ColorU::new(0, 0, 0, 128));
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(256.0, 256.0)));
+    context.restore();
+}
+
+fn draw_blend_modes(context: &mut CanvasRenderingContext2D) {
+    let composite_ops = [
+        CompositeOperation::SourceOver,
+        CompositeOperation::Multiply,
+        CompositeOperation::Screen,
+        CompositeOperation::Overlay,
+        CompositeOperation::Darken,
+        CompositeOperation::Lighten,
+        CompositeOperation::ColorDodge,
+        CompositeOperation::ColorBurn,
+        CompositeOperation::HardLight,
+        CompositeOperation::SoftLight,
+        CompositeOperation::Difference,
+        CompositeOperation::Exclusion,
+        CompositeOperation::Hue,
+        CompositeOperation::Saturation,
+        CompositeOperation::Color,
+        CompositeOperation::Luminosity,
+    ];
+
+    for (index, &composite_op) in composite_ops.iter().enumerate() {
+        let origin = vec2f((index % 4) as f32 * 64.0, (index / 4) as f32 * 64.0);
+
+        context.set_global_composite_operation(CompositeOperation::SourceOver);
+        context.set_fill_style(ColorU::new(40, 120, 220, 255));
+        context.fill_rect(RectF::new(origin + vec2f(4.0, 4.0), vec2f(40.0, 40.0)));
+
+        context.set_global_composite_operation(composite_op);
+        context.set_fill_style(ColorU::new(230, 140, 40, 255));
+        context.fill_rect(RectF::new(origin + vec2f(20.0, 20.0), vec2f(40.0, 40.0)));
+    }
+}
+
+fn draw_global_alpha(context: &mut CanvasRenderingContext2D) {
+    for index in 0..4 {
+        context.set_global_alpha(0.25 * (index + 1) as f32);
+        context.set_fill_style(ColorU::new(200, 40, 40, 255));
+        let origin = vec2f(16.0 + index as f32 * 56.0, 16.0 + index as f32 * 48.0);
+        context.fill_rect(RectF::new(origin, vec2f(80.0, 80.0)));
+    }
+}
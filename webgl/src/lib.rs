@@ -16,11 +16,13 @@ extern crate log;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
 use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode, ClearOps};
-use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, FeatureLevel};
+use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, DeviceCapabilities};
+use pathfinder_gpu::FeatureLevel;
 use pathfinder_gpu::{ImageBinding, Primitive, ProgramKind, RenderOptions, RenderState};
 use pathfinder_gpu::{RenderTarget, ShaderKind, StencilFunc, TextureBinding, TextureData};
 use pathfinder_gpu::{TextureDataRef, TextureFormat, TextureSamplingFlags, UniformData};
 use pathfinder_gpu::{VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
+use pathfinder_gpu::error::RenderError;
 use pathfinder_resources::ResourceLoader;
 use std::cell::RefCell;
 use std::mem;
@@ -32,12 +34,30 @@ use js_sys::{Uint8Array, Uint16Array, Float32Array, Object};
 
 pub struct WebGlDevice {
     context: web_sys::WebGl2RenderingContext,
+    capabilities: DeviceCapabilities,
 }
 
 impl WebGlDevice {
     pub fn new(context: web_sys::WebGl2RenderingContext) -> Self {
-        context.get_extension("EXT_color_buffer_float").unwrap();
-        WebGlDevice { context }
+        // Enabling this extension is what makes float textures renderable.
+        let f16_render_targets = match context.get_extension("EXT_color_buffer_float") {
+            Ok(Some(_)) => true,
+            Ok(None) | Err(_) => false,
+        };
+        let max_texture_size = context.get_parameter(WebGl::MAX_TEXTURE_SIZE)
+                                      .ok()
+                                      .and_then(|max_texture_size| max_texture_size.as_f64())
+                                      .unwrap_or(2048.0) as i32;
+        let capabilities = DeviceCapabilities {
+            max_texture_size,
+            compute: false,
+            dual_source_blending: false,
+            f16_render_targets,
+            // FIXME: Use `EXT_disjoint_timer_query_webgl2`.
+            timer_queries: false,
+            aliasable_transient_textures: false,
+        };
+        WebGlDevice { context, capabilities }
     }
 
     // Error checking
@@ -195,6 +215,12 @@ impl WebGlDevice {
         for (uniform, data) in render_state.uniforms {
             self.set_uniform(uniform, data);
         }
+        for &(uniform_buffer, buffer) in render_state.uniform_buffers {
+            self.context.bind_buffer_base(WebGl::UNIFORM_BUFFER,
+                                          uniform_buffer.binding,
+                                          Some(&buffer.buffer));
+            self.ck();
+        }
         self.set_render_options(&render_state.options);
     }
 
@@ -239,6 +265,10 @@ impl WebGlDevice {
                     BlendFactor::DestAlpha => WebGl::DST_ALPHA,
                     BlendFactor::OneMinusDestAlpha => WebGl::ONE_MINUS_DST_ALPHA,
                     BlendFactor::DestColor => WebGl::DST_COLOR,
+                    BlendFactor::OneMinusSrcColor => WebGl::ONE_MINUS_SRC_COLOR,
+                    BlendFactor::Src1Color | BlendFactor::OneMinusSrc1Color => {
+                        panic!("WebGL doesn't support dual-source blending!")
+                    }
                 };
 
                 self.context.blend_func_separate(
@@ -427,6 +457,7 @@ unsafe fn check_and_extract_data(
 impl Device for WebGlDevice {
     type Buffer = WebGlBuffer;
     type Fence = ();
+    type QueueFence = ();
     type Framebuffer = WebGlFramebuffer;
     type ImageParameter = ();
     type Program = WebGlProgram;
@@ -437,6 +468,7 @@ impl Device for WebGlDevice {
     type TextureParameter = WebGlTextureParameter;
     type TimerQuery = WebGlTimerQuery;
     type Uniform = WebGlUniform;
+    type UniformBuffer = WebGlUniformBuffer;
     type VertexArray = WebGlVertexArray;
     type VertexAttr = WebGlVertexAttr;
 
@@ -445,6 +477,11 @@ impl Device for WebGlDevice {
         FeatureLevel::D3D10
     }
 
+    #[inline]
+    fn capabilities(&self) -> DeviceCapabilities {
+        self.capabilities
+    }
+
     fn create_texture(&self, format: TextureFormat, size: Vector2I) -> WebGlTexture {
         let texture = self.context.create_texture().unwrap();
         let texture = WebGlTexture {
@@ -519,7 +556,7 @@ impl Device for WebGlDevice {
         name: &str,
         source: &[u8],
         kind: ShaderKind,
-    ) -> WebGlShader {
+    ) -> Result<WebGlShader, RenderError> {
         let glsl_version_spec = "300 es";
 
         let source = self.preprocess(source, glsl_version_spec);
@@ -527,7 +564,9 @@ impl Device for WebGlDevice {
         let gl_shader_kind = match kind {
             ShaderKind::Vertex => WebGl::VERTEX_SHADER,
             ShaderKind::Fragment => WebGl::FRAGMENT_SHADER,
-            ShaderKind::Compute => panic!("Compute shaders are unsupported in WebGL!"),
+            ShaderKind::Compute => {
+                return Err(RenderError::UnsupportedFeature("compute shaders in WebGL"));
+            }
         };
 
         let gl_shader = self
@@ -540,13 +579,12 @@ impl Device for WebGlDevice {
             .context
             .get_shader_parameter(&gl_shader, WebGl::COMPILE_STATUS);
         if !compile_status.as_bool().unwrap_or(false) {
-            if let Some(info_log) = self.context.get_shader_info_log(&gl_shader) {
-                info!("Shader info log:\n{}", info_log);
-            }
-            panic!("{:?} shader '{}' compilation failed", kind, name);
+            let log = self.context.get_shader_info_log(&gl_shader).unwrap_or_default();
+            self.context.delete_shader(Some(&gl_shader));
+            return Err(RenderError::ShaderCompilation { name: name.to_owned(), kind, log });
         }
 
-        WebGlShader { gl_shader }
+        Ok(WebGlShader { gl_shader })
     }
 
     fn create_program_from_shaders(
@@ -554,7 +592,7 @@ impl Device for WebGlDevice {
         _resources: &dyn ResourceLoader,
         name: &str,
         shaders: ProgramKind<WebGlShader>,
-    ) -> WebGlProgram {
+    ) -> Result<WebGlProgram, RenderError> {
         let gl_program = self
             .context
             .create_program()
@@ -575,19 +613,18 @@ impl Device for WebGlDevice {
             .as_bool()
             .unwrap_or(false)
         {
-            if let Some(info_log) = self.context.get_program_info_log(&gl_program) {
-                info!("Program info log for {}:\n{}", name, info_log);
-            }
-            panic!("Program {:?} linking failed", name);
+            let log = self.context.get_program_info_log(&gl_program).unwrap_or_default();
+            self.context.delete_program(Some(&gl_program));
+            return Err(RenderError::ProgramLinking { name: name.to_owned(), log });
         }
 
         let parameters = WebGlProgramParameters { textures: vec![] };
 
-        WebGlProgram {
+        Ok(WebGlProgram {
             context: self.context.clone(),
             gl_program,
             parameters: RefCell::new(parameters),
-        }
+        })
     }
 
     #[inline]
@@ -641,6 +678,18 @@ impl Device for WebGlDevice {
         // TODO(pcwalton)
     }
 
+    fn get_uniform_buffer(&self, program: &WebGlProgram, name: &str, binding: u32)
+                          -> WebGlUniformBuffer {
+        let name = format!("u{}", name);
+        let index = self.context.get_uniform_block_index(&program.gl_program, &name);
+        self.ck();
+        if index != WebGl::INVALID_INDEX {
+            self.context.uniform_block_binding(&program.gl_program, index, binding);
+            self.ck();
+        }
+        WebGlUniformBuffer { binding }
+    }
+
     fn configure_vertex_attr(
         &self,
         vertex_array: &WebGlVertexArray,
@@ -746,6 +795,7 @@ impl Device for WebGlDevice {
             BufferTarget::Vertex => WebGl::ARRAY_BUFFER,
             BufferTarget::Index => WebGl::ELEMENT_ARRAY_BUFFER,
             BufferTarget::Storage => panic!("Shader storage buffers are unsupported in WebGL!"),
+            BufferTarget::Uniform => WebGl::UNIFORM_BUFFER,
         };
         self.context.bind_buffer(target, Some(&buffer.buffer));
         self.ck();
@@ -969,14 +1019,17 @@ impl Device for WebGlDevice {
         resources: &dyn ResourceLoader,
         name: &str,
         kind: ShaderKind,
-    ) -> Self::Shader {
+    ) -> Result<Self::Shader, RenderError> {
         let suffix = match kind {
             ShaderKind::Vertex => 'v',
             ShaderKind::Fragment => 'f',
             ShaderKind::Compute => 'c',
         };
         let path = format!("shaders/gl3/{}.{}s.glsl", name, suffix);
-        self.create_shader_from_source(name, &resources.slurp(&path).unwrap(), kind)
+        let source = resources.slurp(&path).map_err(|error| {
+            RenderError::MissingResource { path, reason: error.to_string() }
+        })?;
+        self.create_shader_from_source(name, &source, kind)
     }
 
     fn add_fence(&self) -> Self::Fence {
@@ -986,6 +1039,25 @@ impl Device for WebGlDevice {
     fn wait_for_fence(&self, _: &Self::Fence) {
         // TODO(pcwalton)
     }
+
+    // WebGL exposes only a single queue, so all of these are no-ops.
+
+    #[inline]
+    fn supports_async_compute(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn begin_async_compute_commands(&self) {}
+
+    #[inline]
+    fn end_async_compute_commands(&self) {}
+
+    #[inline]
+    fn signal_queue_fence(&self) -> Self::QueueFence {}
+
+    #[inline]
+    fn wait_for_queue_fence(&self, _: &Self::QueueFence) {}
 }
 
 pub struct WebGlVertexArray {
@@ -1027,6 +1099,11 @@ pub struct WebGlUniform {
     location: Option<web_sys::WebGlUniformLocation>,
 }
 
+#[derive(Debug)]
+pub struct WebGlUniformBuffer {
+    binding: u32,
+}
+
 #[derive(Debug)]
 pub struct WebGlTextureParameter {
     uniform: WebGlUniform,
@@ -1078,6 +1155,7 @@ impl BufferTargetExt for BufferTarget {
             BufferTarget::Vertex => WebGl::ARRAY_BUFFER,
             BufferTarget::Index => WebGl::ELEMENT_ARRAY_BUFFER,
             BufferTarget::Storage => panic!("Shader storage buffers are unsupported in WebGL!"),
+            BufferTarget::Uniform => WebGl::UNIFORM_BUFFER,
         }
     }
 }